@@ -0,0 +1,300 @@
+//! Append-only audit sink for cascade decisions and session disables.
+//! Independent of rule persistence, so it keeps receiving events in
+//! ephemeral mode.
+//!
+//! Each entry ends with a `hash` over the rest of its line, which includes
+//! the previous entry's hash as `prev_hash`. Editing, removing or
+//! reordering entries before the last one breaks the chain, which
+//! [`verify`] reports. The hashes are unkeyed, so dropping the tail or
+//! recomputing the whole chain is only detectable against a head hash kept
+//! outside the log (`verify`'s `since`).
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::AuditConfig;
+use crate::decision::DecisionRecord;
+use crate::error::Result;
+use crate::identity::Identity;
+use crate::session::registration::FileLock;
+
+/// The `prev_hash` of the first chained entry in a log.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// How a chained line ends: `,"hash":"<64 hex digits>"}`.
+const HASH_SUFFIX_LEN: usize = r#","hash":""}"#.len() + 64;
+
+/// One audited decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub logged_at: DateTime<Utc>,
+    pub cwd: String,
+    pub record: DecisionRecord,
+    /// Who was running hookwise, from the configured identity provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<Identity>,
+    /// Audited, not enforced: the session is in audit mode, so the call
+    /// was allowed whatever `record` says.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub not_enforced: bool,
+}
+
+/// What happened to a session's gating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionAction {
+    /// A protected-project disable waiting for its second approver.
+    DisableRequested,
+    Disabled,
+    Enabled,
+}
+
+/// One audited `hookwise disable` or `hookwise enable`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEvent {
+    pub logged_at: DateTime<Utc>,
+    pub cwd: String,
+    pub session_id: String,
+    pub action: SessionAction,
+    /// Who ran the command.
+    pub identity: Identity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// For disables, the requested length (`None` means until re-enabled).
+    /// For enables, how long the session was disabled, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+    /// How a disable was authorized: `unprotected`, `org_token` or
+    /// `second_approver`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization: Option<String>,
+    /// Who asked for a disable that a second approver confirmed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_by: Option<Identity>,
+}
+
+/// Appends [`AuditEvent`]s and [`SessionEvent`]s to a JSONL file.
+pub struct AuditSink {
+    path: PathBuf,
+}
+
+impl AuditSink {
+    /// Build a sink from config. Relative paths resolve against `cwd`.
+    /// Returns `None` when no audit path is configured.
+    pub fn from_config(config: &AuditConfig, cwd: &Path) -> Option<Self> {
+        let path = config.path.as_ref()?;
+        Some(Self::new(cwd.join(path)))
+    }
+
+    /// The sink for an audit-mode session: the configured log, or
+    /// [`default_audit_log_path`] when `audit.path` is unset.
+    pub fn for_audit_mode(config: &AuditConfig, cwd: &Path) -> Self {
+        Self::from_config(config, cwd)
+            .unwrap_or_else(|| Self::new(default_audit_log_path(&cwd.join(".hookwise"))))
+    }
+
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an event, creating parent directories as needed.
+    pub fn record(&self, event: &AuditEvent) -> Result<()> {
+        self.append(event)
+    }
+
+    /// Append a session disable/enable event.
+    pub fn record_session(&self, event: &SessionEvent) -> Result<()> {
+        self.append(event)
+    }
+
+    /// Append `event` chained to the last entry. Concurrent hooks take the
+    /// log's lock so each reads the head it chains to.
+    fn append<T: Serialize>(&self, event: &T) -> Result<()> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let prev_hash = last_line(&self.path)?
+            .and_then(|line| split_entry(&line).map(|(_, hash)| hash.to_string()))
+            .unwrap_or_else(|| GENESIS_HASH.to_string());
+        let mut value = serde_json::to_value(event)?;
+        value["prev_hash"] = serde_json::Value::String(prev_hash);
+        let body = serde_json::to_string(&value)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(
+            file,
+            "{},\"hash\":\"{}\"}}",
+            &body[..body.len() - 1],
+            entry_hash(&body)
+        )?;
+        Ok(())
+    }
+}
+
+/// What [`verify`] found in an audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainReport {
+    /// Chained entries read before the end or the first break.
+    pub entries: usize,
+    /// Leading lines written before the log was chained.
+    pub unchained: usize,
+    /// The hash of the last good entry.
+    pub head: Option<String>,
+    /// Where the chain breaks, if it does.
+    pub broken: Option<ChainBreak>,
+    /// A `since` hash was given and no good entry has it: the log was
+    /// truncated or rewritten after that entry.
+    pub missing_since: bool,
+}
+
+/// The first line of an audit log that doesn't follow from the one before.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainBreak {
+    /// 1-based line number.
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Walk an audit log's hash chain. Lines from before chaining are allowed
+/// only at the start; after them every line must carry a hash over its
+/// contents and the previous entry's hash. A missing log is an empty chain.
+///
+/// The chain shows the log is consistent with itself. Truncating its tail,
+/// or rewriting it from the start, is only caught by checking that `since`,
+/// a head hash from an earlier run, is still in it.
+pub fn verify(path: &Path, since: Option<&str>) -> Result<ChainReport> {
+    let mut report = ChainReport {
+        entries: 0,
+        unchained: 0,
+        head: None,
+        broken: None,
+        missing_since: since.is_some(),
+    };
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(report),
+        Err(e) => return Err(e.into()),
+    };
+    let mut prev = GENESIS_HASH.to_string();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let reason = match split_entry(line) {
+            None if report.entries == 0 => {
+                report.unchained += 1;
+                continue;
+            }
+            None => "entry has no hash".to_string(),
+            Some((body, hash)) => {
+                let linked = serde_json::from_str::<serde_json::Value>(&body)
+                    .ok()
+                    .and_then(|v| v["prev_hash"].as_str().map(|h| h == prev))
+                    .unwrap_or(false);
+                if entry_hash(&body) != hash {
+                    "entry doesn't match its hash".to_string()
+                } else if !linked {
+                    "entry doesn't follow the one before it".to_string()
+                } else {
+                    report.entries += 1;
+                    report.missing_since &= since != Some(hash);
+                    prev = hash.to_string();
+                    report.head = Some(prev.clone());
+                    continue;
+                }
+            }
+        };
+        report.broken = Some(ChainBreak {
+            line: i + 1,
+            reason,
+        });
+        break;
+    }
+    Ok(report)
+}
+
+/// An entry's hash over its line without the hash.
+fn entry_hash(body: &str) -> String {
+    format!("{:x}", Sha256::digest(body.as_bytes()))
+}
+
+/// Split a chained line into the JSON it was hashed over and its hash.
+/// Returns `None` for lines written before chaining.
+fn split_entry(line: &str) -> Option<(String, &str)> {
+    let at = line.len().checked_sub(HASH_SUFFIX_LEN)?;
+    let (body, suffix) = (line.get(..at)?, line.get(at..)?);
+    let hash = suffix.strip_prefix(r#","hash":""#)?.strip_suffix(r#""}"#)?;
+    if !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some((format!("{}}}", body), hash))
+}
+
+/// The last non-empty line of a file, read from the end so appends to a
+/// long log stay cheap.
+fn last_line(path: &Path) -> Result<Option<String>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut pos = file.metadata()?.len();
+    let mut tail: Vec<u8> = Vec::new();
+    loop {
+        let start = pos.saturating_sub(4096);
+        let mut chunk = vec![0; (pos - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        pos = start;
+
+        let end = tail
+            .iter()
+            .rposition(|b| !b.is_ascii_whitespace())
+            .map_or(0, |i| i + 1);
+        let line_start = tail[..end].iter().rposition(|&b| b == b'\n');
+        if line_start.is_some() || pos == 0 {
+            let line = &tail[line_start.map_or(0, |i| i + 1)..end];
+            return Ok((!line.is_empty()).then(|| String::from_utf8_lossy(line).into_owned()));
+        }
+    }
+}
+
+/// Returns the audit-mode log path under a project's `.hookwise/` directory,
+/// used when no `audit.path` is configured. Lives in `.user/` since it
+/// records one person's sessions.
+pub fn default_audit_log_path(hookwise_dir: &Path) -> PathBuf {
+    hookwise_dir.join(".user").join("audit.jsonl")
+}
+
+/// Load all decision events from an audit log, skipping other lines.
+pub fn load_events(path: &Path) -> Vec<AuditEvent> {
+    load(path)
+}
+
+/// Load all session disable/enable events from an audit log.
+pub fn load_session_events(path: &Path) -> Vec<SessionEvent> {
+    load(path)
+}
+
+fn load<T: serde::de::DeserializeOwned>(path: &Path) -> Vec<T> {
+    std::fs::read_to_string(path)
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
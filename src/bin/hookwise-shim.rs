@@ -0,0 +1,20 @@
+use clap::Parser;
+use hookwise::hook_io::HookFormat;
+
+/// Lean PreToolUse/BeforeTool hook: local tiers and the supervisor daemon,
+/// handing everything else to `hookwise check`.
+#[derive(Parser)]
+#[command(name = "hookwise-shim")]
+#[command(version)]
+pub struct Cli {
+    /// Output format: claude (default) or gemini
+    #[arg(long, default_value = "claude")]
+    format: HookFormat,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    hookwise::shim::run(cli.format).await?;
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! File excerpts attached to pending Write/Edit decisions, so approvers in
+//! notifications, the TUI or MCP can judge a change without opening the
+//! repo. The current contents are sanitized before they are attached; the
+//! proposed contents come from the already sanitized tool input.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::AttachmentConfig;
+use crate::sanitize::SanitizePipeline;
+
+/// A bounded run of lines from a larger text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Excerpt {
+    /// 1-based line number of the first line in `text`.
+    pub start_line: usize,
+    pub text: String,
+    /// Lines in the whole text the excerpt was taken from.
+    pub total_lines: usize,
+    /// Whether `text` was cut short by `max_bytes`.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl Excerpt {
+    /// Up to `config.max_lines` lines of `text` starting at 0-based line
+    /// `from`, cut to `config.max_bytes`.
+    pub fn of(text: &str, from: usize, config: &AttachmentConfig) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let from = from.min(lines.len().saturating_sub(1));
+        let end = (from + config.max_lines).min(lines.len());
+        let mut excerpt = lines.get(from..end).unwrap_or_default().join("\n");
+        let truncated = excerpt.len() > config.max_bytes;
+        if truncated {
+            let mut cut = config.max_bytes;
+            while !excerpt.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            excerpt.truncate(cut);
+        }
+        Self {
+            start_line: from + 1,
+            text: excerpt,
+            total_lines: lines.len(),
+            truncated,
+        }
+    }
+
+    /// 1-based number of the last line shown.
+    pub fn end_line(&self) -> usize {
+        self.start_line + self.text.lines().count().max(1) - 1
+    }
+
+    /// Which lines are shown, e.g. `lines 12-31 of 240`.
+    pub fn header(&self) -> String {
+        if self.total_lines == 0 {
+            return "empty".into();
+        }
+        let mut header = format!(
+            "lines {}-{} of {}",
+            self.start_line,
+            self.end_line(),
+            self.total_lines
+        );
+        if self.truncated {
+            header.push_str(", truncated");
+        }
+        header
+    }
+}
+
+/// Excerpts of a Write/Edit target before and after the proposed change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// The target file as it is now, around the edited text. None when the
+    /// file does not exist yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<Excerpt>,
+    /// The content the call would write: Write `content` or Edit
+    /// `new_string`.
+    pub proposed: Excerpt,
+}
+
+impl Attachment {
+    /// Capture an attachment for a Write or Edit call on `file_path`. The
+    /// current contents are read from disk (relative paths against `cwd`)
+    /// and sanitized. None for other tools or when disabled.
+    pub fn capture(
+        tool_name: &str,
+        sanitized_input: &str,
+        file_path: Option<&str>,
+        cwd: Option<&str>,
+        config: &AttachmentConfig,
+        sanitizer: &SanitizePipeline,
+    ) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let input: serde_json::Value = serde_json::from_str(sanitized_input).ok()?;
+        let field = |name: &str| input.get(name).and_then(|v| v.as_str());
+        let (proposed, anchor) = match tool_name {
+            "Write" => (field("content")?, None),
+            "Edit" => (field("new_string")?, field("old_string")),
+            _ => return None,
+        };
+
+        let path = Path::new(file_path?);
+        let path = match cwd {
+            Some(cwd) if path.is_relative() => Path::new(cwd).join(path),
+            _ => path.to_path_buf(),
+        };
+        let current = std::fs::read_to_string(&path).ok().map(|contents| {
+            let contents = sanitizer.sanitize(&contents);
+            let from = anchor
+                .filter(|a| !a.is_empty())
+                .and_then(|a| contents.find(a))
+                .map(|offset| contents[..offset].matches('\n').count())
+                .map(|line| line.saturating_sub(config.context_lines))
+                .unwrap_or(0);
+            Excerpt::of(&contents, from, config)
+        });
+
+        Some(Self {
+            current,
+            proposed: Excerpt::of(proposed, 0, config),
+        })
+    }
+
+    /// Plain-text rendering for notifications and MCP output.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        match &self.current {
+            Some(current) => {
+                out.push_str(&format!("Current ({}):\n", current.header()));
+                out.push_str(&current.text);
+                out.push('\n');
+            }
+            None => out.push_str("Current: (new file)\n"),
+        }
+        out.push_str(&format!("Proposed ({}):\n", self.proposed.header()));
+        out.push_str(&self.proposed.text);
+        out.push('\n');
+        out
+    }
+}
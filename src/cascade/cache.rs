@@ -1,18 +1,85 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
 use async_trait::async_trait;
 use chrono::Utc;
 
+use crate::cascade::git::head_commit;
 use crate::cascade::{CascadeInput, CascadeTier};
+use crate::config::CacheConfig;
 use crate::decision::{CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier};
-use crate::error::Result;
+use crate::error::{HookwiseError, Result};
 
 /// Tier 1: Exact cache lookup.
 pub struct ExactCache {
-    entries: RwLock<HashMap<CacheKey, DecisionRecord>>,
+    entries: RwLock<LruEntries>,
     hits: std::sync::atomic::AtomicU64,
     misses: std::sync::atomic::AtomicU64,
+    evictions: std::sync::atomic::AtomicU64,
+    expiry: CacheConfig,
+    max_entries: usize,
+}
+
+/// Cache entries with least-recently-used ordering. Each entry carries the
+/// tick of its last use; `order` maps ticks back to keys so the oldest entry
+/// is found without scanning.
+#[derive(Default)]
+struct LruEntries {
+    map: HashMap<CacheKey, (DecisionRecord, u64)>,
+    order: BTreeMap<u64, CacheKey>,
+    tick: u64,
+}
+
+impl LruEntries {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn insert(&mut self, record: DecisionRecord) {
+        let tick = self.next_tick();
+        let key = record.key.clone();
+        if let Some((_, old_tick)) = self.map.insert(key.clone(), (record, tick)) {
+            self.order.remove(&old_tick);
+        }
+        self.order.insert(tick, key);
+    }
+
+    /// Mark `key` as most recently used.
+    fn touch(&mut self, key: &CacheKey) {
+        let tick = self.next_tick();
+        if let Some((_, last_used)) = self.map.get_mut(key) {
+            self.order.remove(last_used);
+            *last_used = tick;
+            self.order.insert(tick, key.clone());
+        }
+    }
+
+    /// Evict least-recently-used entries until at most `max` remain.
+    /// Returns the number evicted.
+    fn shrink_to(&mut self, max: usize) -> usize {
+        let mut evicted = 0;
+        while self.map.len() > max {
+            let Some((_, key)) = self.order.pop_first() else {
+                break;
+            };
+            self.map.remove(&key);
+            evicted += 1;
+        }
+        evicted
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&DecisionRecord) -> bool) {
+        let order = &mut self.order;
+        self.map.retain(|_, (record, tick)| {
+            let kept = keep(record);
+            if !kept {
+                order.remove(tick);
+            }
+            kept
+        });
+    }
 }
 
 impl Default for ExactCache {
@@ -24,48 +91,128 @@ impl Default for ExactCache {
 impl ExactCache {
     pub fn new() -> Self {
         Self {
-            entries: RwLock::new(HashMap::new()),
+            entries: RwLock::new(LruEntries::default()),
             hits: std::sync::atomic::AtomicU64::new(0),
             misses: std::sync::atomic::AtomicU64::new(0),
+            evictions: std::sync::atomic::AtomicU64::new(0),
+            expiry: CacheConfig::default(),
+            max_entries: 0,
         }
     }
 
-    /// Load cache from stored decisions.
+    /// Expire entries older than the per-scope max age.
+    pub fn with_expiry(mut self, expiry: CacheConfig) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
+    /// Cap the cache at `max_entries`, evicting the least recently used
+    /// entries beyond it. `0` means unbounded.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// The unexpired entry for `input`'s role, else for the wildcard role.
+    fn lookup(&self, entries: &LruEntries, input: &CascadeInput) -> Option<DecisionRecord> {
+        let role_name = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| "*".to_string());
+
+        let key = CacheKey {
+            sanitized_input: input.sanitized_input.clone(),
+            tool: input.tool_name.clone(),
+            role: role_name,
+        };
+
+        // Try exact role match first, then wildcard
+        entries
+            .map
+            .get(&key)
+            .or_else(|| {
+                let wildcard_key = CacheKey {
+                    sanitized_input: input.sanitized_input.clone(),
+                    tool: input.tool_name.clone(),
+                    role: "*".to_string(),
+                };
+                entries.map.get(&wildcard_key)
+            })
+            .map(|(record, _)| record.clone())
+            // Entries can age past their max age in long-running processes.
+            .filter(|r| !self.is_expired(r))
+    }
+
+    /// The tier that originally made the cached decision for `input`, if any.
+    pub fn origin(&self, input: &CascadeInput) -> Option<DecisionTier> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        self.lookup(&entries, input).map(|r| r.metadata.tier)
+    }
+
+    fn is_expired(&self, record: &DecisionRecord) -> bool {
+        self.expiry
+            .is_expired(record.scope, record.timestamp, Utc::now())
+    }
+
+    fn enforce_cap(&self, entries: &mut LruEntries) {
+        if self.max_entries > 0 {
+            let evicted = entries.shrink_to(self.max_entries);
+            self.evictions
+                .fetch_add(evicted as u64, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    /// Load cache from stored decisions. Expired records are skipped.
     pub fn load_from(&self, records: Vec<DecisionRecord>) {
         let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
         for record in records {
-            entries.insert(record.key.clone(), record);
+            if !self.is_expired(&record) {
+                entries.insert(record);
+            }
         }
+        self.enforce_cap(&mut entries);
+    }
+
+    /// Drop expired entries. Returns the number removed.
+    pub fn evict_expired(&self) -> usize {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let before = entries.map.len();
+        entries.retain(|record| !self.is_expired(record));
+        before - entries.map.len()
     }
 
     /// Insert or update a cache entry.
     pub fn insert(&self, record: DecisionRecord) {
         let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-        entries.insert(record.key.clone(), record);
+        entries.insert(record);
+        self.enforce_cap(&mut entries);
     }
 
     /// Remove all entries for a specific role.
     pub fn invalidate_role(&self, role: &str) {
         let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-        entries.retain(|k, _| k.role != role);
+        entries.retain(|record| record.key.role != role);
     }
 
     /// Remove all entries.
     pub fn invalidate_all(&self) {
         let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-        entries.clear();
+        *entries = LruEntries::default();
     }
 
     /// Get cache statistics.
     pub fn stats(&self) -> CacheStats {
         let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
         let mut stats = CacheStats {
-            total_entries: entries.len(),
+            total_entries: entries.map.len(),
             hits: self.hits.load(std::sync::atomic::Ordering::Relaxed),
             misses: self.misses.load(std::sync::atomic::Ordering::Relaxed),
+            evictions: self.evictions.load(std::sync::atomic::Ordering::Relaxed),
             ..Default::default()
         };
-        for record in entries.values() {
+        for (record, _) in entries.map.values() {
             match record.decision {
                 Decision::Allow => stats.allow_entries += 1,
                 Decision::Deny => stats.deny_entries += 1,
@@ -79,30 +226,11 @@ impl ExactCache {
 #[async_trait]
 impl CascadeTier for ExactCache {
     async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
-        let role_name = input
-            .session
-            .role
-            .as_ref()
-            .map(|r| r.name.clone())
-            .unwrap_or_else(|| "*".to_string());
-
-        let key = CacheKey {
-            sanitized_input: input.sanitized_input.clone(),
-            tool: input.tool_name.clone(),
-            role: role_name.clone(),
-        };
-
-        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
-
-        // Try exact role match first, then wildcard
-        let record = entries.get(&key).or_else(|| {
-            let wildcard_key = CacheKey {
-                sanitized_input: input.sanitized_input.clone(),
-                tool: input.tool_name.clone(),
-                role: "*".to_string(),
-            };
-            entries.get(&wildcard_key)
-        });
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let record = self.lookup(&entries, input);
+        if let Some(cached) = &record {
+            entries.touch(&cached.key);
+        }
 
         match record {
             Some(cached) => {
@@ -121,6 +249,12 @@ impl CascadeTier for ExactCache {
                         ),
                         matched_key: Some(cached.key.clone()),
                         similarity_score: None,
+                        targets: Vec::new(),
+                        message: None,
+                        suggestion: None,
+                        risk_score: None,
+                        secrets: Vec::new(),
+                        client: None,
                     },
                     timestamp: Utc::now(),
                     scope: cached.scope,
@@ -154,4 +288,49 @@ pub struct CacheStats {
     pub ask_entries: usize,
     pub hits: u64,
     pub misses: u64,
+    /// Entries evicted to stay within `max_entries`.
+    pub evictions: u64,
+}
+
+/// Bash commands whose cache keys carry the HEAD commit
+/// (`cache.commit_keyed`), so a decision made at one commit isn't reused
+/// at another.
+pub struct CommitKeys {
+    patterns: Vec<regex::Regex>,
+}
+
+impl CommitKeys {
+    /// Compile `cache.commit_keyed`. None when it is empty.
+    pub fn compile(config: &CacheConfig) -> Result<Option<Self>> {
+        if config.commit_keyed.is_empty() {
+            return Ok(None);
+        }
+        let patterns = config
+            .commit_keyed
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                regex::Regex::new(p).map_err(|e| HookwiseError::ConfigParse {
+                    path: PathBuf::from("policy.yml"),
+                    reason: format!("cache.commit_keyed[{}]: invalid pattern '{}': {}", i, p, e),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(Self { patterns }))
+    }
+
+    /// The call's cache input salted with the HEAD commit of the repository
+    /// containing its cwd, or None when it isn't a matching Bash command or
+    /// no commit is checked out there.
+    pub fn salt(&self, input: &CascadeInput) -> Option<String> {
+        if input.tool_name != "Bash" {
+            return None;
+        }
+        let command = input.tool_input.get("command")?.as_str()?;
+        if !self.patterns.iter().any(|p| p.is_match(command)) {
+            return None;
+        }
+        let commit = head_commit(Path::new(input.cwd.as_deref()?))?;
+        Some(format!("{} @{}", input.sanitized_input, commit))
+    }
 }
@@ -0,0 +1,562 @@
+//! Cloud and cluster CLI rules (`cloud` in policy.yml): classifies
+//! `kubectl`, `aws`, `gcloud` and `az` invocations as reads, mutations or
+//! deletions and applies the first matching rule for the session's role
+//! and the namespace/profile the command targets.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use globset::GlobSet;
+
+use crate::cascade::shell::{split_commands, split_words};
+use crate::cascade::{CascadeInput, CascadeTier};
+use crate::config::roles::build_globset;
+use crate::config::{CloudCli, CloudRule};
+use crate::decision::{
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+};
+use crate::error::Result;
+
+/// What a cloud CLI invocation does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudAction {
+    Read,
+    Mutate,
+    Destroy,
+}
+
+impl std::fmt::Display for CloudAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Mutate => write!(f, "mutate"),
+            Self::Destroy => write!(f, "destroy"),
+        }
+    }
+}
+
+/// One classified cloud CLI command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloudInvocation {
+    pub cli: CloudCli,
+    pub action: CloudAction,
+    /// The words that decided the action, e.g. `delete` or `s3 rm`.
+    pub verb: String,
+    /// The namespace, profile, project or subscription, `default` when the
+    /// command doesn't name one, or None for kubectl `--all-namespaces`.
+    pub scope: Option<String>,
+}
+
+/// Global flags that take a value, per CLI. Scope flags are handled
+/// separately.
+const VALUE_FLAGS: &[(CloudCli, &[&str])] = &[
+    (
+        CloudCli::Kubectl,
+        &[
+            "--context",
+            "--cluster",
+            "--kubeconfig",
+            "--user",
+            "-s",
+            "--server",
+            "--token",
+            "--as",
+            "--as-group",
+            "--request-timeout",
+            "-v",
+            "--v",
+            "-o",
+            "--output",
+            "-l",
+            "--selector",
+            "-f",
+            "--filename",
+            "-c",
+            "--container",
+        ],
+    ),
+    (
+        CloudCli::Aws,
+        &[
+            "--region",
+            "--output",
+            "--endpoint-url",
+            "--query",
+            "--color",
+            "--ca-bundle",
+            "--cli-read-timeout",
+            "--cli-connect-timeout",
+        ],
+    ),
+    (
+        CloudCli::Gcloud,
+        &[
+            "--account",
+            "--configuration",
+            "--format",
+            "--verbosity",
+            "--impersonate-service-account",
+            "--billing-project",
+            "--zone",
+            "--region",
+            "--filter",
+        ],
+    ),
+    (
+        CloudCli::Az,
+        &[
+            "-o",
+            "--output",
+            "--query",
+            "-g",
+            "--resource-group",
+            "-n",
+            "--name",
+            "-l",
+            "--location",
+        ],
+    ),
+];
+
+/// Flags naming the scope, per CLI.
+const SCOPE_FLAGS: &[(CloudCli, &[&str])] = &[
+    (CloudCli::Kubectl, &["-n", "--namespace"]),
+    (CloudCli::Aws, &["--profile"]),
+    (CloudCli::Gcloud, &["--project"]),
+    (CloudCli::Az, &["--subscription"]),
+];
+
+/// Environment variables naming the scope, when set on the command line
+/// (`AWS_PROFILE=prod aws ...` or `export AWS_PROFILE=prod`).
+const SCOPE_VARS: &[(CloudCli, &[&str])] = &[
+    (CloudCli::Aws, &["AWS_PROFILE", "AWS_DEFAULT_PROFILE"]),
+    (CloudCli::Gcloud, &["CLOUDSDK_CORE_PROJECT"]),
+];
+
+/// Verbs that only read, for every CLI.
+const READ_VERBS: &[&str] = &[
+    "get",
+    "list",
+    "ls",
+    "describe",
+    "show",
+    "logs",
+    "log",
+    "read",
+    "tail",
+    "top",
+    "explain",
+    "events",
+    "info",
+    "version",
+    "help",
+    "wait",
+    "exists",
+    "query",
+    "scan",
+    "search",
+    "status",
+    "history",
+    "view",
+    "presign",
+    "can-i",
+    "whoami",
+    "api-resources",
+    "api-versions",
+    "cluster-info",
+    "diff",
+    "current-context",
+    "get-contexts",
+    "get-clusters",
+    "get-users",
+    "get-value",
+];
+
+const READ_PREFIXES: &[&str] = &[
+    "get-",
+    "list-",
+    "describe-",
+    "show-",
+    "head-",
+    "batch-get-",
+    "lookup-",
+    "search-",
+    "validate-",
+    "estimate-",
+];
+
+/// Verbs that delete, for every CLI.
+const DESTROY_VERBS: &[&str] = &[
+    "delete",
+    "rm",
+    "rb",
+    "purge",
+    "destroy",
+    "terminate",
+    "drain",
+    "uninstall",
+];
+
+const DESTROY_PREFIXES: &[&str] = &["delete-", "terminate-", "deregister-", "purge-", "destroy-"];
+
+/// Verbs that change state without deleting, for `gcloud`/`az`, whose
+/// verb comes after a variable number of group words.
+const MUTATE_VERBS: &[&str] = &[
+    "create", "update", "set", "unset", "deploy", "apply", "add", "remove", "patch", "replace",
+    "edit", "start", "stop", "restart", "reset", "resize", "import", "export", "cp", "mv", "rsync",
+    "ssh", "scp", "login", "logout", "activate", "enable", "disable", "attach", "detach", "run",
+    "invoke", "scale", "upload",
+];
+
+const MUTATE_PREFIXES: &[&str] = &[
+    "set-",
+    "add-",
+    "remove-",
+    "update-",
+    "create-",
+    "put-",
+    "attach-",
+    "detach-",
+    "enable-",
+    "disable-",
+    "start-",
+    "stop-",
+    "modify-",
+    "register-",
+    "run-",
+    "invoke-",
+    "import-",
+    "upload-",
+    "reboot-",
+];
+
+/// Every cloud CLI invocation in a (possibly compound) Bash command.
+pub fn cloud_invocations(command: &str) -> Vec<CloudInvocation> {
+    let assignments: Vec<(String, String)> = split_words(command)
+        .into_iter()
+        .filter_map(|w| {
+            let (name, value) = w.split_once('=')?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect();
+    split_commands(command)
+        .iter()
+        .filter_map(|sub_command| {
+            let mut invocation = CloudInvocation::parse(sub_command)?;
+            if invocation.scope.as_deref() == Some("default") {
+                let vars = lookup(SCOPE_VARS, invocation.cli);
+                if let Some((_, value)) = assignments
+                    .iter()
+                    .rev()
+                    .find(|(name, _)| vars.contains(&name.as_str()))
+                {
+                    invocation.scope = Some(value.clone());
+                }
+            }
+            Some(invocation)
+        })
+        .collect()
+}
+
+impl CloudInvocation {
+    /// Parse one simple command, or None if it isn't a cloud CLI command.
+    pub fn parse(command: &str) -> Option<Self> {
+        let words = split_words(command);
+        let (program, args) = words.split_first()?;
+        let cli = match program.rsplit('/').next()? {
+            "kubectl" | "oc" => CloudCli::Kubectl,
+            "aws" => CloudCli::Aws,
+            "gcloud" => CloudCli::Gcloud,
+            "az" => CloudCli::Az,
+            _ => return None,
+        };
+
+        let value_flags = lookup(VALUE_FLAGS, cli);
+        let scope_flags = lookup(SCOPE_FLAGS, cli);
+        let mut scope = Some("default".to_string());
+        let mut positional: Vec<&str> = Vec::new();
+        let mut flags: Vec<&str> = Vec::new();
+        let mut args = args.iter().map(String::as_str);
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') || arg == "-" {
+                positional.push(arg);
+                continue;
+            }
+            if arg == "--" {
+                // kubectl exec/run: the rest is the container's command.
+                break;
+            }
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value)),
+                None => (arg, None),
+            };
+            flags.push(flag);
+            if cli == CloudCli::Kubectl && matches!(flag, "-A" | "--all-namespaces") {
+                scope = None;
+            } else if scope_flags.contains(&flag) {
+                let value = inline.or_else(|| args.next());
+                if scope.is_some() {
+                    scope = value.map(String::from);
+                }
+            } else if inline.is_none() && value_flags.contains(&flag) {
+                args.next();
+            }
+        }
+
+        let (action, verb) = match cli {
+            CloudCli::Kubectl => classify_kubectl(&positional)?,
+            CloudCli::Aws => classify_aws(&positional, &flags)?,
+            CloudCli::Gcloud | CloudCli::Az => classify_grouped(&positional)?,
+        };
+        Some(Self {
+            cli,
+            action,
+            verb,
+            scope,
+        })
+    }
+}
+
+fn lookup(table: &[(CloudCli, &'static [&'static str])], cli: CloudCli) -> &'static [&'static str] {
+    table
+        .iter()
+        .find(|(c, _)| *c == cli)
+        .map_or(&[], |(_, flags)| flags)
+}
+
+/// The action a verb names on its own, if it's a known one.
+fn classify_verb(verb: &str) -> Option<CloudAction> {
+    let matches = |verbs: &[&str], prefixes: &[&str]| {
+        verbs.contains(&verb) || prefixes.iter().any(|p| verb.starts_with(p))
+    };
+    if matches(DESTROY_VERBS, DESTROY_PREFIXES) {
+        Some(CloudAction::Destroy)
+    } else if matches(READ_VERBS, READ_PREFIXES) {
+        Some(CloudAction::Read)
+    } else if matches(MUTATE_VERBS, MUTATE_PREFIXES) {
+        Some(CloudAction::Mutate)
+    } else {
+        None
+    }
+}
+
+/// `kubectl VERB ...`, with `rollout`, `config` and `auth` looking at
+/// their subcommand. Unknown verbs mutate.
+fn classify_kubectl(words: &[&str]) -> Option<(CloudAction, String)> {
+    let (&verb, rest) = words.split_first()?;
+    if matches!(verb, "rollout" | "config" | "auth") {
+        let sub = rest.first().copied().unwrap_or("");
+        let action = match classify_verb(sub) {
+            Some(CloudAction::Read) => CloudAction::Read,
+            _ => CloudAction::Mutate,
+        };
+        return Some((action, format!("{verb} {sub}").trim_end().to_string()));
+    }
+    let action = match verb {
+        "delete" | "drain" => CloudAction::Destroy,
+        "port-forward" | "proxy" | "exec" | "run" | "debug" | "cp" | "attach" => {
+            CloudAction::Mutate
+        }
+        _ => match classify_verb(verb) {
+            Some(CloudAction::Read) => CloudAction::Read,
+            _ => CloudAction::Mutate,
+        },
+    };
+    Some((action, verb.to_string()))
+}
+
+/// `aws SERVICE OPERATION ...`. `s3 sync --delete` destroys; unknown
+/// operations mutate.
+fn classify_aws(words: &[&str], flags: &[&str]) -> Option<(CloudAction, String)> {
+    let (&service, rest) = words.split_first()?;
+    let Some(&operation) = rest.first() else {
+        // `aws configure` and the like.
+        return Some((CloudAction::Mutate, service.to_string()));
+    };
+    let verb = format!("{service} {operation}");
+    if service == "s3" && operation == "sync" && flags.contains(&"--delete") {
+        return Some((CloudAction::Destroy, verb));
+    }
+    let action = classify_verb(operation).unwrap_or(CloudAction::Mutate);
+    Some((action, verb))
+}
+
+/// `gcloud GROUP... VERB` / `az GROUP... VERB`: the first known verb
+/// decides. Without one the command mutates.
+fn classify_grouped(words: &[&str]) -> Option<(CloudAction, String)> {
+    if words.is_empty() {
+        return None;
+    }
+    for (i, word) in words.iter().enumerate() {
+        if let Some(action) = classify_verb(word) {
+            return Some((action, words[..=i].join(" ")));
+        }
+    }
+    Some((CloudAction::Mutate, words.join(" ")))
+}
+
+/// Compiled `cloud` rules.
+pub struct CloudPolicy {
+    rules: Vec<CompiledRule>,
+}
+
+struct CompiledRule {
+    roles: Option<GlobSet>,
+    scopes: Option<GlobSet>,
+    rule: CloudRule,
+}
+
+impl CompiledRule {
+    fn decision(&self, role: &str, invocation: &CloudInvocation) -> Option<Decision> {
+        if self.roles.as_ref().is_some_and(|g| !g.is_match(role)) {
+            return None;
+        }
+        if !self.rule.clis.is_empty() && !self.rule.clis.contains(&invocation.cli) {
+            return None;
+        }
+        if let (Some(scopes), Some(scope)) = (&self.scopes, &invocation.scope) {
+            if !scopes.is_match(scope) {
+                return None;
+            }
+        }
+        match invocation.action {
+            CloudAction::Read => self.rule.read,
+            CloudAction::Mutate => self.rule.mutate,
+            CloudAction::Destroy => self.rule.destroy,
+        }
+    }
+}
+
+impl CloudPolicy {
+    /// Compile `cloud`. None when there are no rules.
+    pub fn compile(config: &[CloudRule]) -> Result<Option<Self>> {
+        if config.is_empty() {
+            return Ok(None);
+        }
+        let globs = |patterns: &[String]| -> Result<Option<GlobSet>> {
+            if patterns.is_empty() {
+                return Ok(None);
+            }
+            build_globset(patterns).map(Some)
+        };
+        let rules = config
+            .iter()
+            .map(|rule| {
+                Ok(CompiledRule {
+                    roles: globs(&rule.roles)?,
+                    scopes: globs(&rule.scopes)?,
+                    rule: rule.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(Self { rules }))
+    }
+
+    /// The decision of the first rule that matches `role` and `invocation`
+    /// and sets its action, with that rule's index.
+    pub fn decide(&self, role: &str, invocation: &CloudInvocation) -> Option<(Decision, usize)> {
+        self.rules
+            .iter()
+            .enumerate()
+            .find_map(|(i, rule)| rule.decision(role, invocation).map(|d| (d, i)))
+    }
+
+    /// The decision for a whole command and the reasons for it. Deny or
+    /// ask from any invocation decides; allow only when every simple
+    /// command is an allowed cloud invocation.
+    pub fn check(&self, role: &str, command: &str) -> Option<(Decision, Vec<String>)> {
+        let invocations = cloud_invocations(command);
+        if invocations.is_empty() {
+            return None;
+        }
+        let mut findings = Vec::new();
+        let mut undecided = split_commands(command).len() > invocations.len();
+        for invocation in &invocations {
+            let Some((decision, rule)) = self.decide(role, invocation) else {
+                undecided = true;
+                continue;
+            };
+            let scope = match &invocation.scope {
+                Some(scope) => format!("{} '{}'", invocation.cli.scope_name(), scope),
+                None => format!("all {}s", invocation.cli.scope_name()),
+            };
+            findings.push((
+                decision,
+                format!(
+                    "`{} {}` is a {} action in {}; cloud[{}] says {}",
+                    invocation.cli, invocation.verb, invocation.action, scope, rule, decision
+                ),
+            ));
+        }
+
+        let worst = findings
+            .iter()
+            .map(|(d, _)| *d)
+            .max_by_key(|d| d.precedence())?;
+        if worst == Decision::Allow && undecided {
+            return None;
+        }
+        let reasons = findings
+            .into_iter()
+            .filter(|(d, _)| *d == worst)
+            .map(|(_, reason)| reason)
+            .collect();
+        Some((worst, reasons))
+    }
+}
+
+#[async_trait]
+impl CascadeTier for CloudPolicy {
+    async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
+        if input.tool_name != "Bash" {
+            return Ok(None);
+        }
+        let command = input
+            .tool_input
+            .get("command")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&input.sanitized_input);
+        let role_name = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| "*".to_string());
+        let Some((decision, reasons)) = self.check(&role_name, command) else {
+            return Ok(None);
+        };
+
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: role_name,
+            },
+            decision,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::CloudCli,
+                confidence: 1.0,
+                reason: reasons.join("; "),
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: None,
+            session_id: String::new(), // Filled by CascadeRunner
+        }))
+    }
+
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::CloudCli
+    }
+
+    fn name(&self) -> &str {
+        "cloud_cli"
+    }
+}
@@ -0,0 +1,381 @@
+//! Built-in command risk score (`command_risk` in policy.yml): a
+//! deterministic score for every call from the riskiest verb category it
+//! uses, destructive flags, sensitive targets and network egress. Recorded
+//! on decisions, and used for queue priority, escalation stages and stats.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::cascade::exfil::network_sends;
+use crate::cascade::human::Priority;
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::shell::{split_commands, split_words};
+use crate::cascade::CascadeInput;
+use crate::config::{CommandRiskConfig, CommandRiskWeights};
+use crate::decision::DecisionRecord;
+use crate::error::Result;
+
+/// Programs that only read or report.
+const READ_PROGRAMS: &[&str] = &[
+    "ls", "cat", "head", "tail", "less", "more", "grep", "rg", "egrep", "fgrep", "find", "wc",
+    "echo", "printf", "pwd", "which", "whereis", "type", "stat", "file", "du", "df", "tree",
+    "diff", "cmp", "sort", "uniq", "cut", "jq", "env", "date", "whoami", "id", "uname", "hostname",
+    "ps", "true", "test",
+];
+
+/// Programs that create, move or change files.
+const WRITE_PROGRAMS: &[&str] = &[
+    "cp", "mv", "mkdir", "touch", "tee", "ln", "install", "patch",
+];
+
+/// Programs that delete files or data.
+const DELETE_PROGRAMS: &[&str] = &[
+    "rm", "rmdir", "unlink", "shred", "truncate", "dd", "mkfs", "wipefs",
+];
+
+/// Programs that run the command after them with other privileges;
+/// [`split_commands`] drops them, so they are looked for first.
+const ELEVATORS: &[&str] = &["sudo", "doas"];
+
+/// Programs that change privileges or ownership.
+const PRIVILEGE_PROGRAMS: &[&str] = &[
+    "su", "chmod", "chown", "chgrp", "setfacl", "setcap", "mount", "umount", "passwd", "useradd",
+    "usermod", "visudo",
+];
+
+/// Programs whose every use talks to another host.
+const NETWORK_PROGRAMS: &[&str] = &[
+    "curl", "wget", "ssh", "scp", "sftp", "nc", "ncat", "netcat", "socat", "telnet", "ftp",
+];
+
+/// `git` subcommands that only read.
+const GIT_READ_SUBCOMMANDS: &[&str] = &[
+    "status",
+    "log",
+    "diff",
+    "show",
+    "blame",
+    "ls-files",
+    "rev-parse",
+    "describe",
+    "grep",
+    "shortlog",
+];
+
+/// `git` subcommands that talk to a remote.
+const GIT_NETWORK_SUBCOMMANDS: &[&str] = &["push", "fetch", "pull", "clone", "ls-remote"];
+
+/// SQL statements that delete data, matched case-insensitively anywhere in
+/// a command.
+const DESTRUCTIVE_SQL: &[&str] = &["drop table", "drop database", "truncate table"];
+
+/// Long flags that skip safety checks or discard data.
+const DESTRUCTIVE_LONG_FLAGS: &[&str] = &[
+    "--force",
+    "--force-with-lease",
+    "--hard",
+    "--no-verify",
+    "--delete",
+    "--prune",
+    "--mirror",
+    "--no-preserve-root",
+];
+
+/// Programs whose `-f` forces an overwrite or deletion.
+const FORCE_PROGRAMS: &[&str] = &["rm", "git", "cp", "mv", "ln"];
+
+/// File tools that write their target.
+const WRITE_TOOLS: &[&str] = &["Write", "Edit", "MultiEdit", "NotebookEdit"];
+
+/// File tools that read their target.
+const READ_TOOLS: &[&str] = &["Read", "Glob", "Grep", "LS"];
+
+/// Tools that fetch from the network.
+const NETWORK_TOOLS: &[&str] = &["WebFetch", "WebSearch"];
+
+/// What a call does, from least to most risky.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VerbCategory {
+    Read,
+    Execute,
+    Write,
+    Delete,
+    Privilege,
+}
+
+impl VerbCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VerbCategory::Read => "read",
+            VerbCategory::Execute => "execute",
+            VerbCategory::Write => "write",
+            VerbCategory::Delete => "delete",
+            VerbCategory::Privilege => "privilege",
+        }
+    }
+
+    fn weight(self, weights: &CommandRiskWeights) -> f64 {
+        match self {
+            VerbCategory::Read => weights.read,
+            VerbCategory::Execute => weights.execute,
+            VerbCategory::Write => weights.write,
+            VerbCategory::Delete => weights.delete,
+            VerbCategory::Privilege => weights.privilege,
+        }
+    }
+}
+
+/// A call's command risk score and the signals behind it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandRisk {
+    pub score: f64,
+    pub category: VerbCategory,
+    /// The signals that added to the score, e.g. `delete (rm)` or
+    /// `destructive flag '-rf'`.
+    pub factors: Vec<String>,
+}
+
+/// Scores calls with `command_risk` weights.
+pub struct CommandRiskScorer {
+    config: CommandRiskConfig,
+    extractor: PathPolicyEngine,
+}
+
+impl CommandRiskScorer {
+    pub fn new(config: &CommandRiskConfig) -> Result<Self> {
+        Ok(Self {
+            config: *config,
+            extractor: PathPolicyEngine::new()?,
+        })
+    }
+
+    /// Score a call.
+    pub fn score(&self, input: &CascadeInput) -> CommandRisk {
+        let weights = &self.config.weights;
+        let mut signals = Signals::default();
+        match input.tool_name.as_str() {
+            "Bash" => {
+                let command = input
+                    .tool_input
+                    .get("command")
+                    .and_then(|c| c.as_str())
+                    .unwrap_or_default();
+                signals.bash(command, input.cwd.as_deref().map(Path::new));
+            }
+            tool if WRITE_TOOLS.contains(&tool) => signals.verb(VerbCategory::Write, tool),
+            tool if READ_TOOLS.contains(&tool) => signals.verb(VerbCategory::Read, tool),
+            tool if NETWORK_TOOLS.contains(&tool) => {
+                signals.verb(VerbCategory::Read, tool);
+                signals.network.get_or_insert_with(|| tool.to_string());
+            }
+            tool => signals.verb(VerbCategory::Execute, tool),
+        }
+        if let Some(policy) = &input.session.path_policy {
+            signals.sensitive = self
+                .extractor
+                .target_paths(input)
+                .into_iter()
+                .find(|p| policy.sensitive_ask_write.is_match(p));
+        }
+
+        let (category, verb) = signals.verb.unwrap_or((VerbCategory::Read, String::new()));
+        let mut score = category.weight(weights);
+        let mut factors = vec![if verb.is_empty() {
+            category.as_str().to_string()
+        } else {
+            format!("{} ({})", category.as_str(), verb)
+        }];
+        if let Some(flag) = signals.destructive_flag {
+            score += weights.destructive_flags;
+            factors.push(format!("destructive flag '{}'", flag));
+        }
+        if let Some(path) = signals.sensitive {
+            score += weights.sensitive_path;
+            factors.push(format!("sensitive path '{}'", path));
+        }
+        if let Some(program) = signals.network {
+            score += weights.network;
+            factors.push(format!("network ({})", program));
+        }
+        CommandRisk {
+            score,
+            category,
+            factors,
+        }
+    }
+
+    /// The lowest queue priority a call with `score` gets, if raised.
+    pub fn priority(&self, score: f64) -> Option<Priority> {
+        priority_for(&self.config, score)
+    }
+}
+
+/// The lowest queue priority `config` gives a call with `score`, if raised.
+pub fn priority_for(config: &CommandRiskConfig, score: f64) -> Option<Priority> {
+    if score >= config.critical {
+        Some(Priority::Critical)
+    } else if score >= config.high {
+        Some(Priority::High)
+    } else {
+        None
+    }
+}
+
+/// Risk signals found in a call.
+#[derive(Default)]
+struct Signals {
+    /// The riskiest verb category and the program or tool that used it.
+    verb: Option<(VerbCategory, String)>,
+    destructive_flag: Option<String>,
+    sensitive: Option<String>,
+    network: Option<String>,
+}
+
+impl Signals {
+    fn verb(&mut self, category: VerbCategory, name: &str) {
+        if self.verb.as_ref().is_none_or(|(c, _)| category > *c) {
+            self.verb = Some((category, name.to_string()));
+        }
+    }
+
+    /// Collect the signals of every simple command in a Bash command.
+    fn bash(&mut self, command: &str, cwd: Option<&Path>) {
+        let lower = command.to_lowercase();
+        if let Some(sql) = DESTRUCTIVE_SQL.iter().find(|s| lower.contains(*s)) {
+            self.verb(VerbCategory::Delete, sql);
+        }
+        let elevated = command
+            .split(['\n', ';', '&', '|', '(', '`'])
+            .filter_map(|part| part.split_whitespace().next())
+            .find(|program| ELEVATORS.contains(program));
+        if let Some(program) = elevated {
+            self.verb(VerbCategory::Privilege, program);
+        }
+        for sub_command in split_commands(command) {
+            self.simple_command(&split_words(&sub_command));
+        }
+        if self.network.is_none() {
+            self.network = network_sends(command, cwd).into_iter().next();
+        }
+        if self.verb.is_none() {
+            self.verb(VerbCategory::Read, "");
+        }
+    }
+
+    fn simple_command(&mut self, words: &[String]) {
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let Some((first, args)) = words.split_first() else {
+            return;
+        };
+        let program = first.rsplit('/').next().unwrap_or(first);
+
+        if PRIVILEGE_PROGRAMS.contains(&program) {
+            self.verb(VerbCategory::Privilege, program);
+        } else if DELETE_PROGRAMS.contains(&program) {
+            self.verb(VerbCategory::Delete, program);
+        } else if program == "find" && args.iter().any(|a| matches!(*a, "-delete" | "-exec")) {
+            self.verb(VerbCategory::Delete, "find");
+        } else if program == "git" {
+            let subcommand = args.iter().find(|a| !a.starts_with('-')).copied();
+            match subcommand {
+                Some("clean") => self.verb(VerbCategory::Delete, "git clean"),
+                Some("reset") if args.contains(&"--hard") => {
+                    self.verb(VerbCategory::Delete, "git reset --hard")
+                }
+                Some(sub) if GIT_READ_SUBCOMMANDS.contains(&sub) => {
+                    self.verb(VerbCategory::Read, "git")
+                }
+                _ => self.verb(VerbCategory::Write, "git"),
+            }
+            if let Some(sub) = subcommand.filter(|s| GIT_NETWORK_SUBCOMMANDS.contains(s)) {
+                self.network.get_or_insert_with(|| format!("git {}", sub));
+            }
+        } else if WRITE_PROGRAMS.contains(&program)
+            || (program == "sed" && args.iter().any(|a| a.starts_with("-i")))
+        {
+            self.verb(VerbCategory::Write, program);
+        } else if READ_PROGRAMS.contains(&program) {
+            self.verb(VerbCategory::Read, program);
+        } else {
+            self.verb(VerbCategory::Execute, program);
+        }
+
+        if NETWORK_PROGRAMS.contains(&program) {
+            self.network.get_or_insert_with(|| program.to_string());
+        }
+        if args.iter().any(|a| is_file_redirect(a)) {
+            self.verb(VerbCategory::Write, program);
+        }
+        if self.destructive_flag.is_none() {
+            self.destructive_flag = args
+                .iter()
+                .find(|a| is_destructive_flag(program, a))
+                .map(|a| a.to_string());
+        }
+    }
+}
+
+/// Whether a word redirects output into a file other than `/dev/null`.
+fn is_file_redirect(word: &str) -> bool {
+    let target = word.trim_start_matches(['1', '2', '&']);
+    target.starts_with('>') && target.trim_start_matches('>') != "/dev/null"
+}
+
+fn is_destructive_flag(program: &str, arg: &str) -> bool {
+    if DESTRUCTIVE_LONG_FLAGS.contains(&arg) {
+        return true;
+    }
+    let Some(short) = arg.strip_prefix('-').filter(|s| !s.starts_with('-')) else {
+        return false;
+    };
+    if short.is_empty() || !short.chars().all(|c| c.is_ascii_alphabetic()) {
+        return false;
+    }
+    let recursive_force = short.contains('f') && short.contains(['r', 'R']);
+    let force = short.contains('f') && FORCE_PROGRAMS.contains(&program);
+    recursive_force || force || (program == "git" && short == "D")
+}
+
+/// Command risk across stored decisions, for `hookwise stats`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandRiskSummary {
+    /// Decisions with a score.
+    pub scored: usize,
+    pub mean: Option<f64>,
+    /// Scored decisions per priority band (`low` for below `high`).
+    pub by_band: HashMap<Priority, usize>,
+    /// The highest-scoring distinct inputs, highest first.
+    pub top: Vec<(f64, String, String)>,
+}
+
+/// Summarize the risk scores of `records`, keeping the `top` riskiest
+/// distinct `(tool, input)` pairs.
+pub fn summarize_command_risk(
+    records: &[DecisionRecord],
+    config: &CommandRiskConfig,
+    top: usize,
+) -> CommandRiskSummary {
+    let mut summary = CommandRiskSummary::default();
+    let mut total = 0.0;
+    let mut inputs: Vec<(f64, String, String)> = Vec::new();
+    for record in records {
+        let Some(score) = record.metadata.risk_score else {
+            continue;
+        };
+        summary.scored += 1;
+        total += score;
+        let band = priority_for(config, score).unwrap_or(Priority::Low);
+        *summary.by_band.entry(band).or_insert(0) += 1;
+        let (tool, input) = (&record.key.tool, &record.key.sanitized_input);
+        if !inputs.iter().any(|(_, t, i)| t == tool && i == input) {
+            inputs.push((score, tool.clone(), input.clone()));
+        }
+    }
+    if summary.scored > 0 {
+        summary.mean = Some(total / summary.scored as f64);
+    }
+    inputs.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.2.cmp(&b.2)));
+    inputs.truncate(top);
+    summary.top = inputs;
+    summary
+}
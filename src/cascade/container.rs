@@ -0,0 +1,256 @@
+//! `docker`/`podman` invocations: privileged runs, host path mounts and the
+//! images they run, so path policy can check what a container could touch.
+
+use crate::cascade::shell::split_words;
+
+/// Container engines with docker-compatible command lines.
+const ENGINES: &[&str] = &["docker", "podman", "nerdctl"];
+
+/// Global engine flags (before the subcommand) that take a value.
+const GLOBAL_VALUE_FLAGS: &[&str] = &[
+    "-H",
+    "--host",
+    "-c",
+    "--context",
+    "--config",
+    "-l",
+    "--log-level",
+    "--tlscacert",
+    "--tlscert",
+    "--tlskey",
+    "--url",
+    "--connection",
+    "--root",
+    "--runroot",
+];
+
+/// `run`/`create` flags that take a value, besides the mount flags.
+const RUN_VALUE_FLAGS: &[&str] = &[
+    "-a",
+    "--attach",
+    "-c",
+    "--cpu-shares",
+    "-e",
+    "--env",
+    "-h",
+    "--hostname",
+    "-l",
+    "--label",
+    "-m",
+    "--memory",
+    "-p",
+    "--publish",
+    "-u",
+    "--user",
+    "-w",
+    "--workdir",
+    "--add-host",
+    "--cap-add",
+    "--cap-drop",
+    "--cgroupns",
+    "--cidfile",
+    "--cpus",
+    "--device",
+    "--dns",
+    "--entrypoint",
+    "--env-file",
+    "--expose",
+    "--gpus",
+    "--group-add",
+    "--health-cmd",
+    "--ipc",
+    "--label-file",
+    "--link",
+    "--log-driver",
+    "--log-opt",
+    "--mac-address",
+    "--memory-swap",
+    "--name",
+    "--network",
+    "--net",
+    "--pid",
+    "--platform",
+    "--pull",
+    "--restart",
+    "--runtime",
+    "--security-opt",
+    "--shm-size",
+    "--stop-signal",
+    "--stop-timeout",
+    "--tmpfs",
+    "--ulimit",
+    "--userns",
+    "--uts",
+    "--volumes-from",
+];
+
+/// A host path mounted into a container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostMount {
+    pub path: String,
+    pub read_only: bool,
+}
+
+/// What one container command does to the host.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContainerCommand {
+    /// `docker`, `podman` or `nerdctl`.
+    pub engine: String,
+    /// `run --privileged`.
+    pub privileged: bool,
+    /// Bind mounts (`-v /host:/ctr`, `--mount type=bind,source=...`).
+    /// Named volumes are not included.
+    pub mounts: Vec<HostMount>,
+    /// The image a `run`/`create` starts.
+    pub image: Option<String>,
+    /// The host path `cp` copies into.
+    pub copy_target: Option<String>,
+}
+
+impl ContainerCommand {
+    /// Parse one simple command, or None if it isn't a container engine
+    /// command.
+    pub fn parse(command: &str) -> Option<Self> {
+        let words = split_words(command);
+        let (program, rest) = words.split_first()?;
+        let engine = program.rsplit('/').next()?;
+        if !ENGINES.contains(&engine) {
+            return None;
+        }
+        let mut parsed = Self {
+            engine: engine.to_string(),
+            ..Default::default()
+        };
+
+        let mut args = rest.iter().map(String::as_str);
+        let subcommand = loop {
+            match args.next() {
+                Some(flag) if GLOBAL_VALUE_FLAGS.contains(&flag) => {
+                    args.next();
+                }
+                Some(flag) if flag.starts_with('-') => {}
+                // `docker container run` is `docker run`.
+                Some("container") => {}
+                Some(subcommand) => break subcommand,
+                None => return Some(parsed),
+            }
+        };
+        let args: Vec<&str> = args.collect();
+        match subcommand {
+            "run" | "create" => parsed.parse_run(&args),
+            "cp" => {
+                let paths: Vec<&str> = args.into_iter().filter(|a| !a.starts_with('-')).collect();
+                if let [_, destination] = paths.as_slice() {
+                    if !is_container_path(destination) {
+                        parsed.copy_target = Some(destination.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+        Some(parsed)
+    }
+
+    fn parse_run(&mut self, args: &[&str]) {
+        let mut args = args.iter().copied();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                self.image = Some(arg.to_string());
+                return; // The rest is the container's command
+            }
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+                _ => (arg, None),
+            };
+            match flag {
+                "--privileged" => self.privileged = inline.is_none_or(|v| v != "false"),
+                "-v" | "--volume" | "--mount" => {
+                    let Some(value) = inline.or_else(|| args.next()) else {
+                        return;
+                    };
+                    let mount = if flag == "--mount" {
+                        bind_mount(value)
+                    } else {
+                        volume_mount(value)
+                    };
+                    self.mounts.extend(mount);
+                }
+                _ if inline.is_none() && RUN_VALUE_FLAGS.contains(&flag) => {
+                    args.next();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The host side of `-v HOST:CTR[:OPTS]`, unless it names a volume.
+fn volume_mount(value: &str) -> Option<HostMount> {
+    // Skip a Windows drive letter so `C:\src:/src` splits after it.
+    let drive = value.len() > 2
+        && value.as_bytes()[0].is_ascii_alphabetic()
+        && value[1..].starts_with([':'])
+        && value[2..].starts_with(['\\', '/']);
+    let split = if drive { 2 } else { 0 };
+    let (host, rest) = match value[split..].find(':') {
+        Some(i) => (&value[..split + i], &value[split + i + 1..]),
+        None => return None, // An anonymous volume
+    };
+    if !is_host_path(host) {
+        return None;
+    }
+    let options = rest.split_once(':').map(|(_, o)| o).unwrap_or("");
+    Some(HostMount {
+        path: host.to_string(),
+        read_only: options.split(',').any(|o| o == "ro" || o == "readonly"),
+    })
+}
+
+/// The source of `--mount type=bind,source=...`.
+fn bind_mount(value: &str) -> Option<HostMount> {
+    let mut bind = false;
+    let mut source = None;
+    let mut read_only = false;
+    for field in value.split(',') {
+        let (key, val) = field.split_once('=').unwrap_or((field, "true"));
+        match key {
+            "type" => bind = val == "bind",
+            "source" | "src" => source = Some(val),
+            "readonly" | "ro" => read_only = val != "false" && val != "0",
+            _ => {}
+        }
+    }
+    let source = source.filter(|s| bind && is_host_path(s))?;
+    Some(HostMount {
+        path: source.to_string(),
+        read_only,
+    })
+}
+
+/// A host path rather than a named volume.
+fn is_host_path(source: &str) -> bool {
+    source.starts_with(['/', '.', '~', '$', '\\'])
+        || (source.len() > 1 && source.as_bytes()[1] == b':')
+}
+
+/// `container:path`, as opposed to a host path, in `docker cp`.
+fn is_container_path(path: &str) -> bool {
+    match path.find(':') {
+        Some(i) => !is_host_path(path) && !path[..i].contains('/'),
+        None => false,
+    }
+}
+
+/// Whether an image reference names a fixed version: a digest, or a tag
+/// other than `latest`.
+pub fn image_is_pinned(image: &str) -> bool {
+    if image.contains("@sha256:") {
+        return true;
+    }
+    // A `:` in the last path component is a tag; earlier ones are ports.
+    let name = image.rsplit('/').next().unwrap_or(image);
+    match name.split_once(':') {
+        Some((_, tag)) => !tag.is_empty() && tag != "latest",
+        None => false,
+    }
+}
@@ -0,0 +1,305 @@
+//! Write-content inspection (`content` in policy.yml): checks the text a
+//! `Write`, `Edit`, `MultiEdit` or `NotebookEdit` call adds to a file for
+//! secrets the sanitizer recognizes and for per-path content patterns,
+//! rather than only the path being written.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use globset::GlobSet;
+use regex::Regex;
+
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::{CascadeInput, CascadeTier};
+use crate::config::roles::{build_globset, default_categories, expand_macros};
+use crate::config::{ContentConfig, SanitizeConfig};
+use crate::decision::{
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+};
+use crate::error::{HookwiseError, Result};
+use crate::sanitize::SanitizePipeline;
+
+/// The text a file-writing call adds, and the text it replaces.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Payload {
+    pub added: String,
+    pub removed: String,
+}
+
+impl Payload {
+    /// The payload of a file-writing tool call, or None for other tools.
+    pub fn of(tool_name: &str, tool_input: &serde_json::Value) -> Option<Self> {
+        let text = |value: &serde_json::Value, key: &str| {
+            value
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        match tool_name {
+            "Write" => Some(Self {
+                added: text(tool_input, "content"),
+                removed: String::new(),
+            }),
+            "Edit" => Some(Self {
+                added: text(tool_input, "new_string"),
+                removed: text(tool_input, "old_string"),
+            }),
+            "NotebookEdit" => Some(Self {
+                added: text(tool_input, "new_source"),
+                removed: String::new(),
+            }),
+            "MultiEdit" => {
+                let edits = tool_input.get("edits")?.as_array()?;
+                let join = |key: &str| {
+                    edits
+                        .iter()
+                        .map(|edit| text(edit, key))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                Some(Self {
+                    added: join("new_string"),
+                    removed: join("old_string"),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+struct CompiledRule {
+    roles: Option<GlobSet>,
+    paths: Option<GlobSet>,
+    patterns: Vec<Regex>,
+    decision: Decision,
+    reason: Option<String>,
+}
+
+/// Compiled `content` checks.
+pub struct ContentInspection {
+    secrets: Option<(Decision, SanitizePipeline)>,
+    ignore_secrets: Vec<String>,
+    rules: Vec<CompiledRule>,
+    extractor: PathPolicyEngine,
+}
+
+impl ContentInspection {
+    /// Compile `content`, scanning for secrets with the sanitizer built
+    /// from `sanitize` and expanding `{{category}}` macros in rule paths
+    /// against `categories` merged over the built-in defaults. None when
+    /// neither secrets nor rules are configured.
+    pub fn compile(
+        config: &ContentConfig,
+        sanitize: &SanitizeConfig,
+        categories: &HashMap<String, Vec<String>>,
+    ) -> Result<Option<Self>> {
+        if config.secrets.is_none() && config.rules.is_empty() {
+            return Ok(None);
+        }
+        let mut merged = default_categories();
+        merged.extend(categories.clone());
+
+        let secrets = match config.secrets {
+            Some(decision) => {
+                check_restrictive(decision, "content.secrets")?;
+                Some((decision, SanitizePipeline::from_config(sanitize)?))
+            }
+            None => None,
+        };
+
+        let mut rules = Vec::new();
+        for (i, rule) in config.rules.iter().enumerate() {
+            let context = format!("content.rules[{}]", i);
+            check_restrictive(rule.decision, &context)?;
+            if rule.patterns.is_empty() {
+                return Err(invalid(&context, "needs at least one pattern".into()));
+            }
+            let patterns = rule
+                .patterns
+                .iter()
+                .map(|p| {
+                    regex::RegexBuilder::new(p)
+                        .multi_line(true)
+                        .build()
+                        .map_err(|e| invalid(&context, format!("invalid pattern '{}': {}", p, e)))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let paths = if rule.paths.is_empty() {
+                None
+            } else {
+                let paths = expand_macros(&rule.paths, &merged, "policy.yml", &context)?;
+                Some(build_globset(&paths)?)
+            };
+            let roles = if rule.roles.is_empty() {
+                None
+            } else {
+                Some(build_globset(&rule.roles)?)
+            };
+            rules.push(CompiledRule {
+                roles,
+                paths,
+                patterns,
+                decision: rule.decision,
+                reason: rule.reason.clone(),
+            });
+        }
+
+        Ok(Some(Self {
+            secrets,
+            ignore_secrets: config.ignore_secrets.clone(),
+            rules,
+            extractor: PathPolicyEngine::new()?,
+        }))
+    }
+
+    /// The decision for a payload written by `role` to `paths`, with the
+    /// reasons for it, or None if nothing is flagged.
+    pub fn check(
+        &self,
+        role: &str,
+        paths: &[String],
+        payload: &Payload,
+    ) -> Option<(Decision, Vec<String>)> {
+        let mut flagged = Vec::new();
+
+        if let Some((decision, pipeline)) = &self.secrets {
+            let added = self.secret_kinds(pipeline, &payload.added);
+            let removed = self.secret_kinds(pipeline, &payload.removed);
+            let introduced: Vec<String> = added
+                .into_iter()
+                .filter_map(|(kind, count)| {
+                    let new = count.saturating_sub(removed.get(&kind).copied().unwrap_or(0));
+                    (new > 0).then(|| format!("{} x{}", kind, new))
+                })
+                .collect();
+            if !introduced.is_empty() {
+                flagged.push((
+                    *decision,
+                    format!(
+                        "adds hardcoded secrets ({}); content.secrets says {}",
+                        introduced.join(", "),
+                        decision
+                    ),
+                ));
+            }
+        }
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            if rule.roles.as_ref().is_some_and(|g| !g.is_match(role)) {
+                continue;
+            }
+            if let Some(globs) = &rule.paths {
+                if !paths.iter().any(|p| globs.is_match(p)) {
+                    continue;
+                }
+            }
+            let Some(pattern) = rule.patterns.iter().find(|re| {
+                re.find_iter(&payload.added).count() > re.find_iter(&payload.removed).count()
+            }) else {
+                continue;
+            };
+            let what = rule
+                .reason
+                .clone()
+                .unwrap_or_else(|| format!("adds content matching /{}/", pattern.as_str()));
+            flagged.push((
+                rule.decision,
+                format!("{}; content.rules[{}] says {}", what, i, rule.decision),
+            ));
+        }
+
+        let worst = flagged
+            .iter()
+            .map(|(d, _)| *d)
+            .max_by_key(|d| d.precedence())?;
+        let reasons = flagged
+            .into_iter()
+            .filter(|(d, _)| *d == worst)
+            .map(|(_, reason)| reason)
+            .collect();
+        Some((worst, reasons))
+    }
+
+    /// Secret finding counts by kind, without ignored kinds.
+    fn secret_kinds(&self, pipeline: &SanitizePipeline, text: &str) -> BTreeMap<String, usize> {
+        if text.is_empty() {
+            return BTreeMap::new();
+        }
+        let (_, findings) = pipeline.sanitize_with_findings(text);
+        findings
+            .into_iter()
+            .filter(|f| !self.ignore_secrets.contains(&f.kind))
+            .map(|f| (f.kind, f.count))
+            .collect()
+    }
+}
+
+fn check_restrictive(decision: Decision, context: &str) -> Result<()> {
+    if decision == Decision::Allow {
+        return Err(invalid(context, "decision must be deny or ask".into()));
+    }
+    Ok(())
+}
+
+fn invalid(context: &str, reason: String) -> HookwiseError {
+    HookwiseError::ConfigParse {
+        path: PathBuf::from("policy.yml"),
+        reason: format!("{}: {}", context, reason),
+    }
+}
+
+#[async_trait]
+impl CascadeTier for ContentInspection {
+    async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
+        let Some(payload) = Payload::of(&input.tool_name, &input.tool_input) else {
+            return Ok(None);
+        };
+        let role_name = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| "*".to_string());
+        let paths = self.extractor.target_paths(input);
+        let Some((decision, reasons)) = self.check(&role_name, &paths, &payload) else {
+            return Ok(None);
+        };
+
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: role_name,
+            },
+            decision,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::ContentInspection,
+                confidence: 1.0,
+                reason: reasons.join("; "),
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: input.file_path.clone(),
+            session_id: String::new(), // Filled by CascadeRunner
+        }))
+    }
+
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::ContentInspection
+    }
+
+    fn name(&self) -> &str {
+        "content_inspection"
+    }
+}
@@ -0,0 +1,120 @@
+//! Log of evaluations that came near the `deadline` in policy.yml or ran
+//! past it, so `hookwise stats` can show which tiers are too slow before
+//! the assistant starts killing the hook.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::DeadlineConfig;
+use crate::decision::{DecisionRecord, DecisionTier};
+use crate::error::Result;
+
+/// One evaluation that took at least `deadline.near` of the deadline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadlineEvent {
+    pub logged_at: DateTime<Utc>,
+    pub session_id: String,
+    pub tool: String,
+    /// The tier that decided, `Deadline` when time ran out.
+    pub tier: DecisionTier,
+    pub elapsed_ms: u64,
+    pub deadline_ms: u64,
+}
+
+impl DeadlineEvent {
+    /// The event for an evaluation that took `elapsed`, if it came near the
+    /// deadline.
+    pub fn observe(
+        config: &DeadlineConfig,
+        record: &DecisionRecord,
+        session_id: &str,
+        elapsed: Duration,
+        now: DateTime<Utc>,
+    ) -> Option<Self> {
+        let deadline = config.duration()?;
+        if record.metadata.tier != DecisionTier::Deadline && !config.is_near(elapsed) {
+            return None;
+        }
+        Some(Self {
+            logged_at: now,
+            session_id: session_id.to_string(),
+            tool: record.key.tool.clone(),
+            tier: record.metadata.tier,
+            elapsed_ms: elapsed.as_millis() as u64,
+            deadline_ms: deadline.as_millis() as u64,
+        })
+    }
+
+    /// Whether the evaluation ran out of time.
+    pub fn exceeded(&self) -> bool {
+        self.tier == DecisionTier::Deadline
+    }
+}
+
+/// Log of deadline events, under the project's `.user/` directory.
+pub fn deadline_log_path(hookwise_dir: &Path) -> PathBuf {
+    hookwise_dir.join(".user").join("deadline.jsonl")
+}
+
+/// Append a deadline event to the log, creating parent directories as
+/// needed.
+pub fn append_event(path: &Path, event: &DeadlineEvent) -> Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Load deadline events. Missing files yield an empty list and malformed
+/// lines are skipped.
+pub fn load_events(path: &Path) -> Vec<DeadlineEvent> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => contents
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Slow evaluations over a window.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeadlineSummary {
+    /// Evaluations that finished near the deadline.
+    pub near: usize,
+    /// Evaluations cut off at the deadline.
+    pub exceeded: usize,
+    /// Near-deadline evaluations by the tier that decided them.
+    pub near_by_tier: BTreeMap<String, usize>,
+    pub max_elapsed_ms: Option<u64>,
+}
+
+/// Summarize the events logged at or after `since`.
+pub fn summarize(events: &[DeadlineEvent], since: DateTime<Utc>) -> DeadlineSummary {
+    let mut summary = DeadlineSummary::default();
+    for event in events.iter().filter(|e| e.logged_at >= since) {
+        if event.exceeded() {
+            summary.exceeded += 1;
+        } else {
+            summary.near += 1;
+            *summary
+                .near_by_tier
+                .entry(format!("{:?}", event.tier))
+                .or_insert(0) += 1;
+        }
+        summary.max_elapsed_ms = summary.max_elapsed_ms.max(Some(event.elapsed_ms));
+    }
+    summary
+}
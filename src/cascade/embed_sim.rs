@@ -1,11 +1,15 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::{Mutex, RwLock};
 
 use async_trait::async_trait;
 use chrono::Utc;
 
-use crate::cascade::{CascadeInput, CascadeTier};
+use crate::cascade::margin::{DenyMargin, MarginVerdict};
+use crate::cascade::{partition_keys, CascadeInput, CascadeTier, PartitionKey};
 use crate::decision::{CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier};
 use crate::error::{HookwiseError, Result};
+use crate::storage::index::EmbeddingStore;
 
 /// An entry in the HNSW index.
 #[derive(Debug, Clone)]
@@ -18,9 +22,9 @@ pub struct EmbeddingEntry {
 #[derive(Clone)]
 pub struct Point(pub Vec<f32>);
 
-impl instant_distance::Point for Point {
-    fn distance(&self, other: &Self) -> f32 {
-        // Cosine distance = 1 - cosine_similarity
+impl Point {
+    /// Cosine distance: 1 - cosine similarity.
+    fn cosine_distance(&self, other: &Self) -> f32 {
         let dot: f32 = self.0.iter().zip(other.0.iter()).map(|(a, b)| a * b).sum();
         let norm_a: f32 = self.0.iter().map(|a| a * a).sum::<f32>().sqrt();
         let norm_b: f32 = other.0.iter().map(|b| b * b).sum::<f32>().sqrt();
@@ -32,185 +36,694 @@ impl instant_distance::Point for Point {
     }
 }
 
+#[cfg(feature = "embeddings")]
+impl instant_distance::Point for Point {
+    fn distance(&self, other: &Self) -> f32 {
+        self.cosine_distance(other)
+    }
+}
+
 /// Wrapper around instant-distance HNSW index.
+#[cfg(feature = "embeddings")]
 pub struct HnswIndex {
     hnsw: instant_distance::HnswMap<Point, usize>,
 }
 
-/// Maximum pending entries before an automatic rebuild.
-const PENDING_REBUILD_THRESHOLD: usize = 50;
+/// Without the `embeddings` feature there is no HNSW graph; searches scan
+/// every point, which is exact and fast enough for hosted-model users.
+#[cfg(not(feature = "embeddings"))]
+pub struct HnswIndex {
+    points: Vec<Point>,
+}
 
-/// Tier 2b: Embedding-based HNSW similarity search.
-pub struct EmbeddingSimilarity {
-    index: RwLock<Option<HnswIndex>>,
-    model: Option<Mutex<fastembed::TextEmbedding>>,
-    threshold: f64,
-    entries: RwLock<Vec<EmbeddingEntry>>,
-    /// Buffer for entries not yet in the HNSW index (linear-scanned on search).
-    pending_entries: RwLock<Vec<EmbeddingEntry>>,
+impl HnswIndex {
+    #[cfg(feature = "embeddings")]
+    fn build(points: Vec<Point>) -> Self {
+        let values: Vec<usize> = (0..points.len()).collect();
+        let hnsw = instant_distance::Builder::default().build(points, values);
+        Self { hnsw }
+    }
+
+    #[cfg(not(feature = "embeddings"))]
+    fn build(points: Vec<Point>) -> Self {
+        Self { points }
+    }
+
+    /// Up to `limit` nearest points as `(distance, position)`, nearest first.
+    #[cfg(feature = "embeddings")]
+    fn search(&self, query: &Point, limit: usize) -> Vec<(f32, usize)> {
+        let mut search_buf = instant_distance::Search::default();
+        self.hnsw
+            .search(query, &mut search_buf)
+            .take(limit)
+            .map(|item| (item.distance, *item.value))
+            .collect()
+    }
+
+    /// Up to `limit` nearest points as `(distance, position)`, nearest first.
+    #[cfg(not(feature = "embeddings"))]
+    fn search(&self, query: &Point, limit: usize) -> Vec<(f32, usize)> {
+        let mut found: Vec<(f32, usize)> = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(position, point)| (query.cosine_distance(point), position))
+            .collect();
+        found.sort_by(|a, b| a.0.total_cmp(&b.0));
+        found.truncate(limit);
+        found
+    }
 }
 
-impl EmbeddingSimilarity {
-    /// Create a new embedding similarity engine.
-    pub fn new(_model_name: &str, threshold: f64) -> Result<Self> {
+/// A source of text embeddings for Tier 2b.
+///
+/// Implementations return one vector per input, in input order. Vectors from
+/// different providers (or models) are not comparable, so `model_id` also
+/// keys the on-disk vector store.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    /// Stable identifier for the model producing the vectors.
+    fn model_id(&self) -> &str;
+}
+
+/// Build a provider from an `embedding_model` spec in the global config:
+///
+/// - `default` / `local` (or unset): the bundled fastembed model (the
+///   `embeddings` feature)
+/// - `openai[:<model>]`: OpenAI embeddings, key from `OPENAI_API_KEY`
+/// - `voyage[:<model>]`: Voyage AI embeddings, key from `VOYAGE_API_KEY`
+/// - `cohere[:<model>]`: Cohere embeddings, key from `COHERE_API_KEY`
+pub fn provider_from_spec(spec: &str) -> Result<Box<dyn EmbeddingProvider>> {
+    let spec = spec.trim();
+    let (name, model) = match spec.split_once(':') {
+        Some((name, model)) => (name, Some(model)),
+        None => (spec, None),
+    };
+    match name {
+        #[cfg(feature = "embeddings")]
+        "" | "default" | "local" if model.is_none() => Ok(Box::new(LocalProvider::new()?)),
+        #[cfg(not(feature = "embeddings"))]
+        "" | "default" | "local" if model.is_none() => Err(HookwiseError::NotCompiledIn {
+            what: "local embedding model",
+            feature: "embeddings",
+        }),
+        "openai" | "voyage" | "cohere" => {
+            let api = EmbeddingApi::from_name(name).expect("matched above");
+            Ok(Box::new(ApiEmbeddingProvider::from_env(api, model)?))
+        }
+        _ => Err(HookwiseError::Embedding {
+            reason: format!(
+                "unknown embedding_model '{}': expected default, openai[:model], voyage[:model], or cohere[:model]",
+                spec
+            ),
+        }),
+    }
+}
+
+/// The bundled local model (fastembed's default), run in-process.
+#[cfg(feature = "embeddings")]
+pub struct LocalProvider {
+    model: Mutex<fastembed::TextEmbedding>,
+}
+
+#[cfg(feature = "embeddings")]
+impl LocalProvider {
+    pub fn new() -> Result<Self> {
         let model = fastembed::TextEmbedding::try_new(Default::default()).map_err(|e| {
             HookwiseError::Embedding {
                 reason: e.to_string(),
             }
         })?;
         Ok(Self {
-            index: RwLock::new(None),
-            model: Some(Mutex::new(model)),
-            threshold,
-            entries: RwLock::new(Vec::new()),
-            pending_entries: RwLock::new(Vec::new()),
+            model: Mutex::new(model),
         })
     }
+}
+
+#[cfg(feature = "embeddings")]
+#[async_trait]
+impl EmbeddingProvider for LocalProvider {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut model = self.model.lock().unwrap_or_else(|e| e.into_inner());
+        model
+            .embed(texts, None)
+            .map_err(|e| HookwiseError::Embedding {
+                reason: e.to_string(),
+            })
+    }
+
+    fn model_id(&self) -> &str {
+        "default"
+    }
+}
+
+/// Hosted embedding APIs supported by [`ApiEmbeddingProvider`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingApi {
+    OpenAi,
+    Voyage,
+    Cohere,
+}
+
+impl EmbeddingApi {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "openai" => Some(Self::OpenAi),
+            "voyage" => Some(Self::Voyage),
+            "cohere" => Some(Self::Cohere),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::Voyage => "voyage",
+            Self::Cohere => "cohere",
+        }
+    }
+
+    pub fn default_model(self) -> &'static str {
+        match self {
+            Self::OpenAi => "text-embedding-3-small",
+            Self::Voyage => "voyage-3-lite",
+            Self::Cohere => "embed-english-v3.0",
+        }
+    }
+
+    pub fn api_key_var(self) -> &'static str {
+        match self {
+            Self::OpenAi => "OPENAI_API_KEY",
+            Self::Voyage => "VOYAGE_API_KEY",
+            Self::Cohere => "COHERE_API_KEY",
+        }
+    }
+
+    fn default_base_url(self) -> &'static str {
+        match self {
+            Self::OpenAi => "https://api.openai.com",
+            Self::Voyage => "https://api.voyageai.com",
+            Self::Cohere => "https://api.cohere.com",
+        }
+    }
+}
+
+/// Embeddings from a hosted API (OpenAI, Voyage, or Cohere).
+pub struct ApiEmbeddingProvider {
+    client: reqwest::Client,
+    api: EmbeddingApi,
+    api_base_url: String,
+    api_key: String,
+    model: String,
+    model_id: String,
+}
+
+impl ApiEmbeddingProvider {
+    pub fn new(api: EmbeddingApi, api_key: String, model: Option<&str>) -> Self {
+        let model = model
+            .filter(|m| !m.is_empty())
+            .unwrap_or(api.default_model())
+            .to_string();
+        Self {
+            client: reqwest::Client::new(),
+            api,
+            api_base_url: api.default_base_url().to_string(),
+            api_key,
+            model_id: format!("{}:{}", api.name(), model),
+            model,
+        }
+    }
+
+    /// Read the API key from the provider's environment variable.
+    pub fn from_env(api: EmbeddingApi, model: Option<&str>) -> Result<Self> {
+        let api_key = std::env::var(api.api_key_var())
+            .ok()
+            .filter(|k| !k.is_empty())
+            .ok_or_else(|| HookwiseError::Embedding {
+                reason: format!(
+                    "{} embeddings require {} to be set",
+                    api.name(),
+                    api.api_key_var()
+                ),
+            })?;
+        Ok(Self::new(api, api_key, model))
+    }
+
+    /// Point at a compatible endpoint (proxy, self-hosted gateway).
+    pub fn with_base_url(mut self, api_base_url: &str) -> Self {
+        self.api_base_url = api_base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn request_body(&self, texts: &[String]) -> (String, serde_json::Value) {
+        match self.api {
+            EmbeddingApi::OpenAi | EmbeddingApi::Voyage => (
+                format!("{}/v1/embeddings", self.api_base_url),
+                serde_json::json!({ "model": self.model, "input": texts }),
+            ),
+            EmbeddingApi::Cohere => (
+                format!("{}/v2/embed", self.api_base_url),
+                serde_json::json!({
+                    "model": self.model,
+                    "texts": texts,
+                    "input_type": "search_document",
+                    "embedding_types": ["float"],
+                }),
+            ),
+        }
+    }
+
+    fn parse_response(&self, json: &serde_json::Value) -> Option<Vec<Vec<f32>>> {
+        let vectors: Vec<&serde_json::Value> = match self.api {
+            EmbeddingApi::OpenAi | EmbeddingApi::Voyage => json["data"]
+                .as_array()?
+                .iter()
+                .map(|d| &d["embedding"])
+                .collect(),
+            EmbeddingApi::Cohere => json["embeddings"]["float"].as_array()?.iter().collect(),
+        };
+        vectors
+            .into_iter()
+            .map(|v| {
+                v.as_array()?
+                    .iter()
+                    .map(|x| x.as_f64().map(|x| x as f32))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for ApiEmbeddingProvider {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (url, body) = self.request_body(texts);
+        let resp = self
+            .client
+            .post(url)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| HookwiseError::Embedding {
+                reason: format!("{} request failed: {}", self.api.name(), e),
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(HookwiseError::Api { status, body });
+        }
+
+        let json: serde_json::Value = resp.json().await.map_err(|e| HookwiseError::Embedding {
+            reason: format!("failed to parse {} response: {}", self.api.name(), e),
+        })?;
+        let vectors = self
+            .parse_response(&json)
+            .filter(|v| v.len() == texts.len())
+            .ok_or_else(|| HookwiseError::Embedding {
+                reason: format!("unexpected {} response shape", self.api.name()),
+            })?;
+        Ok(vectors)
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+}
+
+/// Maximum inputs remembered by [`CachedProvider`].
+const EMBEDDING_CACHE_CAPACITY: usize = 4096;
+
+/// In-memory cache in front of a provider, so an input is embedded at most
+/// once per process (e.g. the query embedded during evaluation is reused when
+/// the resulting decision is inserted). Oldest entries are evicted first.
+pub struct CachedProvider {
+    inner: Box<dyn EmbeddingProvider>,
+    cache: Mutex<EmbeddingCache>,
+}
+
+#[derive(Default)]
+struct EmbeddingCache {
+    vectors: HashMap<String, Vec<f32>>,
+    order: VecDeque<String>,
+}
+
+impl CachedProvider {
+    pub fn new(inner: Box<dyn EmbeddingProvider>) -> Self {
+        Self {
+            inner,
+            cache: Mutex::new(EmbeddingCache::default()),
+        }
+    }
+
+    /// Number of cached inputs.
+    pub fn len(&self) -> usize {
+        self.cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .vectors
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for CachedProvider {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut missing: Vec<String> = {
+            let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            texts
+                .iter()
+                .filter(|t| !cache.vectors.contains_key(*t))
+                .cloned()
+                .collect()
+        };
+        missing.sort_unstable();
+        missing.dedup();
+
+        let mut fresh = HashMap::new();
+        if !missing.is_empty() {
+            let vectors = self.inner.embed(&missing).await?;
+            fresh.extend(missing.into_iter().zip(vectors));
+        }
+
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        let out = texts
+            .iter()
+            .map(|t| {
+                fresh
+                    .get(t)
+                    .or_else(|| cache.vectors.get(t))
+                    .cloned()
+                    .ok_or_else(|| HookwiseError::Embedding {
+                        reason: "no embedding returned".into(),
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (text, vector) in fresh {
+            if cache.vectors.insert(text.clone(), vector).is_none() {
+                cache.order.push_back(text);
+            }
+        }
+        while cache.vectors.len() > EMBEDDING_CACHE_CAPACITY {
+            let Some(oldest) = cache.order.pop_front() else {
+                break;
+            };
+            cache.vectors.remove(&oldest);
+        }
+        Ok(out)
+    }
+
+    fn model_id(&self) -> &str {
+        self.inner.model_id()
+    }
+}
+
+/// Nearest HNSW neighbors considered per query, so a close deny or a
+/// same-role match behind an other-role neighbor is still seen.
+pub const NEIGHBOR_CANDIDATES: usize = 16;
+
+/// Maximum pending entries before an automatic rebuild.
+const PENDING_REBUILD_THRESHOLD: usize = 50;
+
+/// One `(role, tool)` slice of the embedding index.
+#[derive(Default)]
+struct Partition {
+    index: Option<HnswIndex>,
+    entries: Vec<EmbeddingEntry>,
+    /// Buffer for entries not yet in the HNSW index (linear-scanned on search).
+    pending: Vec<EmbeddingEntry>,
+}
+
+impl Partition {
+    /// Flush pending entries into the main entries list and rebuild the HNSW index.
+    fn rebuild(&mut self) {
+        if self.pending.is_empty() && self.index.is_some() {
+            return;
+        }
+        self.entries.append(&mut self.pending);
+        if self.entries.is_empty() {
+            self.index = None;
+            return;
+        }
+        let points: Vec<Point> = self
+            .entries
+            .iter()
+            .map(|e| Point(e.embedding.clone()))
+            .collect();
+        self.index = Some(HnswIndex::build(points));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.pending.is_empty()
+    }
+}
+
+/// Tier 2b: Embedding-based HNSW similarity search.
+///
+/// Entries are partitioned by `(role, tool)`, each with its own HNSW index,
+/// so a decision cached for one role can never match another role's call.
+pub struct EmbeddingSimilarity {
+    partitions: RwLock<HashMap<PartitionKey, Partition>>,
+    provider: Option<CachedProvider>,
+    threshold: f64,
+    model_name: String,
+    /// On-disk vectors, so inputs are only embedded once across processes.
+    store: Option<EmbeddingStore>,
+    deny_margin: DenyMargin,
+}
+
+impl EmbeddingSimilarity {
+    /// Create a new embedding similarity engine for an `embedding_model`
+    /// spec (see [`provider_from_spec`]).
+    pub fn new(spec: &str, threshold: f64) -> Result<Self> {
+        Ok(Self::with_provider(provider_from_spec(spec)?, threshold))
+    }
+
+    /// Create an engine backed by a specific provider, behind an input cache.
+    pub fn with_provider(provider: Box<dyn EmbeddingProvider>, threshold: f64) -> Self {
+        let model_name = provider.model_id().to_string();
+        Self {
+            partitions: RwLock::new(HashMap::new()),
+            provider: Some(CachedProvider::new(provider)),
+            threshold,
+            model_name,
+            store: None,
+            deny_margin: DenyMargin::default(),
+        }
+    }
+
+    /// Identifier of the model producing this tier's vectors.
+    pub fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    /// Persist embedding vectors under `index_dir` (`.hookwise/.index/`).
+    pub fn with_store(mut self, index_dir: PathBuf) -> Self {
+        self.store = Some(EmbeddingStore::new(index_dir, &self.model_name));
+        self
+    }
+
+    /// Set how close a cached deny may score before a match is withheld.
+    pub fn with_deny_margin(mut self, deny_margin: DenyMargin) -> Self {
+        self.deny_margin = deny_margin;
+        self
+    }
 
     /// Create a no-op embedding tier that always returns None.
     /// Used when the embedding model is unavailable.
     pub fn new_noop() -> Self {
         Self {
-            index: RwLock::new(None),
-            model: None,
+            partitions: RwLock::new(HashMap::new()),
+            provider: None,
             threshold: f64::MAX,
-            entries: RwLock::new(Vec::new()),
-            pending_entries: RwLock::new(Vec::new()),
+            model_name: String::new(),
+            store: None,
+            deny_margin: DenyMargin::default(),
         }
     }
 
-    /// Build/rebuild the HNSW index from a set of decision records.
-    pub fn build_index(&self, records: &[DecisionRecord]) -> Result<()> {
+    /// Build/rebuild the HNSW index from a set of decision records,
+    /// embedding every record. With a store, its contents are replaced by
+    /// exactly these vectors (the full rebuild done by `hookwise build`).
+    pub async fn build_index(&self, records: &[DecisionRecord]) -> Result<()> {
         if records.is_empty() {
-            let mut index = self.index.write().unwrap_or_else(|e| e.into_inner());
-            *index = None;
-            let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-            entries.clear();
+            self.invalidate_all();
+            if let Some(store) = &self.store {
+                store.rewrite(&[])?;
+            }
             return Ok(());
         }
 
-        // Generate embeddings for all records
-        let texts: Vec<&str> = records
+        let texts: Vec<String> = records
             .iter()
-            .map(|r| r.key.sanitized_input.as_str())
+            .map(|r| r.key.sanitized_input.clone())
             .collect();
-        let model_mutex = self
-            .model
-            .as_ref()
-            .ok_or_else(|| HookwiseError::Embedding {
-                reason: "embedding model not available (noop tier)".into(),
-            })?;
-        let embeddings = {
-            let mut model = model_mutex.lock().unwrap_or_else(|e| e.into_inner());
-            model
-                .embed(texts, None)
-                .map_err(|e| HookwiseError::Embedding {
-                    reason: e.to_string(),
-                })?
-        };
-
-        // Build entries
-        let mut new_entries = Vec::with_capacity(records.len());
-        for (i, (record, embedding)) in records.iter().zip(embeddings.iter()).enumerate() {
-            new_entries.push(EmbeddingEntry {
-                embedding: embedding.clone(),
-                record: record.clone(),
-            });
-            let _ = i; // index position matches
+        let embeddings = self.embed_batch(&texts).await?;
+        if let Some(store) = &self.store {
+            let mut seen = std::collections::HashSet::new();
+            let items: Vec<(String, Vec<f32>)> = records
+                .iter()
+                .zip(embeddings.iter())
+                .filter(|(r, _)| seen.insert(r.key.sanitized_input.as_str()))
+                .map(|(r, e)| (r.key.sanitized_input.clone(), e.clone()))
+                .collect();
+            store.rewrite(&items)?;
         }
+        self.build_from_embeddings(records, embeddings);
+        Ok(())
+    }
 
-        // Build HNSW index
-        let points: Vec<Point> = embeddings.iter().map(|e| Point(e.clone())).collect();
-        let values: Vec<usize> = (0..points.len()).collect();
-        let hnsw = instant_distance::Builder::default().build(points, values);
-
-        {
-            let mut idx = self.index.write().unwrap_or_else(|e| e.into_inner());
-            *idx = Some(HnswIndex { hnsw });
+    /// Build the HNSW index reusing stored vectors, embedding (and appending
+    /// to the store) only inputs not seen before. Without a store this is
+    /// the same as [`EmbeddingSimilarity::build_index`].
+    pub async fn load_or_build(&self, records: &[DecisionRecord]) -> Result<()> {
+        let Some(store) = &self.store else {
+            return self.build_index(records).await;
+        };
+        if records.is_empty() {
+            return self.build_index(records).await;
         }
-        {
-            let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-            *entries = new_entries;
+
+        let mut known: HashMap<String, Vec<f32>> = store.load().unwrap_or_else(|e| {
+            tracing::warn!("embedding index unreadable, re-embedding: {}", e);
+            HashMap::new()
+        });
+        let mut missing: Vec<String> = records
+            .iter()
+            .map(|r| r.key.sanitized_input.clone())
+            .filter(|t| !known.contains_key(t))
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+
+        if !missing.is_empty() {
+            let fresh = self.embed_batch(&missing).await?;
+            let items: Vec<(String, Vec<f32>)> = missing.into_iter().zip(fresh).collect();
+            if let Err(e) = store.append(&items) {
+                tracing::warn!("failed to persist embeddings: {}", e);
+            }
+            known.extend(items);
         }
 
+        let (records, embeddings): (Vec<DecisionRecord>, Vec<Vec<f32>>) = records
+            .iter()
+            .filter_map(|r| {
+                let embedding = known.get(&r.key.sanitized_input)?;
+                Some((r.clone(), embedding.clone()))
+            })
+            .unzip();
+        self.build_from_embeddings(&records, embeddings);
         Ok(())
     }
 
-    /// Add a single entry to the pending buffer. Does NOT rebuild the HNSW index.
-    /// Pending entries are searched via linear scan until `rebuild()` is called
-    /// or the pending buffer exceeds the threshold.
-    pub fn insert(&self, record: &DecisionRecord) -> Result<()> {
-        let embedding = self.embed(&record.key.sanitized_input)?;
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| HookwiseError::Embedding {
+                reason: "embedding model not available (noop tier)".into(),
+            })?;
+        provider.embed(texts).await
+    }
 
-        let should_rebuild = {
-            let mut pending = self
-                .pending_entries
-                .write()
-                .unwrap_or_else(|e| e.into_inner());
-            pending.push(EmbeddingEntry {
+    fn build_from_embeddings(&self, records: &[DecisionRecord], embeddings: Vec<Vec<f32>>) {
+        let mut partitions: HashMap<PartitionKey, Partition> = HashMap::new();
+        for (record, embedding) in records.iter().zip(embeddings) {
+            let key = (record.key.role.clone(), record.key.tool.clone());
+            partitions.entry(key).or_default().pending.push(EmbeddingEntry {
                 embedding,
                 record: record.clone(),
             });
-            pending.len() >= PENDING_REBUILD_THRESHOLD
-        };
+        }
+        for partition in partitions.values_mut() {
+            partition.rebuild();
+        }
+
+        let mut current = self.partitions.write().unwrap_or_else(|e| e.into_inner());
+        *current = partitions;
+    }
 
-        if should_rebuild {
-            self.rebuild()?;
+    /// Add a single entry to its partition's pending buffer. Does NOT rebuild
+    /// the HNSW index. Pending entries are searched via linear scan until
+    /// `rebuild()` is called or the partition's buffer exceeds the threshold.
+    pub async fn insert(&self, record: &DecisionRecord) -> Result<()> {
+        let embedding = self.embed(&record.key.sanitized_input).await?;
+        if let Some(store) = &self.store {
+            if let Err(e) = store.append(&[(record.key.sanitized_input.clone(), embedding.clone())])
+            {
+                tracing::warn!("failed to persist embedding: {}", e);
+            }
+        }
+
+        let mut partitions = self.partitions.write().unwrap_or_else(|e| e.into_inner());
+        let partition = partitions
+            .entry((record.key.role.clone(), record.key.tool.clone()))
+            .or_default();
+        partition.pending.push(EmbeddingEntry {
+            embedding,
+            record: record.clone(),
+        });
+        if partition.pending.len() >= PENDING_REBUILD_THRESHOLD {
+            partition.rebuild();
         }
 
         Ok(())
     }
 
-    /// Flush pending entries into the main entries list and rebuild the HNSW index.
+    /// Flush pending entries into every partition's HNSW index.
     pub fn rebuild(&self) -> Result<()> {
-        // Move pending entries into the main entries list
-        let drained: Vec<EmbeddingEntry> = {
-            let mut pending = self
-                .pending_entries
-                .write()
-                .unwrap_or_else(|e| e.into_inner());
-            pending.drain(..).collect()
-        };
-
-        if drained.is_empty() {
-            return Ok(());
+        let mut partitions = self.partitions.write().unwrap_or_else(|e| e.into_inner());
+        for partition in partitions.values_mut() {
+            if !partition.pending.is_empty() {
+                partition.rebuild();
+            }
         }
+        Ok(())
+    }
 
-        {
-            let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-            entries.extend(drained);
-        }
+    /// Total number of indexed entries, including pending ones.
+    pub fn len(&self) -> usize {
+        let partitions = self.partitions.read().unwrap_or_else(|e| e.into_inner());
+        partitions
+            .values()
+            .map(|p| p.entries.len() + p.pending.len())
+            .sum()
+    }
 
-        // Rebuild HNSW from all entries
-        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
-        let points: Vec<Point> = entries.iter().map(|e| Point(e.embedding.clone())).collect();
-        let values: Vec<usize> = (0..points.len()).collect();
+    /// Whether the index holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-        if !points.is_empty() {
-            let hnsw = instant_distance::Builder::default().build(points, values);
-            let mut idx = self.index.write().unwrap_or_else(|e| e.into_inner());
-            *idx = Some(HnswIndex { hnsw });
-        }
+    /// Number of non-empty `(role, tool)` partitions.
+    pub fn partition_count(&self) -> usize {
+        let partitions = self.partitions.read().unwrap_or_else(|e| e.into_inner());
+        partitions.values().filter(|p| !p.is_empty()).count()
+    }
 
-        Ok(())
+    /// Whether any entry could match a call by `role` with `tool`.
+    pub fn has_candidates(&self, role: &str, tool: &str) -> bool {
+        let partitions = self.partitions.read().unwrap_or_else(|e| e.into_inner());
+        partition_keys(role, tool)
+            .iter()
+            .any(|key| partitions.get(key).is_some_and(|p| !p.is_empty()))
     }
 
     /// Generate an embedding for a text input.
-    pub fn embed(&self, text: &str) -> Result<Vec<f32>> {
-        let model_mutex = self
-            .model
-            .as_ref()
-            .ok_or_else(|| HookwiseError::Embedding {
-                reason: "embedding model not available (noop tier)".into(),
-            })?;
-        let mut model = model_mutex.lock().unwrap_or_else(|e| e.into_inner());
-        let embeddings =
-            model
-                .embed(vec![text], None)
-                .map_err(|e| HookwiseError::Embedding {
-                    reason: e.to_string(),
-                })?;
-        embeddings
+    pub async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_batch(&[text.to_string()])
+            .await?
             .into_iter()
             .next()
             .ok_or_else(|| HookwiseError::Embedding {
@@ -218,169 +731,153 @@ impl EmbeddingSimilarity {
             })
     }
 
-    /// Search the index for the nearest neighbor.
-    /// Checks both the HNSW index and the pending entries buffer.
-    /// Returns the best match above the threshold, or None.
-    pub fn search(&self, query_embedding: &[f32]) -> Option<(f64, EmbeddingEntry)> {
-        let mut best: Option<(f64, EmbeddingEntry)> = None;
-
-        // 1. Search the HNSW index
-        {
-            let index_guard = self.index.read().unwrap_or_else(|e| e.into_inner());
-            if let Some(hnsw_index) = index_guard.as_ref() {
-                let query_point = Point(query_embedding.to_vec());
-                let mut search_buf = instant_distance::Search::default();
-                // Extract the first result's data before search_buf is dropped
-                let first_result = {
-                    let results = hnsw_index.hnsw.search(&query_point, &mut search_buf);
-                    results.into_iter().next().map(|r| (*r.value, r.distance))
-                };
-
-                if let Some((idx, distance)) = first_result {
-                    let similarity = (1.0 - distance) as f64;
-
-                    if similarity >= self.threshold {
-                        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
-                        if let Some(entry) = entries.get(idx) {
-                            best = Some((similarity, entry.clone()));
-                        }
+    /// Search the `(role, tool)` and wildcard-role partitions for the
+    /// nearest neighbor. Checks both the HNSW indexes and the pending
+    /// buffers. Returns the best match above the threshold, or None.
+    pub fn search(
+        &self,
+        query_embedding: &[f32],
+        role: &str,
+        tool: &str,
+    ) -> Option<(f64, EmbeddingEntry)> {
+        self.neighbors(query_embedding, role, tool)
+            .into_iter()
+            .next()
+            .filter(|(similarity, _)| *similarity >= self.threshold)
+    }
+
+    /// Up to [`NEIGHBOR_CANDIDATES`] nearest entries from each candidate
+    /// partition's HNSW index plus every pending entry, most similar first,
+    /// regardless of threshold.
+    pub fn neighbors(
+        &self,
+        query_embedding: &[f32],
+        role: &str,
+        tool: &str,
+    ) -> Vec<(f64, EmbeddingEntry)> {
+        let query_point = Point(query_embedding.to_vec());
+        let mut found: Vec<(f64, EmbeddingEntry)> = Vec::new();
+        let partitions = self.partitions.read().unwrap_or_else(|e| e.into_inner());
+
+        for key in partition_keys(role, tool) {
+            let Some(partition) = partitions.get(&key) else {
+                continue;
+            };
+
+            // 1. Search the HNSW index
+            if let Some(hnsw_index) = partition.index.as_ref() {
+                for (distance, position) in hnsw_index.search(&query_point, NEIGHBOR_CANDIDATES) {
+                    if let Some(entry) = partition.entries.get(position) {
+                        found.push(((1.0 - distance) as f64, entry.clone()));
                     }
                 }
             }
-        }
 
-        // 2. Linear-scan pending entries for potentially closer matches
-        {
-            let pending = self
-                .pending_entries
-                .read()
-                .unwrap_or_else(|e| e.into_inner());
-            for entry in pending.iter() {
-                let query_point = Point(query_embedding.to_vec());
+            // 2. Linear-scan pending entries
+            for entry in &partition.pending {
                 let entry_point = Point(entry.embedding.clone());
-                let distance =
-                    <Point as instant_distance::Point>::distance(&query_point, &entry_point);
-                let similarity = (1.0 - distance) as f64;
-
-                if similarity >= self.threshold
-                    && best
-                        .as_ref()
-                        .is_none_or(|(best_sim, _)| similarity > *best_sim)
-                {
-                    best = Some((similarity, entry.clone()));
-                }
+                let distance = query_point.cosine_distance(&entry_point);
+                found.push(((1.0 - distance) as f64, entry.clone()));
             }
         }
 
-        best
+        found.sort_by(|a, b| b.0.total_cmp(&a.0));
+        found
     }
 
-    /// Save the HNSW index to disk.
-    pub fn save_index(&self, _path: &std::path::Path) -> Result<()> {
-        // instant-distance doesn't have built-in serialization (serde feature is broken).
-        // Store the entries as JSONL and rebuild on load.
-        // This is a known limitation documented in the ADR.
-        Ok(())
-    }
-
-    /// Load the HNSW index from disk.
-    pub fn load_index(&self, _path: &std::path::Path) -> Result<()> {
-        // See save_index comment -- rebuild from JSONL entries instead.
-        Ok(())
-    }
-
-    /// Remove all entries for a specific role and rebuild.
-    pub fn invalidate_role(&self, role: &str) -> Result<()> {
-        let remaining: Vec<DecisionRecord> = {
-            let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
-            entries
-                .iter()
-                .filter(|e| e.record.key.role != role)
-                .map(|e| e.record.clone())
-                .collect()
-        };
-        self.build_index(&remaining)
+    /// Drop every partition belonging to a specific role.
+    pub fn invalidate_role(&self, role: &str) {
+        let mut partitions = self.partitions.write().unwrap_or_else(|e| e.into_inner());
+        partitions.retain(|(r, _), _| r != role);
     }
 
     /// Clear the entire index.
     pub fn invalidate_all(&self) {
-        let mut index = self.index.write().unwrap_or_else(|e| e.into_inner());
-        *index = None;
-        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-        entries.clear();
-        let mut pending = self
-            .pending_entries
-            .write()
-            .unwrap_or_else(|e| e.into_inner());
-        pending.clear();
+        let mut partitions = self.partitions.write().unwrap_or_else(|e| e.into_inner());
+        partitions.clear();
     }
 }
 
 #[async_trait]
 impl CascadeTier for EmbeddingSimilarity {
     async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
-        // Check if we have any entries to search (index or pending)
-        {
-            let index = self.index.read().unwrap_or_else(|e| e.into_inner());
-            let pending = self
-                .pending_entries
-                .read()
-                .unwrap_or_else(|e| e.into_inner());
-            if index.is_none() && pending.is_empty() {
-                return Ok(None);
-            }
+        let role_name = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.as_str())
+            .unwrap_or("*");
+
+        // Skip embedding the query when no partition could match.
+        if !self.has_candidates(role_name, &input.tool_name) {
+            return Ok(None);
         }
 
-        let query_embedding = self.embed(&input.sanitized_input)?;
-        let result = self.search(&query_embedding);
-
-        match result {
-            Some((similarity, entry)) => {
-                let role_name = input
-                    .session
-                    .role
-                    .as_ref()
-                    .map(|r| r.name.as_str())
-                    .unwrap_or("*");
-
-                // Only match same role or wildcard
-                if entry.record.key.role != role_name && entry.record.key.role != "*" {
-                    return Ok(None);
-                }
-                // Only match same tool
-                if entry.record.key.tool != input.tool_name {
-                    return Ok(None);
-                }
+        let query_embedding = self.embed(&input.sanitized_input).await?;
 
-                // Similarity behavior: allow auto-approves, deny falls through, ask escalates
-                match entry.record.decision {
-                    Decision::Deny => Ok(None),
-                    Decision::Allow | Decision::Ask => Ok(Some(DecisionRecord {
-                        key: CacheKey {
-                            sanitized_input: input.sanitized_input.clone(),
-                            tool: input.tool_name.clone(),
-                            role: role_name.to_string(),
-                        },
-                        decision: entry.record.decision,
-                        metadata: DecisionMetadata {
-                            tier: DecisionTier::EmbeddingSimilarity,
-                            confidence: similarity,
-                            reason: format!(
-                                "embedding cosine similarity {:.3} >= {:.3} with cached {}",
-                                similarity, self.threshold, entry.record.decision
-                            ),
-                            matched_key: Some(entry.record.key.clone()),
-                            similarity_score: Some(similarity),
-                        },
-                        timestamp: Utc::now(),
-                        scope: entry.record.scope,
-                        file_path: input.file_path.clone(),
-                        session_id: String::new(),
-                    })),
-                }
-            }
-            None => Ok(None),
-        }
+        // Neighbors come only from matching partitions and are sorted, so
+        // the first of each kind is the best.
+        let candidates = self.neighbors(&query_embedding, role_name, &input.tool_name);
+        let best_deny = candidates
+            .iter()
+            .find(|(_, e)| e.record.decision == Decision::Deny)
+            .map(|(similarity, _)| *similarity);
+        // Similarity behavior: allow auto-approves, ask escalates, deny never
+        // resolves but vetoes matches that don't clear the margin
+        let Some((similarity, entry)) = candidates
+            .into_iter()
+            .find(|(_, e)| e.record.decision != Decision::Deny)
+            .filter(|(similarity, _)| *similarity >= self.threshold)
+        else {
+            return Ok(None);
+        };
+
+        let (decision, reason) = match self.deny_margin.verdict(similarity, best_deny) {
+            MarginVerdict::FallThrough => return Ok(None),
+            MarginVerdict::Resolve => (
+                entry.record.decision,
+                format!(
+                    "embedding cosine similarity {:.3} >= {:.3} with cached {}",
+                    similarity, self.threshold, entry.record.decision
+                ),
+            ),
+            MarginVerdict::Ask => (
+                Decision::Ask,
+                format!(
+                    "embedding cosine similarity {:.3} with cached {} is within {:.3} of a cached deny ({:.3})",
+                    similarity,
+                    entry.record.decision,
+                    self.deny_margin.margin,
+                    best_deny.unwrap_or_default()
+                ),
+            ),
+        };
+
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: role_name.to_string(),
+            },
+            decision,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::EmbeddingSimilarity,
+                confidence: similarity,
+                reason,
+                matched_key: Some(entry.record.key.clone()),
+                similarity_score: Some(similarity),
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: Utc::now(),
+            scope: entry.record.scope,
+            file_path: input.file_path.clone(),
+            session_id: String::new(),
+        }))
     }
 
     fn tier(&self) -> DecisionTier {
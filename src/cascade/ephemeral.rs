@@ -0,0 +1,114 @@
+//! Fast allow for scratch and build output paths (`ephemeral_paths` in
+//! policy.yml). Agents write to `.tmp/`, `target/` and tool caches
+//! constantly; file tool calls that only touch such paths are allowed
+//! before any other tier runs instead of going through cache lookups and
+//! the supervisor. The records carry their own tier so the audit log and
+//! `hookwise stats` still show them.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use globset::GlobSet;
+
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::{CascadeInput, CascadeTier};
+use crate::config::roles::{build_globset, default_categories, expand_macros};
+use crate::config::{EphemeralPathConfig, SensitivePathConfig};
+use crate::decision::{
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+};
+use crate::error::Result;
+
+/// Compiled `ephemeral_paths`.
+pub struct EphemeralPaths {
+    paths: GlobSet,
+    sensitive: GlobSet,
+    extractor: PathPolicyEngine,
+}
+
+impl EphemeralPaths {
+    /// Compile `ephemeral_paths`, expanding `{{category}}` macros against
+    /// `categories` merged over the built-in defaults. None when disabled
+    /// or empty.
+    pub fn compile(
+        config: &EphemeralPathConfig,
+        sensitive: &SensitivePathConfig,
+        categories: &HashMap<String, Vec<String>>,
+    ) -> Result<Option<Self>> {
+        if !config.enabled || config.paths.is_empty() {
+            return Ok(None);
+        }
+        let mut merged = default_categories();
+        merged.extend(categories.clone());
+        let paths = expand_macros(&config.paths, &merged, "policy.yml", "ephemeral_paths")?;
+        Ok(Some(Self {
+            paths: build_globset(&paths)?,
+            sensitive: build_globset(&sensitive.ask_write)?,
+            extractor: PathPolicyEngine::new()?,
+        }))
+    }
+
+    /// The paths `input` touches, when there are some and all of them are
+    /// ephemeral. Bash commands never qualify: a command can do more than
+    /// touch the paths it names.
+    pub fn matching_paths(&self, input: &CascadeInput) -> Option<Vec<String>> {
+        if input.tool_name == "Bash" {
+            return None;
+        }
+        let paths = self.extractor.target_paths(input);
+        let ephemeral = !paths.is_empty()
+            && paths
+                .iter()
+                .all(|p| self.paths.is_match(p) && !self.sensitive.is_match(p));
+        ephemeral.then_some(paths)
+    }
+}
+
+#[async_trait]
+impl CascadeTier for EphemeralPaths {
+    async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
+        let Some(paths) = self.matching_paths(input) else {
+            return Ok(None);
+        };
+        let role_name = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| "*".to_string());
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: role_name,
+            },
+            decision: Decision::Allow,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::EphemeralPath,
+                confidence: 1.0,
+                reason: format!("ephemeral path: {}", paths.join(", ")),
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: input.file_path.clone(),
+            session_id: String::new(), // Filled by CascadeRunner
+        }))
+    }
+
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::EphemeralPath
+    }
+
+    fn name(&self) -> &str {
+        "ephemeral_path"
+    }
+}
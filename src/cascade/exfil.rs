@@ -0,0 +1,378 @@
+//! Cross-call exfiltration correlation (`exfiltration` in policy.yml):
+//! remembers when a session reads a sensitive file and flags the
+//! network-bound commands it runs afterwards -- `curl`/`wget` uploads,
+//! `git push` to a remote the repository doesn't know, `scp`/`rsync` to
+//! another host -- as possible exfiltration. Each call is checked on its
+//! own by the other tiers; only this one sees the sequence.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use globset::GlobSet;
+
+use crate::cascade::git::{remotes, GitCommand, PUSH_VALUE_FLAGS};
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::shell::{split_commands, split_words};
+use crate::cascade::{CascadeInput, CascadeTier};
+use crate::config::roles::{build_globset, default_categories, expand_macros};
+use crate::config::ExfiltrationConfig;
+use crate::decision::{
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+};
+use crate::error::{HookwiseError, Result};
+use crate::notify::format_elapsed;
+use crate::session::state::{SensitiveRead, SessionStateStore};
+
+/// Tools whose targets count as reads.
+const READ_TOOLS: &[&str] = &["Read", "Grep", "Bash"];
+
+/// `curl` options that send a request body or upload a file.
+const CURL_UPLOAD_FLAGS: &[&str] = &[
+    "-d",
+    "--data",
+    "--data-raw",
+    "--data-binary",
+    "--data-urlencode",
+    "--data-ascii",
+    "--json",
+    "-F",
+    "--form",
+    "--form-string",
+    "-T",
+    "--upload-file",
+];
+
+/// `wget` options that send a request body.
+const WGET_UPLOAD_FLAGS: &[&str] = &["--post-data", "--post-file", "--body-data", "--body-file"];
+
+/// HTTP methods that send data.
+const SENDING_METHODS: &[&str] = &["POST", "PUT", "PATCH"];
+
+/// Programs whose every use is a raw network connection.
+const CONNECTORS: &[&str] = &["sftp", "nc", "ncat", "netcat", "socat"];
+
+/// How many reads a reason names before summarizing the rest.
+const REASON_READS: usize = 3;
+
+/// Compiled `exfiltration` settings for one session.
+pub struct ExfiltrationGuard {
+    sensitive: GlobSet,
+    window: Duration,
+    decision: Decision,
+    store: SessionStateStore,
+    session_id: String,
+    extractor: PathPolicyEngine,
+}
+
+impl ExfiltrationGuard {
+    /// Compile `exfiltration` for `session_id`, keeping its reads in
+    /// `store` and expanding `{{category}}` macros against `categories`
+    /// merged over the built-in defaults. None when no sensitive files are
+    /// configured.
+    pub fn compile(
+        config: &ExfiltrationConfig,
+        categories: &HashMap<String, Vec<String>>,
+        store: SessionStateStore,
+        session_id: &str,
+    ) -> Result<Option<Self>> {
+        if config.sensitive.is_empty() {
+            return Ok(None);
+        }
+        if config.decision == Decision::Allow {
+            return Err(HookwiseError::ConfigParse {
+                path: PathBuf::from("policy.yml"),
+                reason: "exfiltration.decision: decision must be deny or ask".into(),
+            });
+        }
+        let mut merged = default_categories();
+        merged.extend(categories.clone());
+        let sensitive = expand_macros(
+            &config.sensitive,
+            &merged,
+            "policy.yml",
+            "exfiltration.sensitive",
+        )?;
+        let window = i64::try_from(config.window_secs)
+            .ok()
+            .and_then(Duration::try_seconds)
+            .unwrap_or(Duration::MAX);
+
+        Ok(Some(Self {
+            sensitive: build_globset(&sensitive)?,
+            window,
+            decision: config.decision,
+            store,
+            session_id: session_id.to_string(),
+            extractor: PathPolicyEngine::new()?,
+        }))
+    }
+
+    /// The sensitive files a call reads, one form per file. A Bash command
+    /// reads every sensitive file it names as an argument (`cat .env`,
+    /// `curl -d @.env`, `< .env`).
+    pub fn sensitive_reads(&self, input: &CascadeInput) -> Vec<String> {
+        if !READ_TOOLS.contains(&input.tool_name.as_str()) {
+            return Vec::new();
+        }
+        let cwd = input.cwd.as_deref();
+        let groups: Vec<Vec<String>> = match input.tool_input.get("command") {
+            Some(command) if input.tool_name == "Bash" => {
+                bash_arguments(command.as_str().unwrap_or_default())
+                    .iter()
+                    .map(|arg| self.extractor.forms_of(arg, cwd, true))
+                    .collect()
+            }
+            _ => self.extractor.target_path_groups(input),
+        };
+        let mut paths: Vec<String> = Vec::new();
+        for forms in groups {
+            if let Some(path) = forms.into_iter().find(|p| self.sensitive.is_match(p)) {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        paths
+    }
+
+    /// The decision and reason for network `sends` made at `now` after
+    /// `reads`, or None when either is missing.
+    pub fn check(
+        &self,
+        sends: &[String],
+        reads: &[&SensitiveRead],
+        now: DateTime<Utc>,
+    ) -> Option<(Decision, String)> {
+        if sends.is_empty() || reads.is_empty() {
+            return None;
+        }
+        let mut named: Vec<String> = reads
+            .iter()
+            .rev()
+            .take(REASON_READS)
+            .map(|r| {
+                format!(
+                    "'{}' {} ago",
+                    r.path,
+                    format_elapsed(now.signed_duration_since(r.read_at))
+                )
+            })
+            .collect();
+        if reads.len() > REASON_READS {
+            named.push(format!("and {} more", reads.len() - REASON_READS));
+        }
+        Some((
+            self.decision,
+            format!(
+                "possible exfiltration: {} after reading {}; exfiltration says {}",
+                sends.join(", "),
+                named.join(", "),
+                self.decision
+            ),
+        ))
+    }
+}
+
+/// The non-option arguments of every simple command in a Bash command,
+/// with `@file` and redirection prefixes stripped.
+fn bash_arguments(command: &str) -> Vec<String> {
+    split_commands(command)
+        .iter()
+        .flat_map(|c| split_words(c).into_iter().skip(1))
+        .map(|w| w.trim_start_matches(['<', '>', '@']).to_string())
+        .filter(|w| !w.is_empty() && !w.starts_with('-'))
+        .collect()
+}
+
+/// The network-bound sends in a (possibly compound) Bash command run in
+/// `cwd`, described for a decision reason.
+pub fn network_sends(command: &str, cwd: Option<&Path>) -> Vec<String> {
+    split_commands(command)
+        .iter()
+        .filter_map(|c| command_send(&split_words(c), cwd))
+        .collect()
+}
+
+/// The send one simple command makes, if any.
+fn command_send(words: &[String], cwd: Option<&Path>) -> Option<String> {
+    let (program, args) = words.split_first()?;
+    let program = program.rsplit('/').next()?;
+    match program {
+        "curl" => curl_upload(args),
+        "wget" => wget_upload(args),
+        "git" => git_push_to_new_remote(words, cwd),
+        "scp" | "rsync" => {
+            let destination = args.iter().rev().find(|a| !a.starts_with('-'))?;
+            is_remote(destination).then(|| format!("{program} to '{destination}'"))
+        }
+        _ if CONNECTORS.contains(&program) => Some(format!("{program} connection")),
+        _ => None,
+    }
+}
+
+fn curl_upload(args: &[String]) -> Option<String> {
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        let flag = arg.split_once('=').map_or(arg, |(flag, _)| flag);
+        if CURL_UPLOAD_FLAGS.contains(&flag) {
+            return Some(format!("curl upload ({flag})"));
+        }
+        // Attached short values, e.g. `-d@file` or `-XPOST`.
+        if let Some(short) = ["-d", "-F", "-T"].iter().find(|s| arg.starts_with(**s)) {
+            return Some(format!("curl upload ({short})"));
+        }
+        let method = match flag {
+            "-X" | "--request" if arg.contains('=') => arg.split_once('=').map(|(_, m)| m),
+            "-X" | "--request" => args.next(),
+            _ => arg.strip_prefix("-X").filter(|m| !m.is_empty()),
+        };
+        if let Some(method) = method {
+            if SENDING_METHODS.contains(&method.to_ascii_uppercase().as_str()) {
+                return Some(format!("curl {}", method.to_ascii_uppercase()));
+            }
+        }
+    }
+    None
+}
+
+fn wget_upload(args: &[String]) -> Option<String> {
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value)),
+            None => (arg, None),
+        };
+        if WGET_UPLOAD_FLAGS.contains(&flag) {
+            return Some(format!("wget upload ({flag})"));
+        }
+        if flag == "--method" {
+            let method = value.or_else(|| args.next())?.to_ascii_uppercase();
+            if SENDING_METHODS.contains(&method.as_str()) {
+                return Some(format!("wget {method}"));
+            }
+        }
+    }
+    None
+}
+
+/// `git push` to a URL, or to a remote the repository doesn't configure.
+/// Without a cwd only URLs count.
+fn git_push_to_new_remote(words: &[String], cwd: Option<&Path>) -> Option<String> {
+    let git = GitCommand::parse(words)?;
+    if git.subcommand != "push" {
+        return None;
+    }
+    let mut remote = None;
+    let mut args = git.args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        if let Some(repo) = arg.strip_prefix("--repo=") {
+            remote = Some(repo);
+            break;
+        }
+        if arg == "--repo" {
+            remote = args.next();
+            break;
+        }
+        if !arg.starts_with('-') {
+            remote = Some(arg);
+            break;
+        }
+        if PUSH_VALUE_FLAGS.contains(&arg) {
+            args.next();
+        }
+    }
+    let remote = remote?;
+    let dir = match (cwd, git.dir) {
+        (Some(cwd), Some(dir)) => Some(cwd.join(dir)),
+        (Some(cwd), None) => Some(cwd.to_path_buf()),
+        (None, _) => None,
+    };
+    let unknown = || dir.is_some_and(|d| !remotes(&d).iter().any(|r| r == remote));
+    (is_remote(remote) || (!remote.contains('/') && unknown()))
+        .then(|| format!("git push to new remote '{remote}'"))
+}
+
+/// Whether a destination names another host: a URL other than `file://`,
+/// or scp-style `[user@]host:path`.
+fn is_remote(spec: &str) -> bool {
+    if let Some((scheme, _)) = spec.split_once("://") {
+        return scheme != "file";
+    }
+    match spec.split_once(':') {
+        Some((host, _)) => !host.is_empty() && !host.contains('/'),
+        None => false,
+    }
+}
+
+#[async_trait]
+impl CascadeTier for ExfiltrationGuard {
+    async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
+        let now = Utc::now();
+        let since = now
+            .checked_sub_signed(self.window)
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let reads = self.sensitive_reads(input);
+        let sends = match input.tool_input.get("command").and_then(|c| c.as_str()) {
+            Some(command) if input.tool_name == "Bash" => {
+                network_sends(command, input.cwd.as_deref().map(Path::new))
+            }
+            _ => Vec::new(),
+        };
+
+        let state = if !reads.is_empty() {
+            self.store.update(&self.session_id, |state| {
+                state.sensitive_reads.retain(|r| r.read_at >= since);
+                state.record_reads(&reads, &input.tool_name, now);
+            })?
+        } else if !sends.is_empty() {
+            self.store.load(&self.session_id)?
+        } else {
+            return Ok(None);
+        };
+        let Some((decision, reason)) = self.check(&sends, &state.reads_since(since), now) else {
+            return Ok(None);
+        };
+
+        let role_name = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| "*".to_string());
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: role_name,
+            },
+            decision,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::Exfiltration,
+                confidence: 1.0,
+                reason,
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: now,
+            scope: ScopeLevel::Project,
+            file_path: input.file_path.clone(),
+            session_id: String::new(), // Filled by CascadeRunner
+        }))
+    }
+
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::Exfiltration
+    }
+
+    fn name(&self) -> &str {
+        "exfiltration"
+    }
+}
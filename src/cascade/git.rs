@@ -0,0 +1,668 @@
+//! Git operation rules (`git` in policy.yml): recognizes force-pushes,
+//! pushes to protected branches, `git config` and hook changes, tag
+//! deletion and submodule URL changes, and applies the first matching
+//! rule for the session's role.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use globset::GlobSet;
+
+use crate::cascade::shell::{split_commands, split_words};
+use crate::cascade::{CascadeInput, CascadeTier};
+use crate::config::roles::build_globset;
+use crate::config::{GitPolicyConfig, GitRule};
+use crate::decision::{
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+};
+use crate::error::Result;
+
+/// A kind of risky git operation, named as in `git.rules`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitOperation {
+    ForcePush,
+    ProtectedPush,
+    Config,
+    TagDelete,
+    Submodule,
+    Hooks,
+}
+
+impl std::fmt::Display for GitOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ForcePush => write!(f, "force_push"),
+            Self::ProtectedPush => write!(f, "protected_push"),
+            Self::Config => write!(f, "config"),
+            Self::TagDelete => write!(f, "tag_delete"),
+            Self::Submodule => write!(f, "submodule"),
+            Self::Hooks => write!(f, "hooks"),
+        }
+    }
+}
+
+/// One risky operation found in a tool call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitFinding {
+    pub operation: GitOperation,
+    /// What the call does, e.g. `force-push to 'main'`.
+    pub detail: String,
+}
+
+impl GitFinding {
+    fn new(operation: GitOperation, detail: String) -> Self {
+        Self { operation, detail }
+    }
+}
+
+/// Options before the git subcommand that take a value.
+const GLOBAL_VALUE_FLAGS: &[&str] = &[
+    "-C",
+    "-c",
+    "--git-dir",
+    "--work-tree",
+    "--namespace",
+    "--exec-path",
+    "--config-env",
+];
+
+/// `git push` options that take a value.
+pub(crate) const PUSH_VALUE_FLAGS: &[&str] =
+    &["-o", "--push-option", "--repo", "--receive-pack", "--exec"];
+
+/// `git config` options that take a value.
+const CONFIG_VALUE_FLAGS: &[&str] = &[
+    "-f",
+    "--file",
+    "--blob",
+    "-t",
+    "--type",
+    "--default",
+    "--comment",
+    "--value",
+];
+
+/// `git config` options and subcommands that only read.
+const CONFIG_READS: &[&str] = &[
+    "--get",
+    "--get-all",
+    "--get-regexp",
+    "--get-urlmatch",
+    "--get-color",
+    "--get-colorbool",
+    "-l",
+    "--list",
+    "get",
+    "list",
+];
+
+/// `git config` options and subcommands that write.
+const CONFIG_WRITES: &[&str] = &[
+    "--add",
+    "--unset",
+    "--unset-all",
+    "--replace-all",
+    "--rename-section",
+    "--remove-section",
+    "-e",
+    "--edit",
+    "set",
+    "unset",
+    "rename-section",
+    "remove-section",
+    "edit",
+];
+
+/// `git submodule add` options that take a value.
+const SUBMODULE_VALUE_FLAGS: &[&str] = &["-b", "--branch", "--name", "--reference", "--depth"];
+
+/// Risky operations in an edit of `path` by a file tool: files under
+/// `.git/hooks/`, `.git/config` and `.gitmodules`.
+pub fn file_operations(path: &str) -> Vec<GitFinding> {
+    let path = path.replace('\\', "/");
+    let name = path.rsplit('/').next().unwrap_or(&path);
+    let finding = if path.contains(".git/hooks/") {
+        GitFinding::new(GitOperation::Hooks, format!("edit of hook '{path}'"))
+    } else if path == ".git/config" || path.ends_with("/.git/config") {
+        GitFinding::new(GitOperation::Config, format!("edit of '{path}'"))
+    } else if name == ".gitmodules" {
+        GitFinding::new(GitOperation::Submodule, format!("edit of '{path}'"))
+    } else {
+        return Vec::new();
+    };
+    vec![finding]
+}
+
+/// The branch checked out in the repository containing `dir`, or None when
+/// it isn't in a repository or HEAD is detached.
+pub fn current_branch(dir: &Path) -> Option<String> {
+    let head = std::fs::read_to_string(git_dir(dir)?.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(String::from)
+}
+
+/// The commit checked out in the repository containing `dir`, resolving
+/// HEAD through loose refs and `packed-refs`, or None when it isn't in a
+/// repository or the branch has no commits yet.
+pub fn head_commit(dir: &Path) -> Option<String> {
+    let git_dir = git_dir(dir)?;
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let Some(reference) = head.trim().strip_prefix("ref: ") else {
+        return Some(head.trim().to_string());
+    };
+    // Branch refs of linked worktrees live in the common directory.
+    let common = std::fs::read_to_string(git_dir.join("commondir"))
+        .map(|c| git_dir.join(c.trim()))
+        .unwrap_or_else(|_| git_dir.clone());
+    for dir in [&git_dir, &common] {
+        if let Ok(commit) = std::fs::read_to_string(dir.join(reference)) {
+            return Some(commit.trim().to_string());
+        }
+    }
+    let packed = std::fs::read_to_string(common.join("packed-refs")).ok()?;
+    packed.lines().find_map(|line| {
+        let (commit, name) = line.split_once(' ')?;
+        (name == reference).then(|| commit.to_string())
+    })
+}
+
+/// The remotes configured in the repository containing `dir`, from the
+/// `[remote "name"]` sections of its config.
+pub fn remotes(dir: &Path) -> Vec<String> {
+    let Some(git_dir) = git_dir(dir) else {
+        return Vec::new();
+    };
+    // Linked worktrees keep the shared config in the common directory.
+    let common = std::fs::read_to_string(git_dir.join("commondir"))
+        .map(|c| git_dir.join(c.trim()))
+        .unwrap_or(git_dir);
+    let config = std::fs::read_to_string(common.join("config")).unwrap_or_default();
+    config
+        .lines()
+        .filter_map(|line| {
+            let section = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+            let name = section.trim().strip_prefix("remote")?.trim();
+            Some(name.strip_prefix('"')?.strip_suffix('"')?.to_string())
+        })
+        .collect()
+}
+
+/// The git directory of the repository containing `dir`.
+fn git_dir(dir: &Path) -> Option<PathBuf> {
+    let dot_git = dir
+        .ancestors()
+        .map(|d| d.join(".git"))
+        .find(|p| p.exists())?;
+    if dot_git.is_file() {
+        // Worktrees and submodules: `gitdir: <path>`.
+        let contents = std::fs::read_to_string(&dot_git).ok()?;
+        let target = contents.strip_prefix("gitdir:")?.trim();
+        Some(dot_git.parent()?.join(target))
+    } else {
+        Some(dot_git)
+    }
+}
+
+/// A git subcommand with the global options before it resolved.
+pub(crate) struct GitCommand<'a> {
+    pub(crate) subcommand: &'a str,
+    pub(crate) args: &'a [String],
+    /// Directory the command runs in (`-C`), relative to the session cwd.
+    pub(crate) dir: Option<&'a str>,
+    /// `-c key=value` overrides.
+    overrides: Vec<&'a str>,
+}
+
+impl<'a> GitCommand<'a> {
+    pub(crate) fn parse(words: &'a [String]) -> Option<Self> {
+        let (program, rest) = words.split_first()?;
+        if program.rsplit('/').next()? != "git" {
+            return None;
+        }
+        let mut dir = None;
+        let mut overrides = Vec::new();
+        let mut i = 0;
+        while let Some(arg) = rest.get(i) {
+            if !arg.starts_with('-') {
+                return Some(Self {
+                    subcommand: arg,
+                    args: &rest[i + 1..],
+                    dir,
+                    overrides,
+                });
+            }
+            if GLOBAL_VALUE_FLAGS.contains(&arg.as_str()) {
+                let value = rest.get(i + 1)?;
+                match arg.as_str() {
+                    "-C" => dir = Some(value.as_str()),
+                    "-c" => overrides.push(value.as_str()),
+                    _ => {}
+                }
+                i += 1;
+            }
+            i += 1;
+        }
+        None
+    }
+}
+
+/// Compiled `git` policy.
+pub struct GitPolicy {
+    protected: GlobSet,
+    rules: Vec<CompiledRule>,
+}
+
+struct CompiledRule {
+    roles: Option<GlobSet>,
+    rule: GitRule,
+}
+
+impl CompiledRule {
+    fn decision(&self, role: &str, operation: GitOperation) -> Option<Decision> {
+        if self.roles.as_ref().is_some_and(|g| !g.is_match(role)) {
+            return None;
+        }
+        match operation {
+            GitOperation::ForcePush => self.rule.force_push,
+            GitOperation::ProtectedPush => self.rule.protected_push,
+            GitOperation::Config => self.rule.config,
+            GitOperation::TagDelete => self.rule.tag_delete,
+            GitOperation::Submodule => self.rule.submodule,
+            GitOperation::Hooks => self.rule.hooks,
+        }
+    }
+}
+
+impl GitPolicy {
+    /// Compile `git`. None when there are no rules.
+    pub fn compile(config: &GitPolicyConfig) -> Result<Option<Self>> {
+        if config.rules.is_empty() {
+            return Ok(None);
+        }
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| {
+                let roles = if rule.roles.is_empty() {
+                    None
+                } else {
+                    Some(build_globset(&rule.roles)?)
+                };
+                Ok(CompiledRule {
+                    roles,
+                    rule: rule.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(Self {
+            protected: build_globset(&config.protected_branches)?,
+            rules,
+        }))
+    }
+
+    /// Every risky operation in a (possibly compound) Bash command run in
+    /// `cwd`. The cwd locates the current branch for pushes that don't
+    /// name one.
+    pub fn operations(&self, command: &str, cwd: Option<&Path>) -> Vec<GitFinding> {
+        split_commands(command)
+            .iter()
+            .flat_map(|c| self.command_operations(c, cwd))
+            .collect()
+    }
+
+    /// Risky operations in one simple command.
+    fn command_operations(&self, command: &str, cwd: Option<&Path>) -> Vec<GitFinding> {
+        let words = split_words(command);
+        let Some(git) = GitCommand::parse(&words) else {
+            return Vec::new();
+        };
+        let mut findings: Vec<GitFinding> = git
+            .overrides
+            .iter()
+            .filter_map(|o| {
+                let (key, value) = o.split_once('=').unwrap_or((o, ""));
+                key.eq_ignore_ascii_case("core.hooksPath").then(|| {
+                    GitFinding::new(
+                        GitOperation::Hooks,
+                        format!("hooks path override to '{value}'"),
+                    )
+                })
+            })
+            .collect();
+        let dir = match (cwd, git.dir) {
+            (Some(cwd), Some(dir)) => Some(cwd.join(dir)),
+            (Some(cwd), None) => Some(cwd.to_path_buf()),
+            (None, Some(dir)) => Some(PathBuf::from(dir)),
+            (None, None) => None,
+        };
+        match git.subcommand {
+            "push" => findings.extend(self.push_operations(git.args, dir.as_deref())),
+            "config" => findings.extend(config_operations(git.args)),
+            "tag" => findings.extend(tag_operations(git.args)),
+            "submodule" => findings.extend(submodule_operations(git.args)),
+            _ => {}
+        }
+        findings
+    }
+
+    /// `git push [options] [remote [refspec...]]`.
+    fn push_operations(&self, args: &[String], dir: Option<&Path>) -> Vec<GitFinding> {
+        let mut force = false;
+        let mut delete = false;
+        let mut all = false;
+        let mut tags = false;
+        let mut positional: Vec<&str> = Vec::new();
+        let mut args = args.iter().map(String::as_str);
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                positional.push(arg);
+                continue;
+            }
+            let flag = arg.split_once('=').map_or(arg, |(flag, _)| flag);
+            match flag {
+                "--force" | "--force-with-lease" => force = true,
+                "--delete" => delete = true,
+                "--all" | "--branches" => all = true,
+                "--tags" => tags = true,
+                "--mirror" => {
+                    force = true;
+                    all = true;
+                }
+                _ if PUSH_VALUE_FLAGS.contains(&flag) && !arg.contains('=') => {
+                    args.next();
+                }
+                _ if !flag.starts_with("--") => {
+                    // Bundled short options, e.g. `-uf`.
+                    force |= flag.contains('f');
+                    delete |= flag.contains('d');
+                }
+                _ => {}
+            }
+        }
+
+        // (branch, or None for an unknown current branch; deleted; forced)
+        let mut targets: Vec<(Option<String>, bool, bool)> = Vec::new();
+        let mut tags_deleted: Vec<String> = Vec::new();
+        let current = || dir.and_then(current_branch);
+        let refspecs = positional.get(1..).unwrap_or_default();
+        if refspecs.is_empty() && !all && !tags {
+            targets.push((current(), false, force));
+        }
+        for refspec in refspecs {
+            let (plus, refspec) = match refspec.strip_prefix('+') {
+                Some(rest) => (true, rest),
+                None => (false, *refspec),
+            };
+            let (src, dst) = refspec.split_once(':').unwrap_or((refspec, refspec));
+            let deleted = delete || src.is_empty();
+            let forced = force || plus;
+            if let Some(tag) = dst.strip_prefix("refs/tags/") {
+                if deleted {
+                    tags_deleted.push(tag.to_string());
+                } else if forced {
+                    targets.push((Some(dst.to_string()), false, true));
+                }
+                continue;
+            }
+            let branch = match dst.strip_prefix("refs/heads/").unwrap_or(dst) {
+                "HEAD" => current(),
+                branch => Some(branch.to_string()),
+            };
+            targets.push((branch, deleted, forced));
+        }
+
+        let mut findings = Vec::new();
+        if all {
+            findings.push(GitFinding::new(
+                GitOperation::ProtectedPush,
+                "push of every branch".into(),
+            ));
+            if force {
+                findings.push(GitFinding::new(
+                    GitOperation::ForcePush,
+                    "force-push of every branch".into(),
+                ));
+            }
+        }
+        for (branch, deleted, forced) in targets {
+            let protected = branch.as_ref().is_some_and(|b| self.protected.is_match(b));
+            let name = match &branch {
+                Some(branch) => format!("'{branch}'"),
+                None => "the current branch".to_string(),
+            };
+            if forced && !deleted {
+                findings.push(GitFinding::new(
+                    GitOperation::ForcePush,
+                    format!("force-push to {name}"),
+                ));
+            }
+            if protected {
+                let detail = if deleted {
+                    format!("deletion of protected branch {name}")
+                } else {
+                    format!("push to protected branch {name}")
+                };
+                findings.push(GitFinding::new(GitOperation::ProtectedPush, detail));
+            }
+        }
+        findings.extend(tags_deleted.into_iter().map(|tag| {
+            GitFinding::new(GitOperation::TagDelete, format!("deletion of tag '{tag}'"))
+        }));
+        findings
+    }
+
+    /// The decision of the first rule that matches `role` and sets a
+    /// decision for `operation`, with that rule's index.
+    pub fn decide(&self, role: &str, operation: GitOperation) -> Option<(Decision, usize)> {
+        self.rules
+            .iter()
+            .enumerate()
+            .find_map(|(i, rule)| rule.decision(role, operation).map(|d| (d, i)))
+    }
+
+    /// The decision for a Bash command and the reasons for it. Deny or ask
+    /// from any operation decides; allow only when every simple command is
+    /// an allowed git operation.
+    pub fn check(
+        &self,
+        role: &str,
+        command: &str,
+        cwd: Option<&Path>,
+    ) -> Option<(Decision, Vec<String>)> {
+        let commands = split_commands(command);
+        let mut undecided = false;
+        let mut findings = Vec::new();
+        for command in &commands {
+            let operations = self.command_operations(command, cwd);
+            undecided |= operations.is_empty();
+            findings.extend(operations);
+        }
+        self.judge(role, &findings, undecided)
+    }
+
+    fn judge(
+        &self,
+        role: &str,
+        findings: &[GitFinding],
+        mut undecided: bool,
+    ) -> Option<(Decision, Vec<String>)> {
+        let mut decided = Vec::new();
+        for finding in findings {
+            let Some((decision, rule)) = self.decide(role, finding.operation) else {
+                undecided = true;
+                continue;
+            };
+            decided.push((
+                decision,
+                format!("{}; git.rules[{}] says {}", finding.detail, rule, decision),
+            ));
+        }
+
+        let worst = decided
+            .iter()
+            .map(|(d, _)| *d)
+            .max_by_key(|d| d.precedence())?;
+        if worst == Decision::Allow && undecided {
+            return None;
+        }
+        let reasons = decided
+            .into_iter()
+            .filter(|(d, _)| *d == worst)
+            .map(|(_, reason)| reason)
+            .collect();
+        Some((worst, reasons))
+    }
+}
+
+/// `git config [scope] [--file F] <key> [<value>]` and the `set`/`unset`
+/// subcommand forms. Reads are not operations.
+fn config_operations(args: &[String]) -> Vec<GitFinding> {
+    let mut positional: Vec<&str> = Vec::new();
+    let mut file: Option<&str> = None;
+    let mut reads = false;
+    let mut writes = false;
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => (flag, Some(value)),
+            _ => (arg, None),
+        };
+        if CONFIG_VALUE_FLAGS.contains(&flag) {
+            let value = inline.or_else(|| args.next());
+            if matches!(flag, "-f" | "--file") {
+                file = value;
+            }
+        } else if positional.is_empty() && CONFIG_READS.contains(&flag) {
+            reads = true;
+        } else if positional.is_empty() && CONFIG_WRITES.contains(&flag) {
+            writes = true;
+        } else if !arg.starts_with('-') {
+            positional.push(arg);
+        }
+    }
+    // `git config get`/`set` name their subcommand as the first word.
+    if reads || !(writes || positional.len() >= 2) {
+        return Vec::new();
+    }
+
+    let key = positional.first().copied().unwrap_or("");
+    let lower = key.to_ascii_lowercase();
+    let gitmodules = file.is_some_and(|f| f.ends_with(".gitmodules"));
+    let change = |what: &str| match positional.get(1) {
+        Some(value) => format!("{what} change to '{value}'"),
+        None => format!("{what} change"),
+    };
+    let finding = if lower == "core.hookspath" {
+        GitFinding::new(GitOperation::Hooks, change("hooks path"))
+    } else if (lower.starts_with("submodule.") || gitmodules) && lower.ends_with(".url") {
+        GitFinding::new(GitOperation::Submodule, change("submodule URL"))
+    } else if key.is_empty() {
+        GitFinding::new(GitOperation::Config, "git config edit".into())
+    } else {
+        GitFinding::new(
+            GitOperation::Config,
+            format!("git config change to '{key}'"),
+        )
+    };
+    vec![finding]
+}
+
+/// `git tag -d <name>...`.
+fn tag_operations(args: &[String]) -> Vec<GitFinding> {
+    if !args.iter().any(|a| a == "-d" || a == "--delete") {
+        return Vec::new();
+    }
+    args.iter()
+        .filter(|a| !a.starts_with('-'))
+        .map(|tag| GitFinding::new(GitOperation::TagDelete, format!("deletion of tag '{tag}'")))
+        .collect()
+}
+
+/// `git submodule add <url> [path]` and `git submodule set-url <path> <url>`.
+fn submodule_operations(args: &[String]) -> Vec<GitFinding> {
+    let mut args = args
+        .iter()
+        .map(String::as_str)
+        .skip_while(|a| a.starts_with('-'));
+    let Some(subcommand) = args.next() else {
+        return Vec::new();
+    };
+    let mut positional = Vec::new();
+    while let Some(arg) = args.next() {
+        if SUBMODULE_VALUE_FLAGS.contains(&arg) {
+            args.next();
+        } else if !arg.starts_with('-') {
+            positional.push(arg);
+        }
+    }
+    let detail = match (subcommand, positional.as_slice()) {
+        ("add", [url, ..]) => format!("new submodule from '{url}'"),
+        ("set-url", [path, url, ..]) => format!("submodule '{path}' URL change to '{url}'"),
+        _ => return Vec::new(),
+    };
+    vec![GitFinding::new(GitOperation::Submodule, detail)]
+}
+
+#[async_trait]
+impl CascadeTier for GitPolicy {
+    async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
+        let role_name = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| "*".to_string());
+        let cwd = input.cwd.as_deref().map(Path::new);
+        let verdict = if input.tool_name == "Bash" {
+            let command = input
+                .tool_input
+                .get("command")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&input.sanitized_input);
+            self.check(&role_name, command, cwd)
+        } else if let Some(path) = &input.file_path {
+            self.judge(&role_name, &file_operations(path), false)
+        } else {
+            None
+        };
+        let Some((decision, reasons)) = verdict else {
+            return Ok(None);
+        };
+
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: role_name,
+            },
+            decision,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::GitPolicy,
+                confidence: 1.0,
+                reason: reasons.join("; "),
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: input.file_path.clone(),
+            session_id: String::new(), // Filled by CascadeRunner
+        }))
+    }
+
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::GitPolicy
+    }
+
+    fn name(&self) -> &str {
+        "git_policy"
+    }
+}
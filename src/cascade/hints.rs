@@ -0,0 +1,155 @@
+//! Remediation hints for denied and asked calls (`hints` in policy.yml),
+//! e.g. which role may write a path the session's role can't.
+
+use std::collections::HashMap;
+
+use globset::GlobSet;
+
+use crate::cascade::messages::{check_template, render_template};
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::CascadeInput;
+use crate::config::roles::{build_globset, default_categories, expand_macros};
+use crate::config::{HintRule, HintsConfig};
+use crate::decision::Decision;
+use crate::error::Result;
+
+/// Built-in roles with restricted write paths.
+const RESTRICTED_ROLES: &[&str] = &[
+    "coder",
+    "tester",
+    "integrator",
+    "devops",
+    "researcher",
+    "architect",
+    "planner",
+    "reviewer",
+    "security-reviewer",
+    "docs",
+];
+
+/// Built-in hints: for each category, the role that writes it, suggested
+/// to every other restricted role denied a write there.
+pub fn builtin_hints() -> Vec<HintRule> {
+    [
+        ("tests", "tester"),
+        ("test_config", "tester"),
+        ("source", "coder"),
+        ("infra", "integrator"),
+        ("ci", "devops"),
+        ("devops", "devops"),
+        ("docs", "docs"),
+    ]
+    .into_iter()
+    .map(|(category, owner)| HintRule {
+        paths: vec![format!("{{{{{}}}}}", category)],
+        roles: RESTRICTED_ROLES
+            .iter()
+            .filter(|role| **role != owner)
+            .map(|role| role.to_string())
+            .collect(),
+        tools: Vec::new(),
+        decision: Some(Decision::Deny),
+        hint: format!(
+            "{{{{path}}}} belongs to the {owner} role: register a {owner} session or ask the {owner} agent"
+        ),
+    })
+    .collect()
+}
+
+/// A compiled hint rule.
+struct Hint {
+    /// None when the rule matches any call.
+    paths: Option<GlobSet>,
+    rule: HintRule,
+}
+
+/// Compiled `hints` table.
+pub struct HintTable {
+    hints: Vec<Hint>,
+    extractor: PathPolicyEngine,
+}
+
+impl HintTable {
+    /// Compile the project's hint rules followed by the built-in ones,
+    /// expanding `{{category}}` macros against `categories` merged over the
+    /// built-in defaults. None when there are no rules.
+    pub fn compile(
+        config: &HintsConfig,
+        categories: &HashMap<String, Vec<String>>,
+    ) -> Result<Option<Self>> {
+        let mut merged = default_categories();
+        merged.extend(categories.clone());
+
+        let mut rules = config.rules.clone();
+        if config.builtin {
+            rules.extend(builtin_hints());
+        }
+        if rules.is_empty() {
+            return Ok(None);
+        }
+
+        let mut hints = Vec::new();
+        for (i, rule) in rules.into_iter().enumerate() {
+            let context = format!("hints.rules[{}]", i);
+            check_template(&rule.hint, &context)?;
+            let paths = if rule.paths.is_empty() {
+                None
+            } else {
+                let patterns = expand_macros(&rule.paths, &merged, "policy.yml", &context)?;
+                Some(build_globset(&patterns)?)
+            };
+            hints.push(Hint { paths, rule });
+        }
+        Ok(Some(Self {
+            hints,
+            extractor: PathPolicyEngine::new()?,
+        }))
+    }
+
+    /// The hint for `decision` on `input` from the first matching rule, or
+    /// None for allows and calls no rule matches.
+    pub fn suggest(
+        &self,
+        input: &CascadeInput,
+        decision: Decision,
+        reason: &str,
+    ) -> Option<String> {
+        if decision == Decision::Allow {
+            return None;
+        }
+        let role = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.as_str())
+            .unwrap_or("*");
+        let targets = self.extractor.target_paths(input);
+
+        let (path, hint) = self.hints.iter().find_map(|hint| {
+            let rule = &hint.rule;
+            if rule.decision.is_some_and(|d| d != decision)
+                || !(rule.roles.is_empty() || rule.roles.iter().any(|r| r == role))
+                || !(rule.tools.is_empty() || rule.tools.contains(&input.tool_name))
+            {
+                return None;
+            }
+            let path = match &hint.paths {
+                Some(globs) => targets.iter().find(|p| globs.is_match(p))?.as_str(),
+                None => targets.first().map(String::as_str).unwrap_or(""),
+            };
+            Some((path, &rule.hint))
+        })?;
+
+        let decision = decision.to_string();
+        Some(render_template(
+            hint,
+            &[
+                ("path", path),
+                ("tool", &input.tool_name),
+                ("role", role),
+                ("decision", &decision),
+                ("reason", reason),
+            ],
+        ))
+    }
+}
@@ -1,16 +1,27 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering as AtomicOrdering};
 use std::sync::RwLock;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::cascade::attachment::Attachment;
+use crate::cascade::command_risk::CommandRiskScorer;
+use crate::cascade::hints::HintTable;
+use crate::cascade::messages::MessageTemplates;
+use crate::clock::{Clock, IdGenerator, SystemClock, TimestampIds};
 use crate::decision::{
     CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
 };
+use crate::config::OrphanPolicy;
 use crate::error::{HookwiseError, Result};
+use crate::notify::{Notifier, NotifyKind};
+use crate::sanitize::SanitizePipeline;
 use crate::scope::ScopeLevel as ScopeLevelType;
+use crate::session::registration::{write_atomic, FileLock};
+use crate::watch::FileWatcher;
 
 /// A pending decision waiting for human response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +36,279 @@ pub struct PendingDecision {
     pub is_ask_reprompt: bool,
     pub ask_reason: Option<String>,
     pub queued_at: DateTime<Utc>,
+    /// Priority computed from risk signals at enqueue time.
+    #[serde(default)]
+    pub priority: Priority,
+    /// PID of the `check` process blocked on this decision.
+    #[serde(default)]
+    pub waiter_pid: Option<u32>,
+    /// Last time the waiting process confirmed it is still waiting.
+    #[serde(default)]
+    pub heartbeat_at: Option<DateTime<Utc>>,
+    /// For Write/Edit: excerpts of the file before and after the change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Attachment>,
+    /// Remediation hint for the call (`hints` in policy.yml).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// The call's command risk score (`command_risk` in policy.yml).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_score: Option<f64>,
+    /// The project the call was made in. Pruning from a project only
+    /// resolves its own entries, into its own storage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<PathBuf>,
+}
+
+impl PendingDecision {
+    /// Whether this decision is `project`'s to prune. Entries queued by
+    /// older releases carry no project and belong to whichever prunes them.
+    pub fn belongs_to(&self, project: Option<&Path>) -> bool {
+        match (project, &self.project_root) {
+            (Some(project), Some(root)) => root == project,
+            _ => true,
+        }
+    }
+}
+
+/// Priority of a pending decision, derived from risk signals.
+/// Ordered so that `Critical > High > Normal > Low`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Low => write!(f, "low"),
+            Priority::Normal => write!(f, "normal"),
+            Priority::High => write!(f, "high"),
+            Priority::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// Verbs in Bash commands that delete or irreversibly overwrite data.
+const DELETION_VERBS: &[&str] = &[
+    "rm", "rmdir", "unlink", "shred", "truncate", "dd", "mkfs", "-delete",
+];
+
+/// Multi-word destructive command prefixes (matched on whitespace-normalized input).
+const DESTRUCTIVE_PHRASES: &[&str] = &[
+    "git clean",
+    "git reset --hard",
+    "git push --force",
+    "git push -f",
+    "git branch -D",
+    "drop table",
+    "drop database",
+];
+
+/// Compute the priority of a pending decision from its risk signals:
+/// a supervisor deny recommendation, a sensitive target path, and
+/// deletion verbs in the input. Read-only tools without any signal are `Low`.
+pub fn compute_priority(
+    tool_name: &str,
+    sanitized_input: &str,
+    recommendation: Option<&SupervisorRecommendation>,
+    sensitive_path: bool,
+) -> Priority {
+    let supervisor_deny = recommendation.is_some_and(|r| r.decision == Decision::Deny);
+    let destructive = has_deletion_verb(sanitized_input);
+
+    let signals = [supervisor_deny, sensitive_path, destructive]
+        .iter()
+        .filter(|s| **s)
+        .count();
+
+    match signals {
+        0 if matches!(tool_name, "Read" | "Glob" | "Grep") => Priority::Low,
+        0 => Priority::Normal,
+        1 if !supervisor_deny => Priority::High,
+        _ => Priority::Critical,
+    }
+}
+
+/// Check whether an input contains a deletion verb or destructive phrase.
+/// The input is typically the sanitized JSON tool input, so JSON punctuation
+/// is treated as a token separator alongside shell operators.
+fn has_deletion_verb(input: &str) -> bool {
+    let tokens: Vec<&str> = input
+        .split(|c: char| c.is_whitespace() || "\"',;&|(){}:".contains(c))
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    if tokens.iter().any(|t| DELETION_VERBS.contains(t)) {
+        return true;
+    }
+
+    let normalized = tokens.join(" ").to_lowercase();
+    DESTRUCTIVE_PHRASES
+        .iter()
+        .any(|p| normalized.contains(&p.to_lowercase()))
+}
+
+/// Sort order for pending decision listings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum QueueSort {
+    /// Highest priority first, then highest command risk, then oldest.
+    #[default]
+    Priority,
+    /// Oldest first, regardless of priority.
+    Age,
+}
+
+/// Sort pending decisions for display. Ties are broken by ID for stable output.
+pub fn sort_pending(pending: &mut [PendingDecision], sort: QueueSort) {
+    match sort {
+        QueueSort::Priority => pending.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then_with(|| {
+                    let risk = |d: &PendingDecision| d.risk_score.unwrap_or(0.0);
+                    risk(b).total_cmp(&risk(a))
+                })
+                .then_with(|| a.queued_at.cmp(&b.queued_at))
+                .then_with(|| a.id.cmp(&b.id))
+        }),
+        QueueSort::Age => {
+            pending.sort_by(|a, b| a.queued_at.cmp(&b.queued_at).then_with(|| a.id.cmp(&b.id)))
+        }
+    }
+}
+
+/// Whether a pending decision has breached its response SLA: it is at or
+/// above the configured nag priority and has waited longer than the overdue
+/// threshold.
+pub fn is_overdue(
+    pending: &PendingDecision,
+    sla: &crate::config::HumanSlaConfig,
+    now: DateTime<Utc>,
+) -> bool {
+    pending.priority >= sla.nag_min_priority && now - pending.queued_at >= sla.overdue_after()
+}
+
+/// How long `pending` has waited, e.g. `45s` or `12m`.
+pub fn waited(pending: &PendingDecision, now: DateTime<Utc>) -> String {
+    let secs = (now - pending.queued_at).num_seconds();
+    if secs < 60 {
+        format!("{secs}s")
+    } else {
+        format!("{}m", secs / 60)
+    }
+}
+
+/// How a pending decision left the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaOutcome {
+    Responded,
+    TimedOut,
+}
+
+/// Response latency record for one human decision, appended to the SLA log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlaRecord {
+    pub id: String,
+    pub role: String,
+    pub tool_name: String,
+    pub priority: Priority,
+    pub queued_at: DateTime<Utc>,
+    pub resolved_at: DateTime<Utc>,
+    pub latency_secs: f64,
+    pub outcome: SlaOutcome,
+    /// Whether the item was overdue when it left the queue.
+    pub overdue: bool,
+    /// Number of nag notifications sent while the item was pending.
+    pub nags: u32,
+}
+
+/// Returns the SLA log path under a project's `.hookwise/` directory.
+/// Lives in `.user/` since response times are personal, not shared policy.
+pub fn sla_log_path(hookwise_dir: &std::path::Path) -> PathBuf {
+    hookwise_dir.join(".user").join("sla.jsonl")
+}
+
+/// Append an SLA record to the log, creating parent directories as needed.
+pub fn append_sla_record(path: &std::path::Path, record: &SlaRecord) -> Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// Load SLA records from the log. Missing files yield an empty list and
+/// malformed lines are skipped.
+pub fn load_sla_records(path: &std::path::Path) -> Vec<SlaRecord> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => contents
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Aggregate human response statistics over a window of SLA records.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SlaSummary {
+    pub responded: usize,
+    pub timed_out: usize,
+    pub overdue: usize,
+    pub nags: u32,
+    pub median_latency_secs: Option<f64>,
+    pub p90_latency_secs: Option<f64>,
+    pub max_latency_secs: Option<f64>,
+}
+
+/// Summarize SLA records resolved at or after `since`. Latency percentiles
+/// only consider responded items, since timeouts measure the timeout itself.
+pub fn summarize_sla(records: &[SlaRecord], since: DateTime<Utc>) -> SlaSummary {
+    let mut summary = SlaSummary::default();
+    let mut latencies = Vec::new();
+
+    for record in records.iter().filter(|r| r.resolved_at >= since) {
+        match record.outcome {
+            SlaOutcome::Responded => {
+                summary.responded += 1;
+                latencies.push(record.latency_secs);
+            }
+            SlaOutcome::TimedOut => summary.timed_out += 1,
+        }
+        if record.overdue {
+            summary.overdue += 1;
+        }
+        summary.nags += record.nags;
+    }
+
+    latencies.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |p: f64| -> Option<f64> {
+        if latencies.is_empty() {
+            return None;
+        }
+        let idx = ((latencies.len() - 1) as f64 * p).round() as usize;
+        Some(latencies[idx])
+    };
+    summary.median_latency_secs = percentile(0.5);
+    summary.p90_latency_secs = percentile(0.9);
+    summary.max_latency_secs = latencies.last().copied();
+    summary
 }
 
 /// The supervisor's recommendation accompanying a human prompt.
@@ -42,6 +326,58 @@ pub struct HumanResponse {
     pub always_ask: bool,
     pub add_rule: bool,
     pub rule_scope: Option<ScopeLevelType>,
+    /// Set when this response was applied as part of a batch approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch: Option<BatchMatch>,
+    /// Identity of whoever answered, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub responded_by: Option<String>,
+}
+
+/// Links a batch-applied response to the representative decision it matched.
+/// Only the representative is persisted as a rule; batch members resolve like
+/// a token-similarity hit on it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchMatch {
+    pub representative_id: String,
+    pub matched_key: CacheKey,
+    pub similarity: f64,
+}
+
+/// Find pending decisions similar to `representative`: same tool and role,
+/// with a token Jaccard coefficient at or above `threshold`. Inputs with fewer
+/// than `min_tokens` tokens only match exactly, mirroring Tier 2a. Results are
+/// sorted by descending similarity and exclude the representative itself.
+pub fn find_similar_pending(
+    representative: &PendingDecision,
+    pending: &[PendingDecision],
+    threshold: f64,
+    min_tokens: usize,
+) -> Vec<(PendingDecision, f64)> {
+    use crate::cascade::token_sim::TokenJaccard;
+
+    let rep_tokens = TokenJaccard::tokenize(&representative.sanitized_input);
+    let mut similar: Vec<(PendingDecision, f64)> = pending
+        .iter()
+        .filter(|p| {
+            p.id != representative.id
+                && p.tool_name == representative.tool_name
+                && p.role == representative.role
+        })
+        .filter_map(|p| {
+            if p.sanitized_input == representative.sanitized_input {
+                return Some((p.clone(), 1.0));
+            }
+            let tokens = TokenJaccard::tokenize(&p.sanitized_input);
+            if tokens.len() < min_tokens || rep_tokens.len() < min_tokens {
+                return None;
+            }
+            let score = TokenJaccard::jaccard_coefficient(&rep_tokens, &tokens);
+            (score >= threshold).then(|| (p.clone(), score))
+        })
+        .collect();
+    similar.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.id.cmp(&b.0.id)));
+    similar
 }
 
 /// File-backed queue state persisted to disk so separate CLI processes can interact.
@@ -49,6 +385,204 @@ pub struct HumanResponse {
 pub struct QueueFileState {
     pub pending: HashMap<String, PendingDecision>,
     pub responses: HashMap<String, HumanResponse>,
+    /// When each entry in `responses` was written, used to expire responses
+    /// whose waiting process died before picking them up.
+    #[serde(default)]
+    pub responded_at: HashMap<String, DateTime<Utc>>,
+}
+
+/// Entries removed from the queue file by [`prune_queue_state`] and
+/// [`remove_orphaned_pending`].
+#[derive(Debug, Clone, Default)]
+pub struct PruneReport {
+    /// Pending decisions older than the TTL.
+    pub expired: Vec<PendingDecision>,
+    /// Ids of responses nobody claimed within the TTL.
+    pub orphaned_responses: Vec<String>,
+    /// Pending decisions whose waiting process is gone.
+    pub orphaned: Vec<PendingDecision>,
+}
+
+impl PruneReport {
+    pub fn is_empty(&self) -> bool {
+        self.expired.is_empty() && self.orphaned_responses.is_empty() && self.orphaned.is_empty()
+    }
+}
+
+/// How often a waiting `check` process refreshes `heartbeat_at`.
+pub const HEARTBEAT_INTERVAL_SECS: u64 = 10;
+
+/// A heartbeat older than this marks the waiter as gone even if its PID is
+/// still in use (e.g. recycled by an unrelated process).
+pub const HEARTBEAT_STALE_SECS: i64 = 60;
+
+/// Whether a process with this PID exists on this host.
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = i32::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 performs only the existence/permission check.
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+pub fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Whether nobody is waiting on a pending decision any more: its waiter
+/// PID is dead or its heartbeat went stale. Entries without a waiter (from
+/// older releases) are never orphaned; the TTL prune handles them.
+pub fn is_orphaned(
+    pending: &PendingDecision,
+    now: DateTime<Utc>,
+    alive: impl Fn(u32) -> bool,
+) -> bool {
+    let Some(pid) = pending.waiter_pid else {
+        return false;
+    };
+    let stale = pending
+        .heartbeat_at
+        .is_some_and(|at| now - at > chrono::Duration::seconds(HEARTBEAT_STALE_SECS));
+    stale || !alive(pid)
+}
+
+/// Remove orphaned pending decisions (see [`is_orphaned`]) of `project`,
+/// or of every project when `None`, returned oldest first.
+pub fn remove_orphaned_pending(
+    state: &mut QueueFileState,
+    project: Option<&Path>,
+    now: DateTime<Utc>,
+    alive: impl Fn(u32) -> bool,
+) -> Vec<PendingDecision> {
+    let ids: Vec<String> = state
+        .pending
+        .values()
+        .filter(|p| p.belongs_to(project) && is_orphaned(p, now, &alive))
+        .map(|p| p.id.clone())
+        .collect();
+    let mut orphaned: Vec<PendingDecision> = ids
+        .iter()
+        .filter_map(|id| state.pending.remove(id))
+        .collect();
+    orphaned.sort_by(|a, b| a.queued_at.cmp(&b.queued_at).then_with(|| a.id.cmp(&b.id)));
+    orphaned
+}
+
+/// Remove pending decisions of `project` (every project when `None`)
+/// queued more than `ttl_secs` ago, and responses written more than
+/// `ttl_secs` ago (or with no recorded time) that no process collected.
+/// Expired entries are returned oldest first.
+pub fn prune_queue_state(
+    state: &mut QueueFileState,
+    project: Option<&Path>,
+    ttl_secs: u64,
+    now: DateTime<Utc>,
+) -> PruneReport {
+    let cutoff = now - chrono::Duration::seconds(ttl_secs as i64);
+
+    let expired_ids: Vec<String> = state
+        .pending
+        .values()
+        .filter(|p| p.belongs_to(project) && p.queued_at < cutoff)
+        .map(|p| p.id.clone())
+        .collect();
+    let mut expired: Vec<PendingDecision> = expired_ids
+        .iter()
+        .filter_map(|id| state.pending.remove(id))
+        .collect();
+    expired.sort_by(|a, b| a.queued_at.cmp(&b.queued_at).then_with(|| a.id.cmp(&b.id)));
+
+    let mut orphaned_responses: Vec<String> = state
+        .responses
+        .keys()
+        .filter(|id| !matches!(state.responded_at.get(*id), Some(at) if *at >= cutoff))
+        .cloned()
+        .collect();
+    orphaned_responses.sort();
+    for id in &orphaned_responses {
+        state.responses.remove(id);
+    }
+    state
+        .responded_at
+        .retain(|id, _| state.responses.contains_key(id));
+
+    PruneReport {
+        expired,
+        orphaned_responses,
+        orphaned: Vec::new(),
+    }
+}
+
+/// The record stored for a pending decision that expired without an answer.
+pub fn timed_out_record(pending: &PendingDecision, ttl_secs: u64) -> DecisionRecord {
+    DecisionRecord {
+        key: CacheKey {
+            sanitized_input: pending.sanitized_input.clone(),
+            tool: pending.tool_name.clone(),
+            role: pending.role.clone(),
+        },
+        decision: Decision::Deny,
+        metadata: DecisionMetadata {
+            tier: DecisionTier::Human,
+            confidence: 1.0,
+            reason: format!("timed out: no human response within {}s (pruned)", ttl_secs),
+            matched_key: None,
+            similarity_score: None,
+            targets: Vec::new(),
+            message: None,
+            suggestion: None,
+            risk_score: None,
+            secrets: Vec::new(),
+            client: None,
+        },
+        timestamp: Utc::now(),
+        scope: ScopeLevel::Project,
+        file_path: pending.file_path.clone(),
+        session_id: pending.session_id.clone(),
+    }
+}
+
+/// The record for an orphaned pending decision, resolved per `policy`.
+/// Only [`OrphanPolicy::Deny`] records are persisted as rules; both kinds
+/// are written to the audit log.
+pub fn orphaned_record(pending: &PendingDecision, policy: OrphanPolicy) -> DecisionRecord {
+    let waiter = pending
+        .waiter_pid
+        .map(|pid| format!("pid {}", pid))
+        .unwrap_or_else(|| "unknown".into());
+    DecisionRecord {
+        key: CacheKey {
+            sanitized_input: pending.sanitized_input.clone(),
+            tool: pending.tool_name.clone(),
+            role: pending.role.clone(),
+        },
+        decision: Decision::Deny,
+        metadata: DecisionMetadata {
+            tier: DecisionTier::Human,
+            confidence: 1.0,
+            reason: format!(
+                "orphaned: waiting process ({}) exited before a human response (orphaned_queue: {})",
+                waiter, policy
+            ),
+            matched_key: None,
+            similarity_score: None,
+            targets: Vec::new(),
+            message: None,
+            suggestion: None,
+            risk_score: None,
+            secrets: Vec::new(),
+            client: None,
+        },
+        timestamp: Utc::now(),
+        scope: ScopeLevel::Project,
+        file_path: pending.file_path.clone(),
+        session_id: pending.session_id.clone(),
+    }
 }
 
 /// Returns the path for the file-backed pending queue.
@@ -60,11 +594,7 @@ pub fn pending_queue_path() -> PathBuf {
         .unwrap_or_default();
     let filename = format!("hookwise-pending{}.json", team_suffix);
 
-    if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
-        PathBuf::from(runtime_dir).join(filename)
-    } else {
-        PathBuf::from("/tmp").join(filename)
-    }
+    crate::config::dirs_runtime().join(filename)
 }
 
 /// Load the file-backed queue state from disk.
@@ -76,11 +606,13 @@ pub fn load_queue_file() -> QueueFileState {
     }
 }
 
-/// Save the file-backed queue state to disk.
+/// Save the file-backed queue state to disk, atomically so readers never
+/// see a half-written file.
 fn save_queue_file(state: &QueueFileState) -> Result<()> {
     let path = pending_queue_path();
     let json = serde_json::to_string_pretty(state)?;
-    std::fs::write(&path, json)?;
+    write_atomic(&path, json.as_bytes())?;
+    crate::telemetry::record_queue_depth(state.pending.len());
     Ok(())
 }
 
@@ -90,6 +622,9 @@ fn save_queue_file(state: &QueueFileState) -> Result<()> {
 pub struct DecisionQueue {
     pending: RwLock<HashMap<String, PendingDecision>>,
     completed: RwLock<HashMap<String, HumanResponse>>,
+    /// When false the queue file is never touched and all state stays in
+    /// memory (e.g. when the queue location is read-only).
+    file_backed: bool,
 }
 
 impl Default for DecisionQueue {
@@ -103,9 +638,75 @@ impl DecisionQueue {
         Self {
             pending: RwLock::new(HashMap::new()),
             completed: RwLock::new(HashMap::new()),
+            file_backed: true,
+        }
+    }
+
+    /// A queue that keeps all state in memory. Other processes cannot see
+    /// or answer its pending decisions.
+    pub fn memory_only() -> Self {
+        Self {
+            file_backed: false,
+            ..Self::new()
+        }
+    }
+
+    fn load_state(&self) -> QueueFileState {
+        if self.file_backed {
+            return load_queue_file();
+        }
+        QueueFileState {
+            pending: self
+                .pending
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+            responses: self
+                .completed
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+            responded_at: HashMap::new(),
         }
     }
 
+    fn save_state(&self, state: &QueueFileState) -> Result<()> {
+        if self.file_backed {
+            return save_queue_file(state);
+        }
+        *self.pending.write().unwrap_or_else(|e| e.into_inner()) = state.pending.clone();
+        *self.completed.write().unwrap_or_else(|e| e.into_inner()) = state.responses.clone();
+        Ok(())
+    }
+
+    /// Load, change and save the queue state as one step. A file-backed
+    /// queue holds the queue file's lock throughout, so hooks and CLI
+    /// commands writing at the same time don't drop each other's changes.
+    /// `change` returns `None` when it changed nothing; the file is then
+    /// left alone.
+    fn update<R>(
+        &self,
+        change: impl FnOnce(&mut QueueFileState) -> Option<R>,
+    ) -> Result<Option<R>> {
+        let _lock = if self.file_backed {
+            match FileLock::acquire(&pending_queue_path()) {
+                Ok(lock) => Some(lock),
+                Err(e) => {
+                    tracing::warn!("updating the decision queue without its lock: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let mut state = self.load_state();
+        let Some(result) = change(&mut state) else {
+            return Ok(None);
+        };
+        self.save_state(&state)?;
+        Ok(Some(result))
+    }
+
     pub fn enqueue(&self, decision: PendingDecision) -> String {
         let id = decision.id.clone();
         {
@@ -113,20 +714,23 @@ impl DecisionQueue {
             pending.insert(id.clone(), decision.clone());
         }
         // Also write to file for cross-process visibility
-        let mut state = load_queue_file();
-        state.pending.insert(id.clone(), decision);
-        let _ = save_queue_file(&state);
+        let _ = self.update(|state| {
+            state.pending.insert(id.clone(), decision);
+            Some(())
+        });
         id
     }
 
     pub fn list_pending(&self) -> Vec<PendingDecision> {
         // Read from file to get cross-process state
-        let state = load_queue_file();
-        state.pending.values().cloned().collect()
+        let state = self.load_state();
+        let mut pending: Vec<_> = state.pending.values().cloned().collect();
+        sort_pending(&mut pending, QueueSort::Priority);
+        pending
     }
 
     pub fn get_pending(&self, id: &str) -> Option<PendingDecision> {
-        let state = load_queue_file();
+        let state = self.load_state();
         state.pending.get(id).cloned()
     }
 
@@ -140,16 +744,67 @@ impl DecisionQueue {
             completed.insert(id.to_string(), response.clone());
         }
         // Also write to file for cross-process visibility
-        let mut state = load_queue_file();
-        state.pending.remove(id);
-        state.responses.insert(id.to_string(), response);
-        save_queue_file(&state)?;
+        self.update(|state| {
+            state.pending.remove(id);
+            state.responses.insert(id.to_string(), response);
+            state.responded_at.insert(id.to_string(), Utc::now());
+            Some(())
+        })?;
         Ok(())
     }
 
+    /// Expire stale entries in the file-backed queue (see
+    /// [`prune_queue_state`]) and remove orphaned pending decisions (see
+    /// [`remove_orphaned_pending`]) of `project`. With `dry_run` the file is
+    /// left untouched.
+    pub fn prune(
+        &self,
+        ttl_secs: u64,
+        project: Option<&Path>,
+        dry_run: bool,
+    ) -> Result<PruneReport> {
+        let now = Utc::now();
+        let plan = |state: &mut QueueFileState| {
+            let mut report = prune_queue_state(state, project, ttl_secs, now);
+            report.orphaned = remove_orphaned_pending(state, project, now, process_alive);
+            report
+        };
+        if dry_run {
+            return Ok(plan(&mut self.load_state()));
+        }
+        let Some(report) = self.update(|state| Some(plan(state)).filter(|r| !r.is_empty()))? else {
+            return Ok(PruneReport::default());
+        };
+        {
+            let mut pending = self.pending.write().unwrap_or_else(|e| e.into_inner());
+            for p in report.expired.iter().chain(&report.orphaned) {
+                pending.remove(&p.id);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Refresh the heartbeat of a pending decision this process waits on.
+    fn heartbeat(&self, id: &str) {
+        let _ = self.update(|state| {
+            let pending = state.pending.get_mut(id)?;
+            pending.heartbeat_at = Some(Utc::now());
+            Some(())
+        });
+    }
+
     pub async fn wait_for_response(&self, id: &str, timeout_secs: u64) -> Result<HumanResponse> {
         let start = std::time::Instant::now();
         let timeout = std::time::Duration::from_secs(timeout_secs);
+        let heartbeat_interval = std::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS);
+        let mut last_heartbeat = start;
+        // Wake as soon as another process writes a response to the queue
+        // file; the poll interval still bounds in-memory responses.
+        let mut watcher = if self.file_backed {
+            FileWatcher::new([pending_queue_path()]).start().ok()
+        } else {
+            None
+        };
 
         loop {
             // Check in-memory first
@@ -157,11 +812,22 @@ impl DecisionQueue {
                 return Ok(response);
             }
 
-            // Then check file-backed state (response from another process)
-            let mut state = load_queue_file();
-            if let Some(response) = state.responses.remove(id) {
-                state.pending.remove(id);
-                let _ = save_queue_file(&state);
+            // Then check file-backed state (response from another process).
+            // Peek first so idle polls don't take the lock.
+            let answered = self.load_state().responses.contains_key(id);
+            let taken = if answered {
+                self.update(|state| {
+                    let response = state.responses.remove(id)?;
+                    state.pending.remove(id);
+                    state.responded_at.remove(id);
+                    Some(response)
+                })
+                .ok()
+                .flatten()
+            } else {
+                None
+            };
+            if let Some(response) = taken {
                 // Also update in-memory state
                 let mut pending = self.pending.write().unwrap_or_else(|e| e.into_inner());
                 pending.remove(id);
@@ -175,15 +841,76 @@ impl DecisionQueue {
                     pending.remove(id);
                 }
                 // Also clean up file
-                let mut state = load_queue_file();
-                state.pending.remove(id);
-                let _ = save_queue_file(&state);
+                let _ = self.update(|state| state.pending.remove(id).map(|_| ()));
 
                 return Err(HookwiseError::HumanTimeout { timeout_secs });
             }
 
-            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            if last_heartbeat.elapsed() >= heartbeat_interval {
+                self.heartbeat(id);
+                last_heartbeat = std::time::Instant::now();
+            }
+
+            let poll = std::time::Duration::from_millis(200);
+            match &mut watcher {
+                Some(watcher) => {
+                    watcher.changed_within(poll).await;
+                }
+                None => tokio::time::sleep(poll).await,
+            }
+        }
+    }
+
+    /// Respond to `id` and apply the same decision to every similar pending
+    /// entry (see [`find_similar_pending`]). The representative keeps the
+    /// full response; batch members never add rules of their own.
+    /// Returns the ids and similarity scores of the batch members.
+    pub fn respond_like(
+        &self,
+        id: &str,
+        response: HumanResponse,
+        threshold: f64,
+        min_tokens: usize,
+    ) -> Result<Vec<(String, f64)>> {
+        let state = self.load_state();
+        let representative =
+            state
+                .pending
+                .get(id)
+                .cloned()
+                .ok_or_else(|| HookwiseError::Storage {
+                    reason: format!("no pending decision with id {}", id),
+                })?;
+        let pending: Vec<_> = state.pending.values().cloned().collect();
+        let similar = find_similar_pending(&representative, &pending, threshold, min_tokens);
+
+        let matched_key = CacheKey {
+            sanitized_input: representative.sanitized_input.clone(),
+            tool: representative.tool_name.clone(),
+            role: representative.role.clone(),
+        };
+        self.respond(id, response.clone())?;
+        for (member, similarity) in &similar {
+            self.respond(
+                &member.id,
+                HumanResponse {
+                    decision: response.decision,
+                    always_ask: response.always_ask,
+                    add_rule: false,
+                    rule_scope: None,
+                    batch: Some(BatchMatch {
+                        representative_id: id.to_string(),
+                        matched_key: matched_key.clone(),
+                        similarity: *similarity,
+                    }),
+                    responded_by: response.responded_by.clone(),
+                },
+            )?;
         }
+        Ok(similar
+            .into_iter()
+            .map(|(member, similarity)| (member.id, similarity))
+            .collect())
     }
 
     pub fn take_response(&self, id: &str) -> Option<HumanResponse> {
@@ -196,6 +923,24 @@ impl DecisionQueue {
 pub struct HumanTier {
     queue: std::sync::Arc<DecisionQueue>,
     timeout_secs: u64,
+    sla: crate::config::HumanSlaConfig,
+    sla_log: Option<PathBuf>,
+    notifier: Option<std::sync::Arc<Notifier>>,
+    escalation: Vec<EscalationStep>,
+    messages: Option<std::sync::Arc<MessageTemplates>>,
+    hints: Option<std::sync::Arc<HintTable>>,
+    command_risk: Option<std::sync::Arc<CommandRiskScorer>>,
+    attachments: Option<(crate::config::AttachmentConfig, SanitizePipeline)>,
+    clock: std::sync::Arc<dyn Clock>,
+    ids: std::sync::Arc<dyn IdGenerator>,
+}
+
+/// A configured escalation stage with its notifier built.
+struct EscalationStep {
+    name: String,
+    timeout_secs: u64,
+    min_risk: Option<f64>,
+    notifier: Notifier,
 }
 
 impl HumanTier {
@@ -203,8 +948,213 @@ impl HumanTier {
         Self {
             queue,
             timeout_secs,
+            sla: crate::config::HumanSlaConfig::default(),
+            sla_log: None,
+            notifier: None,
+            escalation: Vec::new(),
+            messages: None,
+            hints: None,
+            command_risk: None,
+            attachments: None,
+            clock: std::sync::Arc::new(SystemClock),
+            ids: std::sync::Arc::new(TimestampIds),
+        }
+    }
+
+    /// Read queue and decision times from `clock` instead of the wall clock.
+    pub fn with_clock(mut self, clock: std::sync::Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Name pending decisions with `ids` instead of queue timestamps.
+    pub fn with_ids(mut self, ids: std::sync::Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Escalate unanswered decisions through a chain of approvers. When a
+    /// call goes through any stage, the duration of its stages replaces the
+    /// human timeout.
+    pub fn with_escalation(mut self, config: &crate::config::EscalationConfig) -> Self {
+        self.escalation = config
+            .stages
+            .iter()
+            .map(|stage| EscalationStep {
+                name: stage.name.clone(),
+                timeout_secs: stage.timeout_secs,
+                min_risk: stage.min_risk,
+                notifier: Notifier::new(stage.channels.clone(), Priority::Low),
+            })
+            .collect();
+        self
+    }
+
+    /// The escalation stages a call with `risk_score` goes through.
+    fn escalation_for(&self, risk_score: Option<f64>) -> Vec<&EscalationStep> {
+        let score = risk_score.unwrap_or(0.0);
+        self.escalation
+            .iter()
+            .filter(|step| step.min_risk.is_none_or(|min| score >= min))
+            .collect()
+    }
+
+    /// How long a call with `risk_score` waits for an answer.
+    fn timeout_for(&self, risk_score: Option<f64>) -> u64 {
+        let steps = self.escalation_for(risk_score);
+        if steps.is_empty() {
+            self.timeout_secs
+        } else {
+            steps.iter().map(|step| step.timeout_secs).sum()
         }
     }
+
+    /// Send push notifications when decisions are queued or go overdue.
+    pub fn with_notifier(mut self, notifier: Option<std::sync::Arc<Notifier>>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    /// Render policy authors' ask messages into queue entries (and so into
+    /// notifications).
+    pub fn with_messages(mut self, messages: Option<std::sync::Arc<MessageTemplates>>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Add remediation hints to queue entries.
+    pub fn with_hints(mut self, hints: Option<std::sync::Arc<HintTable>>) -> Self {
+        self.hints = hints;
+        self
+    }
+
+    /// Score queued calls, raising their priority at `command_risk.high`
+    /// and `critical` and picking the escalation stages they go through.
+    pub fn with_command_risk(
+        mut self,
+        command_risk: Option<std::sync::Arc<CommandRiskScorer>>,
+    ) -> Self {
+        self.command_risk = command_risk;
+        self
+    }
+
+    /// Attach sanitized excerpts of the target file to Write/Edit entries.
+    pub fn with_attachments(
+        mut self,
+        config: &crate::config::AttachmentConfig,
+        sanitizer: SanitizePipeline,
+    ) -> Self {
+        self.attachments = config.enabled.then(|| (config.clone(), sanitizer));
+        self
+    }
+
+    /// Configure overdue nagging and record response latency to `sla_log`.
+    pub fn with_sla(
+        mut self,
+        sla: crate::config::HumanSlaConfig,
+        sla_log: Option<PathBuf>,
+    ) -> Self {
+        self.sla = sla;
+        self.sla_log = sla_log;
+        self
+    }
+
+    /// Notify about `pending` once queued, walk the escalation chain, and
+    /// re-notify every nag interval once it is overdue. Never completes on its
+    /// own; it is raced against the queue wait.
+    async fn notify_until_answered(&self, pending: &PendingDecision, nags: &AtomicU32) {
+        tokio::join!(self.escalate(pending), self.nag(pending, nags));
+    }
+
+    /// Notify the configured channels, then each escalation stage in turn as
+    /// the previous stage's timeout elapses.
+    async fn escalate(&self, pending: &PendingDecision) {
+        if let Some(notifier) = &self.notifier {
+            notifier.notify_pending(pending, NotifyKind::Queued).await;
+        }
+
+        let mut offset_secs = 0;
+        for (i, step) in self
+            .escalation_for(pending.risk_score)
+            .into_iter()
+            .enumerate()
+        {
+            let starts_at = pending.queued_at + chrono::Duration::seconds(offset_secs as i64);
+            tokio::time::sleep((starts_at - self.clock.now()).to_std().unwrap_or_default()).await;
+
+            let kind = if i == 0 {
+                NotifyKind::Queued
+            } else {
+                eprintln!(
+                    "hookwise: no response to {} after {}s, escalating to {}",
+                    pending.id, offset_secs, step.name
+                );
+                NotifyKind::Escalated(step.name.clone())
+            };
+            step.notifier.notify_pending(pending, kind).await;
+            offset_secs += step.timeout_secs;
+        }
+
+        std::future::pending::<()>().await;
+    }
+
+    /// Re-notify every nag interval once `pending` is overdue.
+    async fn nag(&self, pending: &PendingDecision, nags: &AtomicU32) {
+        if pending.priority < self.sla.nag_min_priority {
+            std::future::pending::<()>().await;
+        }
+
+        let overdue_at = pending.queued_at + self.sla.overdue_after();
+        let until_overdue = (overdue_at - self.clock.now()).to_std().unwrap_or_default();
+        tokio::time::sleep(until_overdue).await;
+
+        let interval = self.sla.nag_interval();
+        loop {
+            let count = nags.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+            send_nag(pending, count, self.clock.now());
+            if let Some(notifier) = &self.notifier {
+                notifier
+                    .notify_pending(pending, NotifyKind::Overdue(count))
+                    .await;
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    fn record_sla(&self, pending: &PendingDecision, outcome: SlaOutcome, nags: u32) {
+        let Some(path) = &self.sla_log else {
+            return;
+        };
+        let resolved_at = self.clock.now();
+        let record = SlaRecord {
+            id: pending.id.clone(),
+            role: pending.role.clone(),
+            tool_name: pending.tool_name.clone(),
+            priority: pending.priority,
+            queued_at: pending.queued_at,
+            resolved_at,
+            latency_secs: (resolved_at - pending.queued_at).num_milliseconds() as f64 / 1000.0,
+            outcome,
+            overdue: is_overdue(pending, &self.sla, resolved_at),
+            nags,
+        };
+        if let Err(e) = append_sla_record(path, &record) {
+            tracing::warn!("failed to record SLA entry for {}: {}", pending.id, e);
+        }
+    }
+}
+
+/// Re-send the notification for an overdue pending decision.
+fn send_nag(pending: &PendingDecision, count: u32, now: DateTime<Utc>) {
+    eprintln!(
+        "\x07hookwise: OVERDUE ({}) {} {} waiting {} -- approve with `hookwise approve {}` (reminder #{})",
+        pending.priority,
+        pending.tool_name,
+        pending.role,
+        waited(pending, now),
+        pending.id,
+        count
+    );
 }
 
 #[async_trait]
@@ -221,11 +1171,29 @@ impl crate::cascade::CascadeTier for HumanTier {
             .unwrap_or_else(|| "*".to_string());
 
         // Generate a unique ID for this pending decision
-        let id = format!(
-            "{}-{}-{}",
-            role_name,
-            input.tool_name,
-            Utc::now().timestamp_millis()
+        let queued_at = self.clock.now();
+        let id = self.ids.next_id(&role_name, &input.tool_name, queued_at);
+
+        let sensitive_path = match (&input.session.path_policy, &input.file_path) {
+            (Some(policy), Some(path)) => policy.sensitive_ask_write.is_match(path),
+            _ => false,
+        };
+        let risk_score = self
+            .command_risk
+            .as_ref()
+            .map(|scorer| scorer.score(input).score);
+        let priority = compute_priority(
+            &input.tool_name,
+            &input.sanitized_input,
+            input.recommendation.as_ref(),
+            sensitive_path,
+        )
+        .max(
+            self.command_risk
+                .as_ref()
+                .zip(risk_score)
+                .and_then(|(scorer, score)| scorer.priority(score))
+                .unwrap_or(Priority::Low),
         );
 
         let pending = PendingDecision {
@@ -235,16 +1203,54 @@ impl crate::cascade::CascadeTier for HumanTier {
             tool_name: input.tool_name.clone(),
             sanitized_input: input.sanitized_input.clone(),
             file_path: input.file_path.clone(),
-            recommendation: None,
+            recommendation: input.recommendation.clone(),
             is_ask_reprompt: false,
-            ask_reason: None,
-            queued_at: Utc::now(),
+            ask_reason: self.messages.as_ref().and_then(|messages| {
+                messages.render(input, Decision::Ask, "no automatic tier decided")
+            }),
+            queued_at,
+            priority,
+            waiter_pid: Some(std::process::id()),
+            heartbeat_at: Some(queued_at),
+            attachment: self.attachments.as_ref().and_then(|(config, sanitizer)| {
+                Attachment::capture(
+                    &input.tool_name,
+                    &input.sanitized_input,
+                    input.file_path.as_deref(),
+                    input.cwd.as_deref(),
+                    config,
+                    sanitizer,
+                )
+            }),
+            suggestion: self
+                .hints
+                .as_ref()
+                .and_then(|hints| hints.suggest(input, Decision::Ask, "no automatic tier decided")),
+            risk_score,
+            project_root: input.cwd.as_ref().map(PathBuf::from),
         };
 
-        self.queue.enqueue(pending);
+        self.queue.enqueue(pending.clone());
 
-        // Wait for human response
-        let response = self.queue.wait_for_response(&id, self.timeout_secs).await?;
+        // Wait for human response, nagging if the item goes overdue
+        let nags = AtomicU32::new(0);
+        let result = tokio::select! {
+            r = self.queue.wait_for_response(&id, self.timeout_for(risk_score)) => r,
+            _ = self.notify_until_answered(&pending, &nags) => unreachable!("nag loop never completes"),
+        };
+        let nags = nags.load(AtomicOrdering::Relaxed);
+        let response = match result {
+            Ok(response) => {
+                self.record_sla(&pending, SlaOutcome::Responded, nags);
+                response
+            }
+            Err(e) => {
+                if matches!(e, HookwiseError::HumanTimeout { .. }) {
+                    self.record_sla(&pending, SlaOutcome::TimedOut, nags);
+                }
+                return Err(e);
+            }
+        };
 
         // The decision from the human. If always_ask, store as Ask.
         let effective_decision = if response.always_ask {
@@ -253,6 +1259,46 @@ impl crate::cascade::CascadeTier for HumanTier {
             response.decision
         };
 
+        let by = response
+            .responded_by
+            .as_ref()
+            .map(|who| format!(" by {who}"))
+            .unwrap_or_default();
+
+        // Batch members resolve as a similarity hit on the representative so
+        // only the representative is persisted as a rule.
+        let metadata = match response.batch {
+            Some(batch) => DecisionMetadata {
+                tier: DecisionTier::TokenJaccard,
+                confidence: batch.similarity,
+                reason: format!(
+                    "human decision: {}{} (batch with {})",
+                    response.decision, by, batch.representative_id
+                ),
+                matched_key: Some(batch.matched_key),
+                similarity_score: Some(batch.similarity),
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            None => DecisionMetadata {
+                tier: DecisionTier::Human,
+                confidence: 1.0,
+                reason: format!("human decision: {}{}", response.decision, by),
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+        };
+
         Ok(Some(DecisionRecord {
             key: CacheKey {
                 sanitized_input: input.sanitized_input.clone(),
@@ -260,14 +1306,8 @@ impl crate::cascade::CascadeTier for HumanTier {
                 role: role_name,
             },
             decision: effective_decision,
-            metadata: DecisionMetadata {
-                tier: DecisionTier::Human,
-                confidence: 1.0,
-                reason: format!("human decision: {}", response.decision),
-                matched_key: None,
-                similarity_score: None,
-            },
-            timestamp: Utc::now(),
+            metadata,
+            timestamp: self.clock.now(),
             scope: response.rule_scope.unwrap_or(ScopeLevel::Project),
             file_path: input.file_path.clone(),
             session_id: String::new(), // Filled by CascadeRunner
@@ -0,0 +1,368 @@
+//! Inline interpreter scripts (`python -c`, `node -e`, `ruby -e`, ...),
+//! whose file writes the Bash path extractors can't see.
+//!
+//! Calls that write or delete files are found by name, and their path
+//! arguments read when they are string literals. Other string literals are
+//! reported too, so a script that merely reads `.env` can still be asked about.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::cascade::shell::split_words;
+
+/// Interpreters and the flags that take inline code.
+const INTERPRETERS: &[(&str, &[&str])] = &[
+    ("python", &["-c"]),
+    ("node", &["-e", "--eval", "-p", "--print"]),
+    ("nodejs", &["-e", "--eval", "-p", "--print"]),
+    ("bun", &["-e", "--eval", "-p", "--print"]),
+    ("ruby", &["-e"]),
+    ("perl", &["-e", "-E"]),
+    ("php", &["-r"]),
+];
+
+/// A call that writes or deletes files.
+struct WriteCall {
+    /// Interpreters the call belongs to.
+    interpreters: &'static [&'static str],
+    /// Matches the call up to and including its opening parenthesis.
+    pattern: &'static str,
+    /// Positions of the path arguments.
+    paths: &'static [usize],
+    /// Position of an open mode argument: the call only writes when the
+    /// mode does. A missing mode reads; a computed one may write.
+    mode: Option<usize>,
+}
+
+/// A Node `fs` call: `fs.name(`, `fsp.name(` or `require('fs').name(`.
+macro_rules! node_fs {
+    ($names:literal) => {
+        concat!(
+            r#"(?:\b(?:fs|fsp|promises)|require\(\s*['"](?:node:)?fs(?:/promises)?['"]\s*\))\.(?:"#,
+            $names,
+            r")\s*\("
+        )
+    };
+}
+
+const PYTHON: &[&str] = &["python"];
+const NODE: &[&str] = &["node", "nodejs", "bun"];
+const RUBY: &[&str] = &["ruby"];
+const PERL: &[&str] = &["perl"];
+const PHP: &[&str] = &["php"];
+const PERL_PHP: &[&str] = &["perl", "php"];
+
+const WRITE_CALLS: &[WriteCall] = &[
+    // Python
+    WriteCall {
+        interpreters: PYTHON,
+        pattern: r"(?:^|[^\w.])open\s*\(",
+        paths: &[0],
+        mode: Some(1),
+    },
+    WriteCall {
+        interpreters: PYTHON,
+        pattern: r"\b(?:os|shutil)\.(?:remove|unlink|rmdir|removedirs|rmtree|makedirs|mkdir|truncate|chmod|chown)\s*\(",
+        paths: &[0],
+        mode: None,
+    },
+    WriteCall {
+        interpreters: PYTHON,
+        pattern: r"\b(?:os|shutil)\.(?:rename|renames|replace|move|copy|copy2|copyfile|copytree|symlink|link)\s*\(",
+        paths: &[0, 1],
+        mode: None,
+    },
+    // Node and Bun
+    WriteCall {
+        interpreters: NODE,
+        pattern: node_fs!("writeFile|writeFileSync|appendFile|appendFileSync|rm|rmSync|unlink|unlinkSync|rmdir|rmdirSync|mkdir|mkdirSync|truncate|truncateSync|createWriteStream"),
+        paths: &[0],
+        mode: None,
+    },
+    WriteCall {
+        interpreters: NODE,
+        pattern: node_fs!("open|openSync"),
+        paths: &[0],
+        mode: Some(1),
+    },
+    WriteCall {
+        interpreters: NODE,
+        pattern: node_fs!("rename|renameSync|copyFile|copyFileSync|cp|cpSync|symlink|symlinkSync|link|linkSync"),
+        paths: &[0, 1],
+        mode: None,
+    },
+    // Ruby
+    WriteCall {
+        interpreters: RUBY,
+        pattern: r"\bFile\.(?:write|delete|unlink|truncate)\s*\(",
+        paths: &[0],
+        mode: None,
+    },
+    WriteCall {
+        interpreters: RUBY,
+        pattern: r"\bFile\.open\s*\(",
+        paths: &[0],
+        mode: Some(1),
+    },
+    WriteCall {
+        interpreters: RUBY,
+        pattern: r"\bFileUtils\.(?:rm|rm_r|rm_f|rm_rf|rmdir|mkdir|mkdir_p|touch)\s*\(",
+        paths: &[0],
+        mode: None,
+    },
+    WriteCall {
+        interpreters: RUBY,
+        pattern: r"\bFileUtils\.(?:mv|cp|cp_r|ln|ln_s)\s*\(",
+        paths: &[0, 1],
+        mode: None,
+    },
+    // Perl and PHP
+    WriteCall {
+        interpreters: PERL_PHP,
+        pattern: r"(?:^|[^\w.$>:])(?:unlink|rmdir|mkdir|file_put_contents)\s*\(",
+        paths: &[0],
+        mode: None,
+    },
+    WriteCall {
+        interpreters: PERL_PHP,
+        pattern: r"(?:^|[^\w.$>:])(?:rename|copy|symlink)\s*\(",
+        paths: &[0, 1],
+        mode: None,
+    },
+    WriteCall {
+        interpreters: PHP,
+        pattern: r"\bfopen\s*\(",
+        paths: &[0],
+        mode: Some(1),
+    },
+    WriteCall {
+        interpreters: PERL,
+        pattern: r"\bopen\s*\(\s*(?:my\s+)?[$\w]+\s*,",
+        paths: &[1],
+        mode: Some(0),
+    },
+];
+
+static WRITE_CALL_PATTERNS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    WRITE_CALLS
+        .iter()
+        .map(|call| Regex::new(call.pattern).expect("valid write call pattern"))
+        .collect()
+});
+
+/// String literals in single, double or back quotes, with an optional
+/// Python prefix (`r''`, `b''`).
+static STRING_LITERAL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:\b[rRbB]{1,2})?(?:"([^"\\\n]*)"|'([^'\\\n]*)'|`([^`\\]*)`)"#)
+        .expect("valid string literal pattern")
+});
+
+/// Inline code passed to an interpreter on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineScript {
+    /// The interpreter, without path or version (`python`, `node`).
+    pub interpreter: String,
+    pub code: String,
+}
+
+/// What an inline script does to files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptEffects {
+    /// Literal paths written, deleted, moved or created.
+    pub write_targets: Vec<String>,
+    /// Writing calls whose path or mode is computed at run time, e.g.
+    /// `shutil.rmtree(p)`, as they appear in the code.
+    pub dynamic_calls: Vec<String>,
+    /// Every other string literal that looks like a path.
+    pub other_paths: Vec<String>,
+}
+
+impl InlineScript {
+    /// The inline script a simple command runs, if it is an interpreter
+    /// given code with `-c`/`-e` and the like rather than a script file.
+    pub fn parse(command: &str) -> Option<Self> {
+        let words = split_words(command);
+        let (program, args) = words.split_first()?;
+        let interpreter = interpreter_name(program);
+        let (_, flags) = INTERPRETERS.iter().find(|(name, _)| *name == interpreter)?;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if let Some((flag, code)) = arg.split_once('=') {
+                if flag.starts_with("--") && flags.contains(&flag) {
+                    return Some(Self::new(interpreter, code));
+                }
+            }
+            if flags.contains(&arg.as_str())
+                // Combined short flags, as in `python3 -Bc '...'`.
+                || (interpreter == "python"
+                    && arg.len() > 2
+                    && arg.starts_with('-')
+                    && !arg.starts_with("--")
+                    && arg.ends_with('c'))
+            {
+                return args.next().map(|code| Self::new(interpreter, code));
+            }
+            if !arg.starts_with('-') {
+                return None; // A script file
+            }
+        }
+        None
+    }
+
+    fn new(interpreter: &str, code: &str) -> Self {
+        Self {
+            interpreter: interpreter.to_string(),
+            code: code.to_string(),
+        }
+    }
+
+    /// Whether an open mode writes: `w`, `a`, `x`, `+` (and PHP's `c`),
+    /// or Perl's `>` and `+<`.
+    fn mode_writes(&self, mode: &str) -> bool {
+        match self.interpreter.as_str() {
+            "perl" => mode.contains(['>', '+']),
+            "php" => mode.contains(['w', 'a', 'x', 'c', '+']),
+            _ => mode.contains(['w', 'a', 'x', '+']),
+        }
+    }
+
+    /// The files the script writes, as far as string literals tell.
+    pub fn effects(&self) -> ScriptEffects {
+        let mut effects = ScriptEffects::default();
+        let mut call_literals = Vec::new();
+
+        let calls = WRITE_CALLS.iter().zip(WRITE_CALL_PATTERNS.iter());
+        for (call, pattern) in
+            calls.filter(|(call, _)| call.interpreters.contains(&self.interpreter.as_str()))
+        {
+            for m in pattern.find_iter(&self.code) {
+                let (args, len) = call_args(&self.code[m.end()..]);
+                let call_text = &self.code[m.start()..m.end() + len];
+                let call_text = call_text.trim_start_matches(|c: char| !c.is_alphanumeric());
+
+                let writes = match call.mode.map(|i| args.get(i)) {
+                    None => true,
+                    Some(None) => false,
+                    Some(Some(mode)) => {
+                        string_literal(mode).is_none_or(|mode| self.mode_writes(&mode))
+                    }
+                };
+                if !writes {
+                    continue;
+                }
+                for &i in call.paths {
+                    match args.get(i).map(|arg| string_literal(arg)) {
+                        Some(Some(path)) => {
+                            call_literals.push(path.clone());
+                            effects.write_targets.push(path);
+                        }
+                        Some(None) => effects.dynamic_calls.push(call_text.to_string()),
+                        None => {}
+                    }
+                }
+            }
+        }
+
+        for caps in STRING_LITERAL.captures_iter(&self.code) {
+            let literal = (1..caps.len())
+                .filter_map(|i| caps.get(i))
+                .map(|m| m.as_str())
+                .next()
+                .unwrap_or_default();
+            if looks_like_path(literal) && !call_literals.iter().any(|p| p == literal) {
+                effects.other_paths.push(literal.to_string());
+            }
+        }
+
+        for list in [
+            &mut effects.write_targets,
+            &mut effects.dynamic_calls,
+            &mut effects.other_paths,
+        ] {
+            let mut seen = std::collections::HashSet::new();
+            list.retain(|item| seen.insert(item.clone()));
+        }
+        effects
+    }
+}
+
+/// `python` for `/usr/bin/python3.12`, `node` for `node20`.
+fn interpreter_name(program: &str) -> &str {
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    let name = name.strip_suffix(".exe").unwrap_or(name);
+    name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+}
+
+/// The top-level arguments of a call whose opening parenthesis ends just
+/// before `rest`, and the length of the argument list with its closing
+/// parenthesis.
+fn call_args(rest: &str) -> (Vec<&str>, usize) {
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (i, c) in rest.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' | '`' => quote = Some(c),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth > 0 => depth -= 1,
+            ')' => {
+                args.push(rest[start..i].trim());
+                args.retain(|arg| !arg.is_empty());
+                return (args, i + 1);
+            }
+            ',' if depth == 0 => {
+                args.push(rest[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    // Unclosed: take what there is.
+    args.push(rest[start..].trim());
+    args.retain(|arg| !arg.is_empty());
+    (args, rest.len())
+}
+
+/// The value of an argument that is exactly one plain string literal.
+/// Keyword arguments (`mode='w'`) are unwrapped; f-strings and template
+/// literals with substitutions are not plain.
+fn string_literal(arg: &str) -> Option<String> {
+    let arg = match arg.split_once('=') {
+        Some((name, value)) if name.trim().chars().all(|c| c.is_alphanumeric() || c == '_') => {
+            value.trim()
+        }
+        _ => arg,
+    };
+    let caps = STRING_LITERAL.captures(arg)?;
+    if caps.get(0)?.as_str() != arg {
+        return None;
+    }
+    let value = (1..caps.len()).find_map(|i| caps.get(i))?.as_str();
+    if arg.starts_with('`') && value.contains("${") {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+/// Whether a string literal reads as a file path rather than text.
+fn looks_like_path(literal: &str) -> bool {
+    !literal.is_empty()
+        && !literal.contains(char::is_whitespace)
+        && !literal.contains("://")
+        && (literal.contains('/') || literal.starts_with('.') || literal.starts_with('~'))
+}
@@ -0,0 +1,194 @@
+//! Path-range write locks (`locks` in policy.yml): an allowed write locks
+//! the range it lands in for the session that made it, and writes into
+//! that range from any other session get an ask until the lock expires.
+//! Serializes, e.g., a coder and a tester session editing the same files.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use globset::GlobSet;
+
+use crate::cascade::path_policy::{PathPolicyEngine, WRITE_TOOLS};
+use crate::cascade::{CascadeInput, CascadeTier};
+use crate::config::roles::{build_globset, default_categories, expand_macros};
+use crate::config::LockConfig;
+use crate::decision::{
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+};
+use crate::error::Result;
+use crate::notify::format_elapsed;
+use crate::session::state::SessionStateStore;
+
+/// Compiled `locks` settings for one session.
+pub struct PathLocks {
+    /// Each `ranges` entry with its expanded globs.
+    ranges: Vec<(String, GlobSet)>,
+    ttl: Duration,
+    store: SessionStateStore,
+    session_id: String,
+    extractor: PathPolicyEngine,
+}
+
+impl PathLocks {
+    /// Compile `locks` for `session_id`, keeping its locks in `store` and
+    /// expanding `{{category}}` macros against `categories` merged over the
+    /// built-in defaults. None when locking is disabled.
+    pub fn compile(
+        config: &LockConfig,
+        categories: &HashMap<String, Vec<String>>,
+        store: SessionStateStore,
+        session_id: &str,
+    ) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let mut merged = default_categories();
+        merged.extend(categories.clone());
+        let mut ranges = Vec::new();
+        for (i, range) in config.ranges.iter().enumerate() {
+            let patterns = expand_macros(
+                std::slice::from_ref(range),
+                &merged,
+                "policy.yml",
+                &format!("locks.ranges[{}]", i),
+            )?;
+            ranges.push((range.clone(), build_globset(&patterns)?));
+        }
+        let ttl = i64::try_from(config.ttl_secs)
+            .ok()
+            .and_then(Duration::try_seconds)
+            .unwrap_or(Duration::MAX);
+
+        Ok(Some(Self {
+            ranges,
+            ttl,
+            store,
+            session_id: session_id.to_string(),
+            extractor: PathPolicyEngine::new()?,
+        }))
+    }
+
+    /// The ranges a call writes into: for each target, the first `ranges`
+    /// entry matching any of its forms, or else the target itself.
+    pub fn write_ranges(&self, input: &CascadeInput) -> Vec<String> {
+        if !WRITE_TOOLS.contains(&input.tool_name.as_str()) {
+            return Vec::new();
+        }
+        let mut ranges: Vec<String> = Vec::new();
+        for forms in self.extractor.target_path_groups(input) {
+            let range = self
+                .ranges
+                .iter()
+                .find(|(_, globs)| forms.iter().any(|p| globs.is_match(p)))
+                .map(|(range, _)| range.clone())
+                .or_else(|| forms.into_iter().next());
+            if let Some(range) = range {
+                if !ranges.contains(&range) {
+                    ranges.push(range);
+                }
+            }
+        }
+        ranges
+    }
+
+    /// Take or renew the session's locks on the ranges an allowed call
+    /// writes into, as of `now`.
+    pub fn acquire(&self, input: &CascadeInput, now: DateTime<Utc>) -> Result<()> {
+        let ranges = self.write_ranges(input);
+        if ranges.is_empty() {
+            return Ok(());
+        }
+        let role = input.session.role.as_ref().map(|r| r.name.as_str());
+        let expires_at = now
+            .checked_add_signed(self.ttl)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        self.store.update(&self.session_id, |state| {
+            state.record_locks(&ranges, role, now, expires_at);
+        })?;
+        Ok(())
+    }
+
+    /// The reason a call made at `now` must wait, naming the first range
+    /// another session holds, or None when every range is free.
+    pub fn conflict(&self, input: &CascadeInput, now: DateTime<Utc>) -> Result<Option<String>> {
+        let ranges = self.write_ranges(input);
+        if ranges.is_empty() {
+            return Ok(None);
+        }
+        let mut states: Vec<_> = self
+            .store
+            .load_all()?
+            .into_iter()
+            .filter(|(id, _)| *id != self.session_id)
+            .collect();
+        states.sort_by(|a, b| a.0.cmp(&b.0));
+        for range in &ranges {
+            for (id, state) in &states {
+                if let Some(lock) = state.lock_on(range, now) {
+                    let holder = match &lock.role {
+                        Some(role) => format!("session {} ({})", id, role),
+                        None => format!("session {}", id),
+                    };
+                    return Ok(Some(format!(
+                        "'{}' locked by {} for another {}",
+                        range,
+                        holder,
+                        format_elapsed(lock.expires_at.signed_duration_since(now))
+                    )));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl CascadeTier for PathLocks {
+    async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
+        let now = Utc::now();
+        let Some(reason) = self.conflict(input, now)? else {
+            return Ok(None);
+        };
+
+        let role_name = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| "*".to_string());
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: role_name,
+            },
+            decision: Decision::Ask,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::PathLock,
+                confidence: 1.0,
+                reason,
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: now,
+            scope: ScopeLevel::Project,
+            file_path: input.file_path.clone(),
+            session_id: String::new(), // Filled by CascadeRunner
+        }))
+    }
+
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::PathLock
+    }
+
+    fn name(&self) -> &str {
+        "path_lock"
+    }
+}
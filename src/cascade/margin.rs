@@ -0,0 +1,50 @@
+//! Margin-based matching against negative examples for the similarity
+//! tiers: a cached allow/ask only resolves when it beats the closest cached
+//! deny by a clear margin.
+
+use crate::config::{DenyConflict, SimilarityConfig};
+
+/// Outcome of weighing the best positive match against the best deny.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarginVerdict {
+    /// The positive match clears the margin; use its decision.
+    Resolve,
+    /// A deny is too close; defer to later tiers.
+    FallThrough,
+    /// A deny is too close; ask a human.
+    Ask,
+}
+
+/// Required lead of the best allow/ask match over the best deny match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DenyMargin {
+    pub margin: f64,
+    pub on_conflict: DenyConflict,
+}
+
+impl Default for DenyMargin {
+    fn default() -> Self {
+        Self::from_config(&SimilarityConfig::default())
+    }
+}
+
+impl DenyMargin {
+    pub fn from_config(config: &SimilarityConfig) -> Self {
+        Self {
+            margin: config.deny_margin,
+            on_conflict: config.on_deny_conflict,
+        }
+    }
+
+    /// Weigh `best` (a positive match already above the tier threshold)
+    /// against the closest deny, if any.
+    pub fn verdict(&self, best: f64, best_deny: Option<f64>) -> MarginVerdict {
+        match best_deny {
+            Some(deny) if best - deny < self.margin => match self.on_conflict {
+                DenyConflict::Fallthrough => MarginVerdict::FallThrough,
+                DenyConflict::Ask => MarginVerdict::Ask,
+            },
+            _ => MarginVerdict::Resolve,
+        }
+    }
+}
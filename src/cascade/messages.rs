@@ -0,0 +1,136 @@
+//! Policy authors' deny/ask messages (`messages` in policy.yml), rendered
+//! into hook output, queue entries and notifications.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use globset::GlobSet;
+
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::CascadeInput;
+use crate::config::roles::{build_globset, default_categories, expand_macros};
+use crate::config::MessageRule;
+use crate::decision::Decision;
+use crate::error::{HookwiseError, Result};
+
+/// Variables a message template may use.
+pub const TEMPLATE_VARIABLES: &[&str] = &["path", "tool", "role", "decision", "reason"];
+
+/// Compiled `messages` rules.
+pub struct MessageTemplates {
+    rules: Vec<(GlobSet, MessageRule)>,
+    extractor: PathPolicyEngine,
+}
+
+impl MessageTemplates {
+    /// Compile `messages`, expanding `{{category}}` macros against
+    /// `categories` merged over the built-in defaults and rejecting unknown
+    /// template variables. None when empty.
+    pub fn compile(
+        config: &[MessageRule],
+        categories: &HashMap<String, Vec<String>>,
+    ) -> Result<Option<Self>> {
+        if config.is_empty() {
+            return Ok(None);
+        }
+        let mut merged = default_categories();
+        merged.extend(categories.clone());
+
+        let mut rules = Vec::new();
+        for (i, rule) in config.iter().enumerate() {
+            let context = format!("messages[{}]", i);
+            for template in [&rule.message, &rule.deny, &rule.ask].into_iter().flatten() {
+                check_template(template, &context)?;
+            }
+            let patterns = expand_macros(&rule.paths, &merged, "policy.yml", &context)?;
+            rules.push((build_globset(&patterns)?, rule.clone()));
+        }
+        Ok(Some(Self {
+            rules,
+            extractor: PathPolicyEngine::new()?,
+        }))
+    }
+
+    /// The message for `decision` on `input` from the first rule matching a
+    /// path it touches, or None if no rule matches or the rule has no
+    /// template for `decision`.
+    pub fn render(&self, input: &CascadeInput, decision: Decision, reason: &str) -> Option<String> {
+        let paths = self.extractor.target_paths(input);
+        let (path, template) = self.rules.iter().find_map(|(globs, rule)| {
+            let path = paths.iter().find(|p| globs.is_match(p))?;
+            Some((path, rule.template(decision)?))
+        })?;
+        let role = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.as_str())
+            .unwrap_or("*");
+        let decision = decision.to_string();
+        Some(render_template(
+            template,
+            &[
+                ("path", path),
+                ("tool", &input.tool_name),
+                ("role", role),
+                ("decision", &decision),
+                ("reason", reason),
+            ],
+        ))
+    }
+}
+
+/// Replace each `{{name}}` in `template` with its value in `vars`. Unknown
+/// names are left as written.
+pub fn render_template(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some((before, name, after)) = next_variable(rest) {
+        rendered.push_str(before);
+        match vars.iter().find(|(n, _)| *n == name) {
+            Some((_, value)) => rendered.push_str(value),
+            None => rendered.push_str(&format!("{{{{{}}}}}", name)),
+        }
+        rest = after;
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Reject a template using variables outside `TEMPLATE_VARIABLES`.
+/// `context` (e.g. `messages[0]`) locates it in policy.yml.
+pub(crate) fn check_template(template: &str, context: &str) -> Result<()> {
+    match template_variables(template)
+        .into_iter()
+        .find(|name| !TEMPLATE_VARIABLES.contains(name))
+    {
+        Some(unknown) => Err(HookwiseError::ConfigParse {
+            path: PathBuf::from("policy.yml"),
+            reason: format!(
+                "{}: unknown template variable '{{{{{}}}}}'. Available: {:?}",
+                context, unknown, TEMPLATE_VARIABLES
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The `{{name}}` variables a template uses.
+fn template_variables(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some((_, name, after)) = next_variable(rest) {
+        names.push(name);
+        rest = after;
+    }
+    names
+}
+
+/// The text before the first `{{name}}` in `text`, the name, and the text
+/// after it.
+fn next_variable(text: &str) -> Option<(&str, &str, &str)> {
+    let start = text.find("{{")?;
+    let after = &text[start + 2..];
+    let end = after.find("}}")?;
+    Some((&text[..start], &after[..end], &after[end + 2..]))
+}
@@ -1,19 +1,42 @@
+pub mod attachment;
 pub mod cache;
+pub mod cloud;
+pub mod command_risk;
+pub mod container;
+pub mod content;
+pub mod deadline;
 pub mod embed_sim;
+pub mod ephemeral;
+pub mod exfil;
+pub mod git;
+pub mod hints;
 pub mod human;
+pub mod inline_script;
+pub mod locks;
+pub mod margin;
+pub mod messages;
+pub mod packages;
 pub mod path_policy;
+pub mod precedents;
+pub mod risk;
+pub mod shell;
 pub mod supervisor;
+pub mod tier_cap;
 pub mod token_sim;
+pub mod transcript;
 
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use chrono::Utc;
 
+use crate::cascade::human::SupervisorRecommendation;
+use crate::config::SessionMode;
 use crate::decision::{
-    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel, SecretFinding,
 };
 use crate::error::Result;
+use crate::sanitize::SanitizePipeline;
 use crate::session::SessionContext;
 
 /// Input to each cascade tier.
@@ -26,6 +49,34 @@ pub struct CascadeInput {
     pub file_path: Option<String>,
     /// The working directory of the tool call, used to relativize absolute paths.
     pub cwd: Option<String>,
+    /// What the supervisor answered when it wasn't confident enough to
+    /// decide; the human tier shows it and prioritizes by it.
+    pub recommendation: Option<SupervisorRecommendation>,
+}
+
+/// The input a tool call is cached under: the serialized tool input with
+/// secrets redacted and volatile tokens normalized, so retries hit the
+/// cache. Also returns what was redacted.
+pub fn cache_input(
+    sanitizer: &SanitizePipeline,
+    tool_input: &serde_json::Value,
+) -> (String, Vec<SecretFinding>) {
+    let raw_input = serde_json::to_string(tool_input).unwrap_or_default();
+    let (sanitized, secrets) = sanitizer.sanitize_with_findings(&raw_input);
+    (sanitizer.normalize(&sanitized), secrets)
+}
+
+/// Similarity index partition key: `(role, tool)`.
+pub type PartitionKey = (String, String);
+
+/// The similarity partitions a call may match: its own `(role, tool)` and
+/// the wildcard-role partition for the same tool.
+pub fn partition_keys(role: &str, tool: &str) -> Vec<PartitionKey> {
+    let mut keys = vec![(role.to_string(), tool.to_string())];
+    if role != "*" {
+        keys.push(("*".to_string(), tool.to_string()));
+    }
+    keys
 }
 
 /// A single tier in the decision cascade.
@@ -35,6 +86,16 @@ pub trait CascadeTier: Send + Sync {
     /// None if it should fall through to the next tier.
     async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>>;
 
+    /// Like [`evaluate`](Self::evaluate), but also returns an answer the
+    /// tier fell through with, which the runner passes on to later tiers as
+    /// [`CascadeInput::recommendation`]. Only the supervisor has one.
+    async fn consult(
+        &self,
+        input: &CascadeInput,
+    ) -> Result<(Option<DecisionRecord>, Option<SupervisorRecommendation>)> {
+        Ok((self.evaluate(input).await?, None))
+    }
+
     /// The tier identifier.
     fn tier(&self) -> DecisionTier;
 
@@ -45,8 +106,27 @@ pub trait CascadeTier: Send + Sync {
 /// The complete cascade runner. Evaluates tiers in order until one resolves.
 pub struct CascadeRunner {
     pub sanitizer: crate::sanitize::SanitizePipeline,
+    /// Scratch and build output paths allowed before every other tier.
+    pub ephemeral: Option<ephemeral::EphemeralPaths>,
+    /// Write/Edit payload checks; run first when configured so an allowed
+    /// path can't carry flagged content in.
+    pub content_inspection: Option<content::ContentInspection>,
+    /// Cross-call checks for network commands after sensitive reads;
+    /// runs ahead of path policy so every read is recorded.
+    pub exfiltration: Option<exfil::ExfiltrationGuard>,
+    /// Path-range write locks held by other sessions; taken on allowed
+    /// writes when configured.
+    pub locks: Option<locks::PathLocks>,
     pub path_policy: Box<dyn CascadeTier>,
+    /// Package install gating; runs right after path policy when configured.
+    pub package_gate: Option<packages::PackageGate>,
+    /// Cloud CLI action rules; run after the package gate when configured.
+    pub cloud_cli: Option<cloud::CloudPolicy>,
+    /// Git operation rules; run after the cloud CLI rules when configured.
+    pub git_policy: Option<git::GitPolicy>,
     pub exact_cache: Arc<cache::ExactCache>,
+    /// Commands whose cache keys carry the HEAD commit.
+    pub commit_keys: Option<cache::CommitKeys>,
     pub token_jaccard: Arc<token_sim::TokenJaccard>,
     pub embedding_similarity: Arc<embed_sim::EmbeddingSimilarity>,
     pub supervisor: Box<dyn CascadeTier>,
@@ -55,9 +135,33 @@ pub struct CascadeRunner {
     pub policy: crate::config::PolicyConfig,
     /// Normalizes file paths to `category:relative` form for portable storage.
     pub normalizer: Option<crate::config::roles::PathNormalizer>,
+    /// Ceilings on which tiers may decide calls touching certain paths.
+    pub tier_caps: Option<tier_cap::TierCaps>,
+    /// The session's rolling risk score; over the threshold, fewer tiers
+    /// may decide.
+    pub risk: Option<risk::RiskTracker>,
+    /// Policy authors' deny/ask messages, attached to returned records.
+    pub messages: Option<Arc<messages::MessageTemplates>>,
+    /// Remediation hints, attached to returned denies and asks.
+    pub hints: Option<Arc<hints::HintTable>>,
+    /// Built-in command risk score, recorded on every decision.
+    pub command_risk: Option<Arc<command_risk::CommandRiskScorer>>,
+    /// Time source for risk decay and cascade-made records; the wall clock
+    /// when unset.
+    pub clock: Option<Arc<dyn crate::clock::Clock>>,
+    /// The assistant the evaluated call came from, recorded on every
+    /// decision.
+    pub client: Option<crate::decision::ClientInfo>,
 }
 
 impl CascadeRunner {
+    /// The current time, from the injected clock if any.
+    fn now(&self) -> chrono::DateTime<Utc> {
+        self.clock
+            .as_ref()
+            .map_or_else(Utc::now, |clock| clock.now())
+    }
+
     /// Run the full cascade for a tool call.
     pub async fn evaluate(
         &self,
@@ -70,6 +174,12 @@ impl CascadeRunner {
     }
 
     /// Run the full cascade for a tool call, with an optional cwd for path relativization.
+    ///
+    /// For an audit-mode session the returned record is what would have
+    /// been decided: the human tier is replaced by an unqueued ask and
+    /// nothing is cached or persisted. With `deadline.secs` set, a cascade
+    /// still running at the deadline is abandoned for a
+    /// [`DecisionTier::Deadline`] record.
     pub async fn evaluate_with_cwd(
         &self,
         session: &SessionContext,
@@ -77,35 +187,154 @@ impl CascadeRunner {
         tool_input: &serde_json::Value,
         cwd: Option<&str>,
     ) -> Result<DecisionRecord> {
+        let started = std::time::Instant::now();
+        let cascade = self.run_cascade(session, tool_name, tool_input, cwd, started);
+        let Some(deadline) = self.policy.deadline.duration() else {
+            return cascade.await;
+        };
+        match tokio::time::timeout(deadline, cascade).await {
+            Ok(result) => result,
+            Err(_) => Ok(self.deadline_record(session, tool_name, tool_input, started)),
+        }
+    }
+
+    /// The tiers of [`Self::evaluate_with_cwd`], without the deadline.
+    async fn run_cascade(
+        &self,
+        session: &SessionContext,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+        cwd: Option<&str>,
+        started: std::time::Instant,
+    ) -> Result<DecisionRecord> {
+        let audit = session.mode == SessionMode::Audit;
+
         // Sanitize the tool input
-        let raw_input = serde_json::to_string(tool_input).unwrap_or_default();
-        let sanitized_input = self.sanitizer.sanitize(&raw_input);
+        let (sanitized_input, secrets) = cache_input(&self.sanitizer, tool_input);
+        crate::telemetry::record_secret_findings(&secrets, tool_name);
 
         // Extract file path from tool input
         let file_path = Self::extract_file_path(tool_name, tool_input);
 
-        let input = CascadeInput {
+        let mut input = CascadeInput {
             session: session.clone(),
             tool_name: tool_name.to_string(),
             tool_input: tool_input.clone(),
             sanitized_input,
             file_path,
             cwd: cwd.map(String::from),
+            recommendation: None,
+        };
+        if !session.fallback_roles.is_empty() {
+            input.session = self.select_role(&input).await?;
+        }
+        // A commit-keyed command only matches decisions made at the same
+        // HEAD; similarity would find the same command at other commits.
+        let commit_keyed = match self.commit_keys.as_ref().and_then(|k| k.salt(&input)) {
+            Some(salted) => {
+                input.sanitized_input = salted;
+                true
+            }
+            None => false,
         };
+        let risk_score = self
+            .command_risk
+            .as_ref()
+            .map(|scorer| scorer.score(&input).score);
 
-        // Run tiers in order: path_policy -> exact_cache -> token_jaccard ->
+        // Run tiers in order: ephemeral -> content_inspection ->
+        // exfiltration -> locks -> path_policy -> package_gate -> cloud_cli ->
+        // git_policy -> exact_cache -> token_jaccard ->
         // embedding_similarity -> supervisor -> human
-        let tiers: Vec<&dyn CascadeTier> = vec![
-            self.path_policy.as_ref(),
-            self.exact_cache.as_ref(),
+        let mut tiers: Vec<&dyn CascadeTier> = Vec::new();
+        if let Some(ephemeral) = &self.ephemeral {
+            tiers.push(ephemeral);
+        }
+        if let Some(content) = &self.content_inspection {
+            tiers.push(content);
+        }
+        if let Some(exfiltration) = &self.exfiltration {
+            tiers.push(exfiltration);
+        }
+        if let Some(locks) = &self.locks {
+            tiers.push(locks);
+        }
+        tiers.push(self.path_policy.as_ref());
+        if let Some(gate) = &self.package_gate {
+            tiers.push(gate);
+        }
+        if let Some(cloud) = &self.cloud_cli {
+            tiers.push(cloud);
+        }
+        if let Some(git) = &self.git_policy {
+            tiers.push(git);
+        }
+        tiers.extend([
+            self.exact_cache.as_ref() as &dyn CascadeTier,
             self.token_jaccard.as_ref(),
             self.embedding_similarity.as_ref(),
             self.supervisor.as_ref(),
-            self.human.as_ref(),
-        ];
+        ]);
+        if !audit {
+            tiers.push(self.human.as_ref());
+        }
+
+        let cap = self
+            .tier_caps
+            .as_ref()
+            .and_then(|caps| caps.cap_for(&input));
+        let tightened = match &self.risk {
+            Some(risk) => risk.exceeded(&risk.current(self.now())?),
+            None => false,
+        };
+        let permits = |tier: DecisionTier| {
+            cap.is_none_or(|cap| cap.permits(tier))
+                && (!tightened || self.risk.as_ref().is_none_or(|risk| risk.permits(tier)))
+        };
 
         for tier in &tiers {
-            if let Some(mut record) = tier.evaluate(&input).await? {
+            if commit_keyed
+                && matches!(
+                    tier.tier(),
+                    DecisionTier::TokenJaccard | DecisionTier::EmbeddingSimilarity
+                )
+            {
+                continue;
+            }
+            if cap.is_some() || tightened {
+                // A cached decision counts as the tier that made it.
+                let origin = match tier.tier() {
+                    DecisionTier::ExactCache => self.exact_cache.origin(&input),
+                    other => Some(other),
+                };
+                if origin.is_some_and(|t| !permits(t)) {
+                    continue;
+                }
+            }
+            let (resolved, recommendation) = tier.consult(&input).await?;
+            if recommendation.is_some() {
+                input.recommendation = recommendation;
+            }
+            if let Some(mut record) = resolved {
+                // Content inspection, exfiltration and locks only tighten:
+                // a path policy deny still beats their ask.
+                if matches!(
+                    record.metadata.tier,
+                    DecisionTier::ContentInspection
+                        | DecisionTier::Exfiltration
+                        | DecisionTier::PathLock
+                ) && record.decision == Decision::Ask
+                {
+                    if let Some(denied) = self
+                        .path_policy
+                        .evaluate(&input)
+                        .await?
+                        .filter(|r| r.decision == Decision::Deny)
+                    {
+                        record = denied;
+                    }
+                }
+
                 // Fill in session_id on all records
                 if record.session_id.is_empty() {
                     // Use a session identifier from the context
@@ -115,14 +344,34 @@ impl CascadeRunner {
                     );
                 }
 
+                // Record what was redacted from this call, not the cached one
+                record.metadata.secrets = secrets.clone();
+                record.metadata.risk_score = risk_score;
+                record.metadata.client = self.client.clone();
+
                 // Normalize file_path to category:relative form for portable storage
                 self.normalize_record(&mut record);
 
                 // Persist decisions from tiers that produce new decisions
                 match record.metadata.tier {
+                    _ if audit => {
+                        // Unenforced decisions don't become precedents
+                    }
                     DecisionTier::ExactCache => {
                         // Already in exact cache -- no need to persist again
                     }
+                    DecisionTier::EphemeralPath
+                    | DecisionTier::PackageGate
+                    | DecisionTier::CloudCli
+                    | DecisionTier::GitPolicy
+                    | DecisionTier::ContentInspection
+                    | DecisionTier::Exfiltration
+                    | DecisionTier::PathLock => {
+                        // Re-checked on every call: the allowlist, advisories,
+                        // cloud, git and content rules, the current branch,
+                        // the session's reads and other sessions' locks
+                        // change, so the result isn't cached.
+                    }
                     DecisionTier::TokenJaccard | DecisionTier::EmbeddingSimilarity => {
                         // Similarity tiers: insert into exact cache to prevent
                         // "ask drift" where repeated similar commands might match
@@ -135,12 +384,34 @@ impl CascadeRunner {
                     }
                 }
 
+                self.record_risk(&input, &record);
+                if !audit {
+                    self.acquire_locks(&input, &record);
+                }
+                crate::telemetry::record_decision(record.metadata.tier, record.decision);
+                crate::telemetry::record_cascade_latency(started.elapsed(), record.metadata.tier);
+                self.attach_message(&input, &mut record);
                 return Ok(record);
             }
         }
 
-        // If no tier resolved, default to deny (timeout defaults to deny)
-        let role_name = session
+        // If no tier resolved, default to deny (timeout defaults to deny).
+        // Audit mode never reached the human tier: it would have asked.
+        let (decision, tier, reason) = if audit {
+            (
+                Decision::Ask,
+                DecisionTier::Human,
+                "no automatic tier resolved; would ask a human",
+            )
+        } else {
+            (
+                Decision::Deny,
+                DecisionTier::Default,
+                "no cascade tier resolved; default deny",
+            )
+        };
+        let role_name = input
+            .session
             .role
             .as_ref()
             .map(|r| r.name.clone())
@@ -148,33 +419,148 @@ impl CascadeRunner {
 
         let mut record = DecisionRecord {
             key: CacheKey {
-                sanitized_input: input.sanitized_input,
+                sanitized_input: input.sanitized_input.clone(),
                 tool: tool_name.to_string(),
                 role: role_name,
             },
-            decision: Decision::Deny,
+            decision,
             metadata: DecisionMetadata {
-                tier: DecisionTier::Default,
+                tier,
                 confidence: 1.0,
-                reason: "no cascade tier resolved; default deny".to_string(),
+                reason: reason.to_string(),
                 matched_key: None,
                 similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score,
+                secrets,
+                client: self.client.clone(),
             },
-            timestamp: Utc::now(),
+            timestamp: self.now(),
             scope: ScopeLevel::Project,
-            file_path: input.file_path,
+            file_path: input.file_path.clone(),
             session_id: format!("{}/{}/{}", session.org, session.project, session.user),
         };
 
         self.normalize_record(&mut record);
-        self.persist_decision(&record).await?;
+        if !audit {
+            self.persist_decision(&record).await?;
+        }
+        self.record_risk(&input, &record);
+        crate::telemetry::record_decision(record.metadata.tier, record.decision);
+        crate::telemetry::record_cascade_latency(started.elapsed(), record.metadata.tier);
+        self.attach_message(&input, &mut record);
         Ok(record)
     }
 
+    /// The session acting as the first of its roles whose path policy
+    /// doesn't deny the call, else as its primary role. Every tier, and
+    /// the cache key of the decision, then sees that role.
+    async fn select_role(&self, input: &CascadeInput) -> Result<SessionContext> {
+        let permits =
+            |record: Option<DecisionRecord>| record.is_none_or(|r| r.decision != Decision::Deny);
+        if permits(self.path_policy.evaluate(input).await?) {
+            return Ok(input.session.clone());
+        }
+        for (role, policy) in &input.session.fallback_roles {
+            let mut candidate = input.clone();
+            candidate.session.role = Some(role.clone());
+            candidate.session.path_policy = Some(policy.clone());
+            if permits(self.path_policy.evaluate(&candidate).await?) {
+                return Ok(candidate.session);
+            }
+        }
+        Ok(input.session.clone())
+    }
+
+    /// The `deadline.decision` for a call whose cascade ran out of time.
+    /// Never persisted: running slow once says nothing about the call.
+    fn deadline_record(
+        &self,
+        session: &SessionContext,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+        started: std::time::Instant,
+    ) -> DecisionRecord {
+        let (sanitized_input, secrets) = cache_input(&self.sanitizer, tool_input);
+        let reason = format!(
+            "DeadlineExceeded: evaluation ran past the {}s deadline",
+            self.policy.deadline.secs.unwrap_or_default()
+        );
+        let mut record = DecisionRecord {
+            key: CacheKey {
+                sanitized_input,
+                tool: tool_name.to_string(),
+                role: session
+                    .role
+                    .as_ref()
+                    .map(|r| r.name.clone())
+                    .unwrap_or_else(|| "*".to_string()),
+            },
+            decision: self.policy.deadline.decision,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::Deadline,
+                confidence: 1.0,
+                message: Some(reason.clone()),
+                reason,
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                suggestion: None,
+                risk_score: None,
+                secrets,
+                client: self.client.clone(),
+            },
+            timestamp: self.now(),
+            scope: ScopeLevel::Project,
+            file_path: Self::extract_file_path(tool_name, tool_input),
+            session_id: format!("{}/{}/{}", session.org, session.project, session.user),
+        };
+        self.normalize_record(&mut record);
+        crate::telemetry::record_decision(record.metadata.tier, record.decision);
+        crate::telemetry::record_cascade_latency(started.elapsed(), record.metadata.tier);
+        record
+    }
+
+    /// Add a decided call to the session's risk score. A failure only
+    /// loses the event, so it doesn't fail the call.
+    fn record_risk(&self, input: &CascadeInput, record: &DecisionRecord) {
+        if let Some(risk) = &self.risk {
+            if let Err(e) = risk.record(input, record) {
+                eprintln!("hookwise: session risk update failed: {}", e);
+            }
+        }
+    }
+
+    /// Lock the ranges an allowed write lands in for the session. A failure
+    /// only leaves them unlocked, so it doesn't fail the call.
+    fn acquire_locks(&self, input: &CascadeInput, record: &DecisionRecord) {
+        if let (Some(locks), Decision::Allow) = (&self.locks, record.decision) {
+            if let Err(e) = locks.acquire(input, self.now()) {
+                eprintln!("hookwise: path lock update failed: {}", e);
+            }
+        }
+    }
+
+    /// Render the policy author's message and the remediation hint for a
+    /// deny or ask. Done after persisting, so stored decisions never carry
+    /// a stale message.
+    fn attach_message(&self, input: &CascadeInput, record: &mut DecisionRecord) {
+        if let Some(messages) = &self.messages {
+            record.metadata.message =
+                messages.render(input, record.decision, &record.metadata.reason);
+        }
+        if let Some(hints) = &self.hints {
+            record.metadata.suggestion =
+                hints.suggest(input, record.decision, &record.metadata.reason);
+        }
+    }
+
     /// Extract file path from tool input for file-related tools.
-    fn extract_file_path(tool_name: &str, tool_input: &serde_json::Value) -> Option<String> {
+    pub fn extract_file_path(tool_name: &str, tool_input: &serde_json::Value) -> Option<String> {
         match tool_name {
-            "Write" | "Edit" | "Read" => tool_input
+            "Write" | "Edit" | "MultiEdit" | "Read" => tool_input
                 .get("file_path")
                 .and_then(|v| v.as_str())
                 .map(String::from),
@@ -215,7 +601,7 @@ impl CascadeRunner {
         self.token_jaccard.insert(record);
 
         // 4. Update embedding similarity index (may fail if model not loaded)
-        if let Err(e) = self.embedding_similarity.insert(record) {
+        if let Err(e) = self.embedding_similarity.insert(record).await {
             // Log but don't fail -- embedding index is optional
             eprintln!("hookwise: embedding index update failed: {}", e);
         }
@@ -0,0 +1,598 @@
+//! Package install gating (`packages` in policy.yml): finds `npm install`,
+//! `pip install`, `cargo add`, `gem install` and `go get` in Bash commands
+//! and checks the packages and registries they name.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+use crate::cascade::shell::{split_commands, split_words};
+use crate::cascade::{CascadeInput, CascadeTier};
+use crate::config::{Ecosystem, OsvConfig, PackageGateConfig};
+use crate::decision::{
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+};
+use crate::error::{HookwiseError, Result};
+
+/// A package named on an install command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageRef {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// One install command and what it would fetch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageInstall {
+    pub ecosystem: Ecosystem,
+    pub packages: Vec<PackageRef>,
+    /// Registries and URLs named on the command line (`--registry`,
+    /// `--index-url`, `--source`, `--git`, direct URLs). The ecosystem's
+    /// configured default is not included.
+    pub registries: Vec<String>,
+    /// Package sources that can't be inspected, such as `-r requirements.txt`.
+    pub unresolved: Vec<String>,
+}
+
+/// Flags that take a value, per ecosystem. Registry flags are listed
+/// separately in `REGISTRY_FLAGS`.
+const VALUE_FLAGS: &[(Ecosystem, &[&str])] = &[
+    (
+        Ecosystem::Npm,
+        &[
+            "--tag",
+            "--prefix",
+            "-w",
+            "--workspace",
+            "--omit",
+            "--include",
+        ],
+    ),
+    (
+        Ecosystem::Pip,
+        &[
+            "-t",
+            "--target",
+            "--prefix",
+            "--root",
+            "--platform",
+            "--python-version",
+            "--implementation",
+            "--abi",
+            "--src",
+            "--upgrade-strategy",
+            "--progress-bar",
+            "--log",
+            "--cache-dir",
+            "--trusted-host",
+            "--only-binary",
+            "--no-binary",
+        ],
+    ),
+    (
+        Ecosystem::Cargo,
+        &[
+            "-F",
+            "--features",
+            "--rename",
+            "-p",
+            "--package",
+            "--manifest-path",
+            "--branch",
+            "--tag",
+            "--rev",
+            "--target",
+            "--root",
+            "-j",
+            "--jobs",
+            "--profile",
+            "--bin",
+            "--example",
+            "--config",
+        ],
+    ),
+    (
+        Ecosystem::Gem,
+        &["-i", "--install-dir", "-n", "--bindir", "--platform"],
+    ),
+    (Ecosystem::Go, &["-modfile"]),
+];
+
+/// Flags whose value is a registry or source URL.
+const REGISTRY_FLAGS: &[(Ecosystem, &[&str])] = &[
+    (Ecosystem::Npm, &["--registry"]),
+    (
+        Ecosystem::Pip,
+        &[
+            "-i",
+            "--index-url",
+            "--extra-index-url",
+            "-f",
+            "--find-links",
+        ],
+    ),
+    (Ecosystem::Cargo, &["--registry", "--index", "--git"]),
+    (Ecosystem::Gem, &["-s", "--source"]),
+    (Ecosystem::Go, &[]),
+];
+
+/// Flags whose value lists more packages in a file.
+const FILE_FLAGS: &[(Ecosystem, &[&str])] = &[
+    (
+        Ecosystem::Pip,
+        &["-r", "--requirement", "-c", "--constraint"],
+    ),
+    (Ecosystem::Gem, &["-g", "--file"]),
+];
+
+/// Flags whose value is a version.
+const VERSION_FLAGS: &[(Ecosystem, &[&str])] = &[
+    (Ecosystem::Cargo, &["--version", "--vers"]),
+    (Ecosystem::Gem, &["-v", "--version"]),
+];
+
+/// Flags whose value is a local path: the install needs no registry.
+const LOCAL_FLAGS: &[(Ecosystem, &[&str])] = &[
+    (Ecosystem::Pip, &["-e", "--editable"]),
+    (Ecosystem::Cargo, &["--path"]),
+];
+
+fn flags(
+    table: &[(Ecosystem, &'static [&'static str])],
+    ecosystem: Ecosystem,
+) -> &'static [&'static str] {
+    table
+        .iter()
+        .find(|(e, _)| *e == ecosystem)
+        .map(|(_, flags)| *flags)
+        .unwrap_or(&[])
+}
+
+/// Every install in a (possibly compound) Bash command.
+pub fn package_installs(command: &str) -> Vec<PackageInstall> {
+    split_commands(command)
+        .iter()
+        .filter_map(|sub_command| PackageInstall::parse(sub_command))
+        .collect()
+}
+
+impl PackageInstall {
+    /// Parse one simple command, or None if it doesn't install packages.
+    pub fn parse(command: &str) -> Option<Self> {
+        let words = split_words(command);
+        let (ecosystem, args) = install_args(&words)?;
+
+        let mut install = Self {
+            ecosystem,
+            packages: Vec::new(),
+            registries: Vec::new(),
+            unresolved: Vec::new(),
+        };
+        let mut version = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                continue;
+            }
+            if arg.starts_with('-') {
+                let (flag, inline) = match arg.split_once('=') {
+                    Some((flag, value)) => (flag, Some(value.to_string())),
+                    None => (arg.as_str(), None),
+                };
+                let takes_value = [
+                    VALUE_FLAGS,
+                    REGISTRY_FLAGS,
+                    FILE_FLAGS,
+                    VERSION_FLAGS,
+                    LOCAL_FLAGS,
+                ]
+                .iter()
+                .any(|table| flags(table, ecosystem).contains(&flag));
+                if !takes_value {
+                    continue;
+                }
+                let Some(value) = inline.or_else(|| args.next().cloned()) else {
+                    break;
+                };
+                if flags(REGISTRY_FLAGS, ecosystem).contains(&flag) {
+                    install.registries.push(value);
+                } else if flags(FILE_FLAGS, ecosystem).contains(&flag) {
+                    install.unresolved.push(format!("{} {}", flag, value));
+                } else if flags(VERSION_FLAGS, ecosystem).contains(&flag) {
+                    version = Some(value);
+                }
+                continue;
+            }
+            if arg.contains("://") || arg.starts_with("git@") {
+                install.registries.push(arg.clone());
+            } else if !is_local(arg) {
+                install.packages.push(package_ref(ecosystem, arg));
+            }
+        }
+        if let (Some(version), [package]) = (version, install.packages.as_mut_slice()) {
+            package.version.get_or_insert(version);
+        }
+        Some(install)
+    }
+}
+
+/// The ecosystem and the arguments after the install subcommand, or None
+/// if `words` is not an install.
+fn install_args(words: &[String]) -> Option<(Ecosystem, &[String])> {
+    let program = words.first()?.rsplit('/').next()?;
+    let is_pip = |name: &str| {
+        name == "pip"
+            || name
+                .strip_prefix("pip")
+                .is_some_and(|v| v.chars().all(|c| c.is_ascii_digit() || c == '.'))
+    };
+    let (ecosystem, subcommands, rest): (Ecosystem, &[&str], &[String]) = match program {
+        "npm" => (Ecosystem::Npm, &["install", "i", "add"], &words[1..]),
+        "yarn" => (Ecosystem::Npm, &["add"], &words[1..]),
+        "pnpm" | "bun" => (Ecosystem::Npm, &["add", "install", "i"], &words[1..]),
+        "cargo" => (Ecosystem::Cargo, &["add", "install"], &words[1..]),
+        "gem" => (Ecosystem::Gem, &["install"], &words[1..]),
+        "go" => (Ecosystem::Go, &["get", "install"], &words[1..]),
+        "uv" if words.get(1).is_some_and(|w| w == "pip") => {
+            (Ecosystem::Pip, &["install"], &words[2..])
+        }
+        name if is_pip(name) => (Ecosystem::Pip, &["install"], &words[1..]),
+        name if name.starts_with("python") => {
+            let m = words.iter().position(|w| w == "-m")?;
+            if !words.get(m + 1).is_some_and(|w| is_pip(w)) {
+                return None;
+            }
+            (Ecosystem::Pip, &["install"], &words[m + 2..])
+        }
+        _ => return None,
+    };
+    let (subcommand, args) = rest.split_first()?;
+    subcommands
+        .contains(&subcommand.as_str())
+        .then_some((ecosystem, args))
+}
+
+/// A local path or archive rather than a registry package.
+fn is_local(arg: &str) -> bool {
+    arg.starts_with(['.', '/', '~'])
+        || arg.starts_with("file:")
+        || [".tgz", ".tar.gz", ".whl", ".zip", ".gem"]
+            .iter()
+            .any(|ext| arg.ends_with(ext))
+}
+
+/// Split an install argument into name and version.
+fn package_ref(ecosystem: Ecosystem, arg: &str) -> PackageRef {
+    let split = |at: Option<usize>, skip: usize| match at {
+        Some(i) => PackageRef {
+            name: arg[..i].to_string(),
+            version: Some(arg[i + skip..].to_string()).filter(|v| !v.is_empty()),
+        },
+        None => PackageRef {
+            name: arg.to_string(),
+            version: None,
+        },
+    };
+    match ecosystem {
+        // `@scope/name@1.2.3`
+        Ecosystem::Npm => split(arg[1..].find('@').map(|i| i + 1), 1),
+        Ecosystem::Cargo | Ecosystem::Go => split(arg.find('@'), 1),
+        Ecosystem::Gem => split(arg.find(':'), 1),
+        // `name[extra]>=1.0; python_version < "3.12"`
+        Ecosystem::Pip => {
+            let end = arg
+                .find(|c: char| "[=<>!~;@ ".contains(c))
+                .unwrap_or(arg.len());
+            let mut rest = &arg[end..];
+            if rest.starts_with('[') {
+                rest = rest.find(']').map_or("", |i| &rest[i + 1..]);
+            }
+            let spec = rest.split(';').next().unwrap_or("").trim();
+            let spec = spec.strip_prefix("==").unwrap_or(spec);
+            PackageRef {
+                name: arg[..end].to_string(),
+                version: Some(spec.to_string()).filter(|v| !v.is_empty()),
+            }
+        }
+    }
+}
+
+/// The host of a registry URL (`https://user@npm.acme.dev:8443/x` ->
+/// `npm.acme.dev`, `git@github.com:org/repo` -> `github.com`), or the
+/// lowercased value itself for registry names.
+pub fn registry_host(registry: &str) -> String {
+    let rest = match registry.split_once("://") {
+        Some((_, rest)) => rest,
+        None => registry,
+    };
+    let authority = rest.split('/').next().unwrap_or(rest);
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    host.split(':').next().unwrap_or(host).to_lowercase()
+}
+
+/// The ecosystem's name in the OSV schema.
+fn osv_ecosystem(ecosystem: Ecosystem) -> &'static str {
+    match ecosystem {
+        Ecosystem::Npm => "npm",
+        Ecosystem::Pip => "PyPI",
+        Ecosystem::Cargo => "crates.io",
+        Ecosystem::Gem => "RubyGems",
+        Ecosystem::Go => "Go",
+    }
+}
+
+/// Known-malicious package lookups against the OSV batch query API.
+pub struct OsvClient {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct OsvBatchResponse {
+    #[serde(default)]
+    results: Vec<OsvResult>,
+}
+
+#[derive(Deserialize)]
+struct OsvResult {
+    #[serde(default)]
+    vulns: Vec<OsvVuln>,
+}
+
+#[derive(Deserialize)]
+struct OsvVuln {
+    id: String,
+}
+
+impl OsvClient {
+    pub fn new(config: &OsvConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            url: config.url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// For each package, the first malicious-package advisory (`MAL-*`) OSV
+    /// has for it, if any.
+    pub async fn malicious(&self, packages: &[(Ecosystem, &str)]) -> Result<Vec<Option<String>>> {
+        let queries: Vec<serde_json::Value> = packages
+            .iter()
+            .map(|(ecosystem, name)| {
+                serde_json::json!({
+                    "package": { "name": name, "ecosystem": osv_ecosystem(*ecosystem) }
+                })
+            })
+            .collect();
+        let resp = self
+            .client
+            .post(format!("{}/v1/querybatch", self.url))
+            .json(&serde_json::json!({ "queries": queries }))
+            .send()
+            .await
+            .map_err(|e| HookwiseError::Advisory {
+                reason: format!("request failed: {}", e),
+            })?;
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(HookwiseError::Api { status, body });
+        }
+        let batch: OsvBatchResponse = resp.json().await.map_err(|e| HookwiseError::Advisory {
+            reason: format!("invalid response: {}", e),
+        })?;
+        let mut results = batch.results.into_iter().map(|result| {
+            result
+                .vulns
+                .into_iter()
+                .map(|v| v.id)
+                .find(|id| id.starts_with("MAL-"))
+        });
+        Ok(packages.iter().map(|_| results.next().flatten()).collect())
+    }
+}
+
+/// The package gate tier. Denies installs from registries outside
+/// `allowed_registries` and packages OSV flags as malicious; asks for
+/// packages outside the allowlist. Anything else falls through.
+pub struct PackageGate {
+    allowed_registries: Vec<String>,
+    allowlist: Option<HashMap<Ecosystem, GlobSet>>,
+    osv: Option<OsvClient>,
+}
+
+impl PackageGate {
+    /// Compile `packages`. None when it gates nothing.
+    pub fn compile(config: &PackageGateConfig) -> Result<Option<Self>> {
+        if config.allowed_registries.is_empty()
+            && config.allowlist.is_empty()
+            && !config.osv.enabled
+        {
+            return Ok(None);
+        }
+        let allowlist = if config.allowlist.is_empty() {
+            None
+        } else {
+            let mut compiled = HashMap::new();
+            for (ecosystem, patterns) in &config.allowlist {
+                let mut builder = GlobSetBuilder::new();
+                for pattern in patterns {
+                    let glob = GlobBuilder::new(pattern)
+                        .case_insensitive(*ecosystem == Ecosystem::Pip)
+                        .build()
+                        .map_err(|e| HookwiseError::GlobPattern {
+                            pattern: pattern.clone(),
+                            reason: e.to_string(),
+                        })?;
+                    builder.add(glob);
+                }
+                let set = builder.build().map_err(|e| HookwiseError::GlobPattern {
+                    pattern: format!("packages.allowlist.{}", ecosystem),
+                    reason: e.to_string(),
+                })?;
+                compiled.insert(*ecosystem, set);
+            }
+            Some(compiled)
+        };
+        Ok(Some(Self {
+            allowed_registries: config
+                .allowed_registries
+                .iter()
+                .map(|r| registry_host(r))
+                .collect(),
+            allowlist,
+            osv: config.osv.enabled.then(|| OsvClient::new(&config.osv)),
+        }))
+    }
+
+    /// The worst decision for `installs` and the reasons for it, or None if
+    /// nothing is blocked.
+    pub async fn check(&self, installs: &[PackageInstall]) -> Option<(Decision, Vec<String>)> {
+        let mut findings: Vec<(Decision, String)> = Vec::new();
+
+        for install in installs {
+            if !self.allowed_registries.is_empty() {
+                for registry in &install.registries {
+                    if !self.allowed_registries.contains(&registry_host(registry)) {
+                        findings.push((
+                            Decision::Deny,
+                            format!(
+                                "{} install uses registry '{}', which is not in packages.allowed_registries",
+                                install.ecosystem, registry
+                            ),
+                        ));
+                    }
+                }
+            }
+            if let Some(allowlist) = &self.allowlist {
+                for package in &install.packages {
+                    let listed = allowlist
+                        .get(&install.ecosystem)
+                        .is_some_and(|globs| globs.is_match(&package.name));
+                    if !listed {
+                        findings.push((
+                            Decision::Ask,
+                            format!(
+                                "{} package '{}' is not in the packages allowlist",
+                                install.ecosystem, package.name
+                            ),
+                        ));
+                    }
+                }
+                for source in &install.unresolved {
+                    findings.push((
+                        Decision::Ask,
+                        format!(
+                            "{} packages from `{}` can't be checked against the allowlist",
+                            install.ecosystem, source
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let Some(osv) = &self.osv {
+            let packages: Vec<(Ecosystem, &str)> = installs
+                .iter()
+                .flat_map(|i| i.packages.iter().map(|p| (i.ecosystem, p.name.as_str())))
+                .collect();
+            if !packages.is_empty() {
+                match osv.malicious(&packages).await {
+                    Ok(results) => {
+                        for ((ecosystem, name), advisory) in packages.iter().zip(results) {
+                            if let Some(id) = advisory {
+                                findings.push((
+                                    Decision::Deny,
+                                    format!(
+                                        "{} package '{}' is known to be malicious ({})",
+                                        ecosystem, name, id
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+                    Err(e) => tracing::warn!("OSV advisory lookup failed: {}", e),
+                }
+            }
+        }
+
+        let worst = findings
+            .iter()
+            .map(|(d, _)| *d)
+            .max_by_key(|d| d.precedence())?;
+        let reasons = findings
+            .into_iter()
+            .filter(|(d, _)| *d == worst)
+            .map(|(_, reason)| reason)
+            .collect();
+        Some((worst, reasons))
+    }
+}
+
+#[async_trait]
+impl CascadeTier for PackageGate {
+    async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
+        if input.tool_name != "Bash" {
+            return Ok(None);
+        }
+        let command = input
+            .tool_input
+            .get("command")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&input.sanitized_input);
+        let installs = package_installs(command);
+        if installs.is_empty() {
+            return Ok(None);
+        }
+        let Some((decision, reasons)) = self.check(&installs).await else {
+            return Ok(None);
+        };
+
+        let role_name = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| "*".to_string());
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: role_name,
+            },
+            decision,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::PackageGate,
+                confidence: 1.0,
+                reason: reasons.join("; "),
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: None,
+            session_id: String::new(), // Filled by CascadeRunner
+        }))
+    }
+
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::PackageGate
+    }
+
+    fn name(&self) -> &str {
+        "package_gate"
+    }
+}
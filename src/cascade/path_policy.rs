@@ -1,79 +1,230 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 use async_trait::async_trait;
 use chrono::Utc;
 
+use crate::cascade::container::{image_is_pinned, ContainerCommand};
+use crate::cascade::inline_script::InlineScript;
+use crate::cascade::shell::split_commands;
 use crate::cascade::{CascadeInput, CascadeTier};
+use crate::config::roles::CompiledPathPolicy;
+use crate::config::{ContainerPolicy, PathPrecedence};
 use crate::decision::{
-    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel, TargetDecision,
 };
 use crate::error::Result;
 
+/// Tools whose target paths are checked against `allow_write`/`deny_write`.
+pub const WRITE_TOOLS: [&str; 4] = ["Write", "Edit", "MultiEdit", "Bash"];
+
+/// Tools whose target paths are checked against `allow_read`.
+pub const READ_TOOLS: [&str; 3] = ["Read", "Glob", "Grep"];
+
+/// Diff files larger than this are not read for `patch`/`git apply` targets.
+const MAX_PATCH_BYTES: u64 = 1 << 20;
+
 /// Tier 0: Deterministic path policy check.
 pub struct PathPolicyEngine {
     /// Regex patterns for extracting file paths from Bash commands.
     bash_path_extractors: Vec<regex::Regex>,
+    /// Regex patterns for directories a Bash command writes into.
+    bash_dir_extractors: Vec<regex::Regex>,
+    /// Regex patterns for directories a Bash pipeline writes into, matched
+    /// against the whole command since they span sub-commands.
+    bash_pipeline_extractors: Vec<regex::Regex>,
+    /// Whether sensitive patterns or `deny_write` are checked first.
+    precedence: PathPrecedence,
+    /// The home directory `~` and `$HOME` expand to.
+    home: Option<String>,
+    /// Decisions for privileged runs, outside mounts and unpinned images.
+    containers: ContainerPolicy,
 }
 
 impl PathPolicyEngine {
     pub fn new() -> Result<Self> {
         let patterns = vec![
             // rm: extract first path after flags
-            r#"(?:^|[;&|]\s*)rm\s+(?:-[rifvdIRP]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"(?:^|[;&|]\s*)rm\s+(?:-[rifvdIRP]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // mv: extract src and dst
-            r#"(?:^|[;&|]\s*)mv\s+(?:-[fintuvTSZ]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"(?:^|[;&|]\s*)mv\s+(?:-[fintuvTSZ]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // cp: extract src and dst
-            r#"(?:^|[;&|]\s*)cp\s+(?:-[raflinpuvRPdHLsxTZ]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"(?:^|[;&|]\s*)cp\s+(?:-[raflinpuvRPdHLsxTZ]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // mkdir: extract directory path
-            r#"(?:^|[;&|]\s*)mkdir\s+(?:-[pmvZ]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"(?:^|[;&|]\s*)mkdir\s+(?:-[pmvZ]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // touch: extract file path
-            r#"(?:^|[;&|]\s*)touch\s+(?:-[acmr]+\s+(?:\S+\s+)?)*(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"(?:^|[;&|]\s*)touch\s+(?:-[acmr]+\s+(?:\S+\s+)?)*(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // Output redirects (> and >>)
-            r#">{1,2}\s*(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#">{1,2}\s*(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // tee
-            r#"\|\s*tee\s+(?:-[ai]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"\|\s*tee\s+(?:-[ai]+\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // sed -i
-            r#"(?:^|[;&|]\s*)sed\s+(?:-[nEerz]+\s+)*-i(?:\.\S+)?\s+(?:'[^']*'|"[^"]*"|\S+)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"(?:^|[;&|]\s*)sed\s+(?:-[nEerz]+\s+)*-i(?:\.\S+)?\s+(?:'[^']*'|"[^"]*"|\S+)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // chmod
-            r#"(?:^|[;&|]\s*)chmod\s+(?:-[RfvcH]+\s+)*(?:\+?[rwxXstugo0-7,]+)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"(?:^|[;&|]\s*)chmod\s+(?:-[RfvcH]+\s+)*(?:\+?[rwxXstugo0-7,]+)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // chown
-            r#"(?:^|[;&|]\s*)chown\s+(?:-[RfvcHhLP]+\s+)*(?:[\w.:-]+)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"(?:^|[;&|]\s*)chown\s+(?:-[RfvcHhLP]+\s+)*(?:[\w.:-]+)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // git checkout -- <path>
-            r#"(?:^|[;&|]\s*)git\s+checkout\s+(?:-[bBfqm]+\s+)*--\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"(?:^|[;&|]\s*)git\s+checkout\s+(?:-[bBfqm]+\s+)*--\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // curl -o
-            r#"curl\s+.*?(?:-o|--output)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"curl\s+.*?(?:-o|--output)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // wget -O
-            r#"wget\s+.*?(?:-O|--output-document)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.]|\w)[\w./_~*?\[\]{}-]*))"#,
+            r#"wget\s+.*?(?:-O|--output-document)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
             // dd of=
             r#"(?:^|[;&|]\s*)dd\s+.*?of=(?:"([^"]+)"|'([^']+)'|([^\s;&|]+))"#,
+            // rsync: destination is the last argument
+            r#"(?:^|[;&|]\s*)rsync\s+[^;&|]*\s(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))\s*$"#,
+            // install: destination is the last argument
+            r#"(?:^|[;&|]\s*)install\s+[^;&|]*\s(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))\s*$"#,
+            // ln: the link name, not the target
+            r#"(?:^|[;&|]\s*)ln\s+(?:-[sfnvrTLPbi]+\s+)*(?:"[^"]+"|'[^']+'|\S+)\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
+            // truncate
+            r#"(?:^|[;&|]\s*)truncate\s+(?:(?:-[co]+|-[sr]\s*\S+|--(?:size|reference)=\S+|--[\w-]+)\s+)*(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
+        ];
+        let dir_patterns = vec![
+            // tar -x ... -C <dir>
+            r#"(?:^|[;&|]\s*)tar\s+(?:[a-zA-Z]*x[a-zA-Z]*\s+|(?:[^;&|]*\s)?(?:-[a-zA-Z]*x[a-zA-Z]*|--extract|--get)\s+)(?:[^;&|]*\s)?(?:-C\s*|--directory[=\s]\s*)(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
+            // tar -C <dir> ... -x
+            r#"(?:^|[;&|]\s*)tar\s+(?:[^;&|]*\s)?(?:-C\s*|--directory[=\s]\s*)(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))\s+(?:[^;&|]*\s)?(?:-[a-zA-Z]*x[a-zA-Z]*|--extract|--get)(?:\s|$)"#,
+            // unzip -d <dir>
+            r#"(?:^|[;&|]\s*)unzip\s+(?:[^;&|]*\s)?-d\s*(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))"#,
+            // find <dir> ... -delete / -exec rm
+            r#"(?:^|[;&|]\s*)find\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))\s+(?:[^;&|]*\s)?-(?:delete|exec(?:dir)?\s+rm)\b"#,
+        ];
+        let pipeline_patterns = vec![
+            // find <dir> ... | xargs rm
+            r#"(?:^|[;&|]\s*)find\s+(?:"([^"]+)"|'([^']+)'|((?:[/~.\\$]|\w)[\w./\\:_~*?\[\]{}$-]*))[^;&|]*\|\s*xargs\s+(?:-\S+\s+(?:\d+\s+)?)*rm\b"#,
         ];
 
-        let compiled: Vec<regex::Regex> = patterns
-            .iter()
-            .filter_map(|p| regex::Regex::new(p).ok())
-            .collect();
+        let compile = |patterns: Vec<&str>| -> Vec<regex::Regex> {
+            patterns
+                .iter()
+                .filter_map(|p| regex::Regex::new(p).ok())
+                .collect()
+        };
 
         Ok(Self {
-            bash_path_extractors: compiled,
+            bash_path_extractors: compile(patterns),
+            bash_dir_extractors: compile(dir_patterns),
+            bash_pipeline_extractors: compile(pipeline_patterns),
+            precedence: PathPrecedence::default(),
+            home: ["HOME", "USERPROFILE"]
+                .iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .find(|home| !home.is_empty()),
+            containers: ContainerPolicy::default(),
         })
     }
 
-    /// Extract write-target file paths from a Bash command string.
-    fn extract_bash_paths(&self, command: &str) -> Vec<String> {
+    /// Set which wins for a write path matching both `deny_write` and a
+    /// sensitive pattern.
+    pub fn with_precedence(mut self, precedence: PathPrecedence) -> Self {
+        self.precedence = precedence;
+        self
+    }
+
+    /// Set the decisions for risky `docker`/`podman` invocations.
+    pub fn with_containers(mut self, containers: ContainerPolicy) -> Self {
+        self.containers = containers;
+        self
+    }
+
+    /// Set the home directory `~` and `$HOME` expand to (default: `$HOME`).
+    pub fn with_home(mut self, home: Option<String>) -> Self {
+        self.home = home;
+        self
+    }
+
+    /// Extract write-target file paths from a Bash command string, paired
+    /// with the sub-command each came from when the command is compound.
+    /// Directories are returned with a trailing `/`.
+    fn extract_bash_paths(
+        &self,
+        command: &str,
+        cwd: Option<&str>,
+    ) -> Vec<(String, Option<String>)> {
+        let commands = split_commands(command);
+        let compound = commands.len() > 1;
+        let mut paths = Vec::new();
+        for sub_command in &commands {
+            let mut sub_paths = self.extract_simple_bash_paths(sub_command);
+            sub_paths.extend(patch_targets(sub_command, cwd));
+            if let Some(script) = InlineScript::parse(sub_command) {
+                sub_paths.extend(script.effects().write_targets);
+            }
+            if let Some(container) = ContainerCommand::parse(sub_command) {
+                sub_paths.extend(container_write_targets(&container, cwd));
+            }
+            for path in sub_paths {
+                paths.push((path, compound.then(|| sub_command.clone())));
+            }
+        }
+        for re in &self.bash_pipeline_extractors {
+            for caps in re.captures_iter(command) {
+                let pipeline = caps[0].trim_start_matches([';', '&', '|']).trim();
+                for path in captured_paths(&caps) {
+                    paths.push((as_dir(path), Some(pipeline.to_string())));
+                }
+            }
+        }
+        paths
+    }
+
+    /// The inline interpreter scripts a Bash call runs, each with its
+    /// sub-command when the command is compound.
+    fn inline_scripts(&self, input: &CascadeInput) -> Vec<(InlineScript, Option<String>)> {
+        if input.tool_name != "Bash" {
+            return Vec::new();
+        }
+        let command = input
+            .tool_input
+            .get("command")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&input.sanitized_input);
+        let commands = split_commands(command);
+        let compound = commands.len() > 1;
+        commands
+            .iter()
+            .filter_map(|sub_command| {
+                InlineScript::parse(sub_command)
+                    .map(|script| (script, compound.then(|| sub_command.clone())))
+            })
+            .collect()
+    }
+
+    /// The container commands a Bash call runs, each with its sub-command
+    /// when the command is compound.
+    fn container_commands(&self, input: &CascadeInput) -> Vec<(ContainerCommand, Option<String>)> {
+        if input.tool_name != "Bash" {
+            return Vec::new();
+        }
+        let command = input
+            .tool_input
+            .get("command")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&input.sanitized_input);
+        let commands = split_commands(command);
+        let compound = commands.len() > 1;
+        commands
+            .iter()
+            .filter_map(|sub_command| {
+                ContainerCommand::parse(sub_command)
+                    .map(|container| (container, compound.then(|| sub_command.clone())))
+            })
+            .collect()
+    }
+
+    /// Extract write-target file paths from one simple Bash command.
+    fn extract_simple_bash_paths(&self, command: &str) -> Vec<String> {
         let mut paths = Vec::new();
 
         for re in &self.bash_path_extractors {
             for caps in re.captures_iter(command) {
-                // Each pattern has alternation groups for quoted/unquoted paths.
-                // Walk all capture groups and collect non-empty matches.
-                for i in 1..caps.len() {
-                    if let Some(m) = caps.get(i) {
-                        let path = m.as_str().trim();
-                        if !path.is_empty() && path != "/dev/null" {
-                            paths.push(path.to_string());
-                        }
-                    }
-                }
+                paths.extend(captured_paths(&caps));
+            }
+        }
+        for re in &self.bash_dir_extractors {
+            for caps in re.captures_iter(command) {
+                paths.extend(captured_paths(&caps).into_iter().map(as_dir));
             }
         }
 
@@ -82,27 +233,168 @@ impl PathPolicyEngine {
         paths
     }
 
-    /// Make an absolute path relative to the cwd, for glob matching.
-    /// If the path is already relative, or cwd is None, returns the path as-is.
-    fn relativize(path: &str, cwd: Option<&str>) -> String {
-        match cwd {
-            Some(cwd) => {
-                let p = Path::new(path);
-                let c = Path::new(cwd);
-                p.strip_prefix(c)
-                    .map(|rel| rel.to_string_lossy().to_string())
-                    .unwrap_or_else(|_| path.to_string())
+    /// Canonicalize a path for glob matching: resolve it against the cwd,
+    /// follow symlinks and `..` in order as the kernel would, and make it
+    /// relative to the cwd again when it stays inside.
+    /// Paths outside the cwd stay absolute, so relative globs never match them.
+    ///
+    /// Also returns the lexical form (before following symlinks) when it
+    /// differs, since a restrictive rule on the link itself must still apply.
+    ///
+    /// Windows paths are normalized with [`to_slash`] and resolved lexically.
+    /// A trailing `/` (a directory) is kept.
+    pub fn canonicalize(path: &str, cwd: Option<&str>) -> (String, Option<String>) {
+        let (resolved, lexical) = Self::canonicalize_file(path, cwd);
+        if path.ends_with('/') || path.ends_with('\\') {
+            return (as_dir(resolved), lexical.map(as_dir));
+        }
+        (resolved, lexical)
+    }
+
+    fn canonicalize_file(path: &str, cwd: Option<&str>) -> (String, Option<String>) {
+        // `~` is expanded by the shell, not us; match it verbatim.
+        if path.starts_with('~') {
+            return (path.to_string(), None);
+        }
+        if is_windows_path(path) || cwd.is_some_and(is_windows_path) {
+            return (canonicalize_windows(path, cwd), None);
+        }
+        let Some(cwd) = cwd else {
+            let lexical = display(&normalize_lexically(Path::new(path)));
+            return (lexical, None);
+        };
+
+        let cwd_path = Path::new(cwd);
+        let joined = cwd_path.join(path);
+        let lexical = relative_to(&normalize_lexically(&joined), cwd_path);
+
+        let real_cwd = std::fs::canonicalize(cwd_path).unwrap_or_else(|_| cwd_path.to_path_buf());
+        let resolved = relative_to(&resolve_symlinks(&joined), &real_cwd);
+
+        let lexical = (lexical != resolved).then_some(lexical);
+        (resolved, lexical)
+    }
+
+    /// The canonicalized paths a tool call touches, including the lexical
+    /// and `~/` forms. Bash paths that can't be expanded are kept as written.
+    pub fn target_paths(&self, input: &CascadeInput) -> Vec<String> {
+        self.target_path_groups(input)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// [`target_paths`](Self::target_paths) grouped by target: the forms
+    /// of each path the call touches.
+    pub fn target_path_groups(&self, input: &CascadeInput) -> Vec<Vec<String>> {
+        let expand = input.tool_name == "Bash";
+        self.extract_paths(&input.tool_name, input)
+            .into_iter()
+            .map(|(raw, _)| self.forms_of(&raw, input.cwd.as_deref(), expand))
+            .collect()
+    }
+
+    /// The forms of one path as written, with `~`, `$HOME` and `$PWD`
+    /// expanded first when `expand` is set (as the shell would). A path
+    /// that can't be expanded is kept as written.
+    pub fn forms_of(&self, raw: &str, cwd: Option<&str>, expand: bool) -> Vec<String> {
+        let expanded = if expand {
+            expand_path(raw, cwd, self.home.as_deref())
+        } else {
+            Ok(raw.to_string())
+        };
+        match expanded {
+            Ok(path) => self
+                .path_forms(&path, cwd)
+                .into_iter()
+                .map(|(p, _)| p)
+                .collect(),
+            Err(_) => vec![raw.to_string()],
+        }
+    }
+
+    /// The forms of a path glob-matched for one target, each with whether
+    /// it may allow: the canonical path (in `~/` form first when it is
+    /// under the home directory, so `~/.config/**` matches), then the
+    /// lexical form of a symlinked path, which may only ask or deny.
+    fn path_forms(&self, path: &str, cwd: Option<&str>) -> Vec<(String, bool)> {
+        let (resolved, lexical) = Self::canonicalize(path, cwd);
+        let mut forms = Vec::new();
+        for (path, may_allow) in
+            std::iter::once((resolved, true)).chain(lexical.map(|l| (l, false)))
+        {
+            if let Some(tilde) = self.home_relative(&path) {
+                forms.push((tilde, may_allow));
             }
-            None => path.to_string(),
+            forms.push((path, may_allow));
         }
+        forms
+    }
+
+    /// `path` as `~/...` when it is absolute and under the home directory.
+    fn home_relative(&self, path: &str) -> Option<String> {
+        let home = self.home.as_deref()?;
+        let real_home = std::fs::canonicalize(home).ok().map(|p| display(&p));
+        [Some(home.to_string()), real_home]
+            .into_iter()
+            .flatten()
+            .find_map(|home| {
+                let home = to_slash(home.trim_end_matches(['/', '\\']));
+                if home.is_empty() {
+                    return None;
+                }
+                let rest = path.strip_prefix(home.as_str())?.strip_prefix('/')?;
+                Some(format!("~/{}", rest))
+            })
     }
 
-    /// Extract file paths from tool input depending on tool type.
-    fn extract_paths(&self, tool_name: &str, input: &CascadeInput) -> Vec<String> {
+    /// The policy's decision for one canonical path, or `None` when no rule
+    /// covers it.
+    fn check_path(
+        &self,
+        policy: &CompiledPathPolicy,
+        is_read_only: bool,
+        path: &str,
+    ) -> Option<Decision> {
+        if is_read_only {
+            // For read operations, check sensitive paths first, then allow_read
+            if policy.sensitive_ask_write.is_match(path) {
+                Some(Decision::Ask) // Sensitive path read requires human approval
+            } else if policy.allow_read.is_match(path) {
+                None // Allowed, no policy action needed
+            } else {
+                Some(Decision::Deny)
+            }
+        } else {
+            // For write operations, check in order:
+            // 1. sensitive_ask_write -> Ask, deny_write -> Deny
+            //    (swapped with `path_precedence: deny_first`)
+            // 2. allow_write -> Allow
+            let sensitive = policy.sensitive_ask_write.is_match(path);
+            let denied = policy.deny_write.is_match(path);
+            if denied && (!sensitive || self.precedence == PathPrecedence::DenyFirst) {
+                Some(Decision::Deny)
+            } else if sensitive {
+                Some(Decision::Ask)
+            } else if policy.allow_write.is_match(path) {
+                Some(Decision::Allow)
+            } else {
+                None // No match = fall through
+            }
+        }
+    }
+
+    /// Extract file paths from tool input depending on tool type, each with
+    /// the Bash sub-command it came from, if any.
+    fn extract_paths(
+        &self,
+        tool_name: &str,
+        input: &CascadeInput,
+    ) -> Vec<(String, Option<String>)> {
         match tool_name {
-            "Write" | "Edit" | "Read" | "Glob" | "Grep" => {
+            "Write" | "Edit" | "MultiEdit" | "Read" | "Glob" | "Grep" => {
                 if let Some(fp) = &input.file_path {
-                    vec![fp.clone()]
+                    vec![(fp.clone(), None)]
                 } else {
                     Vec::new()
                 }
@@ -113,7 +405,7 @@ impl PathPolicyEngine {
                     .get("command")
                     .and_then(|v| v.as_str())
                     .unwrap_or(&input.sanitized_input);
-                self.extract_bash_paths(command)
+                self.extract_bash_paths(command, input.cwd.as_deref())
             }
             _ => Vec::new(),
         }
@@ -129,67 +421,208 @@ impl CascadeTier for PathPolicyEngine {
         };
 
         let raw_paths = self.extract_paths(&input.tool_name, input);
-        if raw_paths.is_empty() {
+        let scripts = self.inline_scripts(input);
+        let containers = self.container_commands(input);
+        if raw_paths.is_empty() && scripts.is_empty() && containers.is_empty() {
             return Ok(None); // No file paths extracted = fall through
         }
 
-        // Relativize absolute paths against cwd so globs like "src/**" can match.
-        let paths: Vec<String> = raw_paths
-            .iter()
-            .map(|p| Self::relativize(p, input.cwd.as_deref()))
-            .collect();
+        // Every form of every target is matched and the worst decision wins;
+        // an allow must hold for the resolved path, not just the lexical one.
+        let cwd = input.cwd.as_deref();
+        let expand = input.tool_name == "Bash";
+        // Each target's own decision, so a partly blocked call can be
+        // retried with just the targets that were not blocked.
+        let mut targets: Vec<TargetDecision> = Vec::new();
+        let mut forms: Vec<(usize, String, bool, Option<String>)> = Vec::new();
+        // Decisions that don't come from matching a glob: (target, path,
+        // decision, reason, sub-command).
+        let mut forced: Vec<(Option<usize>, String, Decision, String, Option<String>)> = Vec::new();
+        for (raw, sub_command) in &raw_paths {
+            let expanded = if expand {
+                expand_path(raw, cwd, self.home.as_deref())
+            } else {
+                Ok(raw.clone())
+            };
+            match expanded {
+                Ok(path) => {
+                    let target_forms = self.path_forms(&path, cwd);
+                    let index = target_index(&mut targets, target_forms[0].0.clone());
+                    for (path, may_allow) in target_forms {
+                        forms.push((index, path, may_allow, sub_command.clone()));
+                    }
+                }
+                Err(part) => {
+                    let index = target_index(&mut targets, raw.clone());
+                    let reason =
+                        format!("path '{}' uses `{}`, which cannot be resolved", raw, part);
+                    forced.push((
+                        Some(index),
+                        raw.clone(),
+                        Decision::Ask,
+                        reason,
+                        sub_command.clone(),
+                    ));
+                }
+            }
+        }
+
+        // Inline scripts ask when they write somewhere computed at run
+        // time, or name a sensitive path at all.
+        for (script, sub_command) in &scripts {
+            let effects = script.effects();
+            for call in effects.dynamic_calls {
+                let reason = format!(
+                    "inline {} script calls `{}` on a computed path",
+                    script.interpreter, call
+                );
+                forced.push((None, call, Decision::Ask, reason, sub_command.clone()));
+            }
+            for literal in effects.other_paths {
+                let Ok(path) = expand_path(&literal, cwd, self.home.as_deref()) else {
+                    continue;
+                };
+                let target_forms = self.path_forms(&path, cwd);
+                if let Some((path, _)) = target_forms
+                    .iter()
+                    .find(|(path, _)| policy.sensitive_ask_write.is_match(path))
+                {
+                    let index = target_index(&mut targets, target_forms[0].0.clone());
+                    let reason = format!(
+                        "inline {} script references sensitive path '{}'",
+                        script.interpreter, path
+                    );
+                    forced.push((
+                        Some(index),
+                        path.clone(),
+                        Decision::Ask,
+                        reason,
+                        sub_command.clone(),
+                    ));
+                }
+            }
+        }
+
+        // Containers are checked for what they could do to the host beyond
+        // the write targets above: privileged mode, mounts of host paths
+        // outside the project, and images that aren't pinned.
+        let flagged = |flag: bool, decision: Decision| {
+            (flag && decision != Decision::Allow).then_some(decision)
+        };
+        for (container, sub_command) in &containers {
+            if let Some(decision) = flagged(container.privileged, self.containers.privileged) {
+                let reason = format!(
+                    "`{} run --privileged` gives the container full access to the host",
+                    container.engine
+                );
+                forced.push((
+                    None,
+                    "--privileged".into(),
+                    decision,
+                    reason,
+                    sub_command.clone(),
+                ));
+            }
+            if let Some(decision) = flagged(true, self.containers.host_mounts) {
+                for mount in &container.mounts {
+                    let Ok(path) = expand_path(&mount.path, cwd, self.home.as_deref()) else {
+                        continue; // Already forced to ask as a write target
+                    };
+                    let target_forms = self.path_forms(&path, cwd);
+                    let canonical = target_forms[0].0.clone();
+                    if !outside_project(&canonical) {
+                        continue;
+                    }
+                    let index = target_index(&mut targets, canonical.clone());
+                    let reason = format!(
+                        "container mounts host path '{}' outside the project",
+                        canonical
+                    );
+                    forced.push((
+                        Some(index),
+                        canonical,
+                        decision,
+                        reason,
+                        sub_command.clone(),
+                    ));
+                }
+            }
+            if let Some(image) = &container.image {
+                if let Some(decision) =
+                    flagged(!image_is_pinned(image), self.containers.unpinned_images)
+                {
+                    let reason = format!(
+                        "container image '{}' is not pinned to a tag or digest",
+                        image
+                    );
+                    forced.push((None, image.clone(), decision, reason, sub_command.clone()));
+                }
+            }
+        }
 
-        let is_read_only =
-            input.tool_name == "Read" || input.tool_name == "Glob" || input.tool_name == "Grep";
+        let is_read_only = READ_TOOLS.contains(&input.tool_name.as_str());
 
         // Evaluate each path against the policy. Most restrictive wins.
         let mut worst_decision: Option<Decision> = None;
         let mut worst_path = String::new();
         let mut worst_reason = String::new();
 
-        for path in &paths {
-            let decision = if is_read_only {
-                // For read operations, check sensitive paths first, then allow_read
-                if policy.sensitive_ask_write.is_match(path) {
-                    Some(Decision::Ask) // Sensitive path read requires human approval
-                } else if policy.allow_read.is_match(path) {
-                    None // Allowed, no policy action needed
-                } else {
-                    Some(Decision::Deny)
-                }
-            } else {
-                // For write operations, check in order:
-                // 1. sensitive_ask_write -> Ask
-                // 2. deny_write -> Deny
-                // 3. allow_write -> Allow
-                if policy.sensitive_ask_write.is_match(path) {
-                    Some(Decision::Ask)
-                } else if policy.deny_write.is_match(path) {
-                    Some(Decision::Deny)
-                } else if policy.allow_write.is_match(path) {
-                    Some(Decision::Allow)
-                } else {
-                    None // No match = fall through
-                }
+        let checked = forms
+            .into_iter()
+            .map(|(index, path, may_allow, sub_command)| {
+                let decision = self
+                    .check_path(policy, is_read_only, &path)
+                    .filter(|d| may_allow || *d != Decision::Allow);
+                let reason = decision.map(|d| match d {
+                    Decision::Deny => format!("path '{}' denied by role path policy", path),
+                    Decision::Ask => format!("path '{}' matches sensitive path pattern", path),
+                    Decision::Allow => format!("path '{}' allowed by role path policy", path),
+                });
+                (Some(index), path, decision, reason, sub_command)
+            })
+            .chain(
+                forced
+                    .into_iter()
+                    .map(|(index, path, decision, reason, sub_command)| {
+                        (index, path, Some(decision), Some(reason), sub_command)
+                    }),
+            );
+
+        for (index, path, decision, reason, sub_command) in checked {
+            let (Some(d), Some(mut reason)) = (decision, reason) else {
+                continue;
             };
+            if let Some(target) = index.map(|i| &mut targets[i]) {
+                if target
+                    .decision
+                    .is_none_or(|current| d.precedence() > current.precedence())
+                {
+                    target.decision = Some(d);
+                }
+            }
 
-            if let Some(d) = decision {
-                let dominated = match (&worst_decision, &d) {
-                    (None, _) => true,
-                    (Some(current), new) => new.precedence() > current.precedence(),
-                };
-                if dominated {
-                    worst_decision = Some(d);
-                    worst_path = path.clone();
-                    worst_reason = match d {
-                        Decision::Deny => format!("path '{}' denied by role path policy", path),
-                        Decision::Ask => format!("path '{}' matches sensitive path pattern", path),
-                        Decision::Allow => format!("path '{}' allowed by role path policy", path),
-                    };
+            let dominated = match &worst_decision {
+                None => true,
+                Some(current) => d.precedence() > current.precedence(),
+            };
+            if dominated {
+                if let Some(sub_command) = sub_command {
+                    reason.push_str(&format!(" (in `{}`)", sub_command));
                 }
+                worst_decision = Some(d);
+                worst_path = path;
+                worst_reason = reason;
             }
         }
 
+        // A path no rule covers falls through, which outranks allow: one
+        // allowed write must not carry an unreviewed one with it.
+        let unmatched = targets.iter().any(|t| t.decision.is_none());
+
+        if unmatched && worst_decision == Some(Decision::Allow) {
+            return Ok(None);
+        }
+
         match worst_decision {
             Some(decision) => {
                 let role_name = input
@@ -212,6 +645,16 @@ impl CascadeTier for PathPolicyEngine {
                         reason: worst_reason,
                         matched_key: None,
                         similarity_score: None,
+                        targets: if targets.len() > 1 {
+                            targets
+                        } else {
+                            Vec::new()
+                        },
+                        message: None,
+                        suggestion: None,
+                        risk_score: None,
+                        secrets: Vec::new(),
+                        client: None,
                     },
                     timestamp: Utc::now(),
                     scope: ScopeLevel::Role,
@@ -231,3 +674,381 @@ impl CascadeTier for PathPolicyEngine {
         "path-policy"
     }
 }
+
+/// The host paths a container command can write: writable bind mounts
+/// (as directories, unless they name an existing file) and `cp`
+/// destinations.
+fn container_write_targets(container: &ContainerCommand, cwd: Option<&str>) -> Vec<String> {
+    let is_file = |path: &str| {
+        let path = Path::new(path);
+        match cwd {
+            Some(cwd) => Path::new(cwd).join(path).is_file(),
+            None => path.is_file(),
+        }
+    };
+    container
+        .mounts
+        .iter()
+        .filter(|mount| !mount.read_only)
+        .map(|mount| {
+            if is_file(&mount.path) {
+                mount.path.clone()
+            } else {
+                as_dir(mount.path.clone())
+            }
+        })
+        .chain(container.copy_target.clone())
+        .collect()
+}
+
+/// Whether a canonical path (see [`PathPolicyEngine::canonicalize`]) lies
+/// outside the cwd: those stay absolute, or in `~/` form.
+fn outside_project(canonical: &str) -> bool {
+    canonical.starts_with(['/', '~']) || drive_letter(canonical).is_some()
+}
+
+/// The index of the target for `path`, added undecided if it is new.
+fn target_index(targets: &mut Vec<TargetDecision>, path: String) -> usize {
+    match targets.iter().position(|t| t.path == path) {
+        Some(index) => index,
+        None => {
+            targets.push(TargetDecision {
+                path,
+                decision: None,
+            });
+            targets.len() - 1
+        }
+    }
+}
+
+/// Whether a path is in Windows form: always on Windows, elsewhere when it
+/// has a drive letter (`C:\src`, `c:/src`) or is a UNC path (`\\host\share`).
+pub fn is_windows_path(path: &str) -> bool {
+    cfg!(windows) || drive_letter(path).is_some() || path.starts_with(r"\\")
+}
+
+fn drive_letter(path: &str) -> Option<char> {
+    let mut chars = path.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic() => Some(letter),
+        _ => None,
+    }
+}
+
+/// A Windows path with forward slashes and an uppercase drive letter, the
+/// form globs are matched against: `c:\src\main.rs` -> `C:/src/main.rs`.
+pub fn to_slash(path: &str) -> String {
+    let slashed = path.replace('\\', "/");
+    match drive_letter(&slashed) {
+        Some(letter) => format!("{}{}", letter.to_ascii_uppercase(), &slashed[1..]),
+        None => slashed,
+    }
+}
+
+/// Expand a leading `~` and `$HOME`/`$PWD` (bare or braced) in a path the
+/// way the shell would, with `$PWD` as the hook's cwd. Returns the part that
+/// can't be resolved as the error: `~user`, any other variable (its value in
+/// the agent's shell is unknown), or `${...}` with an operator.
+pub fn expand_path(
+    path: &str,
+    cwd: Option<&str>,
+    home: Option<&str>,
+) -> std::result::Result<String, String> {
+    let lookup = |name: &str| match name {
+        "HOME" => home,
+        "PWD" => cwd,
+        _ => None,
+    };
+
+    let mut expanded = String::new();
+    let mut rest = path;
+    if let Some(after) = path.strip_prefix('~') {
+        let end = after.find(['/', '\\']).unwrap_or(after.len());
+        if end > 0 {
+            return Err(format!("~{}", &after[..end]));
+        }
+        expanded.push_str(home.ok_or_else(|| "~".to_string())?);
+        rest = after;
+    }
+
+    while let Some(pos) = rest.find('$') {
+        expanded.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let (name, len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => return Err(rest[pos..].to_string()),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        let token = &rest[pos..pos + 1 + len];
+        let value = lookup(name).ok_or_else(|| token.to_string())?;
+        expanded.push_str(value);
+        rest = &after[len..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// The non-empty paths in an extractor match. Each pattern has alternation
+/// groups for quoted/unquoted paths, so every capture group is walked.
+fn captured_paths(caps: &regex::Captures) -> Vec<String> {
+    (1..caps.len())
+        .filter_map(|i| caps.get(i))
+        .map(|m| m.as_str().trim())
+        .filter(|path| !path.is_empty() && !is_null_device(path))
+        .map(String::from)
+        .collect()
+}
+
+/// Mark a path as a directory, so `tests/**` matches `tests/`.
+fn as_dir(mut path: String) -> String {
+    if !path.ends_with('/') && !path.ends_with('\\') {
+        path.push('/');
+    }
+    path
+}
+
+/// Paths a `patch` or `git apply` command writes: the files named in its
+/// diff (read relative to `cwd` when it exists and is small), plus any
+/// output file or directory given on the command line.
+fn patch_targets(command: &str, cwd: Option<&str>) -> Vec<String> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let (git, args) = match words.as_slice() {
+        ["git", "apply", rest @ ..] => (true, rest),
+        ["patch", rest @ ..] => (false, rest),
+        _ => return Vec::new(),
+    };
+
+    let mut strip = usize::from(git);
+    let mut diff = None;
+    let mut directory = None;
+    let mut positional = Vec::new();
+    let mut targets = Vec::new();
+    let mut args = args.iter().copied();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+            _ => (arg, None),
+        };
+        let mut value = || inline.or_else(|| args.next());
+        match flag {
+            // Dry runs and index-only applies leave the worktree alone.
+            "--dry-run" | "--check" | "--stat" | "--numstat" | "--summary" | "--cached" => {
+                return Vec::new()
+            }
+            "<" | "-i" | "--input" => diff = value(),
+            "-p" | "--strip" => strip = value().and_then(|n| n.parse().ok()).unwrap_or(strip),
+            "-d" | "--directory" => directory = value(),
+            "-o" | "--output" => targets.extend(value().map(String::from)),
+            // `patch` options that take a separate value.
+            "-B" | "-D" | "-F" | "-g" | "-r" | "-V" | "-Y" | "-z" if !git => {
+                value();
+            }
+            _ if flag.starts_with('<') => diff = Some(&arg[1..]),
+            _ if flag.starts_with("-p") => strip = flag[2..].parse().unwrap_or(strip),
+            _ if flag.starts_with("-o") && !git => targets.push(flag[2..].to_string()),
+            _ if flag.starts_with('-') => {}
+            _ => positional.push(arg),
+        }
+    }
+
+    // `git apply <patch>...`; `patch [originalfile [patchfile]]`.
+    if git {
+        diff = diff.or(positional.first().copied());
+    } else {
+        targets.extend(positional.first().map(|p| p.to_string()));
+        diff = diff.or(positional.get(1).copied());
+    }
+
+    if let Some(diff) = diff {
+        let diff_path = match cwd {
+            Some(cwd) => Path::new(cwd).join(diff),
+            None => PathBuf::from(diff),
+        };
+        let contents = std::fs::metadata(&diff_path)
+            .ok()
+            .filter(|m| m.len() <= MAX_PATCH_BYTES)
+            .and_then(|_| std::fs::read_to_string(&diff_path).ok());
+        if let Some(contents) = contents {
+            let prefix = directory.map(|d| as_dir(d.to_string())).unwrap_or_default();
+            targets.extend(
+                diff_paths(&contents, strip)
+                    .into_iter()
+                    .map(|path| format!("{}{}", prefix, path)),
+            );
+        }
+    } else if let Some(directory) = directory {
+        targets.push(as_dir(directory.to_string()));
+    }
+
+    targets.retain(|t| !t.is_empty() && !is_null_device(t));
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+/// The files a unified diff touches (`---`/`+++` headers), with `strip`
+/// leading components removed as by `patch -p`.
+fn diff_paths(diff: &str, strip: usize) -> Vec<String> {
+    diff.lines()
+        .filter_map(|line| {
+            line.strip_prefix("+++ ")
+                .or_else(|| line.strip_prefix("--- "))
+        })
+        .map(|rest| rest.split_once('\t').map_or(rest, |(path, _)| path))
+        .map(|path| path.trim().trim_matches('"'))
+        .filter(|path| !is_null_device(path))
+        .filter_map(|path| {
+            let stripped: Vec<&str> = path.split('/').skip(strip).collect();
+            (!stripped.is_empty()).then(|| stripped.join("/"))
+        })
+        .collect()
+}
+
+/// `nul` is the Windows counterpart of `/dev/null`.
+fn is_null_device(path: &str) -> bool {
+    path == "/dev/null" || path.eq_ignore_ascii_case("nul") || path == "$null"
+}
+
+/// Lexical [`PathPolicyEngine::canonicalize`] for Windows paths. Windows
+/// symlinks need privileges to create and are rare, so they aren't followed;
+/// the cwd prefix is compared case-insensitively, as NTFS does.
+fn canonicalize_windows(path: &str, cwd: Option<&str>) -> String {
+    let path = to_slash(path);
+    let Some(cwd) = cwd.map(to_slash) else {
+        return normalize_slashed(&path);
+    };
+    let absolute = if drive_letter(&path).is_some() || path.starts_with("//") {
+        path
+    } else if path.starts_with('/') {
+        // Rooted on the cwd's drive.
+        let drive = if drive_letter(&cwd).is_some() {
+            &cwd[..2]
+        } else {
+            ""
+        };
+        format!("{drive}{path}")
+    } else {
+        format!("{cwd}/{path}")
+    };
+
+    let absolute = normalize_slashed(&absolute);
+    let base = normalize_slashed(&cwd);
+    if absolute.eq_ignore_ascii_case(&base) {
+        return ".".to_string();
+    }
+    let base = base.trim_end_matches('/');
+    match absolute.get(..base.len()) {
+        Some(head)
+            if head.eq_ignore_ascii_case(base) && absolute[base.len()..].starts_with('/') =>
+        {
+            absolute[base.len() + 1..].to_string()
+        }
+        _ => absolute,
+    }
+}
+
+/// [`normalize_lexically`] for a slash-separated Windows path, keeping its
+/// root (`C:/`, `//` for UNC, or `/`).
+fn normalize_slashed(path: &str) -> String {
+    let (root, rest) = if drive_letter(path).is_some() {
+        match path[2..].strip_prefix('/') {
+            Some(rest) => (&path[..3], rest),
+            None => (&path[..2], &path[2..]),
+        }
+    } else if let Some(rest) = path.strip_prefix("//") {
+        ("//", rest)
+    } else if let Some(rest) = path.strip_prefix('/') {
+        ("/", rest)
+    } else {
+        ("", path)
+    };
+
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if parts.last().is_some_and(|p| *p != "..") {
+                    parts.pop();
+                } else if root.is_empty() {
+                    parts.push("..");
+                }
+            }
+            other => parts.push(other),
+        }
+    }
+    if root.is_empty() && parts.is_empty() {
+        return ".".to_string();
+    }
+    format!("{root}{}", parts.join("/"))
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+/// Leading `..` in a relative path is kept, since there is nothing to pop.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                let popped =
+                    matches!(out.components().next_back(), Some(Component::Normal(_))) && out.pop();
+                if !popped && !out.has_root() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Resolve an absolute path the way the kernel walks it: component by
+/// component, following each symlink before the next `..` is applied, so
+/// `link/..` is the parent of the link's target rather than of the link.
+/// Components that don't exist yet (e.g. a file about to be created inside
+/// a symlinked directory) are appended as written.
+fn resolve_symlinks(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                let popped =
+                    matches!(out.components().next_back(), Some(Component::Normal(_))) && out.pop();
+                if !popped && !out.has_root() {
+                    out.push("..");
+                }
+            }
+            other => {
+                out.push(other);
+                // Everything before `other` is already resolved, so only
+                // the last component can still be a link.
+                if let Ok(real) = std::fs::canonicalize(&out) {
+                    out = real;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// `path` relative to `base` when inside it, else the absolute path.
+fn relative_to(path: &Path, base: &Path) -> String {
+    match path.strip_prefix(base) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Ok(rel) => display(rel),
+        Err(_) => display(path),
+    }
+}
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
@@ -0,0 +1,192 @@
+//! Past human decisions offered to the supervisor as few-shot examples.
+//!
+//! Candidates come from the similarity indexes, so they are always for the
+//! same role (or wildcard) and tool as the call under review. Corrections
+//! recorded with `hookwise feedback` are offered alongside them.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cascade::embed_sim::EmbeddingSimilarity;
+use crate::cascade::token_sim::TokenJaccard;
+use crate::cascade::{partition_keys, CascadeInput};
+use crate::config::SupervisorExamplesConfig;
+use crate::decision::{Decision, DecisionRecord, DecisionTier};
+use crate::feedback::FeedbackRecord;
+
+/// A past human decision on a similar call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Precedent {
+    pub tool_name: String,
+    pub sanitized_input: String,
+    pub decision: Decision,
+    /// Similarity to the call under review, in [0, 1].
+    pub similarity: f64,
+    /// The automatic decision this one corrected (`hookwise feedback`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrected_from: Option<Decision>,
+}
+
+impl Precedent {
+    fn from_record(similarity: f64, record: &DecisionRecord) -> Self {
+        Self {
+            tool_name: record.key.tool.clone(),
+            sanitized_input: record.key.sanitized_input.clone(),
+            decision: record.decision,
+            similarity,
+            corrected_from: None,
+        }
+    }
+
+    /// One prompt line, e.g. `- deny (similarity 0.82) Bash: git push --force`,
+    /// or `- deny (similarity 0.82, corrected from allow) ...` for a correction.
+    pub fn render(&self) -> String {
+        let correction = self
+            .corrected_from
+            .map(|d| format!(", corrected from {}", d))
+            .unwrap_or_default();
+        format!(
+            "- {} (similarity {:.2}{}) {}: {}",
+            self.decision, self.similarity, correction, self.tool_name, self.sanitized_input
+        )
+    }
+
+    /// Rough prompt cost at ~4 characters per token.
+    pub fn estimated_tokens(&self) -> usize {
+        self.render().chars().count().div_ceil(4)
+    }
+}
+
+/// Whether a record was decided by a person rather than inferred.
+fn human_decided(record: &DecisionRecord) -> bool {
+    matches!(
+        record.metadata.tier,
+        DecisionTier::Human | DecisionTier::Override
+    )
+}
+
+/// Deduplicate by input (keeping the most similar, and of equals the
+/// earliest), order most similar first, and keep at most `max_examples` within `max_tokens`.
+pub fn select_within_budget(
+    mut candidates: Vec<Precedent>,
+    max_examples: usize,
+    max_tokens: usize,
+) -> Vec<Precedent> {
+    candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    let mut seen = std::collections::HashSet::new();
+    let mut used = 0;
+    let mut selected = Vec::new();
+    for precedent in candidates {
+        if selected.len() >= max_examples {
+            break;
+        }
+        if !seen.insert((
+            precedent.tool_name.clone(),
+            precedent.sanitized_input.clone(),
+        )) {
+            continue;
+        }
+        let cost = precedent.estimated_tokens();
+        if used + cost > max_tokens {
+            continue;
+        }
+        used += cost;
+        selected.push(precedent);
+    }
+    selected
+}
+
+/// Picks precedents for a supervisor request from the similarity indexes.
+pub struct PrecedentSelector {
+    token_jaccard: Arc<TokenJaccard>,
+    embedding_similarity: Option<Arc<EmbeddingSimilarity>>,
+    /// Corrections from `hookwise feedback`, oldest first.
+    corrections: Vec<FeedbackRecord>,
+    config: SupervisorExamplesConfig,
+}
+
+impl PrecedentSelector {
+    pub fn new(token_jaccard: Arc<TokenJaccard>, config: SupervisorExamplesConfig) -> Self {
+        Self {
+            token_jaccard,
+            embedding_similarity: None,
+            corrections: Vec::new(),
+            config,
+        }
+    }
+
+    /// Offer corrections from `hookwise feedback` as precedents. A
+    /// correction outranks other precedents for the same input, and the
+    /// newest correction of a call outranks older ones.
+    pub fn with_corrections(mut self, feedback: Vec<FeedbackRecord>) -> Self {
+        self.corrections = feedback;
+        self
+    }
+
+    /// Also draw candidates from the embedding index.
+    pub fn with_embedding(mut self, embedding_similarity: Arc<EmbeddingSimilarity>) -> Self {
+        self.embedding_similarity = Some(embedding_similarity);
+        self
+    }
+
+    /// Human-decided precedents most similar to `input`, within the
+    /// configured count and token budget. Empty when disabled.
+    pub async fn select(&self, input: &CascadeInput) -> Vec<Precedent> {
+        if !self.config.enabled || self.config.max_examples == 0 {
+            return Vec::new();
+        }
+        let role = input
+            .session
+            .role
+            .as_ref()
+            .map(|r| r.name.as_str())
+            .unwrap_or("*");
+
+        // Corrections go first so they win deduplication on ties.
+        let query_tokens = TokenJaccard::tokenize(&input.sanitized_input);
+        let partitions = partition_keys(role, &input.tool_name);
+        let mut candidates: Vec<Precedent> = self
+            .corrections
+            .iter()
+            .rev()
+            .filter(|f| partitions.contains(&(f.key.role.clone(), f.key.tool.clone())))
+            .map(|f| {
+                let tokens = TokenJaccard::tokenize(&f.key.sanitized_input);
+                let score = TokenJaccard::jaccard_coefficient(&query_tokens, &tokens);
+                let mut precedent = Precedent::from_record(score, &f.as_decision());
+                precedent.corrected_from = Some(f.original);
+                precedent
+            })
+            .filter(|p| p.similarity > 0.0)
+            .collect();
+
+        candidates.extend(
+            self.token_jaccard
+                .ranked(&input.sanitized_input, role, &input.tool_name)
+                .iter()
+                .filter(|(score, record)| *score > 0.0 && human_decided(record))
+                .map(|(score, record)| Precedent::from_record(*score, record)),
+        );
+
+        if let Some(embedding) = self
+            .embedding_similarity
+            .as_ref()
+            .filter(|e| e.has_candidates(role, &input.tool_name))
+        {
+            // The query vector is usually cached from Tier 2b; an embedding
+            // failure only costs the semantic candidates.
+            if let Ok(query) = embedding.embed(&input.sanitized_input).await {
+                candidates.extend(
+                    embedding
+                        .neighbors(&query, role, &input.tool_name)
+                        .iter()
+                        .filter(|(score, entry)| *score > 0.0 && human_decided(&entry.record))
+                        .map(|(score, entry)| Precedent::from_record(*score, &entry.record)),
+                );
+            }
+        }
+
+        select_within_budget(candidates, self.config.max_examples, self.config.max_tokens)
+    }
+}
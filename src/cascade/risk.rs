@@ -0,0 +1,84 @@
+//! Rolling per-session risk (`risk` in policy.yml). Denied calls, calls
+//! touching sensitive paths and novel calls add to the session's score;
+//! once it crosses the threshold the runner tightens which tiers may decide
+//! for the session.
+
+use chrono::{DateTime, Utc};
+
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::CascadeInput;
+use crate::config::RiskConfig;
+use crate::decision::{Decision, DecisionRecord, DecisionTier};
+use crate::error::Result;
+use crate::session::state::{risk_window_start, RiskKind, RiskSummary, SessionStateStore};
+
+/// Risk accounting for one session.
+pub struct RiskTracker {
+    config: RiskConfig,
+    store: SessionStateStore,
+    session_id: String,
+    extractor: PathPolicyEngine,
+}
+
+impl RiskTracker {
+    /// Track `session_id`'s risk in `store`.
+    pub fn new(config: &RiskConfig, store: SessionStateStore, session_id: &str) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            store,
+            session_id: session_id.to_string(),
+            extractor: PathPolicyEngine::new()?,
+        })
+    }
+
+    /// The session's risk within the window ending at `now`.
+    pub fn current(&self, now: DateTime<Utc>) -> Result<RiskSummary> {
+        let since = risk_window_start(&self.config, now);
+        Ok(self.store.load(&self.session_id)?.risk_since(since))
+    }
+
+    /// Whether `risk` is over the threshold.
+    pub fn exceeded(&self, risk: &RiskSummary) -> bool {
+        self.config.exceeded(risk.score(&self.config.weights))
+    }
+
+    /// Whether `tier` may decide for a session over the threshold.
+    pub fn permits(&self, tier: DecisionTier) -> bool {
+        self.config.permits(tier)
+    }
+
+    /// The risk events a decided call adds.
+    pub fn events(&self, input: &CascadeInput, record: &DecisionRecord) -> Vec<RiskKind> {
+        let mut events = Vec::new();
+        if record.decision == Decision::Deny {
+            events.push(RiskKind::Denied);
+        }
+        if let Some(policy) = &input.session.path_policy {
+            let paths = self.extractor.target_paths(input);
+            if paths.iter().any(|p| policy.sensitive_ask_write.is_match(p)) {
+                events.push(RiskKind::Sensitive);
+            }
+        }
+        if matches!(
+            record.metadata.tier,
+            DecisionTier::Supervisor | DecisionTier::Human | DecisionTier::Default
+        ) {
+            events.push(RiskKind::Novel);
+        }
+        events
+    }
+
+    /// Add the events of a decided call to the session's score.
+    pub fn record(&self, input: &CascadeInput, record: &DecisionRecord) -> Result<()> {
+        let events = self.events(input, record);
+        if events.is_empty() {
+            return Ok(());
+        }
+        let now = Utc::now();
+        let since = risk_window_start(&self.config, now);
+        self.store.update(&self.session_id, |state| {
+            state.record_risk(&events, now, since);
+        })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+//! Splitting compound Bash commands into the simple commands they run, so
+//! policy checks see `rm -rf src` in `ls && rm -rf src` or `echo $(rm -rf src)`.
+
+/// Words that run the command after them (plus any `-flags`).
+const PREFIX_WORDS: &[&str] = &[
+    "sudo", "env", "nohup", "time", "command", "exec", "nice", "xargs", "builtin",
+];
+
+/// Shell keywords that only open or close a compound command.
+const KEYWORDS: &[&str] = &[
+    "{", "}", "!", "if", "then", "elif", "else", "fi", "while", "until", "do", "done",
+];
+
+/// Split a Bash command line into its simple commands, in execution order.
+///
+/// Splits on `&&`, `||`, `;`, `|`, `&`, newlines and subshell parentheses;
+/// each `$( )`, `<( )`, `>( )` and backtick substitution becomes a command of
+/// its own, ahead of the command it appears in. Quotes and escapes are
+/// respected, redirections such as `2>&1`, `&>` and `>|` are not split, and
+/// heredoc bodies are skipped.
+///
+/// Leading `sudo`/`env`/`VAR=value`/shell keywords are dropped so every
+/// command starts with the program it runs.
+pub fn split_commands(command: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    split_into(&command.chars().collect::<Vec<_>>(), &mut commands);
+    commands
+}
+
+/// Split a simple command into its words, removing quotes and escapes as
+/// the shell would. Nothing is expanded.
+pub fn split_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some('\'') if c == '\'' => quote = None,
+            Some('\'') => current.push(c),
+            Some(_) => match c {
+                '"' => quote = None,
+                // Inside double quotes a backslash only escapes these.
+                '\\' => match chars.next() {
+                    Some(next @ ('"' | '\\' | '$' | '`')) => current.push(next),
+                    Some(next) => current.extend(['\\', next]),
+                    None => current.push('\\'),
+                },
+                _ => current.push(c),
+            },
+            None => match c {
+                '\'' | '"' => {
+                    quote = Some(c);
+                    in_word = true;
+                }
+                '\\' => {
+                    current.extend(chars.next());
+                    in_word = true;
+                }
+                c if c.is_whitespace() => {
+                    if in_word {
+                        words.push(std::mem::take(&mut current));
+                        in_word = false;
+                    }
+                }
+                _ => {
+                    current.push(c);
+                    in_word = true;
+                }
+            },
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+fn split_into(chars: &[char], out: &mut Vec<String>) {
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut heredocs: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let prev = current.chars().last();
+
+        match quote {
+            Some('\'') => {
+                current.push(c);
+                if c == '\'' {
+                    quote = None;
+                }
+                i += 1;
+                continue;
+            }
+            // Double quotes still expand substitutions.
+            Some(_) if c != '`' && !(c == '$' && next == Some('(')) => {
+                current.push(c);
+                if c == '\\' {
+                    current.extend(next);
+                    i += 1;
+                } else if c == '"' {
+                    quote = None;
+                }
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        match c {
+            '\\' => {
+                current.push(c);
+                current.extend(next);
+                i += 2;
+            }
+            '\'' | '"' => {
+                quote = Some(c);
+                current.push(c);
+                i += 1;
+            }
+            // `<<<` is a here-string, not a heredoc.
+            '<' if next == Some('<') && chars.get(i + 2) == Some(&'<') => {
+                current.push_str("<<<");
+                i += 3;
+            }
+            '<' if next == Some('<') => {
+                let (delimiter, end) = heredoc_delimiter(chars, i + 2);
+                current.extend(&chars[i..end]);
+                heredocs.extend(delimiter);
+                i = end;
+            }
+            '\n' if !heredocs.is_empty() => {
+                finish(&mut current, out);
+                i = skip_heredoc_bodies(chars, i + 1, &heredocs);
+                heredocs.clear();
+            }
+            '$' | '<' | '>' if next == Some('(') => {
+                let close = matching_paren(chars, i + 1);
+                split_into(&chars[i + 2..close], out);
+                i = close + 1;
+            }
+            '`' => {
+                let close = closing_backtick(chars, i + 1);
+                split_into(&chars[i + 1..close], out);
+                i = close + 1;
+            }
+            '&' if next == Some('&') => {
+                finish(&mut current, out);
+                i += 2;
+            }
+            '&' if prev == Some('>') || next == Some('>') => {
+                current.push(c);
+                i += 1;
+            }
+            '|' if prev == Some('>') => {
+                current.push(c);
+                i += 1;
+            }
+            '|' => {
+                finish(&mut current, out);
+                i += if matches!(next, Some('|' | '&')) {
+                    2
+                } else {
+                    1
+                };
+            }
+            '&' | ';' | '\n' | '(' | ')' => {
+                finish(&mut current, out);
+                i += 1;
+            }
+            _ => {
+                current.push(c);
+                i += 1;
+            }
+        }
+    }
+    finish(&mut current, out);
+}
+
+/// The delimiter of a heredoc whose `<<` ends just before `start`, without
+/// quotes, and the index just past it.
+fn heredoc_delimiter(chars: &[char], start: usize) -> (Option<String>, usize) {
+    let mut i = start;
+    if chars.get(i) == Some(&'-') {
+        i += 1;
+    }
+    while chars.get(i).is_some_and(|c| *c == ' ' || *c == '\t') {
+        i += 1;
+    }
+    let mut delimiter = String::new();
+    while let Some(&c) = chars.get(i) {
+        if c.is_whitespace() || ";|&()<>".contains(c) {
+            break;
+        }
+        if !matches!(c, '\'' | '"' | '\\') {
+            delimiter.push(c);
+        }
+        i += 1;
+    }
+    ((!delimiter.is_empty()).then_some(delimiter), i)
+}
+
+/// Index just past the bodies of `delimiters`' heredocs, which start at
+/// `start` and each end with a line holding only the delimiter.
+fn skip_heredoc_bodies(chars: &[char], start: usize, delimiters: &[String]) -> usize {
+    let mut i = start;
+    for delimiter in delimiters {
+        loop {
+            if i >= chars.len() {
+                return chars.len();
+            }
+            let end = chars[i..]
+                .iter()
+                .position(|c| *c == '\n')
+                .map_or(chars.len(), |n| i + n);
+            let line: String = chars[i..end].iter().collect();
+            i = end + 1;
+            if line.trim_start_matches('\t') == delimiter.as_str() {
+                break;
+            }
+        }
+    }
+    i.min(chars.len())
+}
+
+/// Index of the `)` closing the `(` at `open`, or the end of input.
+fn matching_paren(chars: &[char], open: usize) -> usize {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut i = open;
+    while i < chars.len() {
+        let c = chars[i];
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => i += 1,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// Index of the unescaped backtick closing one opened before `start`, or
+/// the end of input.
+fn closing_backtick(chars: &[char], start: usize) -> usize {
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '`' => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+fn finish(current: &mut String, out: &mut Vec<String>) {
+    let command = strip_prefixes(current);
+    if !command.is_empty() {
+        out.push(command.to_string());
+    }
+    current.clear();
+}
+
+/// Drop leading words that don't name the program being run.
+fn strip_prefixes(command: &str) -> &str {
+    let mut rest = command.trim();
+    let mut after_prefix = false;
+    loop {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..end];
+        let skip = KEYWORDS.contains(&word)
+            || PREFIX_WORDS.contains(&word)
+            || is_assignment(word)
+            || (after_prefix && word.starts_with('-'));
+        if word.is_empty() || !skip {
+            return rest;
+        }
+        after_prefix = PREFIX_WORDS.contains(&word) || (after_prefix && word.starts_with('-'));
+        rest = rest[end..].trim_start();
+    }
+}
+
+/// `NAME=value`, as in `RUST_LOG=debug cargo test`.
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
@@ -2,6 +2,9 @@ use async_trait::async_trait;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
+use crate::cascade::human::SupervisorRecommendation;
+use crate::cascade::precedents::{Precedent, PrecedentSelector};
+use crate::cascade::transcript::{TranscriptEntry, TranscriptLog};
 use crate::config::PolicyConfig;
 use crate::decision::{
     CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
@@ -20,6 +23,9 @@ pub struct SupervisorRequest {
     pub task_description: Option<String>,
     pub agent_prompt_path: Option<String>,
     pub cwd: String,
+    /// Past human decisions on similar calls, most similar first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub precedents: Vec<Precedent>,
 }
 
 /// Response from the supervisor.
@@ -30,6 +36,15 @@ pub struct SupervisorResponse {
     pub reason: String,
 }
 
+/// The raw prompt sent to a supervisor backend and the reply it returned,
+/// captured for transcript logging.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SupervisorExchange {
+    pub prompt: String,
+    /// `None` when the backend failed before replying.
+    pub reply: Option<String>,
+}
+
 /// Pluggable supervisor backend trait.
 #[async_trait]
 pub trait SupervisorBackend: Send + Sync {
@@ -38,9 +53,25 @@ pub trait SupervisorBackend: Send + Sync {
         request: &SupervisorRequest,
         policy: &PolicyConfig,
     ) -> Result<DecisionRecord>;
+
+    /// Like [`evaluate`](Self::evaluate), but also returns the raw exchange.
+    /// Backends that cannot capture it return `None`.
+    async fn evaluate_with_exchange(
+        &self,
+        request: &SupervisorRequest,
+        policy: &PolicyConfig,
+    ) -> (Result<DecisionRecord>, Option<SupervisorExchange>) {
+        (self.evaluate(request, policy).await, None)
+    }
+
+    /// Short backend name recorded in transcripts.
+    fn name(&self) -> &str {
+        "custom"
+    }
 }
 
-/// Unix socket supervisor -- communicates with a Claude Code subagent.
+/// Socket supervisor -- communicates with a Claude Code subagent over a Unix
+/// socket (a named pipe on Windows).
 pub struct UnixSocketSupervisor {
     socket_path: std::path::PathBuf,
     timeout_secs: u64,
@@ -60,28 +91,33 @@ impl SupervisorBackend for UnixSocketSupervisor {
     async fn evaluate(
         &self,
         request: &SupervisorRequest,
-        _policy: &PolicyConfig,
+        policy: &PolicyConfig,
     ) -> Result<DecisionRecord> {
+        self.evaluate_with_exchange(request, policy).await.0
+    }
+
+    async fn evaluate_with_exchange(
+        &self,
+        request: &SupervisorRequest,
+        _policy: &PolicyConfig,
+    ) -> (Result<DecisionRecord>, Option<SupervisorExchange>) {
         use tokio::io::{AsyncReadExt, AsyncWriteExt};
-        use tokio::net::UnixStream;
 
-        if !self.socket_path.exists() {
-            return Err(HookwiseError::SocketNotFound {
-                path: self.socket_path.clone(),
-            });
-        }
+        let request_json = match serde_json::to_string(request) {
+            Ok(json) => json,
+            Err(e) => return (Err(e.into()), None),
+        };
+        let mut exchange = SupervisorExchange {
+            prompt: request_json.clone(),
+            reply: None,
+        };
 
         let timeout = std::time::Duration::from_secs(self.timeout_secs);
 
         let result = tokio::time::timeout(timeout, async {
-            let mut stream = UnixStream::connect(&self.socket_path).await.map_err(|e| {
-                HookwiseError::Ipc {
-                    reason: format!("connect failed: {}", e),
-                }
-            })?;
+            let mut stream = crate::ipc::transport::connect(&self.socket_path).await?;
 
             // Send request as JSON line
-            let request_json = serde_json::to_string(request)?;
             stream
                 .write_all(request_json.as_bytes())
                 .await
@@ -108,28 +144,37 @@ impl SupervisorBackend for UnixSocketSupervisor {
                     reason: format!("read failed: {}", e),
                 })?;
 
-            let response: SupervisorResponse =
-                serde_json::from_slice(&response_buf).map_err(|e| {
-                    HookwiseError::Supervisor {
-                        reason: format!("invalid response: {}", e),
-                    }
-                })?;
-
-            Ok::<SupervisorResponse, HookwiseError>(response)
+            Ok::<Vec<u8>, HookwiseError>(response_buf)
         })
         .await;
 
-        let response = match result {
-            Ok(Ok(r)) => r,
-            Ok(Err(e)) => return Err(e),
+        let response_buf = match result {
+            Ok(Ok(buf)) => buf,
+            Ok(Err(e)) => return (Err(e), Some(exchange)),
             Err(_) => {
-                return Err(HookwiseError::SupervisorTimeout {
-                    timeout_secs: self.timeout_secs,
-                })
+                return (
+                    Err(HookwiseError::SupervisorTimeout {
+                        timeout_secs: self.timeout_secs,
+                    }),
+                    Some(exchange),
+                )
+            }
+        };
+        exchange.reply = Some(String::from_utf8_lossy(&response_buf).into_owned());
+
+        let response: SupervisorResponse = match serde_json::from_slice(&response_buf) {
+            Ok(r) => r,
+            Err(e) => {
+                return (
+                    Err(HookwiseError::Supervisor {
+                        reason: format!("invalid response: {}", e),
+                    }),
+                    Some(exchange),
+                )
             }
         };
 
-        Ok(DecisionRecord {
+        let record = DecisionRecord {
             key: CacheKey {
                 sanitized_input: request.sanitized_input.clone(),
                 tool: request.tool_name.clone(),
@@ -142,16 +187,28 @@ impl SupervisorBackend for UnixSocketSupervisor {
                 reason: response.reason,
                 matched_key: None,
                 similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
             },
             timestamp: Utc::now(),
             scope: ScopeLevel::Project,
             file_path: request.file_path.clone(),
             session_id: request.session_id.clone(),
-        })
+        };
+        (Ok(record), Some(exchange))
+    }
+
+    fn name(&self) -> &str {
+        "socket"
     }
 }
 
 /// API supervisor -- calls the Anthropic API directly.
+#[cfg(feature = "api-supervisor")]
 pub struct ApiSupervisor {
     client: reqwest::Client,
     api_base_url: String,
@@ -160,6 +217,7 @@ pub struct ApiSupervisor {
     max_tokens: u32,
 }
 
+#[cfg(feature = "api-supervisor")]
 impl ApiSupervisor {
     pub fn new(api_base_url: String, api_key: String, model: String, max_tokens: u32) -> Self {
         Self {
@@ -201,6 +259,13 @@ impl ApiSupervisor {
         if let Some(task) = &request.task_description {
             msg.push_str(&format!("\nTask: {}", task));
         }
+        if !request.precedents.is_empty() {
+            msg.push_str("\n\nPast human decisions on similar calls:");
+            for precedent in &request.precedents {
+                msg.push('\n');
+                msg.push_str(&precedent.render());
+            }
+        }
         msg
     }
 
@@ -223,6 +288,7 @@ impl ApiSupervisor {
     }
 }
 
+#[cfg(feature = "api-supervisor")]
 #[async_trait]
 impl SupervisorBackend for ApiSupervisor {
     async fn evaluate(
@@ -230,6 +296,14 @@ impl SupervisorBackend for ApiSupervisor {
         request: &SupervisorRequest,
         policy: &PolicyConfig,
     ) -> Result<DecisionRecord> {
+        self.evaluate_with_exchange(request, policy).await.0
+    }
+
+    async fn evaluate_with_exchange(
+        &self,
+        request: &SupervisorRequest,
+        policy: &PolicyConfig,
+    ) -> (Result<DecisionRecord>, Option<SupervisorExchange>) {
         let system_prompt = self.build_system_prompt(policy);
         let user_message = self.build_user_message(request);
 
@@ -239,8 +313,12 @@ impl SupervisorBackend for ApiSupervisor {
             "system": system_prompt,
             "messages": [{"role": "user", "content": user_message}]
         });
+        let mut exchange = SupervisorExchange {
+            prompt: format!("[system]\n{}\n\n[user]\n{}", system_prompt, user_message),
+            reply: None,
+        };
 
-        let resp = self
+        let resp = match self
             .client
             .post(format!("{}/v1/messages", self.api_base_url))
             .header("x-api-key", &self.api_key)
@@ -249,25 +327,42 @@ impl SupervisorBackend for ApiSupervisor {
             .json(&body)
             .send()
             .await
-            .map_err(|e| HookwiseError::Supervisor {
-                reason: format!("API request failed: {}", e),
-            })?;
+        {
+            Ok(resp) => resp,
+            Err(e) => {
+                return (
+                    Err(HookwiseError::Supervisor {
+                        reason: format!("API request failed: {}", e),
+                    }),
+                    Some(exchange),
+                )
+            }
+        };
 
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
             let body_text = resp.text().await.unwrap_or_default();
-            return Err(HookwiseError::Api {
-                status,
-                body: body_text,
-            });
+            exchange.reply = Some(body_text.clone());
+            return (
+                Err(HookwiseError::Api {
+                    status,
+                    body: body_text,
+                }),
+                Some(exchange),
+            );
         }
 
-        let resp_json: serde_json::Value =
-            resp.json()
-                .await
-                .map_err(|e| HookwiseError::Supervisor {
-                    reason: format!("failed to parse API response: {}", e),
-                })?;
+        let resp_json: serde_json::Value = match resp.json().await {
+            Ok(json) => json,
+            Err(e) => {
+                return (
+                    Err(HookwiseError::Supervisor {
+                        reason: format!("failed to parse API response: {}", e),
+                    }),
+                    Some(exchange),
+                )
+            }
+        };
 
         // Extract text from Anthropic Messages API response
         let text = resp_json["content"]
@@ -275,10 +370,14 @@ impl SupervisorBackend for ApiSupervisor {
             .and_then(|arr| arr.first())
             .and_then(|block| block["text"].as_str())
             .unwrap_or("");
+        exchange.reply = Some(text.to_string());
 
-        let supervisor_response = self.parse_response(text)?;
+        let supervisor_response = match self.parse_response(text) {
+            Ok(r) => r,
+            Err(e) => return (Err(e), Some(exchange)),
+        };
 
-        Ok(DecisionRecord {
+        let record = DecisionRecord {
             key: CacheKey {
                 sanitized_input: request.sanitized_input.clone(),
                 tool: request.tool_name.clone(),
@@ -291,24 +390,99 @@ impl SupervisorBackend for ApiSupervisor {
                 reason: supervisor_response.reason,
                 matched_key: None,
                 similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
             },
             timestamp: Utc::now(),
             scope: ScopeLevel::Project,
             file_path: request.file_path.clone(),
             session_id: request.session_id.clone(),
+        };
+        (Ok(record), Some(exchange))
+    }
+
+    fn name(&self) -> &str {
+        "api"
+    }
+}
+
+/// A backend left out of this build. Every request fails, so the tier
+/// falls through to the human queue.
+pub struct MissingSupervisor {
+    pub what: &'static str,
+    pub feature: &'static str,
+}
+
+#[async_trait]
+impl SupervisorBackend for MissingSupervisor {
+    async fn evaluate(
+        &self,
+        _request: &SupervisorRequest,
+        _policy: &PolicyConfig,
+    ) -> Result<DecisionRecord> {
+        Err(HookwiseError::NotCompiledIn {
+            what: self.what,
+            feature: self.feature,
         })
     }
+
+    fn name(&self) -> &str {
+        "missing"
+    }
 }
 
 /// Wraps a SupervisorBackend as a CascadeTier.
 pub struct SupervisorTier {
     backend: Box<dyn SupervisorBackend>,
     policy: PolicyConfig,
+    transcript: Option<TranscriptLog>,
+    precedents: Option<PrecedentSelector>,
 }
 
 impl SupervisorTier {
     pub fn new(backend: Box<dyn SupervisorBackend>, policy: PolicyConfig) -> Self {
-        Self { backend, policy }
+        Self {
+            backend,
+            policy,
+            transcript: None,
+            precedents: None,
+        }
+    }
+
+    /// Include past human decisions chosen by `selector` in each request.
+    pub fn with_precedents(mut self, selector: PrecedentSelector) -> Self {
+        self.precedents = Some(selector);
+        self
+    }
+
+    /// Log every supervisor exchange to `transcript`.
+    pub fn with_transcript(mut self, transcript: Option<TranscriptLog>) -> Self {
+        self.transcript = transcript;
+        self
+    }
+
+    fn log_exchange(
+        &self,
+        request: &SupervisorRequest,
+        result: &Result<DecisionRecord>,
+        exchange: Option<SupervisorExchange>,
+        latency: std::time::Duration,
+    ) {
+        let Some(log) = &self.transcript else {
+            return;
+        };
+        let exchange = exchange.unwrap_or_else(|| SupervisorExchange {
+            prompt: serde_json::to_string(request).unwrap_or_default(),
+            reply: None,
+        });
+        let entry = TranscriptEntry::new(request, self.backend.name(), exchange, result, latency);
+        if let Err(e) = log.append(entry) {
+            tracing::warn!("failed to write supervisor transcript: {}", e);
+        }
     }
 }
 
@@ -318,6 +492,13 @@ impl crate::cascade::CascadeTier for SupervisorTier {
         &self,
         input: &crate::cascade::CascadeInput,
     ) -> Result<Option<DecisionRecord>> {
+        Ok(self.consult(input).await?.0)
+    }
+
+    async fn consult(
+        &self,
+        input: &crate::cascade::CascadeInput,
+    ) -> Result<(Option<DecisionRecord>, Option<SupervisorRecommendation>)> {
         let role_name = input
             .session
             .role
@@ -332,6 +513,11 @@ impl crate::cascade::CascadeTier for SupervisorTier {
             .map(|r| r.description.clone())
             .unwrap_or_default();
 
+        let precedents = match &self.precedents {
+            Some(selector) => selector.select(input).await,
+            None => Vec::new(),
+        };
+
         let request = SupervisorRequest {
             session_id: String::new(), // Filled by CascadeRunner
             role: role_name,
@@ -346,25 +532,43 @@ impl crate::cascade::CascadeTier for SupervisorTier {
                 .as_ref()
                 .map(|p| p.display().to_string()),
             cwd: String::new(), // Filled by CascadeRunner
+            precedents,
+        };
+
+        let started = std::time::Instant::now();
+        let (result, exchange) = if self.transcript.is_some() {
+            self.backend
+                .evaluate_with_exchange(&request, &self.policy)
+                .await
+        } else {
+            (self.backend.evaluate(&request, &self.policy).await, None)
         };
+        let elapsed = started.elapsed();
+        crate::telemetry::record_supervisor_latency(elapsed, result.is_ok());
+        self.log_exchange(&request, &result, exchange, elapsed);
 
-        let record = match self.backend.evaluate(&request, &self.policy).await {
+        let record = match result {
             Ok(r) => r,
             Err(e) => {
                 eprintln!(
                     "hookwise: supervisor unavailable, falling through ({})",
                     e
                 );
-                return Ok(None);
+                return Ok((None, None));
             }
         };
 
-        // If supervisor has low confidence, return None to escalate to human
+        // If supervisor has low confidence, escalate to human with its answer
         if record.metadata.confidence < self.policy.confidence.project {
-            return Ok(None);
+            let recommendation = SupervisorRecommendation {
+                decision: record.decision,
+                confidence: record.metadata.confidence,
+                reason: record.metadata.reason,
+            };
+            return Ok((None, Some(recommendation)));
         }
 
-        Ok(Some(record))
+        Ok((Some(record), None))
     }
 
     fn tier(&self) -> crate::decision::DecisionTier {
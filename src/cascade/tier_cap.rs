@@ -0,0 +1,59 @@
+//! Per-path ceilings on cascade autonomy (`max_auto_tier` in policy.yml).
+//! Calls touching a capped path skip every automatic tier above the cap,
+//! so only rules and humans decide them.
+
+use std::collections::{BTreeMap, HashMap};
+
+use globset::GlobSet;
+
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::CascadeInput;
+use crate::config::roles::{build_globset, default_categories, expand_macros};
+use crate::config::AutoTier;
+use crate::error::Result;
+
+/// Compiled `max_auto_tier` patterns.
+pub struct TierCaps {
+    caps: Vec<(GlobSet, AutoTier)>,
+    extractor: PathPolicyEngine,
+}
+
+impl TierCaps {
+    /// Compile `max_auto_tier`, expanding `{{category}}` macros against
+    /// `categories` merged over the built-in defaults. None when empty.
+    pub fn compile(
+        config: &BTreeMap<String, AutoTier>,
+        categories: &HashMap<String, Vec<String>>,
+    ) -> Result<Option<Self>> {
+        if config.is_empty() {
+            return Ok(None);
+        }
+        let mut merged = default_categories();
+        merged.extend(categories.clone());
+
+        let mut caps = Vec::new();
+        for (pattern, tier) in config {
+            let patterns = expand_macros(
+                std::slice::from_ref(pattern),
+                &merged,
+                "policy.yml",
+                "max_auto_tier",
+            )?;
+            caps.push((build_globset(&patterns)?, *tier));
+        }
+        Ok(Some(Self {
+            caps,
+            extractor: PathPolicyEngine::new()?,
+        }))
+    }
+
+    /// The lowest cap among the patterns matching any path `input` touches.
+    pub fn cap_for(&self, input: &CascadeInput) -> Option<AutoTier> {
+        let paths = self.extractor.target_paths(input);
+        self.caps
+            .iter()
+            .filter(|(globs, _)| paths.iter().any(|p| globs.is_match(p)))
+            .map(|(_, tier)| *tier)
+            .min()
+    }
+}
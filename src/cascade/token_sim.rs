@@ -1,9 +1,12 @@
+use std::collections::HashMap;
 use std::sync::RwLock;
 
 use async_trait::async_trait;
 use chrono::Utc;
 
-use crate::cascade::{CascadeInput, CascadeTier};
+use crate::cascade::margin::{DenyMargin, MarginVerdict};
+use crate::cascade::{partition_keys, CascadeInput, CascadeTier, PartitionKey};
+use crate::config::TokenMode;
 use crate::decision::{CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier};
 use crate::error::Result;
 
@@ -16,45 +19,137 @@ pub struct TokenEntry {
 }
 
 /// Tier 2a: Token-level Jaccard similarity.
+///
+/// Entries are partitioned by `(role, tool)`; a lookup only scans its own
+/// partition and the wildcard-role one for the same tool.
 pub struct TokenJaccard {
-    entries: RwLock<Vec<TokenEntry>>,
+    partitions: RwLock<HashMap<PartitionKey, Vec<TokenEntry>>>,
+    /// Number of entries containing each token, across all partitions
+    /// (for `TokenMode::Tfidf`).
+    doc_freq: RwLock<HashMap<String, usize>>,
     threshold: f64,
     min_tokens: usize,
+    mode: TokenMode,
+    deny_margin: DenyMargin,
 }
 
 impl TokenJaccard {
     pub fn new(threshold: f64, min_tokens: usize) -> Self {
         Self {
-            entries: RwLock::new(Vec::new()),
+            partitions: RwLock::new(HashMap::new()),
+            doc_freq: RwLock::new(HashMap::new()),
             threshold,
             min_tokens,
+            mode: TokenMode::default(),
+            deny_margin: DenyMargin::default(),
         }
     }
 
+    /// Select how token overlap is scored.
+    pub fn with_mode(mut self, mode: TokenMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set how close a cached deny may score before a match is withheld.
+    pub fn with_deny_margin(mut self, deny_margin: DenyMargin) -> Self {
+        self.deny_margin = deny_margin;
+        self
+    }
+
     /// Load entries from cached decisions.
     pub fn load_from(&self, records: &[DecisionRecord]) {
-        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
         for record in records {
-            let tokens = Self::tokenize(&record.key.sanitized_input);
-            entries.push(TokenEntry {
-                tokens,
-                cache_key: record.key.clone(),
-                record: record.clone(),
-            });
+            self.insert(record);
         }
     }
 
     /// Add a single entry.
     pub fn insert(&self, record: &DecisionRecord) {
         let tokens = Self::tokenize(&record.key.sanitized_input);
-        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-        entries.push(TokenEntry {
+        let mut partitions = self.partitions.write().unwrap_or_else(|e| e.into_inner());
+        let mut doc_freq = self.doc_freq.write().unwrap_or_else(|e| e.into_inner());
+        for token in &tokens {
+            *doc_freq.entry(token.clone()).or_insert(0) += 1;
+        }
+        let key = (record.key.role.clone(), record.key.tool.clone());
+        partitions.entry(key).or_default().push(TokenEntry {
             tokens,
             cache_key: record.key.clone(),
             record: record.clone(),
         });
     }
 
+    /// Score two sorted token slices according to the configured mode.
+    pub fn similarity(&self, a: &[String], b: &[String]) -> f64 {
+        let corpus_size = self.len();
+        let doc_freq = self.doc_freq.read().unwrap_or_else(|e| e.into_inner());
+        self.score(a, b, corpus_size, &doc_freq)
+    }
+
+    fn score(
+        &self,
+        a: &[String],
+        b: &[String],
+        corpus_size: usize,
+        doc_freq: &HashMap<String, usize>,
+    ) -> f64 {
+        match self.mode {
+            TokenMode::Jaccard => Self::jaccard_coefficient(a, b),
+            TokenMode::Tfidf => Self::weighted_jaccard(a, b, |token| {
+                idf(corpus_size, doc_freq.get(token).copied().unwrap_or(0))
+            }),
+        }
+    }
+
+    /// Jaccard coefficient where each token counts with `weight(token)`:
+    /// sum of intersection weights over sum of union weights.
+    pub fn weighted_jaccard(a: &[String], b: &[String], weight: impl Fn(&str) -> f64) -> f64 {
+        if a.is_empty() && b.is_empty() {
+            return 1.0;
+        }
+        let (mut shared, mut union) = (0.0, 0.0);
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            let order = match (a.get(i), b.get(j)) {
+                (Some(x), Some(y)) => x.cmp(y),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, _) => std::cmp::Ordering::Greater,
+            };
+            match order {
+                std::cmp::Ordering::Less => {
+                    union += weight(&a[i]);
+                    i += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    union += weight(&b[j]);
+                    j += 1;
+                }
+                std::cmp::Ordering::Equal => {
+                    let w = weight(&a[i]);
+                    shared += w;
+                    union += w;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        if union == 0.0 {
+            return 0.0;
+        }
+        shared / union
+    }
+
+    fn rebuild_doc_freq(&self, partitions: &HashMap<PartitionKey, Vec<TokenEntry>>) {
+        let mut doc_freq = self.doc_freq.write().unwrap_or_else(|e| e.into_inner());
+        doc_freq.clear();
+        for entry in partitions.values().flatten() {
+            for token in &entry.tokens {
+                *doc_freq.entry(token.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
     /// Tokenize an input string: split on whitespace + punctuation, lowercase,
     /// deduplicate, sort.
     pub fn tokenize(input: &str) -> Vec<String> {
@@ -99,19 +194,58 @@ impl TokenJaccard {
         count
     }
 
+    /// Every entry a call by `role` with `tool` could match, scored against
+    /// `input` and sorted most similar first, regardless of threshold or
+    /// decision. Used to pick supervisor precedents.
+    pub fn ranked(&self, input: &str, role: &str, tool: &str) -> Vec<(f64, DecisionRecord)> {
+        let query_tokens = Self::tokenize(input);
+        let partitions = self.partitions.read().unwrap_or_else(|e| e.into_inner());
+        let doc_freq = self.doc_freq.read().unwrap_or_else(|e| e.into_inner());
+        let corpus_size: usize = partitions.values().map(Vec::len).sum();
+
+        let mut scored: Vec<(f64, DecisionRecord)> = partition_keys(role, tool)
+            .iter()
+            .filter_map(|key| partitions.get(key))
+            .flatten()
+            .map(|entry| {
+                let score = self.score(&query_tokens, &entry.tokens, corpus_size, &doc_freq);
+                (score, entry.record.clone())
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored
+    }
+
+    /// Number of non-empty `(role, tool)` partitions.
+    pub fn partition_count(&self) -> usize {
+        let partitions = self.partitions.read().unwrap_or_else(|e| e.into_inner());
+        partitions.values().filter(|p| !p.is_empty()).count()
+    }
+
     /// Remove all entries for a specific role.
     pub fn invalidate_role(&self, role: &str) {
-        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-        entries.retain(|e| e.cache_key.role != role);
+        let mut partitions = self.partitions.write().unwrap_or_else(|e| e.into_inner());
+        partitions.retain(|(r, _), _| r != role);
+        self.rebuild_doc_freq(&partitions);
     }
 
     /// Remove all entries.
     pub fn invalidate_all(&self) {
-        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
-        entries.clear();
+        let mut partitions = self.partitions.write().unwrap_or_else(|e| e.into_inner());
+        partitions.clear();
+        self.doc_freq
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
     }
 }
 
+/// Smoothed inverse document frequency: tokens absent from the corpus get
+/// the highest weight, tokens present in every entry the lowest (1.0).
+fn idf(corpus_size: usize, doc_freq: usize) -> f64 {
+    ((1 + corpus_size) as f64 / (1 + doc_freq) as f64).ln() + 1.0
+}
+
 #[async_trait]
 impl CascadeTier for TokenJaccard {
     async fn evaluate(&self, input: &CascadeInput) -> Result<Option<DecisionRecord>> {
@@ -129,64 +263,83 @@ impl CascadeTier for TokenJaccard {
             .map(|r| r.name.as_str())
             .unwrap_or("*");
 
-        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        let partitions = self.partitions.read().unwrap_or_else(|e| e.into_inner());
+        let doc_freq = self.doc_freq.read().unwrap_or_else(|e| e.into_inner());
+        let corpus_size: usize = partitions.values().map(Vec::len).sum();
 
         let mut best_match: Option<(f64, &TokenEntry)> = None;
+        let mut best_deny: Option<f64> = None;
 
-        for entry in entries.iter() {
-            // Only match same role or wildcard entries
-            if entry.cache_key.role != role_name && entry.cache_key.role != "*" {
-                continue;
-            }
-            // Only match same tool
-            if entry.cache_key.tool != input.tool_name {
+        // Only the same role (or wildcard) with the same tool is scanned
+        let keys = partition_keys(role_name, &input.tool_name);
+        let candidates = keys.iter().filter_map(|key| partitions.get(key)).flatten();
+        for entry in candidates {
+            let score = self.score(&query_tokens, &entry.tokens, corpus_size, &doc_freq);
+
+            // Denies are negative examples: they never resolve, but they
+            // veto allow/ask matches that don't clear the margin.
+            if entry.record.decision == Decision::Deny {
+                best_deny = Some(best_deny.map_or(score, |d: f64| d.max(score)));
                 continue;
             }
-
-            let score = Self::jaccard_coefficient(&query_tokens, &entry.tokens);
-
             if score >= self.threshold && best_match.as_ref().is_none_or(|(best, _)| score > *best)
             {
                 best_match = Some((score, entry));
             }
         }
 
-        match best_match {
-            Some((score, entry)) => {
-                // Similarity behavior:
-                // - allow -> auto-approve
-                // - deny -> fall through (similarity never auto-denies)
-                // - ask -> return ask (escalate)
-                match entry.record.decision {
-                    Decision::Deny => Ok(None), // Never auto-deny from similarity
-                    Decision::Allow | Decision::Ask => {
-                        Ok(Some(DecisionRecord {
-                            key: CacheKey {
-                                sanitized_input: input.sanitized_input.clone(),
-                                tool: input.tool_name.clone(),
-                                role: role_name.to_string(),
-                            },
-                            decision: entry.record.decision,
-                            metadata: DecisionMetadata {
-                                tier: DecisionTier::TokenJaccard,
-                                confidence: score,
-                                reason: format!(
-                                    "token Jaccard similarity {:.3} >= {:.3} with cached {}",
-                                    score, self.threshold, entry.record.decision
-                                ),
-                                matched_key: Some(entry.cache_key.clone()),
-                                similarity_score: Some(score),
-                            },
-                            timestamp: Utc::now(),
-                            scope: entry.record.scope,
-                            file_path: input.file_path.clone(),
-                            session_id: String::new(), // Filled by CascadeRunner
-                        }))
-                    }
-                }
-            }
-            None => Ok(None), // No match above threshold
-        }
+        // No allow/ask match above threshold
+        let Some((score, entry)) = best_match else {
+            return Ok(None);
+        };
+
+        let (decision, reason) = match self.deny_margin.verdict(score, best_deny) {
+            MarginVerdict::FallThrough => return Ok(None),
+            MarginVerdict::Resolve => (
+                entry.record.decision,
+                format!(
+                    "token {} similarity {:.3} >= {:.3} with cached {}",
+                    self.mode, score, self.threshold, entry.record.decision
+                ),
+            ),
+            MarginVerdict::Ask => (
+                Decision::Ask,
+                format!(
+                    "token {} similarity {:.3} with cached {} is within {:.3} of a cached deny ({:.3})",
+                    self.mode,
+                    score,
+                    entry.record.decision,
+                    self.deny_margin.margin,
+                    best_deny.unwrap_or_default()
+                ),
+            ),
+        };
+
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: role_name.to_string(),
+            },
+            decision,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::TokenJaccard,
+                confidence: score,
+                reason,
+                matched_key: Some(entry.cache_key.clone()),
+                similarity_score: Some(score),
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: Utc::now(),
+            scope: entry.record.scope,
+            file_path: input.file_path.clone(),
+            session_id: String::new(), // Filled by CascadeRunner
+        }))
     }
 
     fn tier(&self) -> DecisionTier {
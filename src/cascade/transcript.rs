@@ -0,0 +1,178 @@
+//! Opt-in supervisor transcript log: the exact prompt sent to the supervisor
+//! and the reply it returned, for debugging misjudgments.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cascade::supervisor::{SupervisorExchange, SupervisorRequest};
+use crate::config::SupervisorTranscriptConfig;
+use crate::decision::{Decision, DecisionRecord};
+use crate::error::Result;
+use crate::sanitize::regex_san::RegexSanitizer;
+use crate::sanitize::{SanitizePipeline, Sanitizer};
+
+/// One supervisor exchange, appended to the transcript log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub backend: String,
+    pub role: String,
+    pub tool_name: String,
+    pub file_path: Option<String>,
+    pub prompt: String,
+    pub reply: Option<String>,
+    pub decision: Option<Decision>,
+    /// Id of the resulting decision record, for `hookwise feedback`.
+    #[serde(default)]
+    pub decision_id: Option<String>,
+    pub confidence: Option<f64>,
+    pub reason: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+impl TranscriptEntry {
+    /// Build an entry for a completed (or failed) supervisor call.
+    pub fn new(
+        request: &SupervisorRequest,
+        backend: &str,
+        exchange: SupervisorExchange,
+        result: &Result<DecisionRecord>,
+        latency: std::time::Duration,
+    ) -> Self {
+        let timestamp = Utc::now();
+        let (decision, decision_id, confidence, reason, error) = match result {
+            Ok(record) => (
+                Some(record.decision),
+                Some(record.id()),
+                Some(record.metadata.confidence),
+                Some(record.metadata.reason.clone()),
+                None,
+            ),
+            Err(e) => (None, None, None, None, Some(e.to_string())),
+        };
+        Self {
+            id: format!(
+                "sup-{}-{}-{}",
+                request.role,
+                request.tool_name,
+                timestamp.timestamp_millis()
+            ),
+            timestamp,
+            backend: backend.to_string(),
+            role: request.role.clone(),
+            tool_name: request.tool_name.clone(),
+            file_path: request.file_path.clone(),
+            prompt: exchange.prompt,
+            reply: exchange.reply,
+            decision,
+            decision_id,
+            confidence,
+            reason,
+            error,
+            latency_ms: latency.as_millis() as u64,
+        }
+    }
+}
+
+/// Returns the transcript log path under a project's `.hookwise/` directory.
+/// Lives in `.user/` since transcripts are local debugging data.
+pub fn transcript_log_path(hookwise_dir: &Path) -> PathBuf {
+    hookwise_dir
+        .join(".user")
+        .join("supervisor-transcript.jsonl")
+}
+
+/// Path of the single rotated log kept once the live log reaches its cap.
+pub fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+/// Appends redacted, size-capped supervisor transcripts.
+pub struct TranscriptLog {
+    path: PathBuf,
+    max_bytes: u64,
+    pipeline: SanitizePipeline,
+    extra: Option<RegexSanitizer>,
+}
+
+impl TranscriptLog {
+    /// Build a transcript log from config. Returns `None` unless enabled.
+    pub fn from_config(path: PathBuf, config: &SupervisorTranscriptConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let extra = if config.redact_patterns.is_empty() {
+            None
+        } else {
+            Some(RegexSanitizer::new(config.redact_patterns.clone())?)
+        };
+        Ok(Some(Self {
+            path,
+            max_bytes: config.max_bytes,
+            pipeline: SanitizePipeline::default_pipeline(),
+            extra,
+        }))
+    }
+
+    /// Run the extra redaction pass: the default pipeline again (supervisor
+    /// prompts include role descriptions and task text that were never
+    /// sanitized) plus the configured `redact_patterns`.
+    pub fn redact(&self, text: &str) -> String {
+        let sanitized = self.pipeline.sanitize(text);
+        match &self.extra {
+            Some(extra) => extra.sanitize(&sanitized),
+            None => sanitized,
+        }
+    }
+
+    /// Redact and append an entry. When the log would exceed `max_bytes` it
+    /// is rotated to `<path>.1`, replacing any previous rotation.
+    pub fn append(&self, mut entry: TranscriptEntry) -> Result<()> {
+        entry.prompt = self.redact(&entry.prompt);
+        entry.reply = entry.reply.map(|r| self.redact(&r));
+        entry.reason = entry.reason.map(|r| self.redact(&r));
+        entry.error = entry.error.map(|e| self.redact(&e));
+        let line = serde_json::to_string(&entry)?;
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let current = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if current > 0 && current + line.len() as u64 + 1 > self.max_bytes {
+            std::fs::rename(&self.path, rotated_path(&self.path))?;
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+/// Load all transcript entries, oldest first, including the rotated log.
+pub fn load_transcripts(path: &Path) -> Vec<TranscriptEntry> {
+    [rotated_path(path), path.to_path_buf()]
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .flat_map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect::<Vec<TranscriptEntry>>()
+        })
+        .collect()
+}
+
+/// Find a transcript entry by id.
+pub fn find_transcript(path: &Path, id: &str) -> Option<TranscriptEntry> {
+    load_transcripts(path).into_iter().find(|e| e.id == id)
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+use crate::audit::{self, AuditSink};
+use crate::error::Result;
+
+/// Check the audit log's hash chain: the configured `audit.path`, else the
+/// audit-mode log, unless `path` is given. Exits 1 if the chain breaks or
+/// `since` is no longer in it.
+pub async fn run_verify(path: Option<&Path>, since: Option<&str>) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let path = match path {
+        Some(path) => cwd.join(path),
+        None => {
+            let policy = crate::cli::queue::load_policy();
+            AuditSink::for_audit_mode(&policy.audit, &cwd)
+                .path()
+                .to_path_buf()
+        }
+    };
+
+    let report = audit::verify(&path, since)?;
+    println!(
+        "{}: {} chained entr{}",
+        path.display(),
+        report.entries,
+        if report.entries == 1 { "y" } else { "ies" }
+    );
+    if report.unchained > 0 {
+        println!(
+            "  {} earlier line(s) were written before the log was chained",
+            report.unchained
+        );
+    }
+    if let Some(head) = &report.head {
+        println!("  head: {}", head);
+    }
+    if since.is_none() {
+        println!(
+            "  without --since, a truncated or rewritten log can't be told from an intact one"
+        );
+    }
+
+    let mut ok = true;
+    if let Some(broken) = &report.broken {
+        eprintln!(
+            "hookwise: audit log tampered with at line {}: {}",
+            broken.line, broken.reason
+        );
+        ok = false;
+    }
+    if report.missing_since {
+        eprintln!(
+            "hookwise: no entry has hash {}; the log was truncated or rewritten after it",
+            since.unwrap_or_default()
+        );
+        ok = false;
+    }
+    if !ok {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -1,21 +1,48 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::cascade::embed_sim::EmbeddingSimilarity;
+use crate::cascade::margin::DenyMargin;
 use crate::cascade::token_sim::TokenJaccard;
-use crate::config::PolicyConfig;
+use crate::config::{GlobalConfig, PolicyConfig};
 use crate::error::Result;
 use crate::scope::ScopeLevel;
 use crate::storage::jsonl::JsonlStorage;
 use crate::storage::StorageBackend;
 
+use super::status::format_size;
+
 /// Rebuild vector indexes from rules.
 pub async fn run_build() -> Result<()> {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     let project_root = cwd.join(".hookwise");
-    let global_root = dirs_global();
+    let global_root = dirs_rules();
     let policy = PolicyConfig::load_project(&cwd)?;
 
-    let storage = JsonlStorage::new(project_root, global_root, None);
+    let index_dir = project_root.join(".index");
+    let storage =
+        JsonlStorage::new(project_root, global_root, None).with_expiry(policy.cache.clone());
+
+    // Compact: drop records past their scope's max age
+    for scope in [ScopeLevel::Project, ScopeLevel::User, ScopeLevel::Org] {
+        let removed = storage.compact(scope)?;
+        if removed > 0 {
+            eprintln!(
+                "hookwise: removed {} expired decision(s) at scope '{}'",
+                removed, scope
+            );
+        }
+    }
+
+    rebuild_indexes(&policy, &storage, &index_dir).await
+}
+
+/// Rebuild the token and embedding indexes from the project's rules,
+/// re-embedding every rule into `index_dir`.
+pub(super) async fn rebuild_indexes(
+    policy: &PolicyConfig,
+    storage: &JsonlStorage,
+    index_dir: &Path,
+) -> Result<()> {
     let decisions = storage.load_decisions(ScopeLevel::Project)?;
 
     eprintln!(
@@ -27,17 +54,26 @@ pub async fn run_build() -> Result<()> {
     let token_jaccard = TokenJaccard::new(
         policy.similarity.jaccard_threshold,
         policy.similarity.jaccard_min_tokens,
-    );
+    )
+    .with_mode(policy.similarity.token_mode)
+    .with_deny_margin(DenyMargin::from_config(&policy.similarity));
     token_jaccard.load_from(&decisions);
-    eprintln!("  Token Jaccard: loaded {} entries", decisions.len());
+    eprintln!(
+        "  Token {}: loaded {} entries",
+        policy.similarity.token_mode,
+        decisions.len()
+    );
 
     // Rebuild embedding similarity index
-    match EmbeddingSimilarity::new("default", policy.similarity.embedding_threshold) {
+    let embedding_spec = GlobalConfig::embedding_spec();
+    match EmbeddingSimilarity::new(&embedding_spec, policy.similarity.embedding_threshold) {
         Ok(es) => {
-            es.build_index(&decisions)?;
+            let es = es.with_store(index_dir.to_path_buf());
+            es.build_index(&decisions).await?;
             eprintln!(
-                "  Embedding HNSW: built index with {} entries",
-                decisions.len()
+                "  Embedding HNSW: built index with {} entries ({})",
+                decisions.len(),
+                index_dir.display()
             );
         }
         Err(e) => {
@@ -53,7 +89,7 @@ pub async fn run_build() -> Result<()> {
 pub async fn run_invalidate(role: Option<&str>, scope: Option<&str>, all: bool) -> Result<()> {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     let project_root = cwd.join(".hookwise");
-    let global_root = dirs_global();
+    let global_root = dirs_rules();
 
     let storage = JsonlStorage::new(project_root, global_root, None);
 
@@ -85,6 +121,125 @@ pub async fn run_invalidate(role: Option<&str>, scope: Option<&str>, all: bool)
     Ok(())
 }
 
-fn dirs_global() -> PathBuf {
-    crate::config::dirs_global()
+/// Drop duplicate records from the rule files, keeping the one per cache
+/// key that scope resolution uses.
+pub async fn run_compact(scope: Option<&str>, archive: bool, dry_run: bool) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let storage = JsonlStorage::new(cwd.join(".hookwise"), dirs_rules(), None);
+
+    for scope in rule_scopes(scope)? {
+        let report = storage.deduplicate(scope, archive, dry_run)?;
+        let verb = if dry_run { "would remove" } else { "removed" };
+        println!(
+            "{}: {} {} of {} record(s), reclaiming {}",
+            report.scope,
+            verb,
+            report.records_removed,
+            report.records_before,
+            format_size(report.bytes_reclaimed())
+        );
+        if let Some(path) = &report.archived {
+            println!("  archived to {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite each scope's records written by an older hookwise at the
+/// current schema.
+pub async fn run_storage_upgrade(scope: Option<&str>, dry_run: bool) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let storage = JsonlStorage::new(cwd.join(".hookwise"), dirs_rules(), None);
+
+    for scope in rule_scopes(scope)? {
+        let report = storage.upgrade(scope, dry_run)?;
+        let verb = if dry_run { "would upgrade" } else { "upgraded" };
+        println!(
+            "{}: {} {} record(s) to schema {}, {} already current",
+            report.scope,
+            verb,
+            report.upgraded,
+            crate::decision::SCHEMA_VERSION,
+            report.current
+        );
+        if report.unreadable > 0 {
+            println!(
+                "  left {} line(s) this hookwise can't read; upgrade hookwise to rewrite them",
+                report.unreadable
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The scopes a rule file command works on: `scope`, or all three.
+fn rule_scopes(scope: Option<&str>) -> Result<Vec<ScopeLevel>> {
+    Ok(match scope {
+        // Role rules live in the project files
+        Some(s) => vec![match s
+            .parse::<ScopeLevel>()
+            .map_err(|e| crate::error::HookwiseError::InvalidPolicy { reason: e })?
+        {
+            ScopeLevel::Role => ScopeLevel::Project,
+            scope => scope,
+        }],
+        None => vec![ScopeLevel::Project, ScopeLevel::User, ScopeLevel::Org],
+    })
+}
+
+/// Delete decision records older than `older_than` seconds, else each
+/// scope's `storage.retention_days`, and rebuild the indexes if any went.
+/// Scopes without either are left alone.
+pub async fn run_prune_decisions(
+    older_than: Option<u64>,
+    archive: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let project_root = cwd.join(".hookwise");
+    let policy = PolicyConfig::load_project(&cwd)?;
+    let storage = JsonlStorage::new(project_root.clone(), dirs_rules(), None)
+        .with_expiry(policy.cache.clone());
+    let now = chrono::Utc::now();
+
+    let mut removed = 0;
+    for scope in [ScopeLevel::Project, ScopeLevel::User, ScopeLevel::Org] {
+        let max_age = match older_than {
+            Some(secs) => i64::try_from(secs)
+                .ok()
+                .and_then(chrono::Duration::try_seconds)
+                .unwrap_or(chrono::Duration::MAX),
+            None => match policy.storage.retention(scope) {
+                Some(retention) => retention,
+                None => continue,
+            },
+        };
+        let cutoff = now
+            .checked_sub_signed(max_age)
+            .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC);
+        let report = storage.prune_older_than(scope, cutoff, archive, dry_run)?;
+        let verb = if dry_run { "would remove" } else { "removed" };
+        println!(
+            "{}: {} {} of {} record(s) written before {}, reclaiming {}",
+            report.scope,
+            verb,
+            report.records_removed,
+            report.records_before,
+            cutoff.format("%Y-%m-%d %H:%M UTC"),
+            format_size(report.bytes_reclaimed())
+        );
+        if let Some(path) = &report.archived {
+            println!("  archived to {}", path.display());
+        }
+        removed += report.records_removed;
+    }
+
+    if removed > 0 && !dry_run {
+        rebuild_indexes(&policy, &storage, &project_root.join(".index")).await?;
+    }
+    Ok(())
+}
+
+fn dirs_rules() -> PathBuf {
+    crate::config::dirs_rules()
 }
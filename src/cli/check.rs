@@ -1,25 +1,57 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::cascade::cache::ExactCache;
+use crate::audit::{AuditEvent, AuditSink};
+use crate::cascade::cache::{CommitKeys, ExactCache};
+use crate::cascade::cloud::CloudPolicy;
+use crate::cascade::command_risk::CommandRiskScorer;
+use crate::cascade::content::ContentInspection;
+use crate::cascade::deadline::{self, deadline_log_path, DeadlineEvent};
 use crate::cascade::embed_sim::EmbeddingSimilarity;
-use crate::cascade::human::{DecisionQueue, HumanTier};
+use crate::cascade::ephemeral::EphemeralPaths;
+use crate::cascade::exfil::ExfiltrationGuard;
+use crate::cascade::git::GitPolicy;
+use crate::cascade::hints::HintTable;
+use crate::cascade::human::{pending_queue_path, sla_log_path, DecisionQueue, HumanTier};
+use crate::cascade::locks::PathLocks;
+use crate::cascade::margin::DenyMargin;
+use crate::cascade::messages::MessageTemplates;
+use crate::cascade::packages::PackageGate;
 use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::precedents::PrecedentSelector;
+use crate::cascade::risk::RiskTracker;
+#[cfg(not(feature = "api-supervisor"))]
+use crate::cascade::supervisor::MissingSupervisor;
 use crate::cascade::supervisor::{SupervisorTier, UnixSocketSupervisor};
+use crate::cascade::tier_cap::TierCaps;
 use crate::cascade::token_sim::TokenJaccard;
-use crate::cascade::CascadeRunner;
-use crate::config::{PolicyConfig, SupervisorConfig};
-use crate::decision::Decision;
-use crate::error::Result;
-use crate::hook_io::{self, HookFormat};
+use crate::cascade::transcript::{transcript_log_path, TranscriptLog};
+use crate::cascade::{cache_input, CascadeRunner};
+use crate::config::{
+    default_socket_path, GlobalConfig, GlobalDirs, OrgPolicyConfig, Persistence, PolicyConfig,
+    SessionMode, SupervisorConfig,
+};
+use crate::decision::{
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+};
+use crate::error::{HookwiseError, Result};
+use crate::fault::{self, FailingStorage, FailingSupervisor, Fault, FAULT_ENV};
+use crate::feedback::{feedback_log_path, load_feedback};
+use crate::hook_io::{self, HookFormat, HookInput};
+use crate::notify::Notifier;
+use crate::rollout::{self, rollout_log_path, Rollout, RolloutEvent};
 use crate::sanitize::SanitizePipeline;
-use crate::session::SessionManager;
+use crate::session::{SessionContext, SessionManager};
 use crate::storage::jsonl::JsonlStorage;
-use crate::storage::StorageBackend;
+use crate::storage::memory::EphemeralStorage;
+use crate::storage::remote::{OrgStore, RemoteOrgStorage};
+use crate::storage::{ensure_writable, StorageBackend};
 
 /// Run the `check` subcommand (hook mode).
 /// Reads JSON from stdin, runs the cascade, writes JSON to stdout.
-pub async fn run(format: HookFormat) -> Result<()> {
+pub async fn run(format: HookFormat, inject_failure: &[Fault]) -> Result<()> {
+    let faults = fault::injected(inject_failure, std::env::var(FAULT_ENV).ok().as_deref())?;
+
     // 1. Read hook input from stdin
     let input = hook_io::read_hook_input()?;
 
@@ -32,12 +64,34 @@ pub async fn run(format: HookFormat) -> Result<()> {
     let normalizer = roles.normalizer().ok();
     let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
 
+    // Cached result of `hookwise maintain`; never a network call here
+    #[cfg(feature = "self-update")]
+    super::self_update::check_update_hint();
+
     // 3. Get session context
     let session_mgr = SessionManager::new(team_id.as_deref());
 
+    // Lapsed `disable --for` windows re-enable before the disabled check
+    if let Err(e) = super::register::expire_disables(&session_mgr, &policy, &cwd_path) {
+        eprintln!("hookwise: could not expire disables ({})", e);
+    }
+
     // Check if session is disabled
     if session_mgr.is_disabled(&input.session_id) {
+        if let Err(e) =
+            super::register::remind_disabled(&session_mgr, &policy, &input.session_id).await
+        {
+            eprintln!("hookwise: disable reminder failed ({})", e);
+        }
         // Disabled sessions always allow
+        audit_fallback(
+            &input,
+            format,
+            &policy,
+            None,
+            Decision::Allow,
+            "session disabled",
+        );
         hook_io::write_hook_output(Decision::Allow, format)?;
         return Ok(());
     }
@@ -50,8 +104,16 @@ pub async fn run(format: HookFormat) -> Result<()> {
         {
             // Registration timeout — write deny JSON so callers always get valid output
             eprintln!("hookwise: {}", e);
+            audit_fallback(
+                &input,
+                format,
+                &policy,
+                None,
+                Decision::Deny,
+                &format!("registration timed out: {}", e),
+            );
             hook_io::write_hook_output(Decision::Deny, format)?;
-            std::process::exit(hook_io::deny_exit_code(format));
+            exit_denied(format);
         }
     }
 
@@ -59,57 +121,222 @@ pub async fn run(format: HookFormat) -> Result<()> {
 
     // If session has no role, deny (unregistered)
     if session.role.is_none() && !session.disabled {
+        audit_fallback(
+            &input,
+            format,
+            &policy,
+            Some(&session),
+            Decision::Deny,
+            "session has no role",
+        );
         hook_io::write_hook_output(Decision::Deny, format)?;
         return Ok(());
     }
 
     // 4. Build cascade runner
     let project_root = cwd_path.join(".hookwise");
-    let global_root = dirs_global();
+    let global_root = dirs_rules();
 
-    let storage = JsonlStorage::new(
+    let jsonl = JsonlStorage::new(
         project_root.clone(),
         global_root.clone(),
         Some(session.org.clone()),
-    );
+    )
+    .with_expiry(policy.cache.clone());
+    // Read-only checkouts: degrade to in-memory state (or deny, per read_only)
+    let state_dir = GlobalDirs::from_env().state;
+    let queue_path = pending_queue_path();
+    let (rules_writable, queue_writable) = match (
+        ensure_writable(&project_root, "rule storage", policy.read_only, &state_dir),
+        ensure_writable(&queue_path, "decision queue", policy.read_only, &state_dir),
+    ) {
+        (Ok(rules), Ok(queue)) => (rules, queue),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("hookwise: {}", e);
+            audit_fallback(
+                &input,
+                format,
+                &policy,
+                Some(&session),
+                Decision::Deny,
+                &e.to_string(),
+            );
+            hook_io::write_hook_output(Decision::Deny, format)?;
+            exit_denied(format);
+        }
+    };
+    // With an org store, the org directory caches the bucket; refresh it
+    // before anything reads the org policy or rules
+    let org_store = match GlobalConfig::load() {
+        Ok(config) => config.and_then(|c| c.org_store),
+        Err(e) => {
+            tracing::warn!("ignoring unreadable global config: {}", e);
+            None
+        }
+    }
+    .map(|config| OrgStore::new(config, &global_root, &session.org));
+    if let Some(store) = &org_store {
+        store.refresh_if_stale().await;
+    }
+    let ephemeral = policy.persistence == Persistence::Ephemeral || !rules_writable;
+    // Ephemeral sessions leave the rule files as they found them
+    let jsonl = jsonl.with_auto_upgrade(policy.storage.auto_upgrade && !ephemeral);
+    let storage: Box<dyn StorageBackend> = if faults.contains(&Fault::Storage) {
+        Box::new(FailingStorage)
+    } else if ephemeral {
+        Box::new(EphemeralStorage::new(Box::new(jsonl)))
+    } else if let Some(store) = org_store {
+        let identity = crate::identity::current(&cwd_path);
+        Box::new(RemoteOrgStorage::new(Box::new(jsonl), store, identity))
+    } else {
+        Box::new(jsonl)
+    };
+
+    // Expire stale queue entries and resolve ones whose waiting process died
+    let decision_queue = Arc::new(if queue_writable {
+        DecisionQueue::new()
+    } else {
+        DecisionQueue::memory_only()
+    });
+    if let Err(e) = crate::cli::queue::prune_and_record(
+        &decision_queue,
+        storage.as_ref(),
+        &policy,
+        &cwd_path,
+        false,
+    ) {
+        eprintln!("hookwise: queue prune failed ({})", e);
+    }
 
-    // Load existing decisions for caches
-    let all_decisions = storage.load_decisions(crate::scope::ScopeLevel::Project)?;
+    // Load existing decisions for caches. Org rules under a canary rollout
+    // are only enforced in canary sessions; control sessions shadow them.
+    let org_policy = OrgPolicyConfig::load(&global_root, &session.org)?;
+    let rollout = Rollout::for_session(&org_policy.rollout, &input.session_id);
+    let (mut all_decisions, org_rules) = match storage
+        .load_decisions(crate::scope::ScopeLevel::Project)
+        .and_then(|project| {
+            Ok((
+                project,
+                storage.load_decisions(crate::scope::ScopeLevel::Org)?,
+            ))
+        }) {
+        Ok(rules) => rules,
+        Err(e) => {
+            // Without the stored rules the cascade can't be trusted to
+            // enforce them; fail closed like any other cascade error.
+            eprintln!(
+                "hookwise: rule storage unavailable, defaulting to deny ({})",
+                e
+            );
+            audit_fallback(
+                &input,
+                format,
+                &policy,
+                Some(&session),
+                Decision::Deny,
+                &format!("rule storage unavailable: {}", e),
+            );
+            hook_io::write_hook_output(Decision::Deny, format)?;
+            exit_denied(format);
+        }
+    };
+    let rolled_out = match &rollout {
+        Some(rollout) => {
+            let (enforced, rolled_out) = rollout.split(org_rules);
+            all_decisions.extend(enforced);
+            rolled_out
+        }
+        None => {
+            all_decisions.extend(org_rules);
+            Vec::new()
+        }
+    };
 
     // Build tiers
-    let path_policy = PathPolicyEngine::new()?;
-    let exact_cache = Arc::new(ExactCache::new());
+    let path_policy = PathPolicyEngine::new()?
+        .with_precedence(policy.path_precedence)
+        .with_containers(policy.containers);
+    let exact_cache = Arc::new(
+        ExactCache::new()
+            .with_expiry(policy.cache.clone())
+            .with_max_entries(policy.cache.max_entries),
+    );
     exact_cache.load_from(all_decisions.clone());
+    crate::telemetry::record_cache_sizes(&exact_cache.stats());
 
-    let token_jaccard = Arc::new(TokenJaccard::new(
-        policy.similarity.jaccard_threshold,
-        policy.similarity.jaccard_min_tokens,
-    ));
+    let token_jaccard = Arc::new(
+        TokenJaccard::new(
+            policy.similarity.jaccard_threshold,
+            policy.similarity.jaccard_min_tokens,
+        )
+        .with_mode(policy.similarity.token_mode)
+        .with_deny_margin(DenyMargin::from_config(&policy.similarity)),
+    );
     token_jaccard.load_from(&all_decisions);
 
     // Embedding similarity -- try to create, fall back to no-op if model loading fails
-    let embedding_similarity =
-        match EmbeddingSimilarity::new("default", policy.similarity.embedding_threshold) {
-            Ok(es) => {
-                let _ = es.build_index(&all_decisions);
-                Arc::new(es)
+    let embedding_spec = GlobalConfig::embedding_spec();
+    let embedding = if faults.contains(&Fault::Embedding) {
+        Err(Fault::Embedding.error())
+    } else {
+        EmbeddingSimilarity::new(&embedding_spec, policy.similarity.embedding_threshold)
+    };
+    let embedding_similarity = match embedding {
+        Ok(es) => {
+            let es = es.with_deny_margin(DenyMargin::from_config(&policy.similarity));
+            // Reuse persisted vectors; ephemeral runs leave .index/ untouched
+            let es = if ephemeral {
+                es
+            } else {
+                es.with_store(project_root.join(".index"))
+            };
+            if let Err(e) = es.load_or_build(&all_decisions).await {
+                eprintln!("hookwise: embedding index load failed ({})", e);
             }
-            Err(e) => {
+            Arc::new(es)
+        }
+        Err(e) => {
+            // Builds without the local model skip the tier quietly
+            if !matches!(e, HookwiseError::NotCompiledIn { .. }) {
                 eprintln!("hookwise: embedding tier unavailable, skipping ({})", e);
-                Arc::new(EmbeddingSimilarity::new_noop())
             }
-        };
+            Arc::new(EmbeddingSimilarity::new_noop())
+        }
+    };
 
     // Supervisor tier
+    let transcript = match TranscriptLog::from_config(
+        transcript_log_path(&project_root),
+        &policy.supervisor_transcript,
+    ) {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("hookwise: supervisor transcript disabled ({})", e);
+            None
+        }
+    };
+    let precedents =
+        PrecedentSelector::new(token_jaccard.clone(), policy.supervisor_examples.clone())
+            .with_embedding(embedding_similarity.clone())
+            .with_corrections(load_feedback(&feedback_log_path(&project_root)));
     let supervisor: Box<dyn crate::cascade::CascadeTier> = match &policy.supervisor {
+        _ if faults.contains(&Fault::Supervisor) => Box::new(
+            SupervisorTier::new(Box::new(FailingSupervisor), policy.clone())
+                .with_transcript(transcript)
+                .with_precedents(precedents),
+        ),
         SupervisorConfig::Socket { socket_path } => {
-            let sock_path = socket_path.clone().unwrap_or_else(|| {
-                let tid = team_id.as_deref().unwrap_or("solo");
-                PathBuf::from(format!("/tmp/hookwise-{tid}.sock"))
-            });
+            let sock_path = socket_path
+                .clone()
+                .unwrap_or_else(|| default_socket_path(team_id.as_deref()));
             let backend = UnixSocketSupervisor::new(sock_path, 30);
-            Box::new(SupervisorTier::new(Box::new(backend), policy.clone()))
+            Box::new(
+                SupervisorTier::new(Box::new(backend), policy.clone())
+                    .with_transcript(transcript)
+                    .with_precedents(precedents),
+            )
         }
+        #[cfg(feature = "api-supervisor")]
         SupervisorConfig::Api {
             api_base_url,
             model,
@@ -126,54 +353,294 @@ pub async fn run(format: HookFormat) -> Result<()> {
                     .unwrap_or_else(|| "claude-sonnet-4-5-20250929".into()),
                 max_tokens.unwrap_or(1024),
             );
-            Box::new(SupervisorTier::new(Box::new(backend), policy.clone()))
+            Box::new(
+                SupervisorTier::new(Box::new(backend), policy.clone())
+                    .with_transcript(transcript)
+                    .with_precedents(precedents),
+            )
         }
+        #[cfg(not(feature = "api-supervisor"))]
+        SupervisorConfig::Api { .. } => Box::new(SupervisorTier::new(
+            Box::new(MissingSupervisor {
+                what: "API supervisor",
+                feature: "api-supervisor",
+            }),
+            policy.clone(),
+        )),
     };
 
+    let messages = MessageTemplates::compile(&policy.messages, &roles.categories)?.map(Arc::new);
+    let hints = HintTable::compile(&policy.hints, &roles.categories)?.map(Arc::new);
+    let command_risk = Arc::new(CommandRiskScorer::new(&policy.command_risk)?);
+
     // Human tier
-    let decision_queue = Arc::new(DecisionQueue::new());
-    let human = HumanTier::new(decision_queue, policy.human_timeout_secs);
+    let human = HumanTier::new(decision_queue, policy.human_timeout_secs)
+        .with_sla(
+            policy.effective_human_sla(),
+            (!ephemeral).then(|| sla_log_path(&project_root)),
+        )
+        .with_notifier(Notifier::from_config(&policy.notifications).map(Arc::new))
+        .with_escalation(&policy.escalation)
+        .with_messages(messages.clone())
+        .with_hints(hints.clone())
+        .with_command_risk(Some(command_risk.clone()))
+        .with_attachments(
+            &policy.attachments,
+            SanitizePipeline::from_config(&policy.sanitize)?,
+        );
 
     let runner = CascadeRunner {
-        sanitizer: SanitizePipeline::default_pipeline(),
+        sanitizer: SanitizePipeline::from_config(&policy.sanitize)?,
+        ephemeral: EphemeralPaths::compile(
+            &policy.ephemeral_paths,
+            &policy.sensitive_paths,
+            &roles.categories,
+        )?,
+        content_inspection: ContentInspection::compile(
+            &policy.content,
+            &policy.sanitize,
+            &roles.categories,
+        )?,
+        exfiltration: ExfiltrationGuard::compile(
+            &policy.exfiltration,
+            &roles.categories,
+            session_mgr.state_store(),
+            &input.session_id,
+        )?,
+        locks: PathLocks::compile(
+            &policy.locks,
+            &roles.categories,
+            session_mgr.state_store(),
+            &input.session_id,
+        )?,
         path_policy: Box::new(path_policy),
+        package_gate: PackageGate::compile(&policy.packages)?,
+        cloud_cli: CloudPolicy::compile(&policy.cloud)?,
+        git_policy: GitPolicy::compile(&policy.git)?,
         exact_cache,
+        commit_keys: CommitKeys::compile(&policy.cache)?,
         token_jaccard,
         embedding_similarity,
         supervisor,
         human: Box::new(human),
-        storage: Box::new(storage),
+        storage,
         policy: policy.clone(),
         normalizer,
+        tier_caps: TierCaps::compile(&policy.max_auto_tier, &roles.categories)?,
+        risk: Some(RiskTracker::new(
+            &policy.risk,
+            session_mgr.state_store(),
+            &input.session_id,
+        )?),
+        messages,
+        hints,
+        command_risk: Some(command_risk),
+        clock: None,
+        client: Some(input.client(format)),
     };
 
     // 5. Run cascade
+    let started = std::time::Instant::now();
     let record = match runner
         .evaluate_with_cwd(&session, &input.tool_name, &input.tool_input, Some(cwd))
         .await
     {
         Ok(record) => record,
+        Err(e) if session.mode == SessionMode::Audit => {
+            eprintln!("hookwise: cascade error, audited, not enforced ({})", e);
+            audit_fallback(
+                &input,
+                format,
+                &policy,
+                Some(&session),
+                Decision::Deny,
+                &format!("cascade error: {}", e),
+            );
+            hook_io::write_hook_output(Decision::Allow, format)?;
+            return Ok(());
+        }
         Err(e) => {
             // On cascade error (e.g. human timeout), default to deny
             // but still write output so callers can parse it.
             eprintln!("hookwise: cascade error, defaulting to deny ({})", e);
+            audit_fallback(
+                &input,
+                format,
+                &policy,
+                Some(&session),
+                Decision::Deny,
+                &format!("cascade error: {}", e),
+            );
             hook_io::write_hook_output(Decision::Deny, format)?;
-            std::process::exit(hook_io::deny_exit_code(format));
+            exit_denied(format);
         }
     };
 
+    if let Some(event) = DeadlineEvent::observe(
+        &policy.deadline,
+        &record,
+        &input.session_id,
+        started.elapsed(),
+        chrono::Utc::now(),
+    ) {
+        eprintln!(
+            "hookwise: evaluation took {}ms of the {}ms deadline",
+            event.elapsed_ms, event.deadline_ms
+        );
+        if !ephemeral {
+            if let Err(e) = deadline::append_event(&deadline_log_path(&project_root), &event) {
+                eprintln!("hookwise: deadline log write failed ({})", e);
+            }
+        }
+    }
+
+    if let Some(rollout) = &rollout {
+        record_rollout(
+            rollout,
+            &rolled_out,
+            &record,
+            &input.session_id,
+            (!ephemeral).then(|| rollout_log_path(&project_root)),
+        );
+    }
+
+    let audit_mode = session.mode == SessionMode::Audit;
+    if let Some(sink) = audit_sink(&policy, &cwd_path, audit_mode) {
+        audit(&sink, cwd, &record, audit_mode);
+    }
+
+    if audit_mode {
+        if record.decision != Decision::Allow {
+            eprintln!(
+                "hookwise: audited, not enforced: would {} ({})",
+                record.decision, record.metadata.reason
+            );
+        }
+        hook_io::write_hook_output(Decision::Allow, format)?;
+        return Ok(());
+    }
+
     // 6. Output result
-    hook_io::write_hook_output(record.decision, format)?;
+    hook_io::write_hook_output_with_reason(record.decision, hook_io::hook_reason(&record), format)?;
 
     // Exit with appropriate code for deny
     if record.decision == Decision::Deny {
-        std::process::exit(hook_io::deny_exit_code(format));
+        exit_denied(format);
     }
 
     Ok(())
 }
 
-/// Get the global config directory.
-fn dirs_global() -> PathBuf {
-    crate::config::dirs_global()
+/// Exit with the deny code for `format`. `std::process::exit` skips
+/// destructors, so pending metrics are flushed first.
+fn exit_denied(format: HookFormat) -> ! {
+    crate::telemetry::shutdown();
+    std::process::exit(hook_io::deny_exit_code(format));
+}
+
+/// The audit log for a call. Audit-mode sessions always log, to the
+/// default path if none is set.
+fn audit_sink(policy: &PolicyConfig, cwd: &std::path::Path, audit_mode: bool) -> Option<AuditSink> {
+    if audit_mode {
+        Some(AuditSink::for_audit_mode(&policy.audit, cwd))
+    } else {
+        AuditSink::from_config(&policy.audit, cwd)
+    }
+}
+
+fn audit(sink: &AuditSink, cwd: &str, record: &DecisionRecord, not_enforced: bool) {
+    let event = AuditEvent {
+        logged_at: chrono::Utc::now(),
+        cwd: cwd.to_string(),
+        record: record.clone(),
+        identity: Some(crate::identity::current(std::path::Path::new(cwd))),
+        not_enforced,
+    };
+    if let Err(e) = sink.record(&event) {
+        eprintln!("hookwise: audit log write failed ({})", e);
+    }
+}
+
+/// Audit a call answered without a cascade record (disabled or unregistered
+/// session, unusable storage, a cascade error) with the answer's reason.
+fn audit_fallback(
+    input: &HookInput,
+    format: HookFormat,
+    policy: &PolicyConfig,
+    session: Option<&SessionContext>,
+    decision: Decision,
+    reason: &str,
+) {
+    let audit_mode = session.is_some_and(|s| s.mode == SessionMode::Audit);
+    let Some(sink) = audit_sink(policy, std::path::Path::new(&input.cwd), audit_mode) else {
+        return;
+    };
+    let sanitizer = SanitizePipeline::from_config(&policy.sanitize)
+        .unwrap_or_else(|_| SanitizePipeline::default_pipeline());
+    let (sanitized_input, secrets) = cache_input(&sanitizer, &input.tool_input);
+    let record = DecisionRecord {
+        key: CacheKey {
+            sanitized_input,
+            tool: input.tool_name.clone(),
+            role: session
+                .and_then(|s| s.role.as_ref())
+                .map(|r| r.name.clone())
+                .unwrap_or_else(|| "*".to_string()),
+        },
+        decision,
+        metadata: DecisionMetadata {
+            tier: DecisionTier::Default,
+            confidence: 1.0,
+            reason: reason.to_string(),
+            matched_key: None,
+            similarity_score: None,
+            targets: Vec::new(),
+            message: None,
+            suggestion: None,
+            risk_score: None,
+            secrets,
+            client: Some(input.client(format)),
+        },
+        timestamp: chrono::Utc::now(),
+        scope: ScopeLevel::Project,
+        file_path: CascadeRunner::extract_file_path(&input.tool_name, &input.tool_input),
+        session_id: input.session_id.clone(),
+    };
+    audit(&sink, &input.cwd, &record, audit_mode);
+}
+
+/// Log a call that a rolled-out org rule matched, with what the session was
+/// told and what the rule says.
+fn record_rollout(
+    rollout: &Rollout,
+    rolled_out: &[DecisionRecord],
+    record: &DecisionRecord,
+    session_id: &str,
+    log: Option<PathBuf>,
+) {
+    let Some(rule) = rollout::evaluate(rolled_out, &record.key) else {
+        return;
+    };
+    let event = RolloutEvent {
+        logged_at: chrono::Utc::now(),
+        since: rollout.since,
+        session_id: session_id.to_string(),
+        cohort: rollout.cohort,
+        rule: rule.id(),
+        tool: record.key.tool.clone(),
+        role: record.key.role.clone(),
+        enforced: record.decision,
+        rolled_out: rule.decision,
+    };
+    crate::telemetry::record_rollout(&event);
+    if let Some(path) = log {
+        if let Err(e) = rollout::append_event(&path, &event) {
+            eprintln!("hookwise: rollout log write failed ({})", e);
+        }
+    }
+}
+
+/// Get the root for user and org rules.
+fn dirs_rules() -> PathBuf {
+    crate::config::dirs_rules()
 }
@@ -0,0 +1,118 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::error::{HookwiseError, Result};
+use crate::hook_io::{HookFormat, HookInput};
+
+/// What to simulate with `hookwise dev simulate-hook`.
+pub struct SimulatedCall<'a> {
+    pub tool: &'a str,
+    pub command: Option<&'a str>,
+    pub file_path: Option<&'a str>,
+    pub content: Option<&'a str>,
+    /// Raw `tool_input` JSON, used as is.
+    pub input: Option<&'a str>,
+}
+
+/// Send a fabricated assistant payload through `hookwise check` in a child
+/// process, as the assistant would, and print what it sees: the payload,
+/// check's stdout and its exit code. Check's stderr passes through, so a
+/// call that reaches the human tier waits for `hookwise approve`/`deny`
+/// from another terminal.
+pub async fn run_simulate_hook(
+    call: &SimulatedCall<'_>,
+    format: HookFormat,
+    session_id: &str,
+    role: Option<&str>,
+) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let tool_input = tool_input(call, &cwd)?;
+    if let Some(role) = role {
+        let roles: Vec<String> = role.split(',').map(String::from).collect();
+        super::register::run_register(session_id, &roles, None, None, false, None, None).await?;
+    }
+    let payload = serde_json::to_string(&HookInput::simulated(
+        format, session_id, call.tool, tool_input, &cwd,
+    ))?;
+    let format_name = format
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default();
+
+    eprintln!(
+        "hookwise: simulating a {} {} call in session {}",
+        format_name, call.tool, session_id
+    );
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(["check", "--format", &format_name])
+        .current_dir(&cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(payload.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    println!("stdin:     {}", payload);
+    println!("stdout:    {}", stdout.trim_end());
+    match output.status.code() {
+        Some(code) => println!("exit code: {}", code),
+        None => println!("exit code: none (killed by a signal)"),
+    }
+    if let Some(decision) = decision(format, &stdout) {
+        println!("decision:  {}", decision);
+    }
+    Ok(())
+}
+
+/// The call's `tool_input`: `input` when given, else built from the flags
+/// the way the assistants shape Bash, Write, Edit and Read calls. File paths
+/// are made absolute against `cwd`, as the assistants send them.
+fn tool_input(call: &SimulatedCall<'_>, cwd: &Path) -> Result<Value> {
+    if let Some(input) = call.input {
+        return serde_json::from_str(input).map_err(|e| HookwiseError::InvalidPolicy {
+            reason: format!("--input is not JSON: {}", e),
+        });
+    }
+    let mut input = json!({});
+    if let Some(command) = call.command {
+        input["command"] = json!(command);
+    }
+    if let Some(path) = call.file_path {
+        input["file_path"] = json!(cwd.join(path));
+    }
+    if let Some(content) = call.content {
+        let field = if call.tool == "Edit" {
+            "new_string"
+        } else {
+            "content"
+        };
+        input[field] = json!(content);
+    }
+    if input.as_object().is_some_and(|fields| fields.is_empty()) {
+        return Err(HookwiseError::InvalidPolicy {
+            reason: "nothing to simulate: pass --command, --file-path or --input".into(),
+        });
+    }
+    if call.tool == "Edit" {
+        input["old_string"] = json!("");
+    }
+    Ok(input)
+}
+
+/// The decision in check's output, as the assistant reads it.
+fn decision(format: HookFormat, stdout: &str) -> Option<String> {
+    let output: Value = serde_json::from_str(stdout.trim()).ok()?;
+    let decision = match format {
+        HookFormat::Claude => &output["hookSpecificOutput"]["permissionDecision"],
+        HookFormat::Gemini => &output["decision"],
+    };
+    decision.as_str().map(str::to_string)
+}
@@ -0,0 +1,75 @@
+use crate::cascade::transcript::{find_transcript, load_transcripts, transcript_log_path};
+use crate::error::{HookwiseError, Result};
+
+/// Number of transcripts listed when no id is given.
+const RECENT_LIMIT: usize = 20;
+
+/// Show the supervisor exchange for a decision id, or list recent ids.
+pub async fn run(supervisor: Option<&str>) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let path = transcript_log_path(&cwd.join(".hookwise"));
+
+    let Some(id) = supervisor else {
+        let entries = load_transcripts(&path);
+        if entries.is_empty() {
+            println!(
+                "No supervisor transcripts. Enable supervisor_transcript.enabled in policy.yml."
+            );
+            return Ok(());
+        }
+        println!("Recent supervisor decisions (newest first):");
+        for entry in entries.iter().rev().take(RECENT_LIMIT) {
+            let outcome = entry
+                .decision
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| "error".into());
+            println!(
+                "  {}  {}  {} {} -> {}",
+                entry.id,
+                entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                entry.role,
+                entry.tool_name,
+                outcome
+            );
+        }
+        println!("\nShow one with: hookwise explain --supervisor <id>");
+        return Ok(());
+    };
+
+    let entry = find_transcript(&path, id).ok_or_else(|| HookwiseError::Storage {
+        reason: format!("no supervisor transcript with id {}", id),
+    })?;
+
+    println!("ID: {}", entry.id);
+    println!("Time: {}", entry.timestamp);
+    println!("Backend: {} ({} ms)", entry.backend, entry.latency_ms);
+    println!("Role: {}", entry.role);
+    println!("Tool: {}", entry.tool_name);
+    if let Some(path) = &entry.file_path {
+        println!("File: {}", path);
+    }
+    match (&entry.decision, &entry.error) {
+        (Some(decision), _) => println!(
+            "Decision: {} (confidence {:.2})",
+            decision,
+            entry.confidence.unwrap_or_default()
+        ),
+        (None, Some(error)) => println!("Error: {}", error),
+        (None, None) => {}
+    }
+    if let Some(reason) = &entry.reason {
+        println!("Reason: {}", reason);
+    }
+    if let Some(decision_id) = &entry.decision_id {
+        println!(
+            "Correct with: hookwise feedback {} --should-have-been <decision>",
+            decision_id
+        );
+    }
+    println!("\n--- prompt ---\n{}", entry.prompt);
+    println!(
+        "\n--- reply ---\n{}",
+        entry.reply.as_deref().unwrap_or("(no reply)")
+    );
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+
+use crate::cascade::transcript::{find_transcript, transcript_log_path};
+use crate::cli::queue_table::truncate_input;
+use crate::decision::{Decision, DecisionMetadata, DecisionRecord, DecisionTier};
+use crate::error::{HookwiseError, Result};
+use crate::feedback::{append_feedback, feedback_log_path, invalidate_derived, FeedbackRecord};
+use crate::scope::ScopeLevel;
+use crate::storage::jsonl::JsonlStorage;
+use crate::storage::StorageBackend;
+
+/// Number of decisions listed when no id is given.
+const RECENT_LIMIT: usize = 20;
+
+/// Correct an automatic decision, or list recent ones.
+pub async fn run(
+    decision_id: Option<&str>,
+    should_have_been: Option<&str>,
+    reason: Option<&str>,
+    add_rule: bool,
+    scope: &str,
+) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let project_root = cwd.join(".hookwise");
+    let storage = JsonlStorage::new(project_root.clone(), crate::config::dirs_rules(), None);
+
+    let mut automatic: Vec<DecisionRecord> = storage
+        .load_decisions(ScopeLevel::Project)?
+        .into_iter()
+        .filter(|r| {
+            !matches!(
+                r.metadata.tier,
+                DecisionTier::Human | DecisionTier::Override
+            )
+        })
+        .collect();
+    automatic.sort_by_key(|r| r.timestamp);
+
+    let Some(id) = decision_id else {
+        if automatic.is_empty() {
+            println!("No automatic decisions recorded for this project.");
+            return Ok(());
+        }
+        println!("Recent automatic decisions (newest first):");
+        for record in automatic.iter().rev().take(RECENT_LIMIT) {
+            println!(
+                "  {}  {}  {} {} -> {} ({:?})  {}",
+                record.id(),
+                record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                record.key.role,
+                record.key.tool,
+                record.decision,
+                record.metadata.tier,
+                truncate_input(&record.key.sanitized_input, 60)
+            );
+        }
+        println!("\nCorrect one with: hookwise feedback <id> --should-have-been <allow|deny|ask>");
+        return Ok(());
+    };
+
+    let corrected = should_have_been
+        .ok_or_else(|| HookwiseError::InvalidPolicy {
+            reason: "--should-have-been is required".into(),
+        })?
+        .parse::<Decision>()
+        .map_err(|reason| HookwiseError::InvalidPolicy { reason })?;
+    let scope_level = scope
+        .parse::<ScopeLevel>()
+        .map_err(|e| HookwiseError::InvalidPolicy { reason: e })?;
+
+    // Accept a supervisor transcript id (`hookwise explain`) as well.
+    let record_id = find_transcript(&transcript_log_path(&project_root), id)
+        .and_then(|entry| entry.decision_id)
+        .unwrap_or_else(|| id.to_string());
+    let original = automatic
+        .into_iter()
+        .find(|r| r.id() == record_id)
+        .ok_or_else(|| HookwiseError::Storage {
+            reason: format!("no automatic decision with id {}", id),
+        })?;
+
+    let reason = reason.unwrap_or("corrected via hookwise feedback");
+    let feedback = FeedbackRecord::new(&original, corrected, reason);
+    append_feedback(&feedback_log_path(&project_root), &feedback)?;
+    let removed = invalidate_derived(&storage, &original.key)?;
+
+    eprintln!(
+        "hookwise: recorded {} -> {} for {} {} ({} cached decision(s) invalidated)",
+        original.decision, corrected, original.key.role, original.key.tool, removed
+    );
+
+    if add_rule {
+        let rule = DecisionRecord {
+            key: original.key.clone(),
+            decision: corrected,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::Override,
+                confidence: 1.0,
+                reason: format!("feedback: {}", reason),
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: Utc::now(),
+            scope: scope_level,
+            file_path: original.file_path.clone(),
+            session_id: "feedback".to_string(),
+        };
+        storage.save_decision(&rule)?;
+        eprintln!("hookwise: added {} rule at scope '{}'", corrected, scope);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! `hookwise init --hooks`: git pre-commit and pre-push hooks that run
+//! `hookwise scan`. Repos using a hook manager (husky, lefthook or the
+//! pre-commit framework) get the config fragment for it instead, since the
+//! manager owns the hooks directory.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+
+/// First line after the shebang of hooks hookwise wrote; only those are
+/// ever rewritten.
+pub const HOOK_MARKER: &str = "# Installed by `hookwise init --hooks`.";
+
+/// The git hooks and the scan each one runs. Pre-commit scans what is
+/// about to be committed; pre-push scans the tree being pushed (`scan`
+/// honors .gitignore).
+pub const GIT_HOOKS: [(&str, &str); 2] = [
+    ("pre-commit", "hookwise scan --staged"),
+    ("pre-push", "hookwise scan ."),
+];
+
+/// Who runs the repository's git hooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HookManager {
+    /// Plain scripts in the git hooks directory.
+    Git,
+    /// husky: `.husky/<hook>` shell files.
+    Husky,
+    /// lefthook: `lefthook.yml`.
+    Lefthook,
+    /// The pre-commit framework: `.pre-commit-config.yaml`.
+    PreCommit,
+}
+
+impl HookManager {
+    /// The hook manager configured in `root`, or plain git hooks.
+    pub fn detect(root: &Path) -> Self {
+        let exists = |name: &str| root.join(name).exists();
+        if exists(".husky") {
+            Self::Husky
+        } else if [
+            "lefthook.yml",
+            "lefthook.yaml",
+            ".lefthook.yml",
+            ".lefthook.yaml",
+        ]
+        .iter()
+        .any(|name| exists(name))
+        {
+            Self::Lefthook
+        } else if exists(".pre-commit-config.yaml") {
+            Self::PreCommit
+        } else {
+            Self::Git
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Git => "git",
+            Self::Husky => "husky",
+            Self::Lefthook => "lefthook",
+            Self::PreCommit => "pre-commit",
+        }
+    }
+
+    /// Where the fragment goes, and the fragment itself. `None` for plain
+    /// git hooks, which are installed as scripts.
+    pub fn fragment(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::Git => None,
+            Self::Husky => Some((
+                ".husky/pre-commit and .husky/pre-push",
+                r#"# .husky/pre-commit
+hookwise scan --staged
+
+# .husky/pre-push
+hookwise scan .
+"#,
+            )),
+            Self::Lefthook => Some((
+                "lefthook.yml",
+                r#"pre-commit:
+  commands:
+    hookwise-scan:
+      run: hookwise scan --staged
+pre-push:
+  commands:
+    hookwise-scan:
+      run: hookwise scan .
+"#,
+            )),
+            Self::PreCommit => Some((
+                ".pre-commit-config.yaml (under `repos:`)",
+                r#"  - repo: local
+    hooks:
+      - id: hookwise-scan
+        name: hookwise secret scan
+        entry: hookwise scan --staged
+        language: system
+        pass_filenames: false
+        stages: [pre-commit]
+      - id: hookwise-scan-push
+        name: hookwise secret scan (push)
+        entry: hookwise scan .
+        language: system
+        pass_filenames: false
+        stages: [pre-push]
+"#,
+            )),
+        }
+    }
+}
+
+/// The script for a plain git hook running `command`.
+pub fn hook_script(command: &str) -> String {
+    format!("#!/bin/sh\n{HOOK_MARKER}\nexec {command}\n")
+}
+
+/// What happened to one git hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookInstall {
+    Installed(PathBuf),
+    /// A hook hookwise didn't write is already there; it is left alone and
+    /// the command is for the user to add.
+    Kept(PathBuf, &'static str),
+}
+
+/// Write the hooks into `hooks_dir`, replacing earlier hookwise hooks but
+/// never anyone else's.
+pub fn install_hooks(hooks_dir: &Path) -> Result<Vec<HookInstall>> {
+    std::fs::create_dir_all(hooks_dir)?;
+    let mut results = Vec::new();
+    for (name, command) in GIT_HOOKS {
+        let path = hooks_dir.join(name);
+        let theirs = match std::fs::read_to_string(&path) {
+            Ok(existing) => !existing.contains(HOOK_MARKER),
+            Err(_) => path.exists(),
+        };
+        if theirs {
+            results.push(HookInstall::Kept(path, command));
+            continue;
+        }
+        std::fs::write(&path, hook_script(command))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        results.push(HookInstall::Installed(path));
+    }
+    Ok(results)
+}
+
+/// The hooks directory git uses in `cwd`, honoring `core.hooksPath` and
+/// worktrees. `None` outside a git repository.
+pub fn hooks_dir(cwd: &Path) -> Option<PathBuf> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .current_dir(cwd)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Some(if path.is_absolute() {
+        path
+    } else {
+        cwd.join(path)
+    })
+}
+
+/// Print the hooks (or the manager's fragment) without writing anything.
+pub fn print(cwd: &Path, manager: Option<HookManager>) {
+    let manager = manager.unwrap_or_else(|| HookManager::detect(cwd));
+    match manager.fragment() {
+        Some((target, fragment)) => {
+            eprintln!("hookwise: add to {}:", target);
+            print!("{}", fragment);
+        }
+        None => {
+            for (name, command) in GIT_HOOKS {
+                println!("# {}\n{}", name, hook_script(command));
+            }
+        }
+    }
+}
+
+/// Install the hooks, or print the fragment for the repository's hook
+/// manager.
+pub fn install(cwd: &Path, manager: Option<HookManager>) -> Result<()> {
+    let manager = manager.unwrap_or_else(|| HookManager::detect(cwd));
+    if manager != HookManager::Git {
+        eprintln!(
+            "hookwise: {} manages this repo's git hooks; not writing hook scripts",
+            manager.name()
+        );
+        print(cwd, Some(manager));
+        return Ok(());
+    }
+
+    let Some(dir) = hooks_dir(cwd) else {
+        eprintln!("hookwise: not a git repository; no hooks installed");
+        std::process::exit(1);
+    };
+    for result in install_hooks(&dir)? {
+        match result {
+            HookInstall::Installed(path) => {
+                eprintln!("hookwise: installed {}", path.display())
+            }
+            HookInstall::Kept(path, command) => eprintln!(
+                "hookwise: {} already exists; add `{}` to it",
+                path.display(),
+                command
+            ),
+        }
+    }
+    Ok(())
+}
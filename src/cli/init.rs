@@ -1,11 +1,28 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::error::Result;
 
-/// Initialize .hookwise/ in the current repo.
-pub async fn run() -> Result<()> {
+use super::git_hooks::{self, HookManager};
+
+/// Initialize .hookwise/ in the current repo and, with `hooks`, install the
+/// git hooks running `hookwise scan` (or just print them with `print`).
+pub async fn run(hooks: bool, print: bool, manager: Option<HookManager>) -> Result<()> {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    if hooks && print {
+        git_hooks::print(&cwd, manager);
+        return Ok(());
+    }
+    init_project(&cwd)?;
+    if hooks {
+        git_hooks::install(&cwd, manager)?;
+    }
+    Ok(())
+}
+
+/// Create .hookwise/ with the default policy, roles and empty rules, unless
+/// it already exists.
+fn init_project(cwd: &Path) -> Result<()> {
     let hook_dir = cwd.join(".hookwise");
 
     if hook_dir.exists() {
@@ -36,6 +53,152 @@ sensitive_paths:
     - "~/.claude/**"
     - "~/.config/**"
 
+# File tool calls that only touch scratch/build output are allowed before
+# any other tier (Bash never is). Default: .tmp/, tmp/, target/,
+# node_modules/.cache/ and Python tool caches.
+ephemeral_paths:
+  enabled: true
+
+# Path matching both deny_write and a sensitive pattern: sensitive_first
+# (ask) or deny_first (deny always wins).
+path_precedence: sensitive_first
+
+# Highest tier allowed to decide calls touching matching paths, e.g.
+#   "{{infra}}": exact_cache   (only rules and humans)
+max_auto_tier: {}
+
+# Deny/ask messages for matching paths, e.g.
+#   - paths: ["{{infra}}"]
+#     deny: "Writes to {{path}} require a ticket"
+messages: []
+
+# Remediation hints added to deny/ask reasons. Built-in hints name the
+# role that writes tests, source, docs, CI and infra paths; add your own:
+#   rules:
+#     - paths: ["{{infra}}"]
+#       roles: [coder]
+#       hint: "open a ticket in #infra for changes to {{path}}"
+hints:
+  builtin: true
+
+# Container privileged runs, host mounts and unpinned images
+containers:
+  privileged: ask
+  host_mounts: ask
+  unpinned_images: allow
+
+# Package install gating, e.g.
+#   allowed_registries: ["registry.npmjs.org"]
+#   allowlist: { npm: ["react", "@acme/*"] }
+#   osv: { enabled: true }
+packages: {}
+
+# kubectl/aws/gcloud/az rules per role and namespace/profile, e.g.
+#   - roles: ["reviewer"]
+#     read: allow
+#     mutate: deny
+#   - scopes: ["prod*"]
+#     destroy: deny
+cloud: []
+
+# Git operation rules per role, e.g.
+#   protected_branches: ["main", "release/*"]
+#   rules:
+#     - force_push: deny
+#       protected_push: ask
+#       hooks: deny
+git:
+  rules: []
+
+# Checks on the text Write/Edit calls add to files, e.g.
+#   secrets: deny
+#   rules:
+#     - paths: [".github/workflows/**"]
+#       patterns: ['curl[^|\n]*\|\s*(ba)?sh']
+#       decision: deny
+content: {}
+
+# Ask before network sends that follow a read of a sensitive file in the
+# same session, e.g.
+#   sensitive: ["**/.env*", "**/*.pem", "~/.ssh/**"]
+#   window_secs: 3600
+#   decision: ask
+exfiltration: {}
+
+# Lock the path range an allowed write lands in for the writing session;
+# writes there from other sessions are asked about until the lock expires,
+# e.g.
+#   enabled: true
+#   ttl_secs: 300
+#   ranges: ["{{tests}}", "src/api/**"]
+locks: {}
+
+# Tighten sessions whose rolling risk score (denied calls, sensitive path
+# touches, novel calls) reaches a threshold, e.g.
+#   weights: { denied: 3, sensitive: 2, novel: 1 }
+#   threshold: 10
+#   disable_similarity: true
+#   max_auto_tier: supervisor
+risk: {}
+
+# Weights of the built-in command risk score recorded on every decision, and
+# the scores that raise queue priority, e.g.
+#   weights: { delete: 3, privilege: 3, destructive_flags: 2, network: 2 }
+#   high: 4
+#   critical: 7
+command_risk: {}
+
+# Sanitized excerpts of the target file and of the proposed content, attached
+# to pending Write/Edit decisions for approvers, e.g.
+#   max_lines: 20
+#   max_bytes: 2048
+#   context_lines: 3
+# Set enabled: false to keep file contents out of notifications.
+attachments: {}
+
+# Signed offline approval bundles (`hookwise queue export`, `respond`,
+# `import-responses`). This side's private key from `hookwise queue keygen`
+# (HOOKWISE_BUNDLE_KEY takes precedence) and the other side's public keys:
+#   key_file: bundle.key   # under ~/.config/hookwise/, never in the project
+#   trusted_keys:
+#     alice@example.com: <public key>
+#   max_age_hours: 72
+offline_approval: {}
+
+# Require two verified approvers, or an org token whose SHA-256 is listed,
+# before `hookwise disable` turns off gating for a session, e.g.
+#   protected: true
+#   org_token_sha256: ["<sha256 of the token>"]
+session_disable:
+  protected: false
+
+# Mode for sessions registered without --mode: enforce, or audit to allow
+# every call while logging each would-be decision as not enforced
+session_mode: enforce
+
+# Scope for rules added by approve/deny --add-rule without --scope; a role's
+# rule_scope in roles.yml takes precedence
+rule_scope: project
+
+# Extra secret prefixes, regexes and literal strings (e.g. internal
+# hostnames) to redact
+sanitize:
+  prefixes: []
+  patterns: []
+  literals: []
+  # Entropy thresholds for prefix-less secrets; hex_min_length opts into
+  # flagging long hex runs (e.g. 32)
+  entropy:
+    min_length: 20
+    min_entropy: 4.0
+  # Placeholders for timestamps, UUIDs, temp paths, loopback ports and
+  # commit ids, so retries hit the cache; set a kind to false to keep it
+  # normalize: { ports: false }
+  # Stages run in this order; leave one out to disable it. max_input_bytes
+  # caps the cache key for giant inputs (the truncate stage).
+  stages: [encoding, literals, prefixes, patterns, entropy, normalize, truncate]
+  # max_input_bytes: 16384
+
 confidence:
   org: 0.9
   project: 0.7
@@ -45,12 +208,40 @@ similarity:
   jaccard_threshold: 0.7
   embedding_threshold: 0.85
   jaccard_min_tokens: 3
+  # tfidf weights rare tokens above common ones like `cargo` or `--release`
+  token_mode: jaccard
+  # Matches within deny_margin of a cached deny fall through (or ask)
+  deny_margin: 0.1
+  on_deny_conflict: fallthrough
 
 human_timeout_secs: 60
 registration_timeout_secs: 5
 
+# Hard limit on one evaluation, under the assistant's hook timeout (Claude
+# Code's is 60s); past it the call gets `decision` and stats count it
+# deadline: { secs: 55, decision: deny }
+
+# Pending decisions and unclaimed responses older than this are expired
+# (stored as timed-out denies) by `hookwise prune` and on each check.
+queue_ttl_secs: 3600
+
+# Pending decisions whose waiting process exited: deny or discard.
+orphaned_queue: deny
+
+# High-priority queue items older than overdue_after_secs are marked overdue
+# and re-notified every nag_interval_secs. Unset, they default to half and a
+# quarter of the human timeout.
+human_sla:
+  nag_min_priority: high
+
 supervisor:
   backend: socket
+
+# Similar past human decisions shown to the supervisor as precedents.
+supervisor_examples:
+  enabled: true
+  max_examples: 5
+  max_tokens: 600
 "#;
     fs::write(hook_dir.join("policy.yml"), policy_content)?;
 
@@ -0,0 +1,56 @@
+//! `hookwise maintain`: scheduled background work kept off the hook path.
+//! Today that is the release check whose cached result `check` turns into
+//! an update hint.
+
+use std::time::Duration;
+
+use crate::config::GlobalConfig;
+use crate::error::Result;
+
+use super::self_update::{self, UpdateCheck};
+
+/// Run the due maintenance tasks once, or every `every` seconds until
+/// killed. In the loop, a failed task is reported and retried on the next
+/// wake-up.
+pub async fn run(every: Option<u64>, force: bool) -> Result<()> {
+    let Some(secs) = every else {
+        return run_once(force).await;
+    };
+    let mut interval = tokio::time::interval(Duration::from_secs(secs.max(1)));
+    let mut force = force;
+    loop {
+        interval.tick().await;
+        if let Err(e) = run_once(force).await {
+            eprintln!("hookwise: maintenance failed: {}", e);
+        }
+        force = false;
+    }
+}
+
+async fn run_once(force: bool) -> Result<()> {
+    // Re-read each time so a long-running daemon follows config changes
+    let config = GlobalConfig::load()?.map(|c| c.updates).unwrap_or_default();
+    if !config.hints {
+        println!("update check: disabled (updates.hints: false)");
+        return Ok(());
+    }
+
+    match self_update::refresh_update_check(&config, force).await? {
+        Some(check) => report(&check),
+        None => println!(
+            "update check: not due (every {}h)",
+            config.check_interval_hours
+        ),
+    }
+    Ok(())
+}
+
+fn report(check: &UpdateCheck) {
+    match check.hint(&check.current_version) {
+        Some(hint) => println!("update check: {}", hint.trim_start_matches("hookwise: ")),
+        None => println!(
+            "update check: hookwise {} is up to date",
+            check.current_version
+        ),
+    }
+}
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use clap::ValueEnum;
 use rmcp::handler::server::tool::ToolRouter;
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::model::{CallToolResult, Content, ServerInfo};
@@ -10,10 +11,16 @@ use rmcp::{tool, tool_router, ErrorData as McpError};
 use serde::Deserialize;
 
 use crate::cascade::cache::ExactCache;
-use crate::cascade::human::{load_queue_file, DecisionQueue, HumanResponse};
+use crate::cascade::human::{
+    load_queue_file, sort_pending, DecisionQueue, HumanResponse, QueueSort,
+};
+use crate::cli::register::{disable_session, enable_session, resolve_role, RoleMatch};
+use crate::config::{PolicyConfig, SessionMode};
 use crate::decision::Decision;
-use crate::error::Result;
+use crate::error::{HookwiseError, Result};
 use crate::scope::ScopeLevel;
+use crate::session::capabilities::Capabilities;
+use crate::session::disable::{authorize_audit_mode, DisableOutcome};
 use crate::session::SessionManager;
 use crate::storage::jsonl::JsonlStorage;
 use crate::storage::StorageBackend;
@@ -41,6 +48,13 @@ pub struct RegisterParams {
     /// Optional task description
     #[serde(default)]
     pub task: Option<String>,
+    /// Register with the closest known role if `role` is unknown
+    #[serde(default)]
+    pub assume_closest: bool,
+    /// "enforce" or "audit" (allow every call, log what would have been
+    /// decided). Defaults to the policy's session_mode.
+    #[serde(default)]
+    pub mode: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -59,9 +73,10 @@ pub struct ApproveParams {
     /// Add as a persistent rule
     #[serde(default)]
     pub add_rule: bool,
-    /// Rule scope: project, user, or org
-    #[serde(default = "default_scope")]
-    pub scope: String,
+    /// Rule scope: org, project, user, or role. Defaults to the role's
+    /// rule_scope, else rule_scope in policy.yml.
+    #[serde(default)]
+    pub scope: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -74,13 +89,17 @@ pub struct DenyParams {
     /// Add as a persistent rule
     #[serde(default)]
     pub add_rule: bool,
-    /// Rule scope: project, user, or org
-    #[serde(default = "default_scope")]
-    pub scope: String,
+    /// Rule scope: org, project, user, or role. Defaults to the role's
+    /// rule_scope, else rule_scope in policy.yml.
+    #[serde(default)]
+    pub scope: Option<String>,
 }
 
-fn default_scope() -> String {
-    "project".to_string()
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct QueueParams {
+    /// Sort order: "priority" (default, highest first) or "age" (oldest first)
+    #[serde(default)]
+    pub sort: Option<String>,
 }
 
 // --- Tool implementations ---
@@ -103,30 +122,65 @@ impl HookwiseMcp {
         let p = params.0;
         let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
         let session_mgr = SessionManager::new(team_id.as_deref());
+        let mode = match p.mode.as_deref() {
+            None => None,
+            Some(s) => Some(SessionMode::from_str(s, true).map_err(|e| {
+                McpError::invalid_params(format!("Invalid mode '{}': {}", s, e), None)
+            })?),
+        };
 
-        // Validate role
         let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        if mode == Some(SessionMode::Audit) {
+            let policy = PolicyConfig::load_project(&cwd).map_err(|e| {
+                McpError::internal_error(format!("Failed to load policy: {}", e), None)
+            })?;
+            let token = std::env::var("HOOKWISE_DISABLE_TOKEN").ok();
+            authorize_audit_mode(
+                &policy.session_disable,
+                policy.session_mode,
+                token.as_deref(),
+            )
+            .map_err(|e| McpError::invalid_request(e.to_string(), None))?;
+        }
+
+        // Validate role
         let roles = crate::config::RolesConfig::load_project(&cwd).map_err(|e| {
             McpError::internal_error(format!("Failed to load roles config: {}", e), None)
         })?;
 
-        if roles.get_role(&p.role).is_none() {
-            let available: Vec<_> = roles.roles.keys().map(|k| k.as_str()).collect();
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "Unknown role '{}'. Available roles: {}",
-                p.role,
-                available.join(", ")
-            ))]));
-        }
+        let role = match resolve_role(&roles, &p.role, p.assume_closest) {
+            RoleMatch::Exact(name) | RoleMatch::Closest(name) => name,
+            RoleMatch::Unknown { suggestion } => {
+                let hint = suggestion
+                    .map(|closest| {
+                        format!(
+                            " Did you mean '{}'? (retry with assume_closest: true to use it)",
+                            closest
+                        )
+                    })
+                    .unwrap_or_default();
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "Unknown role '{}'.{} Available roles: {}",
+                    p.role,
+                    hint,
+                    roles.role_names().join(", ")
+                ))]));
+            }
+        };
 
         session_mgr
-            .register(&p.session_id, &p.role, p.task.as_deref(), None)
+            .register(&p.session_id, role, p.task.as_deref(), None, mode)
             .map_err(|e| McpError::internal_error(format!("Registration failed: {}", e), None))?;
 
-        let role_def = roles.get_role(&p.role).unwrap();
+        let role_def = roles.get_role(role).unwrap();
+        let audit = if mode == Some(SessionMode::Audit) {
+            " Audit mode: calls are logged but not enforced."
+        } else {
+            ""
+        };
         Ok(CallToolResult::success(vec![Content::text(format!(
-            "Session {} registered as '{}'. {}",
-            p.session_id, p.role, role_def.description
+            "Session {} registered as '{}'. {}{}",
+            p.session_id, role, role_def.description, audit
         ))]))
     }
 
@@ -138,17 +192,31 @@ impl HookwiseMcp {
         params: Parameters<SessionIdParams>,
     ) -> std::result::Result<CallToolResult, McpError> {
         let p = params.0;
-        let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
-        let session_mgr = SessionManager::new(team_id.as_deref());
-
-        session_mgr
-            .disable(&p.session_id)
-            .map_err(|e| McpError::internal_error(format!("Disable failed: {}", e), None))?;
-
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Session {} disabled. All tool calls are now permitted.",
-            p.session_id
-        ))]))
+        let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+
+        let outcome =
+            disable_session(&p.session_id, None, None, None, &cwd).map_err(|e| match e {
+                HookwiseError::DisableRefused { .. } | HookwiseError::Identity { .. } => {
+                    McpError::invalid_request(e.to_string(), None)
+                }
+                e => McpError::internal_error(format!("Disable failed: {}", e), None),
+            })?;
+
+        let text = match outcome {
+            DisableOutcome::Disabled { .. } => format!(
+                "Session {} disabled. All tool calls are now permitted.",
+                p.session_id
+            ),
+            DisableOutcome::Pending { expires_at } => format!(
+                "This project requires two people to disable a session. Session {} stays \
+                 gated until another approver runs `hookwise disable --session-id {}` \
+                 (before {}).",
+                p.session_id,
+                p.session_id,
+                expires_at.to_rfc3339()
+            ),
+        };
+        Ok(CallToolResult::success(vec![Content::text(text)]))
     }
 
     #[tool(
@@ -159,11 +227,9 @@ impl HookwiseMcp {
         params: Parameters<SessionIdParams>,
     ) -> std::result::Result<CallToolResult, McpError> {
         let p = params.0;
-        let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
-        let session_mgr = SessionManager::new(team_id.as_deref());
+        let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
 
-        session_mgr
-            .enable(&p.session_id)
+        enable_session(&p.session_id, &cwd)
             .map_err(|e| McpError::internal_error(format!("Enable failed: {}", e), None))?;
 
         Ok(CallToolResult::success(vec![Content::text(format!(
@@ -178,14 +244,16 @@ impl HookwiseMcp {
     async fn hookwise_status(&self) -> std::result::Result<CallToolResult, McpError> {
         let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
         let project_root = cwd.join(".hookwise");
-        let global_root = crate::config::dirs_global();
+        let global_root = crate::config::dirs_rules();
 
-        let storage = JsonlStorage::new(project_root, global_root, None);
+        let expiry = crate::cli::queue::load_policy().cache;
+        let storage =
+            JsonlStorage::new(project_root, global_root, None).with_expiry(expiry.clone());
         let decisions = storage.load_decisions(ScopeLevel::Project).map_err(|e| {
             McpError::internal_error(format!("Failed to load decisions: {}", e), None)
         })?;
 
-        let cache = ExactCache::new();
+        let cache = ExactCache::new().with_expiry(expiry);
         cache.load_from(decisions.clone());
         let stats = cache.stats();
 
@@ -220,10 +288,54 @@ impl HookwiseMcp {
         Ok(CallToolResult::success(vec![Content::text(output)]))
     }
 
-    #[tool(description = "List pending permission decisions waiting for human approval.")]
-    async fn hookwise_queue(&self) -> std::result::Result<CallToolResult, McpError> {
+    #[tool(
+        description = "Describe what a session may do: its role, expanded allow/deny path globs, sensitive paths, which tools are path-checked, cached decisions per tool, and autonomy level (unregistered, gated, unrestricted). Returns JSON; use it to plan work within your permissions."
+    )]
+    async fn hookwise_capabilities(
+        &self,
+        params: Parameters<SessionIdParams>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        let p = params.0;
+        let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
+        let session_mgr = SessionManager::new(team_id.as_deref());
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+        let disabled = session_mgr.is_disabled(&p.session_id);
+        let role = session_mgr.resolve_role(&p.session_id).map_err(|e| {
+            McpError::internal_error(format!("Failed to resolve role: {}", e), None)
+        })?;
+        let policy = crate::cli::queue::load_policy();
+        let storage = JsonlStorage::new(cwd.join(".hookwise"), crate::config::dirs_rules(), None)
+            .with_expiry(policy.cache.clone());
+        let decisions = storage.load_decisions(ScopeLevel::Project).map_err(|e| {
+            McpError::internal_error(format!("Failed to load decisions: {}", e), None)
+        })?;
+
+        let capabilities =
+            Capabilities::new(&p.session_id, disabled, role.as_ref(), &policy, &decisions);
+        let value = serde_json::to_value(&capabilities).map_err(|e| {
+            McpError::internal_error(format!("Failed to encode capabilities: {}", e), None)
+        })?;
+        Ok(CallToolResult::structured(value))
+    }
+
+    #[tool(
+        description = "List pending permission decisions waiting for human approval, highest priority first by default."
+    )]
+    async fn hookwise_queue(
+        &self,
+        params: Parameters<QueueParams>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        let sort = match params.0.sort.as_deref() {
+            None => QueueSort::Priority,
+            Some(s) => QueueSort::from_str(s, true).map_err(|e| {
+                McpError::invalid_params(format!("Invalid sort '{}': {}", s, e), None)
+            })?,
+        };
+
         let state = load_queue_file();
-        let pending: Vec<_> = state.pending.values().cloned().collect();
+        let mut pending: Vec<_> = state.pending.values().cloned().collect();
+        sort_pending(&mut pending, sort);
 
         if pending.is_empty() {
             return Ok(CallToolResult::success(vec![Content::text(
@@ -231,17 +343,39 @@ impl HookwiseMcp {
             )]));
         }
 
+        let sla = crate::cli::queue::load_sla_config();
+        let now = chrono::Utc::now();
         let mut output = String::new();
         for decision in &pending {
+            let overdue = crate::cli::queue::overdue_label(decision, &sla, now)
+                .map(|l| format!("  [{}]", l))
+                .unwrap_or_default();
             output.push_str(&format!(
-                "ID: {}\n  Role: {}\n  Tool: {}\n  Input: {}\n  File: {}\n  Queued: {}\n\n",
+                "ID: {}{}\n  Priority: {}\n  Role: {}\n  Tool: {}\n  Input: {}\n  File: {}\n  Queued: {}\n",
                 decision.id,
+                overdue,
+                decision.priority,
                 decision.role,
                 decision.tool_name,
                 truncate(&decision.sanitized_input, 80),
                 decision.file_path.as_deref().unwrap_or("-"),
                 decision.queued_at,
             ));
+            if let Some(reason) = &decision.ask_reason {
+                output.push_str(&format!("  Reason: {}\n", reason));
+            }
+            if let Some(suggestion) = &decision.suggestion {
+                output.push_str(&format!("  Suggestion: {}\n", suggestion));
+            }
+            if let Some(score) = decision.risk_score {
+                output.push_str(&format!("  Risk: {:.1}\n", score));
+            }
+            if let Some(attachment) = &decision.attachment {
+                for line in attachment.to_text().lines() {
+                    output.push_str(&format!("  | {}\n", line));
+                }
+            }
+            output.push('\n');
         }
         output.push_str(&format!("{} pending decision(s)", pending.len()));
 
@@ -257,11 +391,12 @@ impl HookwiseMcp {
     ) -> std::result::Result<CallToolResult, McpError> {
         let p = params.0;
         let queue = Arc::new(DecisionQueue::new());
+        let policy = crate::cli::queue::load_policy();
+        let approver = crate::identity::approver(&policy.approvals)
+            .map_err(|e| McpError::invalid_request(e.to_string(), None))?;
 
         let rule_scope = if p.add_rule {
-            Some(p.scope.parse::<ScopeLevel>().map_err(|e| {
-                McpError::invalid_params(format!("Invalid scope '{}': {}", p.scope, e), None)
-            })?)
+            Some(rule_scope(&p.id, p.scope.as_deref(), &policy)?)
         } else {
             None
         };
@@ -271,6 +406,8 @@ impl HookwiseMcp {
             always_ask: p.always_ask,
             add_rule: p.add_rule,
             rule_scope,
+            batch: None,
+            responded_by: Some(approver.user),
         };
 
         queue
@@ -281,11 +418,58 @@ impl HookwiseMcp {
         if p.always_ask {
             msg.push_str(" (cached as 'ask' -- will always prompt)");
         }
-        if p.add_rule {
-            msg.push_str(&format!(
-                " (added as persistent rule at scope '{}')",
-                p.scope
-            ));
+        if let Some(scope) = rule_scope {
+            msg.push_str(&format!(" (added as persistent rule at scope '{}')", scope));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(msg)]))
+    }
+
+    #[tool(
+        description = "Approve a pending permission decision plus all similar pending decisions (same tool and role, within the project's Jaccard threshold). Only the given decision is cached as a rule."
+    )]
+    async fn hookwise_approve_like(
+        &self,
+        params: Parameters<ApproveParams>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        let p = params.0;
+        let queue = Arc::new(DecisionQueue::new());
+        let policy = crate::cli::queue::load_policy();
+        let similarity = &policy.similarity;
+        let approver = crate::identity::approver(&policy.approvals)
+            .map_err(|e| McpError::invalid_request(e.to_string(), None))?;
+
+        let rule_scope = if p.add_rule {
+            Some(rule_scope(&p.id, p.scope.as_deref(), &policy)?)
+        } else {
+            None
+        };
+
+        let response = HumanResponse {
+            decision: Decision::Allow,
+            always_ask: p.always_ask,
+            add_rule: p.add_rule,
+            rule_scope,
+            batch: None,
+            responded_by: Some(approver.user),
+        };
+
+        let members = queue
+            .respond_like(
+                &p.id,
+                response,
+                similarity.jaccard_threshold,
+                similarity.jaccard_min_tokens,
+            )
+            .map_err(|e| McpError::internal_error(format!("Approve failed: {}", e), None))?;
+
+        let mut msg = format!(
+            "Approved decision {} and {} similar decision(s)",
+            p.id,
+            members.len()
+        );
+        for (member, score) in &members {
+            msg.push_str(&format!("\n  {} (similarity {:.2})", member, score));
         }
 
         Ok(CallToolResult::success(vec![Content::text(msg)]))
@@ -298,11 +482,12 @@ impl HookwiseMcp {
     ) -> std::result::Result<CallToolResult, McpError> {
         let p = params.0;
         let queue = Arc::new(DecisionQueue::new());
+        let policy = crate::cli::queue::load_policy();
+        let approver = crate::identity::approver(&policy.approvals)
+            .map_err(|e| McpError::invalid_request(e.to_string(), None))?;
 
         let rule_scope = if p.add_rule {
-            Some(p.scope.parse::<ScopeLevel>().map_err(|e| {
-                McpError::invalid_params(format!("Invalid scope '{}': {}", p.scope, e), None)
-            })?)
+            Some(rule_scope(&p.id, p.scope.as_deref(), &policy)?)
         } else {
             None
         };
@@ -312,6 +497,8 @@ impl HookwiseMcp {
             always_ask: p.always_ask,
             add_rule: p.add_rule,
             rule_scope,
+            batch: None,
+            responded_by: Some(approver.user),
         };
 
         queue
@@ -322,11 +509,8 @@ impl HookwiseMcp {
         if p.always_ask {
             msg.push_str(" (cached as 'ask' -- will always prompt)");
         }
-        if p.add_rule {
-            msg.push_str(&format!(
-                " (added as persistent rule at scope '{}')",
-                p.scope
-            ));
+        if let Some(scope) = rule_scope {
+            msg.push_str(&format!(" (added as persistent rule at scope '{}')", scope));
         }
 
         Ok(CallToolResult::success(vec![Content::text(msg)]))
@@ -344,6 +528,16 @@ impl rmcp::handler::server::ServerHandler for HookwiseMcp {
     }
 }
 
+/// The rule scope for answering `id` ([`crate::cli::queue::resolve_rule_scope`]).
+fn rule_scope(
+    id: &str,
+    scope: Option<&str>,
+    policy: &PolicyConfig,
+) -> std::result::Result<ScopeLevel, McpError> {
+    crate::cli::queue::resolve_rule_scope(id, scope, policy)
+        .map_err(|e| McpError::invalid_params(format!("Invalid scope: {}", e), None))
+}
+
 fn truncate(s: &str, max: usize) -> String {
     if s.chars().count() <= max {
         s.to_string()
@@ -0,0 +1,32 @@
+use crate::config::GlobalDirs;
+use crate::error::Result;
+
+/// Move legacy global files out of the config directory (see
+/// [`GlobalDirs::migrate`]).
+pub async fn run(dry_run: bool) -> Result<()> {
+    let dirs = GlobalDirs::from_env();
+    let moves = if dry_run {
+        dirs.plan_migration()
+    } else {
+        dirs.migrate()?
+    };
+
+    if moves.is_empty() {
+        println!("Nothing to migrate; global files already follow the XDG layout.");
+        return Ok(());
+    }
+
+    let verb = if dry_run { "Would move" } else { "Moved" };
+    for m in &moves {
+        println!("{} {} -> {}", verb, m.from.display(), m.to.display());
+    }
+    if !dry_run {
+        println!(
+            "Left symlinks at the old paths; see {} for details.",
+            dirs.config
+                .join(crate::config::dirs::MIGRATION_MARKER)
+                .display()
+        );
+    }
+    Ok(())
+}
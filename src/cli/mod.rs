@@ -1,14 +1,33 @@
+pub mod audit;
 pub mod build;
 pub mod check;
+pub mod dev;
+pub mod explain;
+pub mod feedback;
+pub mod git_hooks;
 pub mod init;
+#[cfg(feature = "self-update")]
+pub mod maintain;
+#[cfg(feature = "mcp")]
 pub mod mcp_server;
+pub mod migrate_dirs;
 pub mod monitor;
 pub mod override_cmd;
+pub mod promote;
 pub mod queue;
+pub mod queue_bundle;
+pub mod queue_table;
+pub mod queue_tui;
+pub mod reconcile;
 pub mod register;
+pub mod sanitize;
 pub mod scan;
+#[cfg(feature = "self-update")]
 pub mod self_update;
 pub mod session_check;
+pub mod status;
+pub mod sync;
+pub mod transfer;
 
 use std::path::PathBuf;
 
@@ -18,33 +37,140 @@ use crate::error::Result;
 /// Dispatch a CLI command.
 pub async fn dispatch(command: crate::Commands) -> Result<()> {
     match command {
-        crate::Commands::Check { format } => check::run(format).await,
+        crate::Commands::Check {
+            format,
+            inject_failure,
+        } => check::run(format, &inject_failure).await,
         crate::Commands::SessionCheck { format } => session_check::run(format).await,
         crate::Commands::Register {
             session_id,
             role,
             task,
             prompt_file,
+            assume_closest,
+            mode,
+            token,
         } => {
-            register::run_register(&session_id, &role, task.as_deref(), prompt_file.as_deref())
-                .await
+            register::run_register(
+                &session_id,
+                &role,
+                task.as_deref(),
+                prompt_file.as_deref(),
+                assume_closest,
+                mode,
+                token.as_deref(),
+            )
+            .await
+        }
+        crate::Commands::Disable {
+            session_id,
+            reason,
+            duration,
+            token,
+        } => {
+            register::run_disable(&session_id, reason.as_deref(), duration, token.as_deref()).await
         }
-        crate::Commands::Disable { session_id } => register::run_disable(&session_id).await,
         crate::Commands::Enable { session_id } => register::run_enable(&session_id).await,
-        crate::Commands::Queue => queue::run_queue().await,
+        crate::Commands::Queue {
+            command: Some(command),
+            ..
+        } => match command {
+            crate::QueueCommand::Export { bundle } => queue_bundle::run_export(&bundle).await,
+            crate::QueueCommand::Respond {
+                bundle,
+                allow,
+                deny,
+                always_ask,
+                add_rule,
+                out,
+            } => {
+                queue_bundle::run_respond(&bundle, &allow, &deny, always_ask, add_rule, &out).await
+            }
+            crate::QueueCommand::ImportResponses { bundle } => {
+                queue_bundle::run_import(&bundle).await
+            }
+            crate::QueueCommand::Keygen { out } => queue_bundle::run_keygen(&out).await,
+        },
+        crate::Commands::Queue {
+            command: None,
+            interactive,
+            sort,
+            wide,
+        } => {
+            if interactive {
+                queue_tui::run(sort).await
+            } else {
+                queue::run_queue(sort, wide).await
+            }
+        }
         crate::Commands::Approve {
             id,
             always_ask,
             add_rule,
             scope,
-        } => queue::run_approve(&id, always_ask, add_rule, &scope).await,
+            like,
+        } => {
+            if like {
+                queue::run_approve_like(&id, always_ask, add_rule, scope.as_deref()).await
+            } else {
+                queue::run_approve(&id, always_ask, add_rule, scope.as_deref()).await
+            }
+        }
         crate::Commands::Deny {
             id,
             always_ask,
             add_rule,
             scope,
-        } => queue::run_deny(&id, always_ask, add_rule, &scope).await,
+        } => queue::run_deny(&id, always_ask, add_rule, scope.as_deref()).await,
         crate::Commands::Build => build::run_build().await,
+        crate::Commands::Compact {
+            scope,
+            archive,
+            dry_run,
+        } => build::run_compact(scope.as_deref(), archive, dry_run).await,
+        crate::Commands::Dev {
+            command:
+                crate::DevCommand::SimulateHook {
+                    tool,
+                    command,
+                    file_path,
+                    content,
+                    input,
+                    format,
+                    session_id,
+                    role,
+                },
+        } => {
+            let call = dev::SimulatedCall {
+                tool: &tool,
+                command: command.as_deref(),
+                file_path: file_path.as_deref(),
+                content: content.as_deref(),
+                input: input.as_deref(),
+            };
+            dev::run_simulate_hook(&call, format, &session_id, role.as_deref()).await
+        }
+        crate::Commands::Storage {
+            command: crate::StorageCommand::Upgrade { scope, dry_run },
+        }
+        | crate::Commands::Migrate { scope, dry_run } => {
+            build::run_storage_upgrade(scope.as_deref(), dry_run).await
+        }
+        crate::Commands::Audit {
+            command: crate::AuditCommand::Verify { path, since },
+        } => audit::run_verify(path.as_deref(), since.as_deref()).await,
+        crate::Commands::Export { scope, output } => transfer::run_export(&scope, &output).await,
+        crate::Commands::Import {
+            bundle,
+            scope,
+            strategy,
+            dry_run,
+        } => transfer::run_import(&bundle, scope.as_deref(), strategy, dry_run).await,
+        crate::Commands::Schema => {
+            let schema = crate::schema::decision_record();
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+            Ok(())
+        }
         crate::Commands::Invalidate { role, scope, all } => {
             build::run_invalidate(role.as_deref(), scope.as_deref(), all).await
         }
@@ -70,19 +196,107 @@ pub async fn dispatch(command: crate::Commands) -> Result<()> {
             )
             .await
         }
+        crate::Commands::Prune {
+            dry_run,
+            older_than,
+            archive,
+        } => {
+            queue::run_prune(dry_run).await?;
+            build::run_prune_decisions(older_than, archive, dry_run).await
+        }
+        crate::Commands::Reconcile {
+            max_commits,
+            dry_run,
+        } => reconcile::run(max_commits, dry_run).await,
+        crate::Commands::Explain { supervisor } => explain::run(supervisor.as_deref()).await,
+        crate::Commands::Feedback {
+            decision_id,
+            should_have_been,
+            reason,
+            add_rule,
+            scope,
+        } => {
+            feedback::run(
+                decision_id.as_deref(),
+                should_have_been.as_deref(),
+                reason.as_deref(),
+                add_rule,
+                &scope,
+            )
+            .await
+        }
+        crate::Commands::Promote { rule_id, to, out } => {
+            promote::run(rule_id.as_deref(), &to, out.as_deref()).await
+        }
         crate::Commands::Monitor => monitor::run_monitor().await,
         crate::Commands::Stats => monitor::run_stats().await,
-        crate::Commands::Scan { staged, path } => scan::run(staged, path.as_deref()).await,
-        crate::Commands::Init => init::run().await,
-        crate::Commands::Config => run_config().await,
-        crate::Commands::Sync => run_sync().await,
+        crate::Commands::Status => status::run().await,
+        crate::Commands::Scan {
+            staged,
+            path,
+            format,
+            baseline,
+            update_baseline,
+            min_severity,
+            verbose,
+        } => {
+            scan::run(
+                staged,
+                path.as_deref(),
+                format,
+                baseline.as_deref(),
+                update_baseline,
+                min_severity,
+                verbose,
+            )
+            .await
+        }
+        crate::Commands::Sanitize {
+            command: Some(crate::SanitizeCommand::Test { corpus }),
+            ..
+        } => sanitize::run_test(&corpus).await,
+        crate::Commands::Sanitize {
+            command: Some(crate::SanitizeCommand::Preview { input, tool, role }),
+            ..
+        } => sanitize::run_preview(input, &tool, &role).await,
+        crate::Commands::Sanitize {
+            command: None,
+            file,
+            ..
+        } => sanitize::run(file.as_deref()).await,
+        crate::Commands::Init {
+            hooks,
+            print,
+            manager,
+        } => init::run(hooks, print, manager).await,
+        crate::Commands::Config { show_origin } => run_config(show_origin).await,
+        crate::Commands::Sync { push } => sync::run(push).await,
+        crate::Commands::MigrateDirs { dry_run } => migrate_dirs::run(dry_run).await,
+        #[cfg(feature = "mcp")]
         crate::Commands::McpServer => mcp_server::run().await,
-        crate::Commands::SelfUpdate { check } => self_update::run(check).await,
+        #[cfg(not(feature = "mcp"))]
+        crate::Commands::McpServer => Err(not_compiled_in("mcp-server", "mcp")),
+        #[cfg(feature = "self-update")]
+        crate::Commands::SelfUpdate { check, install_dir } => {
+            self_update::run(check, install_dir.as_deref()).await
+        }
+        #[cfg(not(feature = "self-update"))]
+        crate::Commands::SelfUpdate { .. } => Err(not_compiled_in("self-update", "self-update")),
+        #[cfg(feature = "self-update")]
+        crate::Commands::Maintain { every, force } => maintain::run(every, force).await,
+        #[cfg(not(feature = "self-update"))]
+        crate::Commands::Maintain { .. } => Err(not_compiled_in("maintain", "self-update")),
     }
 }
 
+/// The error for a subcommand whose feature this build left out.
+#[cfg(not(all(feature = "mcp", feature = "self-update")))]
+fn not_compiled_in(what: &'static str, feature: &'static str) -> crate::error::HookwiseError {
+    crate::error::HookwiseError::NotCompiledIn { what, feature }
+}
+
 /// Display global and project configuration.
-async fn run_config() -> Result<()> {
+async fn run_config(show_origin: bool) -> Result<()> {
     // Show global config
     let global_dir = dirs_global();
     let global_config_path = global_dir.join("config.yml");
@@ -97,6 +311,12 @@ async fn run_config() -> Result<()> {
             if let Some(model) = &config.embedding_model {
                 println!("  Embedding model: {}", model);
             }
+            if !config.updates.hints {
+                println!("  Update hints: off");
+            }
+            if let Some(mirror) = &config.updates.mirror {
+                println!("  Release mirror: {}", mirror);
+            }
         }
         None => {
             println!("  (not configured)");
@@ -108,8 +328,12 @@ async fn run_config() -> Result<()> {
     let project_config_path = cwd.join(".hookwise").join("policy.yml");
 
     println!("\nProject config: {}", project_config_path.display());
-    if project_config_path.exists() {
-        let policy = PolicyConfig::load_project(&cwd)?;
+    let layered = PolicyConfig::load_layered(&cwd)?;
+    if let Some(path) = &layered.machine_path {
+        println!("  Machine policy: {}", path.display());
+    }
+    if project_config_path.exists() || layered.machine_path.is_some() {
+        let policy = &layered.policy;
         println!(
             "  Sensitive paths (ask_write): {:?}",
             policy.sensitive_paths.ask_write
@@ -119,16 +343,26 @@ async fn run_config() -> Result<()> {
             policy.confidence.org, policy.confidence.project, policy.confidence.user
         );
         println!(
-            "  Similarity: jaccard={}, embedding={}, min_tokens={}",
+            "  Similarity: jaccard={}, embedding={}, min_tokens={}, token_mode={}",
             policy.similarity.jaccard_threshold,
             policy.similarity.embedding_threshold,
-            policy.similarity.jaccard_min_tokens
+            policy.similarity.jaccard_min_tokens,
+            policy.similarity.token_mode
         );
         println!("  Human timeout: {}s", policy.human_timeout_secs);
         println!(
             "  Registration timeout: {}s",
             policy.registration_timeout_secs
         );
+        if show_origin {
+            println!("  Origins:");
+            if layered.origins.is_empty() {
+                println!("    (all defaults)");
+            }
+            for (setting, origin) in &layered.origins {
+                println!("    {}: {}", setting, origin);
+            }
+        }
     } else {
         println!("  (not initialized -- run `hookwise init`)");
     }
@@ -136,13 +370,6 @@ async fn run_config() -> Result<()> {
     Ok(())
 }
 
-/// Pull latest org-level rules (placeholder).
-async fn run_sync() -> Result<()> {
-    eprintln!("hookwise: sync is not yet implemented.");
-    eprintln!("Org-level rule syncing will be available in a future release.");
-    Ok(())
-}
-
 fn dirs_global() -> PathBuf {
     crate::config::dirs_global()
 }
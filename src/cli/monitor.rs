@@ -1,13 +1,22 @@
 use std::path::PathBuf;
 
 use crate::cascade::cache::ExactCache;
+use crate::cascade::human::{
+    load_queue_file, load_sla_records, sla_log_path, sort_pending, summarize_sla, Priority,
+    QueueSort,
+};
+use crate::cli::queue_table::truncate_input;
 use crate::error::Result;
+use crate::notify::format_elapsed;
 use crate::scope::ScopeLevel;
+use crate::session::SessionManager;
 use crate::storage::jsonl::JsonlStorage;
 use crate::storage::StorageBackend;
+use crate::watch::FileWatcher;
 
 /// Stream decisions in real time.
 /// Watches the JSONL rule files for changes and prints new decisions.
+/// Newly queued human decisions are printed highest priority first.
 pub async fn run_monitor() -> Result<()> {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     let project_root = cwd.join(".hookwise");
@@ -27,8 +36,39 @@ pub async fn run_monitor() -> Result<()> {
         last_sizes.insert(file.to_string(), size);
     }
 
+    let mut seen_pending: std::collections::HashSet<String> =
+        load_queue_file().pending.into_keys().collect();
+
+    // Wake on changes, with a periodic re-check in case a change was missed.
+    let mut watched: Vec<PathBuf> = ["allow.jsonl", "deny.jsonl", "ask.jsonl"]
+        .iter()
+        .map(|file| rules_dir.join(file))
+        .collect();
+    watched.push(crate::cascade::human::pending_queue_path());
+    let mut watcher = FileWatcher::new(watched).start()?;
+
     loop {
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        watcher
+            .changed_within(std::time::Duration::from_secs(1))
+            .await;
+
+        let mut new_pending: Vec<_> = load_queue_file()
+            .pending
+            .into_values()
+            .filter(|p| !seen_pending.contains(&p.id))
+            .collect();
+        sort_pending(&mut new_pending, QueueSort::Priority);
+        for pending in new_pending {
+            println!(
+                "[{}] PENDING ({}) {} {} -- id {}",
+                pending.queued_at.format("%H:%M:%S"),
+                pending.priority,
+                pending.tool_name,
+                pending.role,
+                pending.id,
+            );
+            seen_pending.insert(pending.id);
+        }
 
         for file in &["allow.jsonl", "deny.jsonl", "ask.jsonl"] {
             let path = rules_dir.join(file);
@@ -83,14 +123,17 @@ pub async fn run_monitor() -> Result<()> {
 pub async fn run_stats() -> Result<()> {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     let project_root = cwd.join(".hookwise");
-    let global_root = dirs_global();
+    let global_root = dirs_rules();
 
-    let storage = JsonlStorage::new(project_root, global_root, None);
+    let policy = crate::cli::queue::load_policy();
+    let expiry = policy.cache.clone();
+    let storage =
+        JsonlStorage::new(project_root.clone(), global_root, None).with_expiry(expiry.clone());
 
     let decisions = storage.load_decisions(ScopeLevel::Project)?;
 
     // Build an ExactCache to get stats
-    let cache = ExactCache::new();
+    let cache = ExactCache::new().with_expiry(expiry);
     cache.load_from(decisions.clone());
     let stats = cache.stats();
 
@@ -130,9 +173,131 @@ pub async fn run_stats() -> Result<()> {
         println!("  {}: {}", tool, count);
     }
 
+    println!("\nBy assistant:");
+    for (client, count) in crate::decision::count_by_client(&decisions) {
+        println!("  {}: {}", client, count);
+    }
+
+    let secrets = crate::sanitize::summarize_secrets(&decisions);
+    if secrets.calls > 0 {
+        println!("\nSecrets redacted ({} calls):", secrets.calls);
+        for (label, counts) in [
+            ("By kind", &secrets.by_kind),
+            ("By role", &secrets.by_role),
+            ("By command", &secrets.by_command),
+        ] {
+            let mut counts: Vec<_> = counts.iter().collect();
+            counts.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            println!("  {}:", label);
+            for (name, count) in counts {
+                println!("    {}: {}", name, count);
+            }
+        }
+    }
+
+    // Built-in command risk of stored decisions
+    let risk =
+        crate::cascade::command_risk::summarize_command_risk(&decisions, &policy.command_risk, 5);
+    if let Some(mean) = risk.mean {
+        println!("\nCommand risk ({} scored, mean {:.1}):", risk.scored, mean);
+        for band in [Priority::Critical, Priority::High, Priority::Low] {
+            if let Some(count) = risk.by_band.get(&band) {
+                println!("  {}: {}", band, count);
+            }
+        }
+        println!("  Riskiest:");
+        for (score, tool, input) in &risk.top {
+            println!("    {:.1}  {}: {}", score, tool, truncate_input(input, 60));
+        }
+    }
+
+    // Human response SLA over the last week
+    let records = load_sla_records(&sla_log_path(&project_root));
+    let summary = summarize_sla(&records, chrono::Utc::now() - chrono::Duration::days(7));
+    println!("\nHuman response SLA (last 7 days):");
+    println!("  Responded: {}", summary.responded);
+    println!("  Timed out: {}", summary.timed_out);
+    println!("  Overdue:   {}", summary.overdue);
+    println!("  Nags sent: {}", summary.nags);
+    if let (Some(median), Some(p90), Some(max)) = (
+        summary.median_latency_secs,
+        summary.p90_latency_secs,
+        summary.max_latency_secs,
+    ) {
+        println!(
+            "  Latency:   median {:.0}s, p90 {:.0}s, max {:.0}s",
+            median, p90, max
+        );
+    }
+
+    // Evaluations near or past the deadline over the last week
+    let events = crate::cascade::deadline::load_events(
+        &crate::cascade::deadline::deadline_log_path(&project_root),
+    );
+    let summary = crate::cascade::deadline::summarize(
+        &events,
+        chrono::Utc::now() - chrono::Duration::days(7),
+    );
+    if let Some(max) = summary.max_elapsed_ms {
+        println!("\nSlow evaluations (last 7 days):");
+        println!("  Near deadline: {}", summary.near);
+        for (tier, count) in &summary.near_by_tier {
+            println!("    {}: {}", tier, count);
+        }
+        println!("  Exceeded:      {}", summary.exceeded);
+        println!("  Slowest:       {:.1}s", max as f64 / 1000.0);
+    }
+
+    // Canary rollout of org rules: the latest rollout seen in the log
+    let events = crate::rollout::load_events(&crate::rollout::rollout_log_path(&project_root));
+    if let Some(since) = events.iter().map(|e| e.since).max() {
+        let summary = crate::rollout::summarize(&events, since);
+        println!("\nOrg rule rollout (rules since {}):", since.to_rfc3339());
+        println!("  Canary hits:  {}", summary.canary_hits);
+        println!("  Shadow hits:  {}", summary.shadow_hits);
+        match summary.divergence_rate() {
+            Some(rate) => println!(
+                "  Diverged:     {} ({:.1}%)",
+                summary.diverged,
+                rate * 100.0
+            ),
+            None => println!("  Diverged:     0"),
+        }
+        for ((enforced, rolled_out), count) in &summary.changes {
+            println!("    {} -> {}: {}", enforced, rolled_out, count);
+        }
+    }
+
+    // Rolling risk per session (denied, sensitive and novel calls)
+    let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
+    let risks = SessionManager::new(team_id.as_deref()).risks(&policy.risk)?;
+    if !risks.is_empty() {
+        println!(
+            "\nSession risk (last {}):",
+            format_elapsed(
+                i64::try_from(policy.risk.window_secs)
+                    .ok()
+                    .and_then(chrono::Duration::try_seconds)
+                    .unwrap_or(chrono::Duration::MAX)
+            )
+        );
+        for (session_id, risk) in risks {
+            let score = risk.score(&policy.risk.weights);
+            let over = if policy.risk.exceeded(score) {
+                " -- over threshold, tightened"
+            } else {
+                ""
+            };
+            println!(
+                "  {}: {:.1} ({} denied, {} sensitive, {} novel){}",
+                session_id, score, risk.denied, risk.sensitive, risk.novel, over
+            );
+        }
+    }
+
     Ok(())
 }
 
-fn dirs_global() -> PathBuf {
-    crate::config::dirs_global()
+fn dirs_rules() -> PathBuf {
+    crate::config::dirs_rules()
 }
@@ -63,6 +63,12 @@ pub async fn run(
             ),
             matched_key: None,
             similarity_score: None,
+            targets: Vec::new(),
+            message: None,
+            suggestion: None,
+            risk_score: None,
+            secrets: Vec::new(),
+            client: None,
         },
         timestamp: Utc::now(),
         scope: scope_level,
@@ -72,7 +78,7 @@ pub async fn run(
 
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     let project_root = cwd.join(".hookwise");
-    let global_root = dirs_global();
+    let global_root = dirs_rules();
 
     let storage = JsonlStorage::new(project_root, global_root, None);
     storage.save_decision(&record)?;
@@ -85,6 +91,6 @@ pub async fn run(
     Ok(())
 }
 
-fn dirs_global() -> PathBuf {
-    crate::config::dirs_global()
+fn dirs_rules() -> PathBuf {
+    crate::config::dirs_rules()
 }
@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+
+use crate::audit::{load_events, AuditSink};
+use crate::cli::queue_table::truncate_input;
+use crate::decision::{DecisionRecord, DecisionTier};
+use crate::error::{HookwiseError, Result};
+use crate::feedback::{feedback_log_path, load_feedback};
+use crate::notify::format_elapsed;
+use crate::promote::{proposals_dir, rule_usage, PromotionProposal};
+use crate::scope::ScopeLevel;
+use crate::storage::jsonl::JsonlStorage;
+use crate::storage::StorageBackend;
+
+/// Number of rules listed when no id is given.
+const RECENT_LIMIT: usize = 20;
+
+/// Write a proposal to promote a rule to a broader scope, or list rules
+/// that could be promoted.
+pub async fn run(rule_id: Option<&str>, to: &str, out: Option<&Path>) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let project_root = cwd.join(".hookwise");
+    let policy = crate::cli::queue::load_policy();
+    let storage = JsonlStorage::new(project_root.clone(), crate::config::dirs_rules(), None)
+        .with_expiry(policy.cache.clone());
+
+    let mut decisions = storage.load_decisions(ScopeLevel::Project)?;
+    decisions.extend(storage.load_decisions(ScopeLevel::User)?);
+    let audit = AuditSink::from_config(&policy.audit, &cwd)
+        .map(|sink| load_events(sink.path()))
+        .unwrap_or_default();
+    let feedback = load_feedback(&feedback_log_path(&project_root));
+
+    let Some(id) = rule_id else {
+        let mut rules: Vec<&DecisionRecord> = decisions
+            .iter()
+            .filter(|r| {
+                matches!(
+                    r.metadata.tier,
+                    DecisionTier::Human | DecisionTier::Override
+                )
+            })
+            .collect();
+        if rules.is_empty() {
+            println!("No project or user rules recorded for this project.");
+            return Ok(());
+        }
+        rules.sort_by_key(|r| r.timestamp);
+        println!("Rules (newest first):");
+        for rule in rules.iter().rev().take(RECENT_LIMIT) {
+            let usage = rule_usage(rule, &decisions, &audit, &feedback);
+            println!(
+                "  {}  {:<7}  {} {} -> {}  {} hit(s), {} match(es)  {}",
+                rule.id(),
+                rule.scope,
+                rule.key.role,
+                rule.key.tool,
+                rule.decision,
+                usage.audited_hits,
+                usage.similarity_matches,
+                truncate_input(&rule.key.sanitized_input, 50)
+            );
+        }
+        println!("\nPropose one for org scope with: hookwise promote <id> --to org");
+        return Ok(());
+    };
+
+    let to = to
+        .parse::<ScopeLevel>()
+        .map_err(|e| HookwiseError::InvalidPolicy { reason: e })?;
+    let rule = decisions
+        .iter()
+        .find(|r| r.id() == id)
+        .ok_or_else(|| HookwiseError::Storage {
+            reason: format!("no project or user rule with id {}", id),
+        })?;
+    let usage = rule_usage(rule, &decisions, &audit, &feedback);
+    let project = cwd
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let proposal = PromotionProposal::new(
+        rule,
+        to,
+        usage,
+        &crate::identity::current(&cwd).user,
+        &project,
+    )?;
+    let dir = out
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| proposals_dir(&project_root));
+    let path = proposal.write(&dir)?;
+
+    let usage = &proposal.usage;
+    let last_used = usage
+        .last_used
+        .map(|at| format!(", last used {} ago", format_elapsed(Utc::now() - at)))
+        .unwrap_or_default();
+    eprintln!("hookwise: wrote promotion proposal {}", path.display());
+    eprintln!(
+        "  {} {} {} ({} -> {})",
+        rule.decision, rule.key.role, rule.key.tool, proposal.from, proposal.to
+    );
+    eprintln!(
+        "  {} audited hit(s), {} similarity match(es), {} correction(s){}",
+        usage.audited_hits, usage.similarity_matches, usage.corrections, last_used
+    );
+    if usage.corrections > 0 {
+        eprintln!("  note: this rule's decisions have been corrected; review before promoting");
+    }
+    eprintln!(
+        "Submit it for review in the {} rules repository; {} scope is not changed locally.",
+        proposal.to, proposal.to
+    );
+    Ok(())
+}
@@ -1,43 +1,104 @@
-use crate::cascade::human::{load_queue_file, DecisionQueue, HumanResponse};
+use crate::audit::{AuditEvent, AuditSink};
+use crate::cascade::human::{
+    is_orphaned, is_overdue, load_queue_file, orphaned_record, process_alive, sort_pending,
+    timed_out_record, waited, DecisionQueue, HumanResponse, PendingDecision, PruneReport,
+    QueueSort,
+};
+use crate::cli::queue_table::{render_table, TableOptions};
+use crate::config::{HumanSlaConfig, OrphanPolicy, PolicyConfig, RolesConfig};
 use crate::decision::Decision;
 use crate::error::Result;
+use crate::identity;
 use crate::scope::ScopeLevel;
+use crate::storage::jsonl::JsonlStorage;
+use crate::storage::StorageBackend;
 
+use std::path::Path;
 use std::sync::Arc;
 
-/// List pending permission decisions.
-pub async fn run_queue() -> Result<()> {
-    let state = load_queue_file();
-    let pending: Vec<_> = state.pending.values().cloned().collect();
+/// List pending permission decisions in the requested order, one aligned
+/// row each (full inputs with `wide`). Entries whose waiting process has
+/// exited are reported as orphaned but left in place: listing changes
+/// nothing, and `hookwise prune` or the next check in the entry's own
+/// project resolves them into that project's storage.
+pub async fn run_queue(sort: QueueSort, wide: bool) -> Result<()> {
+    let policy = load_policy();
+    let now = chrono::Utc::now();
+    let (mut orphaned, mut pending): (Vec<_>, Vec<_>) = load_queue_file()
+        .pending
+        .into_values()
+        .partition(|p| is_orphaned(p, now, process_alive));
+    orphaned.sort_by(|a, b| a.queued_at.cmp(&b.queued_at).then_with(|| a.id.cmp(&b.id)));
+    for pending in &orphaned {
+        let project = pending
+            .project_root
+            .as_ref()
+            .map_or(String::new(), |root| format!(" in {}", root.display()));
+        println!(
+            "[ORPHANED] {} ({} {}{}) -- waiter exited, {} on the next prune",
+            pending.id, pending.role, pending.tool_name, project, policy.orphaned_queue
+        );
+    }
+    if !orphaned.is_empty() {
+        println!();
+    }
+
+    sort_pending(&mut pending, sort);
 
     if pending.is_empty() {
         println!("No pending decisions.");
         return Ok(());
     }
 
-    for decision in &pending {
-        println!(
-            "ID: {}\n  Role: {}\n  Tool: {}\n  Input: {}\n  File: {}\n  Queued: {}\n",
-            decision.id,
-            decision.role,
-            decision.tool_name,
-            truncate(&decision.sanitized_input, 80),
-            decision.file_path.as_deref().unwrap_or("-"),
-            decision.queued_at,
-        );
+    let sla = load_sla_config();
+    let options = TableOptions::for_stdout(wide);
+    for line in render_table(&pending, &sla, chrono::Utc::now(), options) {
+        println!("{}", line);
     }
+    println!();
 
     println!("{} pending decision(s)", pending.len());
     Ok(())
 }
 
+/// Load the current project's policy, falling back to defaults when there is
+/// no policy or it fails to parse.
+pub(crate) fn load_policy() -> PolicyConfig {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| PolicyConfig::load_project(&cwd).ok())
+        .unwrap_or_default()
+}
+
+/// Load the human SLA settings from the current project's policy, with
+/// unset thresholds derived from its timeouts.
+pub(crate) fn load_sla_config() -> HumanSlaConfig {
+    load_policy().effective_human_sla()
+}
+
+/// Label for an overdue pending decision, e.g. `OVERDUE 12m`.
+pub(crate) fn overdue_label(
+    decision: &PendingDecision,
+    sla: &HumanSlaConfig,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<String> {
+    is_overdue(decision, sla, now).then(|| format!("OVERDUE {}", waited(decision, now)))
+}
+
 /// Approve a pending decision. Writes the response to the file-backed queue
 /// so the blocking `check` process can pick it up.
-pub async fn run_approve(id: &str, always_ask: bool, add_rule: bool, scope: &str) -> Result<()> {
+pub async fn run_approve(
+    id: &str,
+    always_ask: bool,
+    add_rule: bool,
+    scope: Option<&str>,
+) -> Result<()> {
     let queue = Arc::new(DecisionQueue::new());
+    let policy = load_policy();
+    let approver = identity::approver(&policy.approvals)?;
 
     let rule_scope = if add_rule {
-        Some(parse_scope(scope)?)
+        Some(resolve_rule_scope(id, scope, &policy)?)
     } else {
         None
     };
@@ -47,6 +108,8 @@ pub async fn run_approve(id: &str, always_ask: bool, add_rule: bool, scope: &str
         always_ask,
         add_rule,
         rule_scope,
+        batch: None,
+        responded_by: Some(approver.user),
     };
 
     queue.respond(id, response)?;
@@ -55,7 +118,58 @@ pub async fn run_approve(id: &str, always_ask: bool, add_rule: bool, scope: &str
     if always_ask {
         eprintln!("  (cached as 'ask' -- will always prompt)");
     }
-    if add_rule {
+    if let Some(scope) = rule_scope {
+        eprintln!("  (added as persistent rule at scope '{}')", scope);
+    }
+
+    Ok(())
+}
+
+/// Approve a pending decision and every similar pending entry. Similarity
+/// uses the project's Jaccard threshold; only the given decision is cached
+/// as a rule.
+pub async fn run_approve_like(
+    id: &str,
+    always_ask: bool,
+    add_rule: bool,
+    scope: Option<&str>,
+) -> Result<()> {
+    let queue = Arc::new(DecisionQueue::new());
+    let policy = load_policy();
+    let similarity = &policy.similarity;
+    let approver = identity::approver(&policy.approvals)?;
+
+    let rule_scope = if add_rule {
+        Some(resolve_rule_scope(id, scope, &policy)?)
+    } else {
+        None
+    };
+
+    let response = HumanResponse {
+        decision: Decision::Allow,
+        always_ask,
+        add_rule,
+        rule_scope,
+        batch: None,
+        responded_by: Some(approver.user),
+    };
+
+    let members = queue.respond_like(
+        id,
+        response,
+        similarity.jaccard_threshold,
+        similarity.jaccard_min_tokens,
+    )?;
+    eprintln!("hookwise: approved {}", id);
+    for (member, score) in &members {
+        eprintln!("  also approved {} (similarity {:.2})", member, score);
+    }
+    eprintln!("  {} similar decision(s) approved", members.len());
+
+    if always_ask {
+        eprintln!("  (cached as 'ask' -- will always prompt)");
+    }
+    if let Some(scope) = rule_scope {
         eprintln!("  (added as persistent rule at scope '{}')", scope);
     }
 
@@ -64,11 +178,18 @@ pub async fn run_approve(id: &str, always_ask: bool, add_rule: bool, scope: &str
 
 /// Deny a pending decision. Writes the response to the file-backed queue
 /// so the blocking `check` process can pick it up.
-pub async fn run_deny(id: &str, always_ask: bool, add_rule: bool, scope: &str) -> Result<()> {
+pub async fn run_deny(
+    id: &str,
+    always_ask: bool,
+    add_rule: bool,
+    scope: Option<&str>,
+) -> Result<()> {
     let queue = Arc::new(DecisionQueue::new());
+    let policy = load_policy();
+    let approver = identity::approver(&policy.approvals)?;
 
     let rule_scope = if add_rule {
-        Some(parse_scope(scope)?)
+        Some(resolve_rule_scope(id, scope, &policy)?)
     } else {
         None
     };
@@ -78,6 +199,8 @@ pub async fn run_deny(id: &str, always_ask: bool, add_rule: bool, scope: &str) -
         always_ask,
         add_rule,
         rule_scope,
+        batch: None,
+        responded_by: Some(approver.user),
     };
 
     queue.respond(id, response)?;
@@ -86,24 +209,124 @@ pub async fn run_deny(id: &str, always_ask: bool, add_rule: bool, scope: &str) -
     if always_ask {
         eprintln!("  (cached as 'ask' -- will always prompt)");
     }
-    if add_rule {
+    if let Some(scope) = rule_scope {
         eprintln!("  (added as persistent rule at scope '{}')", scope);
     }
 
     Ok(())
 }
 
+/// Expire stale queue entries and record expired pending decisions as
+/// timed-out denies in the project's storage.
+pub async fn run_prune(dry_run: bool) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let policy = PolicyConfig::load_project(&cwd)?;
+    let ttl_secs = policy.effective_queue_ttl_secs();
+    let storage = JsonlStorage::new(cwd.join(".hookwise"), crate::config::dirs_rules(), None);
+
+    let report = prune_and_record(&DecisionQueue::new(), &storage, &policy, &cwd, dry_run)?;
+    for pending in &report.expired {
+        println!(
+            "expired {} ({} {}, queued {})",
+            pending.id, pending.role, pending.tool_name, pending.queued_at
+        );
+    }
+    for pending in &report.orphaned {
+        println!(
+            "orphaned {} ({} {}, waiter exited, {})",
+            pending.id, pending.role, pending.tool_name, policy.orphaned_queue
+        );
+    }
+    for id in &report.orphaned_responses {
+        println!("orphaned response {}", id);
+    }
+
+    let verb = if dry_run { "would be pruned" } else { "pruned" };
+    println!(
+        "{} expired, {} orphaned pending and {} orphaned response(s) {} (ttl {}s)",
+        report.expired.len(),
+        report.orphaned.len(),
+        report.orphaned_responses.len(),
+        verb,
+        ttl_secs
+    );
+    Ok(())
+}
+
+/// Prune the queue entries of the project at `cwd` under its TTL, store a
+/// timed-out deny for every expired pending decision, and resolve orphaned
+/// ones per `orphaned_queue`. Other projects' entries are left for them to
+/// prune into their own storage. Nothing is written when `dry_run` is set.
+pub(crate) fn prune_and_record(
+    queue: &DecisionQueue,
+    storage: &dyn StorageBackend,
+    policy: &PolicyConfig,
+    cwd: &Path,
+    dry_run: bool,
+) -> Result<PruneReport> {
+    let ttl_secs = policy.effective_queue_ttl_secs();
+    let report = queue.prune(ttl_secs, Some(cwd), dry_run)?;
+    if !dry_run {
+        for pending in &report.expired {
+            storage.save_decision(&timed_out_record(pending, ttl_secs))?;
+        }
+        record_orphans(&report.orphaned, storage, policy, cwd)?;
+    }
+    Ok(report)
+}
+
+/// Resolve removed orphaned decisions: store a deny rule under
+/// [`OrphanPolicy::Deny`] and note each one in the audit log, if configured.
+pub(crate) fn record_orphans(
+    orphaned: &[PendingDecision],
+    storage: &dyn StorageBackend,
+    policy: &PolicyConfig,
+    cwd: &Path,
+) -> Result<()> {
+    let audit = AuditSink::from_config(&policy.audit, cwd);
+    for pending in orphaned {
+        let record = orphaned_record(pending, policy.orphaned_queue);
+        if policy.orphaned_queue == OrphanPolicy::Deny {
+            storage.save_decision(&record)?;
+        }
+        if let Some(sink) = &audit {
+            let event = AuditEvent {
+                logged_at: chrono::Utc::now(),
+                cwd: cwd.display().to_string(),
+                record,
+                identity: Some(crate::identity::current(cwd)),
+                not_enforced: false,
+            };
+            if let Err(e) = sink.record(&event) {
+                eprintln!("hookwise: audit log write failed ({})", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Scope for a rule added while answering pending decision `id`: `scope`
+/// when given, else the default for the decision's role
+/// ([`PolicyConfig::rule_scope_for`]).
+pub(crate) fn resolve_rule_scope(
+    id: &str,
+    scope: Option<&str>,
+    policy: &PolicyConfig,
+) -> Result<ScopeLevel> {
+    if let Some(scope) = scope {
+        return parse_scope(scope);
+    }
+    let roles = RolesConfig::load_project(&std::env::current_dir()?)?;
+    let role = load_queue_file()
+        .pending
+        .get(id)
+        .map(|pending| pending.role.clone())
+        .unwrap_or_default();
+    Ok(policy.rule_scope_for(&roles, &role))
+}
+
 fn parse_scope(scope: &str) -> Result<ScopeLevel> {
     scope
         .parse::<ScopeLevel>()
         .map_err(|e| crate::error::HookwiseError::InvalidPolicy { reason: e })
 }
-
-fn truncate(s: &str, max: usize) -> String {
-    if s.chars().count() <= max {
-        s.to_string()
-    } else {
-        let truncated: String = s.chars().take(max).collect();
-        format!("{}...", truncated)
-    }
-}
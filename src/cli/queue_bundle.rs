@@ -0,0 +1,784 @@
+//! Offline approval bundles: pending decisions are exported into a signed
+//! tar (`queue export`), answered on the far side of an air gap
+//! (`queue respond`), and the signed responses are applied back to the
+//! queue (`queue import-responses`).
+//!
+//! Each bundle holds `manifest.json`, the payload it describes and
+//! `manifest.sig`, an Ed25519 signature of the manifest by the side that
+//! wrote it. The manifest carries the payload's SHA-256, so the signature
+//! covers both. Each side holds its own private key and the public keys it
+//! trusts (`offline_approval.trusted_keys`), so the gated machine never has
+//! a key that could sign responses, and the approver is whoever owns the
+//! key that verifies. A responses bundle must answer a pending bundle this
+//! machine exported and not yet imported answers for ([`BundleLedger`]),
+//! and must not verify against this machine's own key.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::cascade::human::{load_queue_file, DecisionQueue, HumanResponse, PendingDecision};
+use crate::cli::queue::{load_policy, load_sla_config};
+use crate::cli::queue_table::{render_table, TableOptions};
+use crate::config::{GlobalDirs, OfflineApprovalConfig, RolesConfig};
+use crate::decision::{CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier};
+use crate::error::{HookwiseError, Result};
+use crate::identity::{self, Identity};
+use crate::session::registration::{write_atomic, FileLock};
+use crate::storage::jsonl::JsonlStorage;
+use crate::storage::StorageBackend;
+
+/// Environment variable holding this side's private bundle key.
+pub const KEY_ENV: &str = "HOOKWISE_BUNDLE_KEY";
+/// Bundle format version written into manifests.
+pub const BUNDLE_VERSION: u32 = 2;
+/// Provider recorded on identities taken from a bundle's signing key.
+pub const KEY_PROVIDER: &str = "bundle-key";
+
+const MANIFEST: &str = "manifest.json";
+const SIGNATURE: &str = "manifest.sig";
+const PENDING: &str = "pending.json";
+const RESPONSES: &str = "responses.json";
+
+/// What a bundle carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleKind {
+    Pending,
+    Responses,
+}
+
+impl std::fmt::Display for BundleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundleKind::Pending => write!(f, "pending"),
+            BundleKind::Responses => write!(f, "responses"),
+        }
+    }
+}
+
+/// Signed description of a bundle's payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub kind: BundleKind,
+    /// `bnd-` followed by the first 12 hex digits of the payload hash.
+    pub bundle_id: String,
+    /// For a responses bundle, the pending bundle it answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: String,
+    pub count: usize,
+    pub payload_sha256: String,
+}
+
+/// One answer in a responses bundle. The pending decision is copied in so
+/// the answer can still be stored as a rule after the waiting call has
+/// timed out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleResponse {
+    pub id: String,
+    pub session_id: String,
+    pub role: String,
+    pub tool_name: String,
+    pub sanitized_input: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    pub decision: Decision,
+    #[serde(default)]
+    pub always_ask: bool,
+    #[serde(default)]
+    pub add_rule: bool,
+}
+
+impl BundleResponse {
+    /// Answer `pending` with `decision`.
+    pub fn answer(
+        pending: &PendingDecision,
+        decision: Decision,
+        always_ask: bool,
+        add_rule: bool,
+    ) -> Self {
+        Self {
+            id: pending.id.clone(),
+            session_id: pending.session_id.clone(),
+            role: pending.role.clone(),
+            tool_name: pending.tool_name.clone(),
+            sanitized_input: pending.sanitized_input.clone(),
+            file_path: pending.file_path.clone(),
+            decision,
+            always_ask,
+            add_rule,
+        }
+    }
+
+    /// Whether `pending` is the decision this response was written for.
+    fn answers(&self, pending: &PendingDecision) -> bool {
+        pending.role == self.role
+            && pending.tool_name == self.tool_name
+            && pending.sanitized_input == self.sanitized_input
+    }
+
+    /// The rule stored for this response at `scope`.
+    pub fn record(&self, scope: crate::scope::ScopeLevel, by: &str) -> DecisionRecord {
+        DecisionRecord {
+            key: CacheKey {
+                sanitized_input: self.sanitized_input.clone(),
+                tool: self.tool_name.clone(),
+                role: self.role.clone(),
+            },
+            decision: if self.always_ask {
+                Decision::Ask
+            } else {
+                self.decision
+            },
+            metadata: DecisionMetadata {
+                tier: DecisionTier::Human,
+                confidence: 1.0,
+                reason: format!(
+                    "human decision: {} by {} (offline bundle)",
+                    self.decision, by
+                ),
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: Utc::now(),
+            scope,
+            file_path: self.file_path.clone(),
+            session_id: self.session_id.clone(),
+        }
+    }
+}
+
+/// Payload of a responses bundle. Who answered is not part of it: the
+/// importing side takes that from the key the bundle verifies against.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponsesPayload {
+    pub responses: Vec<BundleResponse>,
+}
+
+/// A bundle whose signature verified.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedBundle<T> {
+    pub manifest: BundleManifest,
+    /// The `trusted_keys` name of the key that signed it.
+    pub signed_by: String,
+    /// That key's base64 public half.
+    pub signing_key: String,
+    pub payload: T,
+}
+
+impl<T> VerifiedBundle<T> {
+    /// Refuse a bundle signed with `own`, this side's key: a trusted list
+    /// that names it would let this machine approve its own decisions.
+    pub fn check_not_signed_by(&self, own: &Ed25519KeyPair) -> Result<()> {
+        let signing_key = STANDARD.decode(self.signing_key.trim()).unwrap_or_default();
+        if signing_key == own.public_key().as_ref() {
+            return Err(HookwiseError::Storage {
+                reason: format!(
+                    "bundle {} is signed with this machine's own key ('{}' in \
+                     offline_approval.trusted_keys); only the other side's keys may be trusted",
+                    self.manifest.bundle_id, self.signed_by
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// The signer as a verified identity, for the `approvals` check.
+    pub fn signer(&self) -> Identity {
+        Identity {
+            user: self.signed_by.clone(),
+            org: None,
+            provider: KEY_PROVIDER.to_string(),
+            verified: true,
+            expires_at: None,
+        }
+    }
+}
+
+/// What importing decided for one response.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportOutcome {
+    /// The decision is still pending: answer it in the queue.
+    Respond,
+    /// The decision is no longer pending but the approver asked for a
+    /// rule: store it directly.
+    Persist,
+    Skipped(String),
+}
+
+/// An import result for one response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportAction {
+    pub id: String,
+    pub outcome: ImportOutcome,
+}
+
+/// This side's private key: `HOOKWISE_BUNDLE_KEY`, else the contents of
+/// `offline_approval.key_file` (relative to the global config directory).
+/// Either holds the base64 PKCS#8 document `hookwise queue keygen` writes.
+/// A key file inside `project_root` is refused, since agents working in
+/// the project could read it and sign their own approvals.
+pub fn load_key(config: &OfflineApprovalConfig, project_root: &Path) -> Result<Ed25519KeyPair> {
+    let key = match (std::env::var(KEY_ENV), &config.key_file) {
+        (Ok(key), _) => key,
+        (Err(_), Some(path)) => {
+            let path = key_file_path(path, project_root)?;
+            std::fs::read_to_string(&path).map_err(|e| HookwiseError::InvalidPolicy {
+                reason: format!(
+                    "cannot read offline_approval.key_file {}: {}",
+                    path.display(),
+                    e
+                ),
+            })?
+        }
+        (Err(_), None) => {
+            return Err(HookwiseError::InvalidPolicy {
+                reason: format!(
+                    "no bundle key: set {} or offline_approval.key_file in policy.yml \
+                     (create one with `hookwise queue keygen`)",
+                    KEY_ENV
+                ),
+            })
+        }
+    };
+    parse_key(&key)
+}
+
+/// Where `key_file` points: relative paths are taken from the global config
+/// directory. Refused when it resolves inside `project_root`.
+pub fn key_file_path(key_file: &Path, project_root: &Path) -> Result<PathBuf> {
+    let path = GlobalDirs::from_env().config.join(key_file);
+    let resolved = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+    let root = std::fs::canonicalize(project_root).unwrap_or_else(|_| project_root.to_path_buf());
+    if resolved.starts_with(&root) {
+        return Err(HookwiseError::InvalidPolicy {
+            reason: format!(
+                "offline_approval.key_file {} is inside the project, where agents can read it; \
+                 keep the key under {} or set {}",
+                path.display(),
+                GlobalDirs::from_env().config.display(),
+                KEY_ENV
+            ),
+        });
+    }
+    Ok(path)
+}
+
+/// Parse a base64 PKCS#8 Ed25519 private key.
+pub fn parse_key(text: &str) -> Result<Ed25519KeyPair> {
+    let invalid = |reason: String| HookwiseError::InvalidPolicy {
+        reason: format!("the bundle key is not an Ed25519 private key: {}", reason),
+    };
+    let der = STANDARD
+        .decode(text.trim())
+        .map_err(|e| invalid(e.to_string()))?;
+    Ed25519KeyPair::from_pkcs8(&der).map_err(|e| invalid(e.to_string()))
+}
+
+/// A new private key and its public half, both base64.
+pub fn generate_key() -> Result<(String, String)> {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).map_err(|_| {
+        HookwiseError::Storage {
+            reason: "cannot generate a bundle key".into(),
+        }
+    })?;
+    let private = STANDARD.encode(pkcs8.as_ref());
+    let public = public_key(&parse_key(&private)?);
+    Ok((private, public))
+}
+
+/// The base64 public half of `key`, as listed in `trusted_keys`.
+pub fn public_key(key: &Ed25519KeyPair) -> String {
+    STANDARD.encode(key.public_key().as_ref())
+}
+
+/// The name and public key of the trusted key whose signature over `data`
+/// is `signature`.
+fn trusted_signer<'a>(
+    trusted: &'a BTreeMap<String, String>,
+    data: &[u8],
+    signature: &[u8],
+) -> Option<(&'a str, &'a str)> {
+    trusted.iter().find_map(|(name, key)| {
+        let der = STANDARD.decode(key.trim()).ok()?;
+        UnparsedPublicKey::new(&ED25519, der)
+            .verify(data, signature)
+            .ok()
+            .map(|_| (name.as_str(), key.as_str()))
+    })
+}
+
+fn bundle_error(path: &Path, reason: &str) -> HookwiseError {
+    HookwiseError::Storage {
+        reason: format!("bundle {}: {}", path.display(), reason),
+    }
+}
+
+fn payload_name(kind: BundleKind) -> &'static str {
+    match kind {
+        BundleKind::Pending => PENDING,
+        BundleKind::Responses => RESPONSES,
+    }
+}
+
+/// Write a signed bundle of `kind` holding `payload` to `path`.
+pub fn write_bundle<T: Serialize>(
+    path: &Path,
+    kind: BundleKind,
+    in_reply_to: Option<&str>,
+    created_by: &str,
+    count: usize,
+    payload: &T,
+    key: &Ed25519KeyPair,
+) -> Result<BundleManifest> {
+    let payload = serde_json::to_vec_pretty(payload)?;
+    let payload_sha256 = format!("{:x}", Sha256::digest(&payload));
+    let manifest = BundleManifest {
+        version: BUNDLE_VERSION,
+        kind,
+        bundle_id: format!("bnd-{}", &payload_sha256[..12]),
+        in_reply_to: in_reply_to.map(String::from),
+        created_at: Utc::now(),
+        created_by: created_by.to_string(),
+        count,
+        payload_sha256,
+    };
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
+    let signature = STANDARD.encode(key.sign(&manifest_bytes).as_ref());
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut builder = tar::Builder::new(std::fs::File::create(path)?);
+    for (name, data) in [
+        (MANIFEST, manifest_bytes.as_slice()),
+        (payload_name(kind), payload.as_slice()),
+        (SIGNATURE, signature.as_bytes()),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(manifest.created_at.timestamp().max(0) as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, name, data)?;
+    }
+    builder.into_inner()?;
+    Ok(manifest)
+}
+
+/// Read a bundle of `kind`, checking that one of the `trusted` public keys
+/// signed it and that the payload matches the manifest.
+pub fn read_bundle<T: serde::de::DeserializeOwned>(
+    path: &Path,
+    kind: BundleKind,
+    trusted: &BTreeMap<String, String>,
+) -> Result<VerifiedBundle<T>> {
+    let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut archive = tar::Archive::new(std::fs::File::open(path)?);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        files.insert(name, data);
+    }
+    let file = |name: &str| {
+        files
+            .get(name)
+            .ok_or_else(|| bundle_error(path, &format!("missing {}", name)))
+    };
+
+    let manifest_bytes = file(MANIFEST)?;
+    let signature = STANDARD
+        .decode(String::from_utf8_lossy(file(SIGNATURE)?).trim())
+        .map_err(|_| bundle_error(path, "malformed signature"))?;
+    let (signed_by, signing_key) =
+        trusted_signer(trusted, manifest_bytes, &signature).ok_or_else(|| {
+            bundle_error(
+                path,
+                "signature does not match any offline_approval.trusted_keys",
+            )
+        })?;
+
+    let manifest: BundleManifest = serde_json::from_slice(manifest_bytes)?;
+    if manifest.version != BUNDLE_VERSION {
+        return Err(bundle_error(
+            path,
+            &format!("unsupported version {}", manifest.version),
+        ));
+    }
+    if manifest.kind != kind {
+        return Err(bundle_error(
+            path,
+            &format!("is a {} bundle, expected {}", manifest.kind, kind),
+        ));
+    }
+    let payload = file(payload_name(kind))?;
+    if format!("{:x}", Sha256::digest(payload)) != manifest.payload_sha256 {
+        return Err(bundle_error(path, "payload does not match the manifest"));
+    }
+    Ok(VerifiedBundle {
+        manifest,
+        signed_by: signed_by.to_string(),
+        signing_key: signing_key.to_string(),
+        payload: serde_json::from_slice(payload)?,
+    })
+}
+
+/// Refuse a responses bundle created more than `max_age_hours` before `now`.
+pub fn check_age(manifest: &BundleManifest, max_age_hours: u64, now: DateTime<Utc>) -> Result<()> {
+    let age = now - manifest.created_at;
+    if age > chrono::Duration::hours(max_age_hours as i64) {
+        return Err(HookwiseError::Storage {
+            reason: format!(
+                "bundle {} is {}h old (offline_approval.max_age_hours is {})",
+                manifest.bundle_id,
+                age.num_hours(),
+                max_age_hours
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// One pending bundle this machine exported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedBundle {
+    pub exported_at: DateTime<Utc>,
+    /// When a responses bundle answering it was imported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answered_at: Option<DateTime<Utc>>,
+}
+
+/// The pending bundles this machine exported, kept in the state directory
+/// so a responses bundle is only imported when it answers one of them, and
+/// only once.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BundleLedger {
+    #[serde(default)]
+    pub exported: BTreeMap<String, ExportedBundle>,
+}
+
+impl BundleLedger {
+    /// `$XDG_STATE_HOME/hookwise/offline-bundles.json`.
+    pub fn default_path() -> PathBuf {
+        GlobalDirs::from_env().state.join("offline-bundles.json")
+    }
+
+    /// Load the ledger at `path`, change it and save it, holding its lock
+    /// throughout.
+    pub fn update<R>(path: &Path, change: impl FnOnce(&mut Self) -> Result<R>) -> Result<R> {
+        let _lock = FileLock::acquire(path)?;
+        let mut ledger: Self = match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+        let result = change(&mut ledger)?;
+        write_atomic(path, &serde_json::to_vec_pretty(&ledger)?)?;
+        Ok(result)
+    }
+
+    /// Record an export. Exporting the same pending decisions again makes
+    /// the bundle answerable again.
+    pub fn record_export(&mut self, manifest: &BundleManifest) {
+        self.exported.insert(
+            manifest.bundle_id.clone(),
+            ExportedBundle {
+                exported_at: manifest.created_at,
+                answered_at: None,
+            },
+        );
+    }
+
+    /// Mark the pending bundle `responses` answers as answered, refusing a
+    /// reply to a bundle this machine didn't export or has already had
+    /// answers for. Answered entries older than `max_age_hours` are dropped:
+    /// any reply to them is refused by [`check_age`] anyway.
+    pub fn claim(
+        &mut self,
+        responses: &BundleManifest,
+        max_age_hours: u64,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let max_age = chrono::Duration::hours(max_age_hours as i64);
+        self.exported
+            .retain(|_, e| e.answered_at.is_none_or(|at| now - at <= max_age));
+
+        let refuse = |reason: String| HookwiseError::Storage {
+            reason: format!("bundle {}: {}", responses.bundle_id, reason),
+        };
+        let Some(answers) = &responses.in_reply_to else {
+            return Err(refuse(
+                "does not say which pending bundle it answers".into(),
+            ));
+        };
+        let Some(exported) = self.exported.get_mut(answers) else {
+            return Err(refuse(format!(
+                "answers {}, which this machine did not export",
+                answers
+            )));
+        };
+        if let Some(at) = exported.answered_at {
+            return Err(refuse(format!(
+                "answers {}, whose responses were already imported at {}",
+                answers,
+                at.to_rfc3339()
+            )));
+        }
+        exported.answered_at = Some(now);
+        Ok(())
+    }
+}
+
+/// Decide what to do with each response given the current queue. A
+/// response applies to a pending decision only when the role, tool and
+/// input still match; one whose decision is gone is stored as a rule when
+/// the approver asked for one.
+pub fn plan_import(
+    responses: &[BundleResponse],
+    pending: &HashMap<String, PendingDecision>,
+) -> Vec<ImportAction> {
+    responses
+        .iter()
+        .map(|response| {
+            let outcome = match pending.get(&response.id) {
+                Some(p) if response.answers(p) => ImportOutcome::Respond,
+                Some(_) => ImportOutcome::Skipped("pending decision does not match".into()),
+                None if response.add_rule => ImportOutcome::Persist,
+                None => ImportOutcome::Skipped("no longer pending".into()),
+            };
+            ImportAction {
+                id: response.id.clone(),
+                outcome,
+            }
+        })
+        .collect()
+}
+
+fn project_root() -> PathBuf {
+    std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+}
+
+/// Export every pending decision into a signed bundle.
+pub async fn run_export(bundle: &Path) -> Result<()> {
+    let cwd = project_root();
+    let policy = load_policy();
+    let key = load_key(&policy.offline_approval, &cwd)?;
+
+    let mut pending: Vec<PendingDecision> = load_queue_file().pending.into_values().collect();
+    if pending.is_empty() {
+        println!("No pending decisions.");
+        return Ok(());
+    }
+    pending.sort_by_key(|p| p.queued_at);
+
+    let by = identity::current(&cwd).user;
+    let manifest = write_bundle(
+        bundle,
+        BundleKind::Pending,
+        None,
+        &by,
+        pending.len(),
+        &pending,
+        &key,
+    )?;
+    BundleLedger::update(&BundleLedger::default_path(), |ledger| {
+        ledger.record_export(&manifest);
+        Ok(())
+    })?;
+    eprintln!(
+        "hookwise: exported {} pending decision(s) to {} ({})",
+        manifest.count,
+        bundle.display(),
+        manifest.bundle_id
+    );
+    Ok(())
+}
+
+/// Create a bundle key at `out` and print the public half to list in the
+/// other side's `offline_approval.trusted_keys`.
+pub async fn run_keygen(out: &Path) -> Result<()> {
+    if out.exists() {
+        return Err(HookwiseError::Storage {
+            reason: format!("{} already exists", out.display()),
+        });
+    }
+    let (private, public) = generate_key()?;
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(out, format!("{}\n", private))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(out, std::fs::Permissions::from_mode(0o600))?;
+    }
+    eprintln!(
+        "hookwise: wrote a private bundle key to {}; keep it out of git",
+        out.display()
+    );
+    println!("{}", public);
+    Ok(())
+}
+
+/// Answer decisions from a pending bundle into a responses bundle, or list
+/// them when no `allow`/`deny` ids are given.
+pub async fn run_respond(
+    bundle: &Path,
+    allow: &[String],
+    deny: &[String],
+    always_ask: bool,
+    add_rule: bool,
+    out: &Path,
+) -> Result<()> {
+    let cwd = project_root();
+    let policy = load_policy();
+    let VerifiedBundle {
+        manifest,
+        signed_by,
+        payload: pending,
+    } = read_bundle::<Vec<PendingDecision>>(
+        bundle,
+        BundleKind::Pending,
+        &policy.offline_approval.trusted_keys,
+    )?;
+
+    if allow.is_empty() && deny.is_empty() {
+        println!(
+            "{} from {} at {}:",
+            manifest.bundle_id,
+            signed_by,
+            manifest.created_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        let options = TableOptions::for_stdout(false);
+        for line in render_table(&pending, &load_sla_config(), Utc::now(), options) {
+            println!("{}", line);
+        }
+        for p in &pending {
+            if let Some(attachment) = &p.attachment {
+                println!("\n{} {}:", p.id, p.file_path.as_deref().unwrap_or("-"));
+                for line in attachment.to_text().lines() {
+                    println!("  | {}", line);
+                }
+            }
+        }
+        println!("\nAnswer with: hookwise queue respond <bundle> --allow <id> --deny <id>");
+        return Ok(());
+    }
+
+    let key = load_key(&policy.offline_approval, &cwd)?;
+    let approver = identity::approver(&policy.approvals)?;
+    let mut responses = Vec::new();
+    for (ids, decision) in [(allow, Decision::Allow), (deny, Decision::Deny)] {
+        for id in ids {
+            let p = pending
+                .iter()
+                .find(|p| &p.id == id)
+                .ok_or_else(|| bundle_error(bundle, &format!("no pending decision {}", id)))?;
+            responses.push(BundleResponse::answer(p, decision, always_ask, add_rule));
+        }
+    }
+    let count = responses.len();
+    let reply = write_bundle(
+        out,
+        BundleKind::Responses,
+        Some(&manifest.bundle_id),
+        &approver.user,
+        count,
+        &ResponsesPayload { responses },
+        &key,
+    )?;
+    eprintln!(
+        "hookwise: wrote {} response(s) to {} ({})",
+        count,
+        out.display(),
+        reply.bundle_id
+    );
+    Ok(())
+}
+
+/// Apply a responses bundle to the pending queue.
+pub async fn run_import(bundle: &Path) -> Result<()> {
+    let cwd = project_root();
+    let policy = load_policy();
+    let verified: VerifiedBundle<ResponsesPayload> = read_bundle(
+        bundle,
+        BundleKind::Responses,
+        &policy.offline_approval.trusted_keys,
+    )?;
+    let (manifest, payload) = (&verified.manifest, &verified.payload);
+    let max_age_hours = policy.offline_approval.max_age_hours;
+    check_age(manifest, max_age_hours, Utc::now())?;
+    verified.check_not_signed_by(&load_key(&policy.offline_approval, &cwd)?)?;
+    identity::check_approver(&policy.approvals, &verified.signer())?;
+    BundleLedger::update(&BundleLedger::default_path(), |ledger| {
+        ledger.claim(manifest, max_age_hours, Utc::now())
+    })?;
+
+    let by = &verified.signed_by;
+    let state = load_queue_file();
+    let actions = plan_import(&payload.responses, &state.pending);
+    let roles = RolesConfig::load_project(&cwd)?;
+    let queue = Arc::new(DecisionQueue::new());
+    let storage = JsonlStorage::new(cwd.join(".hookwise"), crate::config::dirs_rules(), None);
+
+    let mut applied = 0;
+    for (action, response) in actions.iter().zip(&payload.responses) {
+        let rule_scope = response
+            .add_rule
+            .then(|| policy.rule_scope_for(&roles, &response.role));
+        match &action.outcome {
+            ImportOutcome::Respond => {
+                queue.respond(
+                    &action.id,
+                    HumanResponse {
+                        decision: response.decision,
+                        always_ask: response.always_ask,
+                        add_rule: response.add_rule,
+                        rule_scope,
+                        batch: None,
+                        responded_by: Some(by.clone()),
+                    },
+                )?;
+                applied += 1;
+                println!("{} {}", response.decision, action.id);
+            }
+            ImportOutcome::Persist => {
+                let scope = rule_scope.unwrap_or(policy.rule_scope);
+                storage.save_decision(&response.record(scope, by))?;
+                applied += 1;
+                println!(
+                    "{} {} (no longer pending; stored as a {} rule)",
+                    response.decision, action.id, scope
+                );
+            }
+            ImportOutcome::Skipped(reason) => {
+                println!("skip {}: {}", action.id, reason);
+            }
+        }
+    }
+    println!(
+        "{} response(s) from {} applied ({})",
+        applied, by, manifest.bundle_id
+    );
+    Ok(())
+}
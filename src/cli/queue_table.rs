@@ -0,0 +1,234 @@
+//! Column-aligned rendering of the pending queue for `hookwise queue`.
+
+use chrono::{DateTime, Utc};
+
+use crate::cascade::human::{PendingDecision, Priority};
+use crate::cli::queue::overdue_label;
+use crate::config::HumanSlaConfig;
+use crate::decision::Decision;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const BOLD_RED: &str = "\x1b[1;31m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const DIM: &str = "\x1b[2m";
+
+/// Input column width when the terminal width is unknown.
+const DEFAULT_INPUT_WIDTH: usize = 60;
+
+/// Narrowest input column used when fitting to the terminal.
+const MIN_INPUT_WIDTH: usize = 30;
+
+const HEADERS: [&str; 7] = ["ID", "PRIORITY", "ROLE", "TOOL", "HINT", "AGE", "INPUT"];
+
+/// Rendering options for [`render_table`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TableOptions {
+    /// Emit ANSI colors.
+    pub color: bool,
+    /// Never truncate inputs.
+    pub wide: bool,
+    /// Total line width to fit the input column into, if known.
+    pub width: Option<usize>,
+}
+
+impl TableOptions {
+    /// Options for stdout: color only on a terminal without `NO_COLOR`, and
+    /// fit to the terminal width when it can be determined.
+    pub fn for_stdout(wide: bool) -> Self {
+        use std::io::IsTerminal;
+        let tty = std::io::stdout().is_terminal();
+        Self {
+            color: tty && std::env::var_os("NO_COLOR").is_none(),
+            wide,
+            width: tty
+                .then(|| ratatui::crossterm::terminal::size().ok())
+                .flatten()
+                .map(|(cols, _)| cols as usize),
+        }
+    }
+}
+
+/// Compact age such as `just now`, `45s ago`, `3m ago`, `2h ago`, `4d ago`.
+pub fn relative_time(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - then).num_seconds();
+    match secs {
+        s if s < 5 => "just now".into(),
+        s if s < 60 => format!("{}s ago", s),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86_400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86_400),
+    }
+}
+
+/// The most useful single-line view of a pending input: the command for
+/// Bash, otherwise the target path, falling back to the raw input.
+pub fn input_summary(decision: &PendingDecision) -> String {
+    let parsed = serde_json::from_str::<serde_json::Value>(&decision.sanitized_input).ok();
+    let field = |name: &str| {
+        parsed
+            .as_ref()
+            .and_then(|v| v.get(name))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    let summary = field("command")
+        .or_else(|| decision.file_path.clone())
+        .or_else(|| field("file_path"))
+        .or_else(|| field("path"))
+        .or_else(|| field("url"))
+        .or_else(|| field("pattern"))
+        .unwrap_or_else(|| decision.sanitized_input.clone());
+    summary.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Shorten `input` to at most `max` characters, keeping the leading verb
+/// and the target (the last path-like token, else the last token) and
+/// eliding the middle: `git push … origin/main`.
+pub fn truncate_input(input: &str, max: usize) -> String {
+    if input.chars().count() <= max {
+        return input.to_string();
+    }
+    let tokens: Vec<&str> = input.split_whitespace().collect();
+    let target_idx = tokens
+        .iter()
+        .rposition(|t| t.contains('/'))
+        .unwrap_or(tokens.len().saturating_sub(1));
+
+    if tokens.len() >= 2 && target_idx > 0 {
+        let target = tokens[target_idx];
+        let mut head = tokens[0].to_string();
+        let fixed = |head: &str| head.chars().count() + 3 + target.chars().count();
+        if fixed(&head) <= max {
+            for token in &tokens[1..target_idx] {
+                let candidate = format!("{} {}", head, token);
+                if fixed(&candidate) > max {
+                    break;
+                }
+                head = candidate;
+            }
+            return format!("{} … {}", head, target);
+        }
+    }
+
+    // A single huge token (or verb + target that cannot both fit): keep the
+    // start, which carries the verb or path root.
+    let kept: String = input.chars().take(max.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+fn paint(text: &str, code: Option<&str>, color: bool) -> String {
+    match code {
+        Some(code) if color => format!("{}{}{}", code, text, RESET),
+        _ => text.to_string(),
+    }
+}
+
+fn priority_color(priority: Priority) -> Option<&'static str> {
+    match priority {
+        Priority::Critical => Some(BOLD_RED),
+        Priority::High => Some(YELLOW),
+        Priority::Normal => None,
+        Priority::Low => Some(DIM),
+    }
+}
+
+/// Supervisor recommendation shown as a hint, e.g. `allow 0.92`.
+fn hint(decision: &PendingDecision) -> (String, Option<&'static str>) {
+    match &decision.recommendation {
+        Some(rec) => {
+            let code = match rec.decision {
+                Decision::Allow => GREEN,
+                Decision::Deny => RED,
+                Decision::Ask => YELLOW,
+            };
+            (
+                format!("{} {:.2}", rec.decision, rec.confidence),
+                Some(code),
+            )
+        }
+        None if decision.is_ask_reprompt => ("ask".into(), Some(YELLOW)),
+        None => ("-".into(), Some(DIM)),
+    }
+}
+
+/// Render the pending decisions as a header plus one aligned row each.
+/// Padding is computed on the plain text so colors never skew columns.
+pub fn render_table(
+    pending: &[PendingDecision],
+    sla: &HumanSlaConfig,
+    now: DateTime<Utc>,
+    options: TableOptions,
+) -> Vec<String> {
+    struct Row {
+        cells: [(String, Option<&'static str>); 6],
+        input: String,
+    }
+
+    let rows: Vec<Row> = pending
+        .iter()
+        .map(|d| {
+            let age = relative_time(d.queued_at, now);
+            let (age, age_color) = match overdue_label(d, sla, now) {
+                Some(_) => (format!("{} OVERDUE", age), Some(RED)),
+                None => (age, None),
+            };
+            Row {
+                cells: [
+                    (d.id.clone(), None),
+                    (d.priority.to_string(), priority_color(d.priority)),
+                    (d.role.clone(), None),
+                    (d.tool_name.clone(), None),
+                    hint(d),
+                    (age, age_color),
+                ],
+                input: input_summary(d),
+            }
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = HEADERS[..6].iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (w, (text, _)) in widths.iter_mut().zip(&row.cells) {
+            *w = (*w).max(text.chars().count());
+        }
+    }
+    let used: usize = widths.iter().map(|w| w + 2).sum();
+    let input_width = options
+        .width
+        .map(|total| total.saturating_sub(used).max(MIN_INPUT_WIDTH))
+        .unwrap_or(DEFAULT_INPUT_WIDTH);
+
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    let header: Vec<String> = HEADERS[..6]
+        .iter()
+        .zip(&widths)
+        .map(|(h, w)| format!("{:<w$}", h, w = w))
+        .collect();
+    lines.push(paint(
+        &format!("{}  {}", header.join("  "), HEADERS[6]),
+        Some(BOLD),
+        options.color,
+    ));
+
+    for row in rows {
+        let cells: Vec<String> = row
+            .cells
+            .iter()
+            .zip(&widths)
+            .map(|((text, code), w)| {
+                let pad = " ".repeat(w - text.chars().count());
+                format!("{}{}", paint(text, *code, options.color), pad)
+            })
+            .collect();
+        let input = if options.wide {
+            row.input
+        } else {
+            truncate_input(&row.input, input_width)
+        };
+        lines.push(format!("{}  {}", cells.join("  "), input));
+    }
+    lines
+}
@@ -0,0 +1,457 @@
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::cascade::human::{
+    load_queue_file, sort_pending, DecisionQueue, HumanResponse, PendingDecision, Priority,
+    QueueSort,
+};
+use crate::cli::queue::{load_policy, overdue_label};
+use crate::config::HumanSlaConfig;
+use crate::decision::Decision;
+use crate::error::Result;
+use crate::identity;
+use crate::sanitize::SanitizePipeline;
+use crate::scope::ScopeLevel;
+
+/// How often the pending list is re-read from the queue file.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Maximum number of lines considered when diffing file contents.
+const MAX_DIFF_LINES: usize = 400;
+
+/// Scopes the user can cycle through when adding a rule.
+const SCOPES: [ScopeLevel; 3] = [ScopeLevel::Project, ScopeLevel::User, ScopeLevel::Org];
+
+/// Run the interactive queue TUI until the user quits.
+pub async fn run(sort: QueueSort) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, sort);
+    ratatui::restore();
+    result
+}
+
+/// State for the interactive queue view.
+struct QueueApp {
+    queue: DecisionQueue,
+    pending: Vec<PendingDecision>,
+    sort: QueueSort,
+    sla: HumanSlaConfig,
+    list_state: ListState,
+    always_ask: bool,
+    add_rule: bool,
+    scope_idx: usize,
+    status: String,
+    sanitizer: SanitizePipeline,
+}
+
+impl QueueApp {
+    fn new(sort: QueueSort) -> Self {
+        let policy = load_policy();
+        let mut app = Self {
+            queue: DecisionQueue::new(),
+            pending: Vec::new(),
+            sort,
+            sla: policy.effective_human_sla(),
+            list_state: ListState::default(),
+            always_ask: false,
+            add_rule: false,
+            scope_idx: 0,
+            status: String::from(
+                "a: approve  d: deny  x: always-ask  r: add rule  s: scope  q: quit",
+            ),
+            sanitizer: SanitizePipeline::from_config(&policy.sanitize)
+                .unwrap_or_else(|_| SanitizePipeline::default_pipeline()),
+        };
+        app.refresh();
+        app
+    }
+
+    /// Re-read pending decisions from the file-backed queue, keeping the
+    /// selection on the same item when it is still pending.
+    fn refresh(&mut self) {
+        let selected_id = self.selected().map(|d| d.id.clone());
+
+        let mut pending: Vec<PendingDecision> = load_queue_file().pending.into_values().collect();
+        sort_pending(&mut pending, self.sort);
+        self.pending = pending;
+
+        let idx = selected_id
+            .and_then(|id| self.pending.iter().position(|d| d.id == id))
+            .or_else(|| {
+                let prev = self.list_state.selected().unwrap_or(0);
+                (!self.pending.is_empty()).then(|| prev.min(self.pending.len() - 1))
+            });
+        self.list_state.select(idx);
+    }
+
+    fn selected(&self) -> Option<&PendingDecision> {
+        self.list_state.selected().and_then(|i| self.pending.get(i))
+    }
+
+    fn scope(&self) -> ScopeLevel {
+        SCOPES[self.scope_idx]
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.pending.len() as isize - 1);
+        self.list_state.select(Some(next as usize));
+    }
+
+    fn respond(&mut self, decision: Decision) -> Result<()> {
+        let Some(id) = self.selected().map(|d| d.id.clone()) else {
+            return Ok(());
+        };
+
+        let approver = match identity::approver(&load_policy().approvals) {
+            Ok(approver) => approver,
+            Err(e) => {
+                self.status = e.to_string();
+                return Ok(());
+            }
+        };
+
+        let response = HumanResponse {
+            decision,
+            always_ask: self.always_ask,
+            add_rule: self.add_rule,
+            rule_scope: self.add_rule.then(|| self.scope()),
+            batch: None,
+            responded_by: Some(approver.user),
+        };
+        self.queue.respond(&id, response)?;
+
+        self.status = format!(
+            "{} {}",
+            if decision == Decision::Allow {
+                "approved"
+            } else {
+                "denied"
+            },
+            id
+        );
+        if self.always_ask {
+            self.status.push_str(" (cached as 'ask')");
+        }
+        if self.add_rule {
+            self.status
+                .push_str(&format!(" (rule at scope '{}')", self.scope()));
+        }
+        self.refresh();
+        Ok(())
+    }
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, sort: QueueSort) -> Result<()> {
+    let mut app = QueueApp::new(sort);
+    let mut last_refresh = Instant::now();
+
+    loop {
+        terminal.draw(|frame| draw(frame, &mut app))?;
+
+        let timeout = REFRESH_INTERVAL.saturating_sub(last_refresh.elapsed());
+        if event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Down | KeyCode::Char('j') => app.move_selection(1),
+                    KeyCode::Up | KeyCode::Char('k') => app.move_selection(-1),
+                    KeyCode::Char('a') => app.respond(Decision::Allow)?,
+                    KeyCode::Char('d') => app.respond(Decision::Deny)?,
+                    KeyCode::Char('x') => app.always_ask = !app.always_ask,
+                    KeyCode::Char('r') => app.add_rule = !app.add_rule,
+                    KeyCode::Char('s') => app.scope_idx = (app.scope_idx + 1) % SCOPES.len(),
+                    _ => {}
+                }
+            }
+        }
+
+        if last_refresh.elapsed() >= REFRESH_INTERVAL {
+            app.refresh();
+            last_refresh = Instant::now();
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &mut QueueApp) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)])
+        .split(frame.area());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(rows[0]);
+
+    let now = chrono::Utc::now();
+    let items: Vec<ListItem> = app
+        .pending
+        .iter()
+        .map(|d| {
+            let mut spans = vec![
+                Span::styled(
+                    format!("{:<8} ", d.priority),
+                    Style::default().fg(priority_color(d.priority)),
+                ),
+                Span::styled(format!("{:<10} ", d.role), Style::default().fg(Color::Cyan)),
+                Span::styled(
+                    format!("{:<6} ", d.tool_name),
+                    Style::default().fg(Color::Yellow),
+                ),
+            ];
+            if let Some(label) = overdue_label(d, &app.sla, now) {
+                spans.push(Span::styled(
+                    format!("{} ", label),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ));
+            }
+            spans.push(Span::raw(
+                d.file_path.clone().unwrap_or_else(|| d.id.clone()),
+            ));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Pending ({}) ", app.pending.len())),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, columns[0], &mut app.list_state);
+
+    let detail = match app.selected() {
+        Some(d) => detail_lines(d, &app.sanitizer),
+        None => vec![Line::from("No pending decisions.")],
+    };
+    let detail = Paragraph::new(detail)
+        .block(Block::default().borders(Borders::ALL).title(" Detail "))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(detail, columns[1]);
+
+    let flags = format!(
+        " always-ask: {}  add-rule: {}  scope: {} ",
+        on_off(app.always_ask),
+        on_off(app.add_rule),
+        app.scope()
+    );
+    let footer = Paragraph::new(app.status.as_str())
+        .block(Block::default().borders(Borders::ALL).title(flags));
+    frame.render_widget(footer, rows[1]);
+}
+
+fn priority_color(priority: Priority) -> Color {
+    match priority {
+        Priority::Critical => Color::Red,
+        Priority::High => Color::LightRed,
+        Priority::Normal => Color::White,
+        Priority::Low => Color::DarkGray,
+    }
+}
+
+fn on_off(flag: bool) -> &'static str {
+    if flag {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Build the detail pane for a pending decision: metadata, the full sanitized
+/// input, and a diff of the proposed change for Write/Edit calls.
+fn detail_lines(decision: &PendingDecision, sanitizer: &SanitizePipeline) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(format!("ID:     {}", decision.id)),
+        Line::from(format!("Priority: {}", decision.priority)),
+        Line::from(format!("Role:   {}", decision.role)),
+        Line::from(format!("Tool:   {}", decision.tool_name)),
+        Line::from(format!(
+            "File:   {}",
+            decision.file_path.as_deref().unwrap_or("-")
+        )),
+        Line::from(format!("Queued: {}", decision.queued_at)),
+    ];
+    if let Some(rec) = &decision.recommendation {
+        lines.push(Line::from(format!(
+            "Supervisor: {} ({:.2}) -- {}",
+            rec.decision, rec.confidence, rec.reason
+        )));
+    }
+    if let Some(reason) = &decision.ask_reason {
+        lines.push(Line::from(format!("Ask reason: {}", reason)));
+    }
+    if let Some(suggestion) = &decision.suggestion {
+        lines.push(Line::from(format!("Suggestion: {}", suggestion)));
+    }
+    if let Some(score) = decision.risk_score {
+        lines.push(Line::from(format!("Risk:   {:.1}", score)));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::styled(
+        "Input:",
+        Style::default().add_modifier(Modifier::BOLD),
+    ));
+    let input = serde_json::from_str::<serde_json::Value>(&decision.sanitized_input)
+        .ok()
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or_else(|| decision.sanitized_input.clone());
+    lines.extend(input.lines().map(|l| Line::from(l.to_string())));
+
+    // Edit diffs only cover the replaced text; show where it sits in the file.
+    if let Some(current) = decision
+        .attachment
+        .as_ref()
+        .filter(|_| decision.tool_name == "Edit")
+        .and_then(|a| a.current.as_ref())
+    {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            format!("Current file ({}):", current.header()),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        lines.extend(
+            current
+                .text
+                .lines()
+                .zip(current.start_line..)
+                .map(|(l, n)| Line::from(format!("{:>5} {}", n, l))),
+        );
+    }
+
+    let diff = proposed_diff(decision, sanitizer);
+    if !diff.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            "Diff:",
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        lines.extend(diff.into_iter().map(|d| match d {
+            DiffLine::Same(l) => Line::from(format!("  {}", l)),
+            DiffLine::Removed(l) => {
+                Line::styled(format!("- {}", l), Style::default().fg(Color::Red))
+            }
+            DiffLine::Added(l) => {
+                Line::styled(format!("+ {}", l), Style::default().fg(Color::Green))
+            }
+        }));
+    }
+
+    lines
+}
+
+/// Compute the diff a Write/Edit call would apply. The current file contents
+/// are sanitized before diffing so secrets on disk never reach the terminal.
+fn proposed_diff(decision: &PendingDecision, sanitizer: &SanitizePipeline) -> Vec<DiffLine> {
+    let Ok(input) = serde_json::from_str::<serde_json::Value>(&decision.sanitized_input) else {
+        return Vec::new();
+    };
+    let field = |name: &str| input.get(name).and_then(|v| v.as_str()).unwrap_or("");
+
+    match decision.tool_name.as_str() {
+        "Edit" => diff_lines(field("old_string"), field("new_string")),
+        "Write" => {
+            let current = decision
+                .file_path
+                .as_deref()
+                .and_then(|p| std::fs::read_to_string(p).ok())
+                .map(|c| sanitizer.sanitize(&c))
+                .unwrap_or_default();
+            diff_lines(&current, field("content"))
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// A single line in a line-level diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Line-level diff via longest common subsequence. Inputs are capped at
+/// `MAX_DIFF_LINES` lines each to bound the quadratic table.
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().take(MAX_DIFF_LINES).collect();
+    let new: Vec<&str> = new.lines().take(MAX_DIFF_LINES).collect();
+    let (n, m) = (old.len(), new.len());
+
+    // lcs[i][j] = LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u16; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            out.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    out.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    out.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_identical() {
+        let diff = diff_lines("a\nb", "a\nb");
+        assert_eq!(
+            diff,
+            vec![DiffLine::Same("a".into()), DiffLine::Same("b".into())]
+        );
+    }
+
+    #[test]
+    fn test_diff_replacement() {
+        let diff = diff_lines("a\nb\nc", "a\nx\nc");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("a".into()),
+                DiffLine::Removed("b".into()),
+                DiffLine::Added("x".into()),
+                DiffLine::Same("c".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_new_file() {
+        let diff = diff_lines("", "fn main() {}");
+        assert_eq!(diff, vec![DiffLine::Added("fn main() {}".into())]);
+    }
+}
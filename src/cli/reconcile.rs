@@ -0,0 +1,237 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::cascade::human::{load_queue_file, DecisionQueue, HumanResponse, PendingDecision};
+use crate::config::{GitApprovalConfig, PolicyConfig};
+use crate::decision::Decision;
+use crate::error::{HookwiseError, Result};
+
+/// Commit trailer that approves a pending decision.
+pub const APPROVE_TRAILER: &str = "Hookwise-Approve";
+/// Commit trailer that denies a pending decision.
+pub const DENY_TRAILER: &str = "Hookwise-Deny";
+
+/// A commit carrying at least one approval trailer.
+#[derive(Debug, Clone)]
+pub struct TrailerCommit {
+    pub sha: String,
+    pub committer_email: String,
+    /// `git log %G?` signature status (`G` = good signature, `N` = unsigned).
+    pub signature: char,
+    /// `%GS`: the signer, `Name <email>` for GPG or the principal for SSH.
+    pub signer: String,
+    /// `%GF`: the fingerprint of the signing key.
+    pub signer_key: String,
+    pub trailers: Vec<(Decision, String)>,
+}
+
+impl TrailerCommit {
+    /// Who the commit speaks for: the signer of a good signature, else the
+    /// committer email, which anyone can set.
+    pub fn approver(&self) -> &str {
+        if self.signature != 'G' || self.signer.is_empty() {
+            return &self.committer_email;
+        }
+        match (self.signer.rfind('<'), self.signer.rfind('>')) {
+            (Some(start), Some(end)) if start < end => &self.signer[start + 1..end],
+            _ => self.signer.trim(),
+        }
+    }
+
+    /// Whether `approvers` (lowercased) lists the approver, or the signing
+    /// key's fingerprint for a good signature.
+    fn approved_by(&self, approvers: &HashSet<String>) -> bool {
+        approvers.contains(&self.approver().to_lowercase())
+            || (self.signature == 'G'
+                && !self.signer_key.is_empty()
+                && approvers.contains(&self.signer_key.to_lowercase()))
+    }
+}
+
+/// What reconciliation decided for one trailer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReconcileOutcome {
+    Apply(Decision),
+    Skipped(String),
+}
+
+/// A reconciliation result for one pending decision id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconcileAction {
+    pub id: String,
+    pub sha: String,
+    /// Who the commit speaks for, recorded as the responder when applied.
+    pub approver: String,
+    pub outcome: ReconcileOutcome,
+}
+
+/// Extract approve/deny trailers from a commit message. Trailer keys are
+/// matched case-insensitively, as git does.
+pub fn parse_trailers(message: &str) -> Vec<(Decision, String)> {
+    message
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once(':')?;
+            let decision = if key.trim().eq_ignore_ascii_case(APPROVE_TRAILER) {
+                Decision::Allow
+            } else if key.trim().eq_ignore_ascii_case(DENY_TRAILER) {
+                Decision::Deny
+            } else {
+                return None;
+            };
+            let id = value.trim();
+            (!id.is_empty()).then(|| (decision, id.to_string()))
+        })
+        .collect()
+}
+
+/// Read the most recent `max_commits` commits in `repo` and keep those
+/// carrying approval trailers, newest first.
+pub fn read_trailer_commits(repo: &Path, max_commits: usize) -> Result<Vec<TrailerCommit>> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "log",
+            &format!("--max-count={}", max_commits),
+            "--format=%H%x1f%ce%x1f%G?%x1f%GS%x1f%GF%x1f%B%x1e",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(HookwiseError::Storage {
+            reason: format!(
+                "git log failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+
+    let log = String::from_utf8_lossy(&output.stdout);
+    Ok(log
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(6, '\x1f');
+            let sha = fields.next()?.to_string();
+            let committer_email = fields.next()?.to_string();
+            let signature = fields.next()?.chars().next().unwrap_or('N');
+            let signer = fields.next()?.to_string();
+            let signer_key = fields.next()?.to_string();
+            let trailers = parse_trailers(fields.next()?);
+            (!trailers.is_empty()).then_some(TrailerCommit {
+                sha,
+                committer_email,
+                signature,
+                signer,
+                signer_key,
+                trailers,
+            })
+        })
+        .collect())
+}
+
+/// Decide which trailers to apply. Commits are expected newest first, so the
+/// most recent trailer for an id wins. A trailer only applies when the
+/// signature is good (unless `require_signed` is off), its signer or key is
+/// a listed approver (the committer email, when unsigned commits are
+/// allowed), and the decision is still pending at or below the configured
+/// priority.
+pub fn plan_reconcile(
+    commits: &[TrailerCommit],
+    pending: &HashMap<String, PendingDecision>,
+    config: &GitApprovalConfig,
+) -> Vec<ReconcileAction> {
+    let approvers: HashSet<String> = config
+        .approvers
+        .iter()
+        .map(|a| a.trim().to_lowercase())
+        .collect();
+    let mut seen = HashSet::new();
+    let mut actions = Vec::new();
+
+    for commit in commits {
+        for (decision, id) in &commit.trailers {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            let skip = |reason: String| ReconcileOutcome::Skipped(reason);
+            let outcome = if config.require_signed && commit.signature != 'G' {
+                skip("commit is not signed with a trusted key".into())
+            } else if !commit.approved_by(&approvers) {
+                skip(format!("{} is not an approver", commit.approver()))
+            } else {
+                match pending.get(id) {
+                    None => skip("not pending".into()),
+                    Some(p) if p.priority > config.max_priority => skip(format!(
+                        "priority {} requires interactive approval",
+                        p.priority
+                    )),
+                    Some(_) => ReconcileOutcome::Apply(*decision),
+                }
+            };
+            actions.push(ReconcileAction {
+                id: id.clone(),
+                sha: commit.sha.clone(),
+                approver: commit.approver().to_string(),
+                outcome,
+            });
+        }
+    }
+    actions
+}
+
+/// Scan recent commits for approval trailers and apply them to the queue.
+pub async fn run(max_commits: Option<usize>, dry_run: bool) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let policy = PolicyConfig::load_project(&cwd)?;
+    let config = policy.git_approval;
+
+    if config.approvers.is_empty() {
+        eprintln!("hookwise: no approvers configured (git_approval.approvers in policy.yml)");
+        return Ok(());
+    }
+
+    let commits = read_trailer_commits(&cwd, max_commits.unwrap_or(config.max_commits))?;
+    let state = load_queue_file();
+    let actions = plan_reconcile(&commits, &state.pending, &config);
+
+    if actions.is_empty() {
+        println!("No approval trailers found.");
+        return Ok(());
+    }
+
+    let queue = DecisionQueue::new();
+    let mut applied = 0;
+    for action in &actions {
+        let short_sha = &action.sha[..action.sha.len().min(10)];
+        match &action.outcome {
+            ReconcileOutcome::Apply(decision) => {
+                if !dry_run {
+                    queue.respond(
+                        &action.id,
+                        HumanResponse {
+                            decision: *decision,
+                            always_ask: false,
+                            add_rule: false,
+                            rule_scope: None,
+                            batch: None,
+                            responded_by: Some(action.approver.clone()),
+                        },
+                    )?;
+                }
+                applied += 1;
+                println!("{} {} ({})", decision, action.id, short_sha);
+            }
+            ReconcileOutcome::Skipped(reason) => {
+                println!("skip {} ({}): {}", action.id, short_sha, reason);
+            }
+        }
+    }
+
+    if dry_run {
+        println!("{} response(s) would be applied (dry run)", applied);
+    } else {
+        println!("{} response(s) applied", applied);
+    }
+    Ok(())
+}
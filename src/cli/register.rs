@@ -1,51 +1,322 @@
+use std::path::Path;
+
+use chrono::Utc;
+
+use crate::audit::{AuditSink, SessionAction, SessionEvent};
+use crate::config::{PolicyConfig, SessionMode};
 use crate::error::Result;
+use crate::identity::Identity;
+use crate::notify::{Notification, Notifier};
+use crate::session::disable::{
+    authorize_audit_mode, DisableAuthorization, DisableOutcome, DisableRequest,
+};
 use crate::session::SessionManager;
 
-/// Register a session with a role.
+/// Register a session with a role, or an ordered list of roles.
 pub async fn run_register(
     session_id: &str,
-    role: &str,
+    roles_requested: &[String],
     task: Option<&str>,
     prompt_file: Option<&str>,
+    assume_closest: bool,
+    mode: Option<SessionMode>,
+    token: Option<&str>,
 ) -> Result<()> {
     let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
     let session_mgr = SessionManager::new(team_id.as_deref());
 
-    // Validate the role exists
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    if mode == Some(SessionMode::Audit) {
+        let env_token = std::env::var("HOOKWISE_DISABLE_TOKEN").ok();
+        let policy = PolicyConfig::load_project(&cwd)?;
+        authorize_audit_mode(
+            &policy.session_disable,
+            policy.session_mode,
+            token.or(env_token.as_deref()),
+        )?;
+    }
+
+    // Validate the role exists
     let roles = crate::config::RolesConfig::load_project(&cwd)?;
-    if roles.get_role(role).is_none() {
-        eprintln!("hookwise: unknown role '{}'. Available roles:", role);
-        for name in roles.roles.keys() {
-            eprintln!("  - {}", name);
+    let mut names: Vec<String> = Vec::new();
+    for role in roles_requested {
+        let name = match resolve_role(&roles, role, assume_closest) {
+            RoleMatch::Exact(name) => name,
+            RoleMatch::Closest(name) => {
+                eprintln!(
+                    "hookwise: unknown role '{}', using closest match '{}'",
+                    role, name
+                );
+                name
+            }
+            RoleMatch::Unknown { suggestion } => {
+                match suggestion {
+                    Some(closest) => eprintln!(
+                        "hookwise: unknown role '{}'. Did you mean '{}'? (pass --assume-closest to use it)",
+                        role, closest
+                    ),
+                    None => eprintln!("hookwise: unknown role '{}'.", role),
+                }
+                eprintln!("Available roles:");
+                for name in roles.role_names() {
+                    eprintln!("  - {}", name);
+                }
+                std::process::exit(1);
+            }
+        };
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
         }
-        std::process::exit(1);
     }
 
-    session_mgr.register(session_id, role, task, prompt_file)?;
-    eprintln!(
-        "hookwise: session {} registered as '{}'",
-        session_id, role
-    );
+    session_mgr.register_with_fallbacks(
+        session_id,
+        &names[0],
+        &names[1..],
+        task,
+        prompt_file,
+        mode,
+    )?;
+    if names.len() == 1 {
+        eprintln!(
+            "hookwise: session {} registered as '{}'",
+            session_id, names[0]
+        );
+    } else {
+        eprintln!(
+            "hookwise: session {} registered as '{}', each call using the first that may make it",
+            session_id,
+            names.join("', '")
+        );
+    }
+    if mode == Some(SessionMode::Audit) {
+        eprintln!("hookwise: audit mode -- calls are logged but not enforced");
+    }
     Ok(())
 }
 
-/// Disable hookwise for a session.
-pub async fn run_disable(session_id: &str) -> Result<()> {
-    let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
-    let session_mgr = SessionManager::new(team_id.as_deref());
+/// Outcome of matching a requested role name against roles.yml.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoleMatch<'a> {
+    /// The name is a known role.
+    Exact(&'a str),
+    /// Unknown name, replaced by its closest known role (`assume_closest`).
+    Closest(&'a str),
+    /// Unknown name, with the closest known role if any is plausible.
+    Unknown { suggestion: Option<&'a str> },
+}
 
-    session_mgr.disable(session_id)?;
-    eprintln!("hookwise: session {} disabled", session_id);
+/// Match `role` against the configured roles, falling back to the closest
+/// role name when `assume_closest` is set. Shared by the CLI and MCP paths.
+pub fn resolve_role<'a>(
+    roles: &'a crate::config::RolesConfig,
+    role: &str,
+    assume_closest: bool,
+) -> RoleMatch<'a> {
+    if let Some((name, _)) = roles.roles.get_key_value(role) {
+        return RoleMatch::Exact(name);
+    }
+    match roles.closest_role(role) {
+        Some(closest) if assume_closest => RoleMatch::Closest(closest),
+        suggestion => RoleMatch::Unknown { suggestion },
+    }
+}
+
+/// Disable hookwise for a session, subject to the project's two-person rule.
+pub async fn run_disable(
+    session_id: &str,
+    reason: Option<&str>,
+    duration_secs: Option<u64>,
+    token: Option<&str>,
+) -> Result<()> {
+    let env_token = std::env::var("HOOKWISE_DISABLE_TOKEN").ok();
+    let token = token.or(env_token.as_deref());
+    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+
+    match disable_session(session_id, reason, duration_secs, token, &cwd)? {
+        DisableOutcome::Disabled {
+            authorization,
+            until,
+            ..
+        } => {
+            let until = until.map_or(String::new(), |t| format!(" until {}", t.to_rfc3339()));
+            match authorization {
+                DisableAuthorization::Unprotected => {}
+                other => eprintln!("hookwise: authorized by {}", other.as_str()),
+            }
+            eprintln!("hookwise: session {} disabled{}", session_id, until);
+        }
+        DisableOutcome::Pending { expires_at } => eprintln!(
+            "hookwise: this project requires two people to disable a session. \
+             Ask another approver to run `hookwise disable --session-id {}` \
+             before {}, or pass an org token with --token.",
+            session_id,
+            expires_at.to_rfc3339()
+        ),
+    }
     Ok(())
 }
 
 /// Re-enable hookwise for a session.
 pub async fn run_enable(session_id: &str) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    enable_session(session_id, &cwd)?;
+    eprintln!("hookwise: session {} re-enabled", session_id);
+    Ok(())
+}
+
+/// Apply `hookwise disable` under the project's `session_disable` policy and
+/// audit it. Shared by the CLI and MCP paths. A policy that fails to load is
+/// an error, so a broken file can't lift protection.
+pub fn disable_session(
+    session_id: &str,
+    reason: Option<&str>,
+    duration_secs: Option<u64>,
+    token: Option<&str>,
+    cwd: &Path,
+) -> Result<DisableOutcome> {
+    let policy = PolicyConfig::load_project(cwd)?;
     let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
     let session_mgr = SessionManager::new(team_id.as_deref());
+    let identity = crate::identity::current(cwd);
 
-    session_mgr.enable(session_id)?;
-    eprintln!("hookwise: session {} re-enabled", session_id);
+    let request = DisableRequest::new(
+        session_id,
+        identity.clone(),
+        reason.map(String::from),
+        duration_secs,
+    );
+    let outcome = session_mgr.authorize_disable(
+        request,
+        token,
+        &policy.session_disable,
+        &policy.approvals,
+    )?;
+
+    let event = match &outcome {
+        DisableOutcome::Disabled {
+            authorization,
+            request,
+            ..
+        } => SessionEvent {
+            action: SessionAction::Disabled,
+            reason: request.reason.clone(),
+            duration_secs: request.duration_secs,
+            authorization: Some(authorization.as_str().to_string()),
+            requested_by: (*authorization == DisableAuthorization::SecondApprover)
+                .then(|| request.requested_by.clone()),
+            ..session_event(session_id, SessionAction::Disabled, identity, cwd)
+        },
+        DisableOutcome::Pending { .. } => SessionEvent {
+            reason: reason.map(String::from),
+            duration_secs,
+            ..session_event(session_id, SessionAction::DisableRequested, identity, cwd)
+        },
+    };
+    record_session_event(&policy, &event, cwd);
+    Ok(outcome)
+}
+
+/// Re-enable a session and audit how long it was disabled.
+pub fn enable_session(session_id: &str, cwd: &Path) -> Result<()> {
+    let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
+    let session_mgr = SessionManager::new(team_id.as_deref());
+    let removed = session_mgr.enable(session_id)?;
+
+    let policy = PolicyConfig::load_project(cwd).unwrap_or_default();
+    let identity = crate::identity::current(cwd);
+    let event = SessionEvent {
+        duration_secs: removed
+            .and_then(|e| e.disabled_at)
+            .map(|at| (Utc::now() - at).num_seconds().max(0) as u64),
+        ..session_event(session_id, SessionAction::Enabled, identity, cwd)
+    };
+    record_session_event(&policy, &event, cwd);
+    Ok(())
+}
+
+/// Re-enable timed disables that have lapsed, auditing each as an enable.
+pub fn expire_disables(
+    session_mgr: &SessionManager,
+    policy: &PolicyConfig,
+    cwd: &Path,
+) -> Result<()> {
+    let lapsed = session_mgr.expire_disables(Utc::now())?;
+    if lapsed.is_empty() {
+        return Ok(());
+    }
+    let identity = crate::identity::current(cwd);
+    for exclusion in lapsed {
+        let event = SessionEvent {
+            reason: Some("timed disable expired".into()),
+            duration_secs: exclusion
+                .disabled_at
+                .zip(exclusion.until)
+                .map(|(from, to)| (to - from).num_seconds().max(0) as u64),
+            ..session_event(
+                &exclusion.session_id,
+                SessionAction::Enabled,
+                identity.clone(),
+                cwd,
+            )
+        };
+        record_session_event(policy, &event, cwd);
+    }
+    Ok(())
+}
+
+/// Remind on stderr and the notification channels when a session has been
+/// disabled longer than `session_disable.remind_after_secs`.
+pub async fn remind_disabled(
+    session_mgr: &SessionManager,
+    policy: &PolicyConfig,
+    session_id: &str,
+) -> Result<()> {
+    let Some(after) = policy.session_disable.remind_after_secs else {
+        return Ok(());
+    };
+    let after = i64::try_from(after)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .unwrap_or(chrono::Duration::MAX);
+    let now = Utc::now();
+    let Some(exclusion) = session_mgr.due_reminder(session_id, after, now)? else {
+        return Ok(());
+    };
+    let notification = Notification::for_disabled(&exclusion, now);
+    eprintln!(
+        "{}; re-enable with `hookwise enable --session-id {}`",
+        notification.title, session_id
+    );
+    if let Some(notifier) = Notifier::from_config(&policy.notifications) {
+        notifier.notify(&notification).await;
+    }
     Ok(())
 }
+
+fn session_event(
+    session_id: &str,
+    action: SessionAction,
+    identity: Identity,
+    cwd: &Path,
+) -> SessionEvent {
+    SessionEvent {
+        logged_at: Utc::now(),
+        cwd: cwd.display().to_string(),
+        session_id: session_id.to_string(),
+        action,
+        identity,
+        reason: None,
+        duration_secs: None,
+        authorization: None,
+        requested_by: None,
+    }
+}
+
+fn record_session_event(policy: &PolicyConfig, event: &SessionEvent, cwd: &Path) {
+    if let Some(sink) = AuditSink::from_config(&policy.audit, cwd) {
+        if let Err(e) = sink.record_session(event) {
+            eprintln!("hookwise: audit log write failed ({})", e);
+        }
+    }
+}
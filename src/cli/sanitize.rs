@@ -0,0 +1,198 @@
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::{cache_input, CascadeInput, CascadeRunner};
+use crate::config::{PolicyConfig, RolesConfig};
+use crate::decision::{CacheKey, SecretFinding};
+use crate::error::{HookwiseError, Result};
+use crate::sanitize::corpus::{CorpusFailure, SanitizeCorpus};
+use crate::sanitize::SanitizePipeline;
+use crate::session::SessionContext;
+
+/// Print `file` (or stdin) with secrets redacted by the project's
+/// sanitizer, including the custom `sanitize` rules in policy.yml.
+pub async fn run(file: Option<&Path>) -> Result<()> {
+    let pipeline = project_pipeline()?;
+    let input = match file {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            input
+        }
+    };
+    std::io::stdout().write_all(pipeline.sanitize(&input).as_bytes())?;
+    Ok(())
+}
+
+/// Run a labeled corpus against the project's sanitizer, including the
+/// custom `sanitize` rules in policy.yml. Exits 1 on any miss or
+/// over-redaction.
+pub async fn run_test(corpus_path: &Path) -> Result<()> {
+    let pipeline = project_pipeline()?;
+    let corpus = SanitizeCorpus::load(corpus_path)?;
+
+    let report = corpus.run(&pipeline);
+    print_failures("MISS (not redacted)", &report.misses);
+    print_failures("OVER-REDACTED", &report.over_redactions);
+
+    if report.passed() {
+        eprintln!("hookwise: {} corpus input(s) passed.", report.total);
+    } else {
+        eprintln!(
+            "\nhookwise: {} of {} corpus input(s) failed: {} miss(es), {} over-redaction(s).",
+            report.misses.len() + report.over_redactions.len(),
+            report.total,
+            report.misses.len(),
+            report.over_redactions.len()
+        );
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Print how the hook sees a tool call: the redacted and normalized input,
+/// the paths it touches, and its cache key. `input` (or stdin) is a JSON
+/// tool input, or a shell command for Bash.
+pub async fn run_preview(input: Option<String>, tool: &str, role: &str) -> Result<()> {
+    let input = match input {
+        Some(input) => input,
+        None => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            input.trim_end().to_string()
+        }
+    };
+    let preview = preview(&input, tool, role)?;
+    print!("{}", preview.to_text());
+    Ok(())
+}
+
+/// The tool input for `input`: a JSON object as given, anything else as a
+/// Bash `command`.
+pub fn tool_input(input: &str, tool: &str) -> Result<serde_json::Value> {
+    match serde_json::from_str::<serde_json::Value>(input) {
+        Ok(value) if value.is_object() => Ok(value),
+        _ if tool == "Bash" => Ok(serde_json::json!({ "command": input })),
+        _ => Err(HookwiseError::InvalidPolicy {
+            reason: format!("{} input must be a JSON object", tool),
+        }),
+    }
+}
+
+/// What the hook derives from one tool call.
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub raw: String,
+    /// After secret redaction.
+    pub redacted: String,
+    /// After volatile-token normalization: the cached input.
+    pub normalized: String,
+    pub secrets: Vec<SecretFinding>,
+    /// The forms of each path the call touches, as path policy matches them.
+    pub paths: Vec<Vec<String>>,
+    /// The file path as stored on decisions (category form), if any.
+    pub stored_path: Option<String>,
+    pub key: CacheKey,
+}
+
+/// Run `input` through the project's sanitizer, normalizer and path
+/// extraction, as `hookwise check` would for `tool` and `role`.
+pub fn preview(input: &str, tool: &str, role: &str) -> Result<Preview> {
+    let cwd = std::env::current_dir()?;
+    let policy = PolicyConfig::load_project(&cwd)?;
+    let roles = RolesConfig::load_project(&cwd)?;
+    let pipeline = SanitizePipeline::from_config(&policy.sanitize)?;
+    let tool_input = tool_input(input, tool)?;
+
+    let raw = serde_json::to_string(&tool_input)?;
+    let (redacted, secrets) = pipeline.sanitize_with_findings(&raw);
+    let (normalized, _) = cache_input(&pipeline, &tool_input);
+
+    let file_path = CascadeRunner::extract_file_path(tool, &tool_input);
+    let cascade_input = CascadeInput {
+        session: SessionContext::new_minimal(String::new(), String::new(), String::new()),
+        tool_name: tool.to_string(),
+        tool_input,
+        sanitized_input: normalized.clone(),
+        file_path: file_path.clone(),
+        cwd: Some(cwd.to_string_lossy().into_owned()),
+        recommendation: None,
+    };
+    let paths = PathPolicyEngine::new()?
+        .with_containers(policy.containers)
+        .target_path_groups(&cascade_input);
+    let stored_path = match (roles.normalizer().ok(), file_path) {
+        (Some(normalizer), Some(path)) => Some(normalizer.normalize(&path)),
+        (None, path) => path,
+        (_, None) => None,
+    };
+
+    Ok(Preview {
+        raw,
+        redacted,
+        key: CacheKey {
+            sanitized_input: normalized.clone(),
+            tool: tool.to_string(),
+            role: role.to_string(),
+        },
+        normalized,
+        secrets,
+        paths,
+        stored_path,
+    })
+}
+
+impl Preview {
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("Input:      {}\n", self.raw));
+        if self.redacted != self.raw {
+            out.push_str(&format!("Redacted:   {}\n", self.redacted));
+        }
+        if self.normalized != self.redacted {
+            out.push_str(&format!("Normalized: {}\n", self.normalized));
+        }
+        if !self.secrets.is_empty() {
+            let secrets: Vec<String> = self
+                .secrets
+                .iter()
+                .map(|f| format!("{} x{}", f.kind, f.count))
+                .collect();
+            out.push_str(&format!("Secrets:    {}\n", secrets.join(", ")));
+        }
+        if self.paths.is_empty() {
+            out.push_str("Paths:      (none)\n");
+        } else {
+            out.push_str("Paths:\n");
+            for forms in &self.paths {
+                out.push_str(&format!("  {}\n", forms.join("  |  ")));
+            }
+        }
+        if let Some(path) = &self.stored_path {
+            out.push_str(&format!("Stored as:  {}\n", path));
+        }
+        out.push_str("Cache key:\n");
+        out.push_str(&format!("  role:  {}\n", self.key.role));
+        out.push_str(&format!("  tool:  {}\n", self.key.tool));
+        out.push_str(&format!("  input: {}\n", self.key.sanitized_input));
+        out
+    }
+}
+
+/// The sanitizer with the current project's custom rules.
+fn project_pipeline() -> Result<SanitizePipeline> {
+    let cwd = std::env::current_dir()?;
+    let policy = PolicyConfig::load_project(&cwd)?;
+    SanitizePipeline::from_config(&policy.sanitize)
+}
+
+fn print_failures(label: &str, failures: &[CorpusFailure]) {
+    for failure in failures {
+        eprintln!("{}:", label);
+        eprintln!("  input:  {}", failure.input);
+        eprintln!("  output: {}", failure.output);
+    }
+}
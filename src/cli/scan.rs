@@ -1,14 +1,364 @@
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
-use crate::error::Result;
+use globset::GlobSet;
+use ignore::WalkBuilder;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::roles::build_globset;
+use crate::config::{PolicyConfig, ScanRulesConfig, Severity};
+use crate::error::{HookwiseError, Result};
 use crate::sanitize::SanitizePipeline;
 
-/// Pre-commit secret scan on staged files or a specified path.
-pub async fn run(staged: bool, path: Option<&str>) -> Result<()> {
-    let pipeline = SanitizePipeline::default_pipeline();
-    let mut total_findings = 0;
+/// Output format for `hookwise scan`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ScanFormat {
+    /// Findings on stderr, one line each (the default).
+    #[default]
+    Text,
+    /// A JSON array of findings on stdout.
+    Json,
+    /// A SARIF 2.1.0 log on stdout, for GitHub code scanning and other
+    /// tooling.
+    Sarif,
+}
+
+/// One kind of secret found on one line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScanFinding {
+    pub path: String,
+    /// 1-based line number.
+    pub line: usize,
+    /// The sanitizer finding kind that flagged the line, e.g.
+    /// `prefix:ghp_` or `high_entropy`.
+    pub rule: String,
+    /// How many times the rule matched on the line.
+    pub count: usize,
+    pub severity: Severity,
+    /// Stable across runs and line moves: a hash of the path, the rule and
+    /// the redacted line, so the secret itself never leaves the machine.
+    pub fingerprint: String,
+    /// Silenced by a `hookwise:ignore` comment; reported only with
+    /// `--verbose` and never fails the scan.
+    #[serde(skip)]
+    pub suppressed: bool,
+}
+
+/// Marker for inline suppressions, e.g. `# hookwise:ignore high_entropy`.
+const IGNORE_MARKER: &str = "hookwise:ignore";
+
+/// Comment leaders the marker may follow: shell/Python/YAML/TOML, C-like,
+/// SQL/Lua/Haskell, Lisp/INI, TeX/Erlang, and HTML/XML.
+const COMMENT_LEADERS: &[&str] = &["#", "//", "/*", "--", ";", "%", "<!--"];
+
+/// The rule ids a `hookwise:ignore` comment on `line` names; empty when it
+/// names none, which ignores every rule. None without a comment.
+fn ignore_directive(line: &str) -> Option<Vec<String>> {
+    let at = line.find(IGNORE_MARKER)?;
+    let before = line[..at].trim_end();
+    if !COMMENT_LEADERS
+        .iter()
+        .any(|leader| before.ends_with(leader))
+    {
+        return None;
+    }
+    let rest = &line[at + IGNORE_MARKER.len()..];
+    if rest.starts_with(|c: char| !c.is_whitespace() && c != ',') {
+        return None;
+    }
+    Some(
+        rest.split(|c: char| c.is_whitespace() || c == ',')
+            .take_while(|id| !id.starts_with("*/") && !id.starts_with("-->"))
+            .filter(|id| !id.is_empty())
+            .map(|id| {
+                id.trim_end_matches("*/")
+                    .trim_end_matches("-->")
+                    .to_string()
+            })
+            .collect(),
+    )
+}
+
+/// Whether `line` is nothing but a comment, so that a directive in it
+/// applies to the line below.
+fn is_comment_line(line: &str) -> bool {
+    let line = line.trim_start();
+    COMMENT_LEADERS
+        .iter()
+        .any(|leader| line.starts_with(leader))
+}
+
+/// Whether an ignore comment on `line`, or on a comment line right above
+/// it, silences `rule`.
+fn is_ignored(rule: &str, line: &str, above: Option<&str>) -> bool {
+    let directives = [
+        ignore_directive(line),
+        above
+            .filter(|above| is_comment_line(above))
+            .and_then(ignore_directive),
+    ];
+    directives
+        .into_iter()
+        .flatten()
+        .any(|ids| ids.is_empty() || ids.iter().any(|id| id == rule))
+}
+
+/// Compiled `.hookwise/scan-rules.yml`.
+#[derive(Default)]
+pub struct ScanRules {
+    custom: Vec<CustomRule>,
+    /// Severity and file filter per built-in finding kind.
+    builtin: HashMap<String, (Option<Severity>, Option<GlobSet>)>,
+    max_file_bytes: u64,
+}
+
+/// A scan rule with its own pattern.
+struct CustomRule {
+    name: String,
+    pattern: regex::Regex,
+    severity: Severity,
+    files: Option<GlobSet>,
+}
+
+/// Whether a rule limited to `files` applies to `path`.
+fn applies(files: &Option<GlobSet>, path: &str) -> bool {
+    files
+        .as_ref()
+        .is_none_or(|globs| globs.is_match(path.strip_prefix("./").unwrap_or(path)))
+}
+
+impl ScanRules {
+    pub fn compile(config: &ScanRulesConfig) -> Result<Self> {
+        let mut rules = Self {
+            max_file_bytes: config.max_file_bytes,
+            ..Self::default()
+        };
+        for rule in &config.rules {
+            let files = if rule.files.is_empty() {
+                None
+            } else {
+                Some(build_globset(&rule.files)?)
+            };
+            match &rule.pattern {
+                Some(pattern) => rules.custom.push(CustomRule {
+                    name: rule.name.clone(),
+                    pattern: regex::Regex::new(pattern).map_err(|e| {
+                        HookwiseError::InvalidPolicy {
+                            reason: format!("invalid pattern for scan rule '{}': {e}", rule.name),
+                        }
+                    })?,
+                    severity: rule.severity.unwrap_or_default(),
+                    files,
+                }),
+                None => {
+                    rules
+                        .builtin
+                        .insert(rule.name.clone(), (rule.severity, files));
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Findings on one line of `path`. Custom rules run first, each on
+    /// what the previous ones left, then the sanitizer on the rest. Also
+    /// returns the line with everything found redacted.
+    fn check_line(
+        &self,
+        pipeline: &SanitizePipeline,
+        path: &str,
+        line: &str,
+    ) -> (String, Vec<(String, usize, Severity)>) {
+        let mut hits = Vec::new();
+        let mut redacted = line.to_string();
+        for rule in self.custom.iter().filter(|r| applies(&r.files, path)) {
+            let count = rule.pattern.find_iter(&redacted).count();
+            if count > 0 {
+                hits.push((rule.name.clone(), count, rule.severity));
+                redacted = rule
+                    .pattern
+                    .replace_all(&redacted, "<REDACTED>")
+                    .into_owned();
+            }
+        }
+
+        let (sanitized, kinds) = pipeline.sanitize_with_findings(&redacted);
+        if sanitized != redacted {
+            let kinds = if kinds.is_empty() {
+                vec![("redacted".to_string(), 1)]
+            } else {
+                kinds.into_iter().map(|f| (f.kind, f.count)).collect()
+            };
+            for (kind, count) in kinds {
+                if let Some(severity) = self.builtin_severity(&kind, path) {
+                    hits.push((kind, count, severity));
+                }
+            }
+        }
+        (sanitized, hits)
+    }
+
+    /// Severity of a built-in finding kind in `path`, or None when
+    /// scan-rules.yml limits the kind to other files.
+    fn builtin_severity(&self, kind: &str, path: &str) -> Option<Severity> {
+        let (severity, files) = match self.builtin.get(kind) {
+            Some((severity, files)) => (*severity, files),
+            None => (None, &None),
+        };
+        applies(files, path).then(|| severity.unwrap_or_else(|| Severity::builtin(kind)))
+    }
+}
+
+/// First line of a PEM private key block: PKCS#1 and #8, EC, DSA, OpenSSH,
+/// encrypted and PGP keys. The sanitizer only sees the key's base64 lines,
+/// if those look random enough.
+static PEM_KEY_BEGIN: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"-----BEGIN ([A-Z0-9]+ )*PRIVATE KEY( BLOCK)?-----").expect("valid regex")
+});
+
+/// Last line of a PEM private key block.
+static PEM_KEY_END: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"-----END ([A-Z0-9]+ )*PRIVATE KEY( BLOCK)?-----").expect("valid regex")
+});
+
+/// A kubeconfig user's private key, in any file.
+static KUBECONFIG_KEY: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^(\s*-?\s*client-key-data:)\s*\S").expect("valid regex"));
+
+/// Kubeconfig user tokens and passwords, in kubeconfig files only.
+static KUBECONFIG_SECRET: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"^(\s*-?\s*(token|id-token|refresh-token|password|client-secret):)\s*\S")
+        .expect("valid regex")
+});
+
+/// SSH private key file names; `id_rsa_deploy` and `id_rsa.bak` count too.
+const SSH_KEY_NAMES: &[&str] = &["id_rsa", "id_dsa", "id_ecdsa", "id_ed25519"];
+
+/// The rule for a file whose name alone marks it as key material: PKCS#12
+/// bundles (which are binary) and SSH private keys, but not their `.pub`
+/// halves.
+fn key_file_rule(path: &str) -> Option<&'static str> {
+    let name = Path::new(path).file_name()?.to_str()?.to_ascii_lowercase();
+    if name.ends_with(".p12") || name.ends_with(".pfx") {
+        return Some("pkcs12");
+    }
+    let ssh_key = SSH_KEY_NAMES.iter().any(|key| {
+        name.strip_prefix(key)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['_', '.', '-']))
+    });
+    (ssh_key && !name.ends_with(".pub")).then_some("ssh_key_file")
+}
+
+/// Whether `path` is a kubeconfig: `.kube/config`, `kubeconfig` or
+/// `*.kubeconfig`.
+fn is_kubeconfig(path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    let name = path.rsplit('/').next().unwrap_or_default();
+    path.ends_with(".kube/config")
+        || name.starts_with("kubeconfig")
+        || name.ends_with(".kubeconfig")
+}
+
+/// Key material on `line`, as the rule, and the line cut before the secret
+/// for the fingerprint.
+fn key_material(line: &str, kubeconfig: bool) -> Option<(&'static str, String)> {
+    if let Some(found) = PEM_KEY_BEGIN.find(line) {
+        return Some(("private_key", line[..found.end()].to_string()));
+    }
+    let secret = KUBECONFIG_KEY.captures(line).or_else(|| {
+        kubeconfig
+            .then(|| KUBECONFIG_SECRET.captures(line))
+            .flatten()
+    })?;
+    Some((
+        "kubeconfig_credential",
+        format!("{} <REDACTED>", &secret[1]),
+    ))
+}
+
+/// A finding accepted into a baseline. The path and rule are there for
+/// reviewers; only the fingerprint is matched.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub fingerprint: String,
+    pub path: String,
+    pub rule: String,
+}
+
+/// Findings that existed when the scanner was adopted and no longer fail
+/// the scan.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanBaseline {
+    pub findings: BTreeSet<BaselineEntry>,
+}
+
+impl ScanBaseline {
+    /// A baseline accepting every one of `findings`.
+    pub fn from_findings(findings: &[ScanFinding]) -> Self {
+        Self {
+            findings: findings
+                .iter()
+                .map(|f| BaselineEntry {
+                    fingerprint: f.fingerprint.clone(),
+                    path: f.path.clone(),
+                    rule: f.rule.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Load a baseline; None when the file doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(Some(serde_json::from_str(&contents).map_err(|e| {
+                HookwiseError::InvalidPolicy {
+                    reason: format!("invalid scan baseline {}: {}", path.display(), e),
+                }
+            })?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the baseline, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
 
-    if staged {
+    /// Whether `finding` was accepted.
+    pub fn contains(&self, finding: &ScanFinding) -> bool {
+        self.findings
+            .iter()
+            .any(|entry| entry.fingerprint == finding.fingerprint)
+    }
+}
+
+/// Pre-commit secret scan on staged files or a specified path. With a
+/// `baseline`, only findings missing from it fail the scan; a baseline that
+/// doesn't exist yet (or `update_baseline`) is written from this run. Only
+/// findings at `min_severity` or above fail it, and findings silenced by a
+/// `hookwise:ignore` comment never do; `verbose` lists those.
+pub async fn run(
+    staged: bool,
+    path: Option<&str>,
+    format: ScanFormat,
+    baseline: Option<&Path>,
+    update_baseline: bool,
+    min_severity: Severity,
+    verbose: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let pipeline = SanitizePipeline::from_config(&PolicyConfig::load_project(&cwd)?.sanitize)?;
+    let rules = ScanRules::compile(&ScanRulesConfig::load_project(&cwd)?)?;
+    let files = if staged {
         // Scan git staged files
         let output = std::process::Command::new("git")
             .args(["diff", "--cached", "--name-only"])
@@ -20,28 +370,32 @@ pub async fn run(staged: bool, path: Option<&str>) -> Result<()> {
         }
 
         let file_list = String::from_utf8_lossy(&output.stdout);
-        let files: Vec<&str> = file_list.lines().filter(|l| !l.is_empty()).collect();
+        let files: Vec<String> = file_list
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(String::from)
+            .collect();
 
         if files.is_empty() {
             eprintln!("hookwise: no staged files to scan.");
-            return Ok(());
+            // Structured formats still get an (empty) report
+            if format == ScanFormat::Text {
+                return Ok(());
+            }
+        } else {
+            eprintln!("hookwise: scanning {} staged file(s)...", files.len());
         }
 
-        eprintln!("hookwise: scanning {} staged file(s)...", files.len());
-
-        for file in files {
-            let findings = scan_file(&pipeline, file)?;
-            total_findings += findings;
-        }
+        files
     } else if let Some(path) = path {
         let path_buf = PathBuf::from(path);
         if path_buf.is_dir() {
             // Scan all files in directory recursively
             eprintln!("hookwise: scanning directory {}...", path);
-            total_findings += scan_dir(&pipeline, &path_buf)?;
+            list_dir(&path_buf)?
         } else if path_buf.is_file() {
             eprintln!("hookwise: scanning file {}...", path);
-            total_findings += scan_file(&pipeline, path)?;
+            vec![path.to_string()]
         } else {
             eprintln!("hookwise: path not found: {}", path);
             std::process::exit(1);
@@ -53,21 +407,82 @@ pub async fn run(staged: bool, path: Option<&str>) -> Result<()> {
 
         if rules_dir.exists() {
             eprintln!("hookwise: scanning rules directory...");
-            total_findings += scan_dir(&pipeline, &rules_dir)?;
+            list_dir(&rules_dir)?
         } else {
             eprintln!(
                 "hookwise: no .hookwise/rules/ found. Use --staged or provide a path."
             );
             std::process::exit(1);
         }
+    };
+
+    let outcome = scan_files(&pipeline, &rules, &files);
+    outcome.report_skipped(rules.max_file_bytes);
+    let (suppressed, mut findings): (Vec<_>, Vec<_>) =
+        outcome.findings.into_iter().partition(|f| f.suppressed);
+    if !suppressed.is_empty() {
+        eprintln!(
+            "hookwise: {} finding(s) suppressed by hookwise:ignore comments",
+            suppressed.len()
+        );
+        if verbose {
+            for f in &suppressed {
+                eprintln!(
+                    "  {}:{}: suppressed ({} [{}])",
+                    f.path, f.line, f.rule, f.severity
+                );
+            }
+        }
+    }
+
+    if let Some(baseline_path) = baseline {
+        match ScanBaseline::load(baseline_path)? {
+            Some(accepted) if !update_baseline => {
+                let total = findings.len();
+                findings.retain(|f| !accepted.contains(f));
+                if findings.len() < total {
+                    eprintln!(
+                        "hookwise: {} finding(s) accepted by baseline {}",
+                        total - findings.len(),
+                        baseline_path.display()
+                    );
+                }
+            }
+            _ => {
+                ScanBaseline::from_findings(&findings).save(baseline_path)?;
+                eprintln!(
+                    "hookwise: wrote {} finding(s) to baseline {}",
+                    findings.len(),
+                    baseline_path.display()
+                );
+                findings.clear();
+            }
+        }
+    }
+
+    match format {
+        ScanFormat::Text => print_text(&findings),
+        ScanFormat::Json => println!("{}", serde_json::to_string_pretty(&findings)?),
+        ScanFormat::Sarif => println!("{}", serde_json::to_string_pretty(&to_sarif(&findings))?),
     }
 
-    if total_findings > 0 {
+    let failing: Vec<ScanFinding> = findings
+        .iter()
+        .filter(|f| f.severity >= min_severity)
+        .cloned()
+        .collect();
+    if !failing.is_empty() {
         eprintln!(
             "\nhookwise: {} potential secret(s) found. Aborting.",
-            total_findings
+            flagged_lines(&failing)
         );
         std::process::exit(1);
+    } else if !findings.is_empty() {
+        eprintln!(
+            "hookwise: {} potential secret(s) below {} severity; not failing.",
+            flagged_lines(&findings),
+            min_severity
+        );
     } else {
         eprintln!("hookwise: scan clean -- no secrets detected.");
     }
@@ -75,47 +490,284 @@ pub async fn run(staged: bool, path: Option<&str>) -> Result<()> {
     Ok(())
 }
 
-/// Scan a single file for secrets. Returns the number of findings.
-fn scan_file(pipeline: &SanitizePipeline, path: &str) -> Result<usize> {
-    let contents = match std::fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return Ok(0), // Skip binary/unreadable files
-    };
+/// Number of distinct lines with findings.
+fn flagged_lines(findings: &[ScanFinding]) -> usize {
+    let mut lines: Vec<_> = findings.iter().map(|f| (&f.path, f.line)).collect();
+    lines.dedup();
+    lines.len()
+}
+
+/// Print one line per flagged line, naming every rule that matched it.
+fn print_text(findings: &[ScanFinding]) {
+    for group in findings.chunk_by(|a, b| a.path == b.path && a.line == b.line) {
+        let rules: Vec<String> = group
+            .iter()
+            .map(|f| format!("{} [{}]", f.rule, f.severity))
+            .collect();
+        eprintln!(
+            "  {}:{}: potential secret detected ({})",
+            group[0].path,
+            group[0].line,
+            rules.join(", ")
+        );
+    }
+}
+
+/// SARIF result level for a severity.
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Low => "note",
+        Severity::Medium => "warning",
+        Severity::High | Severity::Critical => "error",
+    }
+}
+
+/// GitHub code scanning's `security-severity` score for a severity.
+fn security_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Low => "2.0",
+        Severity::Medium => "5.0",
+        Severity::High => "8.0",
+        Severity::Critical => "9.5",
+    }
+}
+
+/// A SARIF 2.1.0 log of `findings`, with one rule per finding kind.
+pub fn to_sarif(findings: &[ScanFinding]) -> serde_json::Value {
+    let mut rule_ids: Vec<&str> = findings.iter().map(|f| f.rule.as_str()).collect();
+    rule_ids.sort_unstable();
+    rule_ids.dedup();
+    let rules: Vec<_> = rule_ids
+        .iter()
+        .map(|id| {
+            let severity = findings
+                .iter()
+                .filter(|f| f.rule == *id)
+                .map(|f| f.severity)
+                .max()
+                .unwrap_or_default();
+            serde_json::json!({
+                "id": id,
+                "name": id,
+                "shortDescription": { "text": format!("Potential secret ({})", id) },
+                "defaultConfiguration": { "level": sarif_level(severity) },
+                "properties": { "security-severity": security_severity(severity) },
+            })
+        })
+        .collect();
+    let results: Vec<_> = findings
+        .iter()
+        .map(|f| {
+            serde_json::json!({
+                "ruleId": f.rule,
+                "ruleIndex": rule_ids.binary_search(&f.rule.as_str()).unwrap_or_default(),
+                "level": sarif_level(f.severity),
+                "message": {
+                    "text": format!("Potential secret detected ({}, {})", f.rule, f.severity)
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": f.path.replace('\\', "/") },
+                        "region": { "startLine": f.line },
+                    }
+                }],
+                "partialFingerprints": { "hookwiseFingerprint/v1": f.fingerprint },
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "hookwise",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/Epiphytic/hookwise",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Fingerprint of a finding; see [`ScanFinding::fingerprint`].
+fn fingerprint(path: &str, rule: &str, redacted_line: &str) -> String {
+    let hash = Sha256::digest(format!("{}\0{}\0{}", path, rule, redacted_line.trim()));
+    format!("{:x}", hash)[..32].to_string()
+}
+
+/// Bytes checked for a NUL when deciding whether a file is binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
 
-    let mut findings = 0;
+/// Read buffer for streaming files through the scanner.
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Why a file was left out of the scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Skipped {
+    Binary,
+    TooLarge,
+}
+
+/// Findings from a set of files, and how many were skipped.
+#[derive(Debug, Default)]
+struct ScanOutcome {
+    findings: Vec<ScanFinding>,
+    binary: usize,
+    too_large: usize,
+}
 
-    for (line_num, line) in contents.lines().enumerate() {
-        let sanitized = pipeline.sanitize(line);
-        if sanitized != line {
-            findings += 1;
-            eprintln!("  {}:{}: potential secret detected", path, line_num + 1);
+impl ScanOutcome {
+    /// Note skipped files on stderr.
+    fn report_skipped(&self, max_file_bytes: u64) {
+        if self.binary > 0 {
+            eprintln!("hookwise: skipped {} binary file(s)", self.binary);
+        }
+        if self.too_large > 0 {
+            eprintln!(
+                "hookwise: skipped {} file(s) over {} bytes (scan-rules.yml max_file_bytes)",
+                self.too_large, max_file_bytes
+            );
         }
     }
+}
 
-    Ok(findings)
+/// Scan `paths` in parallel, keeping findings in path order.
+fn scan_files(pipeline: &SanitizePipeline, rules: &ScanRules, paths: &[String]) -> ScanOutcome {
+    let results: Vec<_> = paths
+        .par_iter()
+        .map(|path| scan_file(pipeline, rules, path))
+        .collect();
+
+    let mut outcome = ScanOutcome::default();
+    for (findings, skipped) in results {
+        outcome.findings.extend(findings);
+        match skipped {
+            Some(Skipped::Binary) => outcome.binary += 1,
+            Some(Skipped::TooLarge) => outcome.too_large += 1,
+            None => {}
+        }
+    }
+    outcome
 }
 
-/// Scan a directory recursively for secrets. Returns the number of findings.
-fn scan_dir(pipeline: &SanitizePipeline, dir: &PathBuf) -> Result<usize> {
-    let mut total = 0;
+/// Scan a single file for secrets, in line order, and say why its contents
+/// were skipped, if they were. The file is streamed line by line, so
+/// memory stays bounded by the longest line. Key files named like one are
+/// reported on line 1 even when their contents are skipped.
+fn scan_file(
+    pipeline: &SanitizePipeline,
+    rules: &ScanRules,
+    path: &str,
+) -> (Vec<ScanFinding>, Option<Skipped>) {
+    let mut findings = Vec::new();
+    if let Some(rule) = key_file_rule(path) {
+        if let Some(severity) = rules.builtin_severity(rule, path) {
+            findings.push(ScanFinding {
+                path: path.to_string(),
+                line: 1,
+                rule: rule.to_string(),
+                count: 1,
+                severity,
+                fingerprint: fingerprint(path, rule, ""),
+                suppressed: false,
+            });
+        }
+    }
+
+    // Unreadable files (deleted since staging, permissions) have nothing
+    // more to report
+    let Ok(file) = std::fs::File::open(path) else {
+        return (findings, None);
+    };
+    if file
+        .metadata()
+        .is_ok_and(|m| m.len() > rules.max_file_bytes)
+    {
+        return (findings, Some(Skipped::TooLarge));
+    }
+
+    let mut reader = BufReader::with_capacity(CHUNK_BYTES, file);
+    match reader.fill_buf() {
+        Ok(head) if head[..head.len().min(BINARY_SNIFF_BYTES)].contains(&0) => {
+            return (findings, Some(Skipped::Binary))
+        }
+        Ok(_) => {}
+        Err(_) => return (findings, None),
+    }
 
-    for entry in std::fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
+    let kubeconfig = is_kubeconfig(path);
+    let mut buf = Vec::new();
+    let mut above = String::new();
+    let mut line_num = 0;
+    // Inside a PEM private key, already reported on its first line
+    let mut in_key = false;
 
-        if path.is_dir() {
-            // Skip hidden directories
-            if path
-                .file_name()
-                .is_some_and(|n| n.to_string_lossy().starts_with('.'))
-            {
-                continue;
-            }
-            total += scan_dir(pipeline, &path)?;
-        } else if path.is_file() {
-            total += scan_file(pipeline, &path.to_string_lossy())?;
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
         }
+        line_num += 1;
+        // Text in other encodings is still scanned, minus the odd byte
+        let text = String::from_utf8_lossy(&buf);
+        let line = text.trim_end_matches('\n').trim_end_matches('\r');
+
+        let (redacted, hits) = if in_key {
+            in_key = !PEM_KEY_END.is_match(line);
+            (String::new(), Vec::new())
+        } else if let Some((rule, redacted)) = key_material(line, kubeconfig) {
+            // A one-line key (e.g. with escaped newlines in JSON) ends here
+            in_key = rule == "private_key" && !PEM_KEY_END.is_match(line);
+            let hits = rules
+                .builtin_severity(rule, path)
+                .map(|severity| (rule.to_string(), 1, severity));
+            (redacted, hits.into_iter().collect())
+        } else {
+            rules.check_line(pipeline, path, line)
+        };
+        for (rule, count, severity) in hits {
+            findings.push(ScanFinding {
+                path: path.to_string(),
+                line: line_num,
+                fingerprint: fingerprint(path, &rule, &redacted),
+                suppressed: is_ignored(&rule, line, (line_num > 1).then_some(above.as_str())),
+                rule,
+                count,
+                severity,
+            });
+        }
+        above.clear();
+        above.push_str(line);
     }
 
-    Ok(total)
+    (findings, None)
+}
+
+/// Files under `dir`, sorted. Hidden directories are skipped (hidden files
+/// such as `.env` are not), as is anything `.gitignore`, `.ignore` or the
+/// global git excludes exclude, in or out of a git repository.
+fn list_dir(dir: &Path) -> Result<Vec<String>> {
+    let walker = WalkBuilder::new(dir)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_some_and(|t| t.is_dir())
+                || !entry.file_name().to_string_lossy().starts_with('.')
+        })
+        .build();
+
+    let mut paths = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(std::io::Error::other)?;
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            paths.push(entry.path().to_string_lossy().into_owned());
+        }
+    }
+    paths.sort();
+    Ok(paths)
 }
@@ -1,5 +1,6 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::config::{GlobalConfig, UpdatesConfig};
 use crate::error::Result;
 
 const GITHUB_REPO: &str = "Epiphytic/hookwise";
@@ -7,12 +8,36 @@ const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Run the `self-update` subcommand.
 /// If `check_only` is true, just check for updates without installing.
-pub async fn run(check_only: bool) -> Result<()> {
-    let latest = fetch_latest_version().await?;
+/// With `install_dir`, the binary goes there instead of replacing the
+/// running one; without it, binaries owned by a package manager or in a
+/// read-only directory are left alone and the user is told what to do.
+pub async fn run(check_only: bool, install_dir: Option<&Path>) -> Result<()> {
+    let current_exe = std::env::current_exe()
+        .and_then(|exe| exe.canonicalize())
+        .map_err(|e| io_err(format!("Failed to determine current binary path: {}", e)))?;
+    let destination = install_destination(&current_exe, install_dir);
+
+    let config = GlobalConfig::load()?.map(|c| c.updates).unwrap_or_default();
+    let source = ReleaseSource::from_config(&config);
+    let latest = fetch_latest_version(&source).await?;
     let latest_tag = latest.trim_start_matches('v');
 
-    if latest_tag == CURRENT_VERSION {
+    if !is_newer(latest_tag, CURRENT_VERSION) {
         println!("hookwise {} is up to date.", CURRENT_VERSION);
+        // A portable install of the running binary
+        if let Some(dir) = install_dir.filter(|_| !check_only) {
+            let target = dir.join(BINARY_NAME);
+            if !target.exists() {
+                std::fs::create_dir_all(dir)?;
+                replace_binary(&current_exe, &target)?;
+                println!(
+                    "Installed hookwise {} to {}.",
+                    CURRENT_VERSION,
+                    target.display()
+                );
+                warn_path_order(dir);
+            }
+        }
         return Ok(());
     }
 
@@ -22,25 +47,24 @@ pub async fn run(check_only: bool) -> Result<()> {
     );
 
     if check_only {
-        println!("Run `hookwise self-update` to install.");
+        match &destination {
+            Ok(_) => println!("Run `hookwise self-update` to install."),
+            Err(guidance) => println!("{}", guidance),
+        }
         return Ok(());
     }
+    let destination = destination.map_err(io_err)?;
 
     // Determine platform
     let target = detect_target()?;
     let archive_name = format!("hookwise-v{}-{}.tar.gz", latest_tag, target);
     let sha_name = format!("{}.sha256", archive_name);
 
-    let base_url = format!(
-        "https://github.com/{}/releases/download/v{}",
-        GITHUB_REPO, latest_tag
-    );
-
     let client = reqwest::Client::new();
 
     // Download archive
     println!("Downloading {}...", archive_name);
-    let archive_url = format!("{}/{}", base_url, archive_name);
+    let archive_url = source.asset_url(latest_tag, &archive_name);
     let archive_bytes = client
         .get(&archive_url)
         .send()
@@ -53,7 +77,7 @@ pub async fn run(check_only: bool) -> Result<()> {
         .map_err(|e| io_err(format!("Download failed: {}", e)))?;
 
     // Download checksum
-    let sha_url = format!("{}/{}", base_url, sha_name);
+    let sha_url = source.asset_url(latest_tag, &sha_name);
     let sha_text = client
         .get(&sha_url)
         .send()
@@ -97,135 +121,335 @@ pub async fn run(check_only: bool) -> Result<()> {
         return Err(io_err("Binary not found in archive".into()));
     }
 
-    // Find current binary location
-    let current_exe = std::env::current_exe()
-        .map_err(|e| io_err(format!("Failed to determine current binary path: {}", e)))?;
+    if let Some(dir) = install_dir {
+        std::fs::create_dir_all(dir)?;
+    }
+    println!("Installing to {}...", destination.display());
+    replace_binary(&extracted_binary, &destination)?;
+    println!("hookwise updated to v{}.", latest_tag);
+    if let Some(dir) = install_dir {
+        warn_path_order(dir);
+    }
 
-    // Replace binary
-    println!("Installing to {}...", current_exe.display());
-    let backup = current_exe.with_extension("old");
+    Ok(())
+}
+
+/// Replace `target` with `binary`, keeping the old file as a backup until
+/// the copy succeeded.
+fn replace_binary(binary: &Path, target: &Path) -> Result<()> {
+    let backup = target.with_extension("old");
 
     // Move current to backup, copy new, remove backup
-    if current_exe.exists() {
-        std::fs::rename(&current_exe, &backup)
+    if target.exists() {
+        std::fs::rename(target, &backup)
             .map_err(|e| io_err(format!("Failed to create backup: {}", e)))?;
     }
 
-    match std::fs::copy(&extracted_binary, &current_exe) {
+    match std::fs::copy(binary, target) {
         Ok(_) => {
             // Set executable permission
             #[cfg(unix)]
             {
                 use std::os::unix::fs::PermissionsExt;
-                std::fs::set_permissions(&current_exe, std::fs::Permissions::from_mode(0o755))
+                std::fs::set_permissions(target, std::fs::Permissions::from_mode(0o755))
                     .map_err(|e| io_err(format!("Failed to set permissions: {}", e)))?;
             }
 
             // Remove backup
             let _ = std::fs::remove_file(&backup);
-            println!("hookwise updated to v{}.", latest_tag);
+            Ok(())
         }
         Err(e) => {
             // Restore backup on failure
             if backup.exists() {
-                let _ = std::fs::rename(&backup, &current_exe);
+                let _ = std::fs::rename(&backup, target);
             }
-            return Err(io_err(format!("Failed to install binary: {}", e)));
+            Err(io_err(format!("Failed to install binary: {}", e)))
         }
     }
+}
 
-    Ok(())
+const BINARY_NAME: &str = if cfg!(windows) {
+    "hookwise.exe"
+} else {
+    "hookwise"
+};
+
+/// A package manager that owns the binary at `exe`, with how to upgrade
+/// through it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackageManager {
+    pub name: &'static str,
+    pub upgrade: &'static str,
 }
 
-/// Check for updates periodically (once per day) and print a stderr warning.
-/// Called from the hot path (check subcommand). Non-blocking.
+/// The package manager that installed `exe` (a canonical path), if any.
+pub fn package_manager(exe: &Path) -> Option<PackageManager> {
+    if exe.starts_with("/nix/store") {
+        return Some(PackageManager {
+            name: "Nix",
+            upgrade: "update it through your Nix profile, home-manager or system configuration",
+        });
+    }
+    let homebrew = exe
+        .components()
+        .any(|c| c.as_os_str() == "Cellar" || c.as_os_str() == ".linuxbrew");
+    homebrew.then_some(PackageManager {
+        name: "Homebrew",
+        upgrade: "run `brew upgrade hookwise`",
+    })
+}
+
+/// Where `self-update` installs: `install_dir`, or over `current_exe` when
+/// that is ours to replace. Otherwise the error tells the user what to do.
+pub fn install_destination(
+    current_exe: &Path,
+    install_dir: Option<&Path>,
+) -> std::result::Result<PathBuf, String> {
+    if let Some(dir) = install_dir {
+        return Ok(dir.join(BINARY_NAME));
+    }
+    let portable = "or install a copy you can update with `hookwise self-update --install-dir ~/.local/bin` and put that directory first on PATH";
+    if let Some(manager) = package_manager(current_exe) {
+        return Err(format!(
+            "hookwise at {} is managed by {}: {}, {}.",
+            current_exe.display(),
+            manager.name,
+            manager.upgrade,
+            portable
+        ));
+    }
+    let dir = current_exe.parent().unwrap_or(Path::new("."));
+    if !dir_writable(dir) {
+        return Err(format!(
+            "{} is not writable: rerun with the permissions to replace {}, {}.",
+            dir.display(),
+            current_exe.display(),
+            portable
+        ));
+    }
+    Ok(current_exe.to_path_buf())
+}
+
+/// Whether files can be created in `dir`.
+fn dir_writable(dir: &Path) -> bool {
+    tempfile::Builder::new()
+        .prefix(".hookwise-update")
+        .tempfile_in(dir)
+        .is_ok()
+}
+
+/// Why a binary installed to `dir` might not be the one that runs, given
+/// the `PATH` value `path`.
+pub fn path_warning(dir: &Path, path: &std::ffi::OsStr) -> Option<String> {
+    let entries: Vec<PathBuf> = std::env::split_paths(path).collect();
+    let Some(position) = entries.iter().position(|entry| entry == dir) else {
+        return Some(format!(
+            "{} is not on PATH; add it so the new binary runs",
+            dir.display()
+        ));
+    };
+    let shadow = entries[..position]
+        .iter()
+        .map(|entry| entry.join(BINARY_NAME))
+        .find(|binary| binary.is_file())?;
+    Some(format!(
+        "{} comes before {} on PATH; move {} first so the new binary runs",
+        shadow.display(),
+        dir.display(),
+        dir.display()
+    ))
+}
+
+fn warn_path_order(dir: &Path) {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    if let Some(warning) = path_warning(dir, &path) {
+        eprintln!("hookwise: {}", warning);
+    }
+}
+
+/// Print the update hint from the last scheduled check, if any. Called from
+/// the hot path (`check`), so it only reads the cache `hookwise maintain`
+/// writes and never touches the network.
 pub fn check_update_hint() {
-    let config_dir = crate::config::dirs_global();
-    let check_file = config_dir.join("update-check.json");
-
-    // Read last check timestamp
-    if let Ok(contents) = std::fs::read_to_string(&check_file) {
-        if let Ok(last_check) = serde_json::from_str::<UpdateCheck>(&contents) {
-            let elapsed = chrono::Utc::now()
-                .signed_duration_since(last_check.checked_at)
-                .num_hours();
-            if elapsed < 24 {
-                // Already checked recently, and we may have a cached result
-                if let Some(ref latest) = last_check.latest_version {
-                    let latest_tag = latest.trim_start_matches('v');
-                    if latest_tag != CURRENT_VERSION {
-                        eprintln!(
-                            "hookwise: update available v{} -> v{} (run `hookwise self-update`)",
-                            CURRENT_VERSION, latest_tag
-                        );
-                    }
-                }
-                return;
+    let config = match GlobalConfig::load() {
+        Ok(config) => config.map(|c| c.updates).unwrap_or_default(),
+        Err(_) => return,
+    };
+    if !config.hints {
+        return;
+    }
+    if let Some(hint) =
+        UpdateCheck::load(&update_check_path()).and_then(|c| c.hint(CURRENT_VERSION))
+    {
+        eprintln!("{}", hint);
+    }
+}
+
+/// Check for a release if the last check is older than
+/// `check_interval_hours` (or `force` is set) and cache the result for the
+/// hint. Returns the check when one ran; nothing runs with `hints` off.
+pub async fn refresh_update_check(
+    config: &UpdatesConfig,
+    force: bool,
+) -> Result<Option<UpdateCheck>> {
+    if !config.hints {
+        return Ok(None);
+    }
+    let path = update_check_path();
+    let now = chrono::Utc::now();
+    if !force {
+        if let Some(last) = UpdateCheck::load(&path) {
+            if !last.is_due(config.check_interval_hours, now) {
+                return Ok(None);
             }
         }
     }
 
-    // Spawn a background task to check (non-blocking)
-    let check_file = check_file.clone();
-    tokio::spawn(async move {
-        if let Ok(latest) = fetch_latest_version().await {
-            let check = UpdateCheck {
-                checked_at: chrono::Utc::now(),
-                latest_version: Some(latest.clone()),
-                current_version: CURRENT_VERSION.to_string(),
-            };
-            let _ = std::fs::create_dir_all(check_file.parent().unwrap_or(&PathBuf::from(".")));
-            let _ = std::fs::write(
-                &check_file,
-                serde_json::to_string(&check).unwrap_or_default(),
-            );
-
-            let latest_tag = latest.trim_start_matches('v');
-            if latest_tag != CURRENT_VERSION {
-                eprintln!(
-                    "hookwise: update available v{} -> v{} (run `hookwise self-update`)",
-                    CURRENT_VERSION, latest_tag
-                );
-            }
+    let source = ReleaseSource::from_config(config);
+    let check = UpdateCheck {
+        checked_at: now,
+        latest_version: Some(fetch_latest_version(&source).await?),
+        current_version: CURRENT_VERSION.to_string(),
+    };
+    check.save(&path)?;
+    Ok(Some(check))
+}
+
+/// Cached result of the last release check.
+pub fn update_check_path() -> PathBuf {
+    crate::config::dirs_cache().join("update-check.json")
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct UpdateCheck {
+    pub checked_at: chrono::DateTime<chrono::Utc>,
+    pub latest_version: Option<String>,
+    pub current_version: String,
+}
+
+impl UpdateCheck {
+    /// Load a cached check. Missing or unreadable files yield `None`.
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
         }
-    });
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Whether the next check is due `interval_hours` after this one.
+    pub fn is_due(&self, interval_hours: u64, now: chrono::DateTime<chrono::Utc>) -> bool {
+        now.signed_duration_since(self.checked_at).num_hours() >= interval_hours as i64
+    }
+
+    /// The hint to print when the checked release is newer than `current`.
+    pub fn hint(&self, current: &str) -> Option<String> {
+        let latest = self.latest_version.as_deref()?.trim_start_matches('v');
+        is_newer(latest, current).then(|| {
+            format!(
+                "hookwise: update available v{} -> v{} (run `hookwise self-update`)",
+                current, latest
+            )
+        })
+    }
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct UpdateCheck {
-    checked_at: chrono::DateTime<chrono::Utc>,
-    latest_version: Option<String>,
-    current_version: String,
+/// Whether dotted version `latest` is newer than `current`. Pre-release and
+/// build suffixes are ignored.
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(latest) > parse(current)
 }
 
-async fn fetch_latest_version() -> std::result::Result<String, crate::error::HookwiseError> {
-    let url = format!(
-        "https://api.github.com/repos/{}/releases/latest",
-        GITHUB_REPO
-    );
+/// Where releases are published: GitHub, or an internal mirror for
+/// air-gapped orgs (`updates.mirror` in the global config).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReleaseSource {
+    GitHub,
+    Mirror(String),
+}
+
+impl ReleaseSource {
+    pub fn from_config(config: &UpdatesConfig) -> Self {
+        match &config.mirror {
+            Some(url) => Self::Mirror(url.trim_end_matches('/').to_string()),
+            None => Self::GitHub,
+        }
+    }
+
+    /// URL answering with the latest release tag.
+    pub fn latest_url(&self) -> String {
+        match self {
+            Self::GitHub => format!(
+                "https://api.github.com/repos/{}/releases/latest",
+                GITHUB_REPO
+            ),
+            Self::Mirror(base) => format!("{}/latest", base),
+        }
+    }
 
+    /// URL of a release asset, e.g. the archive or its `.sha256`.
+    pub fn asset_url(&self, version: &str, asset: &str) -> String {
+        match self {
+            Self::GitHub => format!(
+                "https://github.com/{}/releases/download/v{}/{}",
+                GITHUB_REPO, version, asset
+            ),
+            Self::Mirror(base) => format!("{}/v{}/{}", base, version, asset),
+        }
+    }
+
+    /// The release tag in a response from [`Self::latest_url`]: GitHub's
+    /// release JSON, or a mirror's plain-text tag.
+    pub fn parse_latest(&self, body: &str) -> Option<String> {
+        let tag = match self {
+            Self::GitHub => serde_json::from_str::<serde_json::Value>(body)
+                .ok()?
+                .get("tag_name")?
+                .as_str()?
+                .to_string(),
+            Self::Mirror(_) => body.trim().to_string(),
+        };
+        (!tag.is_empty()).then_some(tag)
+    }
+}
+
+async fn fetch_latest_version(
+    source: &ReleaseSource,
+) -> std::result::Result<String, crate::error::HookwiseError> {
     let client = reqwest::Client::builder()
         .user_agent("hookwise-updater")
         .build()
         .map_err(|e| io_err(format!("HTTP client error: {}", e)))?;
 
-    let resp = client
+    let url = source.latest_url();
+    let body = client
         .get(&url)
         .send()
         .await
-        .map_err(|e| io_err(format!("GitHub API request failed: {}", e)))?
+        .map_err(|e| io_err(format!("Release check {} failed: {}", url, e)))?
         .error_for_status()
-        .map_err(|e| io_err(format!("GitHub API error: {}", e)))?;
-
-    let body: serde_json::Value = resp
-        .json()
+        .map_err(|e| io_err(format!("Release check {} failed: {}", url, e)))?
+        .text()
         .await
-        .map_err(|e| io_err(format!("Failed to parse GitHub API response: {}", e)))?;
+        .map_err(|e| io_err(format!("Release check {} failed: {}", url, e)))?;
 
-    body["tag_name"]
-        .as_str()
-        .map(|s| s.to_string())
-        .ok_or_else(|| io_err("No tag_name in GitHub release".into()))
+    source
+        .parse_latest(&body)
+        .ok_or_else(|| io_err(format!("No release tag in the response from {}", url)))
 }
 
 fn detect_target() -> std::result::Result<&'static str, crate::error::HookwiseError> {
@@ -1,14 +1,19 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::RoleRecommendation;
+use crate::decision::Decision;
 use crate::error::Result;
-use crate::hook_io::HookFormat;
+use crate::hook_io::{ContextHookOutput, GeminiHookOutput, HookFormat};
 use crate::session::SessionManager;
 
 /// Run the `session-check` subcommand.
 /// Used by the `user_prompt_submit` hook (Claude) or `BeforeAgent` hook (Gemini)
 /// to check if a session is registered.
-/// If not registered, outputs a prompt asking the user to pick a role.
+/// If not registered, emits a registration prompt in the hook's native output format.
 pub async fn run(format: HookFormat) -> Result<()> {
     // Read hook input from stdin to get session_id
-    let input = crate::hook_io::read_hook_input()?;
+    let input = crate::hook_io::read_prompt_hook_input()?;
     let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
     let session_mgr = SessionManager::new(team_id.as_deref());
 
@@ -25,30 +30,132 @@ pub async fn run(format: HookFormat) -> Result<()> {
     // Not registered -- output a registration prompt
     let cwd = std::path::PathBuf::from(&input.cwd);
     let roles = crate::config::RolesConfig::load_project(&cwd)?;
-    let role_names: Vec<&String> = roles.roles.keys().collect();
-
-    let _format = format; // format available for future use (e.g. Gemini-specific messaging)
-
-    eprintln!(
-        "hookwise: session {} is not registered.",
-        input.session_id
-    );
-    eprintln!(
-        "Available roles: {}",
-        role_names
-            .iter()
-            .map(|r| r.as_str())
-            .collect::<Vec<_>>()
-            .join(", ")
-    );
-    eprintln!(
-        "Register with: hookwise register --session-id {} --role <ROLE>",
-        input.session_id
-    );
-    eprintln!(
-        "Or disable: hookwise disable --session-id {}",
-        input.session_id
-    );
-
-    Ok(())
+    let mut role_names: Vec<&str> = roles.roles.keys().map(String::as_str).collect();
+    role_names.sort_unstable();
+    let (changed, tracked) = working_tree(&cwd);
+    let recommended = roles.recommend_role(&changed, &tracked, input.prompt.as_deref());
+
+    crate::hook_io::write_json_output(&registration_output(
+        format,
+        &input.session_id,
+        &role_names,
+        recommended.as_ref(),
+    ))
+}
+
+/// Files changed in the working tree at `cwd` (staged, unstaged, untracked
+/// or deleted) and the files git tracks, relative to `cwd`. hookwise's own
+/// `.hookwise/` files are left out. Empty outside a git repository.
+fn working_tree(cwd: &Path) -> (Vec<String>, Vec<String>) {
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(cwd)
+            .args(args)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let Some(status) = git(&["status", "--porcelain", "--untracked-files=all"]) else {
+        return (Vec::new(), Vec::new());
+    };
+    // Status paths are relative to the repository root
+    let prefix = git(&["rev-parse", "--show-prefix"]).unwrap_or_default();
+    let prefix = prefix.trim();
+    let changed = status
+        .lines()
+        .filter_map(|line| {
+            // "XY path", or "XY from -> to" for a rename
+            let path = line.get(3..)?.rsplit(" -> ").next()?;
+            let path = path.trim_matches('"').strip_prefix(prefix)?;
+            (!path.starts_with(".hookwise/")).then(|| path.to_string())
+        })
+        .collect();
+    let tracked = git(&["ls-files"])
+        .map(|files| files.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+    (changed, tracked)
+}
+
+/// The registration prompt as hook output.
+///
+/// Claude receives it as `additionalContext` so the model asks the user for a
+/// role and registers the session itself. Gemini's `BeforeAgent` hook only
+/// reliably shows a blocking `reason`, so the turn is held back with
+/// instructions addressed to the user.
+pub fn registration_output(
+    format: HookFormat,
+    session_id: &str,
+    roles: &[&str],
+    recommended: Option<&RoleRecommendation>,
+) -> serde_json::Value {
+    let output = match format {
+        HookFormat::Claude => {
+            let context = registration_context(session_id, roles, recommended);
+            serde_json::to_value(ContextHookOutput::new(context))
+        }
+        HookFormat::Gemini => serde_json::to_value(GeminiHookOutput::new(
+            Decision::Deny,
+            Some(registration_notice(session_id, roles, recommended)),
+        )),
+    };
+    output.unwrap_or_default()
+}
+
+/// Instructions for the model: ask the user for a role, offering the
+/// recommended one first, then register via the `hookwise_register` MCP
+/// tool, falling back to the CLI.
+pub fn registration_context(
+    session_id: &str,
+    roles: &[&str],
+    recommended: Option<&RoleRecommendation>,
+) -> String {
+    let suggestion = recommended
+        .map(|r| {
+            format!(
+                "Suggested role: {} ({}). Offer it first, but let the user choose.\n\n",
+                r.role, r.reason
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "hookwise: this session has no role assigned, so tool calls will not be permitted.\n\
+         \n\
+         Available roles: {roles}\n\
+         \n\
+         {suggestion}\
+         Before proceeding, ask the user which role this session should use, or whether to \
+         disable hookwise for it. Then call the `hookwise_register` MCP tool with \
+         session_id \"{id}\" and the chosen role (or `hookwise_disable` with the same \
+         session_id). If the hookwise MCP tools are unavailable, tell the user to run:\n\
+         \n\
+         \x20 hookwise register --session-id {id} --role <ROLE>\n\
+         \x20 hookwise disable --session-id {id}\n\
+         \n\
+         Then continue with the user's original request.",
+        roles = roles.join(", "),
+        id = session_id,
+    )
+}
+
+/// Instructions for the user, shown when the prompt is held back.
+pub fn registration_notice(
+    session_id: &str,
+    roles: &[&str],
+    recommended: Option<&RoleRecommendation>,
+) -> String {
+    let suggestion = recommended
+        .map(|r| format!("Suggested role: {} ({}). ", r.role, r.reason))
+        .unwrap_or_default();
+    format!(
+        "hookwise: session {id} has no role assigned. Available roles: {roles}. \
+         {suggestion}Register with `hookwise register --session-id {id} --role <ROLE>` \
+         (or `hookwise disable --session-id {id}`), or ask the agent to call the \
+         hookwise_register tool, then resend your prompt.",
+        roles = roles.join(", "),
+        id = session_id,
+    )
 }
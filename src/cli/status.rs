@@ -0,0 +1,271 @@
+//! `hookwise status`: one screen answering "is hookwise healthy right now?"
+//! -- supervisor daemon, hook installation, sessions, queue depth, org rule
+//! freshness, policy fingerprint and storage sizes.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+
+use crate::cascade::human::load_queue_file;
+use crate::config::{dirs_cache, dirs_rules, PolicyConfig, SessionMode};
+use crate::error::Result;
+use crate::notify::format_elapsed;
+use crate::session::SessionManager;
+
+/// Text that marks a settings or hooks file as running hookwise.
+const HOOK_COMMAND: &str = "hookwise check";
+
+/// Hook files inside plugin and extension directories.
+const PLUGIN_HOOK_FILES: &[&str] = &["hooks.json", "gemini-hooks.json"];
+
+/// How deep plugin and extension directories are searched.
+const PLUGIN_SEARCH_DEPTH: usize = 5;
+
+/// How long to wait for the supervisor to accept a connection.
+const SUPERVISOR_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Print a health summary for the current project.
+pub async fn run() -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let hookwise_dir = cwd.join(".hookwise");
+    let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
+    let home = std::env::var("HOME").ok().map(PathBuf::from);
+    let policy = PolicyConfig::load_project(&cwd)?;
+    let now = Utc::now();
+
+    println!("hookwise status");
+    println!("===============");
+
+    match policy.supervisor.socket_path(team_id.as_deref()) {
+        Some(path) => {
+            let state = if supervisor_running(&path).await {
+                "running"
+            } else {
+                "not running"
+            };
+            println!("Supervisor: {} ({})", state, path.display());
+        }
+        None => println!("Supervisor: API backend (no daemon)"),
+    }
+
+    let installs = hook_installs(&cwd, home.as_deref());
+    if installs.is_empty() {
+        println!("Hooks: not installed (no `{}` hook found)", HOOK_COMMAND);
+    } else {
+        println!("Hooks: installed");
+        for path in installs {
+            println!("  {}", path.display());
+        }
+    }
+
+    let sessions = SessionManager::new(team_id.as_deref());
+    let mut registered: Vec<_> = sessions.registrations()?.into_iter().collect();
+    registered.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.registered_at));
+    let disabled = sessions.active_exclusions()?;
+    println!(
+        "Sessions: {} registered, {} disabled",
+        registered.len(),
+        disabled.len()
+    );
+    for (id, entry) in &registered {
+        let mode = match entry.mode {
+            Some(SessionMode::Audit) => " (audit)",
+            _ => "",
+        };
+        println!(
+            "  {}  {}{}  registered {} ago",
+            id,
+            entry.roles().join(", "),
+            mode,
+            format_elapsed(now.signed_duration_since(entry.registered_at))
+        );
+    }
+    for exclusion in &disabled {
+        let until = match exclusion.until {
+            Some(until) => format!(
+                " for another {}",
+                format_elapsed(until.signed_duration_since(now))
+            ),
+            None => String::new(),
+        };
+        println!("  {}  disabled{}", exclusion.session_id, until);
+    }
+
+    let queue = load_queue_file();
+    match queue.pending.values().map(|p| p.queued_at).min() {
+        Some(oldest) => println!(
+            "Pending queue: {} (oldest {} ago)",
+            queue.pending.len(),
+            format_elapsed(now.signed_duration_since(oldest))
+        ),
+        None => println!("Pending queue: 0"),
+    }
+
+    let rules_root = dirs_rules();
+    match newest_modification(&rules_root.join("org")) {
+        Some(at) => {
+            let age = SystemTime::now().duration_since(at).unwrap_or_default();
+            println!(
+                "Last sync: org rules updated {} ago",
+                format_elapsed(chrono::Duration::from_std(age).unwrap_or_default())
+            );
+        }
+        None => println!("Last sync: never (no org rules)"),
+    }
+
+    match policy_fingerprint(&hookwise_dir) {
+        Some(fingerprint) => println!("Policy: {} (policy.yml + roles.yml)", fingerprint),
+        None => println!("Policy: not initialized -- run `hookwise init`"),
+    }
+
+    println!("Storage:");
+    for (label, path) in [
+        ("Project rules", hookwise_dir.join("rules")),
+        ("Project index", hookwise_dir.join(".index")),
+        ("Project logs", hookwise_dir.join(".user")),
+        ("Global rules", rules_root),
+        ("Cache", dirs_cache()),
+    ] {
+        println!(
+            "  {:<14} {:>10}  {}",
+            label,
+            format_size(dir_size(&path)),
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Whether a supervisor accepts connections at `socket_path`.
+async fn supervisor_running(socket_path: &Path) -> bool {
+    matches!(
+        tokio::time::timeout(
+            SUPERVISOR_TIMEOUT,
+            crate::ipc::transport::connect(socket_path)
+        )
+        .await,
+        Ok(Ok(_))
+    )
+}
+
+/// Settings and plugin files that register hookwise as a hook: the
+/// project's and user's Claude Code settings, Gemini CLI settings, and hook
+/// files of installed Claude Code plugins and Gemini CLI extensions.
+pub fn hook_installs(cwd: &Path, home: Option<&Path>) -> Vec<PathBuf> {
+    let mut candidates = vec![
+        cwd.join(".claude/settings.json"),
+        cwd.join(".claude/settings.local.json"),
+        cwd.join(".gemini/settings.json"),
+    ];
+    if let Some(home) = home {
+        candidates.push(home.join(".claude/settings.json"));
+        candidates.push(home.join(".gemini/settings.json"));
+        for dir in [
+            home.join(".claude/plugins"),
+            home.join(".gemini/extensions"),
+        ] {
+            find_files(
+                &dir,
+                PLUGIN_HOOK_FILES,
+                PLUGIN_SEARCH_DEPTH,
+                &mut candidates,
+            );
+        }
+    }
+    candidates
+        .into_iter()
+        .filter(|path| {
+            std::fs::read_to_string(path).is_ok_and(|contents| contents.contains(HOOK_COMMAND))
+        })
+        .collect()
+}
+
+fn find_files(dir: &Path, names: &[&str], depth: usize, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() && depth > 0 {
+            find_files(&path, names, depth - 1, found);
+        } else if file_type.is_file()
+            && names
+                .iter()
+                .any(|name| entry.file_name() == std::ffi::OsStr::new(name))
+        {
+            found.push(path);
+        }
+    }
+}
+
+/// A short SHA-256 fingerprint of the project's policy.yml and roles.yml,
+/// for telling at a glance whether two checkouts enforce the same policy.
+/// None when neither file exists.
+pub fn policy_fingerprint(hookwise_dir: &Path) -> Option<String> {
+    let mut hasher = Sha256::new();
+    let mut found = false;
+    for name in ["policy.yml", "roles.yml"] {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        if let Ok(contents) = std::fs::read(hookwise_dir.join(name)) {
+            hasher.update(&contents);
+            found = true;
+        }
+        hasher.update([0]);
+    }
+    found.then(|| {
+        let hash = hasher.finalize();
+        hash[..6].iter().map(|b| format!("{:02x}", b)).collect()
+    })
+}
+
+/// The newest modification time of any file under `dir`.
+fn newest_modification(dir: &Path) -> Option<SystemTime> {
+    let mut newest = None;
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let modified = if meta.is_dir() {
+            newest_modification(&entry.path())
+        } else {
+            meta.modified().ok()
+        };
+        newest = newest.max(modified);
+    }
+    newest
+}
+
+/// Total size of the files under `path`, not following symlinks.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| dir_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// `bytes` in B, KiB, MiB or GiB.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::GlobalConfig;
+use crate::error::{HookwiseError, Result};
+use crate::identity;
+use crate::storage::remote::OrgStore;
+use crate::sync::{self, RuleChange, SyncReport};
+
+/// Pull the org rules from the configured repository, rule server or org
+/// store, or with `push` publish local changes to the repository or store.
+pub async fn run(push: bool) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let global = GlobalConfig::load()?;
+    let identity = identity::current(&cwd);
+    let rules_root = crate::config::dirs_rules();
+
+    let (org_rules, org_store) = global
+        .map(|c| (c.org_rules, c.org_store))
+        .unwrap_or_default();
+    let config = match (org_rules, org_store) {
+        (Some(config), None) => config,
+        (None, Some(config)) => {
+            let org = org_name(None, identity.org.as_deref(), &cwd);
+            let store = OrgStore::new(config, &rules_root, &org);
+            if push {
+                match store.publish(&identity).await? {
+                    0 => println!("org {}: nothing to publish", org),
+                    n => println!("org {}: published {} file(s) to the org store", org, n),
+                }
+            } else {
+                print_report(&store.refresh().await?, "synced");
+            }
+            return Ok(());
+        }
+        (Some(_), Some(_)) => {
+            return Err(HookwiseError::InvalidPolicy {
+                reason: "the global config sets both org_rules and org_store; keep one".into(),
+            })
+        }
+        (None, None) => {
+            return Err(HookwiseError::InvalidPolicy {
+                reason: "no org_rules or org_store in the global config (config.yml)".into(),
+            })
+        }
+    };
+    let org = org_name(config.org.as_deref(), identity.org.as_deref(), &cwd);
+
+    if push {
+        match sync::push(&rules_root, &org, &config, &identity)? {
+            Some(report) => print_report(&report, "published"),
+            None => println!("org {}: nothing to publish", org),
+        }
+    } else {
+        print_report(&sync::pull(&rules_root, &org, &config).await?, "synced");
+    }
+    Ok(())
+}
+
+/// The org directory to sync: configured, else the identity's org, else
+/// the org of the project's `origin` remote, as sessions resolve it.
+fn org_name(configured: Option<&str>, identity_org: Option<&str>, cwd: &Path) -> String {
+    configured
+        .or(identity_org)
+        .map(str::to_string)
+        .unwrap_or_else(|| crate::session::extract_git_org_project(&cwd.to_string_lossy()).0)
+}
+
+fn print_report(report: &SyncReport, verb: &str) {
+    let (added, changed, removed) = report.counts();
+    println!(
+        "org {}: {} {} added, {} changed, {} removed (at {})",
+        report.org, verb, added, changed, removed, report.version
+    );
+    for change in &report.changes {
+        let r = change.record();
+        let what = match change {
+            RuleChange::Added(_) => format!("+ {}", r.decision),
+            RuleChange::Changed { from, to } => format!("~ {} -> {}", from.decision, to.decision),
+            RuleChange::Removed(_) => format!("- {}", r.decision),
+        };
+        println!(
+            "  {} {} {} (role {})",
+            what, r.key.tool, r.key.sanitized_input, r.key.role
+        );
+    }
+    if report.skipped > 0 {
+        println!(
+            "  skipped {} record(s) from a newer hookwise; upgrade hookwise to read them",
+            report.skipped
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::PolicyConfig;
+use crate::decision::DecisionRecord;
+use crate::error::{HookwiseError, Result};
+use crate::identity;
+use crate::scope::ScopeLevel;
+use crate::storage::bundle::{self, ConflictStrategy};
+use crate::storage::jsonl::JsonlStorage;
+use crate::storage::StorageBackend;
+
+fn parse_scope(scope: &str) -> Result<ScopeLevel> {
+    // Role rules live in the project files
+    match scope
+        .parse::<ScopeLevel>()
+        .map_err(|e| HookwiseError::InvalidPolicy { reason: e })?
+    {
+        ScopeLevel::Role => Ok(ScopeLevel::Project),
+        scope => Ok(scope),
+    }
+}
+
+/// Write a scope's decisions to a bundle.
+pub async fn run_export(scope: &str, output: &Path) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let scope = parse_scope(scope)?;
+    let policy = PolicyConfig::load_project(&cwd)?;
+    let storage = JsonlStorage::new(cwd.join(".hookwise"), crate::config::dirs_rules(), None)
+        .with_expiry(policy.cache.clone());
+
+    let records = storage.load_decisions(scope)?;
+    let by = identity::current(&cwd).user;
+    let manifest = bundle::write_bundle(output, scope, &records, &by)?;
+    eprintln!(
+        "hookwise: exported {} {} decision(s) to {}",
+        manifest.count,
+        scope,
+        output.display()
+    );
+    for (client, count) in &manifest.clients {
+        eprintln!("  {}: {}", client, count);
+    }
+    Ok(())
+}
+
+/// Merge a bundle's decisions into a scope, settling conflicts with
+/// `strategy`, and rebuild the indexes if anything changed.
+pub async fn run_import(
+    bundle_path: &Path,
+    scope: Option<&str>,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+) -> Result<()> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let project_root = cwd.join(".hookwise");
+    let policy = PolicyConfig::load_project(&cwd)?;
+    let storage = JsonlStorage::new(project_root.clone(), crate::config::dirs_rules(), None)
+        .with_expiry(policy.cache.clone());
+
+    let (manifest, incoming) = bundle::read_bundle(bundle_path)?;
+    let scope = match scope {
+        Some(scope) => parse_scope(scope)?,
+        None => manifest.scope,
+    };
+    let existing = storage.load_decisions(scope)?;
+
+    let plan = {
+        let mut input = std::io::stdin().lock();
+        bundle::plan_merge(
+            scope,
+            &existing,
+            &incoming,
+            |existing, incoming| match strategy.prefers_incoming(existing, incoming) {
+                Some(take) => Ok(take),
+                None => prompt(&mut input, existing, incoming),
+            },
+        )?
+    };
+
+    let verb = if dry_run { "would add" } else { "added" };
+    println!(
+        "{}: {} {} new decision(s) and replaced {} from {} ({} kept, {} already present)",
+        scope,
+        verb,
+        plan.new_keys(),
+        plan.replaced(),
+        manifest.created_by,
+        plan.conflicts.len() - plan.replaced(),
+        plan.duplicates
+    );
+    for conflict in &plan.conflicts {
+        let (winner, loser) = if conflict.took_incoming {
+            (&conflict.incoming, &conflict.existing)
+        } else {
+            (&conflict.existing, &conflict.incoming)
+        };
+        println!(
+            "  {} over {}: {} {} (role {})",
+            winner.decision,
+            loser.decision,
+            winner.key.tool,
+            winner.key.sanitized_input,
+            winner.key.role
+        );
+    }
+
+    if !dry_run && !plan.add.is_empty() {
+        storage.merge(scope, &plan)?;
+        super::build::rebuild_indexes(&policy, &storage, &project_root.join(".index")).await?;
+    }
+    Ok(())
+}
+
+/// Ask which side of a conflict to keep. Anything but `i` keeps the
+/// existing decision, as does end of input.
+fn prompt(
+    input: &mut impl BufRead,
+    existing: &DecisionRecord,
+    incoming: &DecisionRecord,
+) -> Result<bool> {
+    let describe = |r: &DecisionRecord| {
+        let decision = r.decision.to_string();
+        format!(
+            "{:<5} {}  {}",
+            decision,
+            r.timestamp.format("%Y-%m-%d %H:%M UTC"),
+            r.metadata.reason
+        )
+    };
+    let mut stderr = std::io::stderr();
+    writeln!(
+        stderr,
+        "conflict: {} {} (role {})\n  here:     {}\n  incoming: {}",
+        existing.key.tool,
+        existing.key.sanitized_input,
+        existing.key.role,
+        describe(existing),
+        describe(incoming)
+    )?;
+    write!(stderr, "keep [h]ere or take [i]ncoming? [h] ")?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("i"))
+}
@@ -0,0 +1,91 @@
+//! Time and id sources. Production code reads the wall clock; tests and
+//! replay inject a [`FixedClock`] and [`SequentialIds`] so pending ids and
+//! record timestamps come out the same on every run.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    pub fn new(at: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(at),
+        }
+    }
+
+    /// Move the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += by;
+    }
+
+    /// Set the clock to `at`.
+    pub fn set(&self, at: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = at;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A source of pending decision ids, of the form `<role>-<tool>-<suffix>`.
+pub trait IdGenerator: Send + Sync {
+    /// The id for a decision queued at `now`.
+    fn next_id(&self, role: &str, tool: &str, now: DateTime<Utc>) -> String;
+}
+
+/// Suffixes are the queue time in milliseconds, the historical format.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimestampIds;
+
+impl IdGenerator for TimestampIds {
+    fn next_id(&self, role: &str, tool: &str, now: DateTime<Utc>) -> String {
+        format!("{}-{}-{}", role, tool, now.timestamp_millis())
+    }
+}
+
+/// Suffixes count up: `<role>-<tool>-1`, `-2`, ...
+#[derive(Debug, Default)]
+pub struct SequentialIds {
+    next: AtomicU64,
+}
+
+impl SequentialIds {
+    /// Ids counting up from `start + 1`.
+    pub fn new(start: u64) -> Self {
+        Self {
+            next: AtomicU64::new(start),
+        }
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self, role: &str, tool: &str, _now: DateTime<Utc>) -> String {
+        let n = self.next.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("{}-{}-{}", role, tool, n)
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::error::{HookwiseError, Result};
+
+/// Marker written into the legacy config directory by `hookwise migrate-dirs`.
+pub const MIGRATION_MARKER: &str = "MIGRATED";
+
+/// Global files this layout moves out of the config directory, with the
+/// base directory each one now lives in.
+const MIGRATED_ENTRIES: &[(&str, BaseDir)] = &[
+    ("user", BaseDir::State),
+    ("org", BaseDir::State),
+    ("update-check.json", BaseDir::Cache),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BaseDir {
+    State,
+    Cache,
+}
+
+/// XDG base directories for hookwise's global files.
+///
+/// - config: `config.yml` (`$XDG_CONFIG_HOME/hookwise`, default `~/.config/hookwise`)
+/// - state: user and org rules (`$XDG_STATE_HOME/hookwise`, default `~/.local/state/hookwise`)
+/// - cache: update check (`$XDG_CACHE_HOME/hookwise`, default `~/.cache/hookwise`)
+/// - runtime: queue, sessions (`$XDG_RUNTIME_DIR`, default `/tmp`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalDirs {
+    pub config: PathBuf,
+    pub state: PathBuf,
+    pub cache: PathBuf,
+    pub runtime: PathBuf,
+}
+
+impl GlobalDirs {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok();
+        Self::from_vars(
+            var("HOME").as_deref(),
+            var("XDG_CONFIG_HOME").as_deref(),
+            var("XDG_STATE_HOME").as_deref(),
+            var("XDG_CACHE_HOME").as_deref(),
+            var("XDG_RUNTIME_DIR").as_deref(),
+        )
+    }
+
+    /// Resolve from explicit variable values. Empty or relative XDG values
+    /// are ignored, as the spec requires.
+    pub fn from_vars(
+        home: Option<&str>,
+        config_home: Option<&str>,
+        state_home: Option<&str>,
+        cache_home: Option<&str>,
+        runtime_dir: Option<&str>,
+    ) -> Self {
+        let home = PathBuf::from(home.unwrap_or("/tmp"));
+        let base = |value: Option<&str>, default: &[&str]| {
+            value
+                .map(PathBuf::from)
+                .filter(|p| p.is_absolute())
+                .unwrap_or_else(|| default.iter().fold(home.clone(), |p, c| p.join(c)))
+                .join("hookwise")
+        };
+        Self {
+            config: base(config_home, &[".config"]),
+            state: base(state_home, &[".local", "state"]),
+            cache: base(cache_home, &[".cache"]),
+            runtime: runtime_dir
+                .map(PathBuf::from)
+                .filter(|p| p.is_absolute())
+                .unwrap_or_else(|| PathBuf::from("/tmp")),
+        }
+    }
+
+    /// Root for user and org rules. Rules still sitting in the config
+    /// directory (not yet migrated) keep being used from there.
+    pub fn rules_root(&self) -> PathBuf {
+        let legacy = ["user", "org"].iter().any(|name| {
+            std::fs::symlink_metadata(self.config.join(name))
+                .map(|m| m.is_dir())
+                .unwrap_or(false)
+        });
+        if legacy {
+            self.config.clone()
+        } else {
+            self.state.clone()
+        }
+    }
+
+    fn base(&self, dir: BaseDir) -> &Path {
+        match dir {
+            BaseDir::State => &self.state,
+            BaseDir::Cache => &self.cache,
+        }
+    }
+
+    /// Files in the config directory that belong in another base directory.
+    /// Symlinks (left by a previous migration) are skipped.
+    pub fn plan_migration(&self) -> Vec<DirMove> {
+        MIGRATED_ENTRIES
+            .iter()
+            .filter_map(|(name, dir)| {
+                let from = self.config.join(name);
+                let meta = std::fs::symlink_metadata(&from).ok()?;
+                if meta.file_type().is_symlink() {
+                    return None;
+                }
+                Some(DirMove {
+                    from,
+                    to: self.base(*dir).join(name),
+                })
+            })
+            .collect()
+    }
+
+    /// Apply [`GlobalDirs::plan_migration`]: move each entry, leave a symlink
+    /// at the old path for older binaries, and write a marker file listing
+    /// the new locations.
+    pub fn migrate(&self) -> Result<Vec<DirMove>> {
+        let moves = self.plan_migration();
+        for m in &moves {
+            if m.to.exists() {
+                return Err(HookwiseError::Storage {
+                    reason: format!(
+                        "cannot migrate {}: {} already exists",
+                        m.from.display(),
+                        m.to.display()
+                    ),
+                });
+            }
+        }
+        for m in &moves {
+            if let Some(parent) = m.to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            move_path(&m.from, &m.to)?;
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&m.to, &m.from)?;
+        }
+        if !moves.is_empty() {
+            let marker = MigrationMarker {
+                migrated_at: chrono::Utc::now(),
+                state_dir: self.state.clone(),
+                cache_dir: self.cache.clone(),
+                moved: moves.clone(),
+            };
+            std::fs::write(
+                self.config.join(MIGRATION_MARKER),
+                serde_json::to_string_pretty(&marker)?,
+            )?;
+        }
+        Ok(moves)
+    }
+}
+
+/// One planned move of a legacy global file or directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DirMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+#[derive(Serialize)]
+struct MigrationMarker {
+    migrated_at: chrono::DateTime<chrono::Utc>,
+    state_dir: PathBuf,
+    cache_dir: PathBuf,
+    moved: Vec<DirMove>,
+}
+
+/// Rename, falling back to copy + remove across filesystems.
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to)?;
+    if from.is_dir() {
+        std::fs::remove_dir_all(from)?;
+    } else {
+        std::fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        std::fs::copy(from, to)?;
+    }
+    Ok(())
+}
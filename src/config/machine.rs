@@ -0,0 +1,240 @@
+//! Machine-level policy for managed laptops: an admin-owned `policy.yml`
+//! (`/etc/hookwise/policy.yml`) layered over every project's policy.
+//!
+//! Settings the machine policy sets win over the project's. Mappings merge
+//! key by key, so the machine can pin `deadline.secs` without touching
+//! `deadline.near`. Lists that only ever add restrictions (see
+//! [`UNION_LISTS`]) are unioned instead, machine entries first so they also
+//! win under first-match rules.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::Value;
+
+use super::PolicyConfig;
+use crate::error::{HookwiseError, Result};
+
+/// Lists the project may add to but not shorten.
+pub const UNION_LISTS: &[&str] = &[
+    "sensitive_paths.ask_write",
+    "sanitize.prefixes",
+    "sanitize.patterns",
+    "sanitize.literals",
+    "cloud",
+    "git.protected_branches",
+    "git.rules",
+    "content.rules",
+    "exfiltration.sensitive",
+];
+
+/// Mappings shown as one setting by `config --show-origin`; their keys are
+/// path patterns rather than setting names.
+const PATTERN_MAPS: &[&str] = &["max_auto_tier"];
+
+/// Where a policy setting came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyOrigin {
+    Project,
+    Machine,
+    /// Set by both and overridden by the machine policy.
+    MachineOverride,
+    /// A union (or per-key merge) of both.
+    Merged,
+}
+
+impl std::fmt::Display for PolicyOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Project => "project",
+            Self::Machine => "machine",
+            Self::MachineOverride => "machine (overrides project)",
+            Self::Merged => "machine + project",
+        })
+    }
+}
+
+/// The machine policy file. Debug builds honor `HOOKWISE_MACHINE_POLICY`
+/// so tests can point at a fixture; release builds only read the system
+/// path, which users cannot redirect.
+pub fn machine_policy_path() -> PathBuf {
+    #[cfg(debug_assertions)]
+    if let Some(path) = std::env::var_os("HOOKWISE_MACHINE_POLICY") {
+        return PathBuf::from(path);
+    }
+    if cfg!(windows) {
+        PathBuf::from(r"C:\ProgramData\hookwise\policy.yml")
+    } else {
+        PathBuf::from("/etc/hookwise/policy.yml")
+    }
+}
+
+/// A project policy with the machine policy applied, and where each
+/// explicitly set value came from.
+#[derive(Debug, Clone)]
+pub struct LayeredPolicy {
+    pub policy: PolicyConfig,
+    /// The machine policy file, when one was applied.
+    pub machine_path: Option<PathBuf>,
+    /// Dotted setting paths set by either file. Anything missing is a
+    /// default.
+    pub origins: BTreeMap<String, PolicyOrigin>,
+}
+
+impl LayeredPolicy {
+    /// Load `project_path` and layer `machine_path` over it, if that exists.
+    pub fn load(project_path: &Path, machine_path: &Path) -> Result<Self> {
+        let project_raw = read_yaml(project_path)?;
+        let project: PolicyConfig = match &project_raw {
+            Some(value) => parse(project_path, value.clone())?,
+            None => PolicyConfig::default(),
+        };
+        let Some(machine_raw) = read_yaml(machine_path)? else {
+            let mut origins = BTreeMap::new();
+            if let Some(value) = &project_raw {
+                for path in leaves(value) {
+                    origins.insert(path, PolicyOrigin::Project);
+                }
+            }
+            return Ok(Self {
+                policy: project,
+                machine_path: None,
+                origins,
+            });
+        };
+        warn_unless_admin_owned(machine_path);
+
+        // Start from the project policy with its defaults filled in, so
+        // unions include default entries the project never listed
+        let mut merged =
+            serde_yaml::to_value(&project).map_err(|e| HookwiseError::ConfigParse {
+                path: project_path.to_path_buf(),
+                reason: e.to_string(),
+            })?;
+        overlay(&mut merged, &machine_raw, "");
+        let policy = parse(machine_path, merged)?;
+
+        let project_leaves = project_raw.as_ref().map(leaves).unwrap_or_default();
+        let machine_leaves = leaves(&machine_raw);
+        let mut origins = BTreeMap::new();
+        for path in &project_leaves {
+            origins.insert(path.clone(), PolicyOrigin::Project);
+        }
+        for path in machine_leaves {
+            let origin = if !project_leaves.contains(&path) {
+                PolicyOrigin::Machine
+            } else if UNION_LISTS.contains(&path.as_str()) || PATTERN_MAPS.contains(&path.as_str())
+            {
+                PolicyOrigin::Merged
+            } else {
+                PolicyOrigin::MachineOverride
+            };
+            origins.insert(path, origin);
+        }
+        Ok(Self {
+            policy,
+            machine_path: Some(machine_path.to_path_buf()),
+            origins,
+        })
+    }
+}
+
+fn read_yaml(path: &Path) -> Result<Option<Value>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(path)?;
+    let value: Value = serde_yaml::from_str(&contents).map_err(|e| HookwiseError::ConfigParse {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+    // An empty file parses as null: nothing set
+    Ok(Some(match value {
+        Value::Null => Value::Mapping(Default::default()),
+        value => value,
+    }))
+}
+
+fn parse(path: &Path, value: Value) -> Result<PolicyConfig> {
+    serde_yaml::from_value(value).map_err(|e| HookwiseError::ConfigParse {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })
+}
+
+/// Apply `machine` over `base` at dotted `path`.
+fn overlay(base: &mut Value, machine: &Value, path: &str) {
+    match (base, machine) {
+        (Value::Mapping(base), Value::Mapping(machine)) => {
+            for (key, value) in machine {
+                let child = join(path, key);
+                match base.get_mut(key) {
+                    Some(existing) => overlay(existing, value, &child),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base), Value::Sequence(machine)) if UNION_LISTS.contains(&path) => {
+            let mut merged = machine.clone();
+            for item in base.drain(..) {
+                if !merged.contains(&item) {
+                    merged.push(item);
+                }
+            }
+            *base = merged;
+        }
+        (base, machine) => *base = machine.clone(),
+    }
+}
+
+/// Dotted paths of the values set in a policy document.
+fn leaves(value: &Value) -> Vec<String> {
+    fn walk(value: &Value, path: String, out: &mut Vec<String>) {
+        match value {
+            Value::Mapping(map) if !PATTERN_MAPS.contains(&path.as_str()) => {
+                for (key, value) in map {
+                    walk(value, join(&path, key), out);
+                }
+            }
+            _ if !path.is_empty() => out.push(path),
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk(value, String::new(), &mut out);
+    out
+}
+
+fn join(path: &str, key: &Value) -> String {
+    let key = match key {
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    };
+    if path.is_empty() {
+        key
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// The machine policy is only a constraint if users can't edit it.
+#[cfg(unix)]
+fn warn_unless_admin_owned(path: &Path) {
+    use std::os::unix::fs::MetadataExt;
+    if let Ok(meta) = std::fs::metadata(path) {
+        if meta.uid() != 0 || meta.mode() & 0o022 != 0 {
+            tracing::warn!(
+                "machine policy {} should be owned by root and writable only by it",
+                path.display()
+            );
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_unless_admin_owned(_path: &Path) {}
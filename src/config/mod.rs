@@ -1,13 +1,45 @@
+pub mod dirs;
+pub mod machine;
 pub mod policy;
 pub mod roles;
+pub mod scan_rules;
 
+pub use dirs::GlobalDirs;
+pub use machine::{machine_policy_path, LayeredPolicy, PolicyOrigin};
 pub use policy::*;
 pub use roles::*;
+pub use scan_rules::*;
 
 use std::path::PathBuf;
 
-/// Returns the global config directory path: `~/.config/hookwise/`
+use crate::error::{HookwiseError, Result};
+
+/// Serialize a config as YAML for the `to_yaml` methods.
+pub(crate) fn yaml_string<T: serde::Serialize>(value: &T, what: &str) -> Result<String> {
+    serde_yaml::to_string(value).map_err(|e| HookwiseError::InvalidPolicy {
+        reason: format!("failed to serialize {}: {}", what, e),
+    })
+}
+
+/// Returns the global config directory path: `$XDG_CONFIG_HOME/hookwise/`
+/// (default `~/.config/hookwise/`)
 pub fn dirs_global() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
-    PathBuf::from(home).join(".config").join("hookwise")
+    GlobalDirs::from_env().config
+}
+
+/// Returns the root for user and org rules: `$XDG_STATE_HOME/hookwise/`, or
+/// the config directory while rules there have not been migrated.
+pub fn dirs_rules() -> PathBuf {
+    GlobalDirs::from_env().rules_root()
+}
+
+/// Returns the global cache directory: `$XDG_CACHE_HOME/hookwise/`
+pub fn dirs_cache() -> PathBuf {
+    GlobalDirs::from_env().cache
+}
+
+/// Returns the runtime directory for queue and session files:
+/// `$XDG_RUNTIME_DIR`, falling back to `/tmp`.
+pub fn dirs_runtime() -> PathBuf {
+    GlobalDirs::from_env().runtime
 }
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+use crate::config::RolesConfig;
+use crate::decision::{Decision, DecisionTier};
 use crate::error::{HookwiseError, Result};
+use crate::scope::ScopeLevel;
 
 /// Top-level project policy configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +14,80 @@ pub struct PolicyConfig {
     #[serde(default)]
     pub sensitive_paths: SensitivePathConfig,
 
+    /// Scratch and build output directories whose file tool calls are
+    /// allowed before any other tier runs.
+    #[serde(default)]
+    pub ephemeral_paths: EphemeralPathConfig,
+
+    /// Which wins when a write path matches both `deny_write` and a
+    /// sensitive pattern. Default: sensitive_first (ask).
+    #[serde(default)]
+    pub path_precedence: PathPrecedence,
+
+    /// Highest-autonomy tier that may decide calls touching matching paths,
+    /// keyed by glob or `{{category}}`. Tiers above it are skipped.
+    #[serde(default)]
+    pub max_auto_tier: BTreeMap<String, AutoTier>,
+
+    /// Custom deny/ask messages for calls touching matching paths. The
+    /// first matching rule wins.
+    #[serde(default)]
+    pub messages: Vec<MessageRule>,
+
+    /// Remediation hints added to deny/ask reasons, e.g. which role may
+    /// write a path the session's role can't.
+    #[serde(default)]
+    pub hints: HintsConfig,
+
+    /// Decisions for risky `docker`/`podman` invocations.
+    #[serde(default)]
+    pub containers: ContainerPolicy,
+
+    /// Gating of package installs (`npm install`, `pip install`, ...).
+    #[serde(default)]
+    pub packages: PackageGateConfig,
+
+    /// Decisions for `kubectl`/`aws`/`gcloud`/`az` reads, mutations and
+    /// deletions, per role and namespace/profile. For each command the
+    /// first matching rule that sets its action wins.
+    #[serde(default)]
+    pub cloud: Vec<CloudRule>,
+
+    /// Decisions for risky git operations (force-push, pushes to protected
+    /// branches, config and hook changes, tag deletion, submodule URLs),
+    /// per role.
+    #[serde(default)]
+    pub git: GitPolicyConfig,
+
+    /// Checks on what `Write`/`Edit` calls put into files: added secrets
+    /// and per-path content patterns.
+    #[serde(default)]
+    pub content: ContentConfig,
+
+    /// Asks about network-bound commands run after the session read a
+    /// sensitive file.
+    #[serde(default)]
+    pub exfiltration: ExfiltrationConfig,
+
+    /// Per-session write locks on path ranges, so concurrent sessions
+    /// don't edit the same files.
+    #[serde(default)]
+    pub locks: LockConfig,
+
+    /// Rolling per-session risk score, and how sessions over the threshold
+    /// are tightened.
+    #[serde(default)]
+    pub risk: RiskConfig,
+
+    /// Weights of the built-in per-call command risk score, and the scores
+    /// that raise queue priority.
+    #[serde(default)]
+    pub command_risk: CommandRiskConfig,
+
+    /// Redaction rules added to the built-in sanitizer.
+    #[serde(default)]
+    pub sanitize: SanitizeConfig,
+
     /// Confidence thresholds per scope level.
     #[serde(default)]
     pub confidence: ConfidenceConfig,
@@ -18,6 +96,26 @@ pub struct PolicyConfig {
     #[serde(default)]
     pub similarity: SimilarityConfig,
 
+    /// Expiry of cached decisions per scope.
+    #[serde(default)]
+    pub cache: CacheConfig,
+
+    /// How long stored decision records are kept per scope.
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    /// Whether new decisions are written to the rule files. Default: persistent.
+    #[serde(default)]
+    pub persistence: Persistence,
+
+    /// Append-only decision audit log.
+    #[serde(default)]
+    pub audit: AuditConfig,
+
+    /// What to do when rule storage or the queue file is read-only.
+    #[serde(default)]
+    pub read_only: ReadOnlyPolicy,
+
     /// Human decision timeout in seconds. Default: 60.
     #[serde(default = "default_human_timeout")]
     pub human_timeout_secs: u64,
@@ -26,51 +124,358 @@ pub struct PolicyConfig {
     #[serde(default = "default_registration_timeout")]
     pub registration_timeout_secs: u64,
 
+    /// Hard limit on one cascade evaluation, so the hook answers before the
+    /// assistant's own hook timeout kills it. Off by default.
+    #[serde(default)]
+    pub deadline: DeadlineConfig,
+
+    /// How long a pending decision or unclaimed response may sit in the
+    /// queue file before `hookwise prune` expires it. Default: 3600.
+    #[serde(default = "default_queue_ttl")]
+    pub queue_ttl_secs: u64,
+
+    /// How pending decisions are resolved once the `check` process waiting
+    /// on them has exited. Default: deny.
+    #[serde(default)]
+    pub orphaned_queue: OrphanPolicy,
+
     /// Supervisor backend configuration.
     #[serde(default)]
     pub supervisor: SupervisorConfig,
+
+    /// Opt-in log of raw supervisor prompts and replies.
+    #[serde(default)]
+    pub supervisor_transcript: SupervisorTranscriptConfig,
+
+    /// Past human decisions included in supervisor prompts as precedents.
+    #[serde(default)]
+    pub supervisor_examples: SupervisorExamplesConfig,
+
+    /// Response-time expectations for the human queue.
+    #[serde(default)]
+    pub human_sla: HumanSlaConfig,
+
+    /// Push notifications for pending human decisions.
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+
+    /// Escalation chain for unanswered human decisions.
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+
+    /// File excerpts attached to pending Write/Edit decisions.
+    #[serde(default)]
+    pub attachments: AttachmentConfig,
+
+    /// Asynchronous approval via commit trailers (`hookwise reconcile`).
+    #[serde(default)]
+    pub git_approval: GitApprovalConfig,
+
+    /// Signed queue bundles carried across an air gap (`hookwise queue
+    /// export`/`respond`/`import-responses`).
+    #[serde(default)]
+    pub offline_approval: OfflineApprovalConfig,
+
+    /// Who may answer pending decisions (`hookwise approve`/`deny`, the
+    /// queue TUI, MCP), by the identity the global provider resolves.
+    #[serde(default)]
+    pub approvals: ApprovalConfig,
+
+    /// Two-person rule for `hookwise disable` in protected projects.
+    #[serde(default)]
+    pub session_disable: SessionDisableConfig,
+
+    /// Mode for sessions registered without `--mode`.
+    #[serde(default)]
+    pub session_mode: SessionMode,
+
+    /// Scope for rules added by approve/deny without `--scope`, unless the
+    /// pending decision's role sets its own `rule_scope` in roles.yml.
+    #[serde(default = "default_rule_scope")]
+    pub rule_scope: ScopeLevel,
 }
 
+fn default_rule_scope() -> ScopeLevel {
+    ScopeLevel::Project
+}
 fn default_human_timeout() -> u64 {
     60
 }
 fn default_registration_timeout() -> u64 {
     5
 }
+fn default_queue_ttl() -> u64 {
+    3600
+}
 
 impl Default for PolicyConfig {
     fn default() -> Self {
         Self {
             sensitive_paths: SensitivePathConfig::default(),
+            ephemeral_paths: EphemeralPathConfig::default(),
+            path_precedence: PathPrecedence::default(),
+            max_auto_tier: BTreeMap::new(),
+            messages: Vec::new(),
+            hints: HintsConfig::default(),
+            containers: ContainerPolicy::default(),
+            packages: PackageGateConfig::default(),
+            cloud: Vec::new(),
+            git: GitPolicyConfig::default(),
+            content: ContentConfig::default(),
+            exfiltration: ExfiltrationConfig::default(),
+            locks: LockConfig::default(),
+            risk: RiskConfig::default(),
+            command_risk: CommandRiskConfig::default(),
+            sanitize: SanitizeConfig::default(),
             confidence: ConfidenceConfig::default(),
             similarity: SimilarityConfig::default(),
+            cache: CacheConfig::default(),
+            storage: StorageConfig::default(),
+            persistence: Persistence::default(),
+            audit: AuditConfig::default(),
+            read_only: ReadOnlyPolicy::default(),
             human_timeout_secs: 60,
             registration_timeout_secs: 5,
+            deadline: DeadlineConfig::default(),
+            queue_ttl_secs: default_queue_ttl(),
+            orphaned_queue: OrphanPolicy::default(),
             supervisor: SupervisorConfig::default(),
+            supervisor_transcript: SupervisorTranscriptConfig::default(),
+            supervisor_examples: SupervisorExamplesConfig::default(),
+            human_sla: HumanSlaConfig::default(),
+            notifications: NotificationConfig::default(),
+            escalation: EscalationConfig::default(),
+            attachments: AttachmentConfig::default(),
+            git_approval: GitApprovalConfig::default(),
+            offline_approval: OfflineApprovalConfig::default(),
+            approvals: ApprovalConfig::default(),
+            session_disable: SessionDisableConfig::default(),
+            session_mode: SessionMode::default(),
+            rule_scope: default_rule_scope(),
         }
     }
 }
 
 impl PolicyConfig {
+    /// Queue TTL actually applied: never shorter than the time a `check`
+    /// process may legitimately wait for an answer, so live waiters are not
+    /// pruned out from under themselves.
+    pub fn effective_queue_ttl_secs(&self) -> u64 {
+        let wait = self
+            .escalation
+            .total_timeout_secs()
+            .unwrap_or(self.human_timeout_secs);
+        self.queue_ttl_secs.max(wait)
+    }
+
+    /// SLA thresholds actually applied, with unset ones derived from the
+    /// time a `check` process waits for an answer.
+    pub fn effective_human_sla(&self) -> HumanSlaConfig {
+        let wait = self
+            .escalation
+            .total_timeout_secs()
+            .unwrap_or(self.human_timeout_secs);
+        self.human_sla.resolved(wait)
+    }
+
+    /// Scope for a rule added from a decision by `role` when none is given:
+    /// the role's `rule_scope`, falling back to the policy's.
+    pub fn rule_scope_for(&self, roles: &RolesConfig, role: &str) -> ScopeLevel {
+        roles
+            .get_role(role)
+            .and_then(|role| role.rule_scope)
+            .unwrap_or(self.rule_scope)
+    }
+
     /// Load policy from a YAML file. Returns default if file doesn't exist.
     pub fn load_from(path: &Path) -> Result<Self> {
         if !path.exists() {
             return Ok(Self::default());
         }
         let contents = std::fs::read_to_string(path)?;
-        serde_yaml::from_str(&contents).map_err(|e| HookwiseError::ConfigParse {
+        Self::parse(&contents, path)
+    }
+
+    /// Parse `policy.yml` contents. Missing settings take their defaults.
+    pub fn from_yaml(contents: &str) -> Result<Self> {
+        Self::parse(contents, Path::new("policy.yml"))
+    }
+
+    fn parse(contents: &str, path: &Path) -> Result<Self> {
+        serde_yaml::from_str(contents).map_err(|e| HookwiseError::ConfigParse {
             path: path.to_path_buf(),
             reason: e.to_string(),
         })
     }
 
-    /// Load policy from the project root. Checks `.hookwise/policy.yml`.
+    /// The policy as `policy.yml` contents, every setting spelled out.
+    /// `{{category}}` macros in path settings are kept as written; they are
+    /// only expanded when the policy is applied.
+    pub fn to_yaml(&self) -> Result<String> {
+        super::yaml_string(self, "policy")
+    }
+
+    /// Replace the sensitive write patterns.
+    pub fn with_sensitive_paths<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.sensitive_paths.ask_write = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Cap the tier that may decide calls touching `pattern`.
+    pub fn with_max_auto_tier(mut self, pattern: impl Into<String>, tier: AutoTier) -> Self {
+        self.max_auto_tier.insert(pattern.into(), tier);
+        self
+    }
+
+    /// Add a custom message rule after the existing ones.
+    pub fn with_message(mut self, rule: MessageRule) -> Self {
+        self.messages.push(rule);
+        self
+    }
+
+    /// Use `supervisor` as the supervisor backend.
+    pub fn with_supervisor(mut self, supervisor: SupervisorConfig) -> Self {
+        self.supervisor = supervisor;
+        self
+    }
+
+    /// Replace the per-scope confidence thresholds.
+    pub fn with_confidence(mut self, confidence: ConfidenceConfig) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// Replace the Jaccard and embedding thresholds.
+    pub fn with_similarity(mut self, similarity: SimilarityConfig) -> Self {
+        self.similarity = similarity;
+        self
+    }
+
+    /// Replace the cache expiry settings.
+    pub fn with_cache(mut self, cache: CacheConfig) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Replace the per-scope record retention.
+    pub fn with_storage(mut self, storage: StorageConfig) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Set whether new decisions are written to the rule files.
+    pub fn with_persistence(mut self, persistence: Persistence) -> Self {
+        self.persistence = persistence;
+        self
+    }
+
+    /// Set how long `check` waits for a human decision.
+    pub fn with_human_timeout_secs(mut self, secs: u64) -> Self {
+        self.human_timeout_secs = secs;
+        self
+    }
+
+    /// Set the mode for sessions registered without `--mode`.
+    pub fn with_session_mode(mut self, mode: SessionMode) -> Self {
+        self.session_mode = mode;
+        self
+    }
+
+    /// Set the scope for rules added without `--scope`.
+    pub fn with_rule_scope(mut self, scope: ScopeLevel) -> Self {
+        self.rule_scope = scope;
+        self
+    }
+
+    /// Load policy from the project root. Checks `.hookwise/policy.yml`,
+    /// with the machine policy (see [`super::machine`]) layered over it.
     pub fn load_project(project_root: &Path) -> Result<Self> {
+        Ok(Self::load_layered(project_root)?.policy)
+    }
+
+    /// [`Self::load_project`], keeping where each setting came from.
+    pub fn load_layered(project_root: &Path) -> Result<super::LayeredPolicy> {
         let path = project_root.join(".hookwise").join("policy.yml");
-        Self::load_from(&path)
+        super::LayeredPolicy::load(&path, &super::machine_policy_path())
+    }
+}
+
+/// A policy author's message for deny/ask decisions on matching paths.
+/// Templates may use `{{path}}`, `{{tool}}`, `{{role}}`, `{{decision}}`
+/// and `{{reason}}` (hookwise's own reason).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageRule {
+    /// Globs or `{{category}}` macros.
+    pub paths: Vec<String>,
+    /// Template for both deny and ask, unless overridden below.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deny: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ask: Option<String>,
+}
+
+impl MessageRule {
+    /// The template for `decision`, if any. Allows have no message.
+    pub fn template(&self, decision: Decision) -> Option<&str> {
+        let specific = match decision {
+            Decision::Deny => &self.deny,
+            Decision::Ask => &self.ask,
+            Decision::Allow => return None,
+        };
+        specific.as_deref().or(self.message.as_deref())
+    }
+}
+
+/// The `hints` table: remediation hints for denied or asked calls. Project
+/// rules are tried before the built-in ones, and the first match wins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HintsConfig {
+    /// Include the built-in hints naming the role that may write tests,
+    /// source, docs, CI and infra paths. Default: true.
+    #[serde(default = "default_builtin_hints")]
+    pub builtin: bool,
+    #[serde(default)]
+    pub rules: Vec<HintRule>,
+}
+
+fn default_builtin_hints() -> bool {
+    true
+}
+
+impl Default for HintsConfig {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            rules: Vec::new(),
+        }
     }
 }
 
+/// A remediation hint for calls matching every filter given. The hint is
+/// a template with the same variables as `messages`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HintRule {
+    /// Globs or `{{category}}` macros. Empty: any call.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Roles the hint is for. Empty: every role.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Tools the hint is for. Empty: every tool.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    /// Only for this decision. Default: both deny and ask.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<Decision>,
+    pub hint: String,
+}
+
 /// Sensitive path configuration -- paths that default to `ask`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SensitivePathConfig {
@@ -95,6 +500,51 @@ impl Default for SensitivePathConfig {
     }
 }
 
+/// Paths agents churn through (scratch dirs, build output, tool caches).
+/// File tool calls (Read, Write, Edit, Glob, ...) whose every target
+/// matches `paths`, relative to the project root, are allowed up front and
+/// logged under the `ephemeral_path` tier. Bash commands and paths that are
+/// also sensitive always go through the cascade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EphemeralPathConfig {
+    /// Default: true.
+    #[serde(default = "default_ephemeral_enabled")]
+    pub enabled: bool,
+    /// Globs or `{{category}}` macros. Default: `.tmp/`, `tmp/`, `target/`,
+    /// `node_modules/.cache/` and Python tool caches.
+    #[serde(default = "default_ephemeral_paths")]
+    pub paths: Vec<String>,
+}
+
+fn default_ephemeral_enabled() -> bool {
+    true
+}
+
+fn default_ephemeral_paths() -> Vec<String> {
+    [
+        ".tmp/**",
+        "tmp/**",
+        "target/**",
+        "**/node_modules/.cache/**",
+        "**/__pycache__/**",
+        ".pytest_cache/**",
+        ".mypy_cache/**",
+        ".ruff_cache/**",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl Default for EphemeralPathConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_ephemeral_enabled(),
+            paths: default_ephemeral_paths(),
+        }
+    }
+}
+
 /// Confidence thresholds per scope.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfidenceConfig {
@@ -119,6 +569,21 @@ pub struct SimilarityConfig {
     pub jaccard_threshold: f64,
     pub embedding_threshold: f64,
     pub jaccard_min_tokens: usize,
+    /// How Tier 2a scores token overlap. Default: jaccard.
+    #[serde(default)]
+    pub token_mode: TokenMode,
+    /// How far the best allow/ask match must score above the closest cached
+    /// deny before a similarity tier resolves it. Default: 0.1.
+    #[serde(default = "default_deny_margin")]
+    pub deny_margin: f64,
+    /// What a similarity tier does when a deny is within `deny_margin`.
+    /// Default: fallthrough.
+    #[serde(default)]
+    pub on_deny_conflict: DenyConflict,
+}
+
+fn default_deny_margin() -> f64 {
+    0.1
 }
 
 impl Default for SimilarityConfig {
@@ -127,52 +592,1685 @@ impl Default for SimilarityConfig {
             jaccard_threshold: 0.7,
             embedding_threshold: 0.85,
             jaccard_min_tokens: 3,
+            token_mode: TokenMode::default(),
+            deny_margin: default_deny_margin(),
+            on_deny_conflict: DenyConflict::default(),
         }
     }
 }
 
-/// Supervisor backend configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "backend")]
-pub enum SupervisorConfig {
-    #[serde(rename = "socket")]
-    Socket { socket_path: Option<PathBuf> },
-    #[serde(rename = "api")]
-    Api {
-        api_base_url: Option<String>,
-        model: Option<String>,
-        max_tokens: Option<u32>,
-    },
+/// Similarity-tier behavior when an allow/ask match is too close to a deny.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DenyConflict {
+    /// Defer to the supervisor and human tiers.
+    #[default]
+    Fallthrough,
+    /// Return ask.
+    Ask,
 }
 
-impl Default for SupervisorConfig {
+/// Token similarity scoring for Tier 2a.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenMode {
+    /// Every token counts the same.
+    #[default]
+    Jaccard,
+    /// Tokens are weighted by inverse document frequency over the cached
+    /// decisions, so rare tokens outweigh ubiquitous ones like `cargo`.
+    Tfidf,
+}
+
+impl std::fmt::Display for TokenMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenMode::Jaccard => write!(f, "jaccard"),
+            TokenMode::Tfidf => write!(f, "tfidf"),
+        }
+    }
+}
+
+/// Where new decisions are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Persistence {
+    /// Decisions are appended to `.hookwise/rules/` and reused across runs.
+    #[default]
+    Persistent,
+    /// Existing rules are read, but new decisions live only in memory for
+    /// the process lifetime. For throwaway CI sandboxes.
+    Ephemeral,
+}
+
+/// Whether a session's decisions are enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionMode {
+    /// Decisions are returned to the agent.
+    #[default]
+    Enforce,
+    /// The cascade runs and each would-be decision is audited, but every
+    /// call is allowed. Nothing is cached or persisted, and calls that
+    /// would ask are not queued.
+    Audit,
+}
+
+/// Behavior when a storage location is read-only (e.g. review containers).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadOnlyPolicy {
+    /// Warn once and keep state in memory for the process lifetime.
+    #[default]
+    Degrade,
+    /// Fail the check (which then defaults to deny).
+    Fail,
+}
+
+/// Order of the path policy checks for a write path matching both a
+/// sensitive pattern and `deny_write`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathPrecedence {
+    /// Sensitive paths always prompt, even when a role denies them.
+    #[default]
+    SensitiveFirst,
+    /// `deny_write` is checked first, so a deny always wins.
+    DenyFirst,
+}
+
+impl std::fmt::Display for PathPrecedence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SensitiveFirst => write!(f, "sensitive_first"),
+            Self::DenyFirst => write!(f, "deny_first"),
+        }
+    }
+}
+
+/// An automatic cascade tier, in order of increasing autonomy. Used as a
+/// ceiling in `max_auto_tier`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoTier {
+    /// Only role path policy.
+    PathPolicy,
+    /// Path policy and exact rules: overrides, human decisions, cached
+    /// decisions that came from a permitted tier.
+    ExactCache,
+    TokenJaccard,
+    EmbeddingSimilarity,
+    Supervisor,
+}
+
+impl AutoTier {
+    /// Whether `tier` may decide under this ceiling. Humans always may.
+    pub fn permits(self, tier: DecisionTier) -> bool {
+        let auto = match tier {
+            DecisionTier::PathPolicy => Self::PathPolicy,
+            DecisionTier::ExactCache => Self::ExactCache,
+            DecisionTier::TokenJaccard => Self::TokenJaccard,
+            DecisionTier::EmbeddingSimilarity => Self::EmbeddingSimilarity,
+            DecisionTier::Supervisor => Self::Supervisor,
+            _ => return true,
+        };
+        auto <= self
+    }
+}
+
+/// Resolution of a pending decision whose waiting process has exited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrphanPolicy {
+    /// Store a deny rule so the same call is refused without re-asking.
+    #[default]
+    Deny,
+    /// Drop the entry; the call is asked again next time.
+    Discard,
+}
+
+impl std::fmt::Display for OrphanPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Deny => write!(f, "deny"),
+            Self::Discard => write!(f, "discard"),
+        }
+    }
+}
+
+/// Path policy for `docker`/`podman` commands. Writable bind mounts and
+/// `cp` destinations are checked as write targets like any other path;
+/// these settings decide the rest. `allow` leaves a call to the rest of
+/// the cascade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainerPolicy {
+    /// `run --privileged`. Default: ask.
+    #[serde(default = "default_ask")]
+    pub privileged: Decision,
+    /// Bind mounts of host paths outside the project. Default: ask.
+    #[serde(default = "default_ask")]
+    pub host_mounts: Decision,
+    /// `run` of an image without a digest or a tag other than `latest`.
+    /// Default: allow.
+    #[serde(default = "default_allow")]
+    pub unpinned_images: Decision,
+}
+
+impl Default for ContainerPolicy {
     fn default() -> Self {
-        Self::Socket { socket_path: None }
+        Self {
+            privileged: Decision::Ask,
+            host_mounts: Decision::Ask,
+            unpinned_images: Decision::Allow,
+        }
     }
 }
 
-/// Global hookwise configuration from `~/.config/hookwise/config.yml`.
+fn default_ask() -> Decision {
+    Decision::Ask
+}
+
+fn default_allow() -> Decision {
+    Decision::Allow
+}
+
+/// A package ecosystem whose installs are gated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    /// `npm install`, `yarn add`, `pnpm add`
+    Npm,
+    /// `pip install`, `python -m pip install`
+    Pip,
+    /// `cargo add`, `cargo install`
+    Cargo,
+    /// `gem install`
+    Gem,
+    /// `go get`, `go install`
+    Go,
+}
+
+impl std::fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Npm => write!(f, "npm"),
+            Self::Pip => write!(f, "pip"),
+            Self::Cargo => write!(f, "cargo"),
+            Self::Gem => write!(f, "gem"),
+            Self::Go => write!(f, "go"),
+        }
+    }
+}
+
+/// Package install gating. Installs from a registry not in
+/// `allowed_registries` are denied, packages not in `allowlist` ask, and
+/// packages OSV reports as malicious are denied. Installs that pass fall
+/// through to the rest of the cascade.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageGateConfig {
+    /// Registry hosts (`registry.npmjs.org`) or names (cargo `--registry`)
+    /// installs may use. Empty allows any.
+    #[serde(default)]
+    pub allowed_registries: Vec<String>,
+    /// Package name globs per ecosystem. When non-empty, any package not
+    /// listed for its ecosystem asks.
+    #[serde(default)]
+    pub allowlist: BTreeMap<Ecosystem, Vec<String>>,
+    /// Known-malicious package lookups against the OSV API.
+    #[serde(default)]
+    pub osv: OsvConfig,
+}
+
+/// OSV advisory lookups. Off by default since they need network access;
+/// lookup failures are logged and do not block the install.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GlobalConfig {
-    pub supervisor: SupervisorConfig,
-    pub api_key: Option<String>,
-    pub embedding_model: Option<String>,
+pub struct OsvConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_osv_url")]
+    pub url: String,
+    #[serde(default = "default_osv_timeout")]
+    pub timeout_secs: u64,
 }
 
-impl GlobalConfig {
-    /// Load global config. Returns None if not present.
-    pub fn load() -> Result<Option<Self>> {
-        let home = super::dirs_global();
-        let path = home.join("config.yml");
-        if !path.exists() {
-            return Ok(None);
+impl Default for OsvConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: default_osv_url(),
+            timeout_secs: default_osv_timeout(),
+        }
+    }
+}
+
+fn default_osv_url() -> String {
+    "https://api.osv.dev".into()
+}
+
+fn default_osv_timeout() -> u64 {
+    5
+}
+
+/// A cloud or cluster CLI whose actions `cloud` rules classify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloudCli {
+    Kubectl,
+    Aws,
+    Gcloud,
+    Az,
+}
+
+impl CloudCli {
+    /// What a scope means for this CLI.
+    pub fn scope_name(&self) -> &'static str {
+        match self {
+            Self::Kubectl => "namespace",
+            Self::Aws => "profile",
+            Self::Gcloud => "project",
+            Self::Az => "subscription",
+        }
+    }
+}
+
+impl std::fmt::Display for CloudCli {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Kubectl => write!(f, "kubectl"),
+            Self::Aws => write!(f, "aws"),
+            Self::Gcloud => write!(f, "gcloud"),
+            Self::Az => write!(f, "az"),
+        }
+    }
+}
+
+/// Decisions for cloud CLI actions: reads (`get`, `describe`, `list`),
+/// mutations (`apply`, `create`, `update`) and deletions (`delete`,
+/// `terminate`, `s3 rm`). An unset action, or a command no rule matches,
+/// falls through to the rest of the cascade.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CloudRule {
+    /// Role name globs. Empty matches every role.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Empty matches every CLI.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clis: Vec<CloudCli>,
+    /// Namespace (kubectl), profile (aws), project (gcloud) or subscription
+    /// (az) globs. Commands that don't name one are in `default`; kubectl
+    /// `--all-namespaces` matches every scope. Empty matches any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read: Option<Decision>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mutate: Option<Decision>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destroy: Option<Decision>,
+}
+
+/// Git operation rules: which branches are protected, and per-role
+/// decisions for each kind of risky operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitPolicyConfig {
+    /// Branch name globs. Pushing to or deleting a matching branch is a
+    /// `protected_push`.
+    #[serde(default = "default_protected_branches")]
+    pub protected_branches: Vec<String>,
+    /// For each operation, the first rule matching the role that sets a
+    /// decision for it wins. No rules disables the tier.
+    #[serde(default)]
+    pub rules: Vec<GitRule>,
+}
+
+impl Default for GitPolicyConfig {
+    fn default() -> Self {
+        Self {
+            protected_branches: default_protected_branches(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+fn default_protected_branches() -> Vec<String> {
+    vec!["main".into(), "master".into()]
+}
+
+/// Decisions for risky git operations. An unset operation, or a command no
+/// rule matches, falls through to the rest of the cascade.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitRule {
+    /// Role name globs. Empty matches every role.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// `git push --force`, `--force-with-lease`, `-f` or a `+refspec`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub force_push: Option<Decision>,
+    /// Pushes to, or deletes of, a protected branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protected_push: Option<Decision>,
+    /// `git config` writes (reads are not operations).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<Decision>,
+    /// `git tag -d` and pushes deleting `refs/tags/*`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_delete: Option<Decision>,
+    /// `git submodule add`/`set-url` and `submodule.*.url` config writes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submodule: Option<Decision>,
+    /// `core.hooksPath` changes and edits under `.git/hooks/`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Decision>,
+}
+
+/// Inspection of the text `Write`, `Edit`, `MultiEdit` and `NotebookEdit`
+/// calls add to files. Only text the call adds counts: an edit that keeps
+/// an existing match is not flagged again. Deny and ask only; anything not
+/// flagged falls through to the rest of the cascade.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContentConfig {
+    /// Decision for payloads adding secrets the sanitizer recognizes
+    /// (hardcoded credentials). Unset leaves them to the other checks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<Decision>,
+    /// Secret finding kinds not to act on, e.g. `high_entropy`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_secrets: Vec<String>,
+    /// Content patterns per path. Every matching rule applies; the most
+    /// restrictive decision wins.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ContentRule>,
+}
+
+/// A content pattern that is denied or asked about in matching files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentRule {
+    /// Role name globs. Empty matches every role.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Globs or `{{category}}` macros for the files written. Empty matches
+    /// every file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Regexes (multi-line mode) searched for in the added text.
+    pub patterns: Vec<String>,
+    pub decision: Decision,
+    /// Shown in the decision reason instead of the pattern.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Cross-call exfiltration correlation. Reads of `sensitive` files are
+/// remembered per session, and network-bound commands the session runs
+/// within `window_secs` afterwards (`curl`/`wget` uploads, `git push` to a
+/// remote the repository doesn't know, `scp`/`rsync`/`sftp` to another
+/// host, `nc`) get `decision`. Off while `sensitive` is empty.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExfiltrationConfig {
+    /// Globs or `{{category}}` macros for the files whose reads count.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive: Vec<String>,
+    /// How long a read keeps later network commands flagged. Default: 3600.
+    #[serde(default = "default_exfiltration_window")]
+    pub window_secs: u64,
+    /// Deny or ask. Default: ask.
+    #[serde(default = "default_exfiltration_decision")]
+    pub decision: Decision,
+}
+
+fn default_exfiltration_window() -> u64 {
+    3600
+}
+
+fn default_exfiltration_decision() -> Decision {
+    Decision::Ask
+}
+
+impl Default for ExfiltrationConfig {
+    fn default() -> Self {
+        Self {
+            sensitive: Vec::new(),
+            window_secs: default_exfiltration_window(),
+            decision: default_exfiltration_decision(),
+        }
+    }
+}
+
+/// Path-range write locks. An allowed write takes a lock on each target's
+/// range -- the first `ranges` entry matching it, or the file itself --
+/// for `ttl_secs`, renewed by every later allowed write to it. A write by
+/// another session into a locked range gets an ask naming the holder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockConfig {
+    /// Default: false.
+    #[serde(default)]
+    pub enabled: bool,
+    /// How long a lock is held after the last allowed write. Default: 300.
+    #[serde(default = "default_lock_ttl")]
+    pub ttl_secs: u64,
+    /// Globs or `{{category}}` macros locked as a whole.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranges: Vec<String>,
+}
+
+fn default_lock_ttl() -> u64 {
+    300
+}
+
+impl Default for LockConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_lock_ttl(),
+            ranges: Vec::new(),
+        }
+    }
+}
+
+/// Rolling per-session risk. Denied calls, calls touching sensitive paths
+/// and novel calls (ones no rule or precedent decided) add their `weights`
+/// to the session's score for `window_secs`. Once the score reaches
+/// `threshold`, similarity tiers stop deciding for the session (unless
+/// `disable_similarity` is false) and `max_auto_tier` caps the rest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskConfig {
+    /// How long an event counts toward the score. Default: 3600.
+    #[serde(default = "default_risk_window")]
+    pub window_secs: u64,
+    #[serde(default)]
+    pub weights: RiskWeights,
+    /// Score at which the session is tightened. Unset: never.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+    /// Skip token and embedding similarity once over the threshold, so
+    /// nothing is allowed for resembling an earlier call. Default: true.
+    #[serde(default = "default_disable_similarity")]
+    pub disable_similarity: bool,
+    /// Highest tier allowed to decide once over the threshold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_auto_tier: Option<AutoTier>,
+}
+
+fn default_risk_window() -> u64 {
+    3600
+}
+
+fn default_disable_similarity() -> bool {
+    true
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: default_risk_window(),
+            weights: RiskWeights::default(),
+            threshold: None,
+            disable_similarity: default_disable_similarity(),
+            max_auto_tier: None,
+        }
+    }
+}
+
+impl RiskConfig {
+    /// Whether a session with `score` is over the threshold.
+    pub fn exceeded(&self, score: f64) -> bool {
+        self.threshold.is_some_and(|threshold| score >= threshold)
+    }
+
+    /// Whether `tier` may decide for a session over the threshold.
+    pub fn permits(&self, tier: DecisionTier) -> bool {
+        let similarity = matches!(
+            tier,
+            DecisionTier::TokenJaccard | DecisionTier::EmbeddingSimilarity
+        );
+        !(self.disable_similarity && similarity)
+            && self.max_auto_tier.is_none_or(|cap| cap.permits(tier))
+    }
+}
+
+/// What each kind of risk event adds to a session's score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RiskWeights {
+    /// A call that was denied. Default: 3.
+    #[serde(default = "default_denied_weight")]
+    pub denied: f64,
+    /// A call touching a `sensitive_paths` path. Default: 2.
+    #[serde(default = "default_sensitive_weight")]
+    pub sensitive: f64,
+    /// A call only the supervisor or a human could decide. Default: 1.
+    #[serde(default = "default_novel_weight")]
+    pub novel: f64,
+}
+
+fn default_denied_weight() -> f64 {
+    3.0
+}
+
+fn default_sensitive_weight() -> f64 {
+    2.0
+}
+
+fn default_novel_weight() -> f64 {
+    1.0
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        Self {
+            denied: default_denied_weight(),
+            sensitive: default_sensitive_weight(),
+            novel: default_novel_weight(),
+        }
+    }
+}
+
+/// The built-in command risk score: the weight of the riskiest verb
+/// category a call uses, plus a weight for each other signal present.
+/// Attached to every decision; at `high` or `critical` a queued call gets
+/// at least that priority.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CommandRiskConfig {
+    #[serde(default)]
+    pub weights: CommandRiskWeights,
+    /// Score from which a queued call is at least high priority. Default: 4.
+    #[serde(default = "default_high_risk")]
+    pub high: f64,
+    /// Score from which a queued call is critical. Default: 7.
+    #[serde(default = "default_critical_risk")]
+    pub critical: f64,
+}
+
+fn default_high_risk() -> f64 {
+    4.0
+}
+
+fn default_critical_risk() -> f64 {
+    7.0
+}
+
+impl Default for CommandRiskConfig {
+    fn default() -> Self {
+        Self {
+            weights: CommandRiskWeights::default(),
+            high: default_high_risk(),
+            critical: default_critical_risk(),
+        }
+    }
+}
+
+/// What each command risk signal adds to the score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CommandRiskWeights {
+    /// Reading or listing files. Default: 0.
+    #[serde(default)]
+    pub read: f64,
+    /// Running programs that aren't known to only read. Default: 1.
+    #[serde(default = "default_execute_weight")]
+    pub execute: f64,
+    /// Writing files or repository state. Default: 1.
+    #[serde(default = "default_write_weight")]
+    pub write: f64,
+    /// Deleting files or data (`rm`, `git clean`, `DROP TABLE`). Default: 3.
+    #[serde(default = "default_delete_weight")]
+    pub delete: f64,
+    /// Changing privileges or ownership (`sudo`, `chmod`). Default: 3.
+    #[serde(default = "default_privilege_weight")]
+    pub privilege: f64,
+    /// Flags like `-rf`, `--force` and `--hard`. Default: 2.
+    #[serde(default = "default_destructive_flags_weight")]
+    pub destructive_flags: f64,
+    /// A target under `sensitive_paths.ask_write`. Default: 3.
+    #[serde(default = "default_sensitive_path_weight")]
+    pub sensitive_path: f64,
+    /// Talking to another host (`curl`, `ssh`, `git push`). Default: 2.
+    #[serde(default = "default_network_weight")]
+    pub network: f64,
+}
+
+fn default_execute_weight() -> f64 {
+    1.0
+}
+
+fn default_write_weight() -> f64 {
+    1.0
+}
+
+fn default_delete_weight() -> f64 {
+    3.0
+}
+
+fn default_privilege_weight() -> f64 {
+    3.0
+}
+
+fn default_destructive_flags_weight() -> f64 {
+    2.0
+}
+
+fn default_sensitive_path_weight() -> f64 {
+    3.0
+}
+
+fn default_network_weight() -> f64 {
+    2.0
+}
+
+impl Default for CommandRiskWeights {
+    fn default() -> Self {
+        Self {
+            read: 0.0,
+            execute: default_execute_weight(),
+            write: default_write_weight(),
+            delete: default_delete_weight(),
+            privilege: default_privilege_weight(),
+            destructive_flags: default_destructive_flags_weight(),
+            sensitive_path: default_sensitive_path_weight(),
+            network: default_network_weight(),
+        }
+    }
+}
+
+/// Custom redaction rules, applied in addition to the built-in ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SanitizeConfig {
+    /// Literal secret prefixes (e.g. `acme_live_`); the prefix and the
+    /// token it starts are redacted.
+    #[serde(default)]
+    pub prefixes: Vec<String>,
+    /// Regexes; capture group 1, if present, is kept as a prefix and the
+    /// rest of the match is redacted.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Literal strings (e.g. internal hostnames) redacted wherever they
+    /// appear, ignoring ASCII case.
+    #[serde(default)]
+    pub literals: Vec<String>,
+    /// Thresholds for the high-entropy layer.
+    #[serde(default)]
+    pub entropy: EntropyConfig,
+    /// Volatile tokens rewritten to placeholders before caching.
+    #[serde(default)]
+    pub normalize: NormalizeConfig,
+    /// The stages to run, in order. Leave a stage out to disable it. The
+    /// redaction stages must all come before `normalize` and `truncate`,
+    /// which only shape the cache key.
+    #[serde(default = "default_sanitize_stages")]
+    pub stages: Vec<SanitizeStage>,
+    /// Longest cache-key input, in bytes, for the `truncate` stage.
+    /// Unlimited by default.
+    #[serde(default)]
+    pub max_input_bytes: Option<usize>,
+}
+
+fn default_sanitize_stages() -> Vec<SanitizeStage> {
+    vec![
+        SanitizeStage::Encoding,
+        SanitizeStage::Literals,
+        SanitizeStage::Prefixes,
+        SanitizeStage::Patterns,
+        SanitizeStage::Entropy,
+        SanitizeStage::Normalize,
+        SanitizeStage::Truncate,
+    ]
+}
+
+impl Default for SanitizeConfig {
+    fn default() -> Self {
+        Self {
+            prefixes: Vec::new(),
+            patterns: Vec::new(),
+            literals: Vec::new(),
+            entropy: EntropyConfig::default(),
+            normalize: NormalizeConfig::default(),
+            stages: default_sanitize_stages(),
+            max_input_bytes: None,
+        }
+    }
+}
+
+/// A named stage of the sanitize pipeline (`sanitize.stages`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SanitizeStage {
+    /// Decode base64 and URL-encoded values and redact secrets inside them
+    /// with the enabled `literals`, `prefixes` and `patterns` stages.
+    Encoding,
+    /// `sanitize.literals`, ignoring case.
+    Literals,
+    /// Built-in and `sanitize.prefixes` secret prefixes.
+    Prefixes,
+    /// Built-in and `sanitize.patterns` regexes.
+    Patterns,
+    /// High-entropy tokens (`sanitize.entropy`).
+    Entropy,
+    /// Volatile-token placeholders (`sanitize.normalize`).
+    Normalize,
+    /// Cut inputs over `sanitize.max_input_bytes`.
+    Truncate,
+}
+
+impl SanitizeStage {
+    /// Whether the stage redacts secrets, as opposed to shaping the cache
+    /// key.
+    pub fn redacts(self) -> bool {
+        !matches!(self, SanitizeStage::Normalize | SanitizeStage::Truncate)
+    }
+}
+
+impl std::fmt::Display for SanitizeStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SanitizeStage::Encoding => "encoding",
+            SanitizeStage::Literals => "literals",
+            SanitizeStage::Prefixes => "prefixes",
+            SanitizeStage::Patterns => "patterns",
+            SanitizeStage::Entropy => "entropy",
+            SanitizeStage::Normalize => "normalize",
+            SanitizeStage::Truncate => "truncate",
+        };
+        f.write_str(name)
+    }
+}
+
+/// High-entropy detection (`sanitize.entropy`): tokens that look random
+/// enough are redacted even without a known prefix or pattern.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntropyConfig {
+    /// Shortest token considered. Default: 20.
+    #[serde(default = "default_entropy_min_length")]
+    pub min_length: usize,
+    /// Shannon entropy, in bits per character, a token must exceed.
+    /// Default: 4.0.
+    #[serde(default = "default_entropy_min_entropy")]
+    pub min_entropy: f64,
+    /// Shortest run of hex digits redacted on its own. Hex tops out at 4
+    /// bits per character, under `min_entropy`, so hex secrets need their
+    /// own threshold. Off by default: commit ids and lockfile checksums
+    /// are hex too.
+    #[serde(default)]
+    pub hex_min_length: Option<usize>,
+    /// Shannon entropy a hex run must exceed. Default: 3.0.
+    #[serde(default = "default_entropy_hex_min_entropy")]
+    pub hex_min_entropy: f64,
+}
+
+fn default_entropy_min_length() -> usize {
+    20
+}
+fn default_entropy_min_entropy() -> f64 {
+    4.0
+}
+fn default_entropy_hex_min_entropy() -> f64 {
+    3.0
+}
+
+impl Default for EntropyConfig {
+    fn default() -> Self {
+        Self {
+            min_length: default_entropy_min_length(),
+            min_entropy: default_entropy_min_entropy(),
+            hex_min_length: None,
+            hex_min_entropy: default_entropy_hex_min_entropy(),
+        }
+    }
+}
+
+/// Volatile-token normalization (`sanitize.normalize`): values that change
+/// on every retry are rewritten to stable placeholders in the cached input,
+/// so a retried call still hits the exact cache. Each kind is on by default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NormalizeConfig {
+    /// ISO 8601 dates and times, and Unix epoch seconds or milliseconds.
+    #[serde(default = "default_normalize_kind")]
+    pub timestamps: bool,
+    #[serde(default = "default_normalize_kind")]
+    pub uuids: bool,
+    /// The first component under `/tmp`, `/var/tmp` or a macOS
+    /// `/var/folders/../T` directory, e.g. `/tmp/tmpk2x9`.
+    #[serde(default = "default_normalize_kind")]
+    pub temp_paths: bool,
+    /// Ports on loopback hosts (`localhost:5173`) and `--port` values.
+    #[serde(default = "default_normalize_kind")]
+    pub ports: bool,
+    /// Hex runs of 7 to 40 characters mixing digits and letters.
+    #[serde(default = "default_normalize_kind")]
+    pub git_shas: bool,
+}
+
+fn default_normalize_kind() -> bool {
+    true
+}
+
+impl Default for NormalizeConfig {
+    fn default() -> Self {
+        Self {
+            timestamps: true,
+            uuids: true,
+            temp_paths: true,
+            ports: true,
+            git_shas: true,
+        }
+    }
+}
+
+/// Decision audit log. Every decision returned by `hookwise check` is
+/// appended to `path` (relative to the working directory) as hash-chained
+/// JSONL (see [`crate::audit::verify`]), regardless of `persistence`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditConfig {
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+/// Cached decision expiry. Records older than `max_age_days` for their
+/// scope are skipped on load and removed during compaction.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheConfig {
+    #[serde(default)]
+    pub max_age_days: CacheMaxAge,
+    /// Maximum in-memory exact cache entries; least recently used entries
+    /// are evicted beyond it. `0` (the default) means unbounded.
+    #[serde(default)]
+    pub max_entries: usize,
+    /// Regexes for Bash commands whose decisions are keyed by the HEAD
+    /// commit, so they are re-evaluated once the tree changes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commit_keyed: Vec<String>,
+}
+
+/// Maximum record age in days per scope. `None` means records never expire.
+/// Role-scoped records live alongside project rules and use `project`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheMaxAge {
+    #[serde(default)]
+    pub org: Option<u32>,
+    #[serde(default)]
+    pub project: Option<u32>,
+    #[serde(default)]
+    pub user: Option<u32>,
+}
+
+impl CacheMaxAge {
+    /// The age limit for records in `scope`, if any.
+    pub fn for_scope(&self, scope: ScopeLevel) -> Option<chrono::Duration> {
+        let days = match scope {
+            ScopeLevel::Org => self.org,
+            ScopeLevel::Project | ScopeLevel::Role => self.project,
+            ScopeLevel::User => self.user,
+        }?;
+        Some(chrono::Duration::days(days as i64))
+    }
+}
+
+impl CacheConfig {
+    /// Maximum age for records in `scope`, if any.
+    pub fn max_age(&self, scope: ScopeLevel) -> Option<chrono::Duration> {
+        self.max_age_days.for_scope(scope)
+    }
+
+    /// Whether a record written at `timestamp` in `scope` has expired.
+    pub fn is_expired(
+        &self,
+        scope: ScopeLevel,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        self.max_age(scope)
+            .is_some_and(|max_age| now - timestamp > max_age)
+    }
+}
+
+/// Data retention for the rule files. Records older than `retention_days`
+/// for their scope are deleted (or archived) by `hookwise prune`, whatever
+/// their decision. With `auto_upgrade`, project and user records from an
+/// older schema are rewritten at the current one when first loaded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageConfig {
+    #[serde(default)]
+    pub retention_days: CacheMaxAge,
+    #[serde(default)]
+    pub auto_upgrade: bool,
+}
+
+impl StorageConfig {
+    /// Retention period for records in `scope`, if any.
+    pub fn retention(&self, scope: ScopeLevel) -> Option<chrono::Duration> {
+        self.retention_days.for_scope(scope)
+    }
+}
+
+/// Supervisor backend configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "backend")]
+pub enum SupervisorConfig {
+    #[serde(rename = "socket")]
+    Socket { socket_path: Option<PathBuf> },
+    #[serde(rename = "api")]
+    Api {
+        api_base_url: Option<String>,
+        model: Option<String>,
+        max_tokens: Option<u32>,
+    },
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self::Socket { socket_path: None }
+    }
+}
+
+impl SupervisorConfig {
+    /// The socket the supervisor listens on for `team_id`, or None for the
+    /// API backend.
+    pub fn socket_path(&self, team_id: Option<&str>) -> Option<PathBuf> {
+        match self {
+            Self::Socket { socket_path } => Some(
+                socket_path
+                    .clone()
+                    .unwrap_or_else(|| default_socket_path(team_id)),
+            ),
+            Self::Api { .. } => None,
+        }
+    }
+}
+
+/// The supervisor socket when none is configured:
+/// `/tmp/hookwise-<team-id>.sock` (`solo` without a team).
+pub fn default_socket_path(team_id: Option<&str>) -> PathBuf {
+    let tid = team_id.unwrap_or("solo");
+    PathBuf::from(format!("/tmp/hookwise-{tid}.sock"))
+}
+
+/// Supervisor transcript logging. When enabled, every supervisor prompt and
+/// reply is written to `.hookwise/.user/supervisor-transcript.jsonl` after an
+/// extra redaction pass; `redact_patterns` are additional regexes (group 1,
+/// if present, is kept as a prefix). The log rotates once it reaches
+/// `max_bytes`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupervisorTranscriptConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_transcript_max_bytes")]
+    pub max_bytes: u64,
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+}
+
+fn default_transcript_max_bytes() -> u64 {
+    5 * 1024 * 1024
+}
+
+impl Default for SupervisorTranscriptConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_bytes: default_transcript_max_bytes(),
+            redact_patterns: Vec::new(),
+        }
+    }
+}
+
+/// Few-shot precedents for the supervisor. Up to `max_examples` past human
+/// decisions for the same role and tool, picked by similarity to the call
+/// under review, are sent along with it, capped at roughly `max_tokens`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupervisorExamplesConfig {
+    #[serde(default = "default_examples_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_max_examples")]
+    pub max_examples: usize,
+    #[serde(default = "default_examples_max_tokens")]
+    pub max_tokens: usize,
+}
+
+fn default_examples_enabled() -> bool {
+    true
+}
+
+fn default_max_examples() -> usize {
+    5
+}
+
+fn default_examples_max_tokens() -> usize {
+    600
+}
+
+impl Default for SupervisorExamplesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_examples: default_max_examples(),
+            max_tokens: default_examples_max_tokens(),
+        }
+    }
+}
+
+/// Evaluation deadline. Past `secs` the runner stops waiting on the
+/// cascade (supervisor, human queue, ...) and answers with `decision`.
+/// Evaluations taking at least `near` of the deadline are logged for
+/// `hookwise stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadlineConfig {
+    /// Seconds, e.g. 55 under Claude Code's 60s hook timeout. None: no
+    /// deadline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secs: Option<u64>,
+    /// Decision for calls that run out of time. Default: deny.
+    #[serde(default = "default_deadline_decision")]
+    pub decision: Decision,
+    /// Share of the deadline from which an evaluation counts as near it.
+    /// Default: 0.8.
+    #[serde(default = "default_near_deadline")]
+    pub near: f64,
+}
+
+fn default_deadline_decision() -> Decision {
+    Decision::Deny
+}
+fn default_near_deadline() -> f64 {
+    0.8
+}
+
+impl Default for DeadlineConfig {
+    fn default() -> Self {
+        Self {
+            secs: None,
+            decision: default_deadline_decision(),
+            near: default_near_deadline(),
+        }
+    }
+}
+
+impl DeadlineConfig {
+    /// The deadline, if one is set.
+    pub fn duration(&self) -> Option<std::time::Duration> {
+        self.secs.map(std::time::Duration::from_secs)
+    }
+
+    /// Whether an evaluation that took `elapsed` came near the deadline
+    /// (or passed it).
+    pub fn is_near(&self, elapsed: std::time::Duration) -> bool {
+        self.duration()
+            .is_some_and(|deadline| elapsed.as_secs_f64() >= deadline.as_secs_f64() * self.near)
+    }
+}
+
+/// Human queue SLA configuration. Pending items at or above `nag_min_priority`
+/// that wait longer than `overdue_after_secs` are marked overdue and re-notified
+/// every `nag_interval_secs` until answered or timed out. Unset thresholds are
+/// derived from how long a `check` waits (see
+/// [`PolicyConfig::effective_human_sla`]), so they fire before the timeout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HumanSlaConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overdue_after_secs: Option<u64>,
+    #[serde(default = "default_nag_min_priority")]
+    pub nag_min_priority: crate::cascade::human::Priority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nag_interval_secs: Option<u64>,
+}
+
+fn default_nag_min_priority() -> crate::cascade::human::Priority {
+    crate::cascade::human::Priority::High
+}
+
+impl Default for HumanSlaConfig {
+    fn default() -> Self {
+        Self {
+            overdue_after_secs: None,
+            nag_min_priority: default_nag_min_priority(),
+            nag_interval_secs: None,
+        }
+    }
+}
+
+impl HumanSlaConfig {
+    /// This config with unset thresholds derived from `wait_secs`, the
+    /// longest a `check` waits for an answer: overdue at half of it, then a
+    /// reminder every quarter.
+    pub fn resolved(&self, wait_secs: u64) -> Self {
+        Self {
+            overdue_after_secs: Some(self.overdue_after_secs.unwrap_or(wait_secs / 2)),
+            nag_interval_secs: Some(self.nag_interval_secs.unwrap_or(wait_secs / 4)),
+            ..self.clone()
+        }
+    }
+
+    /// How long an item waits before it is overdue.
+    pub fn overdue_after(&self) -> chrono::Duration {
+        let secs = self
+            .overdue_after_secs
+            .unwrap_or(default_human_timeout() / 2);
+        chrono::Duration::seconds(secs as i64)
+    }
+
+    /// Time between reminders once an item is overdue.
+    pub fn nag_interval(&self) -> std::time::Duration {
+        let secs = self
+            .nag_interval_secs
+            .unwrap_or(default_human_timeout() / 4);
+        std::time::Duration::from_secs(secs.max(1))
+    }
+}
+
+/// Push notification configuration. Every channel is notified when a decision
+/// at or above `min_priority` is queued for a human.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    #[serde(default)]
+    pub channels: Vec<NotificationChannel>,
+    #[serde(default = "default_notify_min_priority")]
+    pub min_priority: crate::cascade::human::Priority,
+}
+
+fn default_notify_min_priority() -> crate::cascade::human::Priority {
+    crate::cascade::human::Priority::Low
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            channels: Vec::new(),
+            min_priority: default_notify_min_priority(),
+        }
+    }
+}
+
+/// A push notification channel. Credentials may be omitted from the policy
+/// file and supplied via environment variables instead, since `policy.yml`
+/// is checked into git.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "provider")]
+pub enum NotificationChannel {
+    /// ntfy.sh or a self-hosted ntfy server. Token falls back to `NTFY_TOKEN`.
+    #[serde(rename = "ntfy")]
+    Ntfy {
+        #[serde(default = "default_ntfy_server")]
+        server: String,
+        topic: String,
+        token: Option<String>,
+    },
+    /// Pushover. Keys fall back to `PUSHOVER_USER_KEY` and `PUSHOVER_API_TOKEN`.
+    #[serde(rename = "pushover")]
+    Pushover {
+        user_key: Option<String>,
+        api_token: Option<String>,
+        device: Option<String>,
+    },
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".into()
+}
+
+/// Escalation chain for human decisions. Each stage notifies its channels and
+/// waits `timeout_secs`; if nobody answers, the next stage is notified. The
+/// decision falls back to deny only after the last stage times out. When
+/// stages are configured they replace `human_timeout_secs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    #[serde(default)]
+    pub stages: Vec<EscalationStage>,
+}
+
+/// One approver in the escalation chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationStage {
+    pub name: String,
+    pub timeout_secs: u64,
+    #[serde(default)]
+    pub channels: Vec<NotificationChannel>,
+    /// Only calls with at least this command risk score go through this
+    /// stage. Unset: every call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_risk: Option<f64>,
+}
+
+impl EscalationConfig {
+    /// Total time the chain waits before giving up, or `None` when no
+    /// escalation is configured.
+    pub fn total_timeout_secs(&self) -> Option<u64> {
+        if self.stages.is_empty() {
+            None
+        } else {
+            Some(self.stages.iter().map(|s| s.timeout_secs).sum())
+        }
+    }
+}
+
+/// Excerpts of the target file's current contents and of the proposed
+/// contents, attached to pending Write/Edit decisions so approvers can judge
+/// them from a notification, the TUI or MCP. Each excerpt is at most
+/// `max_lines` lines and `max_bytes` bytes; Edit excerpts start
+/// `context_lines` above the replaced text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentConfig {
+    #[serde(default = "default_attachments_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_attachment_max_lines")]
+    pub max_lines: usize,
+    #[serde(default = "default_attachment_max_bytes")]
+    pub max_bytes: usize,
+    #[serde(default = "default_attachment_context_lines")]
+    pub context_lines: usize,
+}
+
+fn default_attachments_enabled() -> bool {
+    true
+}
+fn default_attachment_max_lines() -> usize {
+    20
+}
+fn default_attachment_max_bytes() -> usize {
+    2048
+}
+fn default_attachment_context_lines() -> usize {
+    3
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_lines: default_attachment_max_lines(),
+            max_bytes: default_attachment_max_bytes(),
+            context_lines: default_attachment_context_lines(),
+        }
+    }
+}
+
+/// Approval of pending decisions through `Hookwise-Approve: <id>` /
+/// `Hookwise-Deny: <id>` commit trailers. Only commits with a good signature
+/// by a signer listed in `approvers` (by email, SSH principal or key
+/// fingerprint) are honored, and only for decisions at or below
+/// `max_priority` so urgent items still need interactive approval. With
+/// `require_signed: false`, unsigned commits are matched by committer
+/// email, which anyone can set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitApprovalConfig {
+    #[serde(default)]
+    pub approvers: Vec<String>,
+    #[serde(default = "default_git_approval_max_commits")]
+    pub max_commits: usize,
+    #[serde(default = "default_git_approval_require_signed")]
+    pub require_signed: bool,
+    #[serde(default)]
+    pub max_priority: crate::cascade::human::Priority,
+}
+
+fn default_git_approval_max_commits() -> usize {
+    50
+}
+
+fn default_git_approval_require_signed() -> bool {
+    true
+}
+
+impl Default for GitApprovalConfig {
+    fn default() -> Self {
+        Self {
+            approvers: Vec::new(),
+            max_commits: default_git_approval_max_commits(),
+            require_signed: default_git_approval_require_signed(),
+            max_priority: crate::cascade::human::Priority::Normal,
+        }
+    }
+}
+
+/// Offline approval bundles. Each side signs with its own Ed25519 key,
+/// read from `HOOKWISE_BUNDLE_KEY` or from `key_file` (relative to the
+/// global config directory, and refused inside the project), and accepts
+/// bundles signed by one of `trusted_keys`, whose name becomes the
+/// approver's identity. Responses older than `max_age_hours` are refused on
+/// import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineApprovalConfig {
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+    /// Base64 public keys by the identity that holds them.
+    #[serde(default)]
+    pub trusted_keys: BTreeMap<String, String>,
+    #[serde(default = "default_offline_max_age_hours")]
+    pub max_age_hours: u64,
+}
+
+fn default_offline_max_age_hours() -> u64 {
+    72
+}
+
+impl Default for OfflineApprovalConfig {
+    fn default() -> Self {
+        Self {
+            key_file: None,
+            trusted_keys: BTreeMap::new(),
+            max_age_hours: default_offline_max_age_hours(),
+        }
+    }
+}
+
+/// Interactive approval of pending decisions. With `approvers` set, only
+/// those identities (emails or user names; `*` globs allowed) may answer;
+/// with `require_verified`, only identities a provider vouches for (a
+/// signed OIDC token or an identity command) may.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovalConfig {
+    #[serde(default)]
+    pub approvers: Vec<String>,
+    #[serde(default)]
+    pub require_verified: bool,
+}
+
+/// Controls on `hookwise disable`, which turns off all gating for a
+/// session. In a `protected` project a disable only takes effect once a
+/// second identity confirms it, both verified and listed in
+/// `approvals.approvers`, or when it carries an org-scope token whose
+/// SHA-256 is listed in `org_token_sha256`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionDisableConfig {
+    #[serde(default)]
+    pub protected: bool,
+    /// Lowercase hex SHA-256 digests of org tokens.
+    #[serde(default)]
+    pub org_token_sha256: Vec<String>,
+    /// How long a disable request waits for its second approver. Default: 900.
+    #[serde(default = "default_disable_request_ttl")]
+    pub request_ttl_secs: u64,
+    /// Remind (stderr and notification channels) when a session has been
+    /// disabled this long, and again each interval after. `null` turns
+    /// reminders off. Default: 3600.
+    #[serde(default = "default_disable_reminder")]
+    pub remind_after_secs: Option<u64>,
+}
+
+fn default_disable_request_ttl() -> u64 {
+    900
+}
+
+fn default_disable_reminder() -> Option<u64> {
+    Some(3600)
+}
+
+impl Default for SessionDisableConfig {
+    fn default() -> Self {
+        Self {
+            protected: false,
+            org_token_sha256: Vec::new(),
+            request_ttl_secs: default_disable_request_ttl(),
+            remind_after_secs: default_disable_reminder(),
+        }
+    }
+}
+
+/// Where the user and org recorded on sessions, audit events and approvals
+/// come from. Default: the environment.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum IdentityConfig {
+    /// `HOOKWISE_USER` (else `USER`/`USERNAME`) and `HOOKWISE_ORG`.
+    #[default]
+    Env,
+    /// `git config user.email` in the project.
+    Git,
+    /// Claims of an OIDC ID token an SSO agent keeps in a file.
+    Oidc(OidcIdentityConfig),
+    /// The output of a command: a user name, or JSON `{"user", "org"}`.
+    Command(CommandIdentityConfig),
+}
+
+/// An OIDC ID token file. Expiry is always checked; the signature only
+/// when `jwks_file` is set, and only then is the identity verified.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OidcIdentityConfig {
+    pub token_file: PathBuf,
+    /// JWKS with the issuer's signing keys (RS256 and ES256).
+    #[serde(default)]
+    pub jwks_file: Option<PathBuf>,
+    /// Required `iss` claim.
+    #[serde(default)]
+    pub issuer: Option<String>,
+    /// Required entry in the `aud` claim.
+    #[serde(default)]
+    pub audience: Option<String>,
+    #[serde(default = "default_user_claim")]
+    pub user_claim: String,
+    #[serde(default)]
+    pub org_claim: Option<String>,
+}
+
+fn default_user_claim() -> String {
+    "email".into()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandIdentityConfig {
+    /// Program and arguments; not run through a shell.
+    pub command: Vec<String>,
+    #[serde(default = "default_identity_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_identity_timeout() -> u64 {
+    5
+}
+
+/// Release checks by `hookwise maintain` and the update hint `check` prints
+/// from their cached result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdatesConfig {
+    /// Check for releases and print update hints. Managed fleets that ship
+    /// hookwise themselves turn this off.
+    #[serde(default = "default_update_hints")]
+    pub hints: bool,
+    /// Base URL of an internal artifact mirror used instead of GitHub
+    /// releases: `<mirror>/latest` holds the release tag, and archives and
+    /// checksums live under `<mirror>/v<version>/`.
+    #[serde(default)]
+    pub mirror: Option<String>,
+    /// How often `hookwise maintain` checks for a release.
+    #[serde(default = "default_update_check_interval")]
+    pub check_interval_hours: u64,
+}
+
+fn default_update_hints() -> bool {
+    true
+}
+
+fn default_update_check_interval() -> u64 {
+    24
+}
+
+impl Default for UpdatesConfig {
+    fn default() -> Self {
+        Self {
+            hints: true,
+            mirror: None,
+            check_interval_hours: default_update_check_interval(),
+        }
+    }
+}
+
+/// Where `hookwise sync` gets the org rules: a git repository checked out
+/// as the org scope directory, or a rule server. Exactly one of `repo` and
+/// `server` is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrgRulesConfig {
+    /// Clone URL of the repository.
+    #[serde(default)]
+    pub repo: Option<String>,
+    /// Base URL of a rule server speaking the HTTP sync protocol.
+    #[serde(default)]
+    pub server: Option<String>,
+    /// Environment variable holding the rule server's bearer token.
+    /// Defaults to `HOOKWISE_ORG_RULES_TOKEN`.
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Branch to track; the repository's default branch when unset.
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Org directory to sync into. Defaults to the identity's org, else the
+    /// org of the current project's `origin` remote.
+    #[serde(default)]
+    pub org: Option<String>,
+}
+
+/// The org scope kept in a shared S3-compatible bucket (Amazon S3, Google
+/// Cloud Storage with HMAC keys, MinIO, R2). The org directory is a local
+/// cache of the bucket, refreshed by `check` and `hookwise sync`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrgStoreConfig {
+    /// Base URL of the S3 API, e.g. `https://s3.us-east-1.amazonaws.com`
+    /// or `https://storage.googleapis.com`. Buckets are addressed by path.
+    pub endpoint: String,
+    pub bucket: String,
+    /// Key prefix the org directories live under, e.g. `hookwise/`.
+    #[serde(default)]
+    pub prefix: String,
+    /// Signing region; GCS accepts any.
+    #[serde(default = "default_store_region")]
+    pub region: String,
+    /// Environment variables holding the access key pair. Without them the
+    /// bucket is read anonymously.
+    #[serde(default = "default_access_key_env")]
+    pub access_key_env: String,
+    #[serde(default = "default_secret_key_env")]
+    pub secret_key_env: String,
+    /// How long `check` trusts the cache before asking the bucket again.
+    #[serde(default = "default_store_refresh_secs")]
+    pub refresh_secs: u64,
+    /// Per-request timeout; past it the cached rules stay in force.
+    #[serde(default = "default_store_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Publish org-scope decisions made by org maintainers to the bucket.
+    #[serde(default)]
+    pub writable: bool,
+}
+
+fn default_store_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_access_key_env() -> String {
+    "AWS_ACCESS_KEY_ID".to_string()
+}
+
+fn default_secret_key_env() -> String {
+    "AWS_SECRET_ACCESS_KEY".to_string()
+}
+
+fn default_store_refresh_secs() -> u64 {
+    300
+}
+
+fn default_store_timeout_secs() -> u64 {
+    5
+}
+
+/// Global hookwise configuration from `~/.config/hookwise/config.yml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalConfig {
+    pub supervisor: SupervisorConfig,
+    pub api_key: Option<String>,
+    pub embedding_model: Option<String>,
+    #[serde(default)]
+    pub identity: IdentityConfig,
+    #[serde(default)]
+    pub updates: UpdatesConfig,
+    #[serde(default)]
+    pub org_rules: Option<OrgRulesConfig>,
+    #[serde(default)]
+    pub org_store: Option<OrgStoreConfig>,
+}
+
+impl GlobalConfig {
+    /// Load global config. Returns None if not present.
+    pub fn load() -> Result<Option<Self>> {
+        let home = super::dirs_global();
+        let path = home.join("config.yml");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        let config: Self =
+            serde_yaml::from_str(&contents).map_err(|e| HookwiseError::ConfigParse {
+                path: path.clone(),
+                reason: e.to_string(),
+            })?;
+        Ok(Some(config))
+    }
+
+    /// The configured `embedding_model` spec, or `default` (the bundled
+    /// local model) when unset or the global config cannot be read.
+    pub fn embedding_spec() -> String {
+        match Self::load() {
+            Ok(config) => config.and_then(|c| c.embedding_model),
+            Err(e) => {
+                tracing::warn!("ignoring unreadable global config: {}", e);
+                None
+            }
+        }
+        .unwrap_or_else(|| "default".to_string())
+    }
+}
+
+/// Org-wide policy synced next to the org rules, at
+/// `<rules root>/org/<org>/policy.yml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrgPolicyConfig {
+    #[serde(default)]
+    pub rollout: RolloutConfig,
+    /// Identities (emails or user names; `*` globs allowed) that may publish
+    /// org rules with `hookwise sync --push`.
+    #[serde(default)]
+    pub maintainers: Vec<String>,
+}
+
+impl OrgPolicyConfig {
+    /// Load the org policy for `org`. Returns default if the file doesn't
+    /// exist.
+    pub fn load(rules_root: &Path, org: &str) -> Result<Self> {
+        let path = rules_root.join("org").join(org).join("policy.yml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        Self::parse(&contents, path)
+    }
+
+    /// Parse an org `policy.yml`; `path` names it in errors.
+    pub fn parse(contents: &str, path: PathBuf) -> Result<Self> {
+        serde_yaml::from_str(contents).map_err(|e| HookwiseError::ConfigParse {
+            path,
+            reason: e.to_string(),
+        })
+    }
+}
+
+/// Canary rollout of org rules. Org rules recorded at or after `since` are
+/// enforced for `percent` of sessions (picked by a hash of the session id
+/// and `salt`); the other sessions evaluate them in shadow and log where
+/// they would have changed the decision. Without `since`, every org rule is
+/// enforced everywhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutConfig {
+    #[serde(default)]
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default = "default_rollout_percent")]
+    pub percent: u8,
+    /// Change to reshuffle which sessions land in the canary.
+    #[serde(default)]
+    pub salt: String,
+}
+
+fn default_rollout_percent() -> u8 {
+    100
+}
+
+impl Default for RolloutConfig {
+    fn default() -> Self {
+        Self {
+            since: None,
+            percent: default_rollout_percent(),
+            salt: String::new(),
         }
-        let contents = std::fs::read_to_string(&path)?;
-        let config: Self =
-            serde_yaml::from_str(&contents).map_err(|e| HookwiseError::ConfigParse {
-                path: path.clone(),
-                reason: e.to_string(),
-            })?;
-        Ok(Some(config))
     }
 }
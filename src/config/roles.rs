@@ -1,12 +1,13 @@
 use globset::GlobSet;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 use crate::error::{HookwiseError, Result};
+use crate::scope::ScopeLevel;
 
 /// A role definition from `roles.yml`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RoleDefinition {
     /// Role name (e.g., "coder", "tester", "maintainer").
     pub name: String,
@@ -16,16 +17,91 @@ pub struct RoleDefinition {
 
     /// Deterministic path policies for this role.
     pub paths: PathPolicyConfig,
+
+    /// Scope for rules added from this role's decisions when approve/deny
+    /// is given no `--scope`. Defaults to `rule_scope` in policy.yml.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_scope: Option<ScopeLevel>,
 }
 
 /// Raw path policy from YAML (string globs, before compilation).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct PathPolicyConfig {
     pub allow_write: Vec<String>,
     pub deny_write: Vec<String>,
     pub allow_read: Vec<String>,
 }
 
+impl RoleDefinition {
+    /// A role with no path policies yet. Patterns added with the `with_*`
+    /// methods may use `{{category}}` macros; they are kept as written
+    /// until the roles are loaded or [`RolesConfig::expanded`].
+    pub fn new(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            paths: PathPolicyConfig::default(),
+            rule_scope: None,
+        }
+    }
+
+    /// Add globs or macros the role may write.
+    pub fn with_allow_write<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.paths
+            .allow_write
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add globs or macros the role may not write.
+    pub fn with_deny_write<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.paths
+            .deny_write
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add globs or macros the role may read.
+    pub fn with_allow_read<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.paths
+            .allow_read
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the scope for rules added from this role's decisions.
+    pub fn with_rule_scope(mut self, scope: ScopeLevel) -> Self {
+        self.rule_scope = Some(scope);
+        self
+    }
+
+    /// The role as a YAML mapping, as it appears under `roles:`.
+    pub fn to_yaml(&self) -> Result<String> {
+        super::yaml_string(self, "role")
+    }
+}
+
+/// Serialize a map in key order, so YAML output is stable across runs.
+fn sorted<S, V>(map: &HashMap<String, V>, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    V: Serialize,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
 /// Compiled path policy -- globset instances ready for matching.
 /// GlobSet doesn't implement Debug, so we implement it manually.
 pub struct CompiledPathPolicy {
@@ -63,13 +139,24 @@ impl CompiledPathPolicy {
     }
 }
 
-fn build_globset(patterns: &[String]) -> Result<GlobSet> {
+pub(crate) fn build_globset(patterns: &[String]) -> Result<GlobSet> {
     let mut builder = globset::GlobSetBuilder::new();
     for pattern in patterns {
-        let glob = globset::Glob::new(pattern).map_err(|e| HookwiseError::GlobPattern {
-            pattern: pattern.clone(),
-            reason: e.to_string(),
-        })?;
+        // Paths are matched with forward slashes (`path_policy::to_slash`).
+        // On Windows, accept globs written with backslashes and ignore case
+        // as the filesystem does.
+        let normalized = if cfg!(windows) {
+            pattern.replace('\\', "/")
+        } else {
+            pattern.clone()
+        };
+        let glob = globset::GlobBuilder::new(&normalized)
+            .case_insensitive(cfg!(windows))
+            .build()
+            .map_err(|e| HookwiseError::GlobPattern {
+                pattern: pattern.clone(),
+                reason: e.to_string(),
+            })?;
         builder.add(glob);
     }
     builder.build().map_err(|e| HookwiseError::GlobPattern {
@@ -202,11 +289,13 @@ fn macro_regex() -> regex::Regex {
     regex::Regex::new(r"^\{\{([a-z][a-z0-9_]*)\}\}$").expect("macro regex is valid")
 }
 
-/// Expand `{{category_name}}` macros in a list of glob patterns.
-fn expand_macros(
+/// Expand `{{category_name}}` macros in a list of glob patterns. `file` and
+/// `context` (e.g. `role 'coder'`) locate the patterns in error messages.
+pub(crate) fn expand_macros(
     patterns: &[String],
     categories: &HashMap<String, Vec<String>>,
-    role_name: &str,
+    file: &str,
+    context: &str,
 ) -> Result<Vec<String>> {
     let re = macro_regex();
     let mut expanded = Vec::new();
@@ -218,10 +307,10 @@ fn expand_macros(
                 Some(cat_patterns) => expanded.extend(cat_patterns.iter().cloned()),
                 None => {
                     return Err(HookwiseError::ConfigParse {
-                        path: PathBuf::from("roles.yml"),
+                        path: PathBuf::from(file),
                         reason: format!(
-                            "role '{}': unknown category '{{{{{}}}}}'. Available: {:?}",
-                            role_name,
+                            "{}: unknown category '{{{{{}}}}}'. Available: {:?}",
+                            context,
                             name,
                             categories.keys().collect::<Vec<_>>()
                         ),
@@ -325,32 +414,71 @@ impl PathNormalizer {
 // ---------------------------------------------------------------------------
 
 /// Roles configuration loaded from roles.yml.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct RolesConfig {
     /// Semantic path categories. Merged over built-in defaults.
-    #[serde(default)]
+    #[serde(default, serialize_with = "sorted")]
     pub categories: HashMap<String, Vec<String>>,
 
+    #[serde(serialize_with = "sorted")]
     pub roles: HashMap<String, RoleDefinition>,
 }
 
 impl RolesConfig {
+    /// Add or replace a category. It is merged over the built-in ones when
+    /// the roles are expanded.
+    pub fn with_category<I, S>(mut self, name: impl Into<String>, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.categories
+            .insert(name.into(), patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Add or replace a role, keyed by its name.
+    pub fn with_role(mut self, role: RoleDefinition) -> Self {
+        self.roles.insert(role.name.clone(), role);
+        self
+    }
+
+    /// Merge the categories over the built-in ones and expand `{{category}}`
+    /// macros in every role, as loading does.
+    pub fn expanded(mut self) -> Result<Self> {
+        self.expand_categories()?;
+        Ok(self)
+    }
+
+    /// The config as `roles.yml` contents, categories and roles in name
+    /// order. A config built in code keeps its macros; a loaded one has
+    /// them expanded and carries every built-in category. Either loads
+    /// back to the same roles.
+    pub fn to_yaml(&self) -> Result<String> {
+        super::yaml_string(self, "roles")
+    }
+
+    /// Parse `roles.yml` contents and expand `{{category}}` macros.
+    pub fn from_yaml(contents: &str) -> Result<Self> {
+        Self::parse(contents, Path::new("roles.yml"))
+    }
+
     /// Load roles from a YAML file. Expands `{{category}}` macros.
     pub fn load_from(path: &Path) -> Result<Self> {
         if !path.exists() {
-            return Ok(Self {
-                categories: HashMap::new(),
-                roles: HashMap::new(),
-            });
+            return Ok(Self::default());
         }
         let contents = std::fs::read_to_string(path)?;
-        let mut config: Self =
-            serde_yaml::from_str(&contents).map_err(|e| HookwiseError::ConfigParse {
+        Self::parse(&contents, path)
+    }
+
+    fn parse(contents: &str, path: &Path) -> Result<Self> {
+        let config: Self =
+            serde_yaml::from_str(contents).map_err(|e| HookwiseError::ConfigParse {
                 path: path.to_path_buf(),
                 reason: e.to_string(),
             })?;
-        config.expand_categories()?;
-        Ok(config)
+        config.expanded()
     }
 
     /// Load roles from the project root. Checks `.hookwise/roles.yml`.
@@ -364,6 +492,121 @@ impl RolesConfig {
         self.roles.get(name)
     }
 
+    /// Role names in sorted order.
+    pub fn role_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.roles.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// The known role `name` was most likely meant to be: a case-insensitive
+    /// match, the only role it is a prefix of, or the nearest role within a
+    /// small edit distance. None when nothing is plausibly close.
+    pub fn closest_role(&self, name: &str) -> Option<&str> {
+        let wanted = name.to_lowercase();
+        if wanted.is_empty() {
+            return None;
+        }
+        let names = self.role_names();
+        if let Some(exact) = names.iter().find(|n| n.to_lowercase() == wanted) {
+            return Some(exact);
+        }
+        let prefixed: Vec<&&str> = names
+            .iter()
+            .filter(|n| n.to_lowercase().starts_with(&wanted))
+            .collect();
+        if wanted.chars().count() >= 3 && prefixed.len() == 1 {
+            return Some(prefixed[0]);
+        }
+
+        let max_distance = 1 + wanted.chars().count() / 4;
+        names
+            .into_iter()
+            .map(|n| (edit_distance(&wanted, &n.to_lowercase()), n))
+            .filter(|(d, _)| *d <= max_distance)
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, n)| n)
+    }
+
+    /// The role an unregistered session most likely wants, from the files
+    /// `changed` in its working tree and the project's `tracked` files
+    /// (both relative to the project root). Of the roles that may write
+    /// most of the changes, the one whose write access is narrowest for
+    /// what it covers wins, so mostly-test changes point at a tests-only
+    /// role rather than an unrestricted one. When the files settle nothing,
+    /// the one role `prompt` names (or a word starting like it, "tests" for
+    /// "tester") is suggested. None when nothing points anywhere.
+    pub fn recommend_role(
+        &self,
+        changed: &[String],
+        tracked: &[String],
+        prompt: Option<&str>,
+    ) -> Option<RoleRecommendation> {
+        let mut files: Vec<&str> = tracked.iter().chain(changed).map(String::as_str).collect();
+        files.sort_unstable();
+        files.dedup();
+
+        // (role, changed files it may write, project files it may write)
+        let mut best: Option<(&str, usize, usize)> = None;
+        for name in self.role_names() {
+            let Ok(policy) = CompiledPathPolicy::compile(&self.roles[name].paths, &[]) else {
+                continue;
+            };
+            let may_write = |path: &&str| {
+                policy.allow_write.is_match(path) && !policy.deny_write.is_match(path)
+            };
+            let covered = changed.iter().map(String::as_str).filter(may_write).count();
+            if covered * 2 <= changed.len() {
+                continue;
+            }
+            let breadth = files.iter().copied().filter(may_write).count();
+            // Highest share of its writable files changed, then most changes
+            let better = best.is_none_or(|(_, c, b)| {
+                let (mine, theirs) = (covered * b, c * breadth);
+                mine > theirs || (mine == theirs && covered > c)
+            });
+            if better {
+                best = Some((name, covered, breadth));
+            }
+        }
+        if let Some((role, covered, _)) = best {
+            return Some(RoleRecommendation {
+                role: role.to_string(),
+                reason: format!(
+                    "{} of {} changed files are ones it may write",
+                    covered,
+                    changed.len()
+                ),
+            });
+        }
+
+        let prompt = prompt?.to_lowercase();
+        let words: Vec<&str> = prompt
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .collect();
+        let mentioned: Vec<(&str, &str)> = self
+            .role_names()
+            .into_iter()
+            .filter_map(|name| {
+                let lower = name.to_lowercase();
+                let len = lower.chars().count();
+                let stem: String = lower.chars().take(len.saturating_sub(3).max(4)).collect();
+                words
+                    .iter()
+                    .find(|w| w.starts_with(&stem))
+                    .map(|w| (name, *w))
+            })
+            .collect();
+        match mentioned.as_slice() {
+            [(role, word)] => Some(RoleRecommendation {
+                role: role.to_string(),
+                reason: format!("the prompt mentions \"{}\"", word),
+            }),
+            _ => None,
+        }
+    }
+
     /// Build a PathNormalizer from this config's categories.
     pub fn normalizer(&self) -> Result<PathNormalizer> {
         PathNormalizer::new(&self.categories)
@@ -374,9 +617,12 @@ impl RolesConfig {
         let merged = self.merged_categories();
 
         for (role_name, role) in &mut self.roles {
-            role.paths.allow_write = expand_macros(&role.paths.allow_write, &merged, role_name)?;
-            role.paths.deny_write = expand_macros(&role.paths.deny_write, &merged, role_name)?;
-            role.paths.allow_read = expand_macros(&role.paths.allow_read, &merged, role_name)?;
+            let context = format!("role '{}'", role_name);
+            let expand =
+                |patterns: &[String]| expand_macros(patterns, &merged, "roles.yml", &context);
+            role.paths.allow_write = expand(&role.paths.allow_write)?;
+            role.paths.deny_write = expand(&role.paths.deny_write)?;
+            role.paths.allow_read = expand(&role.paths.allow_read)?;
         }
 
         // Store the merged categories for normalizer use
@@ -393,3 +639,25 @@ impl RolesConfig {
         merged
     }
 }
+
+/// A role suggested for an unregistered session, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleRecommendation {
+    pub role: String,
+    pub reason: String,
+}
+
+/// Levenshtein distance between two strings, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
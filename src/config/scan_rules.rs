@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{HookwiseError, Result};
+
+/// How serious a `hookwise scan` finding is. `--min-severity` sets the
+/// lowest one that fails the scan.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Severity of a built-in sanitizer finding kind: known token formats
+    /// and credentials are high, heuristic and encoded matches medium.
+    pub fn builtin(kind: &str) -> Self {
+        match kind {
+            "high_entropy" | "base64_encoded" | "url_encoded" => Self::Medium,
+            "redacted" => Self::Low,
+            _ => Self::High,
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Low => write!(f, "low"),
+            Self::Medium => write!(f, "medium"),
+            Self::High => write!(f, "high"),
+            Self::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// `.hookwise/scan-rules.yml`: extra rules for `hookwise scan`, merged over
+/// the sanitizer's built-in finding kinds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanRulesConfig {
+    #[serde(default)]
+    pub rules: Vec<ScanRule>,
+    /// Files larger than this are skipped (and counted in the summary).
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+}
+
+impl Default for ScanRulesConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_file_bytes: default_max_file_bytes(),
+        }
+    }
+}
+
+fn default_max_file_bytes() -> u64 {
+    1024 * 1024
+}
+
+/// A named scan rule. With a `pattern` it adds a regex check; without one
+/// it changes the severity or files of the built-in finding kind it names
+/// (e.g. `high_entropy` or `prefix:ghp_`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanRule {
+    /// Rule id in reports, e.g. `acme_api_key`.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Default: medium for new rules, the built-in severity otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Globs the scanned path must match, e.g. `*.tf` or `config/**`.
+    /// Empty: every file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+}
+
+impl ScanRulesConfig {
+    /// Load scan rules from a YAML file. Returns defaults if the file
+    /// doesn't exist.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        serde_yaml::from_str(&contents).map_err(|e| HookwiseError::ConfigParse {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    }
+
+    /// Load scan rules from the project root. Checks
+    /// `.hookwise/scan-rules.yml`.
+    pub fn load_project(project_root: &Path) -> Result<Self> {
+        Self::load_from(&project_root.join(".hookwise").join("scan-rules.yml"))
+    }
+}
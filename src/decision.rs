@@ -1,6 +1,9 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::hook_io::HookFormat;
 pub use crate::scope::ScopeLevel;
 
 /// The three possible permission states.
@@ -27,10 +30,26 @@ pub enum DecisionTier {
     Supervisor,
     /// Tier 4: human-in-the-loop
     Human,
+    /// Package install gating (registries, allowlist, advisories)
+    PackageGate,
+    /// kubectl/aws/gcloud/az read vs mutate vs destroy rules
+    CloudCli,
+    /// Force-push, protected branch, git config, tag and submodule rules
+    GitPolicy,
+    /// Secrets and content patterns in text written to files
+    ContentInspection,
+    /// Network-bound commands after sensitive reads in the same session
+    Exfiltration,
+    /// Writes into a path range another session holds a lock on
+    PathLock,
+    /// Fast allow for scratch and build output paths (pre-cascade)
+    EphemeralPath,
     /// Sensitive path default (pre-cascade)
     SensitivePath,
     /// Explicit override (human-set, deterministic)
     Override,
+    /// The cascade ran past `deadline.secs`
+    Deadline,
     /// Default fallback when no cascade tier resolved
     Default,
 }
@@ -52,6 +71,89 @@ pub struct DecisionMetadata {
 
     /// For similarity tiers: the similarity score.
     pub similarity_score: Option<f64>,
+
+    /// For path policy decisions on calls touching several paths: the
+    /// decision for each target, in the order they were found.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<TargetDecision>,
+
+    /// The policy author's message for a deny or ask on this call
+    /// (`messages` in policy.yml), rendered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// A remediation hint for a deny or ask (`hints` in policy.yml), e.g.
+    /// the role that may make this change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+
+    /// The built-in command risk score of the call (`command_risk` in
+    /// policy.yml).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_score: Option<f64>,
+
+    /// What the sanitizer redacted from the tool input, by kind. Values
+    /// are never recorded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<SecretFinding>,
+
+    /// The assistant whose hook call this decision answered. Unset for
+    /// decisions made outside a hook, such as approvals and synced rules.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<ClientInfo>,
+}
+
+/// The path policy's decision for one target of a multi-target call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetDecision {
+    /// The canonicalized target path.
+    pub path: String,
+    /// `None` when no rule covers the path.
+    pub decision: Option<Decision>,
+}
+
+/// How many secrets of one kind the sanitizer redacted from a call, e.g.
+/// `prefix:ghp_`, `bearer_token` or `high_entropy`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretFinding {
+    pub kind: String,
+    pub count: usize,
+}
+
+/// The assistant a hook call came from: its payload format, its name and,
+/// when the payload carries it, its version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientInfo {
+    pub format: HookFormat,
+    /// e.g. `claude-code` or `gemini-cli`.
+    pub assistant: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl std::fmt::Display for ClientInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{} {}", self.assistant, version),
+            None => write!(f, "{}", self.assistant),
+        }
+    }
+}
+
+/// Decision counts by assistant (and version), with decisions that
+/// predate client recording or came from outside a hook under
+/// `unrecorded`.
+pub fn count_by_client(records: &[DecisionRecord]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for record in records {
+        let client = record
+            .metadata
+            .client
+            .as_ref()
+            .map_or_else(|| "unrecorded".to_string(), ToString::to_string);
+        *counts.entry(client).or_insert(0) += 1;
+    }
+    counts
 }
 
 /// A unique key identifying a cached decision.
@@ -78,6 +180,19 @@ impl std::fmt::Display for Decision {
     }
 }
 
+impl std::str::FromStr for Decision {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "allow" => Ok(Decision::Allow),
+            "deny" => Ok(Decision::Deny),
+            "ask" => Ok(Decision::Ask),
+            _ => Err(format!("unknown decision: {s}")),
+        }
+    }
+}
+
 impl Decision {
     /// Returns the precedence rank (higher = more authoritative).
     /// DENY > ASK > ALLOW
@@ -115,8 +230,16 @@ impl std::str::FromStr for ScopeLevel {
     }
 }
 
+/// Version of the [`DecisionRecord`] JSON layout, written into every
+/// record as `schema_version`. Records without it predate versioning and
+/// read as version 1. Bump it, and teach `StoredRecord::upgrade` the old
+/// layout, whenever a change would break a consumer parsing the rule
+/// files. `hookwise schema` prints the layout as JSON Schema.
+pub const SCHEMA_VERSION: u32 = 2;
+
 /// A complete decision record, stored in JSONL and used in the cache.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "StoredRecord")]
 pub struct DecisionRecord {
     /// The cache key for this decision.
     pub key: CacheKey,
@@ -139,3 +262,113 @@ pub struct DecisionRecord {
     /// The session ID that triggered this decision (for audit trail).
     pub session_id: String,
 }
+
+/// A record as written: the current fields, led by `schema_version`.
+#[derive(Serialize)]
+struct RecordOut<'a> {
+    schema_version: u32,
+    key: &'a CacheKey,
+    decision: Decision,
+    metadata: &'a DecisionMetadata,
+    timestamp: DateTime<Utc>,
+    scope: ScopeLevel,
+    file_path: &'a Option<String>,
+    session_id: &'a str,
+}
+
+impl Serialize for DecisionRecord {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        RecordOut {
+            schema_version: SCHEMA_VERSION,
+            key: &self.key,
+            decision: self.decision,
+            metadata: &self.metadata,
+            timestamp: self.timestamp,
+            scope: self.scope,
+            file_path: &self.file_path,
+            session_id: &self.session_id,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// A record as read, from any schema version up to [`SCHEMA_VERSION`].
+#[derive(Deserialize)]
+struct StoredRecord {
+    #[serde(default = "unversioned")]
+    schema_version: u32,
+    key: CacheKey,
+    decision: Decision,
+    metadata: DecisionMetadata,
+    timestamp: DateTime<Utc>,
+    scope: ScopeLevel,
+    #[serde(default)]
+    file_path: Option<String>,
+    #[serde(default)]
+    session_id: String,
+}
+
+fn unversioned() -> u32 {
+    1
+}
+
+impl StoredRecord {
+    /// Convert a record of any supported version to the current layout.
+    /// Version 1 differs only in lacking `schema_version`; fields added
+    /// since then read with their defaults.
+    fn upgrade(self) -> std::result::Result<DecisionRecord, String> {
+        match self.schema_version {
+            1 | SCHEMA_VERSION => Ok(DecisionRecord {
+                key: self.key,
+                decision: self.decision,
+                metadata: self.metadata,
+                timestamp: self.timestamp,
+                scope: self.scope,
+                file_path: self.file_path,
+                session_id: self.session_id,
+            }),
+            v if v > SCHEMA_VERSION => Err(format!(
+                "decision record schema_version {} is newer than this hookwise reads ({}); upgrade hookwise",
+                v, SCHEMA_VERSION
+            )),
+            v => Err(format!("unknown decision record schema_version {}", v)),
+        }
+    }
+}
+
+impl TryFrom<StoredRecord> for DecisionRecord {
+    type Error = String;
+
+    fn try_from(stored: StoredRecord) -> std::result::Result<Self, Self::Error> {
+        stored.upgrade()
+    }
+}
+
+impl DecisionRecord {
+    /// Short stable id derived from the cache key and timestamp, used to
+    /// refer to a stored decision (e.g. `hookwise feedback <id>`).
+    pub fn id(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let hash = Sha256::digest(format!(
+            "{}\0{}\0{}\0{}",
+            self.key.role,
+            self.key.tool,
+            self.key.sanitized_input,
+            self.timestamp.to_rfc3339()
+        ));
+        format!("dec-{}", &format!("{:x}", hash)[..12])
+    }
+
+    /// Targets that kept this call from being allowed (denied or needing
+    /// approval), or empty if the decision is not per-target.
+    pub fn blocked_targets(&self) -> Vec<&TargetDecision> {
+        self.metadata
+            .targets
+            .iter()
+            .filter(|t| matches!(t.decision, Some(Decision::Deny | Decision::Ask)))
+            .collect()
+    }
+}
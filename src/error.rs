@@ -38,9 +38,30 @@ pub enum HookwiseError {
     #[error("human decision timeout after {timeout_secs}s")]
     HumanTimeout { timeout_secs: u64 },
 
+    #[error("notification error: {reason}")]
+    Notification { reason: String },
+
+    #[error("advisory lookup error: {reason}")]
+    Advisory { reason: String },
+
+    #[error("disable refused: {reason}")]
+    DisableRefused { reason: String },
+
+    #[error("identity error: {reason}")]
+    Identity { reason: String },
+
     #[error("ipc error: {reason}")]
     Ipc { reason: String },
 
+    #[error("handed off to `hookwise check`: {reason}")]
+    Handoff { reason: String },
+
+    #[error("{what} not compiled in (rebuild with --features {feature})")]
+    NotCompiledIn {
+        what: &'static str,
+        feature: &'static str,
+    },
+
     #[error("socket not found at {path}")]
     SocketNotFound { path: PathBuf },
 
@@ -0,0 +1,125 @@
+//! Deterministic failure injection for `hookwise check`, to exercise how the
+//! cascade degrades when a component is down. Enabled with the hidden
+//! `--inject-failure` flag or `HOOKWISE_INJECT_FAILURE=supervisor,storage`.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use clap::ValueEnum;
+
+use crate::cascade::supervisor::{SupervisorBackend, SupervisorRequest};
+use crate::config::PolicyConfig;
+use crate::decision::DecisionRecord;
+use crate::error::{HookwiseError, Result};
+use crate::scope::ScopeLevel;
+use crate::storage::{SecretFinding, StorageBackend};
+
+/// Environment variable listing faults to inject, comma-separated.
+pub const FAULT_ENV: &str = "HOOKWISE_INJECT_FAILURE";
+
+/// A component that can be made to fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Fault {
+    /// Every supervisor request errors, as if the socket or API were down.
+    Supervisor,
+    /// Every rule storage read and write errors.
+    Storage,
+    /// The embedding model fails to load.
+    Embedding,
+}
+
+impl Fault {
+    /// The error the failing component returns.
+    pub fn error(self) -> HookwiseError {
+        let reason = "injected failure".to_string();
+        match self {
+            Fault::Supervisor => HookwiseError::Supervisor { reason },
+            Fault::Storage => HookwiseError::Storage { reason },
+            Fault::Embedding => HookwiseError::Embedding { reason },
+        }
+    }
+}
+
+/// The faults to inject: those given on the command line plus those named
+/// in `env` (the value of [`FAULT_ENV`]).
+pub fn injected(flags: &[Fault], env: Option<&str>) -> Result<Vec<Fault>> {
+    let mut faults = flags.to_vec();
+    for name in env.unwrap_or_default().split(',').map(str::trim) {
+        if name.is_empty() {
+            continue;
+        }
+        let fault = Fault::from_str(name, true).map_err(|_| HookwiseError::InvalidPolicy {
+            reason: format!(
+                "unknown {} value '{}' (expected supervisor, storage or embedding)",
+                FAULT_ENV, name
+            ),
+        })?;
+        if !faults.contains(&fault) {
+            faults.push(fault);
+        }
+    }
+    Ok(faults)
+}
+
+/// A supervisor backend that always fails.
+pub struct FailingSupervisor;
+
+#[async_trait]
+impl SupervisorBackend for FailingSupervisor {
+    async fn evaluate(
+        &self,
+        _request: &SupervisorRequest,
+        _policy: &PolicyConfig,
+    ) -> Result<DecisionRecord> {
+        Err(Fault::Supervisor.error())
+    }
+}
+
+/// Rule storage that always fails.
+pub struct FailingStorage;
+
+impl StorageBackend for FailingStorage {
+    fn load_decisions(&self, _scope: ScopeLevel) -> Result<Vec<DecisionRecord>> {
+        Err(Fault::Storage.error())
+    }
+
+    fn load_decisions_for_role(
+        &self,
+        _scope: ScopeLevel,
+        _role: &str,
+    ) -> Result<Vec<DecisionRecord>> {
+        Err(Fault::Storage.error())
+    }
+
+    fn save_decision(&self, _record: &DecisionRecord) -> Result<()> {
+        Err(Fault::Storage.error())
+    }
+
+    fn invalidate_role(&self, _scope: ScopeLevel, _role: &str) -> Result<()> {
+        Err(Fault::Storage.error())
+    }
+
+    fn invalidate_all(&self, _scope: ScopeLevel) -> Result<()> {
+        Err(Fault::Storage.error())
+    }
+
+    fn remove_decisions(
+        &self,
+        _scope: ScopeLevel,
+        _predicate: &dyn Fn(&DecisionRecord) -> bool,
+    ) -> Result<usize> {
+        Err(Fault::Storage.error())
+    }
+
+    fn compact(&self, _scope: ScopeLevel) -> Result<usize> {
+        Err(Fault::Storage.error())
+    }
+
+    fn rebuild_index(&self, _scope: ScopeLevel) -> Result<()> {
+        Err(Fault::Storage.error())
+    }
+
+    fn scan_for_secrets(&self, _path: &Path) -> Result<Vec<SecretFinding>> {
+        Err(Fault::Storage.error())
+    }
+}
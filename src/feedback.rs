@@ -0,0 +1,107 @@
+//! Corrections of automatic decisions (`hookwise feedback`). Kept in
+//! `.hookwise/feedback.jsonl` so they are shared with the project, and fed
+//! to the supervisor as precedents.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::decision::{CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier};
+use crate::error::Result;
+use crate::scope::ScopeLevel;
+use crate::storage::StorageBackend;
+
+/// A human's correction of one decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackRecord {
+    /// Id of the corrected decision ([`DecisionRecord::id`]).
+    pub decision_id: String,
+    pub key: CacheKey,
+    /// What was decided, and by which tier.
+    pub original: Decision,
+    pub original_tier: DecisionTier,
+    /// What should have been decided.
+    pub corrected: Decision,
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl FeedbackRecord {
+    pub fn new(record: &DecisionRecord, corrected: Decision, reason: &str) -> Self {
+        Self {
+            decision_id: record.id(),
+            key: record.key.clone(),
+            original: record.decision,
+            original_tier: record.metadata.tier,
+            corrected,
+            reason: reason.to_string(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// The correction as a human decision, for the supervisor precedent pool.
+    pub fn as_decision(&self) -> DecisionRecord {
+        DecisionRecord {
+            key: self.key.clone(),
+            decision: self.corrected,
+            metadata: DecisionMetadata {
+                tier: DecisionTier::Human,
+                confidence: 1.0,
+                reason: self.reason.clone(),
+                matched_key: None,
+                similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
+            },
+            timestamp: self.timestamp,
+            scope: ScopeLevel::Project,
+            file_path: None,
+            session_id: "feedback".to_string(),
+        }
+    }
+}
+
+/// Returns the feedback log path under a project's `.hookwise/` directory.
+pub fn feedback_log_path(hookwise_dir: &Path) -> PathBuf {
+    hookwise_dir.join("feedback.jsonl")
+}
+
+/// Append a correction, creating parent directories as needed.
+pub fn append_feedback(path: &Path, record: &FeedbackRecord) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// Load all corrections, oldest first, skipping malformed lines.
+pub fn load_feedback(path: &Path) -> Vec<FeedbackRecord> {
+    std::fs::read_to_string(path)
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Remove cached project decisions for `key` and those derived from it by
+/// similarity, so the next matching call is evaluated afresh. Returns the
+/// number removed.
+pub fn invalidate_derived(storage: &dyn StorageBackend, key: &CacheKey) -> Result<usize> {
+    storage.remove_decisions(ScopeLevel::Project, &|r| {
+        r.key == *key || r.metadata.matched_key.as_ref() == Some(key)
+    })
+}
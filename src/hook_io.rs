@@ -1,16 +1,27 @@
 use serde::{Deserialize, Serialize};
 
-use crate::decision::Decision;
+use crate::decision::{ClientInfo, Decision, DecisionRecord};
 use crate::error::Result;
 
 /// Hook format selector for multi-ecosystem support.
-#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
 pub enum HookFormat {
     #[default]
     Claude,
     Gemini,
 }
 
+impl HookFormat {
+    /// The assistant that sends payloads in this format.
+    pub fn assistant(self) -> &'static str {
+        match self {
+            HookFormat::Claude => "claude-code",
+            HookFormat::Gemini => "gemini-cli",
+        }
+    }
+}
+
 /// The JSON payload sent to hooks on stdin.
 /// Works for both Claude Code (PreToolUse) and Gemini CLI (BeforeTool).
 /// Extra Gemini fields are ignored via `#[serde(default)]`.
@@ -31,6 +42,59 @@ pub struct HookInput {
     pub transcript_path: Option<String>,
     #[serde(default)]
     pub mcp_context: Option<serde_json::Value>,
+    /// The assistant's version, for assistants that send it.
+    #[serde(default)]
+    pub client_version: Option<String>,
+}
+
+impl HookInput {
+    /// The payload `format`'s assistant sends for a tool call, for
+    /// `hookwise dev simulate-hook`.
+    pub fn simulated(
+        format: HookFormat,
+        session_id: &str,
+        tool_name: &str,
+        tool_input: serde_json::Value,
+        cwd: &std::path::Path,
+    ) -> Self {
+        let (event, permission_mode, timestamp) = match format {
+            HookFormat::Claude => ("PreToolUse", Some("default".to_string()), None),
+            HookFormat::Gemini => ("BeforeTool", None, Some(chrono::Utc::now().to_rfc3339())),
+        };
+        Self {
+            session_id: session_id.to_string(),
+            tool_name: tool_name.to_string(),
+            tool_input,
+            cwd: cwd.to_string_lossy().into_owned(),
+            permission_mode,
+            hook_event_name: Some(event.to_string()),
+            timestamp,
+            transcript_path: None,
+            mcp_context: None,
+            client_version: None,
+        }
+    }
+
+    /// The assistant this call came from, recorded on its decision.
+    pub fn client(&self, format: HookFormat) -> ClientInfo {
+        ClientInfo {
+            format,
+            assistant: format.assistant().to_string(),
+            version: self.client_version.clone(),
+        }
+    }
+}
+
+/// The JSON payload sent to prompt hooks on stdin: Claude Code's
+/// `UserPromptSubmit` or Gemini CLI's `BeforeAgent`. Carries no tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptHookInput {
+    pub session_id: String,
+    pub cwd: String,
+    #[serde(default)]
+    pub hook_event_name: Option<String>,
+    #[serde(default)]
+    pub prompt: Option<String>,
 }
 
 /// Claude Code hook output: nested `hookSpecificOutput.permissionDecision`.
@@ -45,6 +109,11 @@ pub struct HookOutput {
 pub struct HookSpecificOutput {
     #[serde(rename = "permissionDecision")]
     pub permission_decision: String,
+    #[serde(
+        rename = "permissionDecisionReason",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub permission_decision_reason: Option<String>,
 }
 
 /// Gemini CLI hook output: flat `decision` field.
@@ -55,15 +124,51 @@ pub struct GeminiHookOutput {
     pub reason: Option<String>,
 }
 
+/// Claude Code prompt hook output: `hookSpecificOutput.additionalContext`
+/// is added to the model's context alongside the user's prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextHookOutput {
+    #[serde(rename = "hookSpecificOutput")]
+    pub hook_specific_output: ContextSpecificOutput,
+}
+
+/// The context payload within Claude's ContextHookOutput.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextSpecificOutput {
+    #[serde(rename = "hookEventName")]
+    pub hook_event_name: String,
+    #[serde(rename = "additionalContext")]
+    pub additional_context: String,
+}
+
+impl ContextHookOutput {
+    /// Create a `UserPromptSubmit` output injecting `context`.
+    pub fn new(context: String) -> Self {
+        Self {
+            hook_specific_output: ContextSpecificOutput {
+                hook_event_name: "UserPromptSubmit".into(),
+                additional_context: context,
+            },
+        }
+    }
+}
+
 impl HookOutput {
     /// Create a new Claude HookOutput with the given decision.
     pub fn new(decision: Decision) -> Self {
         Self {
             hook_specific_output: HookSpecificOutput {
                 permission_decision: decision_str(decision),
+                permission_decision_reason: None,
             },
         }
     }
+
+    /// Attach a reason, shown to the agent on deny and to the user on ask.
+    pub fn with_reason(mut self, reason: Option<String>) -> Self {
+        self.hook_specific_output.permission_decision_reason = reason;
+        self
+    }
 }
 
 impl GeminiHookOutput {
@@ -84,6 +189,74 @@ fn decision_str(decision: Decision) -> String {
     }
 }
 
+/// The reason shown to the agent: the policy author's message, followed by
+/// the blocked targets of a multi-target call and the remediation hint.
+pub fn hook_reason(record: &DecisionRecord) -> Option<String> {
+    let parts: Vec<String> = [
+        record.metadata.message.clone(),
+        blocked_targets_reason(record),
+        record
+            .metadata
+            .suggestion
+            .as_ref()
+            .map(|s| format!("suggestion: {}", s)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+/// For a call some of whose targets were blocked by path policy, a reason
+/// listing them and the targets that were not, so the agent can retry
+/// with only the allowed subset.
+pub fn blocked_targets_reason(record: &DecisionRecord) -> Option<String> {
+    let blocked = record.blocked_targets();
+    if record.decision == Decision::Allow || blocked.is_empty() {
+        return None;
+    }
+    let blocked_list: Vec<String> = blocked
+        .iter()
+        .filter_map(|t| t.decision.map(|d| format!("{} ({})", t.path, d)))
+        .collect();
+    let mut reason = format!("blocked targets: {}", blocked_list.join(", "));
+    let rest: Vec<&str> = record
+        .metadata
+        .targets
+        .iter()
+        .filter(|t| !matches!(t.decision, Some(Decision::Deny | Decision::Ask)))
+        .map(|t| t.path.as_str())
+        .collect();
+    if !rest.is_empty() {
+        reason.push_str(&format!(
+            "; not blocked: {}. Retry with only the targets that were not blocked.",
+            rest.join(", ")
+        ));
+    }
+    Some(reason)
+}
+
+/// Longest reason written to the hook output. Assistants truncate or
+/// reject oversized hook output, so longer reasons are cut here.
+pub const MAX_REASON_BYTES: usize = 4096;
+
+/// `reason` cut to [`MAX_REASON_BYTES`] at a char boundary, ending in a
+/// note of how much was dropped.
+pub fn cap_reason(reason: String) -> String {
+    if reason.len() <= MAX_REASON_BYTES {
+        return reason;
+    }
+    let mut end = MAX_REASON_BYTES - 64;
+    while !reason.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}... ({} more bytes truncated)",
+        &reason[..end],
+        reason.len() - end
+    )
+}
+
 /// Read the hook input from stdin.
 pub fn read_hook_input() -> Result<HookInput> {
     let stdin = std::io::stdin();
@@ -91,20 +264,49 @@ pub fn read_hook_input() -> Result<HookInput> {
     Ok(input)
 }
 
+/// Read the prompt hook input from stdin.
+pub fn read_prompt_hook_input() -> Result<PromptHookInput> {
+    let stdin = std::io::stdin();
+    let input: PromptHookInput = serde_json::from_reader(stdin.lock())?;
+    Ok(input)
+}
+
+/// Write a serializable hook output to stdout and flush it.
+pub fn write_json_output<T: Serialize>(output: &T) -> Result<()> {
+    use std::io::Write;
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+    serde_json::to_writer(&mut handle, output)?;
+    handle.flush()?;
+    Ok(())
+}
+
 /// Write the hook output to stdout in the appropriate format.
 /// Explicitly flushes stdout to ensure data is written before any
 /// subsequent `std::process::exit()` call (which does not flush Rust buffers).
 pub fn write_hook_output(decision: Decision, format: HookFormat) -> Result<()> {
+    write_hook_output_with_reason(decision, None, format)
+}
+
+/// Write the hook output with an optional reason, capped at
+/// [`MAX_REASON_BYTES`]. Both formats carry it: Claude as
+/// `permissionDecisionReason`, Gemini as `reason`.
+pub fn write_hook_output_with_reason(
+    decision: Decision,
+    reason: Option<String>,
+    format: HookFormat,
+) -> Result<()> {
     use std::io::Write;
+    let reason = reason.map(cap_reason);
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
     match format {
         HookFormat::Claude => {
-            let output = HookOutput::new(decision);
+            let output = HookOutput::new(decision).with_reason(reason);
             serde_json::to_writer(&mut handle, &output)?;
         }
         HookFormat::Gemini => {
-            let output = GeminiHookOutput::new(decision, None);
+            let output = GeminiHookOutput::new(decision, reason);
             serde_json::to_writer(&mut handle, &output)?;
         }
     }
@@ -0,0 +1,420 @@
+//! Who is running hookwise: the user and org recorded on sessions and
+//! audit events and checked against `approvals` when answering the queue.
+//! Resolved by the provider in the global config (`identity`): the
+//! environment, git config, an OIDC ID token file, or a command.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{
+    ApprovalConfig, CommandIdentityConfig, GlobalConfig, IdentityConfig, OidcIdentityConfig,
+};
+use crate::error::{HookwiseError, Result};
+
+/// Clock skew tolerated on token `exp`/`nbf`.
+const LEEWAY_SECS: i64 = 60;
+
+/// A resolved identity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Identity {
+    pub user: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    /// The provider that resolved it: `env`, `git`, `oidc` or `command`.
+    pub provider: String,
+    /// Whether the provider vouches for the identity rather than reading a
+    /// setting the user controls.
+    #[serde(default)]
+    pub verified: bool,
+    /// When the identity stops being valid (the token's `exp`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Identity {
+    fn unverified(user: String, org: Option<String>, provider: &str) -> Self {
+        Self {
+            user,
+            org,
+            provider: provider.to_string(),
+            verified: false,
+            expires_at: None,
+        }
+    }
+}
+
+/// A source of identities.
+pub trait IdentityProvider: Send + Sync {
+    /// Resolve the identity for a project directory.
+    fn resolve(&self, cwd: &Path) -> Result<Identity>;
+}
+
+/// Build the provider for an `identity` config.
+pub fn provider(config: &IdentityConfig) -> Box<dyn IdentityProvider> {
+    match config {
+        IdentityConfig::Env => Box::new(EnvIdentity),
+        IdentityConfig::Git => Box::new(GitIdentity),
+        IdentityConfig::Oidc(config) => Box::new(OidcIdentity::new(config.clone())),
+        IdentityConfig::Command(config) => Box::new(CommandIdentity::new(config.clone())),
+    }
+}
+
+/// Identities resolved by this process, per project directory.
+static IDENTITIES: LazyLock<DashMap<PathBuf, Identity>> = LazyLock::new(DashMap::new);
+
+/// The identity for `cwd` from the globally configured provider, cached for
+/// the life of the process (or until the token expires). When the provider
+/// fails, falls back to the unverified environment identity with a warning.
+pub fn current(cwd: &Path) -> Identity {
+    if let Some(identity) = IDENTITIES.get(cwd) {
+        if identity.expires_at.is_none_or(|at| at > Utc::now()) {
+            return identity.clone();
+        }
+    }
+    let config = match GlobalConfig::load() {
+        Ok(config) => config.map(|c| c.identity).unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("ignoring unreadable global config: {}", e);
+            IdentityConfig::default()
+        }
+    };
+    let identity = provider(&config).resolve(cwd).unwrap_or_else(|e| {
+        eprintln!("hookwise: {}; using the environment identity", e);
+        env_identity()
+    });
+    IDENTITIES.insert(cwd.to_path_buf(), identity.clone());
+    identity
+}
+
+/// Check that `identity` may answer pending decisions under `approvals`.
+pub fn check_approver(approvals: &ApprovalConfig, identity: &Identity) -> Result<()> {
+    if approvals.require_verified && !identity.verified {
+        return Err(HookwiseError::Identity {
+            reason: format!(
+                "approvals require a verified identity, but '{}' comes from the {} provider",
+                identity.user, identity.provider
+            ),
+        });
+    }
+    if approvals.approvers.is_empty() {
+        return Ok(());
+    }
+    if is_listed(&approvals.approvers, identity) {
+        Ok(())
+    } else {
+        Err(HookwiseError::Identity {
+            reason: format!("'{}' is not an approver", identity.user),
+        })
+    }
+}
+
+/// Whether `identity` matches one of `patterns` (emails or user names, `*`
+/// globs allowed, case-insensitive).
+pub fn is_listed(patterns: &[String], identity: &Identity) -> bool {
+    let user = identity.user.to_lowercase();
+    patterns.iter().any(|pattern| {
+        globset::Glob::new(&pattern.to_lowercase())
+            .map(|g| g.compile_matcher().is_match(&user))
+            .unwrap_or(false)
+    })
+}
+
+/// The current identity for the working directory, if `approvals` lets it
+/// answer pending decisions.
+pub fn approver(approvals: &ApprovalConfig) -> Result<Identity> {
+    let cwd = std::env::current_dir()?;
+    let identity = current(&cwd);
+    check_approver(approvals, &identity)?;
+    Ok(identity)
+}
+
+/// `HOOKWISE_USER` (else `USER`/`USERNAME`) and `HOOKWISE_ORG`.
+pub struct EnvIdentity;
+
+fn env_identity() -> Identity {
+    let user = std::env::var("HOOKWISE_USER")
+        .or_else(|_| std::env::var("USER"))
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".into());
+    Identity::unverified(user, std::env::var("HOOKWISE_ORG").ok(), "env")
+}
+
+impl IdentityProvider for EnvIdentity {
+    fn resolve(&self, _cwd: &Path) -> Result<Identity> {
+        Ok(env_identity())
+    }
+}
+
+/// `git config user.email` in the project.
+pub struct GitIdentity;
+
+impl IdentityProvider for GitIdentity {
+    fn resolve(&self, cwd: &Path) -> Result<Identity> {
+        let output = Command::new("git")
+            .args(["config", "user.email"])
+            .current_dir(cwd)
+            .output()?;
+        let email = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || email.is_empty() {
+            return Err(HookwiseError::Identity {
+                reason: "git config user.email is not set".into(),
+            });
+        }
+        Ok(Identity::unverified(email, None, "git"))
+    }
+}
+
+/// Claims of an OIDC ID token file, verified against a JWKS file when one
+/// is configured.
+pub struct OidcIdentity {
+    config: OidcIdentityConfig,
+}
+
+impl OidcIdentity {
+    pub fn new(config: OidcIdentityConfig) -> Self {
+        Self { config }
+    }
+
+    /// Check a compact JWT and return its identity.
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Result<Identity> {
+        let parts: Vec<&str> = token.trim().split('.').collect();
+        let [header, payload, signature] = parts[..] else {
+            return Err(oidc_error("token is not a compact JWT"));
+        };
+        let header: serde_json::Value = serde_json::from_slice(&decode(header)?)?;
+        let claims: serde_json::Value = serde_json::from_slice(&decode(payload)?)?;
+
+        let verified = match &self.config.jwks_file {
+            Some(path) => {
+                let jwks: serde_json::Value =
+                    serde_json::from_str(&std::fs::read_to_string(expand_home(path))?)?;
+                let message = format!("{}.{}", parts[0], parts[1]);
+                verify_signature(&header, &jwks, message.as_bytes(), &decode(signature)?)?;
+                true
+            }
+            None => false,
+        };
+
+        let time = |claim: &str| {
+            claims
+                .get(claim)
+                .and_then(|v| v.as_i64())
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        };
+        let expires_at = time("exp");
+        if expires_at.is_some_and(|exp| exp.timestamp() + LEEWAY_SECS <= now.timestamp()) {
+            return Err(oidc_error("token has expired"));
+        }
+        if time("nbf").is_some_and(|nbf| nbf.timestamp() - LEEWAY_SECS > now.timestamp()) {
+            return Err(oidc_error("token is not valid yet"));
+        }
+        if let Some(issuer) = &self.config.issuer {
+            if claims.get("iss").and_then(|v| v.as_str()) != Some(issuer) {
+                return Err(oidc_error(&format!("token was not issued by {issuer}")));
+            }
+        }
+        if let Some(audience) = &self.config.audience {
+            let matches = match claims.get("aud") {
+                Some(serde_json::Value::String(aud)) => aud == audience,
+                Some(serde_json::Value::Array(auds)) => {
+                    auds.iter().any(|a| a.as_str() == Some(audience))
+                }
+                _ => false,
+            };
+            if !matches {
+                return Err(oidc_error(&format!("token audience is not {audience}")));
+            }
+        }
+
+        let claim = |name: &str| claims.get(name).and_then(|v| v.as_str()).map(String::from);
+        let user = claim(&self.config.user_claim).ok_or_else(|| {
+            oidc_error(&format!("token has no '{}' claim", self.config.user_claim))
+        })?;
+        Ok(Identity {
+            user,
+            org: self.config.org_claim.as_deref().and_then(claim),
+            provider: "oidc".into(),
+            verified,
+            expires_at,
+        })
+    }
+}
+
+impl IdentityProvider for OidcIdentity {
+    fn resolve(&self, _cwd: &Path) -> Result<Identity> {
+        let path = expand_home(&self.config.token_file);
+        let token = std::fs::read_to_string(&path)
+            .map_err(|e| oidc_error(&format!("cannot read token file {}: {e}", path.display())))?;
+        self.verify(&token, Utc::now())
+    }
+}
+
+fn oidc_error(reason: &str) -> HookwiseError {
+    HookwiseError::Identity {
+        reason: format!("oidc: {reason}"),
+    }
+}
+
+fn decode(part: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(part.trim_end_matches('='))
+        .map_err(|e| oidc_error(&format!("invalid base64url: {e}")))
+}
+
+/// Verify an RS256 or ES256 signature with the JWKS key named by the
+/// header's `kid` (or the only key, when there is no `kid`).
+fn verify_signature(
+    header: &serde_json::Value,
+    jwks: &serde_json::Value,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    use ring::signature::{self, UnparsedPublicKey};
+
+    let alg = header.get("alg").and_then(|v| v.as_str()).unwrap_or("");
+    let kid = header.get("kid").and_then(|v| v.as_str());
+    let keys = jwks
+        .get("keys")
+        .and_then(|k| k.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let key = match kid {
+        Some(kid) => keys
+            .iter()
+            .find(|k| k.get("kid").and_then(|v| v.as_str()) == Some(kid)),
+        None if keys.len() == 1 => keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| oidc_error("no matching key in the JWKS"))?;
+    let field = |name: &str| -> Result<Vec<u8>> {
+        let value = key
+            .get(name)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| oidc_error(&format!("JWKS key has no '{name}'")))?;
+        decode(value)
+    };
+
+    let result = match alg {
+        "RS256" => signature::RsaPublicKeyComponents {
+            n: field("n")?,
+            e: field("e")?,
+        }
+        .verify(&signature::RSA_PKCS1_2048_8192_SHA256, message, signature),
+        "ES256" => {
+            let mut point = vec![0x04];
+            point.extend(field("x")?);
+            point.extend(field("y")?);
+            UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_FIXED, point)
+                .verify(message, signature)
+        }
+        other => {
+            return Err(oidc_error(&format!(
+                "unsupported signing algorithm '{other}'"
+            )))
+        }
+    };
+    result.map_err(|_| oidc_error("token signature is invalid"))
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// A command printing the user name, or JSON `{"user": ..., "org": ...}`.
+pub struct CommandIdentity {
+    config: CommandIdentityConfig,
+}
+
+#[derive(Deserialize)]
+struct CommandOutput {
+    user: String,
+    #[serde(default)]
+    org: Option<String>,
+}
+
+impl CommandIdentity {
+    pub fn new(config: CommandIdentityConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl IdentityProvider for CommandIdentity {
+    fn resolve(&self, cwd: &Path) -> Result<Identity> {
+        let error = |reason: String| HookwiseError::Identity {
+            reason: format!("identity command: {reason}"),
+        };
+        let (program, args) = self
+            .config
+            .command
+            .split_first()
+            .ok_or_else(|| error("no command configured".into()))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| error(format!("cannot run {program}: {e}")))?;
+
+        // Read on a thread so a chatty command can't block on a full pipe.
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let reader = std::thread::spawn(move || {
+            let mut out = String::new();
+            stdout.read_to_string(&mut out).map(|_| out)
+        });
+        let deadline = Instant::now() + Duration::from_secs(self.config.timeout_secs);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(error(format!(
+                    "timed out after {}s",
+                    self.config.timeout_secs
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        let out = reader
+            .join()
+            .map_err(|_| error("output reader panicked".into()))??;
+        if !status.success() {
+            return Err(error(format!("exited with {status}")));
+        }
+
+        let out = out.trim();
+        let parsed = if out.starts_with('{') {
+            serde_json::from_str::<CommandOutput>(out)?
+        } else {
+            CommandOutput {
+                user: out.lines().next().unwrap_or("").trim().to_string(),
+                org: None,
+            }
+        };
+        if parsed.user.is_empty() {
+            return Err(error("printed no user".into()));
+        }
+        Ok(Identity {
+            user: parsed.user,
+            org: parsed.org,
+            provider: "command".into(),
+            verified: true,
+            expires_at: None,
+        })
+    }
+}
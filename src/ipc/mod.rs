@@ -1,12 +1,15 @@
 pub mod pending_queue;
 pub mod socket_client;
+#[cfg(feature = "serve")]
 pub mod socket_server;
+pub mod transport;
 
 use serde::{Deserialize, Serialize};
 
 use crate::decision::{Decision, DecisionMetadata};
 
-/// IPC request sent from worker hook to supervisor via Unix socket.
+/// IPC request sent from worker hook to supervisor via Unix socket (named
+/// pipe on Windows).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpcRequest {
     pub session_id: String,
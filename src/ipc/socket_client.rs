@@ -1,12 +1,12 @@
 use std::path::PathBuf;
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::UnixStream;
 
 use crate::error::{HookwiseError, Result};
+use crate::ipc::transport;
 use crate::ipc::{IpcRequest, IpcResponse};
 
-/// Unix socket client for worker hooks to connect to the supervisor.
+/// IPC client (Unix socket, or named pipe on Windows) for worker hooks to connect to the supervisor.
 pub struct IpcClient {
     socket_path: PathBuf,
     timeout_secs: u64,
@@ -22,20 +22,10 @@ impl IpcClient {
 
     /// Send a request and wait for a response.
     pub async fn request(&self, req: &IpcRequest) -> Result<IpcResponse> {
-        if !self.socket_path.exists() {
-            return Err(HookwiseError::SocketNotFound {
-                path: self.socket_path.clone(),
-            });
-        }
-
         let timeout = std::time::Duration::from_secs(self.timeout_secs);
 
         let result = tokio::time::timeout(timeout, async {
-            let mut stream = UnixStream::connect(&self.socket_path).await.map_err(|e| {
-                HookwiseError::Ipc {
-                    reason: format!("connect failed: {}", e),
-                }
-            })?;
+            let mut stream = transport::connect(&self.socket_path).await?;
 
             // Send request as JSON line
             let request_json = serde_json::to_string(req)?;
@@ -4,13 +4,13 @@ use std::pin::Pin;
 use std::sync::Arc;
 
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::UnixListener;
 use tokio::sync::Notify;
 
 use crate::error::{HookwiseError, Result};
+use crate::ipc::transport::{IpcListener, IpcStream};
 use crate::ipc::{IpcRequest, IpcResponse};
 
-/// Unix socket server for the supervisor agent.
+/// IPC server for the supervisor agent (Unix socket, or named pipe on Windows).
 pub struct IpcServer {
     socket_path: PathBuf,
     shutdown_signal: Arc<Notify>,
@@ -32,28 +32,11 @@ impl IpcServer {
             + Sync
             + 'static,
     {
-        // Remove existing socket if present
-        if self.socket_path.exists() {
-            std::fs::remove_file(&self.socket_path)?;
-        }
-
-        // Create parent directory if needed
-        if let Some(parent) = self.socket_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let listener =
-            UnixListener::bind(&self.socket_path).map_err(|e| HookwiseError::Ipc {
-                reason: format!(
-                    "failed to bind socket at {}: {}",
-                    self.socket_path.display(),
-                    e
-                ),
-            })?;
+        let mut listener = IpcListener::bind(&self.socket_path)?;
 
         eprintln!(
             "hookwise: supervisor listening on {}",
-            self.socket_path.display()
+            listener.endpoint(&self.socket_path)
         );
 
         let handler = Arc::new(handler);
@@ -63,7 +46,7 @@ impl IpcServer {
             tokio::select! {
                 accept_result = listener.accept() => {
                     match accept_result {
-                        Ok((stream, _addr)) => {
+                        Ok(stream) => {
                             let handler = handler.clone();
                             tokio::spawn(async move {
                                 if let Err(e) = handle_connection(stream, handler).await {
@@ -84,7 +67,7 @@ impl IpcServer {
         }
 
         // Clean up socket file
-        let _ = std::fs::remove_file(&self.socket_path);
+        IpcListener::cleanup(&self.socket_path);
         Ok(())
     }
 
@@ -96,14 +79,14 @@ impl IpcServer {
 }
 
 /// Handle a single client connection.
-async fn handle_connection<F>(stream: tokio::net::UnixStream, handler: Arc<F>) -> Result<()>
+async fn handle_connection<F>(stream: Box<dyn IpcStream>, handler: Arc<F>) -> Result<()>
 where
     F: Fn(IpcRequest) -> Pin<Box<dyn Future<Output = Result<IpcResponse>> + Send>>
         + Send
         + Sync
         + 'static,
 {
-    let (reader, mut writer) = stream.into_split();
+    let (reader, mut writer) = tokio::io::split(stream);
     let mut buf_reader = BufReader::new(reader);
     let mut line = String::new();
 
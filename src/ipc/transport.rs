@@ -0,0 +1,167 @@
+//! Platform transport for supervisor IPC: Unix domain sockets, or named
+//! pipes on Windows. Both are addressed by the configured socket path; on
+//! Windows only its file stem is used, as the pipe name (see [`pipe_name`]).
+
+use std::path::Path;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::error::{HookwiseError, Result};
+
+/// A connected, bidirectional IPC stream.
+pub trait IpcStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> IpcStream for T {}
+
+/// The named pipe standing in for a socket path on Windows, e.g.
+/// `/tmp/hookwise-solo.sock` -> `\\.\pipe\hookwise-solo`.
+pub fn pipe_name(socket_path: &Path) -> String {
+    // Take the stem by hand so Windows paths are handled on any host.
+    let raw = socket_path.to_string_lossy();
+    let file = raw.rsplit(['/', '\\']).next().unwrap_or_default();
+    let stem = match file.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => file,
+    };
+    let stem = if stem.is_empty() { "hookwise" } else { stem };
+    format!(r"\\.\pipe\{stem}")
+}
+
+fn connect_error(e: std::io::Error) -> HookwiseError {
+    HookwiseError::Ipc {
+        reason: format!("connect failed: {}", e),
+    }
+}
+
+/// Connect to the supervisor listening at `socket_path`. A missing
+/// endpoint is reported as [`HookwiseError::SocketNotFound`].
+#[cfg(unix)]
+pub async fn connect(socket_path: &Path) -> Result<Box<dyn IpcStream>> {
+    if !socket_path.exists() {
+        return Err(HookwiseError::SocketNotFound {
+            path: socket_path.to_path_buf(),
+        });
+    }
+    let stream = tokio::net::UnixStream::connect(socket_path)
+        .await
+        .map_err(connect_error)?;
+    Ok(Box::new(stream))
+}
+
+/// Connect to the supervisor listening at `socket_path`. A missing
+/// endpoint is reported as [`HookwiseError::SocketNotFound`].
+#[cfg(windows)]
+pub async fn connect(socket_path: &Path) -> Result<Box<dyn IpcStream>> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+
+    /// Every pipe instance is serving another client.
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    let name = pipe_name(socket_path);
+    loop {
+        match ClientOptions::new().open(&name) {
+            Ok(client) => return Ok(Box::new(client)),
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                // The server creates the next instance right after accepting;
+                // callers bound the wait with their own timeout.
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(HookwiseError::SocketNotFound {
+                    path: socket_path.to_path_buf(),
+                });
+            }
+            Err(e) => return Err(connect_error(e)),
+        }
+    }
+}
+
+/// Accepts supervisor IPC connections.
+pub struct IpcListener {
+    #[cfg(unix)]
+    listener: tokio::net::UnixListener,
+    #[cfg(windows)]
+    name: String,
+    /// The pipe instance the next client will connect to.
+    #[cfg(windows)]
+    next: tokio::net::windows::named_pipe::NamedPipeServer,
+}
+
+impl IpcListener {
+    /// Listen at `socket_path`, replacing a stale socket file left behind
+    /// by a previous server.
+    #[cfg(unix)]
+    pub fn bind(socket_path: &Path) -> Result<Self> {
+        if socket_path.exists() {
+            std::fs::remove_file(socket_path)?;
+        }
+        if let Some(parent) = socket_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let listener =
+            tokio::net::UnixListener::bind(socket_path).map_err(|e| HookwiseError::Ipc {
+                reason: format!(
+                    "failed to bind socket at {}: {}",
+                    socket_path.display(),
+                    e
+                ),
+            })?;
+        Ok(Self { listener })
+    }
+
+    /// Listen on the named pipe for `socket_path`. Fails if another
+    /// server already owns the pipe.
+    #[cfg(windows)]
+    pub fn bind(socket_path: &Path) -> Result<Self> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        let name = pipe_name(socket_path);
+        let next = ServerOptions::new()
+            .first_pipe_instance(true)
+            .create(&name)
+            .map_err(|e| HookwiseError::Ipc {
+                reason: format!("failed to create pipe {}: {}", name, e),
+            })?;
+        Ok(Self { name, next })
+    }
+
+    /// Where clients connect, for log messages.
+    #[cfg(unix)]
+    pub fn endpoint(&self, socket_path: &Path) -> String {
+        socket_path.display().to_string()
+    }
+
+    /// Where clients connect, for log messages.
+    #[cfg(windows)]
+    pub fn endpoint(&self, _socket_path: &Path) -> String {
+        self.name.clone()
+    }
+
+    /// Wait for the next client.
+    #[cfg(unix)]
+    pub async fn accept(&mut self) -> std::io::Result<Box<dyn IpcStream>> {
+        let (stream, _addr) = self.listener.accept().await?;
+        Ok(Box::new(stream))
+    }
+
+    /// Wait for the next client.
+    #[cfg(windows)]
+    pub async fn accept(&mut self) -> std::io::Result<Box<dyn IpcStream>> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        self.next.connect().await?;
+        // Open the next instance before handing this one off so clients
+        // arriving meanwhile see a busy pipe rather than a missing one.
+        let next = ServerOptions::new().create(&self.name)?;
+        let connected = std::mem::replace(&mut self.next, next);
+        Ok(Box::new(connected))
+    }
+
+    /// Remove the socket file. Named pipes vanish with their last handle.
+    pub fn cleanup(socket_path: &Path) {
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(socket_path);
+        #[cfg(not(unix))]
+        let _ = socket_path;
+    }
+}
@@ -1,14 +1,29 @@
+pub mod audit;
 pub mod cascade;
 pub mod cli;
+pub mod clock;
 pub mod config;
 pub mod decision;
 pub mod error;
+pub mod fault;
+pub mod feedback;
 pub mod hook_io;
+pub mod identity;
 pub mod ipc;
+pub mod notify;
+pub mod promote;
+pub mod rollout;
 pub mod sanitize;
+pub mod schema;
 pub mod scope;
 pub mod session;
+pub mod shim;
 pub mod storage;
+pub mod sync;
+pub mod telemetry;
+pub mod watch;
+
+use std::path::PathBuf;
 
 use clap::Subcommand;
 
@@ -26,6 +41,10 @@ pub enum Commands {
         /// Output format: claude (default) or gemini
         #[arg(long, default_value = "claude")]
         format: HookFormat,
+        /// Make a component fail, to test how the cascade degrades (also
+        /// HOOKWISE_INJECT_FAILURE=supervisor,storage).
+        #[arg(long, value_enum, hide = true)]
+        inject_failure: Vec<fault::Fault>,
     },
 
     /// Check if session is registered (user_prompt_submit / BeforeAgent hook).
@@ -39,18 +58,40 @@ pub enum Commands {
     Register {
         #[arg(long)]
         session_id: String,
-        #[arg(long)]
-        role: String,
+        /// Role to act as; several (`coder,tester`) make each call use the
+        /// first whose path policy doesn't deny it
+        #[arg(long, required = true, value_delimiter = ',')]
+        role: Vec<String>,
         #[arg(long)]
         task: Option<String>,
         #[arg(long)]
         prompt_file: Option<String>,
+        /// Register with the closest known role when the name is unknown
+        #[arg(long)]
+        assume_closest: bool,
+        /// `audit` allows every call but logs what would have been decided
+        /// (default: the policy's session_mode)
+        #[arg(long, value_enum)]
+        mode: Option<crate::config::SessionMode>,
+        /// Org-scope token for `--mode audit` in protected projects (or
+        /// HOOKWISE_DISABLE_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
     },
 
     /// Disable hookwise for a session.
     Disable {
         #[arg(long)]
         session_id: String,
+        /// Why gating is being turned off (recorded in the audit log)
+        #[arg(long)]
+        reason: Option<String>,
+        /// Re-enable automatically after this long, e.g. 30m, 2h, 1d
+        #[arg(long = "for", value_name = "DURATION", value_parser = crate::session::disable::parse_duration)]
+        duration: Option<u64>,
+        /// Org-scope token for protected projects (or HOOKWISE_DISABLE_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
     },
 
     /// Re-enable hookwise for a disabled session.
@@ -60,7 +101,20 @@ pub enum Commands {
     },
 
     /// List pending permission decisions.
-    Queue,
+    #[command(args_conflicts_with_subcommands = true)]
+    Queue {
+        #[command(subcommand)]
+        command: Option<QueueCommand>,
+        /// Open an interactive TUI with live refresh and keyboard approve/deny.
+        #[arg(long)]
+        interactive: bool,
+        /// Sort order: priority (default, highest first) or age (oldest first)
+        #[arg(long, value_enum, default_value = "priority")]
+        sort: cascade::human::QueueSort,
+        /// Show full inputs instead of truncating to the terminal width.
+        #[arg(long)]
+        wide: bool,
+    },
 
     /// Approve a pending decision.
     Approve {
@@ -69,8 +123,13 @@ pub enum Commands {
         always_ask: bool,
         #[arg(long)]
         add_rule: bool,
-        #[arg(long, default_value = "project")]
-        scope: String,
+        /// Rule scope for --add-rule (default: the role's `rule_scope`,
+        /// else `rule_scope` in policy.yml).
+        #[arg(long)]
+        scope: Option<String>,
+        /// Also approve pending entries within the Jaccard similarity threshold.
+        #[arg(long)]
+        like: bool,
     },
 
     /// Deny a pending decision.
@@ -80,13 +139,112 @@ pub enum Commands {
         always_ask: bool,
         #[arg(long)]
         add_rule: bool,
-        #[arg(long, default_value = "project")]
-        scope: String,
+        /// Rule scope for --add-rule (default: the role's `rule_scope`,
+        /// else `rule_scope` in policy.yml).
+        #[arg(long)]
+        scope: Option<String>,
+    },
+
+    /// Expire stale pending decisions and orphaned responses (queue_ttl_secs),
+    /// and delete decision records past `storage.retention_days`.
+    Prune {
+        /// Show what would be pruned without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete decision records older than this in every scope, e.g. 90d,
+        /// instead of using `storage.retention_days`.
+        #[arg(long, value_name = "DURATION", value_parser = crate::session::disable::parse_duration)]
+        older_than: Option<u64>,
+        /// Keep the deleted decision records in an archive file.
+        #[arg(long)]
+        archive: bool,
+    },
+
+    /// Apply approvals recorded as commit trailers (Hookwise-Approve / Hookwise-Deny).
+    Reconcile {
+        /// Number of recent commits to scan (default: git_approval.max_commits).
+        #[arg(long)]
+        max_commits: Option<usize>,
+        /// Show what would be applied without responding.
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Rebuild vector indexes from rules.
     Build,
 
+    /// Rewrite rule files keeping one record per cache key.
+    Compact {
+        /// Scope to compact (org, project or user); all three by default.
+        #[arg(long)]
+        scope: Option<String>,
+        /// Keep the removed records in an archive file.
+        #[arg(long)]
+        archive: bool,
+        /// Report what would be removed without rewriting anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Tools for wiring up assistants and developing hookwise.
+    Dev {
+        #[command(subcommand)]
+        command: DevCommand,
+    },
+
+    /// Maintain the rule files.
+    Storage {
+        #[command(subcommand)]
+        command: StorageCommand,
+    },
+
+    /// Rewrite rule records written by an older hookwise at the current
+    /// schema; the same as `hookwise storage upgrade`.
+    Migrate {
+        /// Scope to upgrade (org, project or user); all three by default.
+        #[arg(long)]
+        scope: Option<String>,
+        /// Report what would be rewritten without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Inspect the decision audit log.
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+
+    /// Write a scope's decisions to a bundle, to carry a trained cache to
+    /// another machine with `hookwise import`.
+    Export {
+        /// Scope to export (org, project or user).
+        #[arg(long, default_value = "project")]
+        scope: String,
+        /// Where to write the bundle.
+        #[arg(short, long, default_value = "hookwise-decisions.tar")]
+        output: PathBuf,
+    },
+
+    /// Merge the decisions from a `hookwise export` bundle into this
+    /// machine's rule files.
+    Import {
+        bundle: PathBuf,
+        /// Scope to merge into; the scope the bundle was exported from by
+        /// default.
+        #[arg(long)]
+        scope: Option<String>,
+        /// How to settle keys decided differently on the two machines.
+        #[arg(long, value_enum, default_value = "strictest")]
+        strategy: crate::storage::bundle::ConflictStrategy,
+        /// Show what would be merged without writing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Print the JSON Schema of the decision records in the rule files.
+    Schema,
+
     /// Clear cached decisions.
     Invalidate {
         #[arg(long)]
@@ -117,35 +275,286 @@ pub enum Commands {
         scope: String,
     },
 
+    /// Show the raw supervisor prompt and reply behind a decision.
+    /// Without --supervisor, lists recent supervisor decision ids.
+    Explain {
+        /// Supervisor decision id (see `hookwise explain` for recent ids).
+        #[arg(long, value_name = "DECISION_ID")]
+        supervisor: Option<String>,
+    },
+
+    /// Mark an automatic decision as wrong. Without an id, lists recent
+    /// automatic decision ids.
+    Feedback {
+        /// Decision id, or a supervisor id from `hookwise explain`.
+        decision_id: Option<String>,
+        /// The decision that should have been made: allow, deny, or ask.
+        #[arg(long, value_name = "DECISION")]
+        should_have_been: Option<String>,
+        #[arg(long)]
+        reason: Option<String>,
+        /// Also add an override rule with the corrected decision.
+        #[arg(long)]
+        add_rule: bool,
+        #[arg(long, default_value = "project")]
+        scope: String,
+    },
+
+    /// Propose a project or user rule for a broader scope: writes the rule
+    /// and its usage to a proposal file for review instead of changing the
+    /// broader scope. Without an id, lists recent rules.
+    Promote {
+        /// Rule id (see `hookwise promote` for recent ids).
+        rule_id: Option<String>,
+        /// Scope to promote to.
+        #[arg(long, default_value = "org")]
+        to: String,
+        /// Directory for the proposal, e.g. a checkout of the org rules
+        /// repository (default: .hookwise/proposals/).
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
     /// Stream decisions in real time.
     Monitor,
 
     /// Show cache hit rates and decision distribution.
     Stats,
 
+    /// Summarize health: supervisor, hooks, sessions, queue, org rules,
+    /// policy fingerprint and storage sizes.
+    Status,
+
     /// Pre-commit secret scan on staged files.
     Scan {
         #[arg(long)]
         staged: bool,
         path: Option<String>,
+        /// Output format: text (default), json, or sarif for code scanning.
+        #[arg(long, value_enum, default_value = "text")]
+        format: cli::scan::ScanFormat,
+        /// Accepted findings: only findings missing from this file fail the
+        /// scan. Written from the current findings if it doesn't exist.
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<PathBuf>,
+        /// Rewrite the baseline from the current findings.
+        #[arg(long, requires = "baseline")]
+        update_baseline: bool,
+        /// Lowest severity that fails the scan: low (default), medium,
+        /// high or critical. Lower findings are still reported.
+        #[arg(long, value_enum, default_value = "low")]
+        min_severity: config::Severity,
+        /// List findings suppressed by `hookwise:ignore` comments.
+        #[arg(short, long)]
+        verbose: bool,
+    },
+
+    /// Redact secrets from stdin or a file with the project's sanitizer
+    /// and print the result.
+    #[command(args_conflicts_with_subcommands = true)]
+    Sanitize {
+        #[command(subcommand)]
+        command: Option<SanitizeCommand>,
+        /// Read from stdin (the default).
+        #[arg(long, conflicts_with = "file")]
+        stdin: bool,
+        /// Read from a file instead of stdin.
+        #[arg(long)]
+        file: Option<PathBuf>,
     },
 
     /// Initialize .hookwise/ in the current repo.
-    Init,
+    Init {
+        /// Also install git pre-commit and pre-push hooks running
+        /// `hookwise scan`, or print the fragment for the repo's hook manager.
+        #[arg(long)]
+        hooks: bool,
+        /// Print the hooks instead of writing anything.
+        #[arg(long, requires = "hooks")]
+        print: bool,
+        /// Hook manager to target instead of the detected one.
+        #[arg(long, value_enum, requires = "hooks")]
+        manager: Option<cli::git_hooks::HookManager>,
+    },
 
     /// View/edit global configuration.
-    Config,
+    Config {
+        /// Show which file (project or machine policy) set each policy
+        /// setting.
+        #[arg(long)]
+        show_origin: bool,
+    },
 
-    /// Pull latest org-level rules.
-    Sync,
+    /// Pull the org rules from the `org_rules.repo` git repository, the
+    /// `org_rules.server` rule server or the `org_store` bucket in the
+    /// global config, reporting added, changed and removed rules.
+    Sync {
+        /// Publish local changes to the org rules repository or org store
+        /// instead (org maintainers only).
+        #[arg(long)]
+        push: bool,
+    },
+
+    /// Move global files into XDG state/cache directories, leaving symlinks
+    /// at the old paths for older binaries.
+    MigrateDirs {
+        /// Show what would be moved without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
 
     /// Start MCP server over stdio (for Gemini CLI extension).
+    #[cfg_attr(not(feature = "mcp"), command(hide = true))]
     McpServer,
 
-    /// Check for and install binary updates from GitHub releases.
+    /// Check for and install binary updates from GitHub releases (or the
+    /// `updates.mirror` in the global config).
+    #[cfg_attr(not(feature = "self-update"), command(hide = true))]
     SelfUpdate {
         /// Only check for updates, don't install.
         #[arg(long)]
         check: bool,
+        /// Install into this directory (e.g. ~/.local/bin) instead of
+        /// replacing the running binary; for package-managed or read-only
+        /// installs.
+        #[arg(long, value_name = "DIR")]
+        install_dir: Option<PathBuf>,
+    },
+
+    /// Run scheduled background work outside the hook path: the release
+    /// check behind the update hint (every `updates.check_interval_hours`).
+    #[cfg_attr(not(feature = "self-update"), command(hide = true))]
+    Maintain {
+        /// Keep running, waking every N seconds (for a systemd/launchd unit).
+        #[arg(long, value_name = "SECS")]
+        every: Option<u64>,
+        /// Run every task now, even if it isn't due.
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// `hookwise queue` subcommands for approving across an air gap.
+#[derive(Subcommand)]
+pub enum QueueCommand {
+    /// Write all pending decisions into a signed bundle.
+    Export {
+        #[arg(long)]
+        bundle: PathBuf,
+    },
+    /// Answer decisions from a pending bundle into a signed responses
+    /// bundle; lists the bundle when no ids are given.
+    Respond {
+        bundle: PathBuf,
+        /// Approve this pending decision (repeatable).
+        #[arg(long)]
+        allow: Vec<String>,
+        /// Deny this pending decision (repeatable).
+        #[arg(long)]
+        deny: Vec<String>,
+        #[arg(long)]
+        always_ask: bool,
+        #[arg(long)]
+        add_rule: bool,
+        /// Where to write the responses bundle.
+        #[arg(long, default_value = "responses.tar")]
+        out: PathBuf,
+    },
+    /// Apply a signed responses bundle to the pending queue.
+    ImportResponses { bundle: PathBuf },
+    /// Create a private bundle key and print its public half.
+    Keygen {
+        /// Where to write the private key.
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+}
+
+/// `hookwise dev` subcommands.
+#[derive(Subcommand)]
+pub enum DevCommand {
+    /// Send a fabricated assistant hook payload through `check` and print
+    /// the stdout and exit code the assistant would see.
+    SimulateHook {
+        /// Tool name, as the assistant sends it.
+        #[arg(long, default_value = "Bash")]
+        tool: String,
+        /// Bash command.
+        #[arg(long)]
+        command: Option<String>,
+        /// File the call reads or writes, relative to the current directory.
+        #[arg(long)]
+        file_path: Option<String>,
+        /// Content written (Write) or new text (Edit).
+        #[arg(long)]
+        content: Option<String>,
+        /// Raw tool_input JSON, instead of --command/--file-path/--content.
+        #[arg(long)]
+        input: Option<String>,
+        /// Assistant whose payload and output format to use.
+        #[arg(long, default_value = "claude")]
+        format: HookFormat,
+        #[arg(long, default_value = "simulated")]
+        session_id: String,
+        /// Register the session with this role first.
+        #[arg(long)]
+        role: Option<String>,
+    },
+}
+
+/// `hookwise storage` subcommands.
+#[derive(Subcommand)]
+pub enum StorageCommand {
+    /// Rewrite records written by an older hookwise at the current schema.
+    /// Records from a newer hookwise are left as they are.
+    Upgrade {
+        /// Scope to upgrade (org, project or user); all three by default.
+        #[arg(long)]
+        scope: Option<String>,
+        /// Report what would be rewritten without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// `hookwise audit` subcommands.
+#[derive(Subcommand)]
+pub enum AuditCommand {
+    /// Check the audit log's hash chain for edited, removed or reordered
+    /// entries. Exits 1 if it breaks.
+    Verify {
+        /// Audit log to check; the configured `audit.path`, else the
+        /// audit-mode log, by default.
+        #[arg(long)]
+        path: Option<PathBuf>,
+        /// A head hash from an earlier run that must still be in the log,
+        /// to catch truncation or a rewritten log.
+        #[arg(long)]
+        since: Option<String>,
+    },
+}
+
+/// `hookwise sanitize` subcommands.
+#[derive(Subcommand)]
+pub enum SanitizeCommand {
+    /// Run a corpus of should-redact / should-preserve inputs against the
+    /// sanitizer, including custom `sanitize` rules in policy.yml.
+    Test {
+        /// YAML corpus with `should_redact` and `should_preserve` lists.
+        #[arg(long)]
+        corpus: PathBuf,
+    },
+
+    /// Show how hookwise sees a tool call: the sanitized and normalized
+    /// input, the paths it touches, and the cache key it is stored under.
+    Preview {
+        /// A shell command, or a JSON tool input; read from stdin if omitted.
+        input: Option<String>,
+        /// Tool the input is for.
+        #[arg(long, default_value = "Bash")]
+        tool: String,
+        /// Role for the cache key.
+        #[arg(long, default_value = "*")]
+        role: String,
     },
 }
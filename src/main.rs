@@ -13,6 +13,7 @@ pub struct Cli {
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    let _telemetry = hookwise::telemetry::init();
     hookwise::cli::dispatch(cli.command).await?;
     Ok(())
 }
@@ -0,0 +1,293 @@
+//! Push notifications for pending human decisions and long-running
+//! session disables (ntfy, Pushover).
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::cascade::human::{PendingDecision, Priority};
+use crate::config::{NotificationChannel, NotificationConfig};
+use crate::error::{HookwiseError, Result};
+use crate::session::Exclusion;
+
+/// Per-request timeout. Notifications are best-effort and must not hold up
+/// the hook for long.
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Characters of a Write/Edit attachment included in a notification body;
+/// Pushover caps messages at 1024.
+const ATTACHMENT_CHARS: usize = 600;
+
+const PUSHOVER_API_URL: &str = "https://api.pushover.net/1/messages.json";
+
+/// Why a notification is being sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotifyKind {
+    /// A decision was just queued.
+    Queued,
+    /// A reminder for an overdue decision; carries the reminder count.
+    Overdue(u32),
+    /// The previous approver did not respond; carries the stage name.
+    Escalated(String),
+}
+
+/// A rendered notification, independent of the delivery channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    pub priority: Priority,
+}
+
+impl Notification {
+    /// Render a notification for a pending decision. The body includes the
+    /// decision id so it can be approved from another machine.
+    pub fn for_pending(pending: &PendingDecision, kind: &NotifyKind) -> Self {
+        let title = match kind {
+            NotifyKind::Queued => format!(
+                "hookwise: {} wants {} ({})",
+                pending.role, pending.tool_name, pending.priority
+            ),
+            NotifyKind::Overdue(count) => format!(
+                "hookwise: OVERDUE {} {} (reminder #{})",
+                pending.role, pending.tool_name, count
+            ),
+            NotifyKind::Escalated(stage) => format!(
+                "hookwise: ESCALATED to {} -- {} wants {} ({})",
+                stage, pending.role, pending.tool_name, pending.priority
+            ),
+        };
+
+        let mut body = String::new();
+        if let Some(path) = &pending.file_path {
+            body.push_str(&format!("File: {}\n", path));
+        }
+        body.push_str(&format!(
+            "Input: {}\n",
+            truncate(&pending.sanitized_input, 200)
+        ));
+        if let Some(reason) = &pending.ask_reason {
+            body.push_str(&format!("Reason: {}\n", reason));
+        }
+        if let Some(suggestion) = &pending.suggestion {
+            body.push_str(&format!("Suggestion: {}\n", suggestion));
+        }
+        if let Some(attachment) = &pending.attachment {
+            body.push_str(&truncate(&attachment.to_text(), ATTACHMENT_CHARS));
+            body.push('\n');
+        }
+        body.push_str(&format!("ID: {}\n", pending.id));
+        body.push_str(&format!(
+            "Approve: hookwise approve {id} (or hookwise_approve via MCP)",
+            id = pending.id
+        ));
+
+        Self {
+            title,
+            body,
+            priority: pending.priority,
+        }
+    }
+
+    /// Render a reminder that a session is still disabled at `now`.
+    pub fn for_disabled(exclusion: &Exclusion, now: DateTime<Utc>) -> Self {
+        let elapsed = exclusion
+            .disabled_at
+            .map(|at| format!(" for {}", format_elapsed(now - at)))
+            .unwrap_or_default();
+        let title = format!(
+            "hookwise: session {} still disabled{}",
+            exclusion.session_id, elapsed
+        );
+
+        let mut body = String::new();
+        if let Some(by) = &exclusion.disabled_by {
+            body.push_str(&format!("Disabled by: {}\n", by));
+        }
+        if let Some(reason) = &exclusion.reason {
+            body.push_str(&format!("Reason: {}\n", reason));
+        }
+        match exclusion.until {
+            Some(until) => body.push_str(&format!("Re-enables at: {}\n", until.to_rfc3339())),
+            None => body.push_str("Re-enables: never (no --for given)\n"),
+        }
+        body.push_str(&format!(
+            "Re-enable: hookwise enable --session-id {}",
+            exclusion.session_id
+        ));
+
+        Self {
+            title,
+            body,
+            priority: Priority::Normal,
+        }
+    }
+}
+
+/// `2h 5m`, `45m` or `30s`.
+pub(crate) fn format_elapsed(elapsed: chrono::Duration) -> String {
+    let secs = elapsed.num_seconds().max(0);
+    let (hours, minutes) = (secs / 3600, secs % 3600 / 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{}s", secs),
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+/// Sends notifications to every configured channel.
+pub struct Notifier {
+    client: reqwest::Client,
+    channels: Vec<NotificationChannel>,
+    min_priority: Priority,
+}
+
+impl Notifier {
+    /// Build a notifier from config. Returns `None` when no channels are configured.
+    pub fn from_config(config: &NotificationConfig) -> Option<Self> {
+        if config.channels.is_empty() {
+            return None;
+        }
+        Some(Self::new(config.channels.clone(), config.min_priority))
+    }
+
+    /// Build a notifier for an explicit set of channels.
+    pub fn new(channels: Vec<NotificationChannel>, min_priority: Priority) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(SEND_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            channels,
+            min_priority,
+        }
+    }
+
+    /// Notify all channels about a pending decision. Failures are logged and
+    /// do not affect the decision flow.
+    pub async fn notify_pending(&self, pending: &PendingDecision, kind: NotifyKind) {
+        if pending.priority < self.min_priority {
+            return;
+        }
+        let notification = Notification::for_pending(pending, &kind);
+        for channel in &self.channels {
+            if let Err(e) = self.send(channel, &notification).await {
+                tracing::warn!("notification for {} failed: {}", pending.id, e);
+            }
+        }
+    }
+
+    /// Send a notification to all channels, subject to `min_priority`.
+    /// Failures are logged.
+    pub async fn notify(&self, notification: &Notification) {
+        if notification.priority < self.min_priority {
+            return;
+        }
+        for channel in &self.channels {
+            if let Err(e) = self.send(channel, notification).await {
+                tracing::warn!("notification '{}' failed: {}", notification.title, e);
+            }
+        }
+    }
+
+    /// Deliver a notification to a single channel.
+    pub async fn send(
+        &self,
+        channel: &NotificationChannel,
+        notification: &Notification,
+    ) -> Result<()> {
+        let request = match channel {
+            NotificationChannel::Ntfy {
+                server,
+                topic,
+                token,
+            } => {
+                let url = format!("{}/{}", server.trim_end_matches('/'), topic);
+                let mut req = self
+                    .client
+                    .post(url)
+                    .header("Title", &notification.title)
+                    .header("Priority", ntfy_priority(notification.priority).to_string())
+                    .header("Tags", "lock")
+                    .body(notification.body.clone());
+                if let Some(token) = token.clone().or_else(|| std::env::var("NTFY_TOKEN").ok()) {
+                    req = req.bearer_auth(token);
+                }
+                req
+            }
+            NotificationChannel::Pushover {
+                user_key,
+                api_token,
+                device,
+            } => {
+                let user = resolve_credential(user_key, "PUSHOVER_USER_KEY")?;
+                let token = resolve_credential(api_token, "PUSHOVER_API_TOKEN")?;
+                let mut body = serde_json::json!({
+                    "token": token,
+                    "user": user,
+                    "title": notification.title,
+                    "message": notification.body,
+                    "priority": pushover_priority(notification.priority),
+                });
+                if let Some(device) = device {
+                    body["device"] = serde_json::Value::String(device.clone());
+                }
+                self.client.post(PUSHOVER_API_URL).json(&body)
+            }
+        };
+
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| HookwiseError::Notification {
+                reason: format!("request failed: {}", e),
+            })?;
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(HookwiseError::Api { status, body });
+        }
+        Ok(())
+    }
+}
+
+/// Map queue priority to ntfy's 1 (min) .. 5 (max) scale.
+pub fn ntfy_priority(priority: Priority) -> u8 {
+    match priority {
+        Priority::Low => 2,
+        Priority::Normal => 3,
+        Priority::High => 4,
+        Priority::Critical => 5,
+    }
+}
+
+/// Map queue priority to Pushover's -1 (quiet) .. 1 (high) range. Emergency
+/// priority (2) is not used since it requires acknowledgement handling.
+pub fn pushover_priority(priority: Priority) -> i8 {
+    match priority {
+        Priority::Low => -1,
+        Priority::Normal => 0,
+        Priority::High | Priority::Critical => 1,
+    }
+}
+
+fn resolve_credential(value: &Option<String>, env_var: &str) -> Result<String> {
+    value
+        .clone()
+        .or_else(|| std::env::var(env_var).ok())
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| HookwiseError::Notification {
+            reason: format!("missing credential: set it in policy.yml or ${}", env_var),
+        })
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let truncated: String = s.chars().take(max).collect();
+        format!("{}...", truncated)
+    }
+}
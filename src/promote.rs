@@ -0,0 +1,145 @@
+//! Rule promotion proposals (`hookwise promote`). A rule proven at one scope
+//! is packaged with its usage into a reviewable file for the broader scope's
+//! rules repository; nothing is written to the broader scope directly.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::AuditEvent;
+use crate::decision::DecisionRecord;
+use crate::error::{HookwiseError, Result};
+use crate::feedback::FeedbackRecord;
+use crate::scope::ScopeLevel;
+
+/// How a rule has been used since it was recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleUsage {
+    /// Audited decisions for the rule's exact key, including cache hits.
+    pub audited_hits: usize,
+    /// Stored decisions that similarity matched to the rule.
+    pub similarity_matches: usize,
+    /// Corrections recorded against the rule's key with `hookwise feedback`.
+    pub corrections: usize,
+    /// When any of the above last happened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// A proposal to move a rule to a broader scope.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromotionProposal {
+    /// Id of the promoted rule ([`DecisionRecord::id`]).
+    pub rule_id: String,
+    pub from: ScopeLevel,
+    pub to: ScopeLevel,
+    /// The identity that proposed it.
+    pub proposed_by: String,
+    pub proposed_at: DateTime<Utc>,
+    /// The project the rule was proven in.
+    pub project: String,
+    /// The rule as it would be stored at `to`.
+    pub rule: DecisionRecord,
+    pub usage: RuleUsage,
+}
+
+impl PromotionProposal {
+    /// Propose `rule` for scope `to`. Fails unless `to` is broader than the
+    /// rule's current scope.
+    pub fn new(
+        rule: &DecisionRecord,
+        to: ScopeLevel,
+        usage: RuleUsage,
+        proposed_by: &str,
+        project: &str,
+    ) -> Result<Self> {
+        if to.precedence() <= rule.scope.precedence() {
+            return Err(HookwiseError::InvalidPolicy {
+                reason: format!(
+                    "rule {} is at {} scope; it can only be promoted to a broader scope",
+                    rule.id(),
+                    rule.scope
+                ),
+            });
+        }
+        let mut promoted = rule.clone();
+        promoted.scope = to;
+        Ok(Self {
+            rule_id: rule.id(),
+            from: rule.scope,
+            to,
+            proposed_by: proposed_by.to_string(),
+            proposed_at: Utc::now(),
+            project: project.to_string(),
+            rule: promoted,
+            usage,
+        })
+    }
+
+    /// File name for the proposal, e.g. `dec-3f9a1c2b7e4d-to-org.yml`.
+    pub fn file_name(&self) -> String {
+        format!("{}-to-{}.yml", self.rule_id, self.to)
+    }
+
+    /// Write the proposal as YAML into `dir`, creating it as needed.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(self.file_name());
+        let yaml = serde_yaml::to_string(self).map_err(|e| HookwiseError::Storage {
+            reason: format!("failed to serialize proposal: {}", e),
+        })?;
+        std::fs::write(&path, yaml)?;
+        Ok(path)
+    }
+
+    /// Read a proposal written by [`write`](Self::write).
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        serde_yaml::from_str(&contents).map_err(|e| HookwiseError::ConfigParse {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    }
+}
+
+/// Default directory for proposals under a project's `.hookwise/`.
+pub fn proposals_dir(hookwise_dir: &Path) -> PathBuf {
+    hookwise_dir.join("proposals")
+}
+
+/// Count how `rule` has been used across stored decisions, audit events and
+/// feedback.
+pub fn rule_usage(
+    rule: &DecisionRecord,
+    decisions: &[DecisionRecord],
+    audit: &[AuditEvent],
+    feedback: &[FeedbackRecord],
+) -> RuleUsage {
+    let hits: Vec<_> = audit
+        .iter()
+        .filter(|e| !e.not_enforced && e.record.key == rule.key)
+        .map(|e| e.logged_at)
+        .collect();
+    let matches: Vec<_> = decisions
+        .iter()
+        .filter(|r| r.metadata.matched_key.as_ref() == Some(&rule.key))
+        .map(|r| r.timestamp)
+        .collect();
+    let corrections: Vec<_> = feedback
+        .iter()
+        .filter(|f| f.key == rule.key)
+        .map(|f| f.timestamp)
+        .collect();
+    RuleUsage {
+        audited_hits: hits.len(),
+        similarity_matches: matches.len(),
+        corrections: corrections.len(),
+        last_used: hits
+            .iter()
+            .chain(&matches)
+            .chain(&corrections)
+            .max()
+            .copied(),
+    }
+}
@@ -0,0 +1,208 @@
+//! Canary rollout of org rules. Rules newer than the org policy's
+//! `rollout.since` are enforced for a share of sessions only; the rest
+//! evaluate them in shadow, and both cohorts log where the new rules
+//! matched so `hookwise stats` can show how often they would change a
+//! decision before the rollout goes to 100%.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::RolloutConfig;
+use crate::decision::{CacheKey, Decision, DecisionRecord};
+use crate::error::Result;
+use crate::scope::merge::merge_decisions;
+use crate::scope::{ScopeLevel, ScopedDecision};
+
+/// Which side of a rollout a session is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cohort {
+    /// New org rules are enforced.
+    Canary,
+    /// New org rules are evaluated in shadow only.
+    Control,
+}
+
+/// Stable bucket in `0..100` for a session.
+pub fn bucket(session_id: &str, salt: &str) -> u8 {
+    use sha2::{Digest, Sha256};
+    let hash = Sha256::digest(format!("{}\0{}", salt, session_id));
+    (u16::from_be_bytes([hash[0], hash[1]]) % 100) as u8
+}
+
+/// A rollout in progress, as seen by one session.
+#[derive(Debug, Clone)]
+pub struct Rollout {
+    pub since: DateTime<Utc>,
+    pub percent: u8,
+    pub cohort: Cohort,
+}
+
+impl Rollout {
+    /// The rollout `session_id` takes part in. None when nothing is being
+    /// rolled out (no `since`, or `percent` of 100 or more).
+    pub fn for_session(config: &RolloutConfig, session_id: &str) -> Option<Self> {
+        let since = config.since?;
+        if config.percent >= 100 {
+            return None;
+        }
+        let cohort = if bucket(session_id, &config.salt) < config.percent {
+            Cohort::Canary
+        } else {
+            Cohort::Control
+        };
+        Some(Self {
+            since,
+            percent: config.percent,
+            cohort,
+        })
+    }
+
+    /// Whether an org rule is part of this rollout.
+    pub fn is_new(&self, record: &DecisionRecord) -> bool {
+        record.timestamp >= self.since
+    }
+
+    /// Split org rules into those to enforce and those being rolled out.
+    /// Canary sessions enforce both; the returned rules are still needed to
+    /// log their hits.
+    pub fn split(
+        &self,
+        org_rules: Vec<DecisionRecord>,
+    ) -> (Vec<DecisionRecord>, Vec<DecisionRecord>) {
+        let (new, old): (Vec<_>, Vec<_>) = org_rules.into_iter().partition(|r| self.is_new(r));
+        match self.cohort {
+            Cohort::Canary => {
+                let mut enforced = old;
+                enforced.extend(new.iter().cloned());
+                (enforced, new)
+            }
+            Cohort::Control => (old, new),
+        }
+    }
+}
+
+/// The decision the rolled-out rules give for `key`, if any matches:
+/// rules for the key's role or for every role (`*`), deny over ask over
+/// allow.
+pub fn evaluate(rules: &[DecisionRecord], key: &CacheKey) -> Option<DecisionRecord> {
+    let found = rules
+        .iter()
+        .filter(|r| {
+            r.key.tool == key.tool
+                && r.key.sanitized_input == key.sanitized_input
+                && (r.key.role == key.role || r.key.role == "*")
+        })
+        .map(|r| ScopedDecision {
+            decision: r.decision,
+            scope: ScopeLevel::Org,
+            record: r.clone(),
+        })
+        .collect();
+    merge_decisions(found).map(|best| best.record)
+}
+
+/// One call a rolled-out rule matched.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RolloutEvent {
+    pub logged_at: DateTime<Utc>,
+    /// The rollout's `since`, so events from earlier rollouts can be told
+    /// apart.
+    pub since: DateTime<Utc>,
+    pub session_id: String,
+    pub cohort: Cohort,
+    /// Id of the matching org rule.
+    pub rule: String,
+    pub tool: String,
+    pub role: String,
+    /// What the session was told.
+    pub enforced: Decision,
+    /// What the rolled-out rule says.
+    pub rolled_out: Decision,
+}
+
+impl RolloutEvent {
+    /// Whether the new rule would have changed a control session's
+    /// decision.
+    pub fn diverged(&self) -> bool {
+        self.enforced != self.rolled_out
+    }
+}
+
+/// Log of rollout events, under the project's `.user/` directory.
+pub fn rollout_log_path(hookwise_dir: &Path) -> PathBuf {
+    hookwise_dir.join(".user").join("rollout.jsonl")
+}
+
+/// Append a rollout event to the log, creating parent directories as
+/// needed.
+pub fn append_event(path: &Path, event: &RolloutEvent) -> Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Load rollout events. Missing files yield an empty list and malformed
+/// lines are skipped.
+pub fn load_events(path: &Path) -> Vec<RolloutEvent> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => contents
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// How the rolled-out rules have fared.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RolloutSummary {
+    /// Calls in canary sessions that a new rule decided.
+    pub canary_hits: usize,
+    /// Calls in control sessions that a new rule matched in shadow.
+    pub shadow_hits: usize,
+    /// Shadow hits where the new rule disagreed with the decision given.
+    pub diverged: usize,
+    /// Divergences by `(enforced, rolled_out)`.
+    pub changes: BTreeMap<(String, String), usize>,
+}
+
+impl RolloutSummary {
+    /// Share of shadow hits that diverged, if there were any.
+    pub fn divergence_rate(&self) -> Option<f64> {
+        (self.shadow_hits > 0).then(|| self.diverged as f64 / self.shadow_hits as f64)
+    }
+}
+
+/// Summarize the events of the rollout that started at `since`.
+pub fn summarize(events: &[RolloutEvent], since: DateTime<Utc>) -> RolloutSummary {
+    let mut summary = RolloutSummary::default();
+    for event in events.iter().filter(|e| e.since == since) {
+        match event.cohort {
+            Cohort::Canary => summary.canary_hits += 1,
+            Cohort::Control => {
+                summary.shadow_hits += 1;
+                if event.diverged() {
+                    summary.diverged += 1;
+                    *summary
+                        .changes
+                        .entry((event.enforced.to_string(), event.rolled_out.to_string()))
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    summary
+}
@@ -76,16 +76,15 @@ impl AhoCorasickSanitizer {
     }
 }
 
-impl Sanitizer for AhoCorasickSanitizer {
-    fn sanitize(&self, input: &str) -> String {
+impl AhoCorasickSanitizer {
+    /// Spans of prefixed tokens to redact, merged, each with the prefix
+    /// that started it.
+    fn spans(&self, input: &str) -> Vec<(usize, usize, usize)> {
         if self.prefixes.is_empty() {
-            return input.to_string();
+            return Vec::new();
         }
 
-        let mut result = input.to_string();
-        // Find all matches and collect them in reverse order to replace from end to start.
-        let mut matches: Vec<(usize, usize)> = Vec::new();
-
+        let mut matches: Vec<(usize, usize, usize)> = Vec::new();
         for mat in self.automaton.find_iter(input) {
             let start = mat.start();
             // From the prefix match point, extend to the end of the token
@@ -111,26 +110,82 @@ impl Sanitizer for AhoCorasickSanitizer {
             // Only redact if the token is reasonably long (at least the prefix + some chars)
             let prefix_len = mat.end() - mat.start();
             if token_end > prefix_len {
-                matches.push((start, end));
+                matches.push((start, end, mat.pattern().as_usize()));
             }
         }
 
-        // Deduplicate overlapping ranges and apply replacements in reverse order.
-        matches.sort_by(|a, b| a.0.cmp(&b.0));
-        let merged = merge_ranges(&matches);
+        // Deduplicate overlapping ranges, keeping the first prefix.
+        matches.sort_by_key(|a| a.0);
+        let ranges: Vec<(usize, usize)> = matches.iter().map(|&(s, e, _)| (s, e)).collect();
+        merge_ranges(&ranges)
+            .into_iter()
+            .map(|(start, end)| {
+                let prefix = matches.iter().find(|m| m.0 == start).map_or(0, |m| m.2);
+                (start, end, prefix)
+            })
+            .collect()
+    }
+}
 
-        for &(start, end) in merged.iter().rev() {
+impl Sanitizer for AhoCorasickSanitizer {
+    fn sanitize(&self, input: &str) -> String {
+        let mut result = input.to_string();
+        for &(start, end, _) in self.spans(input).iter().rev() {
             result.replace_range(start..end, "<REDACTED>");
         }
-
         result
     }
 
+    fn findings(&self, input: &str) -> Vec<String> {
+        self.spans(input)
+            .into_iter()
+            .map(|(_, _, prefix)| format!("prefix:{}", self.prefixes[prefix]))
+            .collect()
+    }
+
     fn name(&self) -> &str {
         "aho-corasick"
     }
 }
 
+/// Literal string matching via aho-corasick: each occurrence of a literal,
+/// ignoring ASCII case, is redacted on its own (unlike prefixes, the token
+/// around it is kept).
+pub struct LiteralSanitizer {
+    automaton: AhoCorasick,
+}
+
+impl LiteralSanitizer {
+    /// Build from a list of literal strings. Empty strings are ignored.
+    pub fn new(literals: &[String]) -> Self {
+        let literals: Vec<&String> = literals.iter().filter(|l| !l.is_empty()).collect();
+        let automaton = AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .match_kind(aho_corasick::MatchKind::LeftmostLongest)
+            .build(literals)
+            .expect("valid aho-corasick patterns");
+        Self { automaton }
+    }
+}
+
+impl Sanitizer for LiteralSanitizer {
+    fn sanitize(&self, input: &str) -> String {
+        self.automaton
+            .replace_all(input, &vec!["<REDACTED>"; self.automaton.patterns_len()])
+    }
+
+    fn findings(&self, input: &str) -> Vec<String> {
+        self.automaton
+            .find_iter(input)
+            .map(|_| "custom_literal".to_string())
+            .collect()
+    }
+
+    fn name(&self) -> &str {
+        "literal"
+    }
+}
+
 /// Merge overlapping or adjacent ranges.
 fn merge_ranges(ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
     if ranges.is_empty() {
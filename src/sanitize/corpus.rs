@@ -0,0 +1,120 @@
+//! Labeled sanitizer test corpus (`hookwise sanitize test --corpus`).
+//!
+//! ```yaml
+//! should_redact:
+//!   - "deploy --token acme_live_4f9a2c7e1b"
+//!   - input: "DB=postgres://app:hunter22@db/app"
+//!     secret: "hunter22"
+//! should_preserve:
+//!   - "cargo build --release"
+//! ```
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::SanitizePipeline;
+use crate::error::{HookwiseError, Result};
+
+/// A corpus of inputs labeled by whether the pipeline should redact them.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SanitizeCorpus {
+    #[serde(default)]
+    pub should_redact: Vec<CorpusEntry>,
+    #[serde(default)]
+    pub should_preserve: Vec<CorpusEntry>,
+}
+
+/// A corpus input, either bare or with the secret it contains.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum CorpusEntry {
+    Input(String),
+    Labeled {
+        input: String,
+        /// The part that must not survive sanitization. Without it, any
+        /// change to the input counts as a redaction.
+        #[serde(default)]
+        secret: Option<String>,
+    },
+}
+
+impl CorpusEntry {
+    pub fn input(&self) -> &str {
+        match self {
+            Self::Input(input) | Self::Labeled { input, .. } => input,
+        }
+    }
+
+    pub fn secret(&self) -> Option<&str> {
+        match self {
+            Self::Input(_) => None,
+            Self::Labeled { secret, .. } => secret.as_deref(),
+        }
+    }
+}
+
+/// An input the pipeline got wrong, with what it produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusFailure {
+    pub input: String,
+    pub output: String,
+}
+
+/// The result of running a corpus.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorpusReport {
+    pub total: usize,
+    /// `should_redact` inputs whose secret survived.
+    pub misses: Vec<CorpusFailure>,
+    /// `should_preserve` inputs that were changed.
+    pub over_redactions: Vec<CorpusFailure>,
+}
+
+impl CorpusReport {
+    pub fn passed(&self) -> bool {
+        self.misses.is_empty() && self.over_redactions.is_empty()
+    }
+}
+
+impl SanitizeCorpus {
+    /// Load a corpus from a YAML file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        serde_yaml::from_str(&contents).map_err(|e| HookwiseError::ConfigParse {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    }
+
+    /// Run every input through `pipeline`.
+    pub fn run(&self, pipeline: &SanitizePipeline) -> CorpusReport {
+        let mut report = CorpusReport {
+            total: self.should_redact.len() + self.should_preserve.len(),
+            ..Default::default()
+        };
+        for entry in &self.should_redact {
+            let output = pipeline.sanitize(entry.input());
+            let missed = match entry.secret() {
+                Some(secret) => output.contains(secret),
+                None => output == entry.input(),
+            };
+            if missed {
+                report.misses.push(CorpusFailure {
+                    input: entry.input().to_string(),
+                    output,
+                });
+            }
+        }
+        for entry in &self.should_preserve {
+            let output = pipeline.sanitize(entry.input());
+            if output != entry.input() {
+                report.over_redactions.push(CorpusFailure {
+                    input: entry.input().to_string(),
+                    output,
+                });
+            }
+        }
+        report
+    }
+}
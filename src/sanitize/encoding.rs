@@ -113,6 +113,14 @@ impl Sanitizer for EncodingSanitizer {
         result
     }
 
+    fn findings(&self, input: &str) -> Vec<String> {
+        let base64 = self.check_base64(input).len();
+        let url = self.find_url_encoded_spans(input).len();
+        let mut findings = vec!["base64_encoded".to_string(); base64];
+        findings.extend(vec!["url_encoded".to_string(); url]);
+        findings
+    }
+
     fn name(&self) -> &str {
         "encoding"
     }
@@ -1,3 +1,5 @@
+use crate::config::EntropyConfig;
+
 use super::Sanitizer;
 
 /// Layer 3: Shannon entropy detection for unknown secret formats.
@@ -6,6 +8,11 @@ pub struct EntropySanitizer {
     pub min_length: usize,
     /// Minimum Shannon entropy to flag. Default: 4.0.
     pub min_entropy: f64,
+    /// Minimum length of a hex digit run to flag. None: hex runs are only
+    /// caught as ordinary tokens.
+    pub hex_min_length: Option<usize>,
+    /// Minimum Shannon entropy of a hex run to flag. Default: 3.0.
+    pub hex_min_entropy: f64,
 }
 
 impl EntropySanitizer {
@@ -13,9 +20,29 @@ impl EntropySanitizer {
         Self {
             min_length,
             min_entropy,
+            hex_min_length: None,
+            hex_min_entropy: 3.0,
         }
     }
 
+    /// Build from `sanitize.entropy` in policy.yml.
+    pub fn from_config(config: &EntropyConfig) -> Self {
+        Self {
+            min_length: config.min_length,
+            min_entropy: config.min_entropy,
+            hex_min_length: config.hex_min_length,
+            hex_min_entropy: config.hex_min_entropy,
+        }
+    }
+
+    /// Also flag runs of at least `min_length` hex digits whose entropy
+    /// exceeds `min_entropy`.
+    pub fn with_hex(mut self, min_length: usize, min_entropy: f64) -> Self {
+        self.hex_min_length = Some(min_length);
+        self.hex_min_entropy = min_entropy;
+        self
+    }
+
     /// Calculate Shannon entropy of a string.
     fn shannon_entropy(s: &str) -> f64 {
         if s.is_empty() {
@@ -36,10 +63,10 @@ impl EntropySanitizer {
     }
 }
 
-impl Sanitizer for EntropySanitizer {
-    fn sanitize(&self, input: &str) -> String {
+impl EntropySanitizer {
+    /// Merged spans of high-entropy tokens to redact.
+    fn spans(&self, input: &str) -> Vec<(usize, usize)> {
         // Look for tokens after '=' or ':' that are long and high-entropy.
-        let mut result = input.to_string();
         let mut replacements: Vec<(usize, usize)> = Vec::new();
 
         let bytes = input.as_bytes();
@@ -127,18 +154,58 @@ impl Sanitizer for EntropySanitizer {
             }
         }
 
+        // Pass 3: runs of hex digits, which never reach a base64-level
+        // entropy threshold.
+        if let Some(min_length) = self.hex_min_length {
+            replacements.extend(self.hex_runs(input, min_length));
+        }
+
         // Sort and merge overlapping replacements
         replacements.sort_by_key(|&(start, _)| start);
-        let merged = merge_ranges(&replacements);
+        merge_ranges(&replacements)
+    }
 
+    /// Maximal runs of hex digits, not inside a longer alphanumeric word,
+    /// at least `min_length` long and over `hex_min_entropy`.
+    fn hex_runs(&self, input: &str, min_length: usize) -> Vec<(usize, usize)> {
+        let bytes = input.as_bytes();
+        let mut runs = Vec::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            if !bytes[pos].is_ascii_alphanumeric() {
+                pos += 1;
+                continue;
+            }
+            let start = pos;
+            while pos < bytes.len() && bytes[pos].is_ascii_alphanumeric() {
+                pos += 1;
+            }
+            let word = &input[start..pos];
+            if word.len() >= min_length
+                && word.bytes().all(|b| b.is_ascii_hexdigit())
+                && Self::shannon_entropy(word) > self.hex_min_entropy
+            {
+                runs.push((start, pos));
+            }
+        }
+        runs
+    }
+}
+
+impl Sanitizer for EntropySanitizer {
+    fn sanitize(&self, input: &str) -> String {
+        let mut result = input.to_string();
         // Apply replacements in reverse order
-        for &(start, end) in merged.iter().rev() {
+        for &(start, end) in self.spans(input).iter().rev() {
             result.replace_range(start..end, "<REDACTED>");
         }
-
         result
     }
 
+    fn findings(&self, input: &str) -> Vec<String> {
+        vec!["high_entropy".to_string(); self.spans(input).len()]
+    }
+
     fn name(&self) -> &str {
         "entropy"
     }
@@ -1,13 +1,28 @@
 pub mod aho;
+pub mod corpus;
 pub mod encoding;
 pub mod entropy;
+pub mod normalize;
 pub mod regex_san;
+pub mod truncate;
+
+use std::collections::BTreeMap;
+
+use crate::config::{SanitizeConfig, SanitizeStage};
+use crate::decision::{DecisionRecord, SecretFinding};
+use crate::error::{HookwiseError, Result};
 
 /// A single sanitization layer.
 pub trait Sanitizer: Send + Sync {
     /// Sanitize the input string, replacing detected secrets with `<REDACTED>`.
     fn sanitize(&self, input: &str) -> String;
 
+    /// The kind of each secret `sanitize` would redact in `input`, one
+    /// entry per redaction. Never includes the secret itself.
+    fn findings(&self, _input: &str) -> Vec<String> {
+        Vec::new()
+    }
+
     /// Name of this sanitizer layer (for logging/debugging).
     fn name(&self) -> &str;
 }
@@ -15,40 +30,123 @@ pub trait Sanitizer: Send + Sync {
 /// The complete sanitization pipeline. Runs all layers in sequence.
 pub struct SanitizePipeline {
     layers: Vec<Box<dyn Sanitizer>>,
+    /// Stages that only shape the cache key (normalize, truncate).
+    key_stages: Vec<Box<dyn Sanitizer>>,
 }
 
 impl SanitizePipeline {
     /// Create the default pipeline with all layers and built-in patterns.
     /// Order: encoding pre-process -> aho-corasick -> regex -> entropy.
     pub fn default_pipeline() -> Self {
-        let aho_for_encoding =
-            aho::AhoCorasickSanitizer::new(aho::AhoCorasickSanitizer::default_prefixes());
-        let regex_for_encoding =
-            regex_san::RegexSanitizer::new(regex_san::RegexSanitizer::default_patterns())
-                .expect("default regex patterns should compile");
-        let encoding_layer = encoding::EncodingSanitizer::new(vec![
-            Box::new(aho_for_encoding),
-            Box::new(regex_for_encoding),
-        ]);
-
-        let aho = aho::AhoCorasickSanitizer::new(aho::AhoCorasickSanitizer::default_prefixes());
-        let regex = regex_san::RegexSanitizer::new(regex_san::RegexSanitizer::default_patterns())
-            .expect("default regex patterns should compile");
-        let entropy = entropy::EntropySanitizer::new(20, 4.0);
+        Self::from_config(&SanitizeConfig::default())
+            .expect("default regex patterns should compile")
+    }
 
-        Self {
-            layers: vec![
-                Box::new(encoding_layer),
-                Box::new(aho),
-                Box::new(regex),
-                Box::new(entropy),
-            ],
+    /// Create the pipeline `config.stages` lists, with `config`'s prefixes
+    /// and patterns added to the built-in ones. Fails if a custom pattern
+    /// is not a valid regex, or if the stages are listed twice or put a
+    /// redaction stage after `normalize` or `truncate`.
+    pub fn from_config(config: &SanitizeConfig) -> Result<Self> {
+        let mut key_stage = None;
+        for (i, stage) in config.stages.iter().enumerate() {
+            if config.stages[..i].contains(stage) {
+                return Err(invalid_stages(format!("`{}` is listed twice", stage)));
+            }
+            match key_stage {
+                Some(key) if stage.redacts() => {
+                    return Err(invalid_stages(format!(
+                        "`{}` redacts secrets and must come before `{}`",
+                        stage, key
+                    )));
+                }
+                None if !stage.redacts() => key_stage = Some(*stage),
+                _ => {}
+            }
+        }
+        let enabled = |stage| config.stages.contains(&stage);
+
+        let mut prefixes = aho::AhoCorasickSanitizer::default_prefixes();
+        prefixes.extend(config.prefixes.iter().cloned());
+        let mut patterns = regex_san::RegexSanitizer::default_patterns();
+        patterns.extend(config.patterns.iter().cloned());
+
+        let mut layers: Vec<Box<dyn Sanitizer>> = Vec::new();
+        let mut key_stages: Vec<Box<dyn Sanitizer>> = Vec::new();
+        for stage in &config.stages {
+            match stage {
+                SanitizeStage::Encoding => {
+                    // Decoded values go through the enabled matchers
+                    let mut decoded_layers: Vec<Box<dyn Sanitizer>> = Vec::new();
+                    if enabled(SanitizeStage::Prefixes) {
+                        decoded_layers
+                            .push(Box::new(aho::AhoCorasickSanitizer::new(prefixes.clone())));
+                    }
+                    if enabled(SanitizeStage::Patterns) {
+                        decoded_layers
+                            .push(Box::new(regex_san::RegexSanitizer::new(patterns.clone())?));
+                    }
+                    if enabled(SanitizeStage::Literals) && !config.literals.is_empty() {
+                        decoded_layers.push(Box::new(aho::LiteralSanitizer::new(&config.literals)));
+                    }
+                    layers.push(Box::new(encoding::EncodingSanitizer::new(decoded_layers)));
+                }
+                SanitizeStage::Literals => {
+                    if !config.literals.is_empty() {
+                        layers.push(Box::new(aho::LiteralSanitizer::new(&config.literals)));
+                    }
+                }
+                SanitizeStage::Prefixes => {
+                    layers.push(Box::new(aho::AhoCorasickSanitizer::new(prefixes.clone())));
+                }
+                SanitizeStage::Patterns => {
+                    layers.push(Box::new(regex_san::RegexSanitizer::new(patterns.clone())?));
+                }
+                SanitizeStage::Entropy => {
+                    layers.push(Box::new(entropy::EntropySanitizer::from_config(
+                        &config.entropy,
+                    )));
+                }
+                SanitizeStage::Normalize => {
+                    if let Some(normalizer) =
+                        normalize::VolatileNormalizer::from_config(&config.normalize)
+                    {
+                        key_stages.push(Box::new(normalizer));
+                    }
+                }
+                SanitizeStage::Truncate => {
+                    if let Some(max_bytes) = config.max_input_bytes {
+                        key_stages.push(Box::new(truncate::InputTruncator::new(max_bytes)));
+                    }
+                }
+            }
         }
+        Ok(Self { layers, key_stages })
     }
 
-    /// Create a pipeline from custom layers.
+    /// Create a pipeline from custom layers, without normalization.
     pub fn new(layers: Vec<Box<dyn Sanitizer>>) -> Self {
-        Self { layers }
+        Self {
+            layers,
+            key_stages: Vec::new(),
+        }
+    }
+
+    /// Names of the stages that will run, in order.
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.layers
+            .iter()
+            .chain(&self.key_stages)
+            .map(|stage| stage.name())
+            .collect()
+    }
+
+    /// Run the cache-key stages (`normalize`, `truncate`) over already
+    /// sanitized input. Applied to the cache key only; `sanitize` itself
+    /// leaves volatile tokens and long inputs alone.
+    pub fn normalize(&self, sanitized: &str) -> String {
+        self.key_stages
+            .iter()
+            .fold(sanitized.to_string(), |text, stage| stage.sanitize(&text))
     }
 
     /// Run all sanitization layers in sequence.
@@ -59,4 +157,82 @@ impl SanitizePipeline {
         }
         result
     }
+
+    /// Run all layers like [`sanitize`](Self::sanitize), also counting what
+    /// each layer redacted, by kind.
+    pub fn sanitize_with_findings(&self, input: &str) -> (String, Vec<SecretFinding>) {
+        let mut result = input.to_string();
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for layer in &self.layers {
+            for kind in layer.findings(&result) {
+                *counts.entry(kind).or_default() += 1;
+            }
+            result = layer.sanitize(&result);
+        }
+        let findings = counts
+            .into_iter()
+            .map(|(kind, count)| SecretFinding { kind, count })
+            .collect();
+        (result, findings)
+    }
+}
+
+/// Secret findings across stored decisions, for `hookwise stats`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SecretSummary {
+    /// Decisions whose input had anything redacted.
+    pub calls: usize,
+    /// Redactions per finding kind.
+    pub by_kind: BTreeMap<String, usize>,
+    /// Redactions per role.
+    pub by_role: BTreeMap<String, usize>,
+    /// Redactions per tool, with the program name for Bash (`Bash: curl`).
+    pub by_command: BTreeMap<String, usize>,
+}
+
+/// Total the secret findings recorded on `records`.
+pub fn summarize_secrets(records: &[DecisionRecord]) -> SecretSummary {
+    let mut summary = SecretSummary::default();
+    for record in records {
+        let secrets = &record.metadata.secrets;
+        if secrets.is_empty() {
+            continue;
+        }
+        summary.calls += 1;
+        let mut total = 0;
+        for finding in secrets {
+            *summary.by_kind.entry(finding.kind.clone()).or_default() += finding.count;
+            total += finding.count;
+        }
+        *summary.by_role.entry(record.key.role.clone()).or_default() += total;
+        *summary.by_command.entry(command_label(record)).or_default() += total;
+    }
+    summary
+}
+
+fn invalid_stages(reason: String) -> HookwiseError {
+    HookwiseError::InvalidPolicy {
+        reason: format!("sanitize.stages: {}", reason),
+    }
+}
+
+/// The tool name, plus the program a Bash command starts with.
+fn command_label(record: &DecisionRecord) -> String {
+    let tool = &record.key.tool;
+    if tool != "Bash" {
+        return tool.clone();
+    }
+    let program = serde_json::from_str::<serde_json::Value>(&record.key.sanitized_input)
+        .ok()
+        .and_then(|input| {
+            let command = input.get("command")?.as_str()?;
+            command
+                .split_whitespace()
+                .find(|word| !word.contains('='))
+                .map(|word| word.rsplit('/').next().unwrap_or(word).to_string())
+        });
+    match program {
+        Some(program) => format!("Bash: {program}"),
+        None => tool.clone(),
+    }
 }
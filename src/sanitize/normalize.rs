@@ -0,0 +1,95 @@
+use regex::{Captures, Regex};
+
+use crate::config::NormalizeConfig;
+
+use super::Sanitizer;
+
+/// Placeholders written in place of volatile tokens.
+pub const TIMESTAMP: &str = "<TIMESTAMP>";
+pub const UUID: &str = "<UUID>";
+pub const TMP: &str = "<TMP>";
+pub const PORT: &str = "<PORT>";
+pub const SHA: &str = "<SHA>";
+
+/// A rewrite: the pattern and how to build its replacement.
+struct Rule {
+    regex: Regex,
+    replace: fn(&Captures) -> String,
+}
+
+/// Rewrites values that change on every retry (timestamps, UUIDs, temp
+/// paths, ports, commit ids) to stable placeholders. Runs after redaction,
+/// on the input used as the cache key.
+pub struct VolatileNormalizer {
+    rules: Vec<Rule>,
+}
+
+impl VolatileNormalizer {
+    /// Build the rules enabled in `config`. None when every kind is off.
+    pub fn from_config(config: &NormalizeConfig) -> Option<Self> {
+        let rule = |pattern: &str, replace: fn(&Captures) -> String| Rule {
+            regex: Regex::new(pattern).expect("normalization patterns should compile"),
+            replace,
+        };
+        let mut rules = Vec::new();
+        if config.uuids {
+            rules.push(rule(
+                r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b",
+                |_| UUID.into(),
+            ));
+        }
+        if config.timestamps {
+            // ISO 8601 date, optionally with a time and offset
+            rules.push(rule(
+                r"\b\d{4}-\d{2}-\d{2}(?:[T ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?)?\b",
+                |_| TIMESTAMP.into(),
+            ));
+            // Compact stamps as in file names: 20240131T101500, 20240131-101500
+            rules.push(rule(r"\b\d{8}[T_-]?\d{6}\b", |_| TIMESTAMP.into()));
+            // Unix epoch seconds or milliseconds (2001-2033)
+            rules.push(rule(r"\b1\d{9}(?:\d{3})?\b", |_| TIMESTAMP.into()));
+        }
+        if config.temp_paths {
+            rules.push(rule(
+                r#"((?:^|[\s"'=:(])(?:/private)?(?:/tmp|/var/tmp|/var/folders/[^/\s"'\\]+/[^/\s"'\\]+/T))/[^/\s"'\\;|&)]+"#,
+                |c| format!("{}/{}", &c[1], TMP),
+            ));
+        }
+        if config.ports {
+            rules.push(rule(
+                r"(\blocalhost|\b127\.0\.0\.1|\b0\.0\.0\.0|\[::1?\]):\d{1,5}\b",
+                |c| format!("{}:{}", &c[1], PORT),
+            ));
+            rules.push(rule(r"(--port[= ])\d{1,5}\b", |c| {
+                format!("{}{}", &c[1], PORT)
+            }));
+        }
+        if config.git_shas {
+            rules.push(rule(r"\b[0-9a-f]{7,40}\b", |c| {
+                let run = &c[0];
+                let mixed = run.bytes().any(|b| b.is_ascii_digit())
+                    && run.bytes().any(|b| b.is_ascii_alphabetic());
+                if mixed {
+                    SHA.into()
+                } else {
+                    run.into()
+                }
+            }));
+        }
+        (!rules.is_empty()).then_some(Self { rules })
+    }
+}
+
+impl Sanitizer for VolatileNormalizer {
+    fn sanitize(&self, input: &str) -> String {
+        self.rules.iter().fold(input.to_string(), |text, rule| {
+            rule.regex
+                .replace_all(&text, |c: &Captures| (rule.replace)(c))
+                .into_owned()
+        })
+    }
+
+    fn name(&self) -> &str {
+        "normalize"
+    }
+}
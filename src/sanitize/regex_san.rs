@@ -4,10 +4,21 @@ use crate::error::HookwiseError;
 
 use super::Sanitizer;
 
+/// Finding kinds for [`RegexSanitizer::default_patterns`], in order.
+const DEFAULT_KINDS: &[&str] = &[
+    "bearer_token",
+    "credential_assignment",
+    "connection_string",
+    "cli_secret_flag",
+];
+
 /// Layer 2: Positional/contextual pattern matching via RegexSet.
 pub struct RegexSanitizer {
     regex_set: RegexSet,
     patterns: Vec<regex::Regex>,
+    /// Finding kind per pattern: a name for built-in patterns,
+    /// `custom_pattern` for the rest.
+    kinds: Vec<String>,
 }
 
 impl RegexSanitizer {
@@ -24,9 +35,18 @@ impl RegexSanitizer {
                 })
             })
             .collect::<crate::error::Result<Vec<_>>>()?;
+        let defaults = Self::default_patterns();
+        let kinds = patterns
+            .iter()
+            .map(|p| match defaults.iter().position(|d| d == p) {
+                Some(i) => DEFAULT_KINDS[i].to_string(),
+                None => "custom_pattern".to_string(),
+            })
+            .collect();
         Ok(Self {
             regex_set,
             patterns: compiled,
+            kinds,
         })
     }
 
@@ -56,28 +76,44 @@ impl Sanitizer for RegexSanitizer {
 
         let mut result = input.to_string();
         for &idx in &matching {
-            let re = &self.patterns[idx];
-            // Replace the secret part (the capture group after the prefix) with <REDACTED>.
-            // Each pattern is designed so that group 1 is the prefix to keep.
-            result = re
-                .replace_all(&result, |caps: &regex::Captures| {
-                    if let Some(prefix) = caps.get(1) {
-                        format!("{}<REDACTED>", prefix.as_str())
-                    } else {
-                        "<REDACTED>".to_string()
-                    }
-                })
-                .into_owned();
+            result = redact(&self.patterns[idx], &result);
         }
 
         result
     }
 
+    fn findings(&self, input: &str) -> Vec<String> {
+        // Patterns apply in sequence, so count each on what the earlier
+        // ones left.
+        let mut findings = Vec::new();
+        let mut current = input.to_string();
+        for idx in self.regex_set.matches(input).into_iter() {
+            let re = &self.patterns[idx];
+            let count = re.find_iter(&current).count();
+            findings.extend(std::iter::repeat_n(self.kinds[idx].clone(), count));
+            current = redact(re, &current);
+        }
+        findings
+    }
+
     fn name(&self) -> &str {
         "regex"
     }
 }
 
+/// Replace the secret part (the capture group after the prefix) with <REDACTED>.
+/// Each pattern is designed so that group 1 is the prefix to keep.
+fn redact(re: &regex::Regex, input: &str) -> String {
+    re.replace_all(input, |caps: &regex::Captures| {
+        if let Some(prefix) = caps.get(1) {
+            format!("{}<REDACTED>", prefix.as_str())
+        } else {
+            "<REDACTED>".to_string()
+        }
+    })
+    .into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
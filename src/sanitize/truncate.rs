@@ -0,0 +1,101 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::Sanitizer;
+
+/// Cuts tool inputs longer than `max_bytes` before they become a cache key.
+/// JSON input stays valid JSON: the longest string values are shortened
+/// until the whole fits. Each cut value ends in a marker with its original
+/// length and a hash of the full value, so inputs that differ only past the
+/// cut still get different keys.
+pub struct InputTruncator {
+    max_bytes: usize,
+}
+
+impl InputTruncator {
+    pub fn new(max_bytes: usize) -> Self {
+        Self { max_bytes }
+    }
+}
+
+const MARKER: &str = "<TRUNCATED ";
+
+/// The marker ending a cut value.
+fn marker(text: &str) -> String {
+    let hash = Sha256::digest(text.as_bytes());
+    format!(
+        "{}{} bytes sha256:{}>",
+        MARKER,
+        text.len(),
+        &format!("{:x}", hash)[..12]
+    )
+}
+
+/// Bytes `text` takes up inside a JSON string.
+fn escaped_len(text: &str) -> usize {
+    serde_json::to_string(text).map_or(text.len(), |s| s.len() - 2)
+}
+
+/// `text` shortened, marker included, so it takes up `excess` fewer bytes
+/// as a JSON string (or as little as possible).
+fn cut(text: &str, excess: usize) -> String {
+    let marker = marker(text);
+    let budget = escaped_len(text).saturating_sub(excess + escaped_len(&marker));
+    let mut used = 0;
+    let mut end = 0;
+    for (i, c) in text.char_indices() {
+        used += escaped_len(c.encode_utf8(&mut [0; 4]));
+        if used > budget {
+            break;
+        }
+        end = i + c.len_utf8();
+    }
+    format!("{}{}", &text[..end], marker)
+}
+
+/// The longest string in `value` that hasn't been cut yet (first one wins
+/// ties, in document order).
+fn longest_string(value: &mut Value) -> Option<&mut String> {
+    let mut best: Option<&mut String> = None;
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::String(s)
+                if !s.contains(MARKER) && best.as_ref().is_none_or(|b| s.len() > b.len()) =>
+            {
+                best = Some(s)
+            }
+            Value::Array(items) => stack.extend(items.iter_mut().rev()),
+            Value::Object(map) => stack.extend(map.values_mut().rev()),
+            _ => {}
+        }
+    }
+    best
+}
+
+impl Sanitizer for InputTruncator {
+    fn sanitize(&self, input: &str) -> String {
+        if input.len() <= self.max_bytes {
+            return input.to_string();
+        }
+        let Ok(mut value) = serde_json::from_str::<Value>(input) else {
+            return cut(input, input.len() - self.max_bytes);
+        };
+        loop {
+            let serialized = serde_json::to_string(&value).unwrap_or_default();
+            let excess = serialized.len().saturating_sub(self.max_bytes);
+            if excess == 0 {
+                return serialized;
+            }
+            match longest_string(&mut value) {
+                Some(longest) => *longest = cut(longest, excess),
+                // Nothing left to shorten: keys and numbers alone are over
+                None => return serialized,
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "truncate"
+    }
+}
@@ -0,0 +1,137 @@
+//! JSON Schema for the decision records in the rule files
+//! (`rules/{allow,deny,ask}.jsonl`), printed by `hookwise schema`.
+//!
+//! The schema describes [`crate::decision::SCHEMA_VERSION`]. Fields may be
+//! added within a version, so consumers should ignore properties they don't
+//! know; a change that would break them bumps the version.
+
+use serde_json::{json, Value};
+
+use crate::decision::SCHEMA_VERSION;
+
+/// Tier names as they appear in `metadata.tier`.
+const TIERS: &[&str] = &[
+    "PathPolicy",
+    "ExactCache",
+    "TokenJaccard",
+    "EmbeddingSimilarity",
+    "Supervisor",
+    "Human",
+    "PackageGate",
+    "CloudCli",
+    "GitPolicy",
+    "ContentInspection",
+    "Exfiltration",
+    "PathLock",
+    "EphemeralPath",
+    "SensitivePath",
+    "Override",
+    "Deadline",
+    "Default",
+];
+
+/// The schema of one decision record line.
+pub fn decision_record() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "hookwise decision record",
+        "description": "One line of a hookwise rule file: a cached permission decision for a sanitized tool call.",
+        "type": "object",
+        "required": ["schema_version", "key", "decision", "metadata", "timestamp", "scope", "session_id"],
+        "properties": {
+            "schema_version": {
+                "description": "Layout version. Records without it predate versioning and read as version 1, which has the same fields.",
+                "const": SCHEMA_VERSION
+            },
+            "key": { "$ref": "#/$defs/cache_key" },
+            "decision": { "$ref": "#/$defs/decision" },
+            "metadata": metadata(),
+            "timestamp": { "type": "string", "format": "date-time" },
+            "scope": { "enum": ["org", "project", "user", "role"] },
+            "file_path": {
+                "description": "For Write/Edit calls: the file written.",
+                "type": ["string", "null"]
+            },
+            "session_id": { "type": "string" }
+        },
+        "$defs": {
+            "decision": { "enum": ["allow", "deny", "ask"] },
+            "cache_key": cache_key()
+        }
+    })
+}
+
+fn cache_key() -> Value {
+    json!({
+        "type": "object",
+        "required": ["sanitized_input", "tool", "role"],
+        "properties": {
+            "sanitized_input": {
+                "description": "The tool input with secrets and volatile tokens replaced.",
+                "type": "string"
+            },
+            "tool": { "type": "string" },
+            "role": { "description": "Session role, or * for any.", "type": "string" }
+        }
+    })
+}
+
+fn metadata() -> Value {
+    let targets = json!({
+        "description": "Per-path decisions of a call touching several paths.",
+        "type": "array",
+        "items": {
+            "type": "object",
+            "required": ["path"],
+            "properties": {
+                "path": { "type": "string" },
+                "decision": { "anyOf": [{ "$ref": "#/$defs/decision" }, { "type": "null" }] }
+            }
+        }
+    });
+    let secrets = json!({
+        "description": "What the sanitizer redacted, by kind. Values are never recorded.",
+        "type": "array",
+        "items": {
+            "type": "object",
+            "required": ["kind", "count"],
+            "properties": {
+                "kind": { "type": "string" },
+                "count": { "type": "integer", "minimum": 0 }
+            }
+        }
+    });
+    let client = json!({
+        "description": "The assistant whose hook call the decision answered.",
+        "type": "object",
+        "required": ["format", "assistant"],
+        "properties": {
+            "format": { "description": "Hook payload format.", "enum": ["claude", "gemini"] },
+            "assistant": { "description": "e.g. claude-code or gemini-cli.", "type": "string" },
+            "version": { "description": "The assistant's version, when it sends one.", "type": "string" }
+        }
+    });
+    json!({
+        "type": "object",
+        "required": ["tier", "confidence", "reason"],
+        "properties": {
+            "tier": {
+                "description": "The cascade tier that made the decision.",
+                "enum": TIERS
+            },
+            "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+            "reason": { "type": "string" },
+            "matched_key": {
+                "description": "For similarity tiers: the key of the matched record.",
+                "anyOf": [{ "$ref": "#/$defs/cache_key" }, { "type": "null" }]
+            },
+            "similarity_score": { "type": ["number", "null"] },
+            "targets": targets,
+            "message": { "description": "The policy's rendered deny/ask message.", "type": "string" },
+            "suggestion": { "description": "A remediation hint.", "type": "string" },
+            "risk_score": { "description": "Built-in command risk score.", "type": "number" },
+            "secrets": secrets,
+            "client": client
+        }
+    })
+}
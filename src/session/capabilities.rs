@@ -0,0 +1,109 @@
+//! What a session may do, for the `hookwise_capabilities` MCP tool, so an
+//! agent can plan within its permissions instead of probing for denials.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::cascade::path_policy::{READ_TOOLS, WRITE_TOOLS};
+use crate::config::{PathPrecedence, PolicyConfig, RoleDefinition};
+use crate::decision::{Decision, DecisionRecord};
+
+/// How much a session can do without a human in the loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Autonomy {
+    /// No role registered: every tool call is denied until one is.
+    Unregistered,
+    /// Role path policy and the decision cascade gate every tool call.
+    Gated,
+    /// hookwise is disabled for the session: every tool call is permitted.
+    Unrestricted,
+}
+
+/// Cached decisions for one tool.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DecisionCounts {
+    pub allow: usize,
+    pub deny: usize,
+    pub ask: usize,
+}
+
+/// Which tools the path policy inspects, and what is already decided.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolRestrictions {
+    /// Tools whose target paths must match `allow_write` and not `deny_write`.
+    pub write_checked: Vec<String>,
+    /// Tools whose target paths must match `allow_read`.
+    pub read_checked: Vec<String>,
+    /// Cached decisions applying to this role (its own and wildcard), by tool.
+    pub cached: BTreeMap<String, DecisionCounts>,
+}
+
+/// A session's role, expanded path globs, and tool restrictions.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    pub session_id: String,
+    pub autonomy: Autonomy,
+    pub role: Option<String>,
+    pub description: Option<String>,
+    /// Globs with `{{category}}` macros already expanded.
+    pub allow_write: Vec<String>,
+    pub deny_write: Vec<String>,
+    pub allow_read: Vec<String>,
+    /// Writes here always need a human unless `path_precedence` denies them.
+    pub sensitive_paths: Vec<String>,
+    pub path_precedence: PathPrecedence,
+    pub tools: ToolRestrictions,
+}
+
+impl Capabilities {
+    /// Summarize a session from its role (None when unregistered) and the
+    /// project's policy and cached decisions.
+    pub fn new(
+        session_id: &str,
+        disabled: bool,
+        role: Option<&RoleDefinition>,
+        policy: &PolicyConfig,
+        decisions: &[DecisionRecord],
+    ) -> Self {
+        let autonomy = match (disabled, role) {
+            (true, _) => Autonomy::Unrestricted,
+            (false, Some(_)) => Autonomy::Gated,
+            (false, None) => Autonomy::Unregistered,
+        };
+
+        let mut cached: BTreeMap<String, DecisionCounts> = BTreeMap::new();
+        if let Some(role) = role {
+            for record in decisions
+                .iter()
+                .filter(|r| r.key.role == role.name || r.key.role == "*")
+            {
+                let counts = cached.entry(record.key.tool.clone()).or_default();
+                match record.decision {
+                    Decision::Allow => counts.allow += 1,
+                    Decision::Deny => counts.deny += 1,
+                    Decision::Ask => counts.ask += 1,
+                }
+            }
+        }
+
+        let paths = role.map(|r| r.paths.clone());
+        Self {
+            session_id: session_id.to_string(),
+            autonomy,
+            role: role.map(|r| r.name.clone()),
+            description: role.map(|r| r.description.clone()),
+            allow_write: paths.as_ref().map(|p| p.allow_write.clone()).unwrap_or_default(),
+            deny_write: paths.as_ref().map(|p| p.deny_write.clone()).unwrap_or_default(),
+            allow_read: paths.map(|p| p.allow_read).unwrap_or_default(),
+            sensitive_paths: policy.sensitive_paths.ask_write.clone(),
+            path_precedence: policy.path_precedence,
+            tools: ToolRestrictions {
+                write_checked: WRITE_TOOLS.iter().map(|t| t.to_string()).collect(),
+                read_checked: READ_TOOLS.iter().map(|t| t.to_string()).collect(),
+                cached,
+            },
+        }
+    }
+}
@@ -15,6 +15,8 @@ impl SessionContext {
             task_description: None,
             registered_at: None,
             disabled: false,
+            mode: Default::default(),
+            fallback_roles: Vec::new(),
         }
     }
 }
@@ -0,0 +1,296 @@
+//! Two-person rule for `hookwise disable`. In a project whose
+//! `session_disable.protected` is set, a disable only takes effect when two
+//! verified identities listed in `approvals.approvers` ask for it within
+//! `request_ttl_secs`, or when it carries an org-scope token. Pending
+//! requests live next to the exclusion file.
+
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ApprovalConfig, SessionDisableConfig, SessionMode};
+use crate::error::{HookwiseError, Result};
+use crate::identity::{is_listed, Identity};
+
+use super::registration::{write_atomic, FileLock};
+use super::{set_file_permissions_0600, Exclusion, SessionManager, SESSIONS};
+
+/// A request to disable a session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisableRequest {
+    pub session_id: String,
+    pub requested_by: Identity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// How long to disable for. `None` means until re-enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+    pub requested_at: DateTime<Utc>,
+}
+
+impl DisableRequest {
+    pub fn new(
+        session_id: &str,
+        requested_by: Identity,
+        reason: Option<String>,
+        duration_secs: Option<u64>,
+    ) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            requested_by,
+            reason,
+            duration_secs,
+            requested_at: Utc::now(),
+        }
+    }
+
+    /// When the disable lapses if it takes effect at `now`.
+    pub fn until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.duration_secs.map(|secs| after(now, secs))
+    }
+}
+
+/// How a disable was allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisableAuthorization {
+    /// The project isn't protected.
+    Unprotected,
+    /// The caller presented a listed org token.
+    OrgToken,
+    /// A different identity confirmed a pending request.
+    SecondApprover,
+}
+
+impl DisableAuthorization {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unprotected => "unprotected",
+            Self::OrgToken => "org_token",
+            Self::SecondApprover => "second_approver",
+        }
+    }
+}
+
+/// The result of [`SessionManager::authorize_disable`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisableOutcome {
+    /// The session is disabled. `request` is the request that took effect:
+    /// for a second approver, the original one (with its requester).
+    Disabled {
+        authorization: DisableAuthorization,
+        request: DisableRequest,
+        until: Option<DateTime<Utc>>,
+    },
+    /// Recorded; waiting for a second approver until `expires_at`.
+    Pending { expires_at: DateTime<Utc> },
+}
+
+impl SessionManager {
+    /// Disable a session under the two-person rule. Unprotected projects
+    /// disable immediately. In protected ones a valid org `token` disables
+    /// immediately; otherwise the first request is recorded as pending and
+    /// a request from a different identity confirms it. Both requesters
+    /// must be verified and listed in `approvals.approvers`, whatever
+    /// `approvals.require_verified` says.
+    pub fn authorize_disable(
+        &self,
+        request: DisableRequest,
+        token: Option<&str>,
+        config: &SessionDisableConfig,
+        approvals: &ApprovalConfig,
+    ) -> Result<DisableOutcome> {
+        if !config.protected {
+            return self.apply_disable(DisableAuthorization::Unprotected, request);
+        }
+        if let Some(token) = token {
+            if !org_token_matches(config, token) {
+                return Err(HookwiseError::DisableRefused {
+                    reason: "the token does not match any session_disable.org_token_sha256".into(),
+                });
+            }
+            self.take_disable_request(&request.session_id)?;
+            return self.apply_disable(DisableAuthorization::OrgToken, request);
+        }
+
+        check_requester(approvals, &request.requested_by)?;
+
+        let _lock = FileLock::acquire(&self.disable_request_file)?;
+        let now = Utc::now();
+        let ttl = config.request_ttl_secs;
+        let mut pending = read_requests(&self.disable_request_file)?;
+        pending.retain(|r| after(r.requested_at, ttl) > now);
+
+        let Some(i) = pending
+            .iter()
+            .position(|r| r.session_id == request.session_id)
+        else {
+            let expires_at = after(request.requested_at, ttl);
+            pending.push(request);
+            write_requests(&self.disable_request_file, &pending)?;
+            return Ok(DisableOutcome::Pending { expires_at });
+        };
+
+        let original = &pending[i];
+        if original
+            .requested_by
+            .user
+            .eq_ignore_ascii_case(&request.requested_by.user)
+        {
+            let expires_at = after(original.requested_at, ttl);
+            write_requests(&self.disable_request_file, &pending)?;
+            return Ok(DisableOutcome::Pending { expires_at });
+        }
+        // The request file is only as trusted as the runtime directory, so
+        // the first requester is checked again too.
+        check_requester(approvals, &original.requested_by)?;
+
+        let original = pending.remove(i);
+        write_requests(&self.disable_request_file, &pending)?;
+        self.apply_disable(DisableAuthorization::SecondApprover, original)
+    }
+
+    /// Drop any pending disable request for a session, returning it.
+    pub fn take_disable_request(&self, session_id: &str) -> Result<Option<DisableRequest>> {
+        let _lock = FileLock::acquire(&self.disable_request_file)?;
+        let mut pending = read_requests(&self.disable_request_file)?;
+        let Some(i) = pending.iter().position(|r| r.session_id == session_id) else {
+            return Ok(None);
+        };
+        let request = pending.remove(i);
+        write_requests(&self.disable_request_file, &pending)?;
+        Ok(Some(request))
+    }
+
+    fn apply_disable(
+        &self,
+        authorization: DisableAuthorization,
+        request: DisableRequest,
+    ) -> Result<DisableOutcome> {
+        let now = Utc::now();
+        let until = request.until(now);
+        self.add_exclusion(Exclusion {
+            session_id: request.session_id.clone(),
+            disabled_at: Some(now),
+            until,
+            disabled_by: Some(request.requested_by.user.clone()),
+            reason: request.reason.clone(),
+            reminded_at: None,
+        })?;
+        // Let the exclusion file decide when the disable lapses.
+        SESSIONS.remove(&request.session_id);
+        Ok(DisableOutcome::Disabled {
+            authorization,
+            request,
+            until,
+        })
+    }
+}
+
+/// Parse a duration like `90s`, `30m`, `2h` or `1d` (bare numbers are
+/// seconds) into seconds.
+pub fn parse_duration(s: &str) -> std::result::Result<u64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{s}': expected e.g. 30m, 2h or 1d"))?;
+    let scale = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(format!("invalid duration unit '{unit}': use s, m, h or d")),
+    };
+    number
+        .checked_mul(scale)
+        .ok_or_else(|| format!("duration '{s}' is too long"))
+}
+
+/// `at` plus `secs`, saturating at the latest representable time.
+fn after(at: DateTime<Utc>, secs: u64) -> DateTime<Utc> {
+    i64::try_from(secs)
+        .ok()
+        .and_then(Duration::try_seconds)
+        .and_then(|d| at.checked_add_signed(d))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// Whether a session may register in audit mode, which lifts enforcement
+/// as a disable does. Allowed where the policy's `session_mode` is already
+/// audit or the project isn't protected; in a protected project only with a
+/// valid org `token`, since the session registers itself.
+pub fn authorize_audit_mode(
+    config: &SessionDisableConfig,
+    session_mode: SessionMode,
+    token: Option<&str>,
+) -> Result<()> {
+    if session_mode == SessionMode::Audit || !config.protected {
+        return Ok(());
+    }
+    match token {
+        Some(token) if org_token_matches(config, token) => Ok(()),
+        Some(_) => Err(HookwiseError::DisableRefused {
+            reason: "the token does not match any session_disable.org_token_sha256".into(),
+        }),
+        None => Err(HookwiseError::DisableRefused {
+            reason: "audit mode lifts enforcement in a protected project; pass an org token \
+                     with --token, or use `hookwise disable`, which needs a second approver"
+                .into(),
+        }),
+    }
+}
+
+/// Check that `identity` may take part in a two-person disable. Anyone can
+/// claim an environment or git identity, so neither person may use one.
+fn check_requester(approvals: &ApprovalConfig, identity: &Identity) -> Result<()> {
+    if !identity.verified {
+        return Err(HookwiseError::DisableRefused {
+            reason: format!(
+                "'{}' comes from the {} provider; a disable in a protected project needs \
+                 two verified identities, or an org token with --token",
+                identity.user, identity.provider
+            ),
+        });
+    }
+    if !is_listed(&approvals.approvers, identity) {
+        return Err(HookwiseError::DisableRefused {
+            reason: format!(
+                "'{}' is not listed in approvals.approvers; a disable in a protected \
+                 project needs two approvers, or an org token with --token",
+                identity.user
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn org_token_matches(config: &SessionDisableConfig, token: &str) -> bool {
+    use sha2::{Digest, Sha256};
+    let digest = format!("{:x}", Sha256::digest(token.trim().as_bytes()));
+    config
+        .org_token_sha256
+        .iter()
+        .any(|listed| listed.trim().eq_ignore_ascii_case(&digest))
+}
+
+fn read_requests(path: &Path) -> Result<Vec<DisableRequest>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = std::fs::read_to_string(path)?;
+    if contents.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn write_requests(path: &Path, requests: &[DisableRequest]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_atomic(path, serde_json::to_string_pretty(requests)?.as_bytes())?;
+    set_file_permissions_0600(path);
+    Ok(())
+}
@@ -1,13 +1,17 @@
+pub mod capabilities;
 pub mod context;
+pub mod disable;
 pub mod registration;
+pub mod state;
 
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::LazyLock;
 
-use crate::config::{CompiledPathPolicy, PolicyConfig, RoleDefinition, RolesConfig};
+use crate::config::{CompiledPathPolicy, PolicyConfig, RoleDefinition, RolesConfig, SessionMode};
 use crate::error::{HookwiseError, Result};
 
 /// In-memory session context, populated on first tool call from a session.
@@ -24,6 +28,12 @@ pub struct SessionContext {
     pub task_description: Option<String>,
     pub registered_at: Option<DateTime<Utc>>,
     pub disabled: bool,
+    /// Audit-mode sessions are allowed everything, with decisions logged.
+    pub mode: SessionMode,
+    /// Roles after `role` for a session registered with several, in order,
+    /// with their compiled path policies. A call is evaluated as the first
+    /// role whose path policy doesn't deny it.
+    pub fallback_roles: Vec<(RoleDefinition, std::sync::Arc<CompiledPathPolicy>)>,
 }
 
 /// Global concurrent session cache.
@@ -33,6 +43,8 @@ pub static SESSIONS: LazyLock<DashMap<String, SessionContext>> = LazyLock::new(D
 pub struct SessionManager {
     registration_file: PathBuf,
     exclusion_file: PathBuf,
+    disable_request_file: PathBuf,
+    state_file: PathBuf,
 }
 
 impl SessionManager {
@@ -42,6 +54,9 @@ impl SessionManager {
         Self {
             registration_file: runtime_dir.join(format!("hookwise-{suffix}-sessions.json")),
             exclusion_file: runtime_dir.join(format!("hookwise-{suffix}-exclusions.json")),
+            disable_request_file: runtime_dir
+                .join(format!("hookwise-{suffix}-disable-requests.json")),
+            state_file: runtime_dir.join(format!("hookwise-{suffix}-session-state.json")),
         }
     }
 
@@ -81,9 +96,11 @@ impl SessionManager {
             return Ok(ctx.clone());
         }
 
-        // Populate from registration file + git info
-        let (org, project) = extract_git_org_project(cwd);
-        let user = whoami();
+        // Populate from the identity provider, registration file + git info
+        let (git_org, project) = extract_git_org_project(cwd);
+        let identity = crate::identity::current(std::path::Path::new(cwd));
+        let user = identity.user;
+        let org = identity.org.unwrap_or(git_org);
         let team = std::env::var("CLAUDE_TEAM_ID").ok();
 
         let mut ctx = SessionContext {
@@ -98,6 +115,8 @@ impl SessionManager {
             task_description: None,
             registered_at: None,
             disabled: false,
+            mode: Default::default(),
+            fallback_roles: Vec::new(),
         };
 
         // Check if disabled
@@ -122,11 +141,22 @@ impl SessionManager {
                 ctx.path_policy = Some(std::sync::Arc::new(compiled));
                 ctx.role = Some(role_def.clone());
             }
+            for name in &entry.fallback_roles {
+                if let Some(role_def) = roles.get_role(name) {
+                    let compiled = CompiledPathPolicy::compile(
+                        &role_def.paths,
+                        &policy.sensitive_paths.ask_write,
+                    )?;
+                    ctx.fallback_roles
+                        .push((role_def.clone(), std::sync::Arc::new(compiled)));
+                }
+            }
 
             ctx.task_description = entry.task.clone();
             ctx.agent_prompt_hash = entry.prompt_hash.clone();
             ctx.agent_prompt_path = entry.prompt_path.as_ref().map(PathBuf::from);
             ctx.registered_at = Some(entry.registered_at);
+            ctx.mode = entry.mode.unwrap_or(policy.session_mode);
         } else if let Ok(role_name) = std::env::var("HOOKWISE_ROLE") {
             // Env var fallback
             let cwd_path = PathBuf::from(cwd);
@@ -142,19 +172,36 @@ impl SessionManager {
                 ctx.role = Some(role_def.clone());
                 ctx.registered_at = Some(Utc::now());
             }
+            ctx.mode = policy.session_mode;
         }
 
         SESSIONS.insert(session_id.to_string(), ctx.clone());
         Ok(ctx)
     }
 
-    /// Register a session with a role.
+    /// Register a session with a role. A `mode` of None takes the policy's
+    /// `session_mode` when the session is populated.
     pub fn register(
         &self,
         session_id: &str,
         role_name: &str,
         task: Option<&str>,
         prompt_file: Option<&str>,
+        mode: Option<SessionMode>,
+    ) -> Result<()> {
+        self.register_with_fallbacks(session_id, role_name, &[], task, prompt_file, mode)
+    }
+
+    /// Register a session with `role_name` and, after it, `fallback_roles`
+    /// in order, for sessions that both implement and test.
+    pub fn register_with_fallbacks(
+        &self,
+        session_id: &str,
+        role_name: &str,
+        fallback_roles: &[String],
+        task: Option<&str>,
+        prompt_file: Option<&str>,
+        mode: Option<SessionMode>,
     ) -> Result<()> {
         let prompt_hash = prompt_file.and_then(|p| {
             std::fs::read(p).ok().map(|bytes| {
@@ -171,6 +218,8 @@ impl SessionManager {
             prompt_path: prompt_file.map(String::from),
             registered_at: Utc::now(),
             registered_by: None,
+            mode,
+            fallback_roles: fallback_roles.to_vec(),
         };
 
         registration::write_registration_entry(&self.registration_file, session_id, &entry)?;
@@ -188,7 +237,17 @@ impl SessionManager {
 
     /// Disable hookwise for a session.
     pub fn disable(&self, session_id: &str) -> Result<()> {
-        self.add_exclusion(session_id)?;
+        self.disable_until(session_id, None)
+    }
+
+    /// Disable hookwise for a session until `until`, or until re-enabled.
+    pub fn disable_until(&self, session_id: &str, until: Option<DateTime<Utc>>) -> Result<()> {
+        self.add_exclusion(Exclusion {
+            session_id: session_id.to_string(),
+            disabled_at: Some(Utc::now()),
+            until,
+            ..Exclusion::default()
+        })?;
 
         // Update in-memory cache
         if let Some(mut ctx) = SESSIONS.get_mut(session_id) {
@@ -198,24 +257,31 @@ impl SessionManager {
         Ok(())
     }
 
-    /// Re-enable hookwise for a session.
-    pub fn enable(&self, session_id: &str) -> Result<()> {
-        self.remove_exclusion(session_id)?;
+    /// Re-enable hookwise for a session. Returns the exclusion that was
+    /// lifted, if the session was disabled.
+    pub fn enable(&self, session_id: &str) -> Result<Option<Exclusion>> {
+        let removed = self.remove_exclusion(session_id)?;
+        self.take_disable_request(session_id)?;
 
         // Invalidate in-memory cache so it re-populates
         SESSIONS.remove(session_id);
 
-        Ok(())
+        Ok(removed)
     }
 
-    /// Switch a session's role. Clears the session's cache entries.
+    /// Switch a session to `new_role` alone, dropping any fallback roles.
+    /// Clears the session's cache entries.
     pub fn switch_role(&self, session_id: &str, new_role: &str) -> Result<()> {
-        // Read existing entry to preserve task/prompt info
+        // Read existing entry to preserve task/prompt info and mode
         let entries = registration::read_registration_file(&self.registration_file)?;
-        let (task, prompt_file) = if let Some(existing) = entries.get(session_id) {
-            (existing.task.as_deref(), existing.prompt_path.as_deref())
+        let (task, prompt_file, mode) = if let Some(existing) = entries.get(session_id) {
+            (
+                existing.task.as_deref(),
+                existing.prompt_path.as_deref(),
+                existing.mode,
+            )
         } else {
-            (None, None)
+            (None, None, None)
         };
 
         // Re-register with new role (owned copies to avoid borrow issues)
@@ -226,11 +292,26 @@ impl SessionManager {
             new_role,
             task_owned.as_deref(),
             prompt_owned.as_deref(),
+            mode,
         )?;
 
         Ok(())
     }
 
+    /// Every registered session, by session ID.
+    pub fn registrations(&self) -> Result<HashMap<String, RegistrationEntry>> {
+        registration::read_registration_file(&self.registration_file)
+    }
+
+    /// The disables in effect now.
+    pub fn active_exclusions(&self) -> Result<Vec<Exclusion>> {
+        let now = Utc::now();
+        Ok(read_exclusion_file(&self.exclusion_file)?
+            .into_iter()
+            .filter(|e| e.is_active(now))
+            .collect())
+    }
+
     /// Check if a session is registered (either with a role or disabled).
     pub fn is_registered(&self, session_id: &str) -> bool {
         // Check in-memory
@@ -255,20 +336,17 @@ impl SessionManager {
     }
 
     /// Check if a session is disabled.
+    /// The exclusion file is authoritative (not the in-memory cache) so
+    /// that timed disables lapse in long-running processes too.
     pub fn is_disabled(&self, session_id: &str) -> bool {
-        // Check in-memory
-        if let Some(ctx) = SESSIONS.get(session_id) {
-            if ctx.disabled {
-                return true;
-            }
-        }
-
-        // Check exclusion file
-        if let Ok(exclusions) = read_exclusion_file(&self.exclusion_file) {
-            return exclusions.contains(&session_id.to_string());
-        }
-
-        false
+        let now = Utc::now();
+        read_exclusion_file(&self.exclusion_file)
+            .map(|exclusions| {
+                exclusions
+                    .iter()
+                    .any(|e| e.session_id == session_id && e.is_active(now))
+            })
+            .unwrap_or(false)
     }
 
     /// Wait for a session to be registered, polling every 200ms.
@@ -292,19 +370,97 @@ impl SessionManager {
         }
     }
 
-    fn add_exclusion(&self, session_id: &str) -> Result<()> {
+    /// Drop disables that lapsed by `now`, returning them. Timed disables
+    /// stop applying on their own; this re-enables them for good.
+    pub fn expire_disables(&self, now: DateTime<Utc>) -> Result<Vec<Exclusion>> {
+        let exclusions = read_exclusion_file(&self.exclusion_file)?;
+        let (active, lapsed): (Vec<_>, Vec<_>) =
+            exclusions.into_iter().partition(|e| e.is_active(now));
+        if !lapsed.is_empty() {
+            write_exclusion_file(&self.exclusion_file, &active)?;
+            for exclusion in &lapsed {
+                SESSIONS.remove(&exclusion.session_id);
+            }
+        }
+        Ok(lapsed)
+    }
+
+    /// The session's disable if it has been active for `after` and no
+    /// reminder was sent in the last `after`, marking it reminded at `now`.
+    pub fn due_reminder(
+        &self,
+        session_id: &str,
+        after: chrono::Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Exclusion>> {
         let mut exclusions = read_exclusion_file(&self.exclusion_file)?;
-        if !exclusions.contains(&session_id.to_string()) {
-            exclusions.push(session_id.to_string());
+        let Some(exclusion) = exclusions
+            .iter_mut()
+            .find(|e| e.session_id == session_id && e.is_active(now))
+        else {
+            return Ok(None);
+        };
+        let since = exclusion.reminded_at.or(exclusion.disabled_at);
+        if since.is_none_or(|since| now - since < after) {
+            return Ok(None);
         }
-        write_exclusion_file(&self.exclusion_file, &exclusions)
+        exclusion.reminded_at = Some(now);
+        let due = exclusion.clone();
+        write_exclusion_file(&self.exclusion_file, &exclusions)?;
+        Ok(Some(due))
     }
 
-    fn remove_exclusion(&self, session_id: &str) -> Result<()> {
+    fn add_exclusion(&self, exclusion: Exclusion) -> Result<()> {
         let mut exclusions = read_exclusion_file(&self.exclusion_file)?;
-        exclusions.retain(|s| s != session_id);
+        exclusions.retain(|e| e.session_id != exclusion.session_id);
+        exclusions.push(exclusion);
         write_exclusion_file(&self.exclusion_file, &exclusions)
     }
+
+    fn remove_exclusion(&self, session_id: &str) -> Result<Option<Exclusion>> {
+        let mut exclusions = read_exclusion_file(&self.exclusion_file)?;
+        let removed = exclusions
+            .iter()
+            .position(|e| e.session_id == session_id)
+            .map(|i| exclusions.remove(i));
+        write_exclusion_file(&self.exclusion_file, &exclusions)?;
+        Ok(removed)
+    }
+}
+
+/// A disabled session in the on-disk exclusion file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Exclusion {
+    pub session_id: String,
+    /// `None` for entries written before disables were timestamped.
+    #[serde(default)]
+    pub disabled_at: Option<DateTime<Utc>>,
+    /// When the disable lapses. `None` means until re-enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+    /// Who disabled it, and why.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When the last still-disabled reminder was sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reminded_at: Option<DateTime<Utc>>,
+}
+
+impl Exclusion {
+    /// Whether the session is still disabled at `now`.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.until.is_none_or(|until| until > now)
+    }
+}
+
+/// An exclusion file entry: older files hold bare session IDs.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExclusionRecord {
+    Id(String),
+    Entry(Exclusion),
 }
 
 /// A registration entry from the on-disk sessions file.
@@ -316,10 +472,26 @@ pub struct RegistrationEntry {
     pub prompt_path: Option<String>,
     pub registered_at: DateTime<Utc>,
     pub registered_by: Option<String>,
+    /// `register --mode`; None defers to the policy's `session_mode`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<SessionMode>,
+    /// Roles tried after `role`, in order, when its path policy denies a
+    /// call. Older hookwise versions ignore them and use `role` alone.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_roles: Vec<String>,
+}
+
+impl RegistrationEntry {
+    /// `role` and its fallbacks, in the order calls try them.
+    pub fn roles(&self) -> Vec<&str> {
+        std::iter::once(self.role.as_str())
+            .chain(self.fallback_roles.iter().map(String::as_str))
+            .collect()
+    }
 }
 
 /// Extract org and project name from git remote origin URL.
-fn extract_git_org_project(cwd: &str) -> (String, String) {
+pub(crate) fn extract_git_org_project(cwd: &str) -> (String, String) {
     let output = std::process::Command::new("git")
         .arg("remote")
         .arg("get-url")
@@ -370,15 +542,8 @@ fn parse_git_remote_url(url: &str) -> (String, String) {
     ("unknown".into(), "unknown".into())
 }
 
-/// Get the current OS username.
-fn whoami() -> String {
-    std::env::var("USER")
-        .or_else(|_| std::env::var("USERNAME"))
-        .unwrap_or_else(|_| "unknown".into())
-}
-
-/// Read exclusion file (JSON array of session IDs).
-fn read_exclusion_file(path: &PathBuf) -> Result<Vec<String>> {
+/// Read exclusion file (JSON array of exclusions or bare session IDs).
+fn read_exclusion_file(path: &PathBuf) -> Result<Vec<Exclusion>> {
     if !path.exists() {
         return Ok(Vec::new());
     }
@@ -386,12 +551,21 @@ fn read_exclusion_file(path: &PathBuf) -> Result<Vec<String>> {
     if contents.trim().is_empty() {
         return Ok(Vec::new());
     }
-    let exclusions: Vec<String> = serde_json::from_str(&contents)?;
-    Ok(exclusions)
+    let records: Vec<ExclusionRecord> = serde_json::from_str(&contents)?;
+    Ok(records
+        .into_iter()
+        .map(|record| match record {
+            ExclusionRecord::Id(session_id) => Exclusion {
+                session_id,
+                ..Exclusion::default()
+            },
+            ExclusionRecord::Entry(exclusion) => exclusion,
+        })
+        .collect())
 }
 
-/// Write exclusion file (JSON array of session IDs) with restrictive permissions.
-fn write_exclusion_file(path: &PathBuf, exclusions: &[String]) -> Result<()> {
+/// Write exclusion file with restrictive permissions.
+fn write_exclusion_file(path: &PathBuf, exclusions: &[Exclusion]) -> Result<()> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -405,9 +579,7 @@ fn write_exclusion_file(path: &PathBuf, exclusions: &[String]) -> Result<()> {
 /// Prefers XDG_RUNTIME_DIR (typically /run/user/<uid>/, mode 0700).
 /// Falls back to /tmp if not set.
 fn runtime_dir() -> PathBuf {
-    std::env::var("XDG_RUNTIME_DIR")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("/tmp"))
+    crate::config::dirs_runtime()
 }
 
 /// Set file permissions to 0600 (owner read/write only).
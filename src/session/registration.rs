@@ -85,25 +85,63 @@ fn set_file_permissions_0600(_path: &Path) {
 }
 
 /// Advisory file lock using flock(2) on a .lock file.
-struct FileLock {
+pub(crate) struct FileLock {
     _file: fs::File,
 }
 
 impl FileLock {
-    fn acquire(path: &Path) -> Result<Self> {
+    pub(crate) fn acquire(path: &Path) -> Result<Self> {
+        Self::acquire_file(&path.with_extension("lock"))
+    }
+
+    /// Lock `lock_path` itself, creating it and its parent directories.
+    pub(crate) fn acquire_file(lock_path: &Path) -> Result<Self> {
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)?;
+        flock_exclusive(&file)?;
+        Ok(Self { _file: file })
+    }
+
+    /// Take a shared lock on `path`'s `.lock` file, for readers that must
+    /// not observe a writer holding [`acquire`](Self::acquire) mid-update.
+    pub(crate) fn acquire_shared(path: &Path) -> Result<Self> {
         let lock_path = path.with_extension("lock");
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
         let file = fs::OpenOptions::new()
             .create(true)
             .truncate(false)
             .write(true)
             .open(&lock_path)?;
-        flock_exclusive(&file)?;
+        flock_shared(&file)?;
         Ok(Self { _file: file })
     }
 }
 
 // When FileLock is dropped, the file is closed and the lock is released.
 
+/// Replace `path` with `contents` through a temporary file and a rename, so
+/// readers see the old or the new contents and never a partial write.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
 #[cfg(unix)]
 fn flock_exclusive(file: &fs::File) -> Result<()> {
     use std::os::unix::io::AsRawFd;
@@ -123,3 +161,23 @@ fn flock_exclusive(_file: &fs::File) -> Result<()> {
     // No-op on non-Unix platforms
     Ok(())
 }
+
+#[cfg(unix)]
+fn flock_shared(file: &fs::File) -> Result<()> {
+    use std::os::unix::io::AsRawFd;
+    let fd = file.as_raw_fd();
+    // LOCK_SH = 1 (shared lock)
+    let ret = unsafe { libc::flock(fd, libc::LOCK_SH) };
+    if ret != 0 {
+        return Err(crate::error::HookwiseError::Io(
+            std::io::Error::last_os_error(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn flock_shared(_file: &fs::File) -> Result<()> {
+    // No-op on non-Unix platforms
+    Ok(())
+}
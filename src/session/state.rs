@@ -0,0 +1,287 @@
+//! Session-scoped state shared across hook invocations. Every `hookwise
+//! check` is a process of its own, so what one call learns about a session
+//! (e.g. that it read a sensitive file, or how risky its recent calls were)
+//! is kept in a runtime file keyed by session ID for the calls after it.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{RiskConfig, RiskWeights};
+use crate::error::Result;
+
+use super::registration::FileLock;
+use super::{set_file_permissions_0600, SessionManager};
+
+/// What hookwise remembers about one session between calls.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    /// Sensitive files the session has read, most recent read per path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_reads: Vec<SensitiveRead>,
+    /// Events adding to the session's risk score, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub risk_events: Vec<RiskEvent>,
+    /// Path ranges the session holds write locks on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_locks: Vec<PathLock>,
+}
+
+impl SessionState {
+    fn is_empty(&self) -> bool {
+        self.sensitive_reads.is_empty() && self.risk_events.is_empty() && self.path_locks.is_empty()
+    }
+
+    /// Record reads of `paths` at `at`, replacing earlier reads of them.
+    pub fn record_reads(&mut self, paths: &[String], tool: &str, at: DateTime<Utc>) {
+        self.sensitive_reads.retain(|r| !paths.contains(&r.path));
+        self.sensitive_reads
+            .extend(paths.iter().map(|path| SensitiveRead {
+                path: path.clone(),
+                tool: tool.to_string(),
+                read_at: at,
+            }));
+    }
+
+    /// Reads at or after `since`, oldest first.
+    pub fn reads_since(&self, since: DateTime<Utc>) -> Vec<&SensitiveRead> {
+        let mut reads: Vec<_> = self
+            .sensitive_reads
+            .iter()
+            .filter(|r| r.read_at >= since)
+            .collect();
+        reads.sort_by_key(|r| r.read_at);
+        reads
+    }
+}
+
+impl SessionState {
+    /// Record risk events of `kinds` at `at`, dropping events before `since`.
+    pub fn record_risk(&mut self, kinds: &[RiskKind], at: DateTime<Utc>, since: DateTime<Utc>) {
+        self.risk_events.retain(|e| e.at >= since);
+        self.risk_events
+            .extend(kinds.iter().map(|&kind| RiskEvent { kind, at }));
+    }
+
+    /// The risk events at or after `since`, counted by kind.
+    pub fn risk_since(&self, since: DateTime<Utc>) -> RiskSummary {
+        let mut summary = RiskSummary::default();
+        for event in self.risk_events.iter().filter(|e| e.at >= since) {
+            match event.kind {
+                RiskKind::Denied => summary.denied += 1,
+                RiskKind::Sensitive => summary.sensitive += 1,
+                RiskKind::Novel => summary.novel += 1,
+            }
+        }
+        summary
+    }
+}
+
+impl SessionState {
+    /// Lock `ranges` until `expires_at`, renewing locks already held and
+    /// dropping ones expired at `now`.
+    pub fn record_locks(
+        &mut self,
+        ranges: &[String],
+        role: Option<&str>,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) {
+        self.path_locks
+            .retain(|l| l.expires_at > now && !ranges.contains(&l.range));
+        self.path_locks.extend(ranges.iter().map(|range| PathLock {
+            range: range.clone(),
+            role: role.map(String::from),
+            expires_at,
+        }));
+    }
+
+    /// The unexpired lock on `range`, if the session holds one.
+    pub fn lock_on(&self, range: &str, now: DateTime<Utc>) -> Option<&PathLock> {
+        self.path_locks
+            .iter()
+            .find(|l| l.range == range && l.expires_at > now)
+    }
+}
+
+/// A write lock a session holds on a path range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathLock {
+    /// The `locks.ranges` entry, or the file path when none matched.
+    pub range: String,
+    /// The holding session's role.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Something a session did that adds to its risk score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskKind {
+    /// A call was denied.
+    Denied,
+    /// A call touched a `sensitive_paths` path.
+    Sensitive,
+    /// Only the supervisor or a human could decide a call.
+    Novel,
+}
+
+/// A risk event and when it happened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskEvent {
+    pub kind: RiskKind,
+    pub at: DateTime<Utc>,
+}
+
+/// A session's risk events within the window, counted by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RiskSummary {
+    pub denied: usize,
+    pub sensitive: usize,
+    pub novel: usize,
+}
+
+impl RiskSummary {
+    /// The weighted score.
+    pub fn score(&self, weights: &RiskWeights) -> f64 {
+        self.denied as f64 * weights.denied
+            + self.sensitive as f64 * weights.sensitive
+            + self.novel as f64 * weights.novel
+    }
+}
+
+/// The start of `config`'s rolling window ending at `now`.
+pub fn risk_window_start(config: &RiskConfig, now: DateTime<Utc>) -> DateTime<Utc> {
+    i64::try_from(config.window_secs)
+        .ok()
+        .and_then(Duration::try_seconds)
+        .and_then(|window| now.checked_sub_signed(window))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// A read of a sensitive file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensitiveRead {
+    pub path: String,
+    /// The tool that read it (`Read`, `Bash`, ...).
+    pub tool: String,
+    pub read_at: DateTime<Utc>,
+}
+
+/// File-backed [`SessionState`] for every session of a team.
+#[derive(Debug, Clone)]
+pub struct SessionStateStore {
+    path: PathBuf,
+}
+
+impl SessionStateStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The state of every session with any recorded.
+    pub fn load_all(&self) -> Result<HashMap<String, SessionState>> {
+        read_states(&self.path)
+    }
+
+    /// The state of a session; empty when nothing was recorded.
+    pub fn load(&self, session_id: &str) -> Result<SessionState> {
+        Ok(read_states(&self.path)?
+            .remove(session_id)
+            .unwrap_or_default())
+    }
+
+    /// Change a session's state under the file lock, returning the new
+    /// state. Sessions whose state ends up empty are dropped from the file.
+    pub fn update(
+        &self,
+        session_id: &str,
+        change: impl FnOnce(&mut SessionState),
+    ) -> Result<SessionState> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let _lock = FileLock::acquire(&self.path)?;
+        let mut states = read_states(&self.path)?;
+        let mut state = states.remove(session_id).unwrap_or_default();
+        change(&mut state);
+        if !state.is_empty() {
+            states.insert(session_id.to_string(), state.clone());
+        }
+        write_states(&self.path, &states)?;
+        Ok(state)
+    }
+
+    /// Forget a session.
+    pub fn remove(&self, session_id: &str) -> Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        self.update(session_id, |state| *state = SessionState::default())?;
+        Ok(())
+    }
+}
+
+impl SessionManager {
+    /// The state store for this manager's sessions.
+    pub fn state_store(&self) -> SessionStateStore {
+        SessionStateStore::new(self.state_file.clone())
+    }
+
+    /// A session's risk within `config`'s window.
+    pub fn risk(&self, session_id: &str, config: &RiskConfig) -> Result<RiskSummary> {
+        let since = risk_window_start(config, Utc::now());
+        Ok(self.state_store().load(session_id)?.risk_since(since))
+    }
+
+    /// The risk of every session with events within `config`'s window,
+    /// highest score first.
+    pub fn risks(&self, config: &RiskConfig) -> Result<Vec<(String, RiskSummary)>> {
+        let since = risk_window_start(config, Utc::now());
+        let mut risks: Vec<_> = self
+            .state_store()
+            .load_all()?
+            .into_iter()
+            .map(|(id, state)| (id, state.risk_since(since)))
+            .filter(|(_, risk)| *risk != RiskSummary::default())
+            .collect();
+        risks.sort_by(|a, b| {
+            b.1.score(&config.weights)
+                .total_cmp(&a.1.score(&config.weights))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        Ok(risks)
+    }
+}
+
+fn read_states(path: &Path) -> Result<HashMap<String, SessionState>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let contents = std::fs::read_to_string(path)?;
+    if contents.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn write_states(path: &Path, states: &HashMap<String, SessionState>) -> Result<()> {
+    let json = serde_json::to_string_pretty(states)?;
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+    }
+    set_file_permissions_0600(&tmp_path);
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
@@ -0,0 +1,321 @@
+//! `hookwise-shim`: a lean hook entry point for latency-sensitive installs.
+//!
+//! The shim parses the hook input and runs the local tiers in cascade order:
+//! ephemeral paths, content, exfiltration, locks, path policy, package gate,
+//! cloud and git rules, exact cache and token similarity. It never loads the
+//! embedding model, which dominates `hookwise check` cold starts. A call
+//! those tiers leave undecided is forwarded over IPC to the supervisor
+//! daemon (`supervisor.backend: socket`).
+//!
+//! Anything the shim can't finish on its own goes to the full
+//! `hookwise check` with the same input, so a decision never gets looser:
+//! unregistered sessions, audit mode, read-only or ephemeral storage, org
+//! rollouts, API supervisors, and calls the supervisor leaves for a human.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::audit::{AuditEvent, AuditSink};
+use crate::cascade::cache::{CommitKeys, ExactCache};
+use crate::cascade::cloud::CloudPolicy;
+use crate::cascade::command_risk::CommandRiskScorer;
+use crate::cascade::content::ContentInspection;
+use crate::cascade::embed_sim::EmbeddingSimilarity;
+use crate::cascade::ephemeral::EphemeralPaths;
+use crate::cascade::exfil::ExfiltrationGuard;
+use crate::cascade::git::GitPolicy;
+use crate::cascade::hints::HintTable;
+use crate::cascade::locks::PathLocks;
+use crate::cascade::margin::DenyMargin;
+use crate::cascade::messages::MessageTemplates;
+use crate::cascade::packages::PackageGate;
+use crate::cascade::path_policy::PathPolicyEngine;
+use crate::cascade::risk::RiskTracker;
+use crate::cascade::supervisor::{SupervisorTier, UnixSocketSupervisor};
+use crate::cascade::tier_cap::TierCaps;
+use crate::cascade::token_sim::TokenJaccard;
+use crate::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use crate::config::{
+    default_socket_path, OrgPolicyConfig, Persistence, PolicyConfig, RolesConfig, SessionMode,
+    SupervisorConfig,
+};
+use crate::decision::{Decision, DecisionRecord, DecisionTier};
+use crate::error::{HookwiseError, Result};
+use crate::hook_io::{self, HookFormat, HookInput};
+use crate::sanitize::SanitizePipeline;
+use crate::scope::ScopeLevel;
+use crate::session::SessionManager;
+use crate::storage::jsonl::JsonlStorage;
+use crate::storage::{is_writable, StorageBackend};
+
+/// What the shim made of a hook call.
+#[derive(Debug)]
+pub enum ShimOutcome {
+    /// Decided by a local tier or the supervisor daemon.
+    Decided(DecisionRecord),
+    /// Needs the full `hookwise check`, for this reason.
+    Handoff(String),
+}
+
+/// Stands in for the tiers the shim doesn't run. Reaching it stops the
+/// cascade before anything is persisted.
+struct Handoff(&'static str);
+
+#[async_trait]
+impl CascadeTier for Handoff {
+    async fn evaluate(&self, _input: &CascadeInput) -> Result<Option<DecisionRecord>> {
+        Err(HookwiseError::Handoff {
+            reason: self.0.into(),
+        })
+    }
+
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::Human
+    }
+
+    fn name(&self) -> &str {
+        "handoff"
+    }
+}
+
+/// Run the shim as a hook: read stdin, decide or hand off, write the
+/// result.
+pub async fn run(format: HookFormat) -> Result<()> {
+    let mut raw = Vec::new();
+    std::io::stdin().lock().read_to_end(&mut raw)?;
+    let input: HookInput = serde_json::from_slice(&raw)?;
+
+    let outcome = match evaluate(&input, format).await {
+        Ok(outcome) => outcome,
+        // The full check reports (and fails closed on) the same error
+        Err(e) => ShimOutcome::Handoff(e.to_string()),
+    };
+    match outcome {
+        ShimOutcome::Decided(record) => {
+            hook_io::write_hook_output_with_reason(
+                record.decision,
+                hook_io::hook_reason(&record),
+                format,
+            )?;
+            if record.decision == Decision::Deny {
+                std::process::exit(hook_io::deny_exit_code(format));
+            }
+            Ok(())
+        }
+        ShimOutcome::Handoff(reason) => {
+            tracing::debug!("handing off to hookwise check: {}", reason);
+            match hand_off(&full_binary(), format, &raw) {
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("hookwise: {}, defaulting to deny", e);
+                    hook_io::write_hook_output(Decision::Deny, format)?;
+                    std::process::exit(hook_io::deny_exit_code(format));
+                }
+            }
+        }
+    }
+}
+
+/// Decide `input`, sent in `format`, with the shim's tiers, or say why the
+/// full check must.
+pub async fn evaluate(input: &HookInput, format: HookFormat) -> Result<ShimOutcome> {
+    let cwd_path = PathBuf::from(&input.cwd);
+    let policy = PolicyConfig::load_project(&cwd_path)?;
+    let team_id = std::env::var("CLAUDE_TEAM_ID").ok();
+    let session_mgr = SessionManager::new(team_id.as_deref());
+
+    // The full check expires lapsed disables and sends reminders
+    if session_mgr.is_disabled(&input.session_id) {
+        return Ok(handoff("session disabled"));
+    }
+    if !session_mgr.is_registered(&input.session_id) {
+        return Ok(handoff("session not registered yet"));
+    }
+    let session = session_mgr.get_or_populate(&input.session_id, &input.cwd)?;
+    if session.role.is_none() || session.mode == SessionMode::Audit {
+        return Ok(handoff("no role or audit mode"));
+    }
+
+    let project_root = cwd_path.join(".hookwise");
+    let global_root = crate::config::dirs_rules();
+    if policy.persistence == Persistence::Ephemeral || !is_writable(&project_root) {
+        return Ok(handoff("ephemeral or read-only rule storage"));
+    }
+    let org_policy = OrgPolicyConfig::load(&global_root, &session.org)?;
+    if org_policy.rollout.since.is_some() {
+        return Ok(handoff("org rule rollout"));
+    }
+
+    let storage = JsonlStorage::new(project_root, global_root, Some(session.org.clone()))
+        .with_expiry(policy.cache.clone());
+    let mut decisions = storage.load_decisions(ScopeLevel::Project)?;
+    decisions.extend(storage.load_decisions(ScopeLevel::Org)?);
+
+    let roles = RolesConfig::load_project(&cwd_path)?;
+    let mut runner = build_runner(
+        &policy,
+        &roles,
+        &session_mgr,
+        &input.session_id,
+        team_id.as_deref(),
+        decisions,
+        Box::new(storage),
+    )?;
+    runner.client = Some(input.client(format));
+    let record = match runner
+        .evaluate_with_cwd(
+            &session,
+            &input.tool_name,
+            &input.tool_input,
+            Some(&input.cwd),
+        )
+        .await
+    {
+        Ok(record) => record,
+        Err(HookwiseError::Handoff { reason }) => return Ok(ShimOutcome::Handoff(reason)),
+        Err(e) => return Err(e),
+    };
+
+    if let Some(sink) = AuditSink::from_config(&policy.audit, &cwd_path) {
+        let event = AuditEvent {
+            logged_at: chrono::Utc::now(),
+            cwd: input.cwd.clone(),
+            record: record.clone(),
+            identity: Some(crate::identity::current(&cwd_path)),
+            not_enforced: false,
+        };
+        if let Err(e) = sink.record(&event) {
+            eprintln!("hookwise: audit log write failed ({})", e);
+        }
+    }
+    Ok(ShimOutcome::Decided(record))
+}
+
+fn handoff(reason: &str) -> ShimOutcome {
+    ShimOutcome::Handoff(reason.into())
+}
+
+/// The cascade without the embedding tier, the API supervisor or the human
+/// queue.
+fn build_runner(
+    policy: &PolicyConfig,
+    roles: &RolesConfig,
+    session_mgr: &SessionManager,
+    session_id: &str,
+    team_id: Option<&str>,
+    decisions: Vec<DecisionRecord>,
+    storage: Box<dyn StorageBackend>,
+) -> Result<CascadeRunner> {
+    let exact_cache = Arc::new(
+        ExactCache::new()
+            .with_expiry(policy.cache.clone())
+            .with_max_entries(policy.cache.max_entries),
+    );
+    exact_cache.load_from(decisions.clone());
+    let token_jaccard = Arc::new(
+        TokenJaccard::new(
+            policy.similarity.jaccard_threshold,
+            policy.similarity.jaccard_min_tokens,
+        )
+        .with_mode(policy.similarity.token_mode)
+        .with_deny_margin(DenyMargin::from_config(&policy.similarity)),
+    );
+    token_jaccard.load_from(&decisions);
+
+    let supervisor: Box<dyn CascadeTier> = match &policy.supervisor {
+        SupervisorConfig::Socket { socket_path } => {
+            let socket = socket_path
+                .clone()
+                .unwrap_or_else(|| default_socket_path(team_id));
+            Box::new(SupervisorTier::new(
+                Box::new(UnixSocketSupervisor::new(socket, 30)),
+                policy.clone(),
+            ))
+        }
+        SupervisorConfig::Api { .. } => Box::new(Handoff("API supervisor")),
+    };
+    let categories = &roles.categories;
+    let state = session_mgr.state_store();
+
+    Ok(CascadeRunner {
+        sanitizer: SanitizePipeline::from_config(&policy.sanitize)?,
+        ephemeral: EphemeralPaths::compile(
+            &policy.ephemeral_paths,
+            &policy.sensitive_paths,
+            categories,
+        )?,
+        content_inspection: ContentInspection::compile(
+            &policy.content,
+            &policy.sanitize,
+            categories,
+        )?,
+        exfiltration: ExfiltrationGuard::compile(
+            &policy.exfiltration,
+            categories,
+            state.clone(),
+            session_id,
+        )?,
+        locks: PathLocks::compile(&policy.locks, categories, state.clone(), session_id)?,
+        path_policy: Box::new(
+            PathPolicyEngine::new()?
+                .with_precedence(policy.path_precedence)
+                .with_containers(policy.containers),
+        ),
+        package_gate: PackageGate::compile(&policy.packages)?,
+        cloud_cli: CloudPolicy::compile(&policy.cloud)?,
+        git_policy: GitPolicy::compile(&policy.git)?,
+        exact_cache,
+        commit_keys: CommitKeys::compile(&policy.cache)?,
+        token_jaccard,
+        embedding_similarity: Arc::new(EmbeddingSimilarity::new_noop()),
+        supervisor,
+        human: Box::new(Handoff("needs a human decision")),
+        storage,
+        policy: policy.clone(),
+        normalizer: roles.normalizer().ok(),
+        tier_caps: TierCaps::compile(&policy.max_auto_tier, categories)?,
+        risk: Some(RiskTracker::new(&policy.risk, state, session_id)?),
+        messages: MessageTemplates::compile(&policy.messages, categories)?.map(Arc::new),
+        hints: HintTable::compile(&policy.hints, categories)?.map(Arc::new),
+        command_risk: Some(Arc::new(CommandRiskScorer::new(&policy.command_risk)?)),
+        clock: None,
+        client: None,
+    })
+}
+
+/// The full `hookwise` binary: next to the shim, else on PATH.
+/// `HOOKWISE_FULL_BINARY` overrides both.
+pub fn full_binary() -> PathBuf {
+    if let Some(path) = std::env::var_os("HOOKWISE_FULL_BINARY") {
+        return PathBuf::from(path);
+    }
+    let name = format!("hookwise{}", std::env::consts::EXE_SUFFIX);
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&name)))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// Run `hookwise check` on the hook input and return its exit code. Its
+/// output goes straight to the hook's stdout and stderr.
+pub fn hand_off(binary: &Path, format: HookFormat, input: &[u8]) -> Result<i32> {
+    let format = match format {
+        HookFormat::Claude => "claude",
+        HookFormat::Gemini => "gemini",
+    };
+    let mut child = std::process::Command::new(binary)
+        .args(["check", "--format", format])
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| HookwiseError::Ipc {
+            reason: format!("could not run {}: {}", binary.display(), e),
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    Ok(child.wait()?.code().unwrap_or(1))
+}
@@ -0,0 +1,248 @@
+//! Decision bundles: a scope's records exported on one machine
+//! (`hookwise export`) and merged into the same scope on another
+//! (`hookwise import`), so a trained cache survives a laptop switch.
+//!
+//! A bundle is a tar holding `manifest.json` and `decisions.jsonl`. The
+//! manifest carries the payload's SHA-256 to catch truncated copies; it is
+//! not signed, since both ends belong to the same user.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::decision::{CacheKey, DecisionRecord};
+use crate::error::{HookwiseError, Result};
+use crate::scope::ScopeLevel;
+
+/// Bundle format version written into manifests.
+pub const BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const DECISIONS: &str = "decisions.jsonl";
+
+/// Description of a bundle's records.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    /// The scope the records were exported from.
+    pub scope: ScopeLevel,
+    pub created_at: DateTime<Utc>,
+    pub created_by: String,
+    pub count: usize,
+    pub payload_sha256: String,
+    /// Record counts by the assistant that made them
+    /// ([`crate::decision::count_by_client`]).
+    #[serde(default)]
+    pub clients: BTreeMap<String, usize>,
+}
+
+/// How `import` settles a key the two machines decided differently.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConflictStrategy {
+    /// Deny beats ask beats allow, as scope resolution does.
+    #[default]
+    Strictest,
+    /// The most recently written decision wins.
+    Newest,
+    /// Ask on the terminal for each conflict.
+    Interactive,
+}
+
+impl ConflictStrategy {
+    /// Whether `incoming` replaces `existing`, or None when the user has to
+    /// be asked.
+    pub fn prefers_incoming(
+        self,
+        existing: &DecisionRecord,
+        incoming: &DecisionRecord,
+    ) -> Option<bool> {
+        match self {
+            ConflictStrategy::Strictest => {
+                Some(incoming.decision.precedence() > existing.decision.precedence())
+            }
+            ConflictStrategy::Newest => Some(incoming.timestamp > existing.timestamp),
+            ConflictStrategy::Interactive => None,
+        }
+    }
+}
+
+/// A conflict settled while planning an import.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub existing: DecisionRecord,
+    pub incoming: DecisionRecord,
+    /// Whether the incoming record won.
+    pub took_incoming: bool,
+}
+
+/// What merging a bundle into a scope will change.
+#[derive(Debug, Clone, Default)]
+pub struct MergePlan {
+    /// Records to write. Any existing record with the same key is removed
+    /// first.
+    pub add: Vec<DecisionRecord>,
+    pub conflicts: Vec<Conflict>,
+    /// Incoming keys already decided the same way.
+    pub duplicates: usize,
+}
+
+impl MergePlan {
+    /// Incoming records for keys the scope had no decision for.
+    pub fn new_keys(&self) -> usize {
+        self.add.len() - self.replaced()
+    }
+
+    /// Existing decisions the bundle overrode.
+    pub fn replaced(&self) -> usize {
+        self.conflicts.iter().filter(|c| c.took_incoming).count()
+    }
+}
+
+fn bundle_error(path: &Path, reason: &str) -> HookwiseError {
+    HookwiseError::Storage {
+        reason: format!("bundle {}: {}", path.display(), reason),
+    }
+}
+
+/// Write `records` exported from `scope` to a bundle at `path`.
+pub fn write_bundle(
+    path: &Path,
+    scope: ScopeLevel,
+    records: &[DecisionRecord],
+    created_by: &str,
+) -> Result<BundleManifest> {
+    let mut payload = String::new();
+    for record in records {
+        payload.push_str(&serde_json::to_string(record)?);
+        payload.push('\n');
+    }
+    let manifest = BundleManifest {
+        version: BUNDLE_VERSION,
+        scope,
+        created_at: Utc::now(),
+        created_by: created_by.to_string(),
+        count: records.len(),
+        payload_sha256: format!("{:x}", Sha256::digest(payload.as_bytes())),
+        clients: crate::decision::count_by_client(records),
+    };
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut builder = tar::Builder::new(std::fs::File::create(path)?);
+    for (name, data) in [
+        (MANIFEST, manifest_bytes.as_slice()),
+        (DECISIONS, payload.as_bytes()),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(manifest.created_at.timestamp().max(0) as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, name, data)?;
+    }
+    builder.into_inner()?;
+    Ok(manifest)
+}
+
+/// Read a bundle, checking its version and payload hash.
+pub fn read_bundle(path: &Path) -> Result<(BundleManifest, Vec<DecisionRecord>)> {
+    let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut archive = tar::Archive::new(std::fs::File::open(path)?);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        files.insert(name, data);
+    }
+    let file = |name: &str| {
+        files
+            .get(name)
+            .ok_or_else(|| bundle_error(path, &format!("missing {}", name)))
+    };
+
+    let manifest: BundleManifest = serde_json::from_slice(file(MANIFEST)?)?;
+    if manifest.version != BUNDLE_VERSION {
+        return Err(bundle_error(
+            path,
+            &format!("unsupported version {}", manifest.version),
+        ));
+    }
+    let payload = file(DECISIONS)?;
+    if format!("{:x}", Sha256::digest(payload)) != manifest.payload_sha256 {
+        return Err(bundle_error(path, "decisions do not match the manifest"));
+    }
+    let records = String::from_utf8_lossy(payload)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect::<std::result::Result<Vec<DecisionRecord>, _>>()?;
+    Ok((manifest, records))
+}
+
+/// The record per key that scope resolution uses: strictest decision, then
+/// most authoritative scope, then latest.
+pub(crate) fn effective(records: &[DecisionRecord]) -> HashMap<&CacheKey, &DecisionRecord> {
+    let rank = |r: &DecisionRecord| (r.decision.precedence(), r.scope.precedence(), r.timestamp);
+    let mut winners: HashMap<&CacheKey, &DecisionRecord> = HashMap::new();
+    for record in records {
+        match winners.get(&record.key) {
+            Some(current) if rank(current) > rank(record) => {}
+            _ => {
+                winners.insert(&record.key, record);
+            }
+        }
+    }
+    winners
+}
+
+/// Plan merging `incoming` into a scope holding `existing`. Each key is
+/// compared by its effective decision on both sides; when they differ,
+/// `resolve(existing, incoming)` says whether the incoming record wins.
+/// Incoming records are moved to `scope`.
+pub fn plan_merge<F>(
+    scope: ScopeLevel,
+    existing: &[DecisionRecord],
+    incoming: &[DecisionRecord],
+    mut resolve: F,
+) -> Result<MergePlan>
+where
+    F: FnMut(&DecisionRecord, &DecisionRecord) -> Result<bool>,
+{
+    let existing = effective(existing);
+    let mut incoming: Vec<DecisionRecord> = effective(incoming)
+        .into_values()
+        .map(|record| DecisionRecord {
+            scope,
+            ..record.clone()
+        })
+        .collect();
+    // Stable order for prompts and output
+    incoming.sort_by_key(|r| r.timestamp);
+
+    let mut plan = MergePlan::default();
+    for record in incoming {
+        match existing.get(&record.key) {
+            None => plan.add.push(record),
+            Some(current) if current.decision == record.decision => plan.duplicates += 1,
+            Some(current) => {
+                let took_incoming = resolve(current, &record)?;
+                plan.conflicts.push(Conflict {
+                    existing: (*current).clone(),
+                    incoming: record.clone(),
+                    took_incoming,
+                });
+                if took_incoming {
+                    plan.add.push(record);
+                }
+            }
+        }
+    }
+    Ok(plan)
+}
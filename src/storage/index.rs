@@ -1,7 +1,10 @@
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use crate::error::{HookwiseError, Result};
+use crate::session::registration::FileLock;
 
 /// Wrapper around the HNSW index for persistent storage.
 pub struct HnswIndexStore {
@@ -55,6 +58,288 @@ impl HnswIndexStore {
     }
 }
 
+/// Magic bytes at the start of an embedding vector file.
+const EMBEDDING_MAGIC: &[u8; 8] = b"HWEMB\x00\x00\x02";
+
+/// Persistent embedding vectors for the Tier 2b index, stored under
+/// `.hookwise/.index/`:
+///
+/// - `embeddings.vec`: header (magic, dimension, generation, model id)
+///   followed by little-endian `f32` vectors, read through a memory map
+/// - `embeddings.keys`: the generation on the first line, then one JSON
+///   string per line; key `i` is the sanitized input embedded as vector `i`
+///
+/// instant-distance cannot serialize its graph, so the HNSW graph is rebuilt
+/// from these vectors on load; only inputs missing from the store need the
+/// (much slower) embedding model. Both files are append-only between full
+/// rewrites by `hookwise build`, and every rewrite stamps both with a fresh
+/// generation so a key file is never paired with another rewrite's vectors.
+pub struct EmbeddingStore {
+    dir: PathBuf,
+    model: String,
+}
+
+impl EmbeddingStore {
+    pub fn new(dir: PathBuf, model: &str) -> Self {
+        Self {
+            dir,
+            model: model.to_string(),
+        }
+    }
+
+    pub fn vectors_path(&self) -> PathBuf {
+        self.dir.join("embeddings.vec")
+    }
+
+    pub fn keys_path(&self) -> PathBuf {
+        self.dir.join("embeddings.keys")
+    }
+
+    fn header(&self, dim: usize, generation: u64) -> Vec<u8> {
+        let mut header = EMBEDDING_MAGIC.to_vec();
+        header.extend_from_slice(&(dim as u32).to_le_bytes());
+        header.extend_from_slice(&generation.to_le_bytes());
+        header.extend_from_slice(&(self.model.len() as u32).to_le_bytes());
+        header.extend_from_slice(self.model.as_bytes());
+        // Keep the vectors 4-byte aligned within the mapping.
+        while !header.len().is_multiple_of(4) {
+            header.push(0);
+        }
+        header
+    }
+
+    /// Parse the header, returning (dimension, generation, header length).
+    /// `None` when the file is not ours or was written for a different model.
+    fn parse_header(&self, data: &[u8]) -> Option<(usize, u64, usize)> {
+        if data.len() < 24 || &data[..8] != EMBEDDING_MAGIC {
+            return None;
+        }
+        let dim = u32::from_le_bytes(data[8..12].try_into().ok()?) as usize;
+        let generation = u64::from_le_bytes(data[12..20].try_into().ok()?);
+        let model_len = u32::from_le_bytes(data[20..24].try_into().ok()?) as usize;
+        let model = data.get(24..24 + model_len)?;
+        if model != self.model.as_bytes() || dim == 0 {
+            return None;
+        }
+        Some((dim, generation, (24 + model_len).div_ceil(4) * 4))
+    }
+
+    /// Read the key file, returning its generation and the key lines.
+    fn read_keys(&self) -> Option<(u64, String)> {
+        let keys = fs::read_to_string(self.keys_path()).ok()?;
+        let (first, rest) = keys.split_once('\n')?;
+        Some((first.parse().ok()?, rest.to_string()))
+    }
+
+    /// Load all stored vectors keyed by input. Missing, foreign, or
+    /// mismatched-model files, and files from different rewrites, load as
+    /// empty; a torn trailing write is ignored.
+    pub fn load(&self) -> Result<HashMap<String, Vec<f32>>> {
+        if !self.vectors_path().exists() || !self.keys_path().exists() {
+            return Ok(HashMap::new());
+        }
+        let _lock = FileLock::acquire_shared(&self.vectors_path())?;
+        self.load_locked()
+    }
+
+    /// [`load`](Self::load) for callers already holding the store's lock.
+    fn load_locked(&self) -> Result<HashMap<String, Vec<f32>>> {
+        let mut out = HashMap::new();
+        let vec_path = self.vectors_path();
+        if !vec_path.exists() {
+            return Ok(out);
+        }
+        let Some((key_generation, keys)) = self.read_keys() else {
+            return Ok(out);
+        };
+        let mapped = MappedFile::open(&vec_path)?;
+        let Some((dim, generation, header_len)) = self.parse_header(&mapped) else {
+            return Ok(out);
+        };
+        if generation != key_generation {
+            return Ok(out);
+        }
+
+        let body = &mapped[header_len..];
+        let stride = dim * 4;
+        for (key_line, chunk) in keys.lines().zip(body.chunks_exact(stride)) {
+            let Ok(key) = serde_json::from_str::<String>(key_line) else {
+                break;
+            };
+            let vector = chunk
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            out.insert(key, vector);
+        }
+        Ok(out)
+    }
+
+    /// Append vectors. Starts a fresh store if the existing one is missing
+    /// or was written with another model or dimension.
+    pub fn append(&self, items: &[(String, Vec<f32>)]) -> Result<()> {
+        let Some(dim) = items.first().map(|(_, v)| v.len()) else {
+            return Ok(());
+        };
+        fs::create_dir_all(&self.dir)?;
+        let _lock = FileLock::acquire(&self.vectors_path())?;
+
+        // Append only onto a store of one generation with matching dimension
+        // whose key and vector counts agree; otherwise rewrite its readable
+        // prefix.
+        let appendable = self
+            .read_keys()
+            .zip(MappedFile::open(&self.vectors_path()).ok())
+            .and_then(|((key_generation, keys), m)| {
+                let (existing, generation, header_len) = self.parse_header(&m)?;
+                let body = m.len() - header_len;
+                (existing == dim
+                    && generation == key_generation
+                    && body == keys.lines().count() * dim * 4)
+                    .then_some(())
+            })
+            .is_some();
+        if !appendable {
+            let mut all: Vec<(String, Vec<f32>)> = self.load_locked()?.into_iter().collect();
+            all.extend(items.iter().cloned());
+            return self.write_all(&all, dim);
+        }
+
+        let mut vectors = fs::OpenOptions::new()
+            .append(true)
+            .open(self.vectors_path())?;
+        let mut keys = fs::OpenOptions::new().append(true).open(self.keys_path())?;
+        for (key, vector) in items.iter().filter(|(_, v)| v.len() == dim) {
+            vectors.write_all(&encode_vector(vector))?;
+            writeln!(keys, "{}", serde_json::to_string(key)?)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the store with exactly `items` (used by full rebuilds).
+    pub fn rewrite(&self, items: &[(String, Vec<f32>)]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let _lock = FileLock::acquire(&self.vectors_path())?;
+        let dim = items.first().map(|(_, v)| v.len()).unwrap_or(0);
+        self.write_all(items, dim)
+    }
+
+    fn write_all(&self, items: &[(String, Vec<f32>)], dim: usize) -> Result<()> {
+        // Always move past the generation being replaced, even on a coarse
+        // or stepped-back clock.
+        let previous = MappedFile::open(&self.vectors_path())
+            .ok()
+            .and_then(|m| self.parse_header(&m).map(|(_, generation, _)| generation));
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        let generation = previous.map_or(now, |p| now.max(p.wrapping_add(1)));
+        let mut vectors = self.header(dim, generation);
+        let mut keys = format!("{}\n", generation);
+        for (key, vector) in items.iter().filter(|(_, v)| v.len() == dim) {
+            vectors.extend_from_slice(&encode_vector(vector));
+            keys.push_str(&serde_json::to_string(key)?);
+            keys.push('\n');
+        }
+        // The two renames are not atomic together: a reader (or a crash)
+        // between them sees files from different rewrites, which the shared
+        // generation makes `load` reject rather than pair up.
+        write_atomic(&self.keys_path(), keys.as_bytes())?;
+        write_atomic(&self.vectors_path(), &vectors)
+    }
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// A read-only memory map of a whole file (plain read on non-Unix).
+struct MappedFile {
+    #[cfg(unix)]
+    ptr: *mut libc::c_void,
+    #[cfg(unix)]
+    len: usize,
+    #[cfg(not(unix))]
+    data: Vec<u8>,
+}
+
+impl MappedFile {
+    #[cfg(unix)]
+    fn open(path: &Path) -> Result<Self> {
+        use std::os::unix::io::AsRawFd;
+        let file = fs::File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Ok(Self {
+                ptr: std::ptr::null_mut(),
+                len: 0,
+            });
+        }
+        // SAFETY: read-only private mapping of a file we keep open for the
+        // duration of the call; writers replace the file via rename rather
+        // than truncating it, so the mapped pages stay valid.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(HookwiseError::Io(std::io::Error::last_os_error()));
+        }
+        Ok(Self { ptr, len })
+    }
+
+    #[cfg(not(unix))]
+    fn open(path: &Path) -> Result<Self> {
+        Ok(Self {
+            data: fs::read(path)?,
+        })
+    }
+}
+
+impl std::ops::Deref for MappedFile {
+    type Target = [u8];
+
+    #[cfg(unix)]
+    fn deref(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        // SAFETY: ptr/len describe a live mapping owned by self.
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+
+    #[cfg(not(unix))]
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[cfg(unix)]
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: unmapping the region created in `open`.
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,6 +359,64 @@ mod tests {
         assert_eq!(loaded.unwrap(), data);
     }
 
+    #[test]
+    fn test_embedding_store_appends_and_reloads() {
+        let tmp = TempDir::new().unwrap();
+        let store = EmbeddingStore::new(tmp.path().join(".index"), "default");
+        assert!(store.load().unwrap().is_empty());
+
+        store
+            .append(&[("cargo build".into(), vec![1.0, 0.0, 0.5])])
+            .unwrap();
+        store
+            .append(&[("cargo \"test\"".into(), vec![0.0, 1.0, -0.5])])
+            .unwrap();
+
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded["cargo \"test\""], vec![0.0, 1.0, -0.5]);
+
+        // A different model never reuses the vectors.
+        let other = EmbeddingStore::new(tmp.path().join(".index"), "other-model");
+        assert!(other.load().unwrap().is_empty());
+
+        store
+            .rewrite(&[("cargo build".into(), vec![1.0, 0.0, 0.5])])
+            .unwrap();
+        assert_eq!(store.load().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_embedding_store_rejects_files_from_different_rewrites() {
+        let tmp = TempDir::new().unwrap();
+        let store = EmbeddingStore::new(tmp.path().join(".index"), "default");
+        store
+            .rewrite(&[
+                ("cargo build".into(), vec![1.0, 0.0]),
+                ("cargo test".into(), vec![0.0, 1.0]),
+            ])
+            .unwrap();
+        let old_keys = fs::read(store.keys_path()).unwrap();
+        store
+            .rewrite(&[
+                ("cargo test".into(), vec![0.0, 1.0]),
+                ("cargo build".into(), vec![1.0, 0.0]),
+            ])
+            .unwrap();
+
+        // A crash between the two renames: old keys next to new vectors.
+        fs::write(store.keys_path(), old_keys).unwrap();
+        assert!(store.load().unwrap().is_empty());
+
+        // The next append starts a consistent store.
+        store
+            .append(&[("cargo fmt".into(), vec![0.5, 0.5])])
+            .unwrap();
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded["cargo fmt"], vec![0.5, 0.5]);
+    }
+
     #[test]
     fn test_load_missing() {
         let tmp = TempDir::new().unwrap();
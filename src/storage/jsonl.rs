@@ -1,19 +1,156 @@
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use crate::decision::{Decision, DecisionRecord};
+use chrono::{DateTime, Utc};
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::CacheConfig;
+use crate::decision::{CacheKey, Decision, DecisionRecord, SCHEMA_VERSION};
 use crate::error::Result;
 use crate::sanitize::SanitizePipeline;
 use crate::scope::ScopeLevel;
+use crate::session::registration::{write_atomic, FileLock};
 
+use super::bundle::MergePlan;
 use super::{SecretFinding, StorageBackend};
 
+/// What [`JsonlStorage::deduplicate`] or [`JsonlStorage::prune_older_than`]
+/// removed from a scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactReport {
+    pub scope: ScopeLevel,
+    pub records_before: usize,
+    pub records_removed: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Where the removed records went, with `--archive`.
+    pub archived: Option<PathBuf>,
+}
+
+impl CompactReport {
+    pub fn bytes_reclaimed(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// What [`JsonlStorage::upgrade`] rewrote in a scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeReport {
+    pub scope: ScopeLevel,
+    /// Records written by an older schema and rewritten at the current one.
+    pub upgraded: usize,
+    /// Records already at the current schema.
+    pub current: usize,
+    /// Lines this version can't read, left as they are.
+    pub unreadable: usize,
+}
+
+/// A rule file as read for a rewrite.
+struct RuleFile {
+    path: PathBuf,
+    size: u64,
+    records: Vec<DecisionRecord>,
+    /// Each record's line as written, so rewrites keep fields this version
+    /// doesn't know about.
+    lines: Vec<String>,
+    /// Lines this version can't read (a newer schema, an unknown decision
+    /// or tier). Every rewrite carries them over untouched, so an older
+    /// binary never deletes a newer one's records.
+    unreadable: Vec<String>,
+}
+
+impl RuleFile {
+    /// Read a rule file, sorting its lines into records and unreadable
+    /// lines, with one warning for the latter.
+    fn read(path: &Path) -> Result<Self> {
+        let mut file = RuleFile {
+            path: path.to_path_buf(),
+            size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            records: Vec::new(),
+            lines: Vec::new(),
+            unreadable: Vec::new(),
+        };
+        if !path.exists() {
+            return Ok(file);
+        }
+        let reader = BufReader::new(fs::File::open(path)?);
+        let mut first_error = None;
+        for (line_num, line) in reader.lines().enumerate() {
+            let line = line?;
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<DecisionRecord>(trimmed) {
+                Ok(record) => {
+                    file.records.push(record);
+                    file.lines.push(trimmed.to_string());
+                }
+                Err(e) => {
+                    first_error.get_or_insert((line_num + 1, e));
+                    file.unreadable.push(trimmed.to_string());
+                }
+            }
+        }
+        if let Some((line_num, e)) = first_error {
+            tracing::warn!(
+                "skipping {} unreadable line(s) in {} (line {}: {}); they may come from a newer hookwise",
+                file.unreadable.len(),
+                path.display(),
+                line_num,
+                e
+            );
+        }
+        Ok(file)
+    }
+
+    /// The file's contents keeping the records `keep(line, record)`
+    /// accepts, and every unreadable line. Also returns the dropped lines.
+    fn split<F>(&self, keep: F) -> (String, String)
+    where
+        F: Fn(usize, &DecisionRecord) -> bool,
+    {
+        let (mut kept, mut dropped) = (String::new(), String::new());
+        for (i, (record, line)) in self.records.iter().zip(&self.lines).enumerate() {
+            let out = if keep(i, record) {
+                &mut kept
+            } else {
+                &mut dropped
+            };
+            out.push_str(line);
+            out.push('\n');
+        }
+        for line in &self.unreadable {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+        (kept, dropped)
+    }
+}
+
+/// The `schema_version` a rule file line was written with.
+fn line_version(line: &str) -> u32 {
+    #[derive(serde::Deserialize)]
+    struct Versioned {
+        schema_version: Option<u32>,
+    }
+    serde_json::from_str::<Versioned>(line)
+        .ok()
+        .and_then(|v| v.schema_version)
+        .unwrap_or(1)
+}
+
 /// JSONL-based storage implementation.
 pub struct JsonlStorage {
     project_root: PathBuf,
     global_root: PathBuf,
     org_name: Option<String>,
+    expiry: CacheConfig,
+    clock: Arc<dyn Clock>,
+    auto_upgrade: bool,
 }
 
 impl JsonlStorage {
@@ -22,9 +159,33 @@ impl JsonlStorage {
             project_root,
             global_root,
             org_name,
+            expiry: CacheConfig::default(),
+            clock: Arc::new(SystemClock),
+            auto_upgrade: false,
         }
     }
 
+    /// Skip records older than the per-scope max age on load, and remove them
+    /// on [`StorageBackend::compact`].
+    pub fn with_expiry(mut self, expiry: CacheConfig) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
+    /// Judge record age against `clock` instead of the wall clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Rewrite records from an older schema the first time a project or
+    /// user scope loads them, as `hookwise storage upgrade` would. Org rules
+    /// come from the org source and are left for it to upgrade.
+    pub fn with_auto_upgrade(mut self, auto_upgrade: bool) -> Self {
+        self.auto_upgrade = auto_upgrade;
+        self
+    }
+
     /// Resolve the directory path for a given scope.
     fn scope_dir(&self, scope: ScopeLevel) -> PathBuf {
         match scope {
@@ -49,79 +210,280 @@ impl JsonlStorage {
         dir.join(filename)
     }
 
-    /// Read all decision records from a JSONL file.
-    fn read_jsonl_file(path: &Path) -> Result<Vec<DecisionRecord>> {
-        if !path.exists() {
-            return Ok(Vec::new());
+    /// Lock serializing writes to a scope's rule files across processes.
+    /// The project lock lives in the gitignored `.user/` rather than next to
+    /// the checked-in rules.
+    fn lock_path(&self, scope: ScopeLevel) -> PathBuf {
+        match scope {
+            ScopeLevel::Project | ScopeLevel::Role => {
+                self.project_root.join(".user").join("rules.lock")
+            }
+            ScopeLevel::Org | ScopeLevel::User => self.scope_dir(scope).with_extension("lock"),
         }
-        let file = fs::File::open(path)?;
-        let reader = BufReader::new(file);
-        let mut records = Vec::new();
+    }
 
-        for (line_num, line) in reader.lines().enumerate() {
-            let line = line?;
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
+    fn lock(&self, scope: ScopeLevel) -> Result<FileLock> {
+        FileLock::acquire_file(&self.lock_path(scope))
+    }
+
+    /// Where `hookwise compact --archive` and `hookwise prune --archive`
+    /// keep the records they removed.
+    /// Project history stays in the gitignored `.user/`.
+    pub fn archive_dir(&self, scope: ScopeLevel) -> PathBuf {
+        match scope {
+            ScopeLevel::Project | ScopeLevel::Role => {
+                self.project_root.join(".user").join("archive")
             }
-            match serde_json::from_str::<DecisionRecord>(trimmed) {
-                Ok(record) => records.push(record),
-                Err(e) => {
-                    tracing::warn!(
-                        "skipping malformed line {} in {}: {}",
-                        line_num + 1,
-                        path.display(),
-                        e
-                    );
+            ScopeLevel::Org | ScopeLevel::User => self.global_root.join("archive"),
+        }
+    }
+
+    /// Rewrite a scope's rule files keeping one record per cache key: the
+    /// one scope resolution would use, so compaction never changes a
+    /// decision. That is the strictest decision (deny, then ask, then
+    /// allow), then the record at the most authoritative scope, then the
+    /// latest. With `archive`, removed records are appended to a file under
+    /// [`Self::archive_dir`]; with `dry_run`, nothing is written.
+    pub fn deduplicate(
+        &self,
+        scope: ScopeLevel,
+        archive: bool,
+        dry_run: bool,
+    ) -> Result<CompactReport> {
+        let _lock = self.lock(scope)?;
+        let files = self.read_scope_files(scope)?;
+
+        // The winning (file, line) per key; later lines win ties
+        let mut winners: HashMap<&CacheKey, (usize, usize)> = HashMap::new();
+        for (f, file) in files.iter().enumerate() {
+            for (i, record) in file.records.iter().enumerate() {
+                let rank = |r: &DecisionRecord| {
+                    (r.decision.precedence(), r.scope.precedence(), r.timestamp)
+                };
+                let replace = match winners.get(&record.key) {
+                    Some(&(wf, wi)) => rank(record) >= rank(&files[wf].records[wi]),
+                    None => true,
+                };
+                if replace {
+                    winners.insert(&record.key, (f, i));
                 }
             }
         }
 
-        Ok(records)
+        self.rewrite_scope_files(
+            scope,
+            &files,
+            |f, i, record| winners.get(&record.key) == Some(&(f, i)),
+            archive,
+            dry_run,
+        )
+    }
+
+    /// Remove a scope's records written before `cutoff`, whatever their
+    /// decision, for `hookwise prune`. Archiving and `dry_run` work as in
+    /// [`Self::deduplicate`].
+    pub fn prune_older_than(
+        &self,
+        scope: ScopeLevel,
+        cutoff: DateTime<Utc>,
+        archive: bool,
+        dry_run: bool,
+    ) -> Result<CompactReport> {
+        let _lock = self.lock(scope)?;
+        let files = self.read_scope_files(scope)?;
+        self.rewrite_scope_files(
+            scope,
+            &files,
+            |_, _, record| record.timestamp >= cutoff,
+            archive,
+            dry_run,
+        )
+    }
+
+    /// Apply a [`MergePlan`] from `hookwise import`: records sharing a key
+    /// with one being added are removed, then the new records appended.
+    /// Returns the number of existing records removed.
+    pub fn merge(&self, scope: ScopeLevel, plan: &MergePlan) -> Result<usize> {
+        let _lock = self.lock(scope)?;
+        let keys: HashSet<&CacheKey> = plan.add.iter().map(|r| &r.key).collect();
+        let files = self.read_scope_files(scope)?;
+        let report = self.rewrite_scope_files(
+            scope,
+            &files,
+            |_, _, r| !keys.contains(&r.key),
+            false,
+            false,
+        )?;
+        for record in &plan.add {
+            Self::append_jsonl_file(&self.jsonl_path(scope, record.decision), record)?;
+        }
+        Ok(report.records_removed)
     }
 
-    /// Append a record to a JSONL file, creating parent dirs if needed.
+    /// Rewrite a scope's records written by an older schema at
+    /// [`SCHEMA_VERSION`], for `hookwise storage upgrade`. Current records
+    /// and lines this version can't read are left as they are; with
+    /// `dry_run`, nothing is written.
+    pub fn upgrade(&self, scope: ScopeLevel, dry_run: bool) -> Result<UpgradeReport> {
+        let _lock = self.lock(scope)?;
+        let mut report = UpgradeReport {
+            scope,
+            upgraded: 0,
+            current: 0,
+            unreadable: 0,
+        };
+        for file in self.read_scope_files(scope)? {
+            report.unreadable += file.unreadable.len();
+            let mut contents = String::new();
+            let mut upgraded = 0;
+            for (record, line) in file.records.iter().zip(&file.lines) {
+                if line_version(line) < SCHEMA_VERSION {
+                    contents.push_str(&serde_json::to_string(record)?);
+                    upgraded += 1;
+                } else {
+                    contents.push_str(line);
+                }
+                contents.push('\n');
+            }
+            for line in &file.unreadable {
+                contents.push_str(line);
+                contents.push('\n');
+            }
+            report.upgraded += upgraded;
+            report.current += file.records.len() - upgraded;
+            if upgraded > 0 && !dry_run {
+                write_atomic(&file.path, contents.as_bytes())?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Each of a scope's rule files with its size and records. Callers
+    /// hold the scope lock.
+    fn read_scope_files(&self, scope: ScopeLevel) -> Result<Vec<RuleFile>> {
+        [Decision::Allow, Decision::Deny, Decision::Ask]
+            .into_iter()
+            .map(|decision| RuleFile::read(&self.jsonl_path(scope, decision)))
+            .collect()
+    }
+
+    /// Rewrite the files from [`Self::read_scope_files`] keeping the records
+    /// `keep(file, line, record)` accepts, archiving the rest if asked.
+    fn rewrite_scope_files<F>(
+        &self,
+        scope: ScopeLevel,
+        files: &[RuleFile],
+        keep: F,
+        archive: bool,
+        dry_run: bool,
+    ) -> Result<CompactReport>
+    where
+        F: Fn(usize, usize, &DecisionRecord) -> bool,
+    {
+        let mut report = CompactReport {
+            scope,
+            records_before: 0,
+            records_removed: 0,
+            bytes_before: 0,
+            bytes_after: 0,
+            archived: None,
+        };
+        let mut removed = String::new();
+        for (f, file) in files.iter().enumerate() {
+            report.records_before += file.records.len();
+            report.bytes_before += file.size;
+            let (kept, dropped) = file.split(|i, record| keep(f, i, record));
+            if dropped.is_empty() {
+                report.bytes_after += file.size;
+                continue;
+            }
+            report.records_removed += dropped.lines().count();
+            report.bytes_after += kept.len() as u64;
+            removed.push_str(&dropped);
+            if !dry_run {
+                write_atomic(&file.path, kept.as_bytes())?;
+            }
+        }
+
+        if archive && !removed.is_empty() {
+            let stamp = self.clock.now().format("%Y%m%dT%H%M%SZ");
+            let path = self
+                .archive_dir(scope)
+                .join(format!("{}-{}.jsonl", scope, stamp));
+            if !dry_run {
+                fs::create_dir_all(self.archive_dir(scope))?;
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)?
+                    .write_all(removed.as_bytes())?;
+            }
+            report.archived = Some(path);
+        }
+        Ok(report)
+    }
+
+    /// Append a record to a JSONL file, creating parent dirs if needed. The
+    /// line goes out in a single write so concurrent appends never
+    /// interleave.
     fn append_jsonl_file(path: &Path, record: &DecisionRecord) -> Result<()> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
         let mut file = OpenOptions::new().create(true).append(true).open(path)?;
-        let json = serde_json::to_string(record)?;
-        writeln!(file, "{}", json)?;
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
         Ok(())
     }
 
-    /// Rewrite a JSONL file, keeping only records that match a predicate.
-    fn filter_jsonl_file<F>(path: &Path, predicate: F) -> Result<()>
+    /// Rewrite a JSONL file, keeping only records that match a predicate
+    /// and lines this version can't read. Returns the number of records
+    /// removed. The new file replaces the old one atomically; callers hold
+    /// the scope lock.
+    fn filter_jsonl_file<F>(path: &Path, predicate: F) -> Result<usize>
     where
         F: Fn(&DecisionRecord) -> bool,
     {
         if !path.exists() {
-            return Ok(());
+            return Ok(0);
         }
-        let records = Self::read_jsonl_file(path)?;
-        let kept: Vec<&DecisionRecord> = records.iter().filter(|r| predicate(r)).collect();
-
-        // Write the filtered records back
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let mut file = fs::File::create(path)?;
-        for record in kept {
-            let json = serde_json::to_string(record)?;
-            writeln!(file, "{}", json)?;
-        }
-        Ok(())
+        let file = RuleFile::read(path)?;
+        let (kept, dropped) = file.split(|_, record| predicate(record));
+        write_atomic(path, kept.as_bytes())?;
+        Ok(dropped.lines().count())
     }
 }
 
 impl StorageBackend for JsonlStorage {
     fn load_decisions(&self, scope: ScopeLevel) -> Result<Vec<DecisionRecord>> {
+        let now = self.clock.now();
         let mut all = Vec::new();
+        let mut older = false;
         for decision in &[Decision::Allow, Decision::Deny, Decision::Ask] {
-            let path = self.jsonl_path(scope, *decision);
-            let records = Self::read_jsonl_file(&path)?;
-            all.extend(records);
+            let file = RuleFile::read(&self.jsonl_path(scope, *decision))?;
+            older |= file.lines.iter().any(|l| line_version(l) < SCHEMA_VERSION);
+            all.extend(
+                file.records
+                    .into_iter()
+                    .filter(|r| !self.expiry.is_expired(scope, r.timestamp, now)),
+            );
+        }
+        if older && self.auto_upgrade && scope != ScopeLevel::Org {
+            // Role rules live in the project files
+            let files = match scope {
+                ScopeLevel::Role => ScopeLevel::Project,
+                scope => scope,
+            };
+            match self.upgrade(files, false) {
+                Ok(report) => tracing::info!(
+                    "upgraded {} {} record(s) to schema {}",
+                    report.upgraded,
+                    files,
+                    SCHEMA_VERSION
+                ),
+                Err(e) => tracing::warn!("could not upgrade {} records: {}", files, e),
+            }
         }
         Ok(all)
     }
@@ -140,10 +502,12 @@ impl StorageBackend for JsonlStorage {
 
     fn save_decision(&self, record: &DecisionRecord) -> Result<()> {
         let path = self.jsonl_path(record.scope, record.decision);
+        let _lock = self.lock(record.scope)?;
         Self::append_jsonl_file(&path, record)
     }
 
     fn invalidate_role(&self, scope: ScopeLevel, role: &str) -> Result<()> {
+        let _lock = self.lock(scope)?;
         for decision in &[Decision::Allow, Decision::Deny, Decision::Ask] {
             let path = self.jsonl_path(scope, *decision);
             Self::filter_jsonl_file(&path, |r| r.key.role != role)?;
@@ -152,6 +516,7 @@ impl StorageBackend for JsonlStorage {
     }
 
     fn invalidate_all(&self, scope: ScopeLevel) -> Result<()> {
+        let _lock = self.lock(scope)?;
         for decision in &[Decision::Allow, Decision::Deny, Decision::Ask] {
             let path = self.jsonl_path(scope, *decision);
             if path.exists() {
@@ -161,6 +526,36 @@ impl StorageBackend for JsonlStorage {
         Ok(())
     }
 
+    fn remove_decisions(
+        &self,
+        scope: ScopeLevel,
+        predicate: &dyn Fn(&DecisionRecord) -> bool,
+    ) -> Result<usize> {
+        let _lock = self.lock(scope)?;
+        let mut removed = 0;
+        for decision in &[Decision::Allow, Decision::Deny, Decision::Ask] {
+            let path = self.jsonl_path(scope, *decision);
+            removed += Self::filter_jsonl_file(&path, |r| !predicate(r))?;
+        }
+        Ok(removed)
+    }
+
+    fn compact(&self, scope: ScopeLevel) -> Result<usize> {
+        if self.expiry.max_age(scope).is_none() {
+            return Ok(0);
+        }
+        let now = self.clock.now();
+        let _lock = self.lock(scope)?;
+        let mut removed = 0;
+        for decision in &[Decision::Allow, Decision::Deny, Decision::Ask] {
+            let path = self.jsonl_path(scope, *decision);
+            removed += Self::filter_jsonl_file(&path, |r| {
+                !self.expiry.is_expired(scope, r.timestamp, now)
+            })?;
+        }
+        Ok(removed)
+    }
+
     fn rebuild_index(&self, _scope: ScopeLevel) -> Result<()> {
         // Index rebuild is handled by the embedding/jaccard tiers, not storage.
         // This is a no-op placeholder that the cascade engine will call into
@@ -228,6 +623,12 @@ mod tests {
                 reason: "test".into(),
                 matched_key: None,
                 similarity_score: None,
+                targets: Vec::new(),
+                message: None,
+                suggestion: None,
+                risk_score: None,
+                secrets: Vec::new(),
+                client: None,
             },
             timestamp: Utc::now(),
             scope: ScopeLevel::Project,
@@ -305,4 +706,69 @@ mod tests {
         let loaded = storage.load_decisions(ScopeLevel::Project).unwrap();
         assert_eq!(loaded.len(), 0);
     }
+
+    fn thirty_day_expiry() -> CacheConfig {
+        let mut expiry = CacheConfig::default();
+        expiry.max_age_days.project = Some(30);
+        expiry
+    }
+
+    #[test]
+    fn test_expired_records_skipped_on_load() {
+        let tmp = TempDir::new().unwrap();
+        let storage = JsonlStorage::new(tmp.path().to_path_buf(), tmp.path().join("global"), None)
+            .with_expiry(thirty_day_expiry());
+
+        let mut stale = make_record(Decision::Allow, "coder");
+        stale.timestamp = Utc::now() - chrono::Duration::days(45);
+        storage.save_decision(&stale).unwrap();
+        storage
+            .save_decision(&make_record(Decision::Allow, "tester"))
+            .unwrap();
+
+        let loaded = storage.load_decisions(ScopeLevel::Project).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].key.role, "tester");
+    }
+
+    #[test]
+    fn test_compact_removes_expired() {
+        let tmp = TempDir::new().unwrap();
+        let storage = JsonlStorage::new(tmp.path().to_path_buf(), tmp.path().join("global"), None)
+            .with_expiry(thirty_day_expiry());
+
+        let mut stale = make_record(Decision::Deny, "coder");
+        stale.timestamp = Utc::now() - chrono::Duration::days(45);
+        storage.save_decision(&stale).unwrap();
+        storage
+            .save_decision(&make_record(Decision::Deny, "tester"))
+            .unwrap();
+
+        assert_eq!(storage.compact(ScopeLevel::Project).unwrap(), 1);
+        let raw = JsonlStorage::new(tmp.path().to_path_buf(), tmp.path().join("global"), None);
+        assert_eq!(raw.load_decisions(ScopeLevel::Project).unwrap().len(), 1);
+        assert_eq!(storage.compact(ScopeLevel::User).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_expiry_uses_injected_clock() {
+        let tmp = TempDir::new().unwrap();
+        let record = make_record(Decision::Allow, "coder");
+        let clock = Arc::new(crate::clock::FixedClock::new(record.timestamp));
+        let storage = JsonlStorage::new(tmp.path().to_path_buf(), tmp.path().join("global"), None)
+            .with_expiry(thirty_day_expiry())
+            .with_clock(clock.clone());
+        storage.save_decision(&record).unwrap();
+
+        clock.advance(chrono::Duration::days(29));
+        assert_eq!(
+            storage.load_decisions(ScopeLevel::Project).unwrap().len(),
+            1
+        );
+        clock.advance(chrono::Duration::days(2));
+        assert!(storage
+            .load_decisions(ScopeLevel::Project)
+            .unwrap()
+            .is_empty());
+    }
 }
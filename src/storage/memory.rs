@@ -0,0 +1,95 @@
+use std::path::Path;
+use std::sync::RwLock;
+
+use crate::decision::DecisionRecord;
+use crate::error::Result;
+use crate::scope::ScopeLevel;
+
+use super::{SecretFinding, StorageBackend};
+
+/// Storage for `persistence: ephemeral`. Existing rules are read from the
+/// inner backend, but new decisions are kept in memory for the lifetime of
+/// the process and never written to disk.
+pub struct EphemeralStorage {
+    inner: Box<dyn StorageBackend>,
+    records: RwLock<Vec<DecisionRecord>>,
+}
+
+impl EphemeralStorage {
+    pub fn new(inner: Box<dyn StorageBackend>) -> Self {
+        Self {
+            inner,
+            records: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Decisions made during this process.
+    pub fn session_records(&self) -> Vec<DecisionRecord> {
+        self.records
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl StorageBackend for EphemeralStorage {
+    fn load_decisions(&self, scope: ScopeLevel) -> Result<Vec<DecisionRecord>> {
+        let mut all = self.inner.load_decisions(scope)?;
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        all.extend(records.iter().filter(|r| r.scope == scope).cloned());
+        Ok(all)
+    }
+
+    fn load_decisions_for_role(
+        &self,
+        scope: ScopeLevel,
+        role: &str,
+    ) -> Result<Vec<DecisionRecord>> {
+        Ok(self
+            .load_decisions(scope)?
+            .into_iter()
+            .filter(|r| r.key.role == role || r.key.role == "*")
+            .collect())
+    }
+
+    fn save_decision(&self, record: &DecisionRecord) -> Result<()> {
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        records.push(record.clone());
+        Ok(())
+    }
+
+    fn invalidate_role(&self, scope: ScopeLevel, role: &str) -> Result<()> {
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        records.retain(|r| r.scope != scope || r.key.role != role);
+        Ok(())
+    }
+
+    fn invalidate_all(&self, scope: ScopeLevel) -> Result<()> {
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        records.retain(|r| r.scope != scope);
+        Ok(())
+    }
+
+    fn remove_decisions(
+        &self,
+        scope: ScopeLevel,
+        predicate: &dyn Fn(&DecisionRecord) -> bool,
+    ) -> Result<usize> {
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        let before = records.len();
+        records.retain(|r| r.scope != scope || !predicate(r));
+        Ok(before - records.len())
+    }
+
+    fn compact(&self, _scope: ScopeLevel) -> Result<usize> {
+        Ok(0)
+    }
+
+    fn rebuild_index(&self, scope: ScopeLevel) -> Result<()> {
+        self.inner.rebuild_index(scope)
+    }
+
+    fn scan_for_secrets(&self, path: &Path) -> Result<Vec<SecretFinding>> {
+        self.inner.scan_for_secrets(path)
+    }
+}
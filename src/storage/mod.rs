@@ -1,10 +1,16 @@
+pub mod bundle;
 pub mod index;
 pub mod jsonl;
+pub mod memory;
+pub mod remote;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use sha2::{Digest, Sha256};
+
+use crate::config::ReadOnlyPolicy;
 use crate::decision::DecisionRecord;
-use crate::error::Result;
+use crate::error::{HookwiseError, Result};
 use crate::scope::ScopeLevel;
 
 /// Backend for loading and saving decision records.
@@ -25,6 +31,17 @@ pub trait StorageBackend: Send + Sync {
     /// Delete all decisions within a scope.
     fn invalidate_all(&self, scope: ScopeLevel) -> Result<()>;
 
+    /// Delete the decisions within a scope that match `predicate`.
+    /// Returns the number removed.
+    fn remove_decisions(
+        &self,
+        scope: ScopeLevel,
+        predicate: &dyn Fn(&DecisionRecord) -> bool,
+    ) -> Result<usize>;
+
+    /// Remove expired records within a scope. Returns the number removed.
+    fn compact(&self, scope: ScopeLevel) -> Result<usize>;
+
     /// Rebuild the HNSW index from stored decisions.
     fn rebuild_index(&self, scope: ScopeLevel) -> Result<()>;
 
@@ -40,3 +57,69 @@ pub struct SecretFinding {
     pub description: String,
     pub detector: String,
 }
+
+/// Whether files can be created under `path`. A missing directory is judged
+/// by its nearest existing ancestor, since it would be created on first write;
+/// an existing file, such as the queue, by the directory it is rewritten in.
+pub fn is_writable(path: &Path) -> bool {
+    let Some(mut existing) = path.ancestors().find(|p| p.exists()) else {
+        return false;
+    };
+    if existing == path && path.is_file() {
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+        existing = parent;
+    }
+    tempfile::Builder::new()
+        .prefix(".hookwise-probe")
+        .tempfile_in(existing)
+        .is_ok()
+}
+
+/// The marker under `state_dir` recording that the read-only warning for
+/// `path` has been shown.
+pub fn read_only_marker(state_dir: &Path, path: &Path) -> PathBuf {
+    let hash = format!("{:x}", Sha256::digest(path.to_string_lossy().as_bytes()));
+    state_dir.join("read-only").join(&hash[..16])
+}
+
+/// Check that `path` is writable. When it is not, `ReadOnlyPolicy::Fail`
+/// returns an error; `ReadOnlyPolicy::Degrade` returns `Ok(false)` so the
+/// caller can fall back to memory, warning only the first time `path` is
+/// found read-only. Each hook call is its own process, so that is recorded
+/// by a marker under `state_dir`, cleared once `path` is writable again.
+pub fn ensure_writable(
+    path: &Path,
+    what: &str,
+    policy: ReadOnlyPolicy,
+    state_dir: &Path,
+) -> Result<bool> {
+    let marker = read_only_marker(state_dir, path);
+    if is_writable(path) {
+        if marker.exists() {
+            let _ = std::fs::remove_file(&marker);
+        }
+        return Ok(true);
+    }
+    match policy {
+        ReadOnlyPolicy::Fail => Err(HookwiseError::Storage {
+            reason: format!("{} at {} is read-only", what, path.display()),
+        }),
+        ReadOnlyPolicy::Degrade => {
+            if !marker.exists() {
+                eprintln!(
+                    "hookwise: {} at {} is read-only; keeping decisions in memory \
+                     (set read_only: fail in policy.yml to make this an error)",
+                    what,
+                    path.display()
+                );
+                if let Some(parent) = marker.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                let _ = std::fs::write(&marker, path.to_string_lossy().as_bytes());
+            }
+            Ok(false)
+        }
+    }
+}
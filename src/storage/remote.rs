@@ -0,0 +1,721 @@
+//! The org scope in a shared S3-compatible bucket (`org_store` in the
+//! global config). The bucket holds `{prefix}{org}/rules/{allow,deny,ask}.jsonl`
+//! and `{prefix}{org}/policy.yml`, and the org directory,
+//! `<rules root>/org/<org>/`, caches them. [`OrgStore::refresh`] fetches
+//! the objects that changed with ETag-conditional GETs and validates the
+//! whole set before writing, so a bucket holding bad rules, like one that
+//! can't be reached, leaves the cached rules in force.
+//!
+//! Requests are signed with AWS Signature Version 4, which S3, MinIO, R2
+//! and Cloud Storage (with HMAC keys) accept; without credentials the
+//! bucket is read anonymously. Org-scope decisions by an org maintainer
+//! are saved to the cache and then uploaded by [`RemoteOrgStorage`] with
+//! `If-Match`, so a change another writer made in the meantime is never
+//! overwritten.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_MATCH, IF_NONE_MATCH};
+use reqwest::{Method, StatusCode, Url};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{SecretFinding, StorageBackend};
+use crate::config::{OrgPolicyConfig, OrgStoreConfig};
+use crate::decision::DecisionRecord;
+use crate::error::{HookwiseError, Result};
+use crate::identity::{is_listed, Identity};
+use crate::scope::ScopeLevel;
+use crate::session::registration::write_atomic;
+use crate::sync::{
+    current_rules, diff, http, occupied, org_dir, validate, validate_dir, SyncReport, POLICY_FILE,
+    RULE_FILES,
+};
+
+const STATE_FILE: &str = ".store-state.json";
+
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+/// An object as last fetched from or uploaded to the bucket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ObjectState {
+    etag: String,
+    /// SHA-256 of the contents, telling local changes from the bucket's.
+    sha256: String,
+}
+
+/// What the cache holds from the bucket, in the org directory's
+/// `.store-state.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoreState {
+    #[serde(default)]
+    checked_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    objects: BTreeMap<String, ObjectState>,
+}
+
+struct Credentials {
+    access_key: String,
+    secret_key: String,
+}
+
+/// The answer to a GET.
+enum Fetched {
+    /// The object still has the ETag sent.
+    Unchanged,
+    Missing,
+    Found {
+        contents: String,
+        etag: String,
+    },
+}
+
+/// An org's rules in the bucket, cached in its org directory.
+pub struct OrgStore {
+    config: OrgStoreConfig,
+    org: String,
+    dir: PathBuf,
+    credentials: Option<Credentials>,
+}
+
+impl OrgStore {
+    /// The store for `org`, cached in its org directory under `rules_root`,
+    /// with the key pair from the environment variables `config` names.
+    pub fn new(config: OrgStoreConfig, rules_root: &Path, org: &str) -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let credentials = match (var(&config.access_key_env), var(&config.secret_key_env)) {
+            (Some(access_key), Some(secret_key)) => Some(Credentials {
+                access_key,
+                secret_key,
+            }),
+            _ => None,
+        };
+        Self {
+            dir: org_dir(rules_root, org),
+            org: org.to_string(),
+            config,
+            credentials,
+        }
+    }
+
+    /// Sign requests with this key pair instead of the environment's.
+    pub fn with_credentials(mut self, access_key: &str, secret_key: &str) -> Self {
+        self.credentials = Some(Credentials {
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+        });
+        self
+    }
+
+    pub fn org(&self) -> &str {
+        &self.org
+    }
+
+    /// Whether the cache was last checked against the bucket more than
+    /// `refresh_secs` ago, or never.
+    pub fn is_stale(&self) -> bool {
+        let age = |at: DateTime<Utc>| u64::try_from((Utc::now() - at).num_seconds()).ok();
+        self.load_state()
+            .checked_at
+            .and_then(age)
+            .is_none_or(|age| age >= self.config.refresh_secs)
+    }
+
+    /// Fetch the objects that changed since the last refresh into the
+    /// cache, validating the new rule set before writing it, and report how
+    /// the effective rules changed. Objects removed from the bucket are
+    /// removed from the cache. Local changes not yet published are kept
+    /// unless the bucket's copy changed too, which wins.
+    pub async fn refresh(&self) -> Result<SyncReport> {
+        self.check_cache()?;
+        let mut state = self.load_state();
+        let client = self.client()?;
+        let before = current_rules(&self.dir);
+
+        let mut files = BTreeMap::new();
+        let mut objects = BTreeMap::new();
+        for file in RULE_FILES
+            .iter()
+            .map(|(file, _)| *file)
+            .chain([POLICY_FILE])
+        {
+            let local = read_file(&self.dir.join(file))?;
+            let recorded = state.objects.get(file);
+            let pending = local.as_deref().map(sha256_hex) != recorded.map(|o| o.sha256.clone());
+            let fetched = self
+                .get(&client, file, recorded.map(|o| o.etag.as_str()))
+                .await?;
+            let contents = match fetched {
+                Fetched::Unchanged => {
+                    if let Some(object) = recorded {
+                        objects.insert(file.to_string(), object.clone());
+                    }
+                    local
+                }
+                // Created here and not yet published
+                Fetched::Missing if recorded.is_none() => local,
+                Fetched::Missing => {
+                    if pending {
+                        tracing::warn!(
+                            "{} was removed from the org store; discarding local changes",
+                            file
+                        );
+                    }
+                    None
+                }
+                Fetched::Found { contents, etag } => {
+                    if pending {
+                        tracing::warn!(
+                            "{} changed in the org store; discarding local changes",
+                            file
+                        );
+                    }
+                    objects.insert(
+                        file.to_string(),
+                        ObjectState {
+                            etag,
+                            sha256: sha256_hex(&contents),
+                        },
+                    );
+                    Some(contents)
+                }
+            };
+            files.insert(file, contents);
+        }
+        let after = validate(|file| Ok(files.get(file).cloned().flatten()))?;
+
+        std::fs::create_dir_all(self.dir.join("rules"))?;
+        for (file, contents) in &files {
+            let path = self.dir.join(file);
+            match contents {
+                Some(contents) => write_atomic(&path, contents.as_bytes())?,
+                None if path.exists() => std::fs::remove_file(&path)?,
+                None => {}
+            }
+        }
+        state.objects = objects;
+        state.checked_at = Some(Utc::now());
+        self.save_state(&state)?;
+
+        let etags: String = state
+            .objects
+            .iter()
+            .map(|(file, object)| format!("{} {}\n", file, object.etag))
+            .collect();
+        Ok(SyncReport {
+            org: self.org.clone(),
+            version: sha256_hex(&etags)[..12].to_string(),
+            changes: diff(&before.records, &after.records),
+            skipped: after.skipped,
+        })
+    }
+
+    /// [`Self::refresh`] when the cache is stale. When the bucket can't be
+    /// reached or holds invalid rules, warn and keep the cached rules until
+    /// the next attempt, `refresh_secs` later.
+    pub async fn refresh_if_stale(&self) {
+        if !self.is_stale() {
+            return;
+        }
+        if let Err(e) = self.refresh().await {
+            eprintln!(
+                "hookwise: org store refresh failed, using the cached org rules ({})",
+                e
+            );
+            // A directory the store never filled stays untouched
+            if self.dir.join(STATE_FILE).exists() {
+                let mut state = self.load_state();
+                state.checked_at = Some(Utc::now());
+                if let Err(e) = self.save_state(&state) {
+                    tracing::warn!("could not record the org store check: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Upload the cache's unpublished changes to the rule files. The
+    /// bucket's org policy must list `identity` as a maintainer. Returns the
+    /// number of objects written or deleted.
+    pub async fn publish(&self, identity: &Identity) -> Result<usize> {
+        if !self.config.writable {
+            return Err(HookwiseError::InvalidPolicy {
+                reason: "org_store.writable is off; the org rules are read-only".into(),
+            });
+        }
+        let mut state = self.load_state();
+        let mut changed = Vec::new();
+        for (file, _) in RULE_FILES {
+            let local = read_file(&self.dir.join(file))?;
+            let recorded = state.objects.get(file).map(|o| o.sha256.clone());
+            if local.as_deref().map(sha256_hex) != recorded {
+                changed.push((file, local));
+            }
+        }
+        if changed.is_empty() {
+            return Ok(0);
+        }
+        if self.credentials.is_none() {
+            return Err(HookwiseError::Identity {
+                reason: format!(
+                    "publishing org rules needs org store credentials in {} and {}",
+                    self.config.access_key_env, self.config.secret_key_env
+                ),
+            });
+        }
+        validate_dir(&self.dir)?;
+
+        let client = self.client()?;
+        // Maintainers come from the bucket, so a local edit can't add one
+        let policy = match self.get(&client, POLICY_FILE, None).await? {
+            Fetched::Found { contents, .. } => {
+                OrgPolicyConfig::parse(&contents, self.dir.join(POLICY_FILE))?
+            }
+            _ => OrgPolicyConfig::default(),
+        };
+        if !is_listed(&policy.maintainers, identity) {
+            return Err(not_a_maintainer(identity, &self.org));
+        }
+
+        for (file, local) in &changed {
+            let etag = state.objects.get(*file).map(|o| o.etag.clone());
+            match local {
+                Some(contents) => {
+                    let etag = self.put(&client, file, contents, etag.as_deref()).await?;
+                    let object = ObjectState {
+                        etag,
+                        sha256: sha256_hex(contents),
+                    };
+                    state.objects.insert(file.to_string(), object);
+                }
+                None => {
+                    self.delete(&client, file, etag.as_deref()).await?;
+                    state.objects.remove(*file);
+                }
+            }
+            // Recorded as each lands, so a later failure doesn't resend it
+            self.save_state(&state)?;
+        }
+        Ok(changed.len())
+    }
+
+    /// Refuse to cache into an org directory another source fills.
+    fn check_cache(&self) -> Result<()> {
+        let dir = self.dir.display();
+        let reason = if self.dir.join(".git").exists() {
+            format!("{} is a git checkout of the org rules", dir)
+        } else if self.dir.join(http::STATE_FILE).exists() {
+            format!("{} is synced from a rule server", dir)
+        } else if !self.dir.join(STATE_FILE).exists() && occupied(&self.dir) {
+            format!(
+                "{} holds org rules that weren't fetched from the org store",
+                dir
+            )
+        } else {
+            return Ok(());
+        };
+        Err(HookwiseError::Storage {
+            reason: format!("{}; move it aside to use the org store", reason),
+        })
+    }
+
+    fn load_state(&self) -> StoreState {
+        std::fs::read_to_string(self.dir.join(STATE_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_state(&self, state: &StoreState) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        write_atomic(
+            &self.dir.join(STATE_FILE),
+            serde_json::to_string_pretty(state)?.as_bytes(),
+        )
+    }
+
+    /// The object key of `file` in the org directory.
+    fn key(&self, file: &str) -> String {
+        format!("{}{}/{}", self.config.prefix, self.org, file)
+    }
+
+    fn url(&self, file: &str) -> Result<Url> {
+        let url = format!(
+            "{}/{}/{}",
+            self.config.endpoint.trim_end_matches('/'),
+            uri_encode(&self.config.bucket),
+            uri_encode(&self.key(file))
+        );
+        Url::parse(&url).map_err(|e| HookwiseError::InvalidPolicy {
+            reason: format!("org_store.endpoint {}: {}", self.config.endpoint, e),
+        })
+    }
+
+    fn client(&self) -> Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .user_agent(concat!("hookwise/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(self.config.timeout_secs))
+            .build()
+            .map_err(|e| HookwiseError::Storage {
+                reason: format!("org store client: {}", e),
+            })
+    }
+
+    /// A request for `file`, signed when there are credentials.
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        method: Method,
+        file: &str,
+        body: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        let url = self.url(file)?;
+        let mut headers = HeaderMap::new();
+        if let Some(credentials) = &self.credentials {
+            sign(
+                credentials,
+                &self.config.region,
+                &method,
+                &url,
+                body.as_bytes(),
+                Utc::now(),
+                &mut headers,
+            );
+        }
+        let request = client.request(method.clone(), url).headers(headers);
+        Ok(if method == Method::PUT {
+            request.body(body.to_string())
+        } else {
+            request
+        })
+    }
+
+    async fn get(
+        &self,
+        client: &reqwest::Client,
+        file: &str,
+        etag: Option<&str>,
+    ) -> Result<Fetched> {
+        let mut request = self.request(client, Method::GET, file, "")?;
+        if let Some(etag) = etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let response = request.send().await.map_err(|e| self.failed(file, e))?;
+        match response.status() {
+            StatusCode::NOT_MODIFIED => Ok(Fetched::Unchanged),
+            StatusCode::NOT_FOUND => Ok(Fetched::Missing),
+            status if status.is_success() => {
+                let etag = self.etag(file, response.headers())?;
+                let contents = response.text().await.map_err(|e| self.failed(file, e))?;
+                Ok(Fetched::Found { contents, etag })
+            }
+            status => Err(self.refused(file, status)),
+        }
+    }
+
+    /// Write `file`, provided the object still has `etag` (or, without one,
+    /// doesn't exist yet), returning its new ETag.
+    async fn put(
+        &self,
+        client: &reqwest::Client,
+        file: &str,
+        contents: &str,
+        etag: Option<&str>,
+    ) -> Result<String> {
+        let request = self.request(client, Method::PUT, file, contents)?;
+        let request = match etag {
+            Some(etag) => request.header(IF_MATCH, etag),
+            None => request.header(IF_NONE_MATCH, "*"),
+        };
+        let response = request.send().await.map_err(|e| self.failed(file, e))?;
+        match response.status() {
+            status if status.is_success() => self.etag(file, response.headers()),
+            StatusCode::PRECONDITION_FAILED | StatusCode::CONFLICT => Err(self.conflict(file)),
+            status => Err(self.refused(file, status)),
+        }
+    }
+
+    /// Delete `file`, provided the object still has `etag`.
+    async fn delete(&self, client: &reqwest::Client, file: &str, etag: Option<&str>) -> Result<()> {
+        let mut request = self.request(client, Method::DELETE, file, "")?;
+        if let Some(etag) = etag {
+            request = request.header(IF_MATCH, etag);
+        }
+        let response = request.send().await.map_err(|e| self.failed(file, e))?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            StatusCode::NOT_FOUND => Ok(()),
+            StatusCode::PRECONDITION_FAILED | StatusCode::CONFLICT => Err(self.conflict(file)),
+            status => Err(self.refused(file, status)),
+        }
+    }
+
+    fn etag(&self, file: &str, headers: &HeaderMap) -> Result<String> {
+        headers
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| HookwiseError::Storage {
+                reason: format!("no ETag in the org store response for {}", self.key(file)),
+            })
+    }
+
+    fn failed(&self, file: &str, e: reqwest::Error) -> HookwiseError {
+        HookwiseError::Storage {
+            reason: format!("org store request for {} failed: {}", self.key(file), e),
+        }
+    }
+
+    fn refused(&self, file: &str, status: StatusCode) -> HookwiseError {
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            HookwiseError::Identity {
+                reason: format!(
+                    "org store refused {} ({}); check the org_store credentials",
+                    self.key(file),
+                    status
+                ),
+            }
+        } else {
+            HookwiseError::Storage {
+                reason: format!("org store answered {} for {}", status, self.key(file)),
+            }
+        }
+    }
+
+    fn conflict(&self, file: &str) -> HookwiseError {
+        HookwiseError::Storage {
+            reason: format!(
+                "{} changed in the org store since the last refresh; run `hookwise sync` and try again",
+                self.key(file)
+            ),
+        }
+    }
+}
+
+/// Storage whose org scope is an [`OrgStore`]'s cache. Org-scope writes
+/// are allowed for org maintainers when the store is writable, and
+/// published to the bucket once saved locally; when publishing fails they
+/// stay in the cache and go out with the next org write. Other scopes pass
+/// through to `inner`.
+pub struct RemoteOrgStorage {
+    inner: Box<dyn StorageBackend>,
+    store: OrgStore,
+    identity: Identity,
+}
+
+impl RemoteOrgStorage {
+    pub fn new(inner: Box<dyn StorageBackend>, store: OrgStore, identity: Identity) -> Self {
+        Self {
+            inner,
+            store,
+            identity,
+        }
+    }
+
+    /// Check that `identity` may write org rules: the store is writable
+    /// and the cached org policy lists them as a maintainer.
+    fn check_writer(&self) -> Result<()> {
+        if !self.store.config.writable {
+            return Err(HookwiseError::Storage {
+                reason:
+                    "the org rules come from the org store; set org_store.writable to publish to it"
+                        .into(),
+            });
+        }
+        let path = self.store.dir.join(POLICY_FILE);
+        let policy = match read_file(&path)? {
+            Some(contents) => OrgPolicyConfig::parse(&contents, path)?,
+            None => OrgPolicyConfig::default(),
+        };
+        if !is_listed(&policy.maintainers, &self.identity) {
+            return Err(not_a_maintainer(&self.identity, &self.store.org));
+        }
+        Ok(())
+    }
+
+    /// Run `write` against `scope`, publishing the result when it's the org
+    /// scope.
+    fn write<T>(&self, scope: ScopeLevel, write: impl FnOnce() -> Result<T>) -> Result<T> {
+        if scope != ScopeLevel::Org {
+            return write();
+        }
+        self.check_writer()?;
+        let result = write()?;
+        if let Err(e) = block_on(self.store.publish(&self.identity)) {
+            eprintln!(
+                "hookwise: org rules saved locally but not published to the org store ({}); they go out with the next org write",
+                e
+            );
+        }
+        Ok(result)
+    }
+}
+
+impl StorageBackend for RemoteOrgStorage {
+    fn load_decisions(&self, scope: ScopeLevel) -> Result<Vec<DecisionRecord>> {
+        self.inner.load_decisions(scope)
+    }
+
+    fn load_decisions_for_role(
+        &self,
+        scope: ScopeLevel,
+        role: &str,
+    ) -> Result<Vec<DecisionRecord>> {
+        self.inner.load_decisions_for_role(scope, role)
+    }
+
+    fn save_decision(&self, record: &DecisionRecord) -> Result<()> {
+        self.write(record.scope, || self.inner.save_decision(record))
+    }
+
+    fn invalidate_role(&self, scope: ScopeLevel, role: &str) -> Result<()> {
+        self.write(scope, || self.inner.invalidate_role(scope, role))
+    }
+
+    fn invalidate_all(&self, scope: ScopeLevel) -> Result<()> {
+        self.write(scope, || self.inner.invalidate_all(scope))
+    }
+
+    fn remove_decisions(
+        &self,
+        scope: ScopeLevel,
+        predicate: &dyn Fn(&DecisionRecord) -> bool,
+    ) -> Result<usize> {
+        self.write(scope, || self.inner.remove_decisions(scope, predicate))
+    }
+
+    fn compact(&self, scope: ScopeLevel) -> Result<usize> {
+        // Only writers tidy the org rules; everyone else gets the bucket's
+        if scope == ScopeLevel::Org && self.check_writer().is_err() {
+            return Ok(0);
+        }
+        self.write(scope, || self.inner.compact(scope))
+    }
+
+    fn rebuild_index(&self, scope: ScopeLevel) -> Result<()> {
+        self.inner.rebuild_index(scope)
+    }
+
+    fn scan_for_secrets(&self, path: &Path) -> Result<Vec<SecretFinding>> {
+        self.inner.scan_for_secrets(path)
+    }
+}
+
+fn not_a_maintainer(identity: &Identity, org: &str) -> HookwiseError {
+    HookwiseError::Identity {
+        reason: format!(
+            "'{}' is not a maintainer of the {} org rules",
+            identity.user, org
+        ),
+    }
+}
+
+/// Run `future` to completion from synchronous code, which may itself be
+/// on a tokio runtime, on a scoped thread with a runtime of its own.
+fn block_on<T: Send>(future: impl Future<Output = Result<T>> + Send) -> Result<T> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()?
+                    .block_on(future)
+            })
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+fn read_file(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(std::fs::read_to_string(path)?))
+}
+
+/// Add AWS Signature Version 4 headers to a request at `now`.
+fn sign(
+    credentials: &Credentials,
+    region: &str,
+    method: &Method,
+    url: &Url,
+    body: &[u8],
+    now: DateTime<Utc>,
+    headers: &mut HeaderMap,
+) {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &amz_date[..8];
+    let payload = hex(&Sha256::digest(body));
+    let host = url.host_str().unwrap_or_default();
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let canonical = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        method,
+        url.path(),
+        host,
+        payload,
+        amz_date,
+        SIGNED_HEADERS,
+        payload
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical.as_bytes()))
+    );
+    let mut key = format!("AWS4{}", credentials.secret_key).into_bytes();
+    for part in [date, region, "s3", "aws4_request"] {
+        key = hmac_sha256(&key, part.as_bytes());
+    }
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key,
+        scope,
+        SIGNED_HEADERS,
+        hex(&hmac_sha256(&key, string_to_sign.as_bytes()))
+    );
+    for (name, value) in [
+        ("x-amz-date", amz_date),
+        ("x-amz-content-sha256", payload),
+        ("authorization", authorization),
+    ] {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data)
+        .as_ref()
+        .to_vec()
+}
+
+fn sha256_hex(text: &str) -> String {
+    hex(&Sha256::digest(text.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Percent-encode `text` as S3 canonical URIs are, keeping `/`.
+fn uri_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,159 @@
+//! Org rules from a git repository. The repository is cloned as the org
+//! directory, and maintainers listed in the upstream org policy publish
+//! local changes with `hookwise sync --push`.
+
+use std::path::Path;
+use std::process::Command;
+
+use super::{current_rules, diff, occupied, org_dir, validate, validate_dir, OrgRules, SyncReport};
+use crate::error::{HookwiseError, Result};
+use crate::identity::{is_listed, Identity};
+
+/// Run git with `args`, in `dir` when given, returning its trimmed stdout.
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    let output = command.args(args).output()?;
+    if !output.status.success() {
+        return Err(HookwiseError::Storage {
+            reason: format!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Validate the files of commit `rev` in the checkout at `dir`.
+fn validate_rev(dir: &Path, rev: &str) -> Result<OrgRules> {
+    let files = git(Some(dir), &["ls-tree", "-r", "--name-only", rev])?;
+    validate(|file| {
+        if !files.lines().any(|f| f == file) {
+            return Ok(None);
+        }
+        git(Some(dir), &["show", &format!("{}:{}", rev, file)]).map(Some)
+    })
+}
+
+/// Fetch the checkout at `dir` and name the upstream commit to follow.
+fn fetch(dir: &Path, branch: Option<&str>) -> Result<String> {
+    git(Some(dir), &["fetch", "--quiet", "origin"])?;
+    Ok(match branch {
+        Some(branch) => format!("origin/{}", branch),
+        None => "@{upstream}".to_string(),
+    })
+}
+
+/// Clone `repo` as the org checkout for `org` under `rules_root`, or
+/// fast-forward the checkout, validating the incoming rules first, and
+/// report how the effective rules changed.
+pub fn sync(rules_root: &Path, org: &str, repo: &str, branch: Option<&str>) -> Result<SyncReport> {
+    let dir = org_dir(rules_root, org);
+    let (before, after) = if dir.join(".git").exists() {
+        let before = current_rules(&dir);
+        let upstream = fetch(&dir, branch)?;
+        let after = validate_rev(&dir, &upstream)?;
+        git(Some(&dir), &["merge", "--quiet", "--ff-only", &upstream])?;
+        (before, after)
+    } else {
+        if occupied(&dir) {
+            return Err(HookwiseError::Storage {
+                reason: format!(
+                    "{} holds org rules that aren't a git checkout; move them aside to sync from {}",
+                    dir.display(),
+                    repo
+                ),
+            });
+        }
+        // Clone next to the org directory and move it in once validated
+        let staging = rules_root.join("org").join(format!(".{}.sync", org));
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        std::fs::create_dir_all(rules_root.join("org"))?;
+        let staging_arg = staging.to_string_lossy().into_owned();
+        let mut args = vec!["clone", "--quiet"];
+        if let Some(branch) = branch {
+            args.extend(["--branch", branch]);
+        }
+        args.extend([repo, staging_arg.as_str()]);
+        git(None, &args)?;
+        let after = match validate_dir(&staging) {
+            Ok(rules) => rules,
+            Err(e) => {
+                std::fs::remove_dir_all(&staging)?;
+                return Err(e);
+            }
+        };
+        if dir.exists() {
+            std::fs::remove_dir(&dir)?;
+        }
+        std::fs::rename(&staging, &dir)?;
+        (OrgRules::default(), after)
+    };
+
+    Ok(SyncReport {
+        org: org.to_string(),
+        version: git(Some(&dir), &["rev-parse", "--short", "HEAD"])?,
+        changes: diff(&before.records, &after.records),
+        skipped: after.skipped,
+    })
+}
+
+/// Commit the org checkout's local changes and push them, after checking
+/// that `identity` is a maintainer in the upstream org policy and that the
+/// rules validate. The report lists changes against upstream; None when
+/// there was nothing to publish.
+pub fn push(
+    rules_root: &Path,
+    org: &str,
+    branch: Option<&str>,
+    identity: &Identity,
+) -> Result<Option<SyncReport>> {
+    let dir = org_dir(rules_root, org);
+    if !dir.join(".git").exists() {
+        return Err(HookwiseError::Storage {
+            reason: format!(
+                "{} is not an org rules checkout; run `hookwise sync` first",
+                dir.display()
+            ),
+        });
+    }
+    let upstream = fetch(&dir, branch)?;
+    let published = validate_rev(&dir, &upstream)?;
+    // Maintainers come from upstream, so a local edit can't add one
+    if !is_listed(&published.policy.maintainers, identity) {
+        return Err(HookwiseError::Identity {
+            reason: format!(
+                "'{}' is not a maintainer of the {} org rules",
+                identity.user, org
+            ),
+        });
+    }
+    let local = validate_dir(&dir)?;
+
+    if !git(Some(&dir), &["status", "--porcelain"])?.is_empty() {
+        git(Some(&dir), &["add", "--all"])?;
+        let message = format!("Update org rules ({})", identity.user);
+        git(Some(&dir), &["commit", "--quiet", "-m", &message])?;
+    }
+    let ahead = git(
+        Some(&dir),
+        &["rev-list", "--count", &format!("{}..HEAD", upstream)],
+    )?;
+    if ahead == "0" {
+        return Ok(None);
+    }
+    git(Some(&dir), &["push", "--quiet", "origin", "HEAD"])?;
+
+    Ok(Some(SyncReport {
+        org: org.to_string(),
+        version: git(Some(&dir), &["rev-parse", "--short", "HEAD"])?,
+        changes: diff(&published.records, &local.records),
+        skipped: local.skipped,
+    }))
+}
@@ -0,0 +1,222 @@
+//! Org rules from a rule server, over HTTP sync protocol version 1.
+//!
+//! hookwise sends `GET {server}/v1/orgs/{org}/rules` with
+//! `Authorization: Bearer <token>` and, once it has synced, an
+//! `If-None-Match` header carrying the last ETag. The server answers 304
+//! when nothing changed, or 200 with an `ETag` header and a JSON body:
+//!
+//! ```json
+//! {"protocol": 1, "full": false, "files": {"rules/deny.jsonl": "...", "rules/ask.jsonl": null}}
+//! ```
+//!
+//! A full body (`"full": true`) holds every file, and files it leaves out
+//! are removed. A delta holds only the files changed since the ETag sent,
+//! with null for a removed file. Paths other than the rule files and
+//! `policy.yml` are ignored. The ETag synced is kept in the org directory's
+//! `.sync-state.json`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    current_rules, diff, occupied, org_dir, validate, SyncReport, POLICY_FILE, RULE_FILES,
+};
+use crate::config::OrgRulesConfig;
+use crate::error::{HookwiseError, Result};
+use crate::session::registration::write_atomic;
+
+/// The protocol version this hookwise speaks, also the URL prefix.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Environment variable read for the token when `org_rules.token_env` is
+/// unset.
+pub const DEFAULT_TOKEN_ENV: &str = "HOOKWISE_ORG_RULES_TOKEN";
+
+pub(crate) const STATE_FILE: &str = ".sync-state.json";
+
+/// The body of a 200 response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulesResponse {
+    pub protocol: u32,
+    /// Whether `files` is the whole rule set rather than a delta.
+    pub full: bool,
+    /// File contents by path in the org directory; null removes the file.
+    pub files: BTreeMap<String, Option<String>>,
+}
+
+/// What the last pull synced, so the next one can ask for a delta.
+#[derive(Debug, Serialize, Deserialize)]
+struct SyncState {
+    server: String,
+    etag: String,
+}
+
+/// The rule server token from the environment variable `config` names.
+pub fn token(config: &OrgRulesConfig) -> Option<String> {
+    let var = config.token_env.as_deref().unwrap_or(DEFAULT_TOKEN_ENV);
+    std::env::var(var).ok().filter(|t| !t.is_empty())
+}
+
+/// The ETag last synced from `server` into `dir`.
+fn last_etag(dir: &Path, server: &str) -> Option<String> {
+    let contents = std::fs::read_to_string(dir.join(STATE_FILE)).ok()?;
+    let state: SyncState = serde_json::from_str(&contents).ok()?;
+    (state.server == server).then_some(state.etag)
+}
+
+fn read_file(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(std::fs::read_to_string(path)?))
+}
+
+/// Pull `org`'s rules from `server` into the org directory under
+/// `rules_root`, validating the new rule set before writing it, and report
+/// how the effective rules changed.
+pub async fn pull(
+    rules_root: &Path,
+    org: &str,
+    server: &str,
+    token: Option<&str>,
+) -> Result<SyncReport> {
+    let dir = org_dir(rules_root, org);
+    if dir.join(".git").exists() {
+        return Err(HookwiseError::Storage {
+            reason: format!(
+                "{} is a git checkout of the org rules; move it aside to sync from {}",
+                dir.display(),
+                server
+            ),
+        });
+    }
+    if !dir.join(STATE_FILE).exists() && occupied(&dir) {
+        return Err(HookwiseError::Storage {
+            reason: format!(
+                "{} holds org rules that weren't synced from a rule server; move them aside to sync from {}",
+                dir.display(),
+                server
+            ),
+        });
+    }
+    let etag = last_etag(&dir, server);
+
+    let url = format!(
+        "{}/v{}/orgs/{}/rules",
+        server.trim_end_matches('/'),
+        PROTOCOL_VERSION,
+        org
+    );
+    let failed = |e: reqwest::Error| HookwiseError::Storage {
+        reason: format!("rule server request {} failed: {}", url, e),
+    };
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("hookwise/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(failed)?;
+    let mut request = client.get(&url);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    if let Some(etag) = &etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let response = request.send().await.map_err(failed)?;
+
+    let before = current_rules(&dir);
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(SyncReport {
+            org: org.to_string(),
+            version: etag.unwrap_or_default().trim_matches('"').to_string(),
+            changes: Vec::new(),
+            skipped: before.skipped,
+        });
+    }
+    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        return Err(HookwiseError::Identity {
+            reason: format!(
+                "rule server refused {} ({}); check the org_rules token",
+                url, status
+            ),
+        });
+    }
+    if !status.is_success() {
+        return Err(HookwiseError::Storage {
+            reason: format!("rule server answered {} for {}", status, url),
+        });
+    }
+    let new_etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .ok_or_else(|| HookwiseError::Storage {
+            reason: format!("no ETag in the rule server response for {}", url),
+        })?;
+    let body: RulesResponse = response.json().await.map_err(failed)?;
+    if body.protocol != PROTOCOL_VERSION {
+        return Err(HookwiseError::Storage {
+            reason: format!(
+                "rule server speaks sync protocol {}, this hookwise speaks {}",
+                body.protocol, PROTOCOL_VERSION
+            ),
+        });
+    }
+    if !body.full && etag.is_none() {
+        return Err(HookwiseError::Storage {
+            reason: "rule server sent a delta to a first sync".into(),
+        });
+    }
+
+    // The new rule set, file by file, before anything is written
+    let known: Vec<&str> = RULE_FILES
+        .iter()
+        .map(|(file, _)| *file)
+        .chain([POLICY_FILE])
+        .collect();
+    for name in body.files.keys() {
+        if !known.contains(&name.as_str()) {
+            tracing::warn!("ignoring {} from the rule server", name);
+        }
+    }
+    let mut files = BTreeMap::new();
+    for file in known {
+        let contents = match body.files.get(file) {
+            Some(contents) => contents.clone(),
+            None if body.full => None,
+            None => read_file(&dir.join(file))?,
+        };
+        files.insert(file, contents);
+    }
+    let after = validate(|file| Ok(files.get(file).cloned().flatten()))?;
+
+    std::fs::create_dir_all(dir.join("rules"))?;
+    for (file, contents) in &files {
+        let path = dir.join(file);
+        match contents {
+            Some(contents) => write_atomic(&path, contents.as_bytes())?,
+            None if path.exists() => std::fs::remove_file(&path)?,
+            None => {}
+        }
+    }
+    let state = SyncState {
+        server: server.to_string(),
+        etag: new_etag.clone(),
+    };
+    write_atomic(
+        &dir.join(STATE_FILE),
+        serde_json::to_string_pretty(&state)?.as_bytes(),
+    )?;
+
+    Ok(SyncReport {
+        org: org.to_string(),
+        version: new_etag.trim_matches('"').to_string(),
+        changes: diff(&before.records, &after.records),
+        skipped: after.skipped,
+    })
+}
@@ -0,0 +1,234 @@
+//! Org rules synced from a shared source (`hookwise sync`) into the org
+//! scope directory, `<rules root>/org/<org>/`, which holds
+//! `rules/{allow,deny,ask}.jsonl` and the org `policy.yml`. The source is a
+//! git repository (`org_rules.repo`, [`git`]) or a rule server
+//! (`org_rules.server`, [`http`]).
+//!
+//! Incoming rules are validated before anything on disk changes, so a bad
+//! upstream version leaves the last good rules in force.
+
+pub mod git;
+pub mod http;
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::config::{OrgPolicyConfig, OrgRulesConfig};
+use crate::decision::{Decision, DecisionRecord, SCHEMA_VERSION};
+use crate::error::{HookwiseError, Result};
+use crate::identity::Identity;
+use crate::scope::ScopeLevel;
+use crate::storage::bundle;
+
+/// Rule files in an org checkout and the decision each holds.
+pub(crate) const RULE_FILES: [(&str, Decision); 3] = [
+    ("rules/allow.jsonl", Decision::Allow),
+    ("rules/deny.jsonl", Decision::Deny),
+    ("rules/ask.jsonl", Decision::Ask),
+];
+
+pub(crate) const POLICY_FILE: &str = "policy.yml";
+
+/// The org scope directory for `org`, which `sync` keeps in step with the
+/// source.
+pub fn org_dir(rules_root: &Path, org: &str) -> PathBuf {
+    rules_root.join("org").join(org)
+}
+
+/// A difference in an org's effective rules.
+#[derive(Debug, Clone)]
+pub enum RuleChange {
+    Added(DecisionRecord),
+    /// The key's decision changed.
+    Changed {
+        from: DecisionRecord,
+        to: DecisionRecord,
+    },
+    Removed(DecisionRecord),
+}
+
+impl RuleChange {
+    /// The record the change leaves in force, or the one removed.
+    pub fn record(&self) -> &DecisionRecord {
+        match self {
+            RuleChange::Added(r) | RuleChange::Removed(r) => r,
+            RuleChange::Changed { to, .. } => to,
+        }
+    }
+}
+
+/// What a sync or push did to an org's rules.
+#[derive(Debug, Clone)]
+pub struct SyncReport {
+    pub org: String,
+    /// The version synced: the checkout's short commit hash, the rule
+    /// server's ETag, or a digest of the org store objects' ETags.
+    pub version: String,
+    pub changes: Vec<RuleChange>,
+    /// Records from a newer hookwise, kept but not read.
+    pub skipped: usize,
+}
+
+impl SyncReport {
+    /// Counts of added, changed and removed rules.
+    pub fn counts(&self) -> (usize, usize, usize) {
+        let mut counts = (0, 0, 0);
+        for change in &self.changes {
+            match change {
+                RuleChange::Added(_) => counts.0 += 1,
+                RuleChange::Changed { .. } => counts.1 += 1,
+                RuleChange::Removed(_) => counts.2 += 1,
+            }
+        }
+        counts
+    }
+}
+
+/// An org checkout's rules after validation.
+#[derive(Debug, Clone, Default)]
+pub struct OrgRules {
+    pub records: Vec<DecisionRecord>,
+    /// Lines with a `schema_version` newer than this hookwise reads.
+    pub skipped: usize,
+    pub policy: OrgPolicyConfig,
+}
+
+/// Validate an org checkout's files, read through `read(path)` (None when
+/// the file is absent). Every rule line must be a record of the file's
+/// decision at org scope, and `policy.yml` must parse. Records from a newer
+/// hookwise are counted and skipped.
+pub fn validate<F>(read: F) -> Result<OrgRules>
+where
+    F: Fn(&str) -> Result<Option<String>>,
+{
+    let invalid = |file: &str, line: usize, reason: String| HookwiseError::InvalidPolicy {
+        reason: format!("org rules {}:{}: {}", file, line, reason),
+    };
+    let mut rules = OrgRules::default();
+    for (file, decision) in RULE_FILES {
+        let Some(contents) = read(file)? else {
+            continue;
+        };
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let value: Value =
+                serde_json::from_str(line).map_err(|e| invalid(file, i + 1, e.to_string()))?;
+            let version = value["schema_version"].as_u64().unwrap_or(1);
+            if version > u64::from(SCHEMA_VERSION) {
+                rules.skipped += 1;
+                continue;
+            }
+            let record: DecisionRecord =
+                serde_json::from_value(value).map_err(|e| invalid(file, i + 1, e.to_string()))?;
+            if record.decision != decision {
+                return Err(invalid(
+                    file,
+                    i + 1,
+                    format!("a {} record in the {} file", record.decision, decision),
+                ));
+            }
+            if record.scope != ScopeLevel::Org {
+                return Err(invalid(
+                    file,
+                    i + 1,
+                    format!("scope is {}, not org", record.scope),
+                ));
+            }
+            rules.records.push(record);
+        }
+    }
+    if let Some(contents) = read(POLICY_FILE)? {
+        rules.policy = OrgPolicyConfig::parse(&contents, PathBuf::from(POLICY_FILE))?;
+    }
+    Ok(rules)
+}
+
+/// Compare two sets of org rules by each key's effective decision.
+pub fn diff(before: &[DecisionRecord], after: &[DecisionRecord]) -> Vec<RuleChange> {
+    let before = bundle::effective(before);
+    let after = bundle::effective(after);
+    let mut changes: Vec<RuleChange> = after
+        .iter()
+        .filter_map(|(key, to)| match before.get(key) {
+            None => Some(RuleChange::Added((*to).clone())),
+            Some(from) if from.decision != to.decision => Some(RuleChange::Changed {
+                from: (*from).clone(),
+                to: (*to).clone(),
+            }),
+            Some(_) => None,
+        })
+        .collect();
+    changes.extend(
+        before
+            .iter()
+            .filter(|(key, _)| !after.contains_key(*key))
+            .map(|(_, from)| RuleChange::Removed((*from).clone())),
+    );
+    changes.sort_by(|a, b| {
+        let (a, b) = (&a.record().key, &b.record().key);
+        (&a.tool, &a.sanitized_input, &a.role).cmp(&(&b.tool, &b.sanitized_input, &b.role))
+    });
+    changes
+}
+
+/// Validate the rule files under `dir`.
+pub(crate) fn validate_dir(dir: &Path) -> Result<OrgRules> {
+    validate(|file| {
+        let path = dir.join(file);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(std::fs::read_to_string(path)?))
+    })
+}
+
+/// The rules currently under `dir`, or none when they don't validate, so
+/// a sync reports every incoming rule as added.
+pub(crate) fn current_rules(dir: &Path) -> OrgRules {
+    validate_dir(dir).unwrap_or_else(|e| {
+        tracing::warn!(
+            "current org rules are invalid ({}); reporting all as added",
+            e
+        );
+        OrgRules::default()
+    })
+}
+
+/// Whether `dir` has entries.
+pub(crate) fn occupied(dir: &Path) -> bool {
+    std::fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some())
+}
+
+/// Sync `org`'s rules under `rules_root` from the source in `config`.
+pub async fn pull(rules_root: &Path, org: &str, config: &OrgRulesConfig) -> Result<SyncReport> {
+    match (&config.repo, &config.server) {
+        (Some(repo), None) => git::sync(rules_root, org, repo, config.branch.as_deref()),
+        (None, Some(server)) => {
+            http::pull(rules_root, org, server, http::token(config).as_deref()).await
+        }
+        _ => Err(HookwiseError::InvalidPolicy {
+            reason: "org_rules needs one of repo and server".into(),
+        }),
+    }
+}
+
+/// Publish local changes to `org`'s rules. Only a git source takes pushes;
+/// a rule server is updated through the server itself.
+pub fn push(
+    rules_root: &Path,
+    org: &str,
+    config: &OrgRulesConfig,
+    identity: &Identity,
+) -> Result<Option<SyncReport>> {
+    if config.repo.is_none() {
+        return Err(HookwiseError::InvalidPolicy {
+            reason: "sync --push needs a git org_rules.repo; publish through the rule server"
+                .into(),
+        });
+    }
+    git::push(rules_root, org, config.branch.as_deref(), identity)
+}
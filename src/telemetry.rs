@@ -0,0 +1,215 @@
+//! OpenTelemetry metrics. Instruments are recorded through the OTel API and
+//! are no-ops unless a meter provider is installed. With the `otel` feature,
+//! [`init`] installs an OTLP/HTTP exporter when the standard
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` (or `OTEL_EXPORTER_OTLP_METRICS_ENDPOINT`)
+//! environment variable is set.
+
+use std::sync::OnceLock;
+
+use opentelemetry::metrics::{Counter, Gauge, Histogram, Meter};
+use opentelemetry::KeyValue;
+
+use crate::decision::{Decision, DecisionTier, SecretFinding};
+
+const METER_NAME: &str = "hookwise";
+
+/// All hookwise instruments, created lazily from the global meter.
+struct Instruments {
+    decisions: Counter<u64>,
+    secrets: Counter<u64>,
+    supervisor_latency: Histogram<f64>,
+    cascade_latency: Histogram<f64>,
+    queue_depth: Gauge<u64>,
+    cache_entries: Gauge<u64>,
+    rollout: Counter<u64>,
+}
+
+fn instruments() -> &'static Instruments {
+    static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+    INSTRUMENTS.get_or_init(|| {
+        let meter: Meter = opentelemetry::global::meter(METER_NAME);
+        Instruments {
+            decisions: meter
+                .u64_counter("hookwise.decisions")
+                .with_description("Permission decisions by cascade tier and outcome")
+                .build(),
+            secrets: meter
+                .u64_counter("hookwise.secrets.redacted")
+                .with_description("Secrets redacted from tool inputs by kind and tool")
+                .build(),
+            supervisor_latency: meter
+                .f64_histogram("hookwise.supervisor.latency")
+                .with_unit("s")
+                .with_description("Supervisor evaluation latency")
+                .build(),
+            cascade_latency: meter
+                .f64_histogram("hookwise.cascade.latency")
+                .with_unit("s")
+                .with_description("End-to-end cascade evaluation latency")
+                .build(),
+            queue_depth: meter
+                .u64_gauge("hookwise.queue.depth")
+                .with_description("Pending human decisions")
+                .build(),
+            cache_entries: meter
+                .u64_gauge("hookwise.cache.entries")
+                .with_description("Exact cache entries by decision")
+                .build(),
+            rollout: meter
+                .u64_counter("hookwise.rollout.matches")
+                .with_description("Calls matched by rolled-out org rules by cohort and divergence")
+                .build(),
+        }
+    })
+}
+
+/// Count a resolved decision.
+pub fn record_decision(tier: DecisionTier, decision: Decision) {
+    instruments().decisions.add(
+        1,
+        &[
+            KeyValue::new("tier", format!("{:?}", tier)),
+            KeyValue::new("decision", decision.to_string()),
+        ],
+    );
+}
+
+/// Count the secrets redacted from one tool call.
+pub fn record_secret_findings(findings: &[SecretFinding], tool: &str) {
+    for finding in findings {
+        instruments().secrets.add(
+            finding.count as u64,
+            &[
+                KeyValue::new("kind", finding.kind.clone()),
+                KeyValue::new("tool", tool.to_string()),
+            ],
+        );
+    }
+}
+
+/// Record how long the supervisor took to evaluate a request, and whether
+/// the backend returned a result or failed.
+pub fn record_supervisor_latency(elapsed: std::time::Duration, ok: bool) {
+    let outcome = if ok { "ok" } else { "error" };
+    instruments()
+        .supervisor_latency
+        .record(elapsed.as_secs_f64(), &[KeyValue::new("outcome", outcome)]);
+}
+
+/// Record how long a full cascade evaluation took.
+pub fn record_cascade_latency(elapsed: std::time::Duration, tier: DecisionTier) {
+    instruments().cascade_latency.record(
+        elapsed.as_secs_f64(),
+        &[KeyValue::new("tier", format!("{:?}", tier))],
+    );
+}
+
+/// Record the current number of pending human decisions.
+pub fn record_queue_depth(depth: usize) {
+    instruments().queue_depth.record(depth as u64, &[]);
+}
+
+/// Record exact cache sizes per decision type.
+pub fn record_cache_sizes(stats: &crate::cascade::cache::CacheStats) {
+    let gauge = &instruments().cache_entries;
+    gauge.record(
+        stats.allow_entries as u64,
+        &[KeyValue::new("decision", "allow")],
+    );
+    gauge.record(
+        stats.deny_entries as u64,
+        &[KeyValue::new("decision", "deny")],
+    );
+    gauge.record(
+        stats.ask_entries as u64,
+        &[KeyValue::new("decision", "ask")],
+    );
+}
+
+/// Count a call matched by a rolled-out org rule.
+pub fn record_rollout(event: &crate::rollout::RolloutEvent) {
+    let cohort = match event.cohort {
+        crate::rollout::Cohort::Canary => "canary",
+        crate::rollout::Cohort::Control => "control",
+    };
+    instruments().rollout.add(
+        1,
+        &[
+            KeyValue::new("cohort", cohort),
+            KeyValue::new("diverged", event.diverged()),
+        ],
+    );
+}
+
+#[cfg(feature = "otel")]
+static PROVIDER: std::sync::Mutex<Option<opentelemetry_sdk::metrics::SdkMeterProvider>> =
+    std::sync::Mutex::new(None);
+
+/// Calls [`shutdown`] when dropped. Hook invocations are short-lived, so
+/// metrics must be exported before the process exits.
+pub struct TelemetryGuard(());
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        shutdown();
+    }
+}
+
+/// Install the OTLP metrics exporter if the `otel` feature is enabled and an
+/// OTLP endpoint is configured. Otherwise instruments stay no-ops.
+pub fn init() -> TelemetryGuard {
+    #[cfg(feature = "otel")]
+    if let Some(provider) = init_otlp() {
+        if let Ok(mut slot) = PROVIDER.lock() {
+            *slot = Some(provider);
+        }
+    }
+    TelemetryGuard(())
+}
+
+/// Flush pending metrics and shut down the exporter. Call this before
+/// `std::process::exit`, which skips destructors.
+pub fn shutdown() {
+    #[cfg(feature = "otel")]
+    {
+        let provider = PROVIDER.lock().ok().and_then(|mut slot| slot.take());
+        if let Some(provider) = provider {
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!("failed to flush OpenTelemetry metrics: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "otel")]
+fn init_otlp() -> Option<opentelemetry_sdk::metrics::SdkMeterProvider> {
+    let configured = [
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+    ]
+    .iter()
+    .any(|var| std::env::var(var).is_ok_and(|v| !v.is_empty()));
+    if !configured {
+        return None;
+    }
+
+    let exporter = match opentelemetry_otlp::MetricExporter::builder()
+        .with_http()
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            eprintln!("hookwise: OpenTelemetry exporter unavailable ({})", e);
+            return None;
+        }
+    };
+    let resource = opentelemetry_sdk::Resource::builder()
+        .with_service_name(METER_NAME)
+        .build();
+    let provider = opentelemetry_sdk::metrics::SdkMeterProvider::builder()
+        .with_periodic_exporter(exporter)
+        .with_resource(resource)
+        .build();
+    opentelemetry::global::set_meter_provider(provider.clone());
+    Some(provider)
+}
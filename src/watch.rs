@@ -0,0 +1,428 @@
+//! Filesystem change notification shared by `hookwise monitor`, queue
+//! waiting and config reloading. Uses inotify on Linux; on other platforms,
+//! or when inotify can't be set up, it falls back to polling modification
+//! times. Changes are debounced, so a burst of writes (or a write followed
+//! by a rename) arrives as one batch.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use tokio::sync::mpsc;
+
+use crate::error::Result;
+
+/// How often a native watcher wakes up to notice it was dropped.
+const STOP_CHECK: Duration = Duration::from_millis(100);
+
+/// How a watcher learns about changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchBackend {
+    /// OS notifications (inotify).
+    Native,
+    /// Comparing modification times and sizes every poll interval.
+    Polling,
+}
+
+/// Watches files, and the direct entries of directories, for changes.
+pub struct FileWatcher {
+    paths: Vec<PathBuf>,
+    debounce: Duration,
+    poll_interval: Duration,
+    force_polling: bool,
+}
+
+impl FileWatcher {
+    /// Watch `paths`. A file need not exist yet: creating it is a change.
+    /// A directory reports changes to its direct entries, not to deeper
+    /// ones.
+    pub fn new<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Self {
+            paths: paths.into_iter().map(Into::into).collect(),
+            debounce: Duration::from_millis(100),
+            poll_interval: Duration::from_millis(500),
+            force_polling: false,
+        }
+    }
+
+    /// How long to collect changes after the first one before reporting
+    /// them as a batch. Default: 100ms.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// How often the polling backend compares the watched paths.
+    /// Default: 500ms.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Poll even where native notifications are available (e.g. for
+    /// network filesystems that don't deliver them).
+    pub fn with_polling(mut self, force: bool) -> Self {
+        self.force_polling = force;
+        self
+    }
+
+    /// Start watching on a background thread. The thread stops when the
+    /// returned stream is dropped.
+    pub fn start(self) -> Result<WatchStream> {
+        let targets = Targets::new(&self.paths);
+        let native = if self.force_polling {
+            None
+        } else {
+            native::Native::new(&targets.watch_dirs())
+                .inspect_err(|e| {
+                    tracing::debug!("native file watching unavailable, polling instead: {e}")
+                })
+                .ok()
+        };
+        let backend = if native.is_some() {
+            WatchBackend::Native
+        } else {
+            WatchBackend::Polling
+        };
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker = Worker {
+            targets,
+            native,
+            debounce: self.debounce,
+            poll_interval: self.poll_interval,
+            tx,
+            stop: stop.clone(),
+        };
+        std::thread::Builder::new()
+            .name("hookwise-watch".into())
+            .spawn(move || worker.run())?;
+
+        Ok(WatchStream { rx, backend, stop })
+    }
+}
+
+/// Batches of changed paths from a [`FileWatcher`]. Paths are absolute.
+pub struct WatchStream {
+    rx: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    backend: WatchBackend,
+    stop: Arc<AtomicBool>,
+}
+
+impl WatchStream {
+    /// The backend in use, after any fallback to polling.
+    pub fn backend(&self) -> WatchBackend {
+        self.backend
+    }
+
+    /// Wait for the next batch of changed paths. None if the watcher
+    /// stopped.
+    pub async fn changed(&mut self) -> Option<Vec<PathBuf>> {
+        self.rx.recv().await
+    }
+
+    /// Wait at most `timeout` for the next batch; empty if nothing changed
+    /// in time. For loops that also need to wake up on a schedule.
+    pub async fn changed_within(&mut self, timeout: Duration) -> Vec<PathBuf> {
+        tokio::time::timeout(timeout, self.rx.recv())
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for WatchStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The watched paths, made absolute so events can be matched against them.
+struct Targets {
+    files: HashSet<PathBuf>,
+    dirs: HashSet<PathBuf>,
+}
+
+impl Targets {
+    fn new(paths: &[PathBuf]) -> Self {
+        let mut targets = Self {
+            files: HashSet::new(),
+            dirs: HashSet::new(),
+        };
+        for path in paths {
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.clone());
+            if path.is_dir() {
+                targets.dirs.insert(path);
+            } else {
+                targets.files.insert(path);
+            }
+        }
+        targets
+    }
+
+    /// Directories to watch natively: watched directories, and the parents
+    /// of watched files (so creation and replace-by-rename are seen).
+    fn watch_dirs(&self) -> BTreeSet<PathBuf> {
+        let parents = self.files.iter().filter_map(|f| f.parent());
+        self.dirs
+            .iter()
+            .map(PathBuf::as_path)
+            .chain(parents)
+            .map(Path::to_path_buf)
+            .collect()
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        self.files.contains(path) || path.parent().is_some_and(|p| self.dirs.contains(p))
+    }
+
+    /// Modification time and size of every watched file and directory entry.
+    fn snapshot(&self) -> HashMap<PathBuf, (SystemTime, u64)> {
+        let stat = |path: &Path| {
+            let meta = std::fs::metadata(path).ok()?;
+            Some((meta.modified().ok()?, meta.len()))
+        };
+        let mut snapshot = HashMap::new();
+        for file in &self.files {
+            if let Some(stat) = stat(file) {
+                snapshot.insert(file.clone(), stat);
+            }
+        }
+        for dir in &self.dirs {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if let Some(stat) = stat(&path) {
+                    snapshot.insert(path, stat);
+                }
+            }
+        }
+        snapshot
+    }
+}
+
+/// The background thread: collects changes and sends debounced batches.
+struct Worker {
+    targets: Targets,
+    native: Option<native::Native>,
+    debounce: Duration,
+    poll_interval: Duration,
+    tx: mpsc::UnboundedSender<Vec<PathBuf>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Worker {
+    fn run(mut self) {
+        let mut snapshot = self.targets.snapshot();
+        let mut pending = BTreeSet::new();
+        let mut deadline: Option<Instant> = None;
+
+        while !self.stop.load(Ordering::Relaxed) && !self.tx.is_closed() {
+            let tick = match &self.native {
+                Some(_) => STOP_CHECK,
+                None => self.poll_interval,
+            };
+            let tick = match deadline {
+                Some(deadline) => tick.min(deadline.saturating_duration_since(Instant::now())),
+                None => tick,
+            };
+
+            let changed = match &mut self.native {
+                Some(native) => match native.read(tick) {
+                    Ok(changed) => changed,
+                    Err(e) => {
+                        tracing::debug!("native file watching failed, polling instead: {e}");
+                        self.native = None;
+                        Vec::new()
+                    }
+                },
+                None => {
+                    std::thread::sleep(tick);
+                    let next = self.targets.snapshot();
+                    let changed = diff(&snapshot, &next);
+                    snapshot = next;
+                    changed
+                }
+            };
+
+            let changed: Vec<PathBuf> = changed
+                .into_iter()
+                .filter(|p| self.targets.matches(p))
+                .collect();
+            if !changed.is_empty() {
+                pending.extend(changed);
+                // The window starts at the first change, so steady writes
+                // can't hold a batch back forever.
+                deadline.get_or_insert_with(|| Instant::now() + self.debounce);
+            }
+
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                deadline = None;
+                let batch = std::mem::take(&mut pending).into_iter().collect();
+                if self.tx.send(batch).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Paths added, removed or changed between two snapshots.
+fn diff(
+    before: &HashMap<PathBuf, (SystemTime, u64)>,
+    after: &HashMap<PathBuf, (SystemTime, u64)>,
+) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = after
+        .iter()
+        .filter(|(path, stat)| before.get(*path) != Some(stat))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(
+        before
+            .keys()
+            .filter(|path| !after.contains_key(*path))
+            .cloned(),
+    );
+    changed
+}
+
+#[cfg(target_os = "linux")]
+mod native {
+    use std::collections::{BTreeSet, HashMap};
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    const MASK: u32 = libc::IN_MODIFY
+        | libc::IN_CLOSE_WRITE
+        | libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO;
+
+    /// An inotify instance watching a set of directories.
+    pub struct Native {
+        fd: libc::c_int,
+        dirs: HashMap<libc::c_int, PathBuf>,
+    }
+
+    impl Native {
+        pub fn new(dirs: &BTreeSet<PathBuf>) -> std::io::Result<Self> {
+            // SAFETY: plain syscall; the fd is owned by the returned value.
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let mut native = Self {
+                fd,
+                dirs: HashMap::new(),
+            };
+            for dir in dirs {
+                let path = CString::new(dir.as_os_str().as_bytes())
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+                // SAFETY: `path` is a valid NUL-terminated string.
+                let wd = unsafe { libc::inotify_add_watch(fd, path.as_ptr(), MASK) };
+                if wd < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                native.dirs.insert(wd, dir.clone());
+            }
+            Ok(native)
+        }
+
+        /// Wait up to `timeout` for events and return the paths they name.
+        pub fn read(&mut self, timeout: Duration) -> std::io::Result<Vec<PathBuf>> {
+            let mut pollfd = libc::pollfd {
+                fd: self.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let millis = timeout.as_millis().min(i32::MAX as u128) as libc::c_int;
+            // SAFETY: one valid pollfd.
+            let ready = unsafe { libc::poll(&mut pollfd, 1, millis) };
+            if ready < 0 {
+                let err = std::io::Error::last_os_error();
+                if err.kind() == std::io::ErrorKind::Interrupted {
+                    return Ok(Vec::new());
+                }
+                return Err(err);
+            }
+
+            let mut paths = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                // SAFETY: reads into a buffer we own, bounded by its length.
+                let n = unsafe { libc::read(self.fd, buf.as_mut_ptr().cast(), buf.len()) };
+                if n < 0 {
+                    let err = std::io::Error::last_os_error();
+                    if err.kind() == std::io::ErrorKind::WouldBlock {
+                        return Ok(paths);
+                    }
+                    return Err(err);
+                }
+                self.parse(&buf[..n as usize], &mut paths);
+            }
+        }
+
+        fn parse(&self, mut buf: &[u8], paths: &mut Vec<PathBuf>) {
+            let header = std::mem::size_of::<libc::inotify_event>();
+            while buf.len() >= header {
+                // SAFETY: the kernel writes whole events; the header may be
+                // unaligned within the byte buffer.
+                let event: libc::inotify_event =
+                    unsafe { std::ptr::read_unaligned(buf.as_ptr().cast()) };
+                let end = (header + event.len as usize).min(buf.len());
+                let name = &buf[header..end];
+                let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+                if let Some(dir) = self.dirs.get(&event.wd) {
+                    if !name.is_empty() {
+                        paths.push(dir.join(std::ffi::OsStr::from_bytes(name)));
+                    }
+                }
+                buf = &buf[end..];
+            }
+        }
+    }
+
+    impl Drop for Native {
+        fn drop(&mut self) {
+            // SAFETY: we own the fd.
+            unsafe { libc::close(self.fd) };
+        }
+    }
+}
+
+/// Native notifications (FSEvents, ReadDirectoryChangesW) aren't wired up
+/// on this platform yet; the watcher polls instead.
+#[cfg(not(target_os = "linux"))]
+mod native {
+    use std::collections::BTreeSet;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    pub struct Native;
+
+    impl Native {
+        pub fn new(_dirs: &BTreeSet<PathBuf>) -> std::io::Result<Self> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "no native file watcher on this platform",
+            ))
+        }
+
+        pub fn read(&mut self, _timeout: Duration) -> std::io::Result<Vec<PathBuf>> {
+            Ok(Vec::new())
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Tests for the audit log's hash chain: appends chain to the last entry,
+//! `audit::verify` finds edited, removed and reordered entries, and a head
+//! hash from an earlier run catches truncation.
+
+mod common;
+
+use std::path::Path;
+
+use chrono::Utc;
+use tempfile::TempDir;
+
+use hookwise::audit::{self, AuditEvent, AuditSink, GENESIS_HASH};
+use hookwise::decision::{CacheKey, Decision, DecisionRecord, DecisionTier, ScopeLevel};
+
+fn event(command: &str) -> AuditEvent {
+    AuditEvent {
+        logged_at: Utc::now(),
+        cwd: "/work".into(),
+        record: DecisionRecord {
+            key: CacheKey {
+                sanitized_input: command.into(),
+                tool: "Bash".into(),
+                role: "coder".into(),
+            },
+            decision: Decision::Allow,
+            metadata: common::metadata(DecisionTier::ExactCache, "cached"),
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: None,
+            session_id: "chain".into(),
+        },
+        identity: None,
+        not_enforced: false,
+    }
+}
+
+/// A log of `commands`, one entry each.
+fn log(commands: &[&str]) -> (TempDir, AuditSink) {
+    let tmp = TempDir::new().unwrap();
+    let sink = AuditSink::new(tmp.path().join("audit.jsonl"));
+    for command in commands {
+        sink.record(&event(command)).unwrap();
+    }
+    (tmp, sink)
+}
+
+fn lines(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+fn write_lines(path: &Path, lines: &[String]) {
+    std::fs::write(path, lines.join("\n") + "\n").unwrap();
+}
+
+#[test]
+fn entries_chain_to_the_one_before() {
+    let (_tmp, sink) = log(&["ls", "cargo test", "git status"]);
+    let entries: Vec<serde_json::Value> = lines(sink.path())
+        .iter()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(entries[0]["prev_hash"], GENESIS_HASH);
+    assert_eq!(entries[1]["prev_hash"], entries[0]["hash"]);
+    assert_eq!(entries[2]["prev_hash"], entries[1]["hash"]);
+    // Readers of the log don't mind the extra fields
+    assert_eq!(audit::load_events(sink.path()).len(), 3);
+
+    let report = audit::verify(sink.path(), None).unwrap();
+    assert_eq!((report.entries, report.unchained), (3, 0));
+    assert_eq!(report.head.as_deref(), entries[2]["hash"].as_str());
+    assert!(report.broken.is_none());
+}
+
+#[test]
+fn edited_removed_and_reordered_entries_break_the_chain() {
+    let (_tmp, sink) = log(&["ls", "cargo test", "git status"]);
+    let original = lines(sink.path());
+    let broken = |lines: &[String]| {
+        write_lines(sink.path(), lines);
+        let report = audit::verify(sink.path(), None).unwrap();
+        let broken = report.broken.expect("a broken chain");
+        (broken.line, broken.reason)
+    };
+
+    let mut edited = original.clone();
+    edited[1] = edited[1].replace("cargo test", "rm -rf /");
+    let (line, reason) = broken(&edited);
+    assert_eq!(line, 2);
+    assert!(reason.contains("match its hash"), "{reason}");
+
+    let (line, reason) = broken(&[original[0].clone(), original[2].clone()]);
+    assert_eq!(line, 2);
+    assert!(reason.contains("follow"), "{reason}");
+
+    let reordered = [1, 0, 2].map(|i| original[i].clone());
+    assert_eq!(broken(&reordered).0, 1);
+
+    // An entry without a hash can't be slipped in after chaining starts
+    let mut inserted = original.clone();
+    inserted.insert(1, serde_json::to_string(&event("curl")).unwrap());
+    let (line, reason) = broken(&inserted);
+    assert_eq!(line, 2);
+    assert!(reason.contains("no hash"), "{reason}");
+}
+
+#[test]
+fn truncation_is_caught_by_an_earlier_head() {
+    let (_tmp, sink) = log(&["ls", "cargo test"]);
+    let head = audit::verify(sink.path(), None).unwrap().head.unwrap();
+    sink.record(&event("git status")).unwrap();
+    let report = audit::verify(sink.path(), Some(head.as_str())).unwrap();
+    assert!(!report.missing_since);
+    assert_eq!(report.entries, 3);
+
+    let kept = lines(sink.path());
+    write_lines(sink.path(), &kept[..1]);
+    let report = audit::verify(sink.path(), Some(head.as_str())).unwrap();
+    assert!(report.broken.is_none());
+    assert!(report.missing_since);
+}
+
+#[test]
+fn older_unchained_logs_start_a_chain() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("audit.jsonl");
+    let old = serde_json::to_string(&event("ls")).unwrap();
+    std::fs::write(&path, format!("{old}\n{old}\n")).unwrap();
+
+    let sink = AuditSink::new(path.clone());
+    sink.record(&event("cargo test")).unwrap();
+    sink.record(&event("git status")).unwrap();
+    let report = audit::verify(&path, None).unwrap();
+    assert_eq!((report.entries, report.unchained), (2, 2));
+    assert!(report.broken.is_none());
+    assert_eq!(audit::load_events(&path).len(), 4);
+
+    // A missing log is an empty chain
+    let report = audit::verify(&tmp.path().join("none.jsonl"), None).unwrap();
+    assert_eq!((report.entries, report.head), (0, None));
+}
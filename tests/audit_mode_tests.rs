@@ -0,0 +1,204 @@
+//! Tests for audit-mode sessions: the cascade runs and is logged, but
+//! nothing is enforced, queued or learned.
+
+mod common;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tempfile::TempDir;
+
+use hookwise::audit::{default_audit_log_path, AuditEvent, AuditSink};
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::config::{AuditConfig, SessionMode};
+use hookwise::decision::{CacheKey, Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::session::{SessionContext, SessionManager};
+
+/// A tier that decides `decision`, or falls through when it is None.
+struct Fixed {
+    tier: DecisionTier,
+    decision: Option<Decision>,
+}
+
+#[async_trait]
+impl CascadeTier for Fixed {
+    async fn evaluate(
+        &self,
+        input: &CascadeInput,
+    ) -> hookwise::error::Result<Option<DecisionRecord>> {
+        Ok(self.decision.map(|decision| DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: "*".into(),
+            },
+            decision,
+            metadata: common::metadata(self.tier, "fixed"),
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: input.file_path.clone(),
+            session_id: String::new(),
+        }))
+    }
+    fn tier(&self) -> DecisionTier {
+        self.tier
+    }
+    fn name(&self) -> &str {
+        "fixed"
+    }
+}
+
+fn session(mode: SessionMode) -> SessionContext {
+    SessionContext {
+        mode,
+        ..common::session()
+    }
+}
+
+fn runner(tmp: &TempDir, supervisor: Option<Decision>) -> CascadeRunner {
+    common::runner(
+        tmp.path(),
+        Box::new(Fixed {
+            tier: DecisionTier::Supervisor,
+            decision: supervisor,
+        }),
+        Box::new(Fixed {
+            tier: DecisionTier::Human,
+            decision: Some(Decision::Allow),
+        }),
+    )
+}
+
+fn learned(runner: &CascadeRunner) -> usize {
+    runner.exact_cache.stats().total_entries
+        + runner
+            .storage
+            .load_decisions(ScopeLevel::Project)
+            .unwrap()
+            .len()
+}
+
+#[tokio::test]
+async fn audit_mode_would_ask_instead_of_queueing() {
+    let tmp = TempDir::new().unwrap();
+    let runner = runner(&tmp, None);
+    let input = serde_json::json!({ "command": "make deploy" });
+
+    let record = runner
+        .evaluate(&session(SessionMode::Audit), "Bash", &input)
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Ask);
+    assert_eq!(record.metadata.tier, DecisionTier::Human);
+    assert!(record.metadata.reason.contains("would ask a human"));
+    assert_eq!(learned(&runner), 0);
+
+    // Enforced sessions reach the human tier and keep the answer.
+    let record = runner
+        .evaluate(&session(SessionMode::Enforce), "Bash", &input)
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Allow);
+    assert_eq!(record.metadata.tier, DecisionTier::Human);
+    assert!(learned(&runner) > 0);
+}
+
+#[tokio::test]
+async fn audit_mode_reports_the_would_be_decision_without_persisting() {
+    let tmp = TempDir::new().unwrap();
+    let runner = runner(&tmp, Some(Decision::Deny));
+
+    let record = runner
+        .evaluate(
+            &session(SessionMode::Audit),
+            "Bash",
+            &serde_json::json!({ "command": "rm -rf build" }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(record.metadata.tier, DecisionTier::Supervisor);
+    assert_eq!(learned(&runner), 0);
+}
+
+#[test]
+fn registered_mode_overrides_the_policy_default() {
+    let tmp = TempDir::new().unwrap();
+    std::fs::create_dir_all(tmp.path().join(".hookwise")).unwrap();
+    std::fs::write(
+        tmp.path().join(".hookwise/policy.yml"),
+        "session_mode: audit\n",
+    )
+    .unwrap();
+    let cwd = tmp.path().to_str().unwrap();
+
+    let suffix = format!("test-audit-mode-{}", std::process::id());
+    let mgr = SessionManager::new(Some(&suffix));
+    let id = |name: &str| format!("{suffix}-{name}");
+
+    mgr.register(&id("default"), "coder", None, None, None)
+        .unwrap();
+    mgr.register(
+        &id("enforced"),
+        "coder",
+        None,
+        None,
+        Some(SessionMode::Enforce),
+    )
+    .unwrap();
+    let mode = |name: &str| mgr.get_or_populate(&id(name), cwd).unwrap().mode;
+    assert_eq!(mode("default"), SessionMode::Audit);
+    assert_eq!(mode("enforced"), SessionMode::Enforce);
+
+    // Switching roles keeps the registered mode.
+    mgr.switch_role(&id("enforced"), "tester").unwrap();
+    assert_eq!(mode("enforced"), SessionMode::Enforce);
+
+    for kind in ["sessions", "exclusions", "disable-requests"] {
+        let _ = std::fs::remove_file(
+            hookwise::config::dirs_runtime().join(format!("hookwise-{suffix}-{kind}.json")),
+        );
+    }
+}
+
+#[test]
+fn audit_events_carry_the_not_enforced_marker() {
+    let tmp = TempDir::new().unwrap();
+    let sink = AuditSink::for_audit_mode(&AuditConfig::default(), tmp.path());
+    assert_eq!(
+        sink.path(),
+        default_audit_log_path(&tmp.path().join(".hookwise"))
+    );
+    let configured = AuditConfig {
+        path: Some("audit.jsonl".into()),
+    };
+    assert_eq!(
+        AuditSink::for_audit_mode(&configured, tmp.path()).path(),
+        tmp.path().join("audit.jsonl")
+    );
+
+    let event = |not_enforced| AuditEvent {
+        logged_at: Utc::now(),
+        cwd: tmp.path().display().to_string(),
+        record: DecisionRecord {
+            key: CacheKey {
+                sanitized_input: "ls".into(),
+                tool: "Bash".into(),
+                role: "coder".into(),
+            },
+            decision: Decision::Deny,
+            metadata: common::metadata(DecisionTier::Supervisor, "no"),
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: None,
+            session_id: String::new(),
+        },
+        identity: None,
+        not_enforced,
+    };
+    let enforced = serde_json::to_string(&event(false)).unwrap();
+    assert!(!enforced.contains("not_enforced"));
+    sink.record(&event(true)).unwrap();
+    let events = hookwise::audit::load_events(sink.path());
+    assert_eq!(events.len(), 1);
+    assert!(events[0].not_enforced);
+}
@@ -1,35 +1,21 @@
 //! Unit tests for the exact cache (Tier 1) and its tri-state behavior.
 
-use hookwise::cascade::cache::ExactCache;
-use hookwise::decision::{
-    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
-};
-use chrono::Utc;
+mod common;
 
-fn make_key(input: &str, tool: &str, role: &str) -> CacheKey {
-    CacheKey {
-        sanitized_input: input.into(),
-        tool: tool.into(),
-        role: role.into(),
-    }
-}
+use hookwise::cascade::cache::{CommitKeys, ExactCache};
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::config::CacheConfig;
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use chrono::Utc;
+use hookwise::session::SessionContext;
 
 fn make_record(input: &str, tool: &str, role: &str, decision: Decision) -> DecisionRecord {
-    DecisionRecord {
-        key: make_key(input, tool, role),
-        decision,
-        metadata: DecisionMetadata {
-            tier: DecisionTier::Human,
-            confidence: 1.0,
-            reason: "test decision".into(),
-            matched_key: None,
-            similarity_score: None,
-        },
-        timestamp: Utc::now(),
-        scope: ScopeLevel::Project,
-        file_path: None,
-        session_id: "test-session".into(),
-    }
+    let mut record = DecisionRecord {
+        metadata: common::metadata(DecisionTier::Human, "test decision"),
+        ..common::record(input, role, decision)
+    };
+    record.key.tool = tool.into();
+    record
 }
 
 // ---------------------------------------------------------------------------
@@ -201,3 +187,201 @@ fn decision_display() {
     assert_eq!(format!("{}", Decision::Deny), "deny");
     assert_eq!(format!("{}", Decision::Ask), "ask");
 }
+
+// ---------------------------------------------------------------------------
+// Per-scope max age
+// ---------------------------------------------------------------------------
+
+fn aged_record(input: &str, scope: ScopeLevel, age_days: i64) -> DecisionRecord {
+    let mut record = make_record(input, "Bash", "coder", Decision::Allow);
+    record.scope = scope;
+    record.timestamp = Utc::now() - chrono::Duration::days(age_days);
+    record
+}
+
+fn expiry(project: Option<u32>, user: Option<u32>) -> CacheConfig {
+    let mut config = CacheConfig::default();
+    config.max_age_days.project = project;
+    config.max_age_days.user = user;
+    config
+}
+
+#[test]
+fn load_skips_entries_past_scope_max_age() {
+    let cache = ExactCache::new().with_expiry(expiry(Some(30), Some(90)));
+    cache.load_from(vec![
+        aged_record("old project", ScopeLevel::Project, 45),
+        aged_record("new project", ScopeLevel::Project, 5),
+        aged_record("old user", ScopeLevel::User, 45),
+        aged_record("older user", ScopeLevel::User, 120),
+    ]);
+    assert_eq!(cache.stats().total_entries, 2);
+}
+
+#[test]
+fn no_max_age_keeps_everything() {
+    let cache = ExactCache::new();
+    cache.load_from(vec![aged_record("ancient", ScopeLevel::Org, 3650)]);
+    assert_eq!(cache.stats().total_entries, 1);
+}
+
+#[test]
+fn evict_expired_removes_aged_entries() {
+    let cache = ExactCache::new().with_expiry(expiry(Some(30), None));
+    cache.insert(aged_record("old", ScopeLevel::Project, 45));
+    cache.insert(aged_record("new", ScopeLevel::Project, 1));
+    assert_eq!(cache.evict_expired(), 1);
+    assert_eq!(cache.stats().total_entries, 1);
+}
+
+#[test]
+fn cache_max_age_parses_from_policy_yaml() {
+    let yaml = "cache:\n  max_age_days:\n    project: 30\n    org: 180\n";
+    let policy: hookwise::PolicyConfig = serde_yaml::from_str(yaml).unwrap();
+    let now = Utc::now();
+    let cache = &policy.cache;
+    assert!(cache.is_expired(ScopeLevel::Project, now - chrono::Duration::days(31), now));
+    assert!(!cache.is_expired(ScopeLevel::Org, now - chrono::Duration::days(31), now));
+    assert!(cache.is_expired(ScopeLevel::Role, now - chrono::Duration::days(31), now));
+    assert!(!cache.is_expired(ScopeLevel::User, now - chrono::Duration::days(999), now));
+}
+
+// ---------------------------------------------------------------------------
+// LRU size cap
+// ---------------------------------------------------------------------------
+
+fn wildcard_input(input: &str) -> CascadeInput {
+    CascadeInput {
+        session: SessionContext {
+            user: "test-user".into(),
+            org: "test-org".into(),
+            project: "test-project".into(),
+            team: None,
+            role: None,
+            path_policy: None,
+            agent_prompt_hash: None,
+            agent_prompt_path: None,
+            task_description: None,
+            registered_at: None,
+            disabled: false,
+            mode: Default::default(),
+            fallback_roles: Vec::new(),
+        },
+        tool_name: "Bash".into(),
+        tool_input: serde_json::Value::Null,
+        sanitized_input: input.into(),
+        file_path: None,
+        cwd: None,
+        recommendation: None,
+    }
+}
+
+async fn is_hit(cache: &ExactCache, input: &str) -> bool {
+    cache
+        .evaluate(&wildcard_input(input))
+        .await
+        .unwrap()
+        .is_some()
+}
+
+#[test]
+fn max_entries_evicts_oldest_and_counts_evictions() {
+    let cache = ExactCache::new().with_max_entries(2);
+    cache.load_from(vec![
+        make_record("one", "Bash", "*", Decision::Allow),
+        make_record("two", "Bash", "*", Decision::Allow),
+        make_record("three", "Bash", "*", Decision::Allow),
+    ]);
+    let stats = cache.stats();
+    assert_eq!(stats.total_entries, 2);
+    assert_eq!(stats.evictions, 1);
+}
+
+#[test]
+fn zero_max_entries_is_unbounded() {
+    let cache = ExactCache::new().with_max_entries(0);
+    for i in 0..50 {
+        let input = format!("cmd {}", i);
+        cache.insert(make_record(&input, "Bash", "*", Decision::Allow));
+    }
+    assert_eq!(cache.stats().total_entries, 50);
+    assert_eq!(cache.stats().evictions, 0);
+}
+
+#[tokio::test]
+async fn lookup_refreshes_lru_order() {
+    let cache = ExactCache::new().with_max_entries(2);
+    cache.insert(make_record("one", "Bash", "*", Decision::Allow));
+    cache.insert(make_record("two", "Bash", "*", Decision::Allow));
+
+    // Touch "one" so "two" becomes least recently used.
+    assert!(is_hit(&cache, "one").await);
+    cache.insert(make_record("three", "Bash", "*", Decision::Allow));
+
+    assert!(is_hit(&cache, "one").await);
+    assert!(!is_hit(&cache, "two").await);
+    assert!(is_hit(&cache, "three").await);
+    assert_eq!(cache.stats().evictions, 1);
+}
+
+#[test]
+fn reinserting_a_key_does_not_evict() {
+    let cache = ExactCache::new().with_max_entries(2);
+    cache.insert(make_record("one", "Bash", "*", Decision::Allow));
+    cache.insert(make_record("two", "Bash", "*", Decision::Allow));
+    cache.insert(make_record("one", "Bash", "*", Decision::Deny));
+    let stats = cache.stats();
+    assert_eq!(stats.total_entries, 2);
+    assert_eq!(stats.deny_entries, 1);
+    assert_eq!(stats.evictions, 0);
+}
+
+fn bash_in(dir: &std::path::Path, command: &str) -> CascadeInput {
+    let tool_input = serde_json::json!({ "command": command });
+    CascadeInput {
+        session: SessionContext::new_minimal("u".into(), "o".into(), "p".into()),
+        tool_name: "Bash".into(),
+        sanitized_input: tool_input.to_string(),
+        tool_input,
+        file_path: None,
+        cwd: Some(dir.display().to_string()),
+        recommendation: None,
+    }
+}
+
+#[test]
+fn commit_keyed_commands_are_salted_with_head() {
+    let config: CacheConfig = serde_yaml::from_str("commit_keyed: ['^\\./deploy']").unwrap();
+    let keys = CommitKeys::compile(&config).unwrap().unwrap();
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::create_dir(repo.path().join(".git")).unwrap();
+    std::fs::write(repo.path().join(".git/HEAD"), "abc123\n").unwrap();
+
+    let deploy = bash_in(repo.path(), "./deploy.sh prod");
+    assert_eq!(
+        keys.salt(&deploy),
+        Some(format!("{} @abc123", deploy.sanitized_input))
+    );
+    assert_eq!(keys.salt(&bash_in(repo.path(), "cargo test")), None);
+    let outside = tempfile::tempdir().unwrap();
+    assert_eq!(
+        keys.salt(&bash_in(outside.path(), "./deploy.sh prod")),
+        None
+    );
+
+    std::fs::write(repo.path().join(".git/HEAD"), "def456\n").unwrap();
+    assert_eq!(
+        keys.salt(&deploy),
+        Some(format!("{} @def456", deploy.sanitized_input))
+    );
+}
+
+#[test]
+fn commit_keys_compile_only_when_configured() {
+    assert!(CommitKeys::compile(&CacheConfig::default())
+        .unwrap()
+        .is_none());
+    let config: CacheConfig = serde_yaml::from_str("commit_keyed: ['(']").unwrap();
+    let err = CommitKeys::compile(&config).err().unwrap().to_string();
+    assert!(err.contains("cache.commit_keyed[0]"), "{err}");
+}
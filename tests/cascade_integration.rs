@@ -4,16 +4,19 @@
 //! These tests build a CascadeRunner with real tiers (except supervisor/human
 //! which are stubbed) and verify the full pipeline.
 
+mod common;
+
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use chrono::Utc;
 use tempfile::TempDir;
 
-use hookwise::cascade::cache::ExactCache;
 use hookwise::cascade::embed_sim::EmbeddingSimilarity;
-use hookwise::cascade::path_policy::PathPolicyEngine;
-use hookwise::cascade::token_sim::TokenJaccard;
+use hookwise::cascade::human::{
+    DecisionQueue, HumanResponse, HumanTier, PendingDecision, Priority,
+};
+use hookwise::cascade::supervisor::{SupervisorBackend, SupervisorRequest, SupervisorTier};
 use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
 use hookwise::config::policy::PolicyConfig;
 use hookwise::config::roles::{CompiledPathPolicy, PathPolicyConfig, RoleDefinition};
@@ -88,11 +91,8 @@ impl CascadeTier for AllowSupervisor {
             },
             decision: Decision::Allow,
             metadata: DecisionMetadata {
-                tier: DecisionTier::Supervisor,
                 confidence: 0.95,
-                reason: "test supervisor allows".into(),
-                matched_key: None,
-                similarity_score: None,
+                ..common::metadata(DecisionTier::Supervisor, "test supervisor allows")
             },
             timestamp: Utc::now(),
             scope: ScopeLevel::Project,
@@ -108,6 +108,35 @@ impl CascadeTier for AllowSupervisor {
     }
 }
 
+/// A supervisor backend that denies, but without enough confidence to decide.
+struct UnsureDenyBackend;
+
+#[async_trait]
+impl SupervisorBackend for UnsureDenyBackend {
+    async fn evaluate(
+        &self,
+        request: &SupervisorRequest,
+        _policy: &PolicyConfig,
+    ) -> hookwise::error::Result<DecisionRecord> {
+        Ok(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: request.sanitized_input.clone(),
+                tool: request.tool_name.clone(),
+                role: request.role.clone(),
+            },
+            decision: Decision::Deny,
+            metadata: DecisionMetadata {
+                confidence: 0.3,
+                ..common::metadata(DecisionTier::Supervisor, "looks like a production deploy")
+            },
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: None,
+            session_id: request.session_id.clone(),
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Test helpers
 // ---------------------------------------------------------------------------
@@ -130,6 +159,7 @@ fn make_session(role_name: &str) -> SessionContext {
             name: role_name.into(),
             description: "test role".into(),
             paths: path_config,
+            rule_scope: None,
         }),
         path_policy: Some(Arc::new(compiled)),
         agent_prompt_hash: None,
@@ -137,6 +167,8 @@ fn make_session(role_name: &str) -> SessionContext {
         task_description: None,
         registered_at: Some(Utc::now()),
         disabled: false,
+        mode: Default::default(),
+        fallback_roles: Vec::new(),
     }
 }
 
@@ -145,8 +177,6 @@ fn make_runner(
     supervisor: Box<dyn CascadeTier>,
     human: Box<dyn CascadeTier>,
 ) -> CascadeRunner {
-    let storage = JsonlStorage::new(tmp.path().to_path_buf(), tmp.path().join("global"), None);
-
     // Try embedding similarity; if model fails, use noop
     let embedding_sim = match EmbeddingSimilarity::new("default", 0.85) {
         Ok(es) => Arc::new(es),
@@ -161,16 +191,8 @@ fn make_runner(
     };
 
     CascadeRunner {
-        sanitizer: hookwise::sanitize::SanitizePipeline::default_pipeline(),
-        path_policy: Box::new(PathPolicyEngine::new().unwrap()),
-        exact_cache: Arc::new(ExactCache::new()),
-        token_jaccard: Arc::new(TokenJaccard::new(0.7, 3)),
         embedding_similarity: embedding_sim,
-        supervisor,
-        human,
-        storage: Box::new(storage),
-        policy: PolicyConfig::default(),
-        normalizer: None,
+        ..common::runner(tmp.path(), supervisor, human)
     }
 }
 
@@ -219,6 +241,52 @@ async fn cascade_allows_write_to_allowed_path() {
     assert_eq!(record.decision, Decision::Allow);
 }
 
+#[tokio::test]
+async fn cascade_uses_the_first_role_that_may_make_the_call() {
+    let tmp = TempDir::new().unwrap();
+    let runner = make_runner_with_allow_supervisor(&tmp);
+    let tester_paths = PathPolicyConfig {
+        allow_write: vec!["tests/**".into()],
+        deny_write: vec!["src/**".into(), "docs/**".into()],
+        allow_read: vec!["**".into()],
+    };
+    let tester = RoleDefinition {
+        name: "tester".into(),
+        description: "test role".into(),
+        paths: tester_paths.clone(),
+        rule_scope: None,
+    };
+    let compiled = CompiledPathPolicy::compile(&tester_paths, &[]).unwrap();
+    let mut session = make_session("coder");
+    session.fallback_roles = vec![(tester, Arc::new(compiled))];
+
+    async fn write(
+        runner: &CascadeRunner,
+        session: &SessionContext,
+        file: &str,
+    ) -> (Decision, String) {
+        let tool_input = serde_json::json!({"file_path": file, "content": "x"});
+        let record = runner
+            .evaluate(session, "Write", &tool_input)
+            .await
+            .unwrap();
+        (record.decision, record.key.role)
+    }
+    assert_eq!(
+        write(&runner, &session, "src/main.rs").await,
+        (Decision::Allow, "coder".into())
+    );
+    assert_eq!(
+        write(&runner, &session, "tests/unit.rs").await,
+        (Decision::Allow, "tester".into())
+    );
+    // Denied to every role: the primary role's deny
+    assert_eq!(
+        write(&runner, &session, "docs/guide.md").await,
+        (Decision::Deny, "coder".into())
+    );
+}
+
 #[tokio::test]
 async fn cascade_asks_for_sensitive_path() {
     let tmp = TempDir::new().unwrap();
@@ -317,6 +385,7 @@ async fn cascade_deny_wins_over_ask() {
             name: "custom".into(),
             description: "test".into(),
             paths: path_config,
+            rule_scope: None,
         }),
         path_policy: Some(Arc::new(compiled)),
         agent_prompt_hash: None,
@@ -324,24 +393,21 @@ async fn cascade_deny_wins_over_ask() {
         task_description: None,
         registered_at: Some(Utc::now()),
         disabled: false,
+        mode: Default::default(),
+        fallback_roles: Vec::new(),
     };
 
-    // .env matches both deny_write and sensitive_ask_write.
-    // BUG DOCUMENTATION: The path policy engine checks sensitive_ask_write BEFORE
-    // deny_write in its if-else chain (path_policy.rs:139-147). So when a path
-    // matches both, sensitive wins and returns Ask, even though deny has higher
-    // precedence. The precedence logic (worst_decision) only applies across
-    // different paths, not within a single path's match.
-    // This means deny_write patterns that overlap with sensitive_ask_write are
-    // effectively shadowed. This could be an intentional design choice (sensitive
-    // paths always prompt) or a bug where the if-else order should check deny first.
+    // .env matches both deny_write and sensitive_ask_write. With the default
+    // `path_precedence: sensitive_first`, sensitive paths always prompt, so a
+    // deny_write pattern overlapping a sensitive one is shadowed.
+    // `deny_first` reverses this (see path_policy_tests.rs).
     let tool_input = serde_json::json!({"file_path": ".env.local", "content": "x"});
     let record = runner
         .evaluate(&session, "Write", &tool_input)
         .await
         .unwrap();
 
-    // Current behavior: Ask wins because sensitive is checked first in the if-else
+    // Ask wins because sensitive is checked first by default
     assert_eq!(record.decision, Decision::Ask);
 }
 
@@ -465,13 +531,7 @@ fn scope_merge_deny_wins_over_allow() {
             role: "coder".into(),
         },
         decision: Decision::Allow,
-        metadata: DecisionMetadata {
-            tier: DecisionTier::Human,
-            confidence: 1.0,
-            reason: "user allowed".into(),
-            matched_key: None,
-            similarity_score: None,
-        },
+        metadata: common::metadata(DecisionTier::Human, "user allowed"),
         timestamp: Utc::now(),
         scope: ScopeLevel::User,
         file_path: None,
@@ -481,13 +541,7 @@ fn scope_merge_deny_wins_over_allow() {
     let deny_record = DecisionRecord {
         key: allow_record.key.clone(),
         decision: Decision::Deny,
-        metadata: DecisionMetadata {
-            tier: DecisionTier::Human,
-            confidence: 1.0,
-            reason: "org denied".into(),
-            matched_key: None,
-            similarity_score: None,
-        },
+        metadata: common::metadata(DecisionTier::Human, "org denied"),
         timestamp: Utc::now(),
         scope: ScopeLevel::Org,
         file_path: None,
@@ -523,13 +577,7 @@ fn scope_merge_ask_wins_over_allow() {
             role: "coder".into(),
         },
         decision: Decision::Allow,
-        metadata: DecisionMetadata {
-            tier: DecisionTier::Human,
-            confidence: 1.0,
-            reason: "allowed".into(),
-            matched_key: None,
-            similarity_score: None,
-        },
+        metadata: common::metadata(DecisionTier::Human, "allowed"),
         timestamp: Utc::now(),
         scope: ScopeLevel::User,
         file_path: None,
@@ -539,13 +587,7 @@ fn scope_merge_ask_wins_over_allow() {
     let ask_record = DecisionRecord {
         key: allow_record.key.clone(),
         decision: Decision::Ask,
-        metadata: DecisionMetadata {
-            tier: DecisionTier::Human,
-            confidence: 1.0,
-            reason: "sensitive".into(),
-            matched_key: None,
-            similarity_score: None,
-        },
+        metadata: common::metadata(DecisionTier::Human, "sensitive"),
         timestamp: Utc::now(),
         scope: ScopeLevel::Project,
         file_path: None,
@@ -595,6 +637,13 @@ fn decision_queue_enqueue_and_list() {
         is_ask_reprompt: false,
         ask_reason: None,
         queued_at: Utc::now(),
+        priority: Default::default(),
+        waiter_pid: None,
+        heartbeat_at: None,
+        attachment: None,
+        suggestion: None,
+        risk_score: None,
+        project_root: None,
     };
 
     queue.enqueue(pending);
@@ -622,6 +671,13 @@ fn decision_queue_respond_removes_pending() {
         is_ask_reprompt: false,
         ask_reason: None,
         queued_at: Utc::now(),
+        priority: Default::default(),
+        waiter_pid: None,
+        heartbeat_at: None,
+        attachment: None,
+        suggestion: None,
+        risk_score: None,
+        project_root: None,
     };
 
     queue.enqueue(pending);
@@ -634,6 +690,8 @@ fn decision_queue_respond_removes_pending() {
                 always_ask: false,
                 add_rule: true,
                 rule_scope: Some(ScopeLevel::Project),
+                batch: None,
+                responded_by: None,
             },
         )
         .unwrap();
@@ -645,3 +703,52 @@ fn decision_queue_respond_removes_pending() {
     assert!(resp.is_some());
     assert_eq!(resp.unwrap().decision, Decision::Deny);
 }
+
+#[tokio::test]
+async fn unconfident_supervisor_answer_reaches_the_human_queue() {
+    let tmp = TempDir::new().unwrap();
+    let queue = Arc::new(DecisionQueue::memory_only());
+    let runner = make_runner(
+        &tmp,
+        Box::new(SupervisorTier::new(
+            Box::new(UnsureDenyBackend),
+            PolicyConfig::default(),
+        )),
+        Box::new(HumanTier::new(queue.clone(), 5)),
+    );
+    let session = make_session("coder");
+
+    async fn answer_next(queue: &DecisionQueue) -> PendingDecision {
+        loop {
+            if let Some(pending) = queue.list_pending().into_iter().next() {
+                queue
+                    .respond(
+                        &pending.id,
+                        HumanResponse {
+                            decision: Decision::Allow,
+                            always_ask: false,
+                            add_rule: false,
+                            rule_scope: None,
+                            batch: None,
+                            responded_by: None,
+                        },
+                    )
+                    .unwrap();
+                return pending;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    let tool_input = serde_json::json!({"command": "make deploy"});
+    let (record, pending) = tokio::join!(
+        runner.evaluate(&session, "Bash", &tool_input),
+        answer_next(&queue)
+    );
+    assert_eq!(record.unwrap().decision, Decision::Allow);
+
+    let recommendation = pending.recommendation.expect("supervisor answer shown");
+    assert_eq!(recommendation.decision, Decision::Deny);
+    assert_eq!(recommendation.reason, "looks like a production deploy");
+    assert!(pending.priority >= Priority::High);
+}
@@ -0,0 +1,164 @@
+//! Tests for recording the assistant on decisions: the runner stamps the
+//! client on what it returns and persists, older records read without
+//! one, and stats and export bundles count decisions by assistant.
+
+mod common;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tempfile::TempDir;
+
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::decision::{
+    count_by_client, CacheKey, ClientInfo, Decision, DecisionMetadata, DecisionRecord,
+    DecisionTier, ScopeLevel,
+};
+use hookwise::hook_io::{HookFormat, HookInput};
+use hookwise::session::SessionContext;
+use hookwise::storage::bundle;
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::StorageBackend;
+
+/// A supervisor that allows everything, knowing nothing of the client.
+struct AllowAll;
+
+#[async_trait]
+impl CascadeTier for AllowAll {
+    async fn evaluate(
+        &self,
+        input: &CascadeInput,
+    ) -> hookwise::error::Result<Option<DecisionRecord>> {
+        Ok(Some(record(&input.sanitized_input, None)))
+    }
+
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::Supervisor
+    }
+
+    fn name(&self) -> &str {
+        "allow-all"
+    }
+}
+
+fn record(command: &str, client: Option<ClientInfo>) -> DecisionRecord {
+    DecisionRecord {
+        key: CacheKey {
+            sanitized_input: command.into(),
+            tool: "Bash".into(),
+            role: "*".into(),
+        },
+        decision: Decision::Allow,
+        metadata: DecisionMetadata {
+            tier: DecisionTier::Supervisor,
+            confidence: 0.9,
+            reason: "fine".into(),
+            matched_key: None,
+            similarity_score: None,
+            targets: Vec::new(),
+            message: None,
+            suggestion: None,
+            risk_score: None,
+            secrets: Vec::new(),
+            client,
+        },
+        timestamp: Utc::now(),
+        scope: ScopeLevel::Project,
+        file_path: None,
+        session_id: "client".into(),
+    }
+}
+
+fn client(format: HookFormat, version: Option<&str>) -> ClientInfo {
+    let input: HookInput = serde_json::from_value(serde_json::json!({
+        "session_id": "client",
+        "cwd": "/tmp",
+        "tool_name": "Bash",
+        "tool_input": { "command": "ls" },
+        "client_version": version,
+    }))
+    .unwrap();
+    input.client(format)
+}
+
+fn runner(tmp: &TempDir, client: ClientInfo) -> CascadeRunner {
+    CascadeRunner {
+        client: Some(client),
+        ..common::runner(
+            tmp.path(),
+            Box::new(AllowAll),
+            Box::new(common::Pass(DecisionTier::Human)),
+        )
+    }
+}
+
+#[tokio::test]
+async fn runner_records_the_client_on_returned_and_stored_decisions() {
+    let tmp = TempDir::new().unwrap();
+    let session = SessionContext::new_minimal("u".into(), "o".into(), "p".into());
+    let input = serde_json::json!({ "command": "cargo build" });
+
+    let claude = client(HookFormat::Claude, Some("2.1.0"));
+    let mut runner = runner(&tmp, claude.clone());
+    let record = runner.evaluate(&session, "Bash", &input).await.unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::Supervisor);
+    assert_eq!(record.metadata.client.as_ref(), Some(&claude));
+
+    // A cache hit reports the calling assistant; the stored record keeps
+    // the one that made it
+    let gemini = client(HookFormat::Gemini, None);
+    runner.client = Some(gemini.clone());
+    let record = runner.evaluate(&session, "Bash", &input).await.unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::ExactCache);
+    assert_eq!(record.metadata.client, Some(gemini));
+
+    let stored = JsonlStorage::new(tmp.path().to_path_buf(), tmp.path().join("global"), None)
+        .load_decisions(ScopeLevel::Project)
+        .unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].metadata.client, Some(claude));
+}
+
+#[test]
+fn client_is_written_only_when_known() {
+    let line = serde_json::to_string(&record("ls", None)).unwrap();
+    assert!(!line.contains("client"), "{line}");
+    let read: DecisionRecord = serde_json::from_str(&line).unwrap();
+    assert!(read.metadata.client.is_none());
+
+    let line = serde_json::to_string(&record(
+        "ls",
+        Some(client(HookFormat::Gemini, Some("0.9.0"))),
+    ))
+    .unwrap();
+    assert!(
+        line.contains(r#""client":{"format":"gemini","assistant":"gemini-cli","version":"0.9.0"}"#),
+        "{line}"
+    );
+}
+
+#[test]
+fn decisions_are_counted_by_client_in_export_bundles() {
+    let records = vec![
+        record("ls", Some(client(HookFormat::Claude, Some("2.1.0")))),
+        record("pwd", Some(client(HookFormat::Claude, Some("2.1.0")))),
+        record("cat", Some(client(HookFormat::Gemini, None))),
+        record("make", None),
+    ];
+    let counts = count_by_client(&records);
+    let expected = [
+        ("claude-code 2.1.0".to_string(), 2),
+        ("gemini-cli".to_string(), 1),
+        ("unrecorded".to_string(), 1),
+    ];
+    assert_eq!(counts.into_iter().collect::<Vec<_>>(), expected);
+
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("bundle.tar");
+    bundle::write_bundle(&path, ScopeLevel::Project, &records, "dev").unwrap();
+    let (manifest, read) = bundle::read_bundle(&path).unwrap();
+    assert_eq!(manifest.clients.into_iter().collect::<Vec<_>>(), expected);
+    assert_eq!(
+        read[2].metadata.client.as_ref().unwrap().assistant,
+        "gemini-cli"
+    );
+}
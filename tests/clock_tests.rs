@@ -0,0 +1,94 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use hookwise::cascade::human::{DecisionQueue, HumanResponse, HumanTier};
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::clock::{Clock, FixedClock, IdGenerator, SequentialIds, TimestampIds};
+use hookwise::decision::Decision;
+
+fn input(command: &str) -> CascadeInput {
+    common::input(
+        common::session(),
+        "Bash",
+        serde_json::json!({ "command": command }),
+    )
+}
+
+/// Approve the next decision to show up in `queue`, returning its id.
+async fn approve_next(queue: &DecisionQueue) -> String {
+    loop {
+        if let Some(pending) = queue.list_pending().into_iter().next() {
+            queue
+                .respond(
+                    &pending.id,
+                    HumanResponse {
+                        decision: Decision::Allow,
+                        always_ask: false,
+                        add_rule: false,
+                        rule_scope: None,
+                        batch: None,
+                        responded_by: None,
+                    },
+                )
+                .unwrap();
+            return pending.id;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[test]
+fn fixed_clock_only_moves_when_advanced() {
+    let start = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+    let clock = FixedClock::new(start);
+    assert_eq!(clock.now(), start);
+    assert_eq!(clock.now(), start);
+
+    clock.advance(chrono::Duration::seconds(90));
+    assert_eq!(clock.now(), start + chrono::Duration::seconds(90));
+    clock.set(start);
+    assert_eq!(clock.now(), start);
+}
+
+#[test]
+fn id_generators_produce_role_tool_suffix_ids() {
+    let at = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+    assert_eq!(
+        TimestampIds.next_id("coder", "Bash", at),
+        format!("coder-Bash-{}", at.timestamp_millis())
+    );
+
+    let ids = SequentialIds::new(0);
+    assert_eq!(ids.next_id("coder", "Bash", at), "coder-Bash-1");
+    assert_eq!(ids.next_id("*", "Write", at), "*-Write-2");
+    assert_eq!(SequentialIds::new(41).next_id("a", "b", at), "a-b-42");
+}
+
+#[tokio::test]
+async fn human_tier_with_injected_clock_and_ids_is_reproducible() {
+    let at = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+    let run = || async {
+        let queue = Arc::new(DecisionQueue::memory_only());
+        let tier = HumanTier::new(queue.clone(), 5)
+            .with_clock(Arc::new(FixedClock::new(at)))
+            .with_ids(Arc::new(SequentialIds::new(0)));
+
+        let mut ids = Vec::new();
+        let mut timestamps = Vec::new();
+        for command in ["make deploy", "make release"] {
+            let input = input(command);
+            let (record, id) = tokio::join!(tier.evaluate(&input), approve_next(&queue));
+            ids.push(id);
+            timestamps.push(record.unwrap().unwrap().timestamp);
+        }
+        (ids, timestamps)
+    };
+
+    let (ids, timestamps) = run().await;
+    assert_eq!(ids, vec!["*-Bash-1", "*-Bash-2"]);
+    assert_eq!(timestamps, vec![at, at]);
+    assert_eq!(run().await, (ids, timestamps));
+}
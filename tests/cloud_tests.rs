@@ -0,0 +1,239 @@
+//! Tests for cloud CLI action classification and `cloud` rules.
+
+mod common;
+
+use hookwise::cascade::cloud::{cloud_invocations, CloudAction, CloudInvocation, CloudPolicy};
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::config::{CloudCli, CloudRule, PathPolicyConfig};
+use hookwise::decision::{Decision, DecisionTier};
+use hookwise::session::SessionContext;
+
+fn parse(command: &str) -> CloudInvocation {
+    CloudInvocation::parse(command).unwrap_or_else(|| panic!("not a cloud command: {command}"))
+}
+
+fn action(command: &str) -> CloudAction {
+    parse(command).action
+}
+
+fn policy(yaml: &str) -> CloudPolicy {
+    let rules: Vec<CloudRule> = serde_yaml::from_str(yaml).unwrap();
+    CloudPolicy::compile(&rules).unwrap().unwrap()
+}
+
+fn rules() -> CloudPolicy {
+    policy(
+        r#"
+- roles: ["reviewer", "research*"]
+  read: allow
+  mutate: deny
+  destroy: deny
+- clis: [kubectl]
+  scopes: ["prod", "prod-*"]
+  mutate: ask
+  destroy: deny
+- read: allow
+"#,
+    )
+}
+
+fn decide(policy: &CloudPolicy, role: &str, command: &str) -> Option<Decision> {
+    policy.check(role, command).map(|(d, _)| d)
+}
+
+#[test]
+fn classifies_kubectl_verbs_and_namespaces() {
+    let get = parse("kubectl -n prod get pods -o wide");
+    assert_eq!(get.cli, CloudCli::Kubectl);
+    assert_eq!(get.action, CloudAction::Read);
+    assert_eq!(get.verb, "get");
+    assert_eq!(get.scope.as_deref(), Some("prod"));
+
+    assert_eq!(action("kubectl apply -f deploy.yaml"), CloudAction::Mutate);
+    assert_eq!(action("kubectl delete pod web-1"), CloudAction::Destroy);
+    assert_eq!(action("kubectl drain node-1"), CloudAction::Destroy);
+    assert_eq!(
+        action("kubectl rollout status deploy/web"),
+        CloudAction::Read
+    );
+    assert_eq!(
+        action("kubectl rollout restart deploy/web"),
+        CloudAction::Mutate
+    );
+    assert_eq!(action("kubectl config get-contexts"), CloudAction::Read);
+    assert_eq!(
+        action("kubectl config use-context prod"),
+        CloudAction::Mutate
+    );
+    assert_eq!(
+        action("kubectl exec -it web-1 -- cat /etc/passwd"),
+        CloudAction::Mutate
+    );
+    assert_eq!(action("kubectl frobnicate"), CloudAction::Mutate);
+
+    assert_eq!(
+        parse("kubectl get pods --namespace=staging")
+            .scope
+            .as_deref(),
+        Some("staging")
+    );
+    assert_eq!(parse("kubectl get pods").scope.as_deref(), Some("default"));
+    assert_eq!(parse("kubectl get pods -A").scope, None);
+}
+
+#[test]
+fn classifies_aws_gcloud_and_az() {
+    let describe = parse("aws --profile prod ec2 describe-instances --region us-east-1");
+    assert_eq!(describe.action, CloudAction::Read);
+    assert_eq!(describe.verb, "ec2 describe-instances");
+    assert_eq!(describe.scope.as_deref(), Some("prod"));
+    assert_eq!(action("aws s3 ls s3://bucket"), CloudAction::Read);
+    assert_eq!(action("aws sts get-caller-identity"), CloudAction::Read);
+    assert_eq!(action("aws s3 cp a.txt s3://bucket/"), CloudAction::Mutate);
+    assert_eq!(action("aws s3 rm s3://bucket/a.txt"), CloudAction::Destroy);
+    assert_eq!(
+        action("aws s3 sync . s3://bucket --delete"),
+        CloudAction::Destroy
+    );
+    assert_eq!(
+        action("aws ec2 terminate-instances --instance-ids i-1"),
+        CloudAction::Destroy
+    );
+    assert_eq!(action("aws configure"), CloudAction::Mutate);
+
+    let list = parse("gcloud --project=prod-data compute instances list");
+    assert_eq!(list.action, CloudAction::Read);
+    assert_eq!(list.verb, "compute instances list");
+    assert_eq!(list.scope.as_deref(), Some("prod-data"));
+    assert_eq!(
+        action("gcloud compute instances delete vm-1 --zone us-east1-b"),
+        CloudAction::Destroy
+    );
+    assert_eq!(
+        action("gcloud container clusters get-credentials prod"),
+        CloudAction::Read
+    );
+    assert_eq!(
+        action("gcloud config set project prod"),
+        CloudAction::Mutate
+    );
+
+    let az = parse("az vm delete -g rg -n vm1 --subscription Prod");
+    assert_eq!(az.action, CloudAction::Destroy);
+    assert_eq!(az.scope.as_deref(), Some("Prod"));
+    assert_eq!(action("az group list -o table"), CloudAction::Read);
+    assert_eq!(
+        action("az storage account create -n acct -g rg"),
+        CloudAction::Mutate
+    );
+
+    assert!(CloudInvocation::parse("awk '{print $1}' file").is_none());
+}
+
+#[test]
+fn scope_comes_from_environment_assignments() {
+    let invocations = cloud_invocations(
+        "export AWS_PROFILE=prod && aws s3 ls && CLOUDSDK_CORE_PROJECT=dev gcloud run services list",
+    );
+    let scopes: Vec<_> = invocations.iter().map(|i| i.scope.as_deref()).collect();
+    assert_eq!(scopes, vec![Some("prod"), Some("dev")]);
+    assert_eq!(
+        cloud_invocations("AWS_PROFILE=prod aws --profile dev s3 ls")[0]
+            .scope
+            .as_deref(),
+        Some("dev"),
+        "--profile wins over the environment"
+    );
+}
+
+#[test]
+fn first_rule_setting_the_action_wins() {
+    let policy = rules();
+    assert_eq!(
+        decide(&policy, "reviewer", "kubectl -n dev get pods"),
+        Some(Decision::Allow)
+    );
+    assert_eq!(
+        decide(&policy, "researcher", "kubectl -n dev apply -f x.yaml"),
+        Some(Decision::Deny)
+    );
+    assert_eq!(
+        decide(&policy, "coder", "kubectl -n prod-eu apply -f x.yaml"),
+        Some(Decision::Ask)
+    );
+    assert_eq!(
+        decide(
+            &policy,
+            "coder",
+            "kubectl delete pod web-1 --all-namespaces"
+        ),
+        Some(Decision::Deny),
+        "all namespaces includes prod"
+    );
+    // No rule sets mutate outside prod, so it falls through.
+    assert_eq!(
+        decide(&policy, "coder", "kubectl -n dev apply -f x.yaml"),
+        None
+    );
+    assert_eq!(decide(&policy, "coder", "aws s3 ls"), Some(Decision::Allow));
+
+    let (_, reasons) = policy
+        .check("coder", "kubectl -n prod delete pod web-1")
+        .unwrap();
+    assert_eq!(
+        reasons,
+        vec!["`kubectl delete` is a destroy action in namespace 'prod'; cloud[1] says deny"]
+    );
+}
+
+#[test]
+fn allow_needs_every_command_to_be_an_allowed_cloud_read() {
+    let policy = rules();
+    assert_eq!(
+        decide(&policy, "coder", "kubectl get pods | grep web"),
+        None
+    );
+    assert_eq!(
+        decide(
+            &policy,
+            "coder",
+            "kubectl get pods && kubectl -n dev apply -f x"
+        ),
+        None
+    );
+    assert_eq!(
+        decide(&policy, "coder", "kubectl get pods && aws s3 ls"),
+        Some(Decision::Allow)
+    );
+    assert_eq!(
+        decide(&policy, "coder", "ls && kubectl -n prod delete ns prod"),
+        Some(Decision::Deny)
+    );
+    assert_eq!(decide(&policy, "coder", "cargo build"), None);
+}
+
+#[test]
+fn empty_rules_compile_to_none() {
+    assert!(CloudPolicy::compile(&[]).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn tier_uses_the_session_role() {
+    let policy = rules();
+    let session = SessionContext {
+        role: Some(common::role("reviewer", PathPolicyConfig::default())),
+        ..common::session()
+    };
+    let input = CascadeInput {
+        sanitized_input: "kubectl -n dev scale deploy/web --replicas 0".into(),
+        ..common::input(
+            session,
+            "Bash",
+            serde_json::json!({ "command": "kubectl -n dev scale deploy/web --replicas 0" }),
+        )
+    };
+    let record = policy.evaluate(&input).await.unwrap().unwrap();
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(record.metadata.tier, DecisionTier::CloudCli);
+    assert_eq!(record.key.role, "reviewer");
+}
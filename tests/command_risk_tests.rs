@@ -0,0 +1,215 @@
+//! Tests for the built-in command risk score: verb categories, destructive
+//! flags, sensitive paths and egress, and how the score feeds the queue.
+
+mod common;
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use tempfile::TempDir;
+
+use hookwise::cascade::command_risk::{summarize_command_risk, CommandRiskScorer, VerbCategory};
+use hookwise::cascade::human::{
+    sort_pending, DecisionQueue, HumanTier, PendingDecision, Priority, QueueSort,
+};
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::config::roles::{CompiledPathPolicy, PathPolicyConfig};
+use hookwise::config::{CommandRiskConfig, EscalationConfig, EscalationStage, PolicyConfig};
+use hookwise::decision::{Decision, DecisionTier};
+use hookwise::error::HookwiseError;
+
+fn scorer() -> CommandRiskScorer {
+    CommandRiskScorer::new(&CommandRiskConfig::default()).unwrap()
+}
+
+fn call(tool: &str, tool_input: serde_json::Value) -> CascadeInput {
+    common::input(common::session(), tool, tool_input)
+}
+
+fn bash(command: &str) -> CascadeInput {
+    call("Bash", serde_json::json!({ "command": command }))
+}
+
+#[test]
+fn verb_categories_take_the_riskiest_part_of_a_command() {
+    let scorer = scorer();
+    let category = |command: &str| scorer.score(&bash(command)).category;
+    assert_eq!(category("ls -la && git status"), VerbCategory::Read);
+    assert_eq!(category("cargo test"), VerbCategory::Execute);
+    assert_eq!(category("echo hi > notes.txt"), VerbCategory::Write);
+    assert_eq!(category("echo hi 2>/dev/null"), VerbCategory::Read);
+    assert_eq!(category("git commit -m wip"), VerbCategory::Write);
+    assert_eq!(category("make && rm build.log"), VerbCategory::Delete);
+    assert_eq!(category("find . -name '*.o' -delete"), VerbCategory::Delete);
+    assert_eq!(category("git clean -fdx"), VerbCategory::Delete);
+    assert_eq!(category("psql -c 'DROP TABLE users'"), VerbCategory::Delete);
+    assert_eq!(category("sudo apt-get install jq"), VerbCategory::Privilege);
+    assert_eq!(category("chmod 777 run.sh"), VerbCategory::Privilege);
+
+    let input = serde_json::json!({ "file_path": "src/lib.rs" });
+    assert_eq!(
+        scorer.score(&call("Write", input.clone())).category,
+        VerbCategory::Write
+    );
+    assert_eq!(
+        scorer.score(&call("Read", input)).category,
+        VerbCategory::Read
+    );
+}
+
+#[test]
+fn signals_add_their_weights_once() {
+    let scorer = scorer();
+
+    let risk = scorer.score(&bash("ls"));
+    assert_eq!(risk.score, 0.0);
+    assert_eq!(risk.factors, vec!["read (ls)"]);
+
+    // delete 3 + destructive flag 2
+    let risk = scorer.score(&bash("rm -rf build && rm -rf dist"));
+    assert_eq!(risk.score, 5.0);
+    assert_eq!(risk.factors, vec!["delete (rm)", "destructive flag '-rf'"]);
+
+    // sudo unwraps: privilege 3 + destructive flag 2
+    let risk = scorer.score(&bash("sudo rm -rf /var/cache/app"));
+    assert_eq!(risk.score, 5.0);
+
+    // write 1 + destructive flag 2 + network 2
+    let risk = scorer.score(&bash("git push --force origin main"));
+    assert_eq!(risk.score, 5.0);
+    assert!(risk.factors.contains(&"network (git push)".to_string()));
+
+    // execute 1 + network 2; `tail -f` isn't a forced deletion
+    assert_eq!(scorer.score(&bash("curl https://example.com")).score, 3.0);
+    assert_eq!(scorer.score(&bash("tail -f app.log")).score, 0.0);
+    assert_eq!(
+        scorer
+            .score(&call("WebFetch", serde_json::json!({ "url": "https://x" })))
+            .score,
+        2.0
+    );
+}
+
+#[test]
+fn sensitive_targets_and_custom_weights_count() {
+    let paths = PathPolicyConfig {
+        allow_write: vec!["**".into()],
+        deny_write: Vec::new(),
+        allow_read: vec!["**".into()],
+    };
+    let mut input = call("Write", serde_json::json!({ "file_path": ".env" }));
+    input.session.path_policy = Some(Arc::new(
+        CompiledPathPolicy::compile(&paths, &[".env".to_string()]).unwrap(),
+    ));
+    let risk = scorer().score(&input);
+    assert_eq!(risk.score, 4.0);
+    assert_eq!(risk.factors[1], "sensitive path '.env'");
+
+    let config: CommandRiskConfig =
+        serde_yaml::from_str("weights: { write: 5, sensitive_path: 0 }\nhigh: 5").unwrap();
+    let custom = CommandRiskScorer::new(&config).unwrap();
+    assert_eq!(custom.score(&input).score, 5.0);
+    assert_eq!(custom.priority(5.0), Some(Priority::High));
+    assert_eq!(custom.priority(4.9), None);
+    assert_eq!(custom.priority(7.0), Some(Priority::Critical));
+    assert_eq!(
+        PolicyConfig::default().command_risk,
+        CommandRiskConfig::default()
+    );
+}
+
+fn pending(id: &str, priority: Priority, risk_score: Option<f64>) -> PendingDecision {
+    PendingDecision {
+        id: id.into(),
+        session_id: "session-1".into(),
+        role: "coder".into(),
+        tool_name: "Bash".into(),
+        sanitized_input: id.into(),
+        file_path: None,
+        recommendation: None,
+        is_ask_reprompt: false,
+        ask_reason: None,
+        queued_at: Utc::now(),
+        priority,
+        waiter_pid: None,
+        heartbeat_at: None,
+        attachment: None,
+        suggestion: None,
+        risk_score,
+        project_root: None,
+    }
+}
+
+#[test]
+fn equal_priorities_list_the_riskiest_first() {
+    let mut queue = vec![
+        pending("a", Priority::High, Some(4.0)),
+        pending("b", Priority::High, Some(6.0)),
+        pending("c", Priority::Critical, Some(1.0)),
+        pending("d", Priority::High, None),
+    ];
+    sort_pending(&mut queue, QueueSort::Priority);
+    let ids: Vec<_> = queue.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(ids, vec!["c", "b", "a", "d"]);
+}
+
+#[tokio::test]
+async fn escalation_stages_below_min_risk_are_skipped() {
+    let runtime_dir = TempDir::new().unwrap();
+    std::env::set_var("XDG_RUNTIME_DIR", runtime_dir.path());
+    let stage = |name: &str, min_risk: Option<f64>| EscalationStage {
+        name: name.into(),
+        timeout_secs: 1,
+        channels: Vec::new(),
+        min_risk,
+    };
+    let config = EscalationConfig {
+        stages: vec![stage("primary", None), stage("security", Some(5.0))],
+    };
+    let tier = HumanTier::new(Arc::new(DecisionQueue::new()), 600)
+        .with_escalation(&config)
+        .with_command_risk(Some(Arc::new(scorer())));
+
+    // Only the primary stage: the wait is its timeout alone
+    let result = tier.evaluate(&bash("cargo build")).await;
+    assert!(
+        matches!(result, Err(HookwiseError::HumanTimeout { timeout_secs: 1 })),
+        "{result:?}"
+    );
+    let result = tier.evaluate(&bash("rm -rf target")).await;
+    assert!(
+        matches!(result, Err(HookwiseError::HumanTimeout { timeout_secs: 2 })),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn runner_records_the_score_on_decisions() {
+    let tmp = TempDir::new().unwrap();
+    let runner = CascadeRunner {
+        command_risk: Some(Arc::new(scorer())),
+        ..common::runner(
+            tmp.path(),
+            Box::new(common::Pass(DecisionTier::Supervisor)),
+            Box::new(common::Pass(DecisionTier::Human)),
+        )
+    };
+    let session = common::session();
+    let record = runner
+        .evaluate(
+            &session,
+            "Bash",
+            &serde_json::json!({ "command": "rm -rf dist" }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(record.metadata.risk_score, Some(5.0));
+
+    let summary =
+        summarize_command_risk(&[record.clone(), record], &CommandRiskConfig::default(), 5);
+    assert_eq!(summary.scored, 2);
+    assert_eq!(summary.mean, Some(5.0));
+    assert_eq!(summary.by_band.get(&Priority::High), Some(&2));
+    assert_eq!(summary.top.len(), 1);
+}
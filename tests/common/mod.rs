@@ -0,0 +1,175 @@
+//! Fixtures shared by the integration tests. A test file pulls them in with
+//! `mod common;` and overrides what it needs with struct update syntax, so a
+//! new field on these types only changes this file.
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use hookwise::cascade::cache::ExactCache;
+use hookwise::cascade::embed_sim::EmbeddingSimilarity;
+use hookwise::cascade::path_policy::PathPolicyEngine;
+use hookwise::cascade::token_sim::TokenJaccard;
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::config::roles::{CompiledPathPolicy, PathPolicyConfig, RoleDefinition};
+use hookwise::config::PolicyConfig;
+use hookwise::decision::{
+    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+};
+use hookwise::session::SessionContext;
+use hookwise::storage::jsonl::JsonlStorage;
+
+/// An unregistered session: no role and no path policy.
+pub fn session() -> SessionContext {
+    SessionContext::new_minimal("test-user".into(), "test-org".into(), "test-project".into())
+}
+
+/// A role named `name` with `paths`.
+pub fn role(name: &str, paths: PathPolicyConfig) -> RoleDefinition {
+    RoleDefinition {
+        name: name.into(),
+        description: "test role".into(),
+        paths,
+        rule_scope: None,
+    }
+}
+
+/// A session registered as `role` with its path policy compiled against
+/// `sensitive`.
+pub fn session_as(
+    role_name: &str,
+    paths: PathPolicyConfig,
+    sensitive: &[String],
+) -> SessionContext {
+    let compiled = CompiledPathPolicy::compile(&paths, sensitive).unwrap();
+    SessionContext {
+        role: Some(role(role_name, paths)),
+        path_policy: Some(Arc::new(compiled)),
+        registered_at: Some(Utc::now()),
+        ..session()
+    }
+}
+
+/// A call as the cascade tiers see it, cached under the raw tool input.
+pub fn input(
+    session: SessionContext,
+    tool_name: &str,
+    tool_input: serde_json::Value,
+) -> CascadeInput {
+    CascadeInput {
+        session,
+        tool_name: tool_name.into(),
+        sanitized_input: tool_input.to_string(),
+        file_path: CascadeRunner::extract_file_path(tool_name, &tool_input),
+        tool_input,
+        cwd: None,
+        recommendation: None,
+    }
+}
+
+/// A Bash call known only by its cache key, for the similarity tiers.
+pub fn keyed(sanitized_input: &str) -> CascadeInput {
+    CascadeInput {
+        sanitized_input: sanitized_input.into(),
+        ..input(session(), "Bash", serde_json::Value::Null)
+    }
+}
+
+/// Metadata for a decision by `tier` at full confidence, with nothing else
+/// recorded.
+pub fn metadata(tier: DecisionTier, reason: &str) -> DecisionMetadata {
+    DecisionMetadata {
+        tier,
+        confidence: 1.0,
+        reason: reason.into(),
+        matched_key: None,
+        similarity_score: None,
+        targets: Vec::new(),
+        message: None,
+        suggestion: None,
+        risk_score: None,
+        secrets: Vec::new(),
+        client: None,
+    }
+}
+
+/// A human `decision` on the Bash call `sanitized_input` by `role`, made
+/// now at project scope.
+pub fn record(sanitized_input: &str, role: &str, decision: Decision) -> DecisionRecord {
+    DecisionRecord {
+        key: CacheKey {
+            sanitized_input: sanitized_input.into(),
+            tool: "Bash".into(),
+            role: role.into(),
+        },
+        decision,
+        metadata: metadata(DecisionTier::Human, "test"),
+        timestamp: Utc::now(),
+        scope: ScopeLevel::Project,
+        file_path: None,
+        session_id: "test-session".into(),
+    }
+}
+
+/// A tier that never decides.
+pub struct Pass(pub DecisionTier);
+
+#[async_trait]
+impl CascadeTier for Pass {
+    async fn evaluate(
+        &self,
+        _input: &CascadeInput,
+    ) -> hookwise::error::Result<Option<DecisionRecord>> {
+        Ok(None)
+    }
+    fn tier(&self) -> DecisionTier {
+        self.0
+    }
+    fn name(&self) -> &str {
+        "pass"
+    }
+}
+
+/// A runner with only the always-on tiers (path policy, exact cache, token
+/// similarity, a no-op embedding tier) and rule storage under `dir`. Every
+/// optional tier is off.
+pub fn runner(
+    dir: &Path,
+    supervisor: Box<dyn CascadeTier>,
+    human: Box<dyn CascadeTier>,
+) -> CascadeRunner {
+    CascadeRunner {
+        sanitizer: hookwise::sanitize::SanitizePipeline::default_pipeline(),
+        ephemeral: None,
+        content_inspection: None,
+        exfiltration: None,
+        locks: None,
+        path_policy: Box::new(PathPolicyEngine::new().unwrap()),
+        package_gate: None,
+        cloud_cli: None,
+        git_policy: None,
+        exact_cache: Arc::new(ExactCache::new()),
+        commit_keys: None,
+        token_jaccard: Arc::new(TokenJaccard::new(0.7, 3)),
+        embedding_similarity: Arc::new(EmbeddingSimilarity::new_noop()),
+        supervisor,
+        human,
+        storage: Box::new(JsonlStorage::new(
+            dir.to_path_buf(),
+            dir.join("global"),
+            None,
+        )),
+        policy: PolicyConfig::default(),
+        normalizer: None,
+        tier_caps: None,
+        risk: None,
+        messages: None,
+        hints: None,
+        command_risk: None,
+        clock: None,
+        client: None,
+    }
+}
@@ -0,0 +1,136 @@
+//! Tests for `hookwise compact`: one record per cache key, the record scope
+//! resolution would use, the bytes reclaimed, archives and dry runs.
+
+mod common;
+
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::StorageBackend;
+
+fn record(command: &str, decision: Decision, age_days: i64, reason: &str) -> DecisionRecord {
+    DecisionRecord {
+        metadata: common::metadata(DecisionTier::Human, reason),
+        timestamp: Utc::now() - Duration::days(age_days),
+        ..common::record(command, "coder", decision)
+    }
+}
+
+fn storage(root: &std::path::Path) -> JsonlStorage {
+    JsonlStorage::new(root.join(".hookwise"), root.join("global"), None)
+}
+
+#[test]
+fn duplicates_collapse_to_the_latest_record() {
+    let tmp = TempDir::new().unwrap();
+    let storage = storage(tmp.path());
+    for age in [30, 20, 10] {
+        storage
+            .save_decision(&record(
+                "cargo test",
+                Decision::Allow,
+                age,
+                &format!("{age}d"),
+            ))
+            .unwrap();
+    }
+    storage
+        .save_decision(&record("cargo build", Decision::Allow, 5, "other"))
+        .unwrap();
+
+    let report = storage
+        .deduplicate(ScopeLevel::Project, false, false)
+        .unwrap();
+    assert_eq!(report.records_before, 4);
+    assert_eq!(report.records_removed, 2);
+    assert!(report.bytes_reclaimed() > 0);
+    let size = std::fs::metadata(tmp.path().join(".hookwise/rules/allow.jsonl"))
+        .unwrap()
+        .len();
+    assert_eq!(report.bytes_after, size);
+
+    let records = storage.load_decisions(ScopeLevel::Project).unwrap();
+    assert_eq!(records.len(), 2);
+    let test = records
+        .iter()
+        .find(|r| r.key.sanitized_input == "cargo test")
+        .unwrap();
+    assert_eq!(test.metadata.reason, "10d");
+
+    // Already compact
+    let again = storage
+        .deduplicate(ScopeLevel::Project, false, false)
+        .unwrap();
+    assert_eq!(again.records_removed, 0);
+    assert_eq!(again.bytes_reclaimed(), 0);
+}
+
+#[test]
+fn the_record_resolution_uses_survives_conflicts() {
+    let tmp = TempDir::new().unwrap();
+    let storage = storage(tmp.path());
+    // A newer allow doesn't outrank a deny, so compaction keeps the deny
+    storage
+        .save_decision(&record("rm -rf build", Decision::Deny, 10, "deny"))
+        .unwrap();
+    storage
+        .save_decision(&record("rm -rf build", Decision::Allow, 1, "allow"))
+        .unwrap();
+    // A role-scoped record in the shared project files yields to the
+    // project-scoped one
+    let mut role = record("make", Decision::Ask, 1, "role");
+    role.scope = ScopeLevel::Role;
+    storage.save_decision(&role).unwrap();
+    storage
+        .save_decision(&record("make", Decision::Ask, 10, "project"))
+        .unwrap();
+
+    let report = storage
+        .deduplicate(ScopeLevel::Project, false, false)
+        .unwrap();
+    assert_eq!(report.records_removed, 2);
+    let records = storage.load_decisions(ScopeLevel::Project).unwrap();
+    let reasons: Vec<_> = records.iter().map(|r| r.metadata.reason.as_str()).collect();
+    assert_eq!(reasons.len(), 2);
+    assert!(reasons.contains(&"deny"), "{reasons:?}");
+    assert!(reasons.contains(&"project"), "{reasons:?}");
+    assert!(!tmp
+        .path()
+        .join(".hookwise/rules/allow.jsonl")
+        .metadata()
+        .is_ok_and(|m| m.len() > 0));
+}
+
+#[test]
+fn dry_runs_report_without_writing_and_archives_keep_removed_records() {
+    let tmp = TempDir::new().unwrap();
+    let storage = storage(tmp.path());
+    for age in [3, 2, 1] {
+        storage
+            .save_decision(&record("ls", Decision::Allow, age, &format!("{age}d")))
+            .unwrap();
+    }
+    let rules = tmp.path().join(".hookwise/rules/allow.jsonl");
+    let before = std::fs::read_to_string(&rules).unwrap();
+
+    let dry = storage
+        .deduplicate(ScopeLevel::Project, true, true)
+        .unwrap();
+    assert_eq!(dry.records_removed, 2);
+    assert_eq!(std::fs::read_to_string(&rules).unwrap(), before);
+    assert!(!dry.archived.as_ref().unwrap().exists());
+
+    let report = storage
+        .deduplicate(ScopeLevel::Project, true, false)
+        .unwrap();
+    assert_eq!(report.records_removed, dry.records_removed);
+    assert_eq!(report.bytes_after, dry.bytes_after);
+    let archived = report.archived.unwrap();
+    assert!(archived.starts_with(tmp.path().join(".hookwise/.user/archive")));
+    let archived = std::fs::read_to_string(archived).unwrap();
+    assert_eq!(archived.lines().count(), 2);
+    assert!(archived.contains("\"3d\"") && archived.contains("\"2d\""));
+    assert_eq!(std::fs::read_to_string(&rules).unwrap().lines().count(), 1);
+}
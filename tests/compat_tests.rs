@@ -0,0 +1,193 @@
+//! Tests for rule files shared between hookwise versions: fields and
+//! records this version doesn't know survive every rewrite, and
+//! `JsonlStorage::upgrade` (or `with_auto_upgrade` on load) rewrites only
+//! older records.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier, ScopeLevel, SCHEMA_VERSION};
+use hookwise::storage::bundle::MergePlan;
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::StorageBackend;
+
+fn line(command: &str, role: &str) -> Value {
+    json!({
+        "key": { "sanitized_input": command, "tool": "Bash", "role": role },
+        "decision": "allow",
+        "metadata": {
+            "tier": "Human",
+            "confidence": 1.0,
+            "reason": "approved",
+            "matched_key": null,
+            "similarity_score": null,
+        },
+        "timestamp": "2026-01-01T00:00:00Z",
+        "scope": "project",
+        "file_path": null,
+        "session_id": "compat",
+    })
+}
+
+/// A record from a newer hookwise: a later schema with a field this
+/// version has never heard of.
+fn newer_line(command: &str) -> Value {
+    let mut value = line(command, "coder");
+    value["schema_version"] = json!(SCHEMA_VERSION + 1);
+    value["reviewed_by"] = json!("alice");
+    value
+}
+
+fn record(command: &str, role: &str) -> DecisionRecord {
+    DecisionRecord {
+        metadata: common::metadata(DecisionTier::Human, "approved"),
+        ..common::record(command, role, Decision::Allow)
+    }
+}
+
+fn setup(lines: &[Value]) -> (TempDir, JsonlStorage, PathBuf) {
+    let tmp = TempDir::new().unwrap();
+    let rules = tmp.path().join(".hookwise/rules");
+    std::fs::create_dir_all(&rules).unwrap();
+    let path = rules.join("allow.jsonl");
+    let contents: String = lines.iter().map(|l| format!("{l}\n")).collect();
+    std::fs::write(&path, contents).unwrap();
+    let storage = JsonlStorage::new(
+        tmp.path().join(".hookwise"),
+        tmp.path().join("global"),
+        None,
+    );
+    (tmp, storage, path)
+}
+
+fn read_lines(path: &Path) -> Vec<Value> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn unknown_fields_are_ignored_on_load_and_kept_on_rewrite() {
+    let mut extended = line("cargo test", "coder");
+    extended["reviewed_by"] = json!("alice");
+    extended["metadata"]["model"] = json!("local");
+    let (_tmp, storage, path) = setup(&[extended.clone(), line("ls", "reviewer")]);
+
+    let loaded = storage.load_decisions(ScopeLevel::Project).unwrap();
+    assert_eq!(loaded.len(), 2);
+
+    storage
+        .invalidate_role(ScopeLevel::Project, "reviewer")
+        .unwrap();
+    assert_eq!(read_lines(&path), [extended]);
+}
+
+#[test]
+fn unreadable_records_are_skipped_on_load() {
+    let mut unknown_tier = line("make", "coder");
+    unknown_tier["metadata"]["tier"] = json!("QuantumOracle");
+    let (_tmp, storage, _path) = setup(&[
+        newer_line("git push"),
+        unknown_tier,
+        line("cargo test", "coder"),
+    ]);
+
+    let loaded = storage.load_decisions(ScopeLevel::Project).unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].key.sanitized_input, "cargo test");
+}
+
+#[test]
+fn rewrites_carry_unreadable_records_over() {
+    let newer = newer_line("git push");
+    let old = line("cargo test", "coder");
+    let (_tmp, storage, path) = setup(&[newer.clone(), old.clone(), old.clone()]);
+
+    let report = storage
+        .deduplicate(ScopeLevel::Project, false, false)
+        .unwrap();
+    assert_eq!(report.records_removed, 1);
+    assert!(read_lines(&path).contains(&newer));
+
+    let cutoff = Utc::now() + Duration::days(1);
+    storage
+        .prune_older_than(ScopeLevel::Project, cutoff, false, false)
+        .unwrap();
+    assert_eq!(read_lines(&path), [newer.clone()]);
+
+    storage.save_decision(&record("ls", "coder")).unwrap();
+    storage
+        .remove_decisions(ScopeLevel::Project, &|r| r.key.sanitized_input == "ls")
+        .unwrap();
+    assert_eq!(read_lines(&path), [newer.clone()]);
+
+    let plan = MergePlan {
+        add: vec![record("cargo fmt", "coder")],
+        ..MergePlan::default()
+    };
+    storage.merge(ScopeLevel::Project, &plan).unwrap();
+    let lines = read_lines(&path);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], newer);
+}
+
+#[test]
+fn upgrade_rewrites_only_older_records() {
+    let newer = newer_line("git push");
+    let (_tmp, storage, path) = setup(&[line("cargo test", "coder"), newer.clone()]);
+    storage.save_decision(&record("ls", "coder")).unwrap();
+    let before = std::fs::read_to_string(&path).unwrap();
+
+    let report = storage.upgrade(ScopeLevel::Project, true).unwrap();
+    assert_eq!(
+        (report.upgraded, report.current, report.unreadable),
+        (1, 1, 1)
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), before);
+
+    storage.upgrade(ScopeLevel::Project, false).unwrap();
+    let lines = read_lines(&path);
+    assert_eq!(lines.len(), 3);
+    assert!(lines
+        .iter()
+        .filter(|l| **l != newer)
+        .all(|l| l["schema_version"] == SCHEMA_VERSION));
+    assert!(lines.contains(&newer));
+
+    let again = storage.upgrade(ScopeLevel::Project, false).unwrap();
+    assert_eq!(again.upgraded, 0);
+}
+
+#[test]
+fn auto_upgrade_rewrites_older_records_on_load() {
+    let newer = newer_line("git push");
+    let (_tmp, storage, path) = setup(&[line("cargo test", "coder"), newer.clone()]);
+    let before = std::fs::read_to_string(&path).unwrap();
+
+    // Off by default: loading leaves the file alone
+    assert_eq!(
+        storage.load_decisions(ScopeLevel::Project).unwrap().len(),
+        1
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), before);
+
+    let storage = storage.with_auto_upgrade(true);
+    let records = storage
+        .load_decisions_for_role(ScopeLevel::Role, "coder")
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    let lines = read_lines(&path);
+    assert_eq!(lines[0]["schema_version"], SCHEMA_VERSION);
+    assert!(lines.contains(&newer));
+    assert_eq!(
+        storage.upgrade(ScopeLevel::Project, true).unwrap().upgraded,
+        0
+    );
+}
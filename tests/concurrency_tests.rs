@@ -0,0 +1,139 @@
+//! Stress tests for concurrent writers: rule appends and rewrites in the
+//! JSONL storage, and read-modify-write cycles on the pending queue file,
+//! from several processes at once.
+
+mod common;
+
+use std::process::{Command, Stdio};
+
+use chrono::Utc;
+use tempfile::TempDir;
+
+use hookwise::cascade::human::{load_queue_file, DecisionQueue, HumanResponse, PendingDecision};
+use hookwise::decision::{Decision, DecisionRecord, ScopeLevel};
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::StorageBackend;
+
+const WRITER_ENV: &str = "HOOKWISE_STRESS_WRITER";
+const WRITERS: usize = 6;
+const PER_WRITER: usize = 30;
+
+fn record(writer: &str, n: usize) -> DecisionRecord {
+    let command = format!("cargo test --writer {writer} --n {n} {}", "x".repeat(512));
+    DecisionRecord {
+        session_id: writer.into(),
+        ..common::record(&command, "coder", Decision::Allow)
+    }
+}
+
+fn pending(id: String) -> PendingDecision {
+    PendingDecision {
+        id: id.clone(),
+        session_id: "stress".into(),
+        role: "coder".into(),
+        tool_name: "Bash".into(),
+        sanitized_input: id,
+        file_path: None,
+        recommendation: None,
+        is_ask_reprompt: false,
+        ask_reason: None,
+        queued_at: Utc::now(),
+        priority: Default::default(),
+        waiter_pid: None,
+        heartbeat_at: None,
+        attachment: None,
+        suggestion: None,
+        risk_score: None,
+        project_root: None,
+    }
+}
+
+fn storage(root: &std::path::Path) -> JsonlStorage {
+    JsonlStorage::new(root.join(".hookwise"), root.join("global"), None)
+}
+
+/// Body of one writer process; a no-op unless spawned by the stress test.
+#[test]
+fn concurrent_writer_child() {
+    let Ok(writer) = std::env::var(WRITER_ENV) else {
+        return;
+    };
+    let root = std::path::PathBuf::from(std::env::var("STRESS_ROOT").unwrap());
+    let storage = storage(&root);
+    let queue = DecisionQueue::new();
+    for n in 0..PER_WRITER {
+        storage.save_decision(&record(&writer, n)).unwrap();
+        // A rewrite racing the other writers' appends
+        if n % 5 == 0 {
+            storage
+                .invalidate_role(ScopeLevel::Project, "nobody")
+                .unwrap();
+        }
+
+        let id = format!("{writer}-{n}");
+        queue.enqueue(pending(id.clone()));
+        if n % 2 == 0 {
+            queue
+                .respond(
+                    &id,
+                    HumanResponse {
+                        decision: Decision::Allow,
+                        always_ask: false,
+                        add_rule: false,
+                        rule_scope: None,
+                        batch: None,
+                        responded_by: None,
+                    },
+                )
+                .unwrap();
+        }
+    }
+}
+
+#[test]
+fn concurrent_writers_lose_no_rules_or_queue_entries() {
+    let tmp = TempDir::new().unwrap();
+    let exe = std::env::current_exe().unwrap();
+    let children: Vec<_> = (0..WRITERS)
+        .map(|writer| {
+            Command::new(&exe)
+                .args(["--exact", "concurrent_writer_child", "--test-threads", "1"])
+                .env(WRITER_ENV, format!("w{writer}"))
+                .env("STRESS_ROOT", tmp.path())
+                .env("XDG_RUNTIME_DIR", tmp.path())
+                .env_remove("CLAUDE_TEAM_ID")
+                .stdout(Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    let records = storage(tmp.path())
+        .load_decisions(ScopeLevel::Project)
+        .unwrap();
+    assert_eq!(records.len(), WRITERS * PER_WRITER);
+    // Every line parsed: nothing was interleaved or torn
+    let rules = std::fs::read_to_string(tmp.path().join(".hookwise/rules/allow.jsonl")).unwrap();
+    assert_eq!(rules.lines().count(), WRITERS * PER_WRITER);
+    // The lock stays out of the checked-in rules directory
+    assert!(tmp.path().join(".hookwise/.user/rules.lock").exists());
+    assert_eq!(
+        std::fs::read_dir(tmp.path().join(".hookwise/rules"))
+            .unwrap()
+            .count(),
+        1
+    );
+
+    std::env::set_var("XDG_RUNTIME_DIR", tmp.path());
+    std::env::remove_var("CLAUDE_TEAM_ID");
+    let state = load_queue_file();
+    assert_eq!(state.pending.len(), WRITERS * PER_WRITER / 2);
+    assert_eq!(state.responses.len(), WRITERS * PER_WRITER / 2);
+    assert!(state
+        .pending
+        .keys()
+        .all(|id| !state.responses.contains_key(id)));
+}
@@ -0,0 +1,147 @@
+//! Tests for building PolicyConfig and RolesConfig in code and writing them
+//! back out with `to_yaml`.
+
+use hookwise::config::{
+    AutoTier, CacheMaxAge, MessageRule, Persistence, PolicyConfig, RoleDefinition, RolesConfig,
+    StorageConfig, SupervisorConfig,
+};
+use hookwise::decision::ScopeLevel;
+
+fn roles() -> RolesConfig {
+    RolesConfig::default()
+        .with_category("fixtures", ["testdata/**"])
+        .with_role(
+            RoleDefinition::new("tester", "Writes tests")
+                .with_allow_write(["{{tests}}", "{{fixtures}}"])
+                .with_deny_write(["{{source}}"])
+                .with_allow_read(["**"]),
+        )
+        .with_role(
+            RoleDefinition::new("maintainer", "Full access")
+                .with_allow_write(["**"])
+                .with_rule_scope(ScopeLevel::Org),
+        )
+}
+
+#[test]
+fn built_roles_keep_macros_in_yaml() {
+    let yaml = roles().to_yaml().unwrap();
+    assert!(yaml.contains("{{tests}}"), "{yaml}");
+    assert!(yaml.contains("{{fixtures}}"), "{yaml}");
+    assert!(!yaml.contains("src/**"), "{yaml}");
+    // Stable output: roles are written in name order.
+    assert!(yaml.find("maintainer:").unwrap() < yaml.find("tester:").unwrap());
+    assert_eq!(yaml, roles().to_yaml().unwrap());
+}
+
+#[test]
+fn built_roles_load_back_expanded() {
+    let built = roles();
+    let loaded = RolesConfig::from_yaml(&built.to_yaml().unwrap()).unwrap();
+    assert_eq!(loaded, built.clone().expanded().unwrap());
+
+    let tester = loaded.get_role("tester").unwrap();
+    assert!(tester
+        .paths
+        .allow_write
+        .contains(&"testdata/**".to_string()));
+    assert!(!tester.paths.allow_write.iter().any(|p| p.contains("{{")));
+    assert_eq!(
+        loaded.get_role("maintainer").unwrap().rule_scope,
+        Some(ScopeLevel::Org)
+    );
+}
+
+#[test]
+fn loaded_roles_round_trip() {
+    let loaded = RolesConfig::from_yaml(&roles().to_yaml().unwrap()).unwrap();
+    let yaml = loaded.to_yaml().unwrap();
+    let reloaded = RolesConfig::from_yaml(&yaml).unwrap();
+    assert_eq!(reloaded, loaded);
+    assert_eq!(reloaded.to_yaml().unwrap(), yaml);
+}
+
+#[test]
+fn role_definition_to_yaml() {
+    let yaml = RoleDefinition::new("docs", "Writes docs")
+        .with_allow_write(["{{docs}}"])
+        .to_yaml()
+        .unwrap();
+    let role: RoleDefinition = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(role.name, "docs");
+    assert_eq!(role.paths.allow_write, ["{{docs}}"]);
+    assert_eq!(role.rule_scope, None);
+}
+
+#[test]
+fn from_yaml_rejects_unknown_categories() {
+    let yaml = RolesConfig::default()
+        .with_role(RoleDefinition::new("x", "x").with_allow_write(["{{nope}}"]))
+        .to_yaml()
+        .unwrap();
+    let err = RolesConfig::from_yaml(&yaml).unwrap_err();
+    assert!(err.to_string().contains("nope"), "{err}");
+}
+
+fn policy() -> PolicyConfig {
+    PolicyConfig::default()
+        .with_sensitive_paths([".env*", "{{ci}}"])
+        .with_max_auto_tier("{{infra}}", AutoTier::ExactCache)
+        .with_message(MessageRule {
+            paths: vec!["{{migrations}}".into()],
+            message: Some("{{path}} needs DBA review".into()),
+            ..Default::default()
+        })
+        .with_supervisor(SupervisorConfig::Api {
+            api_base_url: None,
+            model: Some("small".into()),
+            max_tokens: Some(256),
+        })
+        .with_storage(StorageConfig {
+            retention_days: CacheMaxAge {
+                project: Some(90),
+                ..Default::default()
+            },
+            auto_upgrade: false,
+        })
+        .with_persistence(Persistence::Ephemeral)
+        .with_human_timeout_secs(30)
+        .with_rule_scope(ScopeLevel::User)
+}
+
+#[test]
+fn built_policy_round_trips() {
+    let yaml = policy().to_yaml().unwrap();
+    let loaded = PolicyConfig::from_yaml(&yaml).unwrap();
+    assert_eq!(loaded.to_yaml().unwrap(), yaml);
+
+    assert_eq!(loaded.sensitive_paths.ask_write, [".env*", "{{ci}}"]);
+    assert_eq!(
+        loaded.max_auto_tier.get("{{infra}}"),
+        Some(&AutoTier::ExactCache)
+    );
+    assert_eq!(
+        loaded.messages[0].message.as_deref(),
+        Some("{{path}} needs DBA review")
+    );
+    assert!(matches!(
+        loaded.supervisor,
+        SupervisorConfig::Api {
+            max_tokens: Some(256),
+            ..
+        }
+    ));
+    assert_eq!(loaded.storage.retention_days.project, Some(90));
+    assert_eq!(loaded.persistence, Persistence::Ephemeral);
+    assert_eq!(loaded.human_timeout_secs, 30);
+    assert_eq!(loaded.rule_scope, ScopeLevel::User);
+}
+
+#[test]
+fn default_policy_round_trips() {
+    let yaml = PolicyConfig::default().to_yaml().unwrap();
+    assert_eq!(
+        PolicyConfig::from_yaml(&yaml).unwrap().to_yaml().unwrap(),
+        yaml
+    );
+}
@@ -0,0 +1,177 @@
+//! Tests for `docker`/`podman` command policy: privileged runs, host
+//! mounts and unpinned images.
+
+mod common;
+
+use std::sync::Arc;
+
+use hookwise::cascade::container::{image_is_pinned, ContainerCommand, HostMount};
+use hookwise::cascade::path_policy::PathPolicyEngine;
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::config::roles::{CompiledPathPolicy, PathPolicyConfig};
+use hookwise::config::ContainerPolicy;
+use hookwise::decision::{Decision, DecisionRecord};
+use hookwise::session::SessionContext;
+
+fn parse(command: &str) -> ContainerCommand {
+    ContainerCommand::parse(command).unwrap_or_else(|| panic!("not a container command: {command}"))
+}
+
+fn mount(path: &str, read_only: bool) -> HostMount {
+    HostMount {
+        path: path.into(),
+        read_only,
+    }
+}
+
+fn bash(command: &str) -> CascadeInput {
+    let config = PathPolicyConfig {
+        allow_write: vec!["src/**".into(), "data/**".into()],
+        deny_write: vec!["tests/**".into()],
+        allow_read: vec!["**".into()],
+    };
+    let policy = CompiledPathPolicy::compile(&config, &[".env*".into()]).unwrap();
+    let session = SessionContext {
+        path_policy: Some(Arc::new(policy)),
+        ..common::session()
+    };
+    CascadeInput {
+        sanitized_input: command.into(),
+        cwd: Some("/work/project".into()),
+        ..common::input(session, "Bash", serde_json::json!({ "command": command }))
+    }
+}
+
+async fn evaluate_with(policy: ContainerPolicy, command: &str) -> Option<DecisionRecord> {
+    PathPolicyEngine::new()
+        .unwrap()
+        .with_home(Some("/home/dev".into()))
+        .with_containers(policy)
+        .evaluate(&bash(command))
+        .await
+        .unwrap()
+}
+
+async fn decide(command: &str) -> Option<Decision> {
+    evaluate_with(ContainerPolicy::default(), command)
+        .await
+        .map(|r| r.decision)
+}
+
+#[test]
+fn parses_run_flags_mounts_and_image() {
+    let run = parse(
+        "docker run --rm -it --privileged -e A=1 -v ./data:/data -v cache:/cache \
+         --mount type=bind,source=/etc,target=/host-etc,readonly -w /app node:20 npm test",
+    );
+    assert_eq!(run.engine, "docker");
+    assert!(run.privileged);
+    assert_eq!(
+        run.mounts,
+        vec![mount("./data", false), mount("/etc", true)]
+    );
+    assert_eq!(run.image.as_deref(), Some("node:20"));
+
+    let podman = parse("podman container create --volume=$HOME/.ssh:/root/.ssh:ro,z alpine");
+    assert_eq!(podman.mounts, vec![mount("$HOME/.ssh", true)]);
+    assert!(!podman.privileged);
+    assert_eq!(podman.image.as_deref(), Some("alpine"));
+
+    assert_eq!(
+        parse("docker cp app:/out/report.txt tests/report.txt")
+            .copy_target
+            .as_deref(),
+        Some("tests/report.txt")
+    );
+    assert_eq!(parse("docker cp src/a.txt app:/a.txt").copy_target, None);
+    assert_eq!(
+        parse("docker ps -a"),
+        ContainerCommand {
+            engine: "docker".into(),
+            ..Default::default()
+        }
+    );
+    assert!(ContainerCommand::parse("dockerd --debug").is_none());
+}
+
+#[test]
+fn pinned_images_have_a_digest_or_a_version_tag() {
+    assert!(image_is_pinned("node:20"));
+    assert!(image_is_pinned("registry.local:5000/team/app:1.4.2"));
+    assert!(image_is_pinned("alpine@sha256:abc123"));
+    assert!(!image_is_pinned("alpine"));
+    assert!(!image_is_pinned("alpine:latest"));
+    assert!(!image_is_pinned("registry.local:5000/team/app"));
+}
+
+#[tokio::test]
+async fn writable_mounts_are_checked_as_write_targets() {
+    assert_eq!(
+        decide("docker run -v ./tests:/t node:20 rm -rf /t").await,
+        Some(Decision::Deny)
+    );
+    assert_eq!(
+        decide("docker run -v $PWD/data:/data node:20").await,
+        Some(Decision::Allow)
+    );
+    assert_eq!(
+        decide("docker cp app:/out/report.txt tests/report.txt").await,
+        Some(Decision::Deny)
+    );
+    // Read-only mounts inside the project write nothing.
+    assert_eq!(decide("docker run -v ./tests:/t:ro node:20").await, None);
+    assert_eq!(decide("docker run -v cache:/cache node:20").await, None);
+}
+
+#[tokio::test]
+async fn privileged_and_outside_mounts_ask() {
+    let privileged = evaluate_with(
+        ContainerPolicy::default(),
+        "docker run --privileged node:20",
+    )
+    .await
+    .unwrap();
+    assert_eq!(privileged.decision, Decision::Ask);
+    assert!(privileged.metadata.reason.contains("--privileged"));
+
+    let outside = evaluate_with(
+        ContainerPolicy::default(),
+        "docker run -v ~/.ssh:/root/.ssh:ro alpine:3",
+    )
+    .await
+    .unwrap();
+    assert_eq!(outside.decision, Decision::Ask);
+    assert_eq!(
+        outside.metadata.reason,
+        "container mounts host path '~/.ssh' outside the project"
+    );
+    assert_eq!(
+        decide("docker run -v /var/run/docker.sock:/var/run/docker.sock alpine:3").await,
+        Some(Decision::Ask)
+    );
+}
+
+#[tokio::test]
+async fn container_decisions_are_configurable() {
+    let strict = ContainerPolicy {
+        privileged: Decision::Deny,
+        host_mounts: Decision::Allow,
+        unpinned_images: Decision::Ask,
+    };
+    let decide_strict = |command: &'static str| async move {
+        evaluate_with(strict, command).await.map(|r| r.decision)
+    };
+    assert_eq!(
+        decide_strict("docker run --privileged node:20").await,
+        Some(Decision::Deny)
+    );
+    assert_eq!(
+        decide_strict("docker run -v /etc:/etc:ro node:20").await,
+        None
+    );
+    assert_eq!(
+        decide_strict("docker run alpine").await,
+        Some(Decision::Ask)
+    );
+    assert_eq!(decide("docker run alpine").await, None);
+}
@@ -0,0 +1,177 @@
+//! Tests for the evaluation deadline, the near-deadline log behind
+//! `hookwise stats`, and the cap on hook output reasons.
+
+mod common;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tempfile::TempDir;
+
+use hookwise::cascade::deadline::{append_event, load_events, summarize, DeadlineEvent};
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::config::{DeadlineConfig, PolicyConfig};
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier};
+use hookwise::hook_io::{cap_reason, hook_reason, MAX_REASON_BYTES};
+
+/// A supervisor that takes `delay` to say nothing.
+struct Slow {
+    delay: Duration,
+}
+
+#[async_trait]
+impl CascadeTier for Slow {
+    async fn evaluate(
+        &self,
+        _input: &CascadeInput,
+    ) -> hookwise::error::Result<Option<DecisionRecord>> {
+        tokio::time::sleep(self.delay).await;
+        Ok(None)
+    }
+    fn tier(&self) -> DecisionTier {
+        DecisionTier::Supervisor
+    }
+    fn name(&self) -> &str {
+        "slow"
+    }
+}
+
+fn runner(tmp: &TempDir, delay: Duration, deadline: DeadlineConfig) -> CascadeRunner {
+    CascadeRunner {
+        policy: PolicyConfig {
+            deadline,
+            ..PolicyConfig::default()
+        },
+        ..common::runner(
+            tmp.path(),
+            Box::new(Slow { delay }),
+            Box::new(common::Pass(DecisionTier::Human)),
+        )
+    }
+}
+
+#[tokio::test]
+async fn a_cascade_past_the_deadline_gets_the_configured_decision() {
+    let tmp = TempDir::new().unwrap();
+    let deadline = DeadlineConfig {
+        secs: Some(1),
+        decision: Decision::Ask,
+        ..DeadlineConfig::default()
+    };
+    let runner = runner(&tmp, Duration::from_secs(30), deadline);
+
+    let started = std::time::Instant::now();
+    let record = runner
+        .evaluate(
+            &common::session(),
+            "Bash",
+            &serde_json::json!({ "command": "cargo build" }),
+        )
+        .await
+        .unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(record.decision, Decision::Ask);
+    assert_eq!(record.metadata.tier, DecisionTier::Deadline);
+    assert!(record.metadata.reason.starts_with("DeadlineExceeded"));
+    assert!(hook_reason(&record).unwrap().contains("1s deadline"));
+
+    // Not a precedent for the next call
+    assert!(runner
+        .storage
+        .load_decisions(hookwise::decision::ScopeLevel::Project)
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn without_a_deadline_the_cascade_runs_to_the_end() {
+    let tmp = TempDir::new().unwrap();
+    let runner = runner(&tmp, Duration::from_millis(1200), DeadlineConfig::default());
+    let record = runner
+        .evaluate(
+            &common::session(),
+            "Bash",
+            &serde_json::json!({ "command": "cargo build" }),
+        )
+        .await
+        .unwrap();
+    assert_ne!(record.metadata.tier, DecisionTier::Deadline);
+}
+
+#[test]
+fn near_deadline_evaluations_are_logged_and_summarized() {
+    let config: DeadlineConfig = serde_yaml::from_str("secs: 10").unwrap();
+    assert_eq!(config.decision, Decision::Deny);
+    assert_eq!(config.near, 0.8);
+    assert_eq!(PolicyConfig::default().deadline.secs, None);
+
+    let record = |tier: DecisionTier| {
+        let mut record: DecisionRecord = serde_json::from_value(serde_json::json!({
+            "key": { "sanitized_input": "x", "tool": "Bash", "role": "coder" },
+            "decision": "deny",
+            "metadata": {
+                "tier": "Supervisor",
+                "confidence": 1.0,
+                "reason": "",
+                "matched_key": null,
+                "similarity_score": null
+            },
+            "timestamp": "2026-01-01T00:00:00Z",
+            "scope": "project",
+            "file_path": null,
+            "session_id": ""
+        }))
+        .unwrap();
+        record.metadata.tier = tier;
+        record
+    };
+    let now = Utc::now();
+    let observe = |tier, ms| {
+        DeadlineEvent::observe(&config, &record(tier), "s1", Duration::from_millis(ms), now)
+    };
+    assert_eq!(observe(DecisionTier::Supervisor, 7_999), None);
+    assert_eq!(
+        DeadlineEvent::observe(
+            &DeadlineConfig::default(),
+            &record(DecisionTier::Supervisor),
+            "s1",
+            Duration::from_secs(60),
+            now,
+        ),
+        None
+    );
+
+    let tmp = TempDir::new().unwrap();
+    let log = tmp.path().join(".user/deadline.jsonl");
+    for event in [
+        observe(DecisionTier::Supervisor, 8_000),
+        observe(DecisionTier::Human, 9_500),
+        observe(DecisionTier::Deadline, 10_002),
+    ] {
+        append_event(&log, &event.unwrap()).unwrap();
+    }
+    let events = load_events(&log);
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0].deadline_ms, 10_000);
+
+    let summary = summarize(&events, now - chrono::Duration::days(7));
+    assert_eq!(summary.near, 2);
+    assert_eq!(summary.exceeded, 1);
+    assert_eq!(summary.near_by_tier.get("Human"), Some(&1));
+    assert_eq!(summary.max_elapsed_ms, Some(10_002));
+    assert_eq!(
+        summarize(&events, now + chrono::Duration::seconds(1)).near,
+        0
+    );
+}
+
+#[test]
+fn long_reasons_are_capped_at_a_char_boundary() {
+    let short = "denied by path policy".to_string();
+    assert_eq!(cap_reason(short.clone()), short);
+
+    let capped = cap_reason("é".repeat(MAX_REASON_BYTES));
+    assert!(capped.len() <= MAX_REASON_BYTES);
+    assert!(capped.ends_with("more bytes truncated)"), "{capped}");
+}
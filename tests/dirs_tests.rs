@@ -0,0 +1,80 @@
+//! Tests for the XDG global directory layout and `migrate-dirs`.
+
+use std::path::{Path, PathBuf};
+
+use hookwise::config::dirs::MIGRATION_MARKER;
+use hookwise::config::GlobalDirs;
+
+fn dirs_in(root: &Path) -> GlobalDirs {
+    GlobalDirs::from_vars(
+        Some(root.join("home").to_str().unwrap()),
+        None,
+        None,
+        None,
+        Some(root.join("run").to_str().unwrap()),
+    )
+}
+
+#[test]
+fn defaults_follow_home_and_xdg_overrides_win() {
+    let dirs = GlobalDirs::from_vars(Some("/home/dev"), None, None, None, None);
+    assert_eq!(dirs.config, PathBuf::from("/home/dev/.config/hookwise"));
+    assert_eq!(dirs.state, PathBuf::from("/home/dev/.local/state/hookwise"));
+    assert_eq!(dirs.cache, PathBuf::from("/home/dev/.cache/hookwise"));
+    assert_eq!(dirs.runtime, PathBuf::from("/tmp"));
+
+    let dirs = GlobalDirs::from_vars(
+        Some("/home/dev"),
+        Some("/xdg/config"),
+        Some("relative/state"),
+        Some(""),
+        Some("/run/user/1000"),
+    );
+    assert_eq!(dirs.config, PathBuf::from("/xdg/config/hookwise"));
+    assert_eq!(dirs.state, PathBuf::from("/home/dev/.local/state/hookwise"));
+    assert_eq!(dirs.cache, PathBuf::from("/home/dev/.cache/hookwise"));
+    assert_eq!(dirs.runtime, PathBuf::from("/run/user/1000"));
+}
+
+#[test]
+fn migrate_moves_legacy_files_and_leaves_symlinks() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dirs = dirs_in(tmp.path());
+    std::fs::create_dir_all(dirs.config.join("user")).unwrap();
+    std::fs::write(dirs.config.join("user/allow.jsonl"), "{}\n").unwrap();
+    std::fs::write(dirs.config.join("update-check.json"), "{}").unwrap();
+    std::fs::write(dirs.config.join("config.yml"), "supervisor: {}").unwrap();
+    assert_eq!(dirs.rules_root(), dirs.config);
+
+    let plan = dirs.plan_migration();
+    assert_eq!(plan.len(), 2);
+
+    let moved = dirs.migrate().unwrap();
+    assert_eq!(moved, plan);
+    assert!(dirs.state.join("user/allow.jsonl").exists());
+    assert!(dirs.cache.join("update-check.json").exists());
+    assert!(dirs.config.join("config.yml").exists());
+    assert!(dirs.config.join(MIGRATION_MARKER).exists());
+
+    // Older binaries still find the files through the symlinks.
+    let link = std::fs::symlink_metadata(dirs.config.join("user")).unwrap();
+    assert!(link.file_type().is_symlink());
+    assert!(dirs.config.join("user/allow.jsonl").exists());
+
+    assert_eq!(dirs.rules_root(), dirs.state);
+    assert!(dirs.plan_migration().is_empty());
+}
+
+#[test]
+fn migrate_refuses_to_overwrite_existing_targets() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dirs = dirs_in(tmp.path());
+    std::fs::create_dir_all(dirs.config.join("org")).unwrap();
+    std::fs::create_dir_all(dirs.state.join("org")).unwrap();
+
+    assert!(dirs.migrate().is_err());
+    assert!(!std::fs::symlink_metadata(dirs.config.join("org"))
+        .unwrap()
+        .file_type()
+        .is_symlink());
+}
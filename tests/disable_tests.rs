@@ -0,0 +1,400 @@
+//! Tests for timed disables and the two-person rule on `hookwise disable`.
+
+use chrono::Utc;
+use hookwise::config::{dirs_runtime, ApprovalConfig, SessionDisableConfig, SessionMode};
+use hookwise::identity::Identity;
+use hookwise::session::disable::{
+    authorize_audit_mode, parse_duration, DisableAuthorization, DisableOutcome, DisableRequest,
+};
+use hookwise::session::SessionManager;
+use sha2::{Digest, Sha256};
+
+/// A session manager with its own runtime files, removed on drop.
+struct Sessions {
+    suffix: String,
+    mgr: SessionManager,
+}
+
+impl Sessions {
+    fn new(name: &str) -> Self {
+        let suffix = format!("test-{name}-{}", std::process::id());
+        Self {
+            mgr: SessionManager::new(Some(&suffix)),
+            suffix,
+        }
+    }
+
+    fn exclusion_file(&self) -> std::path::PathBuf {
+        dirs_runtime().join(format!("hookwise-{}-exclusions.json", self.suffix))
+    }
+}
+
+impl Drop for Sessions {
+    fn drop(&mut self) {
+        for kind in ["sessions", "exclusions", "disable-requests"] {
+            let _ = std::fs::remove_file(
+                dirs_runtime().join(format!("hookwise-{}-{kind}.json", self.suffix)),
+            );
+        }
+        let _ = std::fs::remove_file(
+            dirs_runtime().join(format!("hookwise-{}-disable-requests.lock", self.suffix)),
+        );
+    }
+}
+
+/// An identity a provider vouches for, as a second approver needs.
+fn person(user: &str) -> Identity {
+    Identity {
+        user: user.into(),
+        org: None,
+        provider: "oidc".into(),
+        verified: true,
+        expires_at: None,
+    }
+}
+
+fn protected() -> SessionDisableConfig {
+    SessionDisableConfig {
+        protected: true,
+        org_token_sha256: vec![format!("{:x}", Sha256::digest(b"org-secret"))],
+        ..SessionDisableConfig::default()
+    }
+}
+
+/// Approvals listing everyone the tests ask as.
+fn approvers() -> ApprovalConfig {
+    ApprovalConfig {
+        approvers: vec!["alice".into(), "bob".into()],
+        require_verified: false,
+    }
+}
+
+fn request(session_id: &str, user: &str) -> DisableRequest {
+    DisableRequest::new(
+        session_id,
+        person(user),
+        Some(format!("{user} asked")),
+        None,
+    )
+}
+
+#[test]
+fn unprotected_projects_disable_immediately() {
+    let s = Sessions::new("dis-open");
+    let outcome = s
+        .mgr
+        .authorize_disable(
+            request("s1", "alice"),
+            None,
+            &SessionDisableConfig::default(),
+            &ApprovalConfig::default(),
+        )
+        .unwrap();
+    assert!(matches!(
+        outcome,
+        DisableOutcome::Disabled {
+            authorization: DisableAuthorization::Unprotected,
+            until: None,
+            ..
+        }
+    ));
+    assert!(s.mgr.is_disabled("s1"));
+}
+
+#[test]
+fn protected_projects_need_a_second_person() {
+    let s = Sessions::new("dis-two");
+    let config = protected();
+    let approvals = approvers();
+    let disable = |user: &str| {
+        s.mgr
+            .authorize_disable(request("s1", user), None, &config, &approvals)
+    };
+
+    assert!(matches!(
+        disable("alice").unwrap(),
+        DisableOutcome::Pending { .. }
+    ));
+    assert!(!s.mgr.is_disabled("s1"));
+    // Asking again doesn't count as a second person.
+    assert!(matches!(
+        disable("Alice").unwrap(),
+        DisableOutcome::Pending { .. }
+    ));
+    assert!(!s.mgr.is_disabled("s1"));
+
+    let DisableOutcome::Disabled {
+        authorization,
+        request,
+        ..
+    } = disable("bob").unwrap()
+    else {
+        panic!("expected the second approver to disable the session");
+    };
+    assert_eq!(authorization, DisableAuthorization::SecondApprover);
+    assert_eq!(request.requested_by.user, "alice");
+    assert_eq!(request.reason.as_deref(), Some("alice asked"));
+    assert!(s.mgr.is_disabled("s1"));
+
+    // Enabling clears the disable, so the next one starts over.
+    s.mgr.enable("s1").unwrap();
+    assert!(matches!(
+        disable("bob").unwrap(),
+        DisableOutcome::Pending { .. }
+    ));
+}
+
+#[test]
+fn both_people_must_be_approvers() {
+    let s = Sessions::new("dis-approver");
+    let config = protected();
+    let approvals = ApprovalConfig {
+        approvers: vec!["*@sec.example.com".into()],
+        require_verified: false,
+    };
+    let disable = |user: &str| {
+        s.mgr
+            .authorize_disable(request("s1", user), None, &config, &approvals)
+    };
+
+    let err = disable("alice").unwrap_err();
+    assert!(err.to_string().contains("not listed"), "{err}");
+    assert!(matches!(
+        disable("erin@sec.example.com").unwrap(),
+        DisableOutcome::Pending { .. }
+    ));
+    let err = disable("mallory").unwrap_err();
+    assert!(err.to_string().contains("not listed"), "{err}");
+    assert!(!s.mgr.is_disabled("s1"));
+
+    disable("frank@sec.example.com").unwrap();
+    assert!(s.mgr.is_disabled("s1"));
+}
+
+#[test]
+fn both_people_must_be_verified() {
+    let s = Sessions::new("dis-verified");
+    let config = protected();
+    // Even when approvals don't ask for verified identities.
+    let approvals = approvers();
+    // Anyone can set HOOKWISE_USER to a colleague's name.
+    let claimed = |user: &str| {
+        let mut request = request("s1", user);
+        request.requested_by.provider = "env".into();
+        request.requested_by.verified = false;
+        request
+    };
+
+    // A made-up first requester can't be confirmed by the real second one.
+    let err = s
+        .mgr
+        .authorize_disable(claimed("alice"), None, &config, &approvals)
+        .unwrap_err();
+    assert!(err.to_string().contains("verified identities"), "{err}");
+    assert!(matches!(
+        s.mgr
+            .authorize_disable(request("s1", "bob"), None, &config, &approvals)
+            .unwrap(),
+        DisableOutcome::Pending { .. }
+    ));
+    assert!(!s.mgr.is_disabled("s1"));
+
+    let err = s
+        .mgr
+        .authorize_disable(claimed("alice"), None, &config, &approvals)
+        .unwrap_err();
+    assert!(err.to_string().contains("verified identities"), "{err}");
+    assert!(!s.mgr.is_disabled("s1"));
+
+    s.mgr
+        .authorize_disable(request("s1", "alice"), None, &config, &approvals)
+        .unwrap();
+    assert!(s.mgr.is_disabled("s1"));
+}
+
+#[test]
+fn concurrent_requests_are_all_kept() {
+    let s = Sessions::new("dis-concurrent");
+    let config = protected();
+    let approvals = approvers();
+    std::thread::scope(|scope| {
+        for i in 0..8 {
+            let (s, config, approvals) = (&s, &config, &approvals);
+            scope.spawn(move || {
+                s.mgr
+                    .authorize_disable(request(&format!("s{i}"), "alice"), None, config, approvals)
+                    .unwrap()
+            });
+        }
+    });
+    for i in 0..8 {
+        assert!(s
+            .mgr
+            .take_disable_request(&format!("s{i}"))
+            .unwrap()
+            .is_some());
+    }
+}
+
+#[test]
+fn org_tokens_skip_the_second_person() {
+    let s = Sessions::new("dis-token");
+    let config = protected();
+    let approvals = ApprovalConfig::default();
+
+    let err = s
+        .mgr
+        .authorize_disable(request("s1", "alice"), Some("guess"), &config, &approvals)
+        .unwrap_err();
+    assert!(err.to_string().starts_with("disable refused"), "{err}");
+    assert!(!s.mgr.is_disabled("s1"));
+
+    let outcome = s
+        .mgr
+        .authorize_disable(
+            request("s1", "alice"),
+            Some("org-secret\n"),
+            &config,
+            &approvals,
+        )
+        .unwrap();
+    assert!(matches!(
+        outcome,
+        DisableOutcome::Disabled {
+            authorization: DisableAuthorization::OrgToken,
+            ..
+        }
+    ));
+    assert!(s.mgr.is_disabled("s1"));
+}
+
+#[test]
+fn audit_mode_registration_is_a_disable_in_protected_projects() {
+    let open = SessionDisableConfig::default();
+    assert!(authorize_audit_mode(&open, SessionMode::Enforce, None).is_ok());
+
+    // A session registering itself can't lift enforcement on its own
+    let config = protected();
+    let err = authorize_audit_mode(&config, SessionMode::Enforce, None).unwrap_err();
+    assert!(err.to_string().contains("protected project"), "{err}");
+    assert!(authorize_audit_mode(&config, SessionMode::Enforce, Some("guess")).is_err());
+    assert!(authorize_audit_mode(&config, SessionMode::Enforce, Some("org-secret")).is_ok());
+
+    // Nothing is lifted where the policy already audits every session
+    assert!(authorize_audit_mode(&config, SessionMode::Audit, None).is_ok());
+}
+
+#[test]
+fn timed_disables_lapse() {
+    let s = Sessions::new("dis-timed");
+    s.mgr
+        .disable_until("s1", Some(Utc::now() - chrono::Duration::seconds(1)))
+        .unwrap();
+    assert!(!s.mgr.is_disabled("s1"));
+
+    let mut long = request("s2", "alice");
+    long.duration_secs = Some(3600);
+    let DisableOutcome::Disabled { until, .. } = s
+        .mgr
+        .authorize_disable(
+            long,
+            None,
+            &SessionDisableConfig::default(),
+            &ApprovalConfig::default(),
+        )
+        .unwrap()
+    else {
+        panic!("unprotected disables take effect");
+    };
+    assert!(until.unwrap() > Utc::now() + chrono::Duration::minutes(59));
+    assert!(s.mgr.is_disabled("s2"));
+
+    let lifted = s.mgr.enable("s2").unwrap().unwrap();
+    assert_eq!(lifted.until, until);
+    assert!(lifted.disabled_at.is_some());
+    assert!(s.mgr.enable("s2").unwrap().is_none());
+}
+
+#[test]
+fn lapsed_disables_are_expired() {
+    let s = Sessions::new("dis-expire");
+    let now = Utc::now();
+    s.mgr
+        .disable_until("gone", Some(now - chrono::Duration::seconds(1)))
+        .unwrap();
+    s.mgr
+        .disable_until("kept", Some(now + chrono::Duration::hours(1)))
+        .unwrap();
+    s.mgr.disable("forever").unwrap();
+
+    let lapsed = s.mgr.expire_disables(now).unwrap();
+    let ids: Vec<_> = lapsed.iter().map(|e| e.session_id.as_str()).collect();
+    assert_eq!(ids, vec!["gone"]);
+    assert!(s.mgr.expire_disables(now).unwrap().is_empty());
+    assert!(s.mgr.is_disabled("kept"));
+    assert!(s.mgr.is_disabled("forever"));
+
+    let later = now + chrono::Duration::hours(2);
+    let ids: Vec<_> = s
+        .mgr
+        .expire_disables(later)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.session_id)
+        .collect();
+    assert_eq!(ids, vec!["kept"]);
+}
+
+#[test]
+fn reminders_repeat_each_interval() {
+    let s = Sessions::new("dis-remind");
+    let hour = chrono::Duration::hours(1);
+    s.mgr.disable("s1").unwrap();
+    let start = Utc::now();
+
+    assert!(s.mgr.due_reminder("s1", hour, start).unwrap().is_none());
+    let first = start + chrono::Duration::minutes(61);
+    let due = s.mgr.due_reminder("s1", hour, first).unwrap().unwrap();
+    assert_eq!(due.reminded_at, Some(first));
+    assert!(s.mgr.due_reminder("s1", hour, first).unwrap().is_none());
+    assert!(s
+        .mgr
+        .due_reminder("s1", hour, first + chrono::Duration::minutes(30))
+        .unwrap()
+        .is_none());
+    assert!(s
+        .mgr
+        .due_reminder("s1", hour, first + hour)
+        .unwrap()
+        .is_some());
+
+    assert!(s.mgr.due_reminder("other", hour, first).unwrap().is_none());
+    s.mgr.enable("s1").unwrap();
+    assert!(s
+        .mgr
+        .due_reminder("s1", hour, first + hour * 3)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn legacy_exclusion_files_still_read() {
+    let s = Sessions::new("dis-legacy");
+    std::fs::write(s.exclusion_file(), r#"["old-session"]"#).unwrap();
+    assert!(s.mgr.is_disabled("old-session"));
+    let lifted = s.mgr.enable("old-session").unwrap().unwrap();
+    assert_eq!(lifted.disabled_at, None);
+    assert!(!s.mgr.is_disabled("old-session"));
+}
+
+#[test]
+fn durations_parse() {
+    assert_eq!(parse_duration("90").unwrap(), 90);
+    assert_eq!(parse_duration("45s").unwrap(), 45);
+    assert_eq!(parse_duration("30m").unwrap(), 1800);
+    assert_eq!(parse_duration("2h").unwrap(), 7200);
+    assert_eq!(parse_duration("1d").unwrap(), 86400);
+    assert!(parse_duration("2w").is_err());
+    assert!(parse_duration("soon").is_err());
+    assert!(parse_duration("").is_err());
+}
@@ -0,0 +1,211 @@
+//! Tests for Tier 2b embedding providers and the input cache.
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use hookwise::cascade::embed_sim::{
+    provider_from_spec, CachedProvider, EmbeddingProvider, EmbeddingSimilarity,
+};
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::config::roles::{PathPolicyConfig, RoleDefinition};
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier};
+use hookwise::error::Result;
+use tempfile::TempDir;
+
+/// Embeds text as letter frequencies and counts every input it is asked for.
+struct FakeProvider {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl EmbeddingProvider for FakeProvider {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.calls.fetch_add(texts.len(), Ordering::SeqCst);
+        Ok(texts
+            .iter()
+            .map(|t| {
+                let mut v = vec![0.0; 26];
+                for c in t.chars().filter(|c| c.is_ascii_lowercase()) {
+                    v[(c as u8 - b'a') as usize] += 1.0;
+                }
+                v
+            })
+            .collect())
+    }
+
+    fn model_id(&self) -> &str {
+        "fake:letters"
+    }
+}
+
+fn fake() -> (Box<dyn EmbeddingProvider>, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    (
+        Box::new(FakeProvider {
+            calls: calls.clone(),
+        }),
+        calls,
+    )
+}
+
+fn make_record(input: &str, decision: Decision) -> DecisionRecord {
+    make_role_record(input, "*", decision)
+}
+
+fn make_role_record(input: &str, role: &str, decision: Decision) -> DecisionRecord {
+    common::record(input, role, decision)
+}
+
+fn role(name: &str) -> RoleDefinition {
+    common::role(name, PathPolicyConfig::default())
+}
+
+fn make_input(input: &str) -> CascadeInput {
+    common::keyed(input)
+}
+
+#[tokio::test]
+async fn cached_provider_embeds_each_input_once() {
+    let (inner, calls) = fake();
+    let cached = CachedProvider::new(inner);
+
+    let texts = vec!["cargo build".to_string(), "cargo build".to_string()];
+    let first = cached.embed(&texts).await.unwrap();
+    assert_eq!(first.len(), 2);
+    assert_eq!(first[0], first[1]);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    cached.embed(&["cargo build".to_string()]).await.unwrap();
+    cached
+        .embed(&["cargo build".to_string(), "cargo test".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(cached.len(), 2);
+    assert_eq!(cached.model_id(), "fake:letters");
+}
+
+#[tokio::test]
+async fn evaluate_then_insert_reuses_query_embedding() {
+    let (provider, calls) = fake();
+    let es = EmbeddingSimilarity::with_provider(provider, 0.9);
+    es.build_index(&[make_record("cargo build --release", Decision::Allow)])
+        .await
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let result = es
+        .evaluate(&make_input("cargo build --release"))
+        .await
+        .unwrap()
+        .expect("identical input should match");
+    assert_eq!(result.decision, Decision::Allow);
+    assert_eq!(result.metadata.tier, DecisionTier::EmbeddingSimilarity);
+
+    // Persisting the decision for the same input hits the cache.
+    es.insert(&make_record("cargo build --release", Decision::Allow))
+        .await
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn close_deny_vetoes_embedding_allow() {
+    let (provider, _) = fake();
+    let es = EmbeddingSimilarity::with_provider(provider, 0.8);
+    es.build_index(&[
+        make_record("abcd", Decision::Allow),
+        make_record("abce", Decision::Deny),
+    ])
+    .await
+    .unwrap();
+
+    // Equally close to both: the deny is within the margin.
+    assert!(es.evaluate(&make_input("abcde")).await.unwrap().is_none());
+    // Identical to the allow and clearly further from the deny.
+    let result = es.evaluate(&make_input("abcd")).await.unwrap().unwrap();
+    assert_eq!(result.decision, Decision::Allow);
+}
+
+#[tokio::test]
+async fn partitions_keep_roles_apart() {
+    let (provider, calls) = fake();
+    let es = EmbeddingSimilarity::with_provider(provider, 0.9);
+    es.build_index(&[make_role_record("git push origin main", "maintainer", Decision::Allow)])
+        .await
+        .unwrap();
+    es.insert(&make_role_record("cargo test", "*", Decision::Allow))
+        .await
+        .unwrap();
+    assert_eq!(es.len(), 2);
+    assert_eq!(es.partition_count(), 2);
+
+    let mut input = make_input("git push origin main");
+    input.session.role = Some(role("tester"));
+    let before = calls.load(Ordering::SeqCst);
+    assert!(es.evaluate(&input).await.unwrap().is_none());
+
+    input.session.role = Some(role("maintainer"));
+    let result = es.evaluate(&input).await.unwrap().unwrap();
+    assert_eq!(result.decision, Decision::Allow);
+    assert_eq!(calls.load(Ordering::SeqCst), before);
+
+    // Wildcard entries still match any role; pending entries are searched.
+    input.session.role = Some(role("tester"));
+    input.sanitized_input = "cargo test".into();
+    assert!(es.evaluate(&input).await.unwrap().is_some());
+
+    es.invalidate_role("maintainer");
+    assert_eq!(es.partition_count(), 1);
+    input.session.role = Some(role("maintainer"));
+    input.sanitized_input = "git push origin main".into();
+    assert!(es.evaluate(&input).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn store_is_keyed_by_provider_model() {
+    let tmp = TempDir::new().unwrap();
+    let index_dir = tmp.path().join(".index");
+    let records = vec![make_record("npm test", Decision::Allow)];
+
+    let (provider, _) = fake();
+    let es = EmbeddingSimilarity::with_provider(provider, 0.9).with_store(index_dir.clone());
+    assert_eq!(es.model_name(), "fake:letters");
+    es.build_index(&records).await.unwrap();
+
+    // A fresh process with the same provider loads vectors from disk.
+    let (provider, calls) = fake();
+    let es = EmbeddingSimilarity::with_provider(provider, 0.9).with_store(index_dir);
+    es.load_or_build(&records).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn spec_rejects_unknown_provider() {
+    let err = provider_from_spec("acme:embed-1").err().unwrap();
+    assert!(err.to_string().contains("unknown embedding_model"));
+    assert!(provider_from_spec("local:other-model").is_err());
+}
+
+#[test]
+fn api_spec_requires_key() {
+    // Only assert when the variable is genuinely unset in this environment.
+    if std::env::var("VOYAGE_API_KEY").is_err() {
+        let err = provider_from_spec("voyage").err().unwrap();
+        assert!(err.to_string().contains("VOYAGE_API_KEY"));
+    }
+}
+
+#[test]
+fn api_spec_uses_default_model() {
+    use hookwise::cascade::embed_sim::{ApiEmbeddingProvider, EmbeddingApi};
+
+    let openai = ApiEmbeddingProvider::new(EmbeddingApi::OpenAi, "k".into(), None);
+    assert_eq!(openai.model_id(), "openai:text-embedding-3-small");
+    let cohere =
+        ApiEmbeddingProvider::new(EmbeddingApi::Cohere, "k".into(), Some("embed-v4.0"));
+    assert_eq!(cohere.model_id(), "cohere:embed-v4.0");
+}
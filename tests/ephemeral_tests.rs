@@ -0,0 +1,190 @@
+//! Tests for `ephemeral_paths`: fast allow of scratch and build output.
+
+mod common;
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tempfile::TempDir;
+
+use hookwise::cascade::ephemeral::EphemeralPaths;
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::config::{EphemeralPathConfig, SensitivePathConfig};
+use hookwise::decision::{CacheKey, Decision, DecisionRecord, DecisionTier, ScopeLevel};
+
+/// A tier that always decides `decision`.
+struct Fixed {
+    tier: DecisionTier,
+    decision: Decision,
+}
+
+#[async_trait]
+impl CascadeTier for Fixed {
+    async fn evaluate(
+        &self,
+        input: &CascadeInput,
+    ) -> hookwise::error::Result<Option<DecisionRecord>> {
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: "*".into(),
+            },
+            decision: self.decision,
+            metadata: common::metadata(self.tier, "fixed"),
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: input.file_path.clone(),
+            session_id: String::new(),
+        }))
+    }
+    fn tier(&self) -> DecisionTier {
+        self.tier
+    }
+    fn name(&self) -> &str {
+        "fixed"
+    }
+}
+
+fn input(tool: &str, tool_input: serde_json::Value, cwd: &TempDir) -> CascadeInput {
+    CascadeInput {
+        cwd: Some(cwd.path().to_string_lossy().into_owned()),
+        ..common::input(common::session(), tool, tool_input)
+    }
+}
+
+fn write(file_path: &str, cwd: &TempDir) -> CascadeInput {
+    input(
+        "Write",
+        serde_json::json!({ "file_path": file_path, "content": "x" }),
+        cwd,
+    )
+}
+
+fn ephemeral() -> EphemeralPaths {
+    EphemeralPaths::compile(
+        &EphemeralPathConfig::default(),
+        &SensitivePathConfig::default(),
+        &HashMap::new(),
+    )
+    .unwrap()
+    .unwrap()
+}
+
+#[test]
+fn default_paths_cover_scratch_and_build_output() {
+    let cwd = TempDir::new().unwrap();
+    let ephemeral = ephemeral();
+    for path in [
+        ".tmp/notes.md",
+        "target/debug/build/out.txt",
+        "web/node_modules/.cache/babel/x.json",
+        "src/pkg/__pycache__/mod.pyc",
+    ] {
+        assert!(
+            ephemeral.matching_paths(&write(path, &cwd)).is_some(),
+            "{path}"
+        );
+    }
+    for path in [
+        "src/main.rs",
+        "node_modules/left-pad/index.js",
+        "targets.md",
+    ] {
+        assert!(
+            ephemeral.matching_paths(&write(path, &cwd)).is_none(),
+            "{path}"
+        );
+    }
+}
+
+#[test]
+fn escaping_sensitive_and_bash_calls_are_not_fast_allowed() {
+    let cwd = TempDir::new().unwrap();
+    let ephemeral = ephemeral();
+    // `..` is resolved before matching
+    assert!(ephemeral
+        .matching_paths(&write(".tmp/../src/main.rs", &cwd))
+        .is_none());
+    // Absolute paths outside the project stay absolute
+    assert!(ephemeral
+        .matching_paths(&write("/etc/target/x", &cwd))
+        .is_none());
+    // Sensitive paths win over ephemeral ones
+    assert!(ephemeral
+        .matching_paths(&write(".tmp/.env", &cwd))
+        .is_none());
+    // A command can do more than touch the paths it names
+    let bash = input(
+        "Bash",
+        serde_json::json!({ "command": "rm -rf target/debug" }),
+        &cwd,
+    );
+    assert!(ephemeral.matching_paths(&bash).is_none());
+}
+
+#[test]
+fn disabled_or_empty_config_compiles_to_nothing() {
+    let sensitive = SensitivePathConfig::default();
+    let disabled = EphemeralPathConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    let empty = EphemeralPathConfig {
+        paths: Vec::new(),
+        ..Default::default()
+    };
+    for config in [disabled, empty] {
+        assert!(
+            EphemeralPaths::compile(&config, &sensitive, &HashMap::new())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
+
+#[tokio::test]
+async fn runner_allows_ephemeral_writes_ahead_of_every_tier_and_keeps_no_rule() {
+    let tmp = TempDir::new().unwrap();
+    let runner = CascadeRunner {
+        ephemeral: Some(ephemeral()),
+        ..common::runner(
+            tmp.path(),
+            Box::new(Fixed {
+                tier: DecisionTier::Supervisor,
+                decision: Decision::Deny,
+            }),
+            Box::new(Fixed {
+                tier: DecisionTier::Human,
+                decision: Decision::Deny,
+            }),
+        )
+    };
+    let cwd = tmp.path().to_string_lossy().into_owned();
+
+    let record = runner
+        .evaluate_with_cwd(
+            &common::session(),
+            "Write",
+            &serde_json::json!({ "file_path": ".tmp/scratch.py", "content": "x" }),
+            Some(&cwd),
+        )
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Allow);
+    assert_eq!(record.metadata.tier, DecisionTier::EphemeralPath);
+    assert!(record.metadata.reason.contains(".tmp/scratch.py"));
+    assert!(!tmp.path().join("rules").exists());
+
+    let record = runner
+        .evaluate_with_cwd(
+            &common::session(),
+            "Write",
+            &serde_json::json!({ "file_path": "src/lib.rs", "content": "x" }),
+            Some(&cwd),
+        )
+        .await
+        .unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::Supervisor);
+}
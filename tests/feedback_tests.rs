@@ -0,0 +1,143 @@
+//! Tests for correcting automatic decisions with `hookwise feedback`.
+
+mod common;
+
+use std::sync::Arc;
+
+use hookwise::cascade::precedents::PrecedentSelector;
+use hookwise::cascade::token_sim::TokenJaccard;
+use hookwise::cascade::CascadeInput;
+use hookwise::config::SupervisorExamplesConfig;
+use hookwise::decision::{Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::feedback::{
+    append_feedback, feedback_log_path, invalidate_derived, load_feedback, FeedbackRecord,
+};
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::StorageBackend;
+
+fn make_record(input: &str, decision: Decision, tier: DecisionTier) -> DecisionRecord {
+    DecisionRecord {
+        metadata: DecisionMetadata {
+            confidence: 0.9,
+            ..common::metadata(tier, "test")
+        },
+        ..common::record(input, "coder", decision)
+    }
+}
+
+fn make_input(input: &str) -> CascadeInput {
+    common::keyed(input)
+}
+
+#[test]
+fn decision_id_is_stable_across_serialization() {
+    let record = make_record(
+        "git push --force",
+        Decision::Allow,
+        DecisionTier::Supervisor,
+    );
+    let id = record.id();
+    assert!(id.starts_with("dec-"));
+    assert_eq!(id.len(), "dec-".len() + 12);
+
+    let json = serde_json::to_string(&record).unwrap();
+    let round_tripped: DecisionRecord = serde_json::from_str(&json).unwrap();
+    assert_eq!(round_tripped.id(), id);
+
+    let other = make_record("git push", Decision::Allow, DecisionTier::Supervisor);
+    assert_ne!(other.id(), id);
+}
+
+#[test]
+fn feedback_log_round_trips() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = feedback_log_path(&tmp.path().join(".hookwise"));
+    assert!(load_feedback(&path).is_empty());
+
+    let record = make_record("rm -rf target", Decision::Allow, DecisionTier::Supervisor);
+    append_feedback(
+        &path,
+        &FeedbackRecord::new(&record, Decision::Deny, "too broad"),
+    )
+    .unwrap();
+    append_feedback(
+        &path,
+        &FeedbackRecord::new(&record, Decision::Ask, "on reflection"),
+    )
+    .unwrap();
+
+    let loaded = load_feedback(&path);
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded[0].decision_id, record.id());
+    assert_eq!(loaded[0].original, Decision::Allow);
+    assert_eq!(loaded[0].original_tier, DecisionTier::Supervisor);
+    assert_eq!(loaded[0].corrected, Decision::Deny);
+    assert_eq!(loaded[1].reason, "on reflection");
+}
+
+#[test]
+fn correction_becomes_a_human_decision() {
+    let record = make_record("rm -rf target", Decision::Allow, DecisionTier::Supervisor);
+    let decision = FeedbackRecord::new(&record, Decision::Deny, "too broad").as_decision();
+    assert_eq!(decision.key, record.key);
+    assert_eq!(decision.decision, Decision::Deny);
+    assert_eq!(decision.metadata.tier, DecisionTier::Human);
+    assert_eq!(decision.metadata.reason, "too broad");
+}
+
+#[test]
+fn invalidate_removes_the_decision_and_those_derived_from_it() {
+    let tmp = tempfile::tempdir().unwrap();
+    let storage = JsonlStorage::new(
+        tmp.path().join(".hookwise"),
+        tmp.path().join("global"),
+        None,
+    );
+    let original = make_record("rm -rf target", Decision::Allow, DecisionTier::Supervisor);
+    let mut derived = make_record(
+        "rm -rf target/",
+        Decision::Allow,
+        DecisionTier::TokenJaccard,
+    );
+    derived.metadata.matched_key = Some(original.key.clone());
+    let unrelated = make_record("cargo build", Decision::Allow, DecisionTier::Supervisor);
+    for record in [&original, &derived, &unrelated] {
+        storage.save_decision(record).unwrap();
+    }
+
+    assert_eq!(invalidate_derived(&storage, &original.key).unwrap(), 2);
+
+    let remaining = storage.load_decisions(ScopeLevel::Project).unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].key, unrelated.key);
+}
+
+#[tokio::test]
+async fn corrections_are_offered_as_precedents_first() {
+    let mut human = make_record("rm -rf target", Decision::Allow, DecisionTier::Human);
+    human.key.role = "*".into();
+    let token_jaccard = Arc::new(TokenJaccard::new(0.7, 1));
+    token_jaccard.load_from(&[human.clone()]);
+
+    let mut wrong = make_record("rm -rf target", Decision::Allow, DecisionTier::Supervisor);
+    wrong.key.role = "*".into();
+    let mut other_role = wrong.clone();
+    other_role.key.role = "reviewer".into();
+    let corrections = vec![
+        FeedbackRecord::new(&wrong, Decision::Ask, "older"),
+        FeedbackRecord::new(&wrong, Decision::Deny, "newer"),
+        FeedbackRecord::new(&other_role, Decision::Deny, "other role"),
+    ];
+
+    let selected = PrecedentSelector::new(token_jaccard, SupervisorExamplesConfig::default())
+        .with_corrections(corrections)
+        .select(&make_input("rm -rf target/debug"))
+        .await;
+
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].decision, Decision::Deny);
+    assert_eq!(selected[0].corrected_from, Some(Decision::Allow));
+    assert!(selected[0]
+        .render()
+        .starts_with("- deny (similarity 0.75, corrected from allow) Bash:"));
+}
@@ -0,0 +1,86 @@
+//! Tests for `hookwise init --hooks`: hook manager detection, manager
+//! fragments, and installing git hooks next to existing ones.
+
+use tempfile::TempDir;
+
+use hookwise::cli::git_hooks::{hook_script, install_hooks, HookInstall, HookManager, HOOK_MARKER};
+
+#[test]
+fn detects_the_hook_manager_in_the_repo_root() {
+    let tmp = TempDir::new().unwrap();
+    assert_eq!(HookManager::detect(tmp.path()), HookManager::Git);
+
+    std::fs::write(tmp.path().join(".pre-commit-config.yaml"), "repos: []\n").unwrap();
+    assert_eq!(HookManager::detect(tmp.path()), HookManager::PreCommit);
+    std::fs::write(tmp.path().join(".lefthook.yml"), "").unwrap();
+    assert_eq!(HookManager::detect(tmp.path()), HookManager::Lefthook);
+    std::fs::create_dir(tmp.path().join(".husky")).unwrap();
+    assert_eq!(HookManager::detect(tmp.path()), HookManager::Husky);
+}
+
+#[test]
+fn manager_fragments_are_valid_config_running_the_scans() {
+    assert_eq!(HookManager::Git.fragment(), None);
+
+    let (_, lefthook) = HookManager::Lefthook.fragment().unwrap();
+    let lefthook: serde_yaml::Value = serde_yaml::from_str(lefthook).unwrap();
+    assert_eq!(
+        lefthook["pre-commit"]["commands"]["hookwise-scan"]["run"],
+        "hookwise scan --staged"
+    );
+    assert_eq!(
+        lefthook["pre-push"]["commands"]["hookwise-scan"]["run"],
+        "hookwise scan ."
+    );
+
+    // The fragment goes under an existing `repos:` key
+    let (target, pre_commit) = HookManager::PreCommit.fragment().unwrap();
+    assert!(target.contains("repos:"));
+    let config: serde_yaml::Value = serde_yaml::from_str(&format!("repos:\n{pre_commit}")).unwrap();
+    let hooks = &config["repos"][0]["hooks"];
+    assert_eq!(config["repos"][0]["repo"], "local");
+    assert_eq!(hooks[0]["entry"], "hookwise scan --staged");
+    assert_eq!(hooks[0]["stages"][0], "pre-commit");
+    assert_eq!(hooks[1]["stages"][0], "pre-push");
+
+    let (_, husky) = HookManager::Husky.fragment().unwrap();
+    assert!(husky.contains("# .husky/pre-push\nhookwise scan .\n"));
+}
+
+#[test]
+fn install_replaces_its_own_hooks_but_keeps_others() {
+    let tmp = TempDir::new().unwrap();
+    let hooks = tmp.path().join("hooks");
+    std::fs::create_dir(&hooks).unwrap();
+    std::fs::write(hooks.join("pre-push"), "#!/bin/sh\nmake lint\n").unwrap();
+
+    let results = install_hooks(&hooks).unwrap();
+    assert_eq!(
+        results,
+        vec![
+            HookInstall::Installed(hooks.join("pre-commit")),
+            HookInstall::Kept(hooks.join("pre-push"), "hookwise scan ."),
+        ]
+    );
+    let script = std::fs::read_to_string(hooks.join("pre-commit")).unwrap();
+    assert_eq!(script, hook_script("hookwise scan --staged"));
+    assert!(script.starts_with("#!/bin/sh\n") && script.contains(HOOK_MARKER));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(hooks.join("pre-commit"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o111, 0o111);
+    }
+    assert_eq!(
+        std::fs::read_to_string(hooks.join("pre-push")).unwrap(),
+        "#!/bin/sh\nmake lint\n"
+    );
+
+    // Running again rewrites only the hook it wrote
+    let results = install_hooks(&hooks).unwrap();
+    assert_eq!(results[0], HookInstall::Installed(hooks.join("pre-commit")));
+    assert!(matches!(results[1], HookInstall::Kept(..)));
+}
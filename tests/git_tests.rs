@@ -0,0 +1,357 @@
+//! Tests for git operation recognition and `git` rules.
+
+mod common;
+
+use std::path::Path;
+
+use hookwise::cascade::git::{
+    current_branch, file_operations, head_commit, GitOperation, GitPolicy,
+};
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::config::{GitPolicyConfig, PathPolicyConfig};
+use hookwise::decision::{Decision, DecisionTier};
+use hookwise::session::SessionContext;
+
+fn policy(yaml: &str) -> GitPolicy {
+    let config: GitPolicyConfig = serde_yaml::from_str(yaml).unwrap();
+    GitPolicy::compile(&config).unwrap().unwrap()
+}
+
+fn rules() -> GitPolicy {
+    policy(
+        r#"
+protected_branches: ["main", "release/*"]
+rules:
+  - roles: ["release*"]
+    force_push: ask
+    protected_push: allow
+    tag_delete: allow
+  - force_push: deny
+    protected_push: ask
+    config: ask
+    tag_delete: deny
+    submodule: ask
+    hooks: deny
+"#,
+    )
+}
+
+fn operations(policy: &GitPolicy, command: &str) -> Vec<GitOperation> {
+    policy
+        .operations(command, None)
+        .into_iter()
+        .map(|f| f.operation)
+        .collect()
+}
+
+fn decide(policy: &GitPolicy, role: &str, command: &str) -> Option<Decision> {
+    policy.check(role, command, None).map(|(d, _)| d)
+}
+
+/// A repository in a temp dir with `branch` checked out.
+fn repo(branch: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    std::fs::write(
+        dir.path().join(".git/HEAD"),
+        format!("ref: refs/heads/{branch}\n"),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn recognizes_force_and_protected_pushes() {
+    let policy = rules();
+    use GitOperation::*;
+    assert_eq!(
+        operations(&policy, "git push --force origin feature"),
+        vec![ForcePush]
+    );
+    assert_eq!(
+        operations(&policy, "git push -uf origin main"),
+        vec![ForcePush, ProtectedPush]
+    );
+    assert_eq!(
+        operations(&policy, "git push origin +feature:release/1.2"),
+        vec![ForcePush, ProtectedPush]
+    );
+    assert_eq!(
+        operations(
+            &policy,
+            "git push --force-with-lease=main origin HEAD:refs/heads/main"
+        ),
+        vec![ForcePush, ProtectedPush]
+    );
+    assert_eq!(
+        operations(&policy, "git push origin :main"),
+        vec![ProtectedPush]
+    );
+    assert_eq!(
+        operations(&policy, "git push --mirror backup"),
+        vec![ProtectedPush, ForcePush]
+    );
+    assert!(operations(&policy, "git push origin feature").is_empty());
+    assert!(operations(&policy, "git push --tags").is_empty());
+    assert!(operations(&policy, "git push -o ci.skip origin feature").is_empty());
+
+    let findings = policy.operations("git push origin :refs/tags/v1.0 +refs/tags/v2", None);
+    let details: Vec<_> = findings.iter().map(|f| f.detail.as_str()).collect();
+    assert_eq!(
+        details,
+        vec!["force-push to 'refs/tags/v2'", "deletion of tag 'v1.0'"]
+    );
+}
+
+#[test]
+fn pushes_without_a_refspec_use_the_current_branch() {
+    let policy = rules();
+    let main = repo("main");
+    let feature = repo("feature");
+    let on = |dir: &Path, command: &str| {
+        policy
+            .operations(command, Some(dir))
+            .into_iter()
+            .map(|f| f.detail)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        on(main.path(), "git push"),
+        vec!["push to protected branch 'main'"]
+    );
+    assert_eq!(
+        on(feature.path(), "git push -f origin HEAD"),
+        vec!["force-push to 'feature'"]
+    );
+    assert_eq!(
+        on(
+            feature.path(),
+            &format!("git -C {} push", main.path().display())
+        ),
+        vec!["push to protected branch 'main'"]
+    );
+    assert_eq!(
+        policy
+            .operations("git push --force", None)
+            .into_iter()
+            .map(|f| f.detail)
+            .collect::<Vec<_>>(),
+        vec!["force-push to the current branch"]
+    );
+
+    let nested = main.path().join("src/deep");
+    std::fs::create_dir_all(&nested).unwrap();
+    assert_eq!(current_branch(&nested).as_deref(), Some("main"));
+    std::fs::write(main.path().join(".git/HEAD"), "0123abcd\n").unwrap();
+    assert_eq!(current_branch(main.path()), None, "detached HEAD");
+}
+
+#[test]
+fn head_commit_resolves_loose_packed_and_detached_heads() {
+    let main = repo("main");
+    assert_eq!(head_commit(main.path()), None, "no commits yet");
+
+    std::fs::write(
+        main.path().join(".git/packed-refs"),
+        "# pack-refs with: peeled\naaaa1111 refs/heads/main\nbbbb2222 refs/heads/other\n",
+    )
+    .unwrap();
+    assert_eq!(head_commit(main.path()).as_deref(), Some("aaaa1111"));
+
+    std::fs::create_dir_all(main.path().join(".git/refs/heads")).unwrap();
+    std::fs::write(main.path().join(".git/refs/heads/main"), "cccc3333\n").unwrap();
+    let nested = main.path().join("src");
+    std::fs::create_dir_all(&nested).unwrap();
+    assert_eq!(head_commit(&nested).as_deref(), Some("cccc3333"));
+
+    std::fs::write(main.path().join(".git/HEAD"), "dddd4444\n").unwrap();
+    assert_eq!(head_commit(main.path()).as_deref(), Some("dddd4444"));
+}
+
+#[test]
+fn recognizes_config_tag_submodule_and_hook_changes() {
+    let policy = rules();
+    use GitOperation::*;
+    assert_eq!(
+        operations(&policy, "git config --global user.email a@b.c"),
+        vec![Config]
+    );
+    assert_eq!(
+        operations(&policy, "git config set user.name Bot"),
+        vec![Config]
+    );
+    assert_eq!(
+        operations(&policy, "git config --unset core.hooksPath"),
+        vec![Hooks]
+    );
+    assert_eq!(
+        operations(&policy, "git config core.hooksPath .githooks"),
+        vec![Hooks]
+    );
+    assert_eq!(
+        operations(&policy, "git -c core.hooksPath=/dev/null commit -m wip"),
+        vec![Hooks]
+    );
+    assert_eq!(
+        operations(
+            &policy,
+            "git config submodule.vendor/lib.url https://evil.example/lib"
+        ),
+        vec![Submodule]
+    );
+    assert_eq!(
+        operations(
+            &policy,
+            "git config -f .gitmodules submodule.lib.url https://x/lib"
+        ),
+        vec![Submodule]
+    );
+    assert!(operations(&policy, "git config user.email").is_empty());
+    assert!(operations(&policy, "git config --get remote.origin.url").is_empty());
+    assert!(operations(&policy, "git config --list --show-origin").is_empty());
+
+    assert_eq!(
+        operations(&policy, "git tag -d v1.0 v1.1"),
+        vec![TagDelete, TagDelete]
+    );
+    assert!(operations(&policy, "git tag -a v1.2 -m release").is_empty());
+
+    let submodules = policy.operations(
+        "git submodule add -b main https://example.com/lib.git vendor/lib && \
+         git submodule set-url vendor/lib https://mirror.example/lib.git",
+        None,
+    );
+    let details: Vec<_> = submodules.iter().map(|f| f.detail.as_str()).collect();
+    assert_eq!(
+        details,
+        vec![
+            "new submodule from 'https://example.com/lib.git'",
+            "submodule 'vendor/lib' URL change to 'https://mirror.example/lib.git'",
+        ]
+    );
+    assert!(operations(&policy, "git submodule update --init").is_empty());
+
+    let edits: Vec<_> = [
+        ".git/hooks/pre-commit",
+        "/repo/.git/config",
+        "a/.gitmodules",
+        "src/git.rs",
+    ]
+    .iter()
+    .flat_map(|p| file_operations(p))
+    .map(|f| f.operation)
+    .collect();
+    assert_eq!(edits, vec![Hooks, Config, Submodule]);
+}
+
+#[test]
+fn first_rule_setting_the_operation_wins() {
+    let policy = rules();
+    assert_eq!(
+        decide(&policy, "release-bot", "git push origin main"),
+        Some(Decision::Allow)
+    );
+    assert_eq!(
+        decide(&policy, "release-bot", "git push -f origin main"),
+        Some(Decision::Ask),
+        "force_push ask outranks protected_push allow"
+    );
+    assert_eq!(
+        decide(&policy, "coder", "git push origin main"),
+        Some(Decision::Ask)
+    );
+    // config isn't set for release roles, so the second rule decides.
+    assert_eq!(
+        decide(&policy, "release-bot", "git config user.name Bot"),
+        Some(Decision::Ask)
+    );
+
+    let (_, reasons) = policy
+        .check("coder", "git push --force origin main", None)
+        .unwrap();
+    assert_eq!(
+        reasons,
+        vec!["force-push to 'main'; git.rules[1] says deny"]
+    );
+}
+
+#[test]
+fn allow_needs_every_command_to_be_an_allowed_git_operation() {
+    let policy = rules();
+    assert_eq!(
+        decide(
+            &policy,
+            "release-bot",
+            "git push origin main && rm -rf build"
+        ),
+        None
+    );
+    assert_eq!(
+        decide(
+            &policy,
+            "release-bot",
+            "git tag -d v1 && git push origin :refs/tags/v1"
+        ),
+        Some(Decision::Allow)
+    );
+    assert_eq!(
+        decide(
+            &policy,
+            "coder",
+            "cargo build && git push -f origin feature"
+        ),
+        Some(Decision::Deny)
+    );
+    assert_eq!(decide(&policy, "coder", "git status"), None);
+}
+
+#[test]
+fn empty_rules_compile_to_none() {
+    let config = GitPolicyConfig::default();
+    assert_eq!(config.protected_branches, vec!["main", "master"]);
+    assert!(GitPolicy::compile(&config).unwrap().is_none());
+}
+
+fn input(tool_name: &str, tool_input: serde_json::Value, file_path: Option<&str>) -> CascadeInput {
+    let session = SessionContext {
+        role: Some(common::role("coder", PathPolicyConfig::default())),
+        ..common::session()
+    };
+    CascadeInput {
+        file_path: file_path.map(String::from),
+        ..common::input(session, tool_name, tool_input)
+    }
+}
+
+#[tokio::test]
+async fn tier_checks_bash_commands_and_file_edits() {
+    let policy = rules();
+    let push = input(
+        "Bash",
+        serde_json::json!({ "command": "git push --force origin feature" }),
+        None,
+    );
+    let record = policy.evaluate(&push).await.unwrap().unwrap();
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(record.metadata.tier, DecisionTier::GitPolicy);
+    assert_eq!(record.key.role, "coder");
+
+    let hook = input(
+        "Write",
+        serde_json::json!({ "file_path": ".git/hooks/pre-push", "content": "exit 0" }),
+        Some(".git/hooks/pre-push"),
+    );
+    let record = policy.evaluate(&hook).await.unwrap().unwrap();
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(
+        record.metadata.reason,
+        "edit of hook '.git/hooks/pre-push'; git.rules[1] says deny"
+    );
+
+    let source = input(
+        "Edit",
+        serde_json::json!({ "file_path": "src/main.rs" }),
+        Some("src/main.rs"),
+    );
+    assert!(policy.evaluate(&source).await.unwrap().is_none());
+}
@@ -0,0 +1,194 @@
+//! Tests for remediation hints on denied and asked calls.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tempfile::TempDir;
+
+use hookwise::cascade::hints::HintTable;
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::config::roles::PathPolicyConfig;
+use hookwise::config::{HintRule, HintsConfig, PolicyConfig};
+use hookwise::decision::{Decision, DecisionTier};
+use hookwise::error::HookwiseError;
+use hookwise::hook_io::hook_reason;
+use hookwise::session::SessionContext;
+
+fn session(role: &str) -> SessionContext {
+    let paths = PathPolicyConfig {
+        allow_write: vec!["src/**".into()],
+        deny_write: vec!["tests/**".into(), "infra/**".into()],
+        allow_read: vec!["**".into()],
+    };
+    common::session_as(role, paths, &[])
+}
+
+fn write_input(role: &str, file_path: &str) -> CascadeInput {
+    CascadeInput {
+        sanitized_input: file_path.into(),
+        ..common::input(
+            session(role),
+            "Write",
+            serde_json::json!({ "file_path": file_path }),
+        )
+    }
+}
+
+fn table(config: HintsConfig) -> HintTable {
+    HintTable::compile(&config, &HashMap::new())
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn builtin_hints_name_the_role_that_owns_the_path() {
+    let hints = table(HintsConfig::default());
+    assert_eq!(
+        hints
+            .suggest(&write_input("coder", "tests/api.rs"), Decision::Deny, "")
+            .as_deref(),
+        Some("tests/api.rs belongs to the tester role: register a tester session or ask the tester agent")
+    );
+    assert_eq!(
+        hints
+            .suggest(&write_input("tester", "src/lib.rs"), Decision::Deny, "")
+            .as_deref(),
+        Some(
+            "src/lib.rs belongs to the coder role: register a coder session or ask the coder agent"
+        )
+    );
+    // Not for the owner, for asks, or for allows
+    assert_eq!(
+        hints.suggest(&write_input("tester", "tests/api.rs"), Decision::Deny, ""),
+        None
+    );
+    assert_eq!(
+        hints.suggest(&write_input("coder", "tests/api.rs"), Decision::Ask, ""),
+        None
+    );
+    assert_eq!(
+        hints.suggest(&write_input("coder", "tests/api.rs"), Decision::Allow, ""),
+        None
+    );
+}
+
+#[test]
+fn project_rules_win_over_builtin_ones_and_filter_by_role_and_tool() {
+    let hints = table(HintsConfig {
+        builtin: true,
+        rules: vec![
+            HintRule {
+                paths: vec!["{{tests}}".into()],
+                roles: vec!["coder".into()],
+                tools: vec!["Write".into()],
+                decision: None,
+                hint: "{{role}} got {{decision}} ({{reason}}): ask the QA team".into(),
+            },
+            HintRule {
+                tools: vec!["Bash".into()],
+                hint: "never used for Write".into(),
+                ..Default::default()
+            },
+        ],
+    });
+    let input = write_input("coder", "tests/api.rs");
+    assert_eq!(
+        hints
+            .suggest(&input, Decision::Deny, "denied by path policy")
+            .as_deref(),
+        Some("coder got deny (denied by path policy): ask the QA team")
+    );
+    assert_eq!(
+        hints.suggest(&input, Decision::Ask, "x").as_deref(),
+        Some("coder got ask (x): ask the QA team")
+    );
+    assert_eq!(
+        hints
+            .suggest(&write_input("devops", "src/lib.rs"), Decision::Deny, "")
+            .as_deref(),
+        Some(
+            "src/lib.rs belongs to the coder role: register a coder session or ask the coder agent"
+        )
+    );
+}
+
+#[test]
+fn compile_rejects_unknown_variables_and_can_drop_the_builtins() {
+    let err = HintTable::compile(
+        &HintsConfig {
+            builtin: false,
+            rules: vec![HintRule {
+                hint: "see {{ticket}}".into(),
+                ..Default::default()
+            }],
+        },
+        &HashMap::new(),
+    )
+    .err()
+    .unwrap();
+    assert!(
+        matches!(&err, HookwiseError::ConfigParse { reason, .. }
+            if reason.contains("hints.rules[0]") && reason.contains("{{ticket}}")),
+        "{err}"
+    );
+
+    let none = HintsConfig {
+        builtin: false,
+        rules: Vec::new(),
+    };
+    assert!(HintTable::compile(&none, &HashMap::new())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn hints_parse_from_policy_yaml() {
+    let policy: PolicyConfig = serde_yaml::from_str(
+        r#"
+hints:
+  rules:
+    - paths: ["{{infra}}"]
+      decision: deny
+      hint: "open an infra ticket"
+"#,
+    )
+    .unwrap();
+    assert!(policy.hints.builtin);
+    assert_eq!(policy.hints.rules[0].decision, Some(Decision::Deny));
+    assert!(PolicyConfig::default().hints.builtin);
+}
+
+#[tokio::test]
+async fn runner_adds_the_hint_to_the_reason_but_does_not_store_it() {
+    let tmp = TempDir::new().unwrap();
+    let runner = CascadeRunner {
+        hints: Some(Arc::new(table(HintsConfig::default()))),
+        ..common::runner(
+            tmp.path(),
+            Box::new(common::Pass(DecisionTier::Supervisor)),
+            Box::new(common::Pass(DecisionTier::Human)),
+        )
+    };
+
+    let input = write_input("coder", "tests/api.rs");
+    let record = runner
+        .evaluate(&input.session, &input.tool_name, &input.tool_input)
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(record.metadata.tier, DecisionTier::PathPolicy);
+    let reason = hook_reason(&record).unwrap();
+    assert!(
+        reason.starts_with("suggestion: tests/api.rs belongs to the tester role"),
+        "{reason}"
+    );
+
+    let stored = CascadeInput {
+        sanitized_input: record.key.sanitized_input.clone(),
+        ..input
+    };
+    let cached = runner.exact_cache.evaluate(&stored).await.unwrap().unwrap();
+    assert_eq!(cached.metadata.suggestion, None);
+}
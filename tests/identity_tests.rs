@@ -0,0 +1,280 @@
+//! Tests for identity providers (env, git, OIDC token file, command) and
+//! approver checks.
+
+use std::path::PathBuf;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use hookwise::config::{
+    ApprovalConfig, CommandIdentityConfig, GlobalConfig, IdentityConfig, OidcIdentityConfig,
+};
+use hookwise::identity::{
+    check_approver, provider, CommandIdentity, Identity, IdentityProvider, OidcIdentity,
+};
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+fn b64(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// An ES256 signing key and its JWKS, written to `dir`.
+struct Issuer {
+    key: EcdsaKeyPair,
+    jwks_file: PathBuf,
+}
+
+impl Issuer {
+    fn new(dir: &std::path::Path) -> Self {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+            .unwrap();
+        let point = key.public_key().as_ref();
+        let jwks = serde_json::json!({ "keys": [{
+            "kty": "EC", "crv": "P-256", "kid": "k1",
+            "x": b64(&point[1..33]), "y": b64(&point[33..65]),
+        }]});
+        let jwks_file = dir.join("jwks.json");
+        std::fs::write(&jwks_file, jwks.to_string()).unwrap();
+        Self { key, jwks_file }
+    }
+
+    fn sign(&self, claims: serde_json::Value) -> String {
+        let header = b64(br#"{"alg":"ES256","kid":"k1"}"#);
+        let payload = b64(claims.to_string().as_bytes());
+        let message = format!("{header}.{payload}");
+        let signature = self
+            .key
+            .sign(&SystemRandom::new(), message.as_bytes())
+            .unwrap();
+        format!("{message}.{}", b64(signature.as_ref()))
+    }
+}
+
+fn oidc(jwks_file: Option<PathBuf>) -> OidcIdentity {
+    OidcIdentity::new(OidcIdentityConfig {
+        token_file: "unused".into(),
+        jwks_file,
+        issuer: Some("https://sso.example.com".into()),
+        audience: Some("hookwise".into()),
+        user_claim: "email".into(),
+        org_claim: Some("org".into()),
+    })
+}
+
+fn claims() -> serde_json::Value {
+    serde_json::json!({
+        "iss": "https://sso.example.com",
+        "aud": ["hookwise", "other"],
+        "email": "alice@example.com",
+        "org": "acme",
+        "exp": (Utc::now() + Duration::hours(1)).timestamp(),
+    })
+}
+
+fn identity(user: &str, verified: bool) -> Identity {
+    Identity {
+        user: user.into(),
+        org: None,
+        provider: if verified { "oidc" } else { "env" }.into(),
+        verified,
+        expires_at: None,
+    }
+}
+
+#[test]
+fn oidc_tokens_are_verified_against_the_jwks() {
+    let dir = tempfile::tempdir().unwrap();
+    let issuer = Issuer::new(dir.path());
+    let token = issuer.sign(claims());
+
+    let identity = oidc(Some(issuer.jwks_file.clone()))
+        .verify(&token, Utc::now())
+        .unwrap();
+    assert_eq!(identity.user, "alice@example.com");
+    assert_eq!(identity.org.as_deref(), Some("acme"));
+    assert_eq!(identity.provider, "oidc");
+    assert!(identity.verified);
+    assert!(identity.expires_at.is_some());
+
+    // Without a JWKS the claims are still read, but not vouched for.
+    let unsigned = oidc(None).verify(&token, Utc::now()).unwrap();
+    assert!(!unsigned.verified);
+
+    // A payload swapped under the signature is rejected.
+    let mut forged = claims();
+    forged["email"] = "mallory@example.com".into();
+    let parts: Vec<&str> = token.split('.').collect();
+    let tampered = format!(
+        "{}.{}.{}",
+        parts[0],
+        b64(forged.to_string().as_bytes()),
+        parts[2]
+    );
+    let err = oidc(Some(issuer.jwks_file.clone()))
+        .verify(&tampered, Utc::now())
+        .unwrap_err();
+    assert!(err.to_string().contains("signature is invalid"), "{err}");
+
+    // A key from another issuer doesn't verify it either.
+    let other = tempfile::tempdir().unwrap();
+    let other = Issuer::new(other.path());
+    assert!(oidc(Some(other.jwks_file))
+        .verify(&token, Utc::now())
+        .is_err());
+}
+
+#[test]
+fn oidc_claims_are_checked() {
+    let dir = tempfile::tempdir().unwrap();
+    let issuer = Issuer::new(dir.path());
+    let check = |claims: serde_json::Value| {
+        oidc(Some(issuer.jwks_file.clone()))
+            .verify(&issuer.sign(claims), Utc::now())
+            .map_err(|e| e.to_string())
+    };
+
+    let mut expired = claims();
+    expired["exp"] = (Utc::now() - Duration::hours(1)).timestamp().into();
+    assert!(check(expired).unwrap_err().contains("expired"));
+
+    let mut issuer_claim = claims();
+    issuer_claim["iss"] = "https://evil.example.com".into();
+    assert!(check(issuer_claim).unwrap_err().contains("not issued by"));
+
+    let mut audience = claims();
+    audience["aud"] = "something-else".into();
+    assert!(check(audience).unwrap_err().contains("audience"));
+
+    let mut no_user = claims();
+    no_user.as_object_mut().unwrap().remove("email");
+    assert!(check(no_user).unwrap_err().contains("no 'email' claim"));
+
+    assert!(oidc(None).verify("not-a-jwt", Utc::now()).is_err());
+}
+
+#[test]
+fn oidc_provider_reads_the_token_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let issuer = Issuer::new(dir.path());
+    let token_file = dir.path().join("id_token");
+    std::fs::write(&token_file, format!("{}\n", issuer.sign(claims()))).unwrap();
+
+    let config = IdentityConfig::Oidc(OidcIdentityConfig {
+        token_file,
+        jwks_file: Some(issuer.jwks_file),
+        issuer: None,
+        audience: None,
+        user_claim: "email".into(),
+        org_claim: None,
+    });
+    let identity = provider(&config).resolve(dir.path()).unwrap();
+    assert_eq!(identity.user, "alice@example.com");
+    assert!(identity.verified);
+}
+
+#[cfg(unix)]
+#[test]
+fn command_provider_reads_json_or_a_plain_user() {
+    let dir = tempfile::tempdir().unwrap();
+    let run = |script: &str, timeout_secs: u64| {
+        CommandIdentity::new(CommandIdentityConfig {
+            command: vec!["sh".into(), "-c".into(), script.into()],
+            timeout_secs,
+        })
+        .resolve(dir.path())
+    };
+
+    let json = run(r#"echo '{"user": "bob@example.com", "org": "acme"}'"#, 5).unwrap();
+    assert_eq!(json.user, "bob@example.com");
+    assert_eq!(json.org.as_deref(), Some("acme"));
+    assert_eq!(json.provider, "command");
+    assert!(json.verified);
+
+    assert_eq!(run("printf 'carol\\n'", 5).unwrap().user, "carol");
+    assert!(run("exit 3", 5).unwrap_err().to_string().contains("exited"));
+    assert!(run("true", 5).unwrap_err().to_string().contains("no user"));
+    assert!(run("sleep 10", 1)
+        .unwrap_err()
+        .to_string()
+        .contains("timed out"));
+}
+
+#[test]
+fn git_provider_uses_the_configured_email() {
+    let dir = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.email", "dana@example.com"]);
+
+    let identity = provider(&IdentityConfig::Git).resolve(dir.path()).unwrap();
+    assert_eq!(identity.user, "dana@example.com");
+    assert_eq!(identity.provider, "git");
+    assert!(!identity.verified);
+}
+
+#[test]
+fn approvers_are_matched_by_identity() {
+    let open = ApprovalConfig::default();
+    assert!(check_approver(&open, &identity("anyone", false)).is_ok());
+
+    let listed = ApprovalConfig {
+        approvers: vec!["alice@example.com".into(), "*@sec.example.com".into()],
+        require_verified: false,
+    };
+    assert!(check_approver(&listed, &identity("Alice@Example.com", false)).is_ok());
+    assert!(check_approver(&listed, &identity("erin@sec.example.com", false)).is_ok());
+    let err = check_approver(&listed, &identity("mallory@example.com", false)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "identity error: 'mallory@example.com' is not an approver"
+    );
+
+    let verified = ApprovalConfig {
+        require_verified: true,
+        ..listed
+    };
+    assert!(check_approver(&verified, &identity("alice@example.com", true)).is_ok());
+    assert!(
+        check_approver(&verified, &identity("alice@example.com", false))
+            .unwrap_err()
+            .to_string()
+            .contains("verified identity")
+    );
+}
+
+#[test]
+fn identity_config_parses_from_the_global_config() {
+    let config: GlobalConfig = serde_yaml::from_str(
+        r#"
+supervisor: {backend: api}
+identity:
+  provider: oidc
+  token_file: ~/.config/hookwise/id_token
+  jwks_file: ~/.config/hookwise/jwks.json
+  issuer: https://sso.example.com
+"#,
+    )
+    .unwrap();
+    let IdentityConfig::Oidc(oidc) = config.identity else {
+        panic!("expected oidc, got {:?}", config.identity);
+    };
+    assert_eq!(oidc.user_claim, "email");
+    assert_eq!(oidc.issuer.as_deref(), Some("https://sso.example.com"));
+
+    let config: GlobalConfig = serde_yaml::from_str("supervisor: {backend: api}").unwrap();
+    assert_eq!(config.identity, IdentityConfig::Env);
+    let config: GlobalConfig = serde_yaml::from_str(
+        "supervisor: {backend: api}\nidentity: {provider: command, command: [sso-whoami, --json]}",
+    )
+    .unwrap();
+    assert!(matches!(config.identity, IdentityConfig::Command(c) if c.timeout_secs == 5));
+}
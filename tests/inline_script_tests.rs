@@ -0,0 +1,169 @@
+//! Tests for inline interpreter scripts (`python -c`, `node -e`, ...).
+
+mod common;
+
+use std::sync::Arc;
+
+use hookwise::cascade::inline_script::{InlineScript, ScriptEffects};
+use hookwise::cascade::path_policy::PathPolicyEngine;
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::config::roles::{CompiledPathPolicy, PathPolicyConfig};
+use hookwise::decision::{Decision, DecisionRecord};
+use hookwise::session::SessionContext;
+
+fn effects(command: &str) -> ScriptEffects {
+    InlineScript::parse(command)
+        .unwrap_or_else(|| panic!("not an inline script: {command}"))
+        .effects()
+}
+
+fn bash(command: &str) -> CascadeInput {
+    let config = PathPolicyConfig {
+        allow_write: vec!["src/**".into()],
+        deny_write: vec!["tests/**".into()],
+        allow_read: vec!["**".into()],
+    };
+    let policy = CompiledPathPolicy::compile(&config, &[".env*".into()]).unwrap();
+    let session = SessionContext {
+        path_policy: Some(Arc::new(policy)),
+        ..common::session()
+    };
+    CascadeInput {
+        sanitized_input: command.into(),
+        ..common::input(session, "Bash", serde_json::json!({ "command": command }))
+    }
+}
+
+async fn evaluate(command: &str) -> Option<DecisionRecord> {
+    PathPolicyEngine::new()
+        .unwrap()
+        .evaluate(&bash(command))
+        .await
+        .unwrap()
+}
+
+async fn decide(command: &str) -> Option<Decision> {
+    evaluate(command).await.map(|r| r.decision)
+}
+
+#[test]
+fn parses_interpreters_and_inline_flags() {
+    let script =
+        InlineScript::parse(r#"/usr/bin/python3.12 -c "import os; os.remove('a')""#).unwrap();
+    assert_eq!(script.interpreter, "python");
+    assert_eq!(script.code, "import os; os.remove('a')");
+    assert_eq!(
+        InlineScript::parse("python3 -Bc 'pass'").unwrap().code,
+        "pass"
+    );
+    assert_eq!(
+        InlineScript::parse("node --eval='1+1'")
+            .unwrap()
+            .interpreter,
+        "node"
+    );
+    assert_eq!(
+        InlineScript::parse("perl -E 'say 1'").unwrap().interpreter,
+        "perl"
+    );
+    assert!(InlineScript::parse("python3 script.py -c x").is_none());
+    assert!(InlineScript::parse("cargo -c x").is_none());
+    assert!(InlineScript::parse("python3").is_none());
+}
+
+#[test]
+fn write_calls_yield_literal_targets() {
+    assert_eq!(
+        effects(r#"python -c "open('.env','w').write('x')""#).write_targets,
+        vec![".env"]
+    );
+    assert_eq!(
+        effects(r#"python3 -c "import shutil; shutil.move('a.txt', 'out/b.txt')""#).write_targets,
+        vec!["a.txt", "out/b.txt"]
+    );
+    assert_eq!(
+        effects(r#"node -e "fs.rmSync('src',{recursive:true})""#).write_targets,
+        vec!["src"]
+    );
+    assert_eq!(
+        effects(r#"node -e "require('fs').writeFileSync('dist/x.js', code)""#).write_targets,
+        vec!["dist/x.js"]
+    );
+    assert_eq!(
+        effects(r#"ruby -e "FileUtils.rm_rf('tmp/cache')""#).write_targets,
+        vec!["tmp/cache"]
+    );
+    assert_eq!(
+        effects(r#"perl -e 'open(my $fh, ">", "log/out.txt")'"#).write_targets,
+        vec!["log/out.txt"]
+    );
+    assert_eq!(
+        effects(r#"php -r "file_put_contents('public/index.php', '');""#).write_targets,
+        vec!["public/index.php"]
+    );
+}
+
+#[test]
+fn reads_are_not_writes() {
+    let python = effects(r#"python -c "print(open('config/app.yml').read())""#);
+    assert!(python.write_targets.is_empty());
+    assert_eq!(python.other_paths, vec!["config/app.yml"]);
+
+    let perl = effects(r#"perl -e 'open(my $fh, "<", "a/b.txt")'"#);
+    assert!(perl.write_targets.is_empty());
+}
+
+#[test]
+fn computed_paths_are_reported_as_dynamic() {
+    let rmtree = effects(r#"python -c "import shutil,sys; shutil.rmtree(sys.argv[1])""#);
+    assert!(rmtree.write_targets.is_empty());
+    assert_eq!(rmtree.dynamic_calls, vec!["shutil.rmtree(sys.argv[1])"]);
+
+    let f_string = effects(r#"python -c "open(f'{d}/x', 'w')""#);
+    assert_eq!(f_string.dynamic_calls, vec!["open(f'{d}/x', 'w')"]);
+}
+
+#[tokio::test]
+async fn inline_writes_are_checked_against_path_policy() {
+    assert_eq!(
+        decide(r#"python -c "open('.env','w').write('KEY=1')""#).await,
+        Some(Decision::Ask)
+    );
+    assert_eq!(
+        decide(r#"node -e "fs.rmSync('tests/unit',{recursive:true})""#).await,
+        Some(Decision::Deny)
+    );
+    assert_eq!(
+        decide(r#"python3 -c "open('src/gen.rs','w').write('')""#).await,
+        Some(Decision::Allow)
+    );
+    assert_eq!(decide(r#"python3 -c "print(1 + 1)""#).await, None);
+}
+
+#[tokio::test]
+async fn inline_scripts_touching_sensitive_paths_ask() {
+    let record = evaluate(r#"python -c "print(open('.env').read())""#)
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Ask);
+    assert!(record
+        .metadata
+        .reason
+        .contains("inline python script references sensitive path '.env'"));
+}
+
+#[tokio::test]
+async fn computed_inline_writes_ask() {
+    let record = evaluate(r#"cd /tmp && python -c "import os; os.remove(os.environ['F'])""#)
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Ask);
+    assert!(
+        record
+            .metadata
+            .reason
+            .contains("calls `os.remove(os.environ['F'])` on a computed path"),
+        "{}",
+        record.metadata.reason
+    );
+}
@@ -1,5 +1,9 @@
 //! Integration tests for IPC: socket server/client round-trip.
 
+#![cfg(feature = "serve")]
+
+mod common;
+
 use std::future::Future;
 use std::path::PathBuf;
 use std::pin::Pin;
@@ -47,11 +51,8 @@ fn ipc_response_serialization_roundtrip() {
     let response = IpcResponse {
         decision: Decision::Allow,
         metadata: DecisionMetadata {
-            tier: DecisionTier::Supervisor,
             confidence: 0.95,
-            reason: "looks safe".into(),
-            matched_key: None,
-            similarity_score: None,
+            ..common::metadata(DecisionTier::Supervisor, "looks safe")
         },
     };
 
@@ -98,11 +99,8 @@ async fn ipc_server_client_roundtrip() {
                 Ok(IpcResponse {
                     decision: Decision::Allow,
                     metadata: DecisionMetadata {
-                        tier: DecisionTier::Supervisor,
                         confidence: 0.9,
-                        reason: "test approved".into(),
-                        matched_key: None,
-                        similarity_score: None,
+                        ..common::metadata(DecisionTier::Supervisor, "test approved")
                     },
                 })
             })
@@ -152,11 +150,8 @@ async fn ipc_server_handles_deny_response() {
                 Ok(IpcResponse {
                     decision: Decision::Deny,
                     metadata: DecisionMetadata {
-                        tier: DecisionTier::Supervisor,
                         confidence: 0.99,
-                        reason: "dangerous operation".into(),
-                        matched_key: None,
-                        similarity_score: None,
+                        ..common::metadata(DecisionTier::Supervisor, "dangerous operation")
                     },
                 })
             })
@@ -202,11 +197,8 @@ async fn ipc_server_handles_ask_response() {
                 Ok(IpcResponse {
                     decision: Decision::Ask,
                     metadata: DecisionMetadata {
-                        tier: DecisionTier::Supervisor,
                         confidence: 0.5,
-                        reason: "needs human review".into(),
-                        matched_key: None,
-                        similarity_score: None,
+                        ..common::metadata(DecisionTier::Supervisor, "needs human review")
                     },
                 })
             })
@@ -273,11 +265,11 @@ async fn ipc_multiple_sequential_requests() {
             Ok(IpcResponse {
                 decision: Decision::Allow,
                 metadata: DecisionMetadata {
-                    tier: DecisionTier::Supervisor,
                     confidence: 0.9,
-                    reason: format!("approved {}", req.tool_name),
-                    matched_key: None,
-                    similarity_score: None,
+                    ..common::metadata(
+                        DecisionTier::Supervisor,
+                        &format!("approved {}", req.tool_name),
+                    )
                 },
             })
         })
@@ -314,6 +306,29 @@ async fn ipc_multiple_sequential_requests() {
     let _ = std::fs::remove_file(&socket_path);
 }
 
+#[tokio::test]
+async fn ipc_nonexistent_socket_is_socket_not_found() {
+    use hookwise::ipc::transport;
+    use hookwise::HookwiseError;
+
+    let tmp = TempDir::new().unwrap();
+    let result = transport::connect(&tmp.path().join("missing.sock")).await;
+    assert!(matches!(result, Err(HookwiseError::SocketNotFound { .. })));
+}
+
+#[test]
+fn pipe_name_uses_socket_file_stem() {
+    use hookwise::ipc::transport::pipe_name;
+    use std::path::Path;
+
+    assert_eq!(pipe_name(Path::new("/tmp/hookwise-solo.sock")), r"\\.\pipe\hookwise-solo");
+    assert_eq!(
+        pipe_name(Path::new(r"C:\Users\dev\AppData\Local\Temp\hookwise-team.sock")),
+        r"\\.\pipe\hookwise-team"
+    );
+    assert_eq!(pipe_name(Path::new("/")), r"\\.\pipe\hookwise");
+}
+
 // ---------------------------------------------------------------------------
 // Pending queue serialization
 // ---------------------------------------------------------------------------
@@ -336,6 +351,13 @@ fn pending_queue_serialization_roundtrip() {
             is_ask_reprompt: false,
             ask_reason: None,
             queued_at: Utc::now(),
+            priority: Default::default(),
+            waiter_pid: None,
+            heartbeat_at: None,
+            attachment: None,
+            suggestion: None,
+            risk_score: None,
+            project_root: None,
         },
         PendingDecision {
             id: "id-2".into(),
@@ -348,6 +370,13 @@ fn pending_queue_serialization_roundtrip() {
             is_ask_reprompt: true,
             ask_reason: Some("sensitive path".into()),
             queued_at: Utc::now(),
+            priority: Default::default(),
+            waiter_pid: None,
+            heartbeat_at: None,
+            attachment: None,
+            suggestion: None,
+            risk_score: None,
+            project_root: None,
         },
     ];
 
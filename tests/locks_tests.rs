@@ -0,0 +1,181 @@
+//! Tests for path-range write locks shared between sessions.
+
+mod common;
+
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use hookwise::cascade::locks::PathLocks;
+use hookwise::cascade::{CascadeInput, CascadeRunner};
+use hookwise::config::roles::PathPolicyConfig;
+use hookwise::config::{LockConfig, PolicyConfig};
+use hookwise::decision::{Decision, DecisionTier};
+use hookwise::session::state::SessionStateStore;
+use hookwise::session::SessionContext;
+
+fn config(yaml: &str) -> LockConfig {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+fn store(tmp: &TempDir) -> SessionStateStore {
+    SessionStateStore::new(tmp.path().join("session-state.json"))
+}
+
+fn locks(tmp: &TempDir, session_id: &str) -> PathLocks {
+    PathLocks::compile(
+        &config("enabled: true\nttl_secs: 300\nranges: ['src/api/**', '{{tests}}']"),
+        &HashMap::new(),
+        store(tmp),
+        session_id,
+    )
+    .unwrap()
+    .unwrap()
+}
+
+fn session(role: &str) -> SessionContext {
+    let paths = PathPolicyConfig {
+        allow_write: vec!["src/**".into(), "tests/**".into()],
+        deny_write: vec!["infra/**".into()],
+        allow_read: vec!["**".into()],
+    };
+    common::session_as(role, paths, &[])
+}
+
+fn write(role: &str, file_path: &str) -> CascadeInput {
+    CascadeInput {
+        sanitized_input: file_path.into(),
+        ..common::input(
+            session(role),
+            "Write",
+            serde_json::json!({ "file_path": file_path }),
+        )
+    }
+}
+
+#[test]
+fn targets_lock_their_range_or_themselves() {
+    let tmp = TempDir::new().unwrap();
+    let locks = locks(&tmp, "s1");
+    assert_eq!(
+        locks.write_ranges(&write("coder", "src/api/users.rs")),
+        vec!["src/api/**"]
+    );
+    assert_eq!(
+        locks.write_ranges(&write("tester", "tests/api_tests.rs")),
+        vec!["{{tests}}"]
+    );
+    assert_eq!(
+        locks.write_ranges(&write("coder", "src/lib.rs")),
+        vec!["src/lib.rs"]
+    );
+    let read = CascadeInput {
+        tool_name: "Read".into(),
+        ..write("coder", "src/lib.rs")
+    };
+    assert!(locks.write_ranges(&read).is_empty());
+}
+
+#[test]
+fn other_sessions_conflict_until_the_lock_expires() {
+    let tmp = TempDir::new().unwrap();
+    let (coder, tester) = (locks(&tmp, "s1"), locks(&tmp, "s2"));
+    let now = Utc::now();
+    coder
+        .acquire(&write("coder", "src/api/users.rs"), now)
+        .unwrap();
+
+    // The holder itself, and other ranges, are free
+    let same_range = write("tester", "src/api/orders.rs");
+    assert_eq!(coder.conflict(&same_range, now).unwrap(), None);
+    assert_eq!(
+        tester
+            .conflict(&write("tester", "src/lib.rs"), now)
+            .unwrap(),
+        None
+    );
+
+    let reason = tester.conflict(&same_range, now).unwrap().unwrap();
+    assert_eq!(
+        reason,
+        "'src/api/**' locked by session s1 (coder) for another 5m"
+    );
+
+    let later = now + Duration::seconds(301);
+    assert_eq!(tester.conflict(&same_range, later).unwrap(), None);
+    // Once expired, the other session can take the range
+    tester.acquire(&same_range, later).unwrap();
+    assert!(coder
+        .conflict(&write("coder", "src/api/users.rs"), later)
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn compile_is_off_by_default_and_checks_macros() {
+    let tmp = TempDir::new().unwrap();
+    let compile =
+        |yaml: &str| PathLocks::compile(&config(yaml), &HashMap::new(), store(&tmp), "s1");
+    assert!(compile("{}").unwrap().is_none());
+    assert!(compile("ranges: ['src/**']").unwrap().is_none());
+    let err = compile("enabled: true\nranges: ['{{nope}}']")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("locks.ranges[0]"), "{err}");
+    assert!(!PolicyConfig::default().locks.enabled);
+    assert_eq!(PolicyConfig::default().locks.ttl_secs, 300);
+}
+
+fn runner(tmp: &TempDir, session_id: &str) -> CascadeRunner {
+    CascadeRunner {
+        locks: Some(locks(tmp, session_id)),
+        ..common::runner(
+            tmp.path(),
+            Box::new(common::Pass(DecisionTier::Supervisor)),
+            Box::new(common::Pass(DecisionTier::Human)),
+        )
+    }
+}
+
+#[tokio::test]
+async fn cascade_locks_allowed_writes_and_asks_other_sessions() {
+    let tmp = TempDir::new().unwrap();
+    let (coder, tester) = (runner(&tmp, "s1"), runner(&tmp, "s2"));
+    let edit = serde_json::json!({ "file_path": "src/api/users.rs" });
+
+    let record = coder
+        .evaluate(&session("coder"), "Write", &edit)
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Allow);
+    assert_eq!(record.metadata.tier, DecisionTier::PathPolicy);
+    assert!(store(&tmp).load("s1").unwrap().path_locks[0].range == "src/api/**");
+
+    let cached = tester.exact_cache.stats().total_entries;
+    let record = tester
+        .evaluate(&session("tester"), "Write", &edit)
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Ask);
+    assert_eq!(record.metadata.tier, DecisionTier::PathLock);
+    assert!(
+        record
+            .metadata
+            .reason
+            .contains("locked by session s1 (coder)"),
+        "{}",
+        record.metadata.reason
+    );
+    assert_eq!(tester.exact_cache.stats().total_entries, cached);
+    // An ask takes no lock
+    assert!(store(&tmp).load("s2").unwrap().path_locks.is_empty());
+
+    // The holder keeps writing
+    let record = coder
+        .evaluate(&session("coder"), "Write", &edit)
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Allow);
+}
@@ -0,0 +1,196 @@
+//! Tests for the machine policy layered over project policy: which keys it
+//! pins, which lists it unions, and the origin recorded for each setting.
+
+use tempfile::TempDir;
+
+use hookwise::config::{AutoTier, LayeredPolicy, PolicyConfig, PolicyOrigin};
+
+struct Fixture {
+    _tmp: TempDir,
+    project: std::path::PathBuf,
+    machine: std::path::PathBuf,
+}
+
+fn fixture(project: Option<&str>, machine: Option<&str>) -> Fixture {
+    let tmp = TempDir::new().unwrap();
+    let project_path = tmp.path().join("project/.hookwise/policy.yml");
+    let machine_path = tmp.path().join("etc/hookwise/policy.yml");
+    for (path, contents) in [(&project_path, project), (&machine_path, machine)] {
+        if let Some(contents) = contents {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+    }
+    Fixture {
+        _tmp: tmp,
+        project: project_path,
+        machine: machine_path,
+    }
+}
+
+fn load(f: &Fixture) -> LayeredPolicy {
+    LayeredPolicy::load(&f.project, &f.machine).unwrap()
+}
+
+#[test]
+fn without_a_machine_policy_the_project_policy_is_unchanged() {
+    let f = fixture(Some("human_timeout_secs: 90\n"), None);
+    let layered = load(&f);
+    assert_eq!(layered.machine_path, None);
+    assert_eq!(layered.policy.human_timeout_secs, 90);
+    assert_eq!(
+        layered.policy.git.protected_branches,
+        PolicyConfig::default().git.protected_branches
+    );
+    assert_eq!(
+        layered.origins.get("human_timeout_secs"),
+        Some(&PolicyOrigin::Project)
+    );
+    assert_eq!(layered.origins.len(), 1);
+}
+
+#[test]
+fn machine_settings_win_and_merge_key_by_key() {
+    let f = fixture(
+        Some(
+            r#"
+human_timeout_secs: 600
+confidence:
+  org: 0.1
+  project: 0.2
+  user: 0.3
+"#,
+        ),
+        Some(
+            r#"
+human_timeout_secs: 30
+confidence:
+  org: 0.99
+"#,
+        ),
+    );
+    let layered = load(&f);
+    assert_eq!(layered.machine_path.as_deref(), Some(f.machine.as_path()));
+    assert_eq!(layered.policy.human_timeout_secs, 30);
+    assert_eq!(layered.policy.confidence.org, 0.99);
+    // Keys the machine policy leaves alone stay the project's
+    assert_eq!(layered.policy.confidence.project, 0.2);
+
+    assert_eq!(
+        layered.origins.get("human_timeout_secs"),
+        Some(&PolicyOrigin::MachineOverride)
+    );
+    assert_eq!(
+        layered.origins.get("confidence.org"),
+        Some(&PolicyOrigin::MachineOverride)
+    );
+    assert_eq!(
+        layered.origins.get("confidence.project"),
+        Some(&PolicyOrigin::Project)
+    );
+    assert_eq!(layered.origins.get("similarity.jaccard_threshold"), None);
+}
+
+#[test]
+fn restrictive_lists_are_unioned_machine_first() {
+    let f = fixture(
+        Some(
+            r#"
+sensitive_paths:
+  ask_write: ["secrets/**"]
+git:
+  protected_branches: ["develop"]
+"#,
+        ),
+        Some(
+            r#"
+sensitive_paths:
+  ask_write: ["/etc/**", "secrets/**"]
+git:
+  protected_branches: ["release/*"]
+"#,
+        ),
+    );
+    let layered = load(&f);
+    assert_eq!(
+        layered.policy.sensitive_paths.ask_write,
+        vec!["/etc/**".to_string(), "secrets/**".to_string()]
+    );
+    assert_eq!(
+        layered.policy.git.protected_branches,
+        vec!["release/*".to_string(), "develop".to_string()]
+    );
+    assert_eq!(
+        layered.origins.get("git.protected_branches"),
+        Some(&PolicyOrigin::Merged)
+    );
+}
+
+#[test]
+fn unions_keep_the_defaults_a_project_never_listed() {
+    let f = fixture(None, Some("git:\n  protected_branches: [\"release/*\"]\n"));
+    let layered = load(&f);
+    assert_eq!(
+        layered.policy.git.protected_branches,
+        vec![
+            "release/*".to_string(),
+            "main".to_string(),
+            "master".to_string()
+        ]
+    );
+    assert_eq!(
+        layered.origins.get("git.protected_branches"),
+        Some(&PolicyOrigin::Machine)
+    );
+}
+
+#[test]
+fn a_project_cannot_raise_a_machine_tier_cap() {
+    let f = fixture(
+        Some(
+            r#"
+max_auto_tier:
+  "deploy/**": supervisor
+  "docs/**": supervisor
+"#,
+        ),
+        Some(
+            r#"
+max_auto_tier:
+  "deploy/**": exact_cache
+"#,
+        ),
+    );
+    let layered = load(&f);
+    let caps = &layered.policy.max_auto_tier;
+    assert_eq!(caps.get("deploy/**"), Some(&AutoTier::ExactCache));
+    assert_eq!(caps.get("docs/**"), Some(&AutoTier::Supervisor));
+    // One setting, not one per pattern
+    assert_eq!(
+        layered.origins.get("max_auto_tier"),
+        Some(&PolicyOrigin::Merged)
+    );
+    assert!(!layered.origins.keys().any(|k| k.contains("deploy")));
+}
+
+#[test]
+fn an_empty_machine_policy_applies_nothing() {
+    let f = fixture(Some("human_timeout_secs: 90\n"), Some(""));
+    let layered = load(&f);
+    assert_eq!(layered.machine_path.as_deref(), Some(f.machine.as_path()));
+    assert_eq!(layered.policy.human_timeout_secs, 90);
+    assert_eq!(
+        layered.origins.get("human_timeout_secs"),
+        Some(&PolicyOrigin::Project)
+    );
+}
+
+#[test]
+fn an_invalid_machine_policy_is_an_error() {
+    let f = fixture(None, Some("human_timeout_secs: soon\n"));
+    let err = LayeredPolicy::load(&f.project, &f.machine).unwrap_err();
+    assert!(
+        err.to_string().contains(&f.machine.display().to_string()),
+        "{err}"
+    );
+}
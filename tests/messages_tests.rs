@@ -0,0 +1,235 @@
+//! Tests for policy authors' deny/ask message templates.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tempfile::TempDir;
+
+use hookwise::cascade::human::{PendingDecision, Priority};
+use hookwise::cascade::messages::{render_template, MessageTemplates};
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::config::MessageRule;
+use hookwise::decision::{CacheKey, Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::error::HookwiseError;
+use hookwise::hook_io::hook_reason;
+use hookwise::notify::{Notification, NotifyKind};
+
+/// A tier that always decides `decision`.
+struct Fixed {
+    tier: DecisionTier,
+    decision: Decision,
+}
+
+#[async_trait]
+impl CascadeTier for Fixed {
+    async fn evaluate(
+        &self,
+        input: &CascadeInput,
+    ) -> hookwise::error::Result<Option<DecisionRecord>> {
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: "*".into(),
+            },
+            decision: self.decision,
+            metadata: common::metadata(self.tier, "looks destructive"),
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: input.file_path.clone(),
+            session_id: String::new(),
+        }))
+    }
+    fn tier(&self) -> DecisionTier {
+        self.tier
+    }
+    fn name(&self) -> &str {
+        "fixed"
+    }
+}
+
+fn write_input(file_path: &str) -> CascadeInput {
+    CascadeInput {
+        sanitized_input: file_path.into(),
+        ..common::input(
+            common::session(),
+            "Write",
+            serde_json::json!({ "file_path": file_path }),
+        )
+    }
+}
+
+fn rule(paths: &[&str]) -> MessageRule {
+    MessageRule {
+        paths: paths.iter().map(|p| p.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn templates(rules: Vec<MessageRule>) -> MessageTemplates {
+    MessageTemplates::compile(&rules, &HashMap::new())
+        .unwrap()
+        .unwrap()
+}
+
+fn infra_templates() -> MessageTemplates {
+    templates(vec![MessageRule {
+        deny: Some("Writes to {{path}} require a ticket ({{tool}}: {{reason}})".into()),
+        ask: Some("{{path}} needs a second pair of eyes".into()),
+        ..rule(&["infra/**"])
+    }])
+}
+
+#[test]
+fn render_template_substitutes_known_variables() {
+    assert_eq!(
+        render_template(
+            "{{tool}} on {{path}} is {{decision}}",
+            &[("tool", "Write"), ("path", "a.txt"), ("decision", "deny")]
+        ),
+        "Write on a.txt is deny"
+    );
+    assert_eq!(
+        render_template("{{other}} and {{ unclosed", &[("other", "x")]),
+        "x and {{ unclosed"
+    );
+    assert_eq!(render_template("{{missing}}", &[]), "{{missing}}");
+}
+
+#[test]
+fn compile_rejects_unknown_variables_and_categories() {
+    let err = MessageTemplates::compile(
+        &[MessageRule {
+            message: Some("see {{ticket}}".into()),
+            ..rule(&["**"])
+        }],
+        &HashMap::new(),
+    )
+    .err()
+    .unwrap();
+    assert!(
+        matches!(&err, HookwiseError::ConfigParse { reason, .. }
+            if reason.contains("messages[0]") && reason.contains("{{ticket}}")),
+        "{err}"
+    );
+
+    assert!(MessageTemplates::compile(&[rule(&["{{nope}}"])], &HashMap::new()).is_err());
+    assert!(MessageTemplates::compile(&[], &HashMap::new())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn picks_the_template_for_the_decision() {
+    let messages = infra_templates();
+    let input = write_input("infra/main.tf");
+    assert_eq!(
+        messages
+            .render(&input, Decision::Deny, "protected")
+            .as_deref(),
+        Some("Writes to infra/main.tf require a ticket (Write: protected)")
+    );
+    assert_eq!(
+        messages
+            .render(&input, Decision::Ask, "protected")
+            .as_deref(),
+        Some("infra/main.tf needs a second pair of eyes")
+    );
+    assert_eq!(messages.render(&input, Decision::Allow, "ok"), None);
+    assert_eq!(
+        messages.render(&write_input("src/lib.rs"), Decision::Deny, "x"),
+        None
+    );
+}
+
+#[test]
+fn message_is_the_fallback_and_first_matching_rule_wins() {
+    let messages = templates(vec![
+        MessageRule {
+            message: Some("{{decision}}: ask #infra".into()),
+            ..rule(&["infra/**"])
+        },
+        MessageRule {
+            message: Some("never used for infra".into()),
+            ..rule(&["**"])
+        },
+    ]);
+    let input = write_input("infra/main.tf");
+    assert_eq!(
+        messages.render(&input, Decision::Deny, "").as_deref(),
+        Some("deny: ask #infra")
+    );
+    assert_eq!(
+        messages.render(&input, Decision::Ask, "").as_deref(),
+        Some("ask: ask #infra")
+    );
+}
+
+#[tokio::test]
+async fn runner_attaches_message_but_does_not_store_it() {
+    let tmp = TempDir::new().unwrap();
+    let runner = CascadeRunner {
+        messages: Some(Arc::new(infra_templates())),
+        ..common::runner(
+            tmp.path(),
+            Box::new(Fixed {
+                tier: DecisionTier::Supervisor,
+                decision: Decision::Deny,
+            }),
+            Box::new(Fixed {
+                tier: DecisionTier::Human,
+                decision: Decision::Ask,
+            }),
+        )
+    };
+
+    let input = write_input("infra/main.tf");
+    let record = runner
+        .evaluate(&input.session, &input.tool_name, &input.tool_input)
+        .await
+        .unwrap();
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(
+        record.metadata.message.as_deref(),
+        Some("Writes to infra/main.tf require a ticket (Write: looks destructive)")
+    );
+    assert_eq!(hook_reason(&record), record.metadata.message);
+
+    let stored = CascadeInput {
+        sanitized_input: record.key.sanitized_input.clone(),
+        ..input
+    };
+    let cached = runner.exact_cache.evaluate(&stored).await.unwrap().unwrap();
+    assert_eq!(cached.metadata.message, None);
+}
+
+#[test]
+fn notification_includes_the_ask_reason() {
+    let pending = PendingDecision {
+        id: "coder-Write-1700000000000".into(),
+        session_id: "session-1".into(),
+        role: "coder".into(),
+        tool_name: "Write".into(),
+        sanitized_input: r#"{"file_path":"infra/main.tf"}"#.into(),
+        file_path: Some("infra/main.tf".into()),
+        recommendation: None,
+        is_ask_reprompt: false,
+        ask_reason: Some("infra/main.tf needs a second pair of eyes".into()),
+        queued_at: Utc::now(),
+        priority: Priority::Normal,
+        waiter_pid: None,
+        heartbeat_at: None,
+        attachment: None,
+        suggestion: None,
+        risk_score: None,
+        project_root: None,
+    };
+    let n = Notification::for_pending(&pending, &NotifyKind::Queued);
+    assert!(n
+        .body
+        .contains("Reason: infra/main.tf needs a second pair of eyes"));
+}
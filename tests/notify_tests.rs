@@ -0,0 +1,295 @@
+//! Tests for push notification rendering, delivery, and escalation.
+
+mod common;
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use hookwise::cascade::human::{DecisionQueue, HumanTier, PendingDecision, Priority};
+use hookwise::cascade::CascadeTier;
+use hookwise::config::{
+    EscalationConfig, EscalationStage, NotificationChannel, NotificationConfig, PolicyConfig,
+};
+use hookwise::error::HookwiseError;
+use hookwise::notify::{ntfy_priority, pushover_priority, Notification, Notifier, NotifyKind};
+use hookwise::session::{Exclusion, SessionContext};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+fn make_pending(priority: Priority) -> PendingDecision {
+    PendingDecision {
+        id: "coder-Write-1700000000000".into(),
+        session_id: "session-1".into(),
+        role: "coder".into(),
+        tool_name: "Write".into(),
+        sanitized_input: r#"{"file_path":"src/main.rs"}"#.into(),
+        file_path: Some("src/main.rs".into()),
+        recommendation: None,
+        is_ask_reprompt: false,
+        ask_reason: None,
+        queued_at: Utc::now(),
+        priority,
+        waiter_pid: None,
+        heartbeat_at: None,
+        attachment: None,
+        suggestion: None,
+        risk_score: None,
+        project_root: None,
+    }
+}
+
+/// Accept one HTTP request on a local listener and return it as text.
+async fn capture_one_request(listener: TcpListener) -> String {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut buf = vec![0u8; 8192];
+    let mut request = String::new();
+    loop {
+        let n = stream.read(&mut buf).await.unwrap();
+        request.push_str(&String::from_utf8_lossy(&buf[..n]));
+        if let Some(header_end) = request.find("\r\n\r\n") {
+            let content_length = request[..header_end]
+                .lines()
+                .find_map(|l| {
+                    l.to_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(|v| v.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if request.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+        if n == 0 {
+            break;
+        }
+    }
+    stream
+        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+        .await
+        .unwrap();
+    request
+}
+
+#[test]
+fn notification_includes_decision_id() {
+    let n = Notification::for_pending(&make_pending(Priority::High), &NotifyKind::Queued);
+    assert!(n.title.contains("coder"));
+    assert!(n.title.contains("Write"));
+    assert!(n.body.contains("ID: coder-Write-1700000000000"));
+    assert!(n
+        .body
+        .contains("hookwise approve coder-Write-1700000000000"));
+    assert!(n.body.contains("File: src/main.rs"));
+}
+
+#[test]
+fn overdue_notification_mentions_reminder() {
+    let n = Notification::for_pending(&make_pending(Priority::High), &NotifyKind::Overdue(2));
+    assert!(n.title.contains("OVERDUE"));
+    assert!(n.title.contains("#2"));
+}
+
+#[test]
+fn disabled_reminder_says_for_how_long_and_how_to_re_enable() {
+    let now = Utc::now();
+    let exclusion = Exclusion {
+        session_id: "session-1".into(),
+        disabled_at: Some(now - chrono::Duration::minutes(125)),
+        disabled_by: Some("alice".into()),
+        reason: Some("bisecting".into()),
+        ..Exclusion::default()
+    };
+    let n = Notification::for_disabled(&exclusion, now);
+    assert_eq!(
+        n.title,
+        "hookwise: session session-1 still disabled for 2h 5m"
+    );
+    assert!(n.body.contains("Disabled by: alice\nReason: bisecting\n"));
+    assert!(n.body.contains("Re-enables: never"));
+    assert!(n.body.ends_with("hookwise enable --session-id session-1"));
+
+    let timed = Exclusion {
+        until: Some(now + chrono::Duration::minutes(10)),
+        ..exclusion
+    };
+    assert!(Notification::for_disabled(&timed, now)
+        .body
+        .contains("Re-enables at: "));
+}
+
+#[test]
+fn priority_mappings_are_monotonic() {
+    assert!(ntfy_priority(Priority::Low) < ntfy_priority(Priority::Critical));
+    assert_eq!(ntfy_priority(Priority::Critical), 5);
+    assert_eq!(pushover_priority(Priority::Low), -1);
+    assert_eq!(pushover_priority(Priority::Critical), 1);
+}
+
+#[test]
+fn notifier_disabled_without_channels() {
+    assert!(Notifier::from_config(&NotificationConfig::default()).is_none());
+}
+
+#[test]
+fn notification_config_parses_from_policy_yaml() {
+    let yaml = r#"
+notifications:
+  min_priority: high
+  channels:
+    - provider: ntfy
+      topic: my-approvals
+    - provider: pushover
+      device: phone
+"#;
+    let policy: PolicyConfig = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(policy.notifications.min_priority, Priority::High);
+    assert_eq!(policy.notifications.channels.len(), 2);
+    match &policy.notifications.channels[0] {
+        NotificationChannel::Ntfy { server, topic, .. } => {
+            assert_eq!(server, "https://ntfy.sh");
+            assert_eq!(topic, "my-approvals");
+        }
+        other => panic!("expected ntfy channel, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn ntfy_delivery_posts_to_topic() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(capture_one_request(listener));
+
+    let config = NotificationConfig {
+        channels: vec![NotificationChannel::Ntfy {
+            server: format!("http://{}", addr),
+            topic: "approvals".into(),
+            token: Some("tk_test".into()),
+        }],
+        min_priority: Priority::Low,
+    };
+    let notifier = Notifier::from_config(&config).unwrap();
+    notifier
+        .notify_pending(&make_pending(Priority::Critical), NotifyKind::Queued)
+        .await;
+
+    let request = server.await.unwrap();
+    assert!(request.starts_with("POST /approvals "));
+    let lower = request.to_lowercase();
+    assert!(lower.contains("priority: 5"));
+    assert!(lower.contains("authorization: bearer tk_test"));
+    assert!(request.contains("ID: coder-Write-1700000000000"));
+}
+
+#[tokio::test]
+async fn notifications_below_min_priority_are_skipped() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = NotificationConfig {
+        channels: vec![NotificationChannel::Ntfy {
+            server: format!("http://{}", addr),
+            topic: "approvals".into(),
+            token: None,
+        }],
+        min_priority: Priority::High,
+    };
+    let notifier = Notifier::from_config(&config).unwrap();
+    notifier
+        .notify_pending(&make_pending(Priority::Normal), NotifyKind::Queued)
+        .await;
+
+    let accepted =
+        tokio::time::timeout(std::time::Duration::from_millis(200), listener.accept()).await;
+    assert!(accepted.is_err(), "no request should have been sent");
+}
+
+// ---------------------------------------------------------------------------
+// Escalation chain
+// ---------------------------------------------------------------------------
+
+#[test]
+fn escalation_total_timeout_sums_stages() {
+    let yaml = r#"
+escalation:
+  stages:
+    - name: primary
+      timeout_secs: 60
+      channels:
+        - provider: ntfy
+          topic: alice
+    - name: secondary
+      timeout_secs: 120
+"#;
+    let policy: PolicyConfig = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(policy.escalation.stages.len(), 2);
+    assert_eq!(policy.escalation.total_timeout_secs(), Some(180));
+    assert_eq!(
+        PolicyConfig::default().escalation.total_timeout_secs(),
+        None
+    );
+}
+
+#[test]
+fn escalated_notification_names_stage() {
+    let n = Notification::for_pending(
+        &make_pending(Priority::Normal),
+        &NotifyKind::Escalated("secondary".into()),
+    );
+    assert!(n.title.contains("ESCALATED to secondary"));
+}
+
+#[tokio::test]
+async fn escalation_notifies_each_stage_then_times_out() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("XDG_RUNTIME_DIR", runtime_dir.path());
+
+    let primary = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let secondary = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let ntfy = |addr: std::net::SocketAddr| NotificationChannel::Ntfy {
+        server: format!("http://{}", addr),
+        topic: "approvals".into(),
+        token: None,
+    };
+    let config = EscalationConfig {
+        stages: vec![
+            EscalationStage {
+                name: "primary".into(),
+                timeout_secs: 1,
+                channels: vec![ntfy(primary.local_addr().unwrap())],
+                min_risk: None,
+            },
+            EscalationStage {
+                name: "secondary".into(),
+                timeout_secs: 1,
+                channels: vec![ntfy(secondary.local_addr().unwrap())],
+                min_risk: None,
+            },
+        ],
+    };
+    let primary_req = tokio::spawn(capture_one_request(primary));
+    let secondary_req = tokio::spawn(capture_one_request(secondary));
+
+    // The tier's own timeout is overridden by the chain's total.
+    let tier = HumanTier::new(Arc::new(DecisionQueue::new()), 600).with_escalation(&config);
+    let input = common::input(
+        SessionContext {
+            registered_at: Some(Utc::now()),
+            ..common::session()
+        },
+        "Bash",
+        serde_json::json!({"command": "make deploy"}),
+    );
+
+    let start = std::time::Instant::now();
+    let result = tier.evaluate(&input).await;
+    assert!(matches!(
+        result,
+        Err(HookwiseError::HumanTimeout { timeout_secs: 2 })
+    ));
+    assert!(start.elapsed() >= std::time::Duration::from_secs(2));
+
+    let primary_req = primary_req.await.unwrap();
+    assert!(!primary_req.contains("ESCALATED"));
+    let secondary_req = secondary_req.await.unwrap();
+    assert!(secondary_req.contains("ESCALATED to secondary"));
+}
@@ -0,0 +1,396 @@
+//! Tests for the org scope in an S3-compatible bucket: refreshing the
+//! local cache with signed, conditional GETs, keeping the cached rules when
+//! the bucket is unreachable or invalid, and publishing maintainers'
+//! org-scope decisions with conditional PUTs.
+
+mod common;
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tempfile::TempDir;
+
+use hookwise::config::OrgStoreConfig;
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::identity::Identity;
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::remote::{OrgStore, RemoteOrgStorage};
+use hookwise::storage::StorageBackend;
+use hookwise::sync::{self, RuleChange};
+
+const BUCKET: &str = "team-rules";
+
+fn record(command: &str, decision: Decision, scope: ScopeLevel) -> DecisionRecord {
+    DecisionRecord {
+        metadata: common::metadata(DecisionTier::Human, "org rule"),
+        scope,
+        ..common::record(command, "*", decision)
+    }
+}
+
+fn line(command: &str, decision: Decision) -> String {
+    serde_json::to_string(&record(command, decision, ScopeLevel::Org)).unwrap() + "\n"
+}
+
+/// An in-memory bucket answering S3 GET, PUT and DELETE by path, with
+/// ETags and conditional requests.
+#[derive(Clone, Default)]
+struct Bucket {
+    /// Contents and ETag by request path.
+    objects: Arc<Mutex<HashMap<String, (String, String)>>>,
+    /// Request heads received, lowercased.
+    requests: Arc<Mutex<Vec<String>>>,
+    versions: Arc<AtomicUsize>,
+}
+
+impl Bucket {
+    /// Serve the bucket on a local port, returning its endpoint.
+    fn serve(&self) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let bucket = self.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                bucket.answer(stream);
+            }
+        });
+        endpoint
+    }
+
+    fn path(key: &str) -> String {
+        format!("/{}/hookwise/acme/{}", BUCKET, key)
+    }
+
+    fn next_etag(&self) -> String {
+        format!("\"v{}\"", self.versions.fetch_add(1, Ordering::SeqCst))
+    }
+
+    fn put(&self, key: &str, contents: &str) {
+        let etag = self.next_etag();
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(Self::path(key), (contents.into(), etag));
+    }
+
+    fn remove(&self, key: &str) {
+        self.objects.lock().unwrap().remove(&Self::path(key));
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(&Self::path(key))
+            .map(|(contents, _)| contents.clone())
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn answer(&self, mut stream: TcpStream) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 {
+                return;
+            }
+            if line == "\r\n" {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let lower = head.to_lowercase();
+        let header = |name: &str| {
+            lower
+                .lines()
+                .find_map(|l| l.strip_prefix(&format!("{}: ", name)))
+                .map(|v| v.trim().to_string())
+        };
+        let length = header("content-length")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        self.requests.lock().unwrap().push(lower.clone());
+
+        let mut request_line = head.split_whitespace();
+        let method = request_line.next().unwrap().to_string();
+        let path = request_line.next().unwrap().to_string();
+        let mut objects = self.objects.lock().unwrap();
+        let current = objects.get(&path).cloned();
+        let (status, etag, contents) = match method.as_str() {
+            "GET" => match current {
+                None => ("404 Not Found", None, String::new()),
+                Some((_, etag)) if header("if-none-match").as_ref() == Some(&etag) => {
+                    ("304 Not Modified", Some(etag), String::new())
+                }
+                Some((contents, etag)) => ("200 OK", Some(etag), contents),
+            },
+            "PUT" | "DELETE" => {
+                let current_etag = current.map(|(_, etag)| etag);
+                let met = match (header("if-match"), header("if-none-match")) {
+                    (Some(etag), _) => current_etag == Some(etag),
+                    (None, Some(_)) => current_etag.is_none(),
+                    (None, None) => true,
+                };
+                if !met {
+                    ("412 Precondition Failed", None, String::new())
+                } else if method == "PUT" {
+                    let etag = self.next_etag();
+                    objects.insert(path, (String::from_utf8(body).unwrap(), etag.clone()));
+                    ("200 OK", Some(etag), String::new())
+                } else {
+                    objects.remove(&path);
+                    ("204 No Content", None, String::new())
+                }
+            }
+            _ => ("405 Method Not Allowed", None, String::new()),
+        };
+        drop(objects);
+        let etag = etag.map(|e| format!("etag: {}\r\n", e)).unwrap_or_default();
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\n{}content-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            etag,
+            contents.len(),
+            contents
+        )
+        .unwrap();
+    }
+}
+
+fn config(endpoint: &str, writable: bool) -> OrgStoreConfig {
+    OrgStoreConfig {
+        endpoint: endpoint.into(),
+        bucket: BUCKET.into(),
+        prefix: "hookwise/".into(),
+        region: "us-east-1".into(),
+        access_key_env: "HOOKWISE_TEST_NO_ACCESS_KEY".into(),
+        secret_key_env: "HOOKWISE_TEST_NO_SECRET_KEY".into(),
+        refresh_secs: 300,
+        timeout_secs: 2,
+        writable,
+    }
+}
+
+fn store(endpoint: &str, rules_root: &Path, writable: bool) -> OrgStore {
+    OrgStore::new(config(endpoint, writable), rules_root, "acme").with_credentials("AKID", "secret")
+}
+
+fn identity(user: &str) -> Identity {
+    Identity {
+        user: user.into(),
+        org: Some("acme".into()),
+        provider: "env".into(),
+        verified: false,
+        expires_at: None,
+    }
+}
+
+fn summary(changes: &[RuleChange]) -> Vec<String> {
+    changes
+        .iter()
+        .map(|change| {
+            let command = &change.record().key.sanitized_input;
+            match change {
+                RuleChange::Added(r) => format!("+{} {}", r.decision, command),
+                RuleChange::Changed { from, to } => {
+                    format!("~{}>{} {}", from.decision, to.decision, command)
+                }
+                RuleChange::Removed(r) => format!("-{} {}", r.decision, command),
+            }
+        })
+        .collect()
+}
+
+/// A bucket with a maintainer and one allow rule, refreshed into a cache
+/// under the returned rules root.
+fn seeded(tmp: &TempDir) -> (Bucket, String, PathBuf) {
+    let bucket = Bucket::default();
+    bucket.put("policy.yml", "maintainers: [lead@example.com]\n");
+    bucket.put("rules/allow.jsonl", &line("cargo test", Decision::Allow));
+    let endpoint = bucket.serve();
+    (bucket, endpoint, tmp.path().join("state"))
+}
+
+fn storage(tmp: &TempDir, store: OrgStore, user: &str) -> RemoteOrgStorage {
+    let inner = JsonlStorage::new(
+        tmp.path().join(".hookwise"),
+        tmp.path().join("state"),
+        Some("acme".into()),
+    );
+    RemoteOrgStorage::new(Box::new(inner), store, identity(user))
+}
+
+#[tokio::test]
+async fn refresh_mirrors_the_bucket_with_signed_conditional_gets() {
+    let tmp = TempDir::new().unwrap();
+    let (bucket, endpoint, rules_root) = seeded(&tmp);
+    let store = store(&endpoint, &rules_root, false);
+    let dir = sync::org_dir(&rules_root, "acme");
+    assert!(store.is_stale());
+
+    let report = store.refresh().await.unwrap();
+    assert_eq!(summary(&report.changes), ["+allow cargo test"]);
+    assert!(dir.join("policy.yml").exists());
+    assert!(!store.is_stale());
+    let cached = JsonlStorage::new(
+        tmp.path().join(".hookwise"),
+        rules_root.clone(),
+        Some("acme".into()),
+    );
+    assert_eq!(cached.load_decisions(ScopeLevel::Org).unwrap().len(), 1);
+
+    let report = store.refresh().await.unwrap();
+    assert!(report.changes.is_empty());
+
+    bucket.remove("rules/allow.jsonl");
+    bucket.put("rules/deny.jsonl", &line("cargo test", Decision::Deny));
+    let report = store.refresh().await.unwrap();
+    assert_eq!(summary(&report.changes), ["~allow>deny cargo test"]);
+    assert!(!dir.join("rules/allow.jsonl").exists());
+
+    // Each refresh GETs the rule files, then the policy
+    let requests = bucket.requests();
+    assert!(requests[0].starts_with("get /team-rules/hookwise/acme/rules/allow.jsonl "));
+    assert!(requests[3].starts_with("get /team-rules/hookwise/acme/policy.yml "));
+    for request in &requests {
+        assert!(
+            request.contains("authorization: aws4-hmac-sha256 credential=akid/")
+                && request.contains("/us-east-1/s3/aws4_request, signedheaders=host;"),
+            "{request}"
+        );
+    }
+    // An empty payload's hash
+    assert!(requests[0].contains(
+        "x-amz-content-sha256: e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    ));
+    assert!(!requests[0].contains("if-none-match"));
+    assert!(requests[4].contains("if-none-match: \"v1\""));
+    assert!(requests[7].contains("if-none-match: \"v0\""));
+}
+
+#[tokio::test]
+async fn invalid_or_unreachable_buckets_leave_the_cache_in_force() {
+    let tmp = TempDir::new().unwrap();
+    let (bucket, endpoint, rules_root) = seeded(&tmp);
+    store(&endpoint, &rules_root, false)
+        .refresh()
+        .await
+        .unwrap();
+    let allow = sync::org_dir(&rules_root, "acme").join("rules/allow.jsonl");
+    let before = std::fs::read_to_string(&allow).unwrap();
+
+    bucket.put("rules/allow.jsonl", &line("rm -rf /", Decision::Deny));
+    let err = store(&endpoint, &rules_root, false)
+        .refresh()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("rules/allow.jsonl:1"), "{err}");
+    assert_eq!(std::fs::read_to_string(&allow).unwrap(), before);
+
+    // Nothing listens on a closed listener's port
+    let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+    let offline = format!("http://{}", closed.local_addr().unwrap());
+    drop(closed);
+    let mut config = config(&offline, false);
+    config.refresh_secs = 0;
+    let store = OrgStore::new(config, &rules_root, "acme");
+    assert!(store.refresh().await.is_err());
+    store.refresh_if_stale().await;
+    assert_eq!(std::fs::read_to_string(&allow).unwrap(), before);
+}
+
+#[test]
+fn maintainer_writes_are_published_with_conditional_puts() {
+    let tmp = TempDir::new().unwrap();
+    let (bucket, endpoint, rules_root) = seeded(&tmp);
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let org_store = store(&endpoint, &rules_root, true);
+    runtime.block_on(org_store.refresh()).unwrap();
+    let storage = storage(&tmp, org_store, "lead@example.com");
+
+    storage
+        .save_decision(&record("npm publish", Decision::Deny, ScopeLevel::Org))
+        .unwrap();
+    assert!(bucket
+        .get("rules/deny.jsonl")
+        .unwrap()
+        .contains("npm publish"));
+    storage
+        .save_decision(&record("cargo fmt", Decision::Allow, ScopeLevel::Org))
+        .unwrap();
+    let allow = bucket.get("rules/allow.jsonl").unwrap();
+    assert!(allow.contains("cargo test") && allow.contains("cargo fmt"));
+    let puts: Vec<String> = bucket
+        .requests()
+        .into_iter()
+        .filter(|r| r.starts_with("put "))
+        .collect();
+    assert_eq!(puts.len(), 2);
+    assert!(puts[0].contains("if-none-match: *"));
+    assert!(puts[1].contains("if-match: \"v"));
+
+    // A write racing another writer's stays local, and the bucket wins
+    bucket.put("rules/allow.jsonl", &line("make", Decision::Allow));
+    storage
+        .save_decision(&record("cargo doc", Decision::Allow, ScopeLevel::Org))
+        .unwrap();
+    assert!(!bucket
+        .get("rules/allow.jsonl")
+        .unwrap()
+        .contains("cargo doc"));
+    let report = runtime
+        .block_on(store(&endpoint, &rules_root, true).refresh())
+        .unwrap();
+    assert!(summary(&report.changes).contains(&"+allow make".to_string()));
+    let cached =
+        std::fs::read_to_string(sync::org_dir(&rules_root, "acme").join("rules/allow.jsonl"))
+            .unwrap();
+    assert!(cached.contains("make") && !cached.contains("cargo doc"));
+}
+
+#[test]
+fn org_writes_need_a_writable_store_and_a_maintainer() {
+    let tmp = TempDir::new().unwrap();
+    let (bucket, endpoint, rules_root) = seeded(&tmp);
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime
+        .block_on(store(&endpoint, &rules_root, true).refresh())
+        .unwrap();
+
+    let developer = storage(&tmp, store(&endpoint, &rules_root, true), "dev@example.com");
+    let err = developer
+        .save_decision(&record("curl", Decision::Allow, ScopeLevel::Org))
+        .unwrap_err();
+    assert!(err.to_string().contains("not a maintainer"), "{err}");
+    // Other scopes are the developer's own
+    developer
+        .save_decision(&record("curl", Decision::Allow, ScopeLevel::Project))
+        .unwrap();
+
+    let read_only = storage(
+        &tmp,
+        store(&endpoint, &rules_root, false),
+        "lead@example.com",
+    );
+    let err = read_only
+        .save_decision(&record("curl", Decision::Allow, ScopeLevel::Org))
+        .unwrap_err();
+    assert!(err.to_string().contains("org_store.writable"), "{err}");
+
+    assert!(!bucket.requests().iter().any(|r| r.starts_with("put ")));
+    assert_eq!(
+        read_only.load_decisions(ScopeLevel::Org).unwrap().len(),
+        1,
+        "refused writes leave the cache alone"
+    );
+}
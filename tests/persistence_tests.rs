@@ -0,0 +1,146 @@
+//! Tests for ephemeral persistence, read-only storage handling, and the
+//! decision audit sink.
+
+mod common;
+
+use std::path::Path;
+
+use chrono::Utc;
+use hookwise::audit::{load_events, AuditEvent, AuditSink};
+use hookwise::config::{AuditConfig, Persistence, PolicyConfig, ReadOnlyPolicy};
+use hookwise::decision::{Decision, DecisionRecord, ScopeLevel};
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::memory::EphemeralStorage;
+use hookwise::storage::{ensure_writable, is_writable, read_only_marker, StorageBackend};
+
+fn make_record(input: &str, role: &str, decision: Decision) -> DecisionRecord {
+    common::record(input, role, decision)
+}
+
+#[test]
+fn persistence_defaults_to_persistent() {
+    assert_eq!(PolicyConfig::default().persistence, Persistence::Persistent);
+    let policy: PolicyConfig =
+        serde_yaml::from_str("persistence: ephemeral\naudit:\n  path: /tmp/audit.jsonl\n").unwrap();
+    assert_eq!(policy.persistence, Persistence::Ephemeral);
+    assert!(policy.audit.path.is_some());
+}
+
+#[test]
+fn ephemeral_storage_reads_existing_rules_without_writing() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = tmp.path().join(".hookwise");
+    let jsonl = JsonlStorage::new(project.clone(), tmp.path().join("global"), None);
+    jsonl
+        .save_decision(&make_record("cargo build", "coder", Decision::Allow))
+        .unwrap();
+    let rules_before = std::fs::read_to_string(project.join("rules/allow.jsonl")).unwrap();
+
+    let storage = EphemeralStorage::new(Box::new(JsonlStorage::new(
+        project.clone(),
+        tmp.path().join("global"),
+        None,
+    )));
+    storage
+        .save_decision(&make_record("cargo test", "coder", Decision::Allow))
+        .unwrap();
+    storage
+        .save_decision(&make_record("rm -rf /", "tester", Decision::Deny))
+        .unwrap();
+
+    assert_eq!(
+        storage.load_decisions(ScopeLevel::Project).unwrap().len(),
+        3
+    );
+    assert_eq!(storage.session_records().len(), 2);
+    assert_eq!(
+        std::fs::read_to_string(project.join("rules/allow.jsonl")).unwrap(),
+        rules_before
+    );
+    assert!(!project.join("rules/deny.jsonl").exists());
+
+    storage
+        .invalidate_role(ScopeLevel::Project, "tester")
+        .unwrap();
+    assert_eq!(storage.session_records().len(), 1);
+}
+
+#[test]
+fn read_only_policy_defaults_to_degrade() {
+    assert_eq!(PolicyConfig::default().read_only, ReadOnlyPolicy::Degrade);
+    let policy: PolicyConfig = serde_yaml::from_str("read_only: fail\n").unwrap();
+    assert_eq!(policy.read_only, ReadOnlyPolicy::Fail);
+}
+
+#[test]
+fn writability_is_judged_by_nearest_existing_ancestor() {
+    let tmp = tempfile::tempdir().unwrap();
+    assert!(is_writable(&tmp.path().join("not/yet/created")));
+
+    // A regular file can't hold a directory, regardless of permissions.
+    let blocker = tmp.path().join("blocker");
+    std::fs::write(&blocker, "").unwrap();
+    let blocked = blocker.join(".hookwise");
+    assert!(!is_writable(&blocked));
+
+    let state = tmp.path().join("state");
+    let check = |path: &Path, policy| ensure_writable(path, "rule storage", policy, &state);
+    assert!(check(tmp.path(), ReadOnlyPolicy::Fail).unwrap());
+    assert!(!check(&blocked, ReadOnlyPolicy::Degrade).unwrap());
+    assert!(check(&blocked, ReadOnlyPolicy::Fail).is_err());
+
+    // An existing file, like the pending queue after its first ask, is
+    // judged by its directory
+    let queue = tmp.path().join("hookwise-pending.json");
+    std::fs::write(&queue, "[]").unwrap();
+    assert!(is_writable(&queue));
+    assert!(ensure_writable(&queue, "decision queue", ReadOnlyPolicy::Fail, &state).unwrap());
+}
+
+#[test]
+fn read_only_warnings_are_recorded_per_location() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = tmp.path().join("state");
+    let blocker = tmp.path().join("blocker");
+    std::fs::write(&blocker, "").unwrap();
+    let rules = blocker.join(".hookwise");
+    let queue = blocker.join("hookwise-pending.json");
+
+    let degrade = |path: &Path, what| ensure_writable(path, what, ReadOnlyPolicy::Degrade, &state);
+    assert!(!degrade(&rules, "rule storage").unwrap());
+    assert!(read_only_marker(&state, &rules).exists());
+    assert!(!read_only_marker(&state, &queue).exists());
+    assert!(!degrade(&queue, "decision queue").unwrap());
+    assert!(read_only_marker(&state, &queue).exists());
+
+    // Writable again: the next time it is read-only warns again
+    std::fs::remove_file(&blocker).unwrap();
+    assert!(degrade(&rules, "rule storage").unwrap());
+    assert!(!read_only_marker(&state, &rules).exists());
+}
+
+#[test]
+fn audit_sink_appends_events() {
+    let tmp = tempfile::tempdir().unwrap();
+    let config = AuditConfig {
+        path: Some("logs/audit.jsonl".into()),
+    };
+    let sink = AuditSink::from_config(&config, tmp.path()).unwrap();
+    assert_eq!(sink.path(), tmp.path().join("logs/audit.jsonl"));
+
+    for input in ["cargo build", "cargo test"] {
+        sink.record(&AuditEvent {
+            logged_at: Utc::now(),
+            cwd: tmp.path().display().to_string(),
+            record: make_record(input, "coder", Decision::Allow),
+            identity: None,
+            not_enforced: false,
+        })
+        .unwrap();
+    }
+
+    let events = load_events(sink.path());
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].record.key.sanitized_input, "cargo test");
+    assert!(AuditSink::from_config(&AuditConfig::default(), tmp.path()).is_none());
+}
@@ -0,0 +1,186 @@
+//! Tests for supervisor precedents drawn from past human decisions.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use hookwise::cascade::precedents::{select_within_budget, Precedent, PrecedentSelector};
+use hookwise::cascade::supervisor::{SupervisorBackend, SupervisorRequest, SupervisorTier};
+use hookwise::cascade::token_sim::TokenJaccard;
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::config::{PolicyConfig, SupervisorExamplesConfig};
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier};
+use hookwise::error::Result;
+
+fn make_record(input: &str, decision: Decision, tier: DecisionTier) -> DecisionRecord {
+    DecisionRecord {
+        metadata: common::metadata(tier, "test"),
+        ..common::record(input, "*", decision)
+    }
+}
+
+fn make_input(input: &str) -> CascadeInput {
+    common::keyed(input)
+}
+
+fn precedent(input: &str, similarity: f64) -> Precedent {
+    Precedent {
+        tool_name: "Bash".into(),
+        sanitized_input: input.into(),
+        decision: Decision::Allow,
+        similarity,
+        corrected_from: None,
+    }
+}
+
+fn selector(records: &[DecisionRecord], config: SupervisorExamplesConfig) -> PrecedentSelector {
+    let token_jaccard = Arc::new(TokenJaccard::new(0.7, 1));
+    token_jaccard.load_from(records);
+    PrecedentSelector::new(token_jaccard, config)
+}
+
+/// Records every request it is asked to evaluate.
+struct CapturingBackend {
+    requests: Arc<Mutex<Vec<SupervisorRequest>>>,
+}
+
+#[async_trait]
+impl SupervisorBackend for CapturingBackend {
+    async fn evaluate(
+        &self,
+        request: &SupervisorRequest,
+        _policy: &PolicyConfig,
+    ) -> Result<DecisionRecord> {
+        self.requests.lock().unwrap().push(request.clone());
+        Ok(make_record(
+            &request.sanitized_input,
+            Decision::Allow,
+            DecisionTier::Supervisor,
+        ))
+    }
+}
+
+#[test]
+fn budget_keeps_most_similar_first_and_dedupes() {
+    let selected = select_within_budget(
+        vec![
+            precedent("cargo test", 0.4),
+            precedent("cargo build", 0.9),
+            precedent("cargo build", 0.5),
+            precedent("cargo check", 0.6),
+        ],
+        2,
+        1000,
+    );
+    let inputs: Vec<&str> = selected
+        .iter()
+        .map(|p| p.sanitized_input.as_str())
+        .collect();
+    assert_eq!(inputs, vec!["cargo build", "cargo check"]);
+    assert_eq!(selected[0].similarity, 0.9);
+}
+
+#[test]
+fn budget_skips_precedents_that_do_not_fit() {
+    let long = precedent(&"x".repeat(400), 0.9);
+    let short = precedent("cargo test", 0.5);
+    let budget = short.estimated_tokens();
+    let selected = select_within_budget(vec![long, short.clone()], 5, budget);
+    assert_eq!(selected, vec![short]);
+    assert!(select_within_budget(vec![precedent("cargo test", 0.5)], 5, 0).is_empty());
+}
+
+#[test]
+fn render_shows_decision_similarity_and_input() {
+    let mut p = precedent("git push --force", 0.8231);
+    p.decision = Decision::Deny;
+    assert_eq!(
+        p.render(),
+        "- deny (similarity 0.82) Bash: git push --force"
+    );
+}
+
+#[tokio::test]
+async fn selects_only_human_decided_similar_records() {
+    let records = vec![
+        make_record("git push origin main", Decision::Deny, DecisionTier::Human),
+        make_record(
+            "git push origin dev",
+            Decision::Allow,
+            DecisionTier::Supervisor,
+        ),
+        make_record("git push --tags", Decision::Allow, DecisionTier::Override),
+        make_record("npm install", Decision::Allow, DecisionTier::Human),
+    ];
+    let selected = selector(&records, SupervisorExamplesConfig::default())
+        .select(&make_input("git push origin feature"))
+        .await;
+
+    let inputs: Vec<&str> = selected
+        .iter()
+        .map(|p| p.sanitized_input.as_str())
+        .collect();
+    assert_eq!(inputs, vec!["git push origin main", "git push --tags"]);
+    assert_eq!(selected[0].decision, Decision::Deny);
+}
+
+#[tokio::test]
+async fn disabled_selects_nothing() {
+    let records = vec![make_record(
+        "cargo test",
+        Decision::Allow,
+        DecisionTier::Human,
+    )];
+    let config = SupervisorExamplesConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    assert!(selector(&records, config)
+        .select(&make_input("cargo test --all"))
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+async fn supervisor_tier_sends_precedents() {
+    let records = vec![make_record(
+        "cargo publish --dry-run",
+        Decision::Allow,
+        DecisionTier::Human,
+    )];
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let backend = CapturingBackend {
+        requests: requests.clone(),
+    };
+    let tier = SupervisorTier::new(Box::new(backend), PolicyConfig::default())
+        .with_precedents(selector(&records, SupervisorExamplesConfig::default()));
+
+    tier.evaluate(&make_input("cargo publish")).await.unwrap();
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests[0].precedents.len(), 1);
+    assert_eq!(
+        requests[0].precedents[0].sanitized_input,
+        "cargo publish --dry-run"
+    );
+}
+
+#[test]
+fn request_json_omits_empty_precedents() {
+    let request: SupervisorRequest = serde_json::from_value(serde_json::json!({
+        "session_id": "s",
+        "role": "coder",
+        "role_description": "",
+        "tool_name": "Bash",
+        "sanitized_input": "ls",
+        "file_path": null,
+        "task_description": null,
+        "agent_prompt_path": null,
+        "cwd": "/tmp",
+    }))
+    .unwrap();
+    assert!(request.precedents.is_empty());
+    let json = serde_json::to_value(&request).unwrap();
+    assert!(json.get("precedents").is_none());
+}
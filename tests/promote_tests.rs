@@ -0,0 +1,124 @@
+//! Tests for rule promotion proposals: usage counting, scope checks and the
+//! proposal file.
+
+mod common;
+
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use hookwise::audit::AuditEvent;
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::feedback::FeedbackRecord;
+use hookwise::promote::{proposals_dir, rule_usage, PromotionProposal, RuleUsage};
+
+fn record(input: &str, tier: DecisionTier, scope: ScopeLevel) -> DecisionRecord {
+    DecisionRecord {
+        metadata: common::metadata(tier, "test"),
+        timestamp: Utc::now() - Duration::days(3),
+        scope,
+        ..common::record(input, "coder", Decision::Allow)
+    }
+}
+
+fn audited(record: &DecisionRecord, hours_ago: i64, not_enforced: bool) -> AuditEvent {
+    AuditEvent {
+        logged_at: Utc::now() - Duration::hours(hours_ago),
+        cwd: "/repo".into(),
+        record: record.clone(),
+        identity: None,
+        not_enforced,
+    }
+}
+
+#[test]
+fn usage_counts_hits_matches_and_corrections() {
+    let rule = record("cargo test", DecisionTier::Human, ScopeLevel::Project);
+    let mut similar = record(
+        "cargo test --all",
+        DecisionTier::TokenJaccard,
+        ScopeLevel::Project,
+    );
+    similar.metadata.matched_key = Some(rule.key.clone());
+    let other = record("cargo build", DecisionTier::Human, ScopeLevel::Project);
+    let decisions = vec![rule.clone(), similar, other.clone()];
+
+    let audit = vec![
+        audited(&rule, 5, false),
+        audited(&rule, 1, false),
+        // Audit-mode events weren't enforced, and other keys don't count.
+        audited(&rule, 0, true),
+        audited(&other, 0, false),
+    ];
+    let usage = rule_usage(&rule, &decisions, &audit, &[]);
+    assert_eq!(usage.audited_hits, 2);
+    assert_eq!(usage.similarity_matches, 1);
+    assert_eq!(usage.corrections, 0);
+    assert_eq!(usage.last_used, Some(audit[1].logged_at));
+
+    let feedback = vec![FeedbackRecord::new(&rule, Decision::Ask, "too broad")];
+    let usage = rule_usage(&rule, &decisions, &[], &feedback);
+    assert_eq!(usage.corrections, 1);
+    assert_eq!(usage.last_used, Some(feedback[0].timestamp));
+
+    assert_eq!(
+        rule_usage(&other, &decisions, &[], &[]),
+        RuleUsage::default()
+    );
+}
+
+#[test]
+fn proposals_only_go_to_broader_scopes() {
+    let rule = record("cargo test", DecisionTier::Human, ScopeLevel::Project);
+    let proposal =
+        PromotionProposal::new(&rule, ScopeLevel::Org, RuleUsage::default(), "alice", "app")
+            .unwrap();
+    assert_eq!(proposal.rule_id, rule.id());
+    assert_eq!(proposal.from, ScopeLevel::Project);
+    assert_eq!(proposal.rule.scope, ScopeLevel::Org);
+    assert_eq!(proposal.rule.key, rule.key);
+    assert_eq!(proposal.file_name(), format!("{}-to-org.yml", rule.id()));
+
+    for to in [ScopeLevel::Project, ScopeLevel::User] {
+        let err = PromotionProposal::new(&rule, to, RuleUsage::default(), "alice", "app")
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("broader scope"), "{err}");
+    }
+    let user_rule = record("cargo test", DecisionTier::Override, ScopeLevel::User);
+    assert!(PromotionProposal::new(
+        &user_rule,
+        ScopeLevel::Project,
+        RuleUsage::default(),
+        "a",
+        "b"
+    )
+    .is_ok());
+}
+
+#[test]
+fn proposal_round_trips_through_its_file() {
+    let tmp = TempDir::new().unwrap();
+    let rule = record("npm run lint", DecisionTier::Override, ScopeLevel::Project);
+    let usage = RuleUsage {
+        audited_hits: 12,
+        similarity_matches: 3,
+        corrections: 0,
+        last_used: Some(Utc::now()),
+    };
+    let proposal =
+        PromotionProposal::new(&rule, ScopeLevel::Org, usage.clone(), "alice", "app").unwrap();
+    let dir = proposals_dir(&tmp.path().join(".hookwise"));
+    let path = proposal.write(&dir).unwrap();
+    assert_eq!(path, dir.join(proposal.file_name()));
+
+    let loaded = PromotionProposal::load(&path).unwrap();
+    assert_eq!(loaded.rule_id, rule.id());
+    assert_eq!(loaded.to, ScopeLevel::Org);
+    assert_eq!(loaded.proposed_by, "alice");
+    assert_eq!(loaded.project, "app");
+    assert_eq!(loaded.usage, usage);
+    assert_eq!(loaded.rule.key.sanitized_input, "npm run lint");
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("to: org"), "{contents}");
+}
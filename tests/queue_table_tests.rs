@@ -0,0 +1,135 @@
+//! Tests for the column-aligned `hookwise queue` listing.
+
+use chrono::{Duration, Utc};
+use hookwise::cascade::human::{PendingDecision, Priority, SupervisorRecommendation};
+use hookwise::cli::queue_table::{
+    input_summary, relative_time, render_table, truncate_input, TableOptions,
+};
+use hookwise::config::HumanSlaConfig;
+use hookwise::decision::Decision;
+
+fn make_pending(id: &str, tool: &str, input: &str, age_secs: i64) -> PendingDecision {
+    PendingDecision {
+        id: id.into(),
+        session_id: "session-1".into(),
+        role: "coder".into(),
+        tool_name: tool.into(),
+        sanitized_input: input.into(),
+        file_path: None,
+        recommendation: None,
+        is_ask_reprompt: false,
+        ask_reason: None,
+        queued_at: Utc::now() - Duration::seconds(age_secs),
+        priority: Priority::Normal,
+        waiter_pid: None,
+        heartbeat_at: None,
+        attachment: None,
+        suggestion: None,
+        risk_score: None,
+        project_root: None,
+    }
+}
+
+#[test]
+fn relative_time_buckets() {
+    let now = Utc::now();
+    assert_eq!(relative_time(now, now), "just now");
+    assert_eq!(relative_time(now - Duration::seconds(45), now), "45s ago");
+    assert_eq!(relative_time(now - Duration::seconds(200), now), "3m ago");
+    assert_eq!(relative_time(now - Duration::hours(2), now), "2h ago");
+    assert_eq!(relative_time(now - Duration::days(4), now), "4d ago");
+}
+
+#[test]
+fn summary_prefers_command_then_path() {
+    let bash = make_pending("a", "Bash", r#"{"command":"cargo  test\n--all"}"#, 0);
+    assert_eq!(input_summary(&bash), "cargo test --all");
+
+    let mut write = make_pending("b", "Write", r#"{"content":"x"}"#, 0);
+    write.file_path = Some("src/main.rs".into());
+    assert_eq!(input_summary(&write), "src/main.rs");
+
+    let raw = make_pending("c", "Custom", "not json", 0);
+    assert_eq!(input_summary(&raw), "not json");
+}
+
+#[test]
+fn truncation_keeps_verb_and_target_path() {
+    let input = "rsync -avz --delete --exclude target --exclude node_modules ./build/ deploy@host:/srv/app/releases/current";
+    let short = truncate_input(input, 60);
+    assert!(short.chars().count() <= 60, "{}", short);
+    assert!(short.starts_with("rsync -avz"));
+    assert!(short.ends_with("deploy@host:/srv/app/releases/current"));
+    assert!(short.contains(" … "));
+
+    assert_eq!(truncate_input("ls -la", 60), "ls -la");
+}
+
+#[test]
+fn truncation_falls_back_to_prefix_for_single_token() {
+    let input = "a".repeat(100);
+    let short = truncate_input(&input, 20);
+    assert_eq!(short.chars().count(), 20);
+    assert!(short.ends_with('…'));
+}
+
+#[test]
+fn table_columns_align_without_color() {
+    let mut first = make_pending("coder-Bash-1", "Bash", r#"{"command":"cargo build"}"#, 200);
+    first.recommendation = Some(SupervisorRecommendation {
+        decision: Decision::Allow,
+        confidence: 0.92,
+        reason: "build".into(),
+    });
+    let second = make_pending("tester-Write-22", "Write", r#"{"file_path":"tests/a.rs"}"#, 5);
+
+    let lines = render_table(
+        &[first, second],
+        &HumanSlaConfig::default(),
+        Utc::now(),
+        TableOptions::default(),
+    );
+    assert_eq!(lines.len(), 3);
+    assert!(lines.iter().all(|l| !l.contains('\x1b')));
+    let input_col = lines[0].find("INPUT").unwrap();
+    assert_eq!(&lines[1][input_col..], "cargo build");
+    assert_eq!(&lines[2][input_col..], "tests/a.rs");
+    assert!(lines[1].contains("allow 0.92"));
+    assert!(lines[1].contains("3m ago"));
+}
+
+#[test]
+fn table_colors_hints_and_honors_wide() {
+    let long = format!(r#"{{"command":"echo {} /tmp/out"}}"#, "x ".repeat(80));
+    let mut pending = make_pending("p", "Bash", &long, 0);
+    pending.recommendation = Some(SupervisorRecommendation {
+        decision: Decision::Deny,
+        confidence: 0.8,
+        reason: "risky".into(),
+    });
+    let sla = HumanSlaConfig::default();
+
+    let colored = render_table(
+        std::slice::from_ref(&pending),
+        &sla,
+        Utc::now(),
+        TableOptions {
+            color: true,
+            ..Default::default()
+        },
+    );
+    assert!(colored[1].contains("\x1b[31mdeny 0.80\x1b[0m"));
+    assert!(colored[1].contains(" … /tmp/out"));
+
+    let wide = render_table(
+        &[pending],
+        &sla,
+        Utc::now(),
+        TableOptions {
+            wide: true,
+            ..Default::default()
+        },
+    );
+    assert!(wide[1].ends_with("x /tmp/out"));
+    assert!(!wide[1].contains('…'));
+}
@@ -0,0 +1,582 @@
+//! Tests for pending decision priority, queue ordering, SLA tracking, batch
+//! approval, TTL pruning, and orphan collection.
+
+use chrono::{Duration, Utc};
+use hookwise::cascade::human::{
+    append_sla_record, compute_priority, find_similar_pending, is_orphaned, is_overdue,
+    load_queue_file, load_sla_records, orphaned_record, process_alive, prune_queue_state,
+    remove_orphaned_pending, sla_log_path, sort_pending, summarize_sla, timed_out_record,
+    DecisionQueue, HumanResponse, PendingDecision, Priority, QueueFileState, QueueSort,
+    SlaOutcome, SlaRecord, SlaSummary, SupervisorRecommendation, HEARTBEAT_STALE_SECS,
+};
+use hookwise::config::{HumanSlaConfig, OrphanPolicy, PolicyConfig, RolesConfig};
+use hookwise::decision::{Decision, DecisionTier};
+use hookwise::scope::ScopeLevel;
+
+fn make_pending(id: &str, priority: Priority, age_secs: i64) -> PendingDecision {
+    PendingDecision {
+        id: id.into(),
+        session_id: "session-1".into(),
+        role: "coder".into(),
+        tool_name: "Bash".into(),
+        sanitized_input: "echo hello".into(),
+        file_path: None,
+        recommendation: None,
+        is_ask_reprompt: false,
+        ask_reason: None,
+        queued_at: Utc::now() - Duration::seconds(age_secs),
+        priority,
+        waiter_pid: None,
+        heartbeat_at: None,
+        attachment: None,
+        suggestion: None,
+        risk_score: None,
+        project_root: None,
+    }
+}
+
+fn deny_recommendation() -> SupervisorRecommendation {
+    SupervisorRecommendation {
+        decision: Decision::Deny,
+        confidence: 0.6,
+        reason: "looks destructive".into(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Priority computation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn priority_normal_without_signals() {
+    let p = compute_priority("Bash", r#"{"command":"cargo build"}"#, None, false);
+    assert_eq!(p, Priority::Normal);
+}
+
+#[test]
+fn priority_low_for_read_only_tools() {
+    let p = compute_priority("Read", r#"{"file_path":"src/main.rs"}"#, None, false);
+    assert_eq!(p, Priority::Low);
+}
+
+#[test]
+fn priority_high_for_deletion_verb() {
+    let p = compute_priority("Bash", r#"{"command":"rm -rf build"}"#, None, false);
+    assert_eq!(p, Priority::High);
+}
+
+#[test]
+fn priority_high_for_destructive_git_phrase() {
+    let p = compute_priority(
+        "Bash",
+        r#"{"command":"git reset --hard HEAD~3"}"#,
+        None,
+        false,
+    );
+    assert_eq!(p, Priority::High);
+}
+
+#[test]
+fn priority_high_for_sensitive_path() {
+    let p = compute_priority("Write", r#"{"file_path":".env"}"#, None, true);
+    assert_eq!(p, Priority::High);
+}
+
+#[test]
+fn priority_critical_for_supervisor_deny() {
+    let rec = deny_recommendation();
+    let p = compute_priority("Bash", r#"{"command":"cargo build"}"#, Some(&rec), false);
+    assert_eq!(p, Priority::Critical);
+}
+
+#[test]
+fn priority_critical_for_combined_signals() {
+    let p = compute_priority("Bash", r#"{"command":"rm .env"}"#, None, true);
+    assert_eq!(p, Priority::Critical);
+}
+
+#[test]
+fn deletion_verb_requires_whole_token() {
+    // "format" contains "rm" but is not a deletion verb
+    let p = compute_priority("Bash", r#"{"command":"cargo fmt --format"}"#, None, false);
+    assert_eq!(p, Priority::Normal);
+}
+
+// ---------------------------------------------------------------------------
+// Sorting
+// ---------------------------------------------------------------------------
+
+#[test]
+fn sort_by_priority_then_age() {
+    let mut pending = vec![
+        make_pending("normal-old", Priority::Normal, 300),
+        make_pending("critical-new", Priority::Critical, 10),
+        make_pending("normal-new", Priority::Normal, 10),
+        make_pending("high", Priority::High, 100),
+    ];
+    sort_pending(&mut pending, QueueSort::Priority);
+    let ids: Vec<_> = pending.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec!["critical-new", "high", "normal-old", "normal-new"]
+    );
+}
+
+#[test]
+fn sort_by_age_ignores_priority() {
+    let mut pending = vec![
+        make_pending("critical-new", Priority::Critical, 10),
+        make_pending("low-old", Priority::Low, 500),
+        make_pending("high-mid", Priority::High, 100),
+    ];
+    sort_pending(&mut pending, QueueSort::Age);
+    let ids: Vec<_> = pending.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(ids, vec!["low-old", "high-mid", "critical-new"]);
+}
+
+#[test]
+fn priority_defaults_when_missing_from_queue_file() {
+    // Queue files written by older binaries have no priority field.
+    let json = r#"{
+        "id": "legacy",
+        "session_id": "s",
+        "role": "coder",
+        "tool_name": "Bash",
+        "sanitized_input": "ls",
+        "file_path": null,
+        "recommendation": null,
+        "is_ask_reprompt": false,
+        "ask_reason": null,
+        "queued_at": "2026-01-01T00:00:00Z"
+    }"#;
+    let pending: PendingDecision = serde_json::from_str(json).unwrap();
+    assert_eq!(pending.priority, Priority::Normal);
+}
+
+// ---------------------------------------------------------------------------
+// SLA: overdue detection and latency summaries
+// ---------------------------------------------------------------------------
+
+fn make_sla_record(latency_secs: f64, outcome: SlaOutcome, overdue: bool, nags: u32) -> SlaRecord {
+    let resolved_at = Utc::now();
+    SlaRecord {
+        id: format!("id-{}", latency_secs),
+        role: "coder".into(),
+        tool_name: "Bash".into(),
+        priority: Priority::High,
+        queued_at: resolved_at - Duration::milliseconds((latency_secs * 1000.0) as i64),
+        resolved_at,
+        latency_secs,
+        outcome,
+        overdue,
+        nags,
+    }
+}
+
+#[test]
+fn overdue_requires_age_and_priority() {
+    let sla = HumanSlaConfig::default();
+    let now = Utc::now();
+
+    let old_high = make_pending("a", Priority::High, 45);
+    assert!(is_overdue(&old_high, &sla, now));
+
+    let new_high = make_pending("b", Priority::High, 10);
+    assert!(!is_overdue(&new_high, &sla, now));
+
+    let old_normal = make_pending("c", Priority::Normal, 60 * 60);
+    assert!(!is_overdue(&old_normal, &sla, now));
+}
+
+#[test]
+fn overdue_respects_configured_min_priority() {
+    let sla = HumanSlaConfig {
+        overdue_after_secs: Some(60),
+        nag_min_priority: Priority::Low,
+        nag_interval_secs: Some(60),
+    };
+    let pending = make_pending("a", Priority::Low, 120);
+    assert!(is_overdue(&pending, &sla, Utc::now()));
+}
+
+#[test]
+fn sla_summary_counts_and_percentiles() {
+    let records = vec![
+        make_sla_record(10.0, SlaOutcome::Responded, false, 0),
+        make_sla_record(20.0, SlaOutcome::Responded, false, 0),
+        make_sla_record(30.0, SlaOutcome::Responded, false, 0),
+        make_sla_record(900.0, SlaOutcome::Responded, true, 2),
+        make_sla_record(60.0, SlaOutcome::TimedOut, false, 0),
+    ];
+    let summary = summarize_sla(&records, Utc::now() - Duration::days(7));
+    assert_eq!(summary.responded, 4);
+    assert_eq!(summary.timed_out, 1);
+    assert_eq!(summary.overdue, 1);
+    assert_eq!(summary.nags, 2);
+    assert_eq!(summary.median_latency_secs, Some(30.0));
+    assert_eq!(summary.max_latency_secs, Some(900.0));
+}
+
+#[test]
+fn sla_summary_excludes_records_outside_window() {
+    let mut old = make_sla_record(10.0, SlaOutcome::Responded, false, 0);
+    old.resolved_at = Utc::now() - Duration::days(8);
+    let summary = summarize_sla(&[old], Utc::now() - Duration::days(7));
+    assert_eq!(summary, SlaSummary::default());
+}
+
+#[test]
+fn sla_log_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = sla_log_path(dir.path());
+    append_sla_record(
+        &path,
+        &make_sla_record(5.0, SlaOutcome::Responded, false, 0),
+    )
+    .unwrap();
+    append_sla_record(&path, &make_sla_record(60.0, SlaOutcome::TimedOut, true, 1)).unwrap();
+
+    let records = load_sla_records(&path);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].outcome, SlaOutcome::TimedOut);
+    assert_eq!(records[1].nags, 1);
+}
+
+#[test]
+fn sla_config_parses_from_policy_yaml() {
+    let yaml = "human_sla:\n  overdue_after_secs: 180\n  nag_min_priority: critical\n";
+    let policy: PolicyConfig = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(policy.human_sla.overdue_after_secs, Some(180));
+    assert_eq!(policy.human_sla.nag_min_priority, Priority::Critical);
+    assert_eq!(policy.human_sla.nag_interval_secs, None);
+
+    let sla = policy.effective_human_sla();
+    assert_eq!(sla.overdue_after_secs, Some(180));
+    assert_eq!(sla.nag_interval_secs, Some(15));
+}
+
+#[test]
+fn default_sla_fires_before_the_human_timeout() {
+    let policy = PolicyConfig::default();
+    let sla = policy.effective_human_sla();
+    let timeout = Duration::seconds(policy.human_timeout_secs as i64);
+    let first_reminder = sla.overdue_after() + Duration::from_std(sla.nag_interval()).unwrap();
+    assert!(sla.overdue_after() < timeout);
+    assert!(first_reminder < timeout);
+
+    let policy = policy.with_human_timeout_secs(600);
+    assert_eq!(policy.effective_human_sla().overdue_after_secs, Some(300));
+}
+
+// ---------------------------------------------------------------------------
+// Batch approval of similar pending decisions
+// ---------------------------------------------------------------------------
+
+fn make_bash_pending(id: &str, command: &str) -> PendingDecision {
+    let mut p = make_pending(id, Priority::Normal, 10);
+    p.sanitized_input = format!(r#"{{"command":"{}"}}"#, command);
+    p
+}
+
+#[test]
+fn similar_pending_matches_near_duplicates() {
+    let rep = make_bash_pending("rep", "cargo test --package core --lib");
+    let pending = vec![
+        rep.clone(),
+        make_bash_pending("near", "cargo test --package core --lib --release"),
+        make_bash_pending("far", "rm -rf target"),
+    ];
+    let similar = find_similar_pending(&rep, &pending, 0.7, 3);
+    let ids: Vec<_> = similar.iter().map(|(p, _)| p.id.as_str()).collect();
+    assert_eq!(ids, vec!["near"]);
+    assert!(similar[0].1 >= 0.7);
+}
+
+#[test]
+fn similar_pending_requires_same_tool_and_role() {
+    let rep = make_bash_pending("rep", "cargo test --package core --lib");
+    let mut other_role = make_bash_pending("role", "cargo test --package core --lib");
+    other_role.role = "tester".into();
+    let mut other_tool = make_bash_pending("tool", "cargo test --package core --lib");
+    other_tool.tool_name = "Write".into();
+
+    let similar = find_similar_pending(&rep, &[other_role, other_tool], 0.7, 3);
+    assert!(similar.is_empty());
+}
+
+#[test]
+fn similar_pending_short_inputs_only_match_exactly() {
+    let rep = make_bash_pending("rep", "ls");
+    let pending = vec![
+        make_bash_pending("same", "ls"),
+        make_bash_pending("other", "ls -la"),
+    ];
+    let similar = find_similar_pending(&rep, &pending, 0.5, 5);
+    let ids: Vec<_> = similar.iter().map(|(p, _)| p.id.as_str()).collect();
+    assert_eq!(ids, vec!["same"]);
+}
+
+#[test]
+fn respond_like_marks_batch_members() {
+    let runtime_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("XDG_RUNTIME_DIR", runtime_dir.path());
+
+    let queue = DecisionQueue::new();
+    queue.enqueue(make_bash_pending("rep", "cargo test --package core --lib"));
+    queue.enqueue(make_bash_pending(
+        "near",
+        "cargo test --package core --lib --release",
+    ));
+    queue.enqueue(make_bash_pending("far", "rm -rf target"));
+
+    let response = HumanResponse {
+        decision: Decision::Allow,
+        always_ask: false,
+        add_rule: true,
+        rule_scope: None,
+        batch: None,
+        responded_by: None,
+    };
+    let members = queue.respond_like("rep", response, 0.7, 3).unwrap();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].0, "near");
+
+    let state = load_queue_file();
+    assert!(state.pending.contains_key("far"));
+    assert!(state.responses["rep"].batch.is_none());
+    let member = &state.responses["near"];
+    assert!(!member.add_rule);
+    let batch = member.batch.as_ref().unwrap();
+    assert_eq!(batch.representative_id, "rep");
+    assert_eq!(batch.matched_key.tool, "Bash");
+}
+
+// ---------------------------------------------------------------------------
+// Queue TTL pruning
+// ---------------------------------------------------------------------------
+
+fn approve() -> HumanResponse {
+    HumanResponse {
+        decision: Decision::Allow,
+        always_ask: false,
+        add_rule: false,
+        rule_scope: None,
+        batch: None,
+        responded_by: None,
+    }
+}
+
+#[test]
+fn prune_expires_pending_older_than_ttl() {
+    let mut state = QueueFileState::default();
+    for p in [
+        make_pending("fresh", Priority::Normal, 30),
+        make_pending("stale", Priority::Normal, 7200),
+        make_pending("staler", Priority::High, 9000),
+    ] {
+        state.pending.insert(p.id.clone(), p);
+    }
+
+    let report = prune_queue_state(&mut state, None, 3600, Utc::now());
+    let ids: Vec<_> = report.expired.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(ids, vec!["staler", "stale"]);
+    assert_eq!(state.pending.len(), 1);
+    assert!(state.pending.contains_key("fresh"));
+}
+
+#[test]
+fn prune_leaves_other_projects_entries() {
+    let mut state = QueueFileState::default();
+    let mut ours = make_pending("ours", Priority::Normal, 7200);
+    ours.project_root = Some("/work/api".into());
+    let mut theirs = make_pending("theirs", Priority::Normal, 7200);
+    theirs.project_root = Some("/work/web".into());
+    let mut dead = waited_on("theirs-orphaned", 2, 5);
+    dead.project_root = Some("/work/web".into());
+    let legacy = make_pending("legacy", Priority::Normal, 7200);
+    for p in [ours, theirs, dead, legacy] {
+        state.pending.insert(p.id.clone(), p);
+    }
+
+    let project = Some(std::path::Path::new("/work/api"));
+    let report = prune_queue_state(&mut state, project, 3600, Utc::now());
+    let mut ids: Vec<_> = report.expired.iter().map(|p| p.id.as_str()).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["legacy", "ours"]);
+    assert!(remove_orphaned_pending(&mut state, project, Utc::now(), |_| false).is_empty());
+    assert!(state.pending.contains_key("theirs"));
+    assert!(state.pending.contains_key("theirs-orphaned"));
+}
+
+#[test]
+fn prune_removes_orphaned_responses() {
+    let now = Utc::now();
+    let mut state = QueueFileState::default();
+    for id in ["recent", "old", "untimed"] {
+        state.responses.insert(id.into(), approve());
+    }
+    state.responded_at.insert("recent".into(), now - Duration::seconds(5));
+    state.responded_at.insert("old".into(), now - Duration::hours(2));
+
+    let report = prune_queue_state(&mut state, None, 3600, now);
+    assert_eq!(report.orphaned_responses, vec!["old", "untimed"]);
+    assert!(state.responses.contains_key("recent"));
+    assert_eq!(state.responded_at.len(), 1);
+}
+
+#[test]
+fn prune_with_nothing_stale_is_empty() {
+    let mut state = QueueFileState::default();
+    let p = make_pending("fresh", Priority::Normal, 30);
+    state.pending.insert(p.id.clone(), p);
+    assert!(prune_queue_state(&mut state, None, 3600, Utc::now()).is_empty());
+}
+
+#[test]
+fn timed_out_record_is_a_human_deny() {
+    let pending = make_pending("stale", Priority::Normal, 7200);
+    let record = timed_out_record(&pending, 3600);
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(record.metadata.tier, DecisionTier::Human);
+    assert!(record.metadata.reason.contains("timed out"));
+    assert_eq!(record.key.sanitized_input, pending.sanitized_input);
+    assert_eq!(record.session_id, "session-1");
+}
+
+#[test]
+fn queue_ttl_never_shorter_than_human_wait() {
+    let yaml = "human_timeout_secs: 600\nqueue_ttl_secs: 60\n";
+    let policy: PolicyConfig = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(policy.effective_queue_ttl_secs(), 600);
+    assert_eq!(PolicyConfig::default().effective_queue_ttl_secs(), 3600);
+}
+
+#[tokio::test]
+async fn memory_only_queue_round_trips_without_the_queue_file() {
+    let queue = DecisionQueue::memory_only();
+    let id = queue.enqueue(make_pending("mem-only-1", Priority::Normal, 0));
+    assert_eq!(queue.list_pending().len(), 1);
+    assert!(!load_queue_file().pending.contains_key(&id));
+
+    queue
+        .respond(
+            &id,
+            HumanResponse {
+                decision: Decision::Allow,
+                always_ask: false,
+                add_rule: false,
+                rule_scope: None,
+                batch: None,
+                responded_by: None,
+            },
+        )
+        .unwrap();
+    assert!(queue.list_pending().is_empty());
+    let response = queue.wait_for_response(&id, 1).await.unwrap();
+    assert_eq!(response.decision, Decision::Allow);
+}
+
+fn waited_on(id: &str, pid: u32, heartbeat_age_secs: i64) -> PendingDecision {
+    let mut p = make_pending(id, Priority::Normal, heartbeat_age_secs);
+    p.waiter_pid = Some(pid);
+    p.heartbeat_at = Some(Utc::now() - Duration::seconds(heartbeat_age_secs));
+    p
+}
+
+#[test]
+fn orphaned_when_waiter_pid_is_gone() {
+    let now = Utc::now();
+    let p = waited_on("gone", 4242, 0);
+    assert!(is_orphaned(&p, now, |_| false));
+    assert!(!is_orphaned(&p, now, |_| true));
+}
+
+#[test]
+fn orphaned_when_heartbeat_is_stale_even_if_pid_reused() {
+    let p = waited_on("stale", 4242, HEARTBEAT_STALE_SECS + 30);
+    assert!(is_orphaned(&p, Utc::now(), |_| true));
+}
+
+#[test]
+fn entries_without_waiter_are_never_orphaned() {
+    let p = make_pending("legacy", Priority::Normal, 99_999);
+    assert!(!is_orphaned(&p, Utc::now(), |_| false));
+}
+
+#[test]
+fn remove_orphaned_pending_keeps_live_waiters() {
+    let mut state = QueueFileState::default();
+    for p in [
+        waited_on("live", 1, 0),
+        waited_on("dead", 2, 5),
+        waited_on("dead-older", 2, 20),
+        make_pending("legacy", Priority::Normal, 30),
+    ] {
+        state.pending.insert(p.id.clone(), p);
+    }
+
+    let orphaned = remove_orphaned_pending(&mut state, None, Utc::now(), |pid| pid == 1);
+    let ids: Vec<_> = orphaned.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(ids, vec!["dead-older", "dead"]);
+    assert!(state.pending.contains_key("live"));
+    assert!(state.pending.contains_key("legacy"));
+}
+
+#[test]
+fn own_process_is_alive() {
+    assert!(process_alive(std::process::id()));
+}
+
+#[test]
+fn orphaned_record_notes_waiter_and_policy() {
+    let record = orphaned_record(&waited_on("dead", 4242, 0), OrphanPolicy::Discard);
+    assert_eq!(record.decision, Decision::Deny);
+    assert!(record.metadata.reason.contains("pid 4242"));
+    assert!(record.metadata.reason.contains("orphaned_queue: discard"));
+}
+
+#[test]
+fn orphaned_queue_policy_parses() {
+    let policy: PolicyConfig = serde_yaml::from_str("orphaned_queue: discard\n").unwrap();
+    assert_eq!(policy.orphaned_queue, OrphanPolicy::Discard);
+    assert_eq!(PolicyConfig::default().orphaned_queue, OrphanPolicy::Deny);
+}
+
+#[test]
+fn rule_scope_defaults_to_the_role_then_the_policy() {
+    let roles: RolesConfig = serde_yaml::from_str(
+        r#"
+roles:
+  maintainer:
+    name: maintainer
+    description: "Full access"
+    rule_scope: user
+    paths: { allow_write: ["**"], deny_write: [], allow_read: ["**"] }
+  coder:
+    name: coder
+    description: "Writes code"
+    paths: { allow_write: ["src/**"], deny_write: [], allow_read: ["**"] }
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        roles.get_role("maintainer").unwrap().rule_scope,
+        Some(ScopeLevel::User)
+    );
+
+    let policy = PolicyConfig::default();
+    assert_eq!(policy.rule_scope, ScopeLevel::Project);
+    assert_eq!(
+        policy.rule_scope_for(&roles, "maintainer"),
+        ScopeLevel::User
+    );
+    assert_eq!(policy.rule_scope_for(&roles, "coder"), ScopeLevel::Project);
+
+    let policy: PolicyConfig = serde_yaml::from_str("rule_scope: org").unwrap();
+    assert_eq!(policy.rule_scope_for(&roles, "coder"), ScopeLevel::Org);
+    assert_eq!(policy.rule_scope_for(&roles, "unknown"), ScopeLevel::Org);
+    assert_eq!(
+        policy.rule_scope_for(&roles, "maintainer"),
+        ScopeLevel::User
+    );
+}
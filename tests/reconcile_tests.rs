@@ -0,0 +1,240 @@
+//! Tests for asynchronous approval via commit trailers.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::Utc;
+use hookwise::cascade::human::{PendingDecision, Priority};
+use hookwise::cli::reconcile::{
+    parse_trailers, plan_reconcile, read_trailer_commits, ReconcileOutcome, TrailerCommit,
+};
+use hookwise::config::GitApprovalConfig;
+use hookwise::decision::Decision;
+
+fn make_pending(id: &str, priority: Priority) -> PendingDecision {
+    PendingDecision {
+        id: id.into(),
+        session_id: "session-1".into(),
+        role: "coder".into(),
+        tool_name: "Bash".into(),
+        sanitized_input: "npm install".into(),
+        file_path: None,
+        recommendation: None,
+        is_ask_reprompt: true,
+        ask_reason: None,
+        queued_at: Utc::now(),
+        priority,
+        waiter_pid: None,
+        heartbeat_at: None,
+        attachment: None,
+        suggestion: None,
+        risk_score: None,
+        project_root: None,
+    }
+}
+
+fn pending_map(items: Vec<PendingDecision>) -> HashMap<String, PendingDecision> {
+    items.into_iter().map(|p| (p.id.clone(), p)).collect()
+}
+
+fn config(approvers: &[&str]) -> GitApprovalConfig {
+    GitApprovalConfig {
+        approvers: approvers.iter().map(|a| a.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+/// A commit with a good signature by `email`.
+fn commit(sha: &str, email: &str, trailers: Vec<(Decision, &str)>) -> TrailerCommit {
+    TrailerCommit {
+        sha: sha.into(),
+        committer_email: email.into(),
+        signature: 'G',
+        signer: format!("Someone <{email}>"),
+        signer_key: "4AEE18F83AFDEB23".into(),
+        trailers: trailers
+            .into_iter()
+            .map(|(d, id)| (d, id.to_string()))
+            .collect(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Trailer parsing
+// ---------------------------------------------------------------------------
+
+#[test]
+fn parse_approve_and_deny_trailers() {
+    let msg = "Review queued tool calls\n\nHookwise-Approve: id-1\nHookwise-Deny: id-2\nSigned-off-by: A <a@example.com>\n";
+    let trailers = parse_trailers(msg);
+    assert_eq!(
+        trailers,
+        vec![
+            (Decision::Allow, "id-1".to_string()),
+            (Decision::Deny, "id-2".to_string())
+        ]
+    );
+}
+
+#[test]
+fn parse_trailers_case_insensitive_and_ignores_empty() {
+    let trailers = parse_trailers("hookwise-approve: id-1\nHookwise-Approve:   \n");
+    assert_eq!(trailers, vec![(Decision::Allow, "id-1".to_string())]);
+}
+
+// ---------------------------------------------------------------------------
+// Planning
+// ---------------------------------------------------------------------------
+
+#[test]
+fn plan_applies_trailer_from_approver() {
+    let pending = pending_map(vec![make_pending("id-1", Priority::Normal)]);
+    let commits = vec![commit(
+        "abc",
+        "Lead@Example.com",
+        vec![(Decision::Allow, "id-1")],
+    )];
+    let actions = plan_reconcile(&commits, &pending, &config(&["lead@example.com"]));
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].outcome, ReconcileOutcome::Apply(Decision::Allow));
+    assert_eq!(actions[0].approver, "Lead@Example.com");
+}
+
+#[test]
+fn plan_skips_non_approver() {
+    let pending = pending_map(vec![make_pending("id-1", Priority::Low)]);
+    let commits = vec![commit(
+        "abc",
+        "intern@example.com",
+        vec![(Decision::Allow, "id-1")],
+    )];
+    let actions = plan_reconcile(&commits, &pending, &config(&["lead@example.com"]));
+    assert!(matches!(actions[0].outcome, ReconcileOutcome::Skipped(_)));
+}
+
+#[test]
+fn plan_skips_high_priority_items() {
+    let pending = pending_map(vec![make_pending("id-1", Priority::High)]);
+    let commits = vec![commit(
+        "abc",
+        "lead@example.com",
+        vec![(Decision::Allow, "id-1")],
+    )];
+    let actions = plan_reconcile(&commits, &pending, &config(&["lead@example.com"]));
+    assert!(matches!(actions[0].outcome, ReconcileOutcome::Skipped(_)));
+}
+
+#[test]
+fn plan_requires_signature_by_default() {
+    let pending = pending_map(vec![make_pending("id-1", Priority::Normal)]);
+    let mut cfg = config(&["lead@example.com"]);
+    assert!(cfg.require_signed);
+
+    let mut unsigned = vec![commit(
+        "abc",
+        "lead@example.com",
+        vec![(Decision::Allow, "id-1")],
+    )];
+    unsigned[0].signature = 'N';
+    unsigned[0].signer.clear();
+    unsigned[0].signer_key.clear();
+    let actions = plan_reconcile(&unsigned, &pending, &cfg);
+    assert!(matches!(actions[0].outcome, ReconcileOutcome::Skipped(_)));
+
+    // Opting out trusts the committer email.
+    cfg.require_signed = false;
+    let actions = plan_reconcile(&unsigned, &pending, &cfg);
+    assert_eq!(actions[0].outcome, ReconcileOutcome::Apply(Decision::Allow));
+}
+
+#[test]
+fn plan_matches_the_signer_not_the_committer() {
+    let pending = pending_map(vec![make_pending("id-1", Priority::Normal)]);
+    // Anyone can commit as the lead; the signature is the intern's.
+    let mut spoofed = commit("abc", "intern@example.com", vec![(Decision::Allow, "id-1")]);
+    spoofed.committer_email = "lead@example.com".into();
+    let actions = plan_reconcile(&[spoofed.clone()], &pending, &config(&["lead@example.com"]));
+    assert_eq!(
+        actions[0].outcome,
+        ReconcileOutcome::Skipped("intern@example.com is not an approver".into())
+    );
+
+    // An approver may also be listed by key fingerprint.
+    let actions = plan_reconcile(&[spoofed], &pending, &config(&["4aee18f83afdeb23"]));
+    assert_eq!(actions[0].outcome, ReconcileOutcome::Apply(Decision::Allow));
+}
+
+#[test]
+fn plan_newest_trailer_wins() {
+    let pending = pending_map(vec![make_pending("id-1", Priority::Normal)]);
+    let commits = vec![
+        commit("new", "lead@example.com", vec![(Decision::Deny, "id-1")]),
+        commit("old", "lead@example.com", vec![(Decision::Allow, "id-1")]),
+    ];
+    let actions = plan_reconcile(&commits, &pending, &config(&["lead@example.com"]));
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].sha, "new");
+    assert_eq!(actions[0].outcome, ReconcileOutcome::Apply(Decision::Deny));
+}
+
+#[test]
+fn plan_skips_ids_not_pending() {
+    let commits = vec![commit(
+        "abc",
+        "lead@example.com",
+        vec![(Decision::Allow, "gone")],
+    )];
+    let actions = plan_reconcile(&commits, &HashMap::new(), &config(&["lead@example.com"]));
+    assert!(matches!(actions[0].outcome, ReconcileOutcome::Skipped(_)));
+}
+
+// ---------------------------------------------------------------------------
+// Reading commits from git
+// ---------------------------------------------------------------------------
+
+fn git(repo: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .env("GIT_AUTHOR_NAME", "Lead")
+        .env("GIT_AUTHOR_EMAIL", "lead@example.com")
+        .env("GIT_COMMITTER_NAME", "Lead")
+        .env("GIT_COMMITTER_EMAIL", "lead@example.com")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn read_trailer_commits_from_repo() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path();
+    git(repo, &["init", "-q"]);
+    git(
+        repo,
+        &["commit", "-q", "--allow-empty", "-m", "unrelated change"],
+    );
+    git(
+        repo,
+        &[
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "Approve queued install\n\nHookwise-Approve: coder-Bash-1",
+        ],
+    );
+
+    let commits = read_trailer_commits(repo, 10).unwrap();
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].committer_email, "lead@example.com");
+    assert_eq!(commits[0].signature, 'N');
+    assert!(commits[0].signer.is_empty());
+    assert_eq!(
+        commits[0].trailers,
+        vec![(Decision::Allow, "coder-Bash-1".to_string())]
+    );
+    assert_eq!(commits[0].sha.len(), 40);
+}
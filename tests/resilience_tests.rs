@@ -0,0 +1,165 @@
+//! End-to-end checks of how `hookwise check` degrades when a component
+//! fails, using the hidden `--inject-failure` flag and
+//! `HOOKWISE_INJECT_FAILURE`.
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use hookwise::fault::{injected, Fault};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn hookwise() -> Command {
+    assert_cmd::cargo::cargo_bin_cmd!("hookwise")
+}
+
+/// An initialized project with session `s-1` registered as coder, and a
+/// one-second human timeout so unresolved calls finish quickly.
+fn project() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    std::fs::create_dir_all(tmp.path().join("runtime")).unwrap();
+    hookwise()
+        .arg("init")
+        .current_dir(tmp.path())
+        .assert()
+        .success();
+    let policy = tmp.path().join(".hookwise/policy.yml");
+    let contents = std::fs::read_to_string(&policy).unwrap();
+    std::fs::write(
+        &policy,
+        contents.replace("human_timeout_secs: 60", "human_timeout_secs: 1"),
+    )
+    .unwrap();
+    check_cmd(tmp.path())
+        .args(["register", "--session-id", "s-1", "--role", "coder"])
+        .assert()
+        .success();
+    tmp
+}
+
+fn check_cmd(dir: &Path) -> Command {
+    let mut cmd = hookwise();
+    cmd.current_dir(dir)
+        .env("HOME", dir.join("home"))
+        .env("XDG_RUNTIME_DIR", dir.join("runtime"))
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("XDG_STATE_HOME")
+        .env_remove("CLAUDE_TEAM_ID")
+        .env_remove("HOOKWISE_INJECT_FAILURE");
+    cmd
+}
+
+fn call(dir: &Path, tool: &str, tool_input: serde_json::Value) -> String {
+    serde_json::json!({
+        "session_id": "s-1",
+        "cwd": dir,
+        "tool_name": tool,
+        "tool_input": tool_input,
+    })
+    .to_string()
+}
+
+#[test]
+fn faults_combine_flags_and_env() {
+    assert_eq!(
+        injected(&[Fault::Storage], Some("supervisor, Storage")).unwrap(),
+        vec![Fault::Storage, Fault::Supervisor]
+    );
+    assert!(injected(&[], None).unwrap().is_empty());
+    assert!(injected(&[], Some("network"))
+        .unwrap_err()
+        .to_string()
+        .contains("unknown HOOKWISE_INJECT_FAILURE value 'network'"));
+}
+
+#[test]
+fn storage_failure_denies() {
+    let tmp = project();
+    check_cmd(tmp.path())
+        .args(["check", "--inject-failure", "storage"])
+        .write_stdin(call(
+            tmp.path(),
+            "Bash",
+            serde_json::json!({"command": "ls"}),
+        ))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"deny\""))
+        .stderr(predicate::str::contains(
+            "rule storage unavailable, defaulting to deny (storage error: injected failure)",
+        ));
+}
+
+#[test]
+fn storage_failure_deny_is_audited() {
+    let tmp = project();
+    let policy = tmp.path().join(".hookwise/policy.yml");
+    let contents = std::fs::read_to_string(&policy).unwrap();
+    std::fs::write(
+        &policy,
+        format!("{contents}\naudit:\n  path: audit.jsonl\n"),
+    )
+    .unwrap();
+    check_cmd(tmp.path())
+        .args(["check", "--inject-failure", "storage"])
+        .write_stdin(call(
+            tmp.path(),
+            "Bash",
+            serde_json::json!({"command": "ls"}),
+        ))
+        .assert()
+        .failure();
+
+    let events = hookwise::audit::load_events(&tmp.path().join("audit.jsonl"));
+    assert_eq!(events.len(), 1);
+    let record = &events[0].record;
+    assert_eq!(record.decision, hookwise::decision::Decision::Deny);
+    assert_eq!(record.key.tool, "Bash");
+    assert!(record
+        .metadata
+        .reason
+        .starts_with("rule storage unavailable: "));
+}
+
+#[test]
+fn supervisor_failure_falls_through_to_a_human() {
+    let tmp = project();
+    // Nobody answers within the timeout, so the call is denied.
+    check_cmd(tmp.path())
+        .arg("check")
+        .env("HOOKWISE_INJECT_FAILURE", "supervisor")
+        .write_stdin(call(
+            tmp.path(),
+            "Bash",
+            serde_json::json!({"command": "make release"}),
+        ))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"deny\""))
+        .stderr(predicate::str::contains(
+            "supervisor unavailable, falling through (supervisor error: injected failure)",
+        ))
+        .stderr(predicate::str::contains("human decision timeout after 1s"));
+}
+
+#[test]
+fn embedding_failure_skips_the_tier() {
+    let tmp = project();
+    // Path policy still decides on its own.
+    check_cmd(tmp.path())
+        .args(["check", "--inject-failure", "embedding"])
+        .write_stdin(call(
+            tmp.path(),
+            "Write",
+            serde_json::json!({
+                "file_path": tmp.path().join(".env"),
+                "content": "DEBUG=1",
+            }),
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"ask\""))
+        .stderr(predicate::str::contains(
+            "embedding tier unavailable, skipping (embedding error: injected failure)",
+        ));
+}
@@ -0,0 +1,128 @@
+//! Tests for decision retention: `storage.retention_days` per scope and
+//! `JsonlStorage::prune_older_than` behind `hookwise prune`.
+
+mod common;
+
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use hookwise::config::PolicyConfig;
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::StorageBackend;
+
+fn record(command: &str, decision: Decision, age_days: i64) -> DecisionRecord {
+    DecisionRecord {
+        metadata: common::metadata(DecisionTier::Human, &format!("{age_days}d")),
+        timestamp: Utc::now() - Duration::days(age_days),
+        ..common::record(command, "coder", decision)
+    }
+}
+
+fn storage(root: &std::path::Path) -> JsonlStorage {
+    JsonlStorage::new(root.join(".hookwise"), root.join("global"), None)
+}
+
+#[test]
+fn retention_days_are_per_scope() {
+    let policy: PolicyConfig =
+        serde_yaml::from_str("storage:\n  retention_days:\n    project: 90\n    org: 365\n")
+            .unwrap();
+    assert_eq!(
+        policy.storage.retention(ScopeLevel::Project),
+        Some(Duration::days(90))
+    );
+    assert_eq!(
+        policy.storage.retention(ScopeLevel::Role),
+        Some(Duration::days(90))
+    );
+    assert_eq!(
+        policy.storage.retention(ScopeLevel::Org),
+        Some(Duration::days(365))
+    );
+    assert_eq!(policy.storage.retention(ScopeLevel::User), None);
+    assert_eq!(
+        PolicyConfig::default()
+            .storage
+            .retention(ScopeLevel::Project),
+        None
+    );
+}
+
+#[test]
+fn prune_removes_old_records_of_every_decision() {
+    let tmp = TempDir::new().unwrap();
+    let storage = storage(tmp.path());
+    storage
+        .save_decision(&record("cargo test", Decision::Allow, 120))
+        .unwrap();
+    storage
+        .save_decision(&record("rm -rf target", Decision::Deny, 100))
+        .unwrap();
+    storage
+        .save_decision(&record("cargo build", Decision::Allow, 10))
+        .unwrap();
+    storage
+        .save_decision(&record("git push", Decision::Ask, 5))
+        .unwrap();
+
+    let cutoff = Utc::now() - Duration::days(90);
+    let report = storage
+        .prune_older_than(ScopeLevel::Project, cutoff, false, false)
+        .unwrap();
+    assert_eq!(report.records_before, 4);
+    assert_eq!(report.records_removed, 2);
+    assert!(report.bytes_reclaimed() > 0);
+    assert!(report.archived.is_none());
+
+    let mut left: Vec<String> = storage
+        .load_decisions(ScopeLevel::Project)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.key.sanitized_input)
+        .collect();
+    left.sort();
+    assert_eq!(left, ["cargo build", "git push"]);
+}
+
+#[test]
+fn prune_archives_removed_records() {
+    let tmp = TempDir::new().unwrap();
+    let storage = storage(tmp.path());
+    storage
+        .save_decision(&record("cargo test", Decision::Allow, 200))
+        .unwrap();
+    storage
+        .save_decision(&record("cargo build", Decision::Allow, 1))
+        .unwrap();
+
+    let cutoff = Utc::now() - Duration::days(90);
+    let report = storage
+        .prune_older_than(ScopeLevel::Project, cutoff, true, false)
+        .unwrap();
+    let archive = report.archived.unwrap();
+    assert!(archive.starts_with(tmp.path().join(".hookwise/.user/archive")));
+    let archived = std::fs::read_to_string(&archive).unwrap();
+    assert_eq!(archived.lines().count(), 1);
+    assert!(archived.contains("cargo test"));
+}
+
+#[test]
+fn prune_dry_run_writes_nothing() {
+    let tmp = TempDir::new().unwrap();
+    let storage = storage(tmp.path());
+    storage
+        .save_decision(&record("cargo test", Decision::Allow, 200))
+        .unwrap();
+    let rules = tmp.path().join(".hookwise/rules/allow.jsonl");
+    let before = std::fs::read_to_string(&rules).unwrap();
+
+    let cutoff = Utc::now() - Duration::days(90);
+    let report = storage
+        .prune_older_than(ScopeLevel::Project, cutoff, true, true)
+        .unwrap();
+    assert_eq!(report.records_removed, 1);
+    assert_eq!(report.bytes_after, 0);
+    assert!(!report.archived.unwrap().exists());
+    assert_eq!(std::fs::read_to_string(&rules).unwrap(), before);
+}
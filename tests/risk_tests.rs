@@ -0,0 +1,240 @@
+//! Tests for rolling session risk: event scoring, the window, and the
+//! tightening applied once a session crosses the threshold.
+
+mod common;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use common::session;
+
+use hookwise::cascade::risk::RiskTracker;
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::config::{AutoTier, CompiledPathPolicy, PathPolicyConfig, PolicyConfig, RiskConfig};
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier};
+use hookwise::session::state::{RiskKind, RiskSummary, SessionState, SessionStateStore};
+
+/// A tier that always decides `decision`.
+struct Fixed {
+    tier: DecisionTier,
+    decision: Decision,
+}
+
+#[async_trait]
+impl CascadeTier for Fixed {
+    async fn evaluate(
+        &self,
+        input: &CascadeInput,
+    ) -> hookwise::error::Result<Option<DecisionRecord>> {
+        Ok(Some(record(input, self.tier, self.decision)))
+    }
+    fn tier(&self) -> DecisionTier {
+        self.tier
+    }
+    fn name(&self) -> &str {
+        "fixed"
+    }
+}
+
+fn record(input: &CascadeInput, tier: DecisionTier, decision: Decision) -> DecisionRecord {
+    let mut record = DecisionRecord {
+        metadata: common::metadata(tier, "fixed"),
+        file_path: input.file_path.clone(),
+        ..common::record(&input.sanitized_input, "*", decision)
+    };
+    record.key.tool = input.tool_name.clone();
+    record
+}
+
+fn config(yaml: &str) -> RiskConfig {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+fn store(tmp: &TempDir) -> SessionStateStore {
+    SessionStateStore::new(tmp.path().join("session-state.json"))
+}
+
+fn tracker(tmp: &TempDir, yaml: &str) -> RiskTracker {
+    RiskTracker::new(&config(yaml), store(tmp), "s1").unwrap()
+}
+
+fn runner(tmp: &TempDir, risk: Option<RiskTracker>) -> CascadeRunner {
+    CascadeRunner {
+        risk,
+        ..common::runner(
+            tmp.path(),
+            Box::new(Fixed {
+                tier: DecisionTier::Supervisor,
+                decision: Decision::Allow,
+            }),
+            Box::new(Fixed {
+                tier: DecisionTier::Human,
+                decision: Decision::Allow,
+            }),
+        )
+    }
+}
+
+#[test]
+fn risk_config_defaults_and_tightening() {
+    let defaults = RiskConfig::default();
+    assert_eq!(PolicyConfig::default().risk, defaults);
+    assert_eq!(defaults.window_secs, 3600);
+    assert_eq!(defaults.threshold, None);
+    assert!(!defaults.exceeded(1000.0));
+
+    let risk = config("threshold: 5");
+    assert!(!risk.exceeded(4.5));
+    assert!(risk.exceeded(5.0));
+    assert!(!risk.permits(DecisionTier::TokenJaccard));
+    assert!(!risk.permits(DecisionTier::EmbeddingSimilarity));
+    assert!(risk.permits(DecisionTier::ExactCache));
+    assert!(risk.permits(DecisionTier::Supervisor));
+
+    let risk = config("threshold: 5\ndisable_similarity: false\nmax_auto_tier: exact_cache");
+    assert_eq!(risk.max_auto_tier, Some(AutoTier::ExactCache));
+    assert!(risk.permits(DecisionTier::ExactCache));
+    assert!(!risk.permits(DecisionTier::TokenJaccard));
+    assert!(!risk.permits(DecisionTier::Supervisor));
+    assert!(risk.permits(DecisionTier::Human));
+}
+
+#[test]
+fn score_weights_events_within_the_window() {
+    let now = Utc::now();
+    let mut state = SessionState::default();
+    let old = now - Duration::hours(2);
+    state.record_risk(&[RiskKind::Denied], old, old);
+    state.record_risk(
+        &[RiskKind::Denied, RiskKind::Sensitive, RiskKind::Novel],
+        now,
+        old,
+    );
+    state.record_risk(&[RiskKind::Novel], now, old);
+
+    let since = now - Duration::hours(1);
+    let risk = state.risk_since(since);
+    assert_eq!(
+        risk,
+        RiskSummary {
+            denied: 1,
+            sensitive: 1,
+            novel: 2
+        }
+    );
+    assert_eq!(risk.score(&RiskConfig::default().weights), 7.0);
+    let weights = config("weights: { denied: 10, novel: 0 }").weights;
+    assert_eq!(risk.score(&weights), 12.0);
+
+    // Recording drops events that have left the window.
+    state.record_risk(&[RiskKind::Novel], now, since);
+    assert_eq!(state.risk_events.len(), 5);
+}
+
+#[test]
+fn calls_add_denied_sensitive_and_novel_events() {
+    let tmp = TempDir::new().unwrap();
+    let tracker = tracker(&tmp, "{}");
+    let mut session = session();
+    session.path_policy = Some(Arc::new(
+        CompiledPathPolicy::compile(
+            &PathPolicyConfig {
+                allow_write: Vec::new(),
+                deny_write: Vec::new(),
+                allow_read: Vec::new(),
+            },
+            &["**/.env*".to_string()],
+        )
+        .unwrap(),
+    ));
+    let input = |file_path: &str| CascadeInput {
+        sanitized_input: file_path.into(),
+        ..common::input(
+            session.clone(),
+            "Write",
+            serde_json::json!({ "file_path": file_path }),
+        )
+    };
+
+    let env = input("config/.env.local");
+    assert_eq!(
+        tracker.events(
+            &env,
+            &record(&env, DecisionTier::PathPolicy, Decision::Deny)
+        ),
+        vec![RiskKind::Denied, RiskKind::Sensitive]
+    );
+    let src = input("src/lib.rs");
+    assert_eq!(
+        tracker.events(
+            &src,
+            &record(&src, DecisionTier::Supervisor, Decision::Allow)
+        ),
+        vec![RiskKind::Novel]
+    );
+    assert!(tracker
+        .events(
+            &src,
+            &record(&src, DecisionTier::ExactCache, Decision::Allow)
+        )
+        .is_empty());
+}
+
+#[tokio::test]
+async fn runner_records_risk_for_the_session() {
+    let tmp = TempDir::new().unwrap();
+    let mut runner = runner(&tmp, Some(tracker(&tmp, "{}")));
+    runner.supervisor = Box::new(Fixed {
+        tier: DecisionTier::Supervisor,
+        decision: Decision::Deny,
+    });
+    let input = serde_json::json!({ "command": "rm -rf /srv/data" });
+    runner.evaluate(&session(), "Bash", &input).await.unwrap();
+
+    let risk = tracker(&tmp, "{}").current(Utc::now()).unwrap();
+    assert_eq!(
+        risk,
+        RiskSummary {
+            denied: 1,
+            sensitive: 0,
+            novel: 1
+        }
+    );
+
+    // The repeat is an exact cache hit: denied again, but not novel.
+    runner.evaluate(&session(), "Bash", &input).await.unwrap();
+    let risk = tracker(&tmp, "{}").current(Utc::now()).unwrap();
+    assert_eq!((risk.denied, risk.novel), (2, 1));
+}
+
+#[tokio::test]
+async fn risky_sessions_skip_similarity() {
+    let tmp = TempDir::new().unwrap();
+    let command = |cmd: &str| serde_json::json!({ "command": cmd });
+    let first = command("cargo build --release --locked --offline");
+    let similar = command("cargo build --release --locked --offline --verbose");
+
+    let runner = runner(&tmp, Some(tracker(&tmp, "threshold: 3")));
+    let record = runner.evaluate(&session(), "Bash", &first).await.unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::Supervisor);
+    let record = runner.evaluate(&session(), "Bash", &similar).await.unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::TokenJaccard);
+
+    // A denied call puts the session over the threshold (1 novel + 3):
+    // similar commands go back to the supervisor instead.
+    store(&tmp)
+        .update("s1", |state| {
+            state.record_risk(&[RiskKind::Denied], Utc::now(), Utc::now())
+        })
+        .unwrap();
+    let other = command("cargo build --release --locked --offline --jobs 4");
+    let record = runner.evaluate(&session(), "Bash", &other).await.unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::Supervisor);
+
+    // Exact matches still resolve.
+    let record = runner.evaluate(&session(), "Bash", &first).await.unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::ExactCache);
+}
@@ -0,0 +1,170 @@
+//! Tests for canary rollout of org rules.
+
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use hookwise::config::{OrgPolicyConfig, RolloutConfig};
+use hookwise::decision::{CacheKey, Decision, DecisionRecord, DecisionTier};
+use hookwise::rollout::{bucket, evaluate, summarize, Cohort, Rollout, RolloutEvent};
+use hookwise::scope::ScopeLevel;
+use tempfile::TempDir;
+
+fn org_rule(input: &str, role: &str, decision: Decision, at: DateTime<Utc>) -> DecisionRecord {
+    DecisionRecord {
+        metadata: common::metadata(DecisionTier::Human, "org rule"),
+        timestamp: at,
+        scope: ScopeLevel::Org,
+        ..common::record(input, role, decision)
+    }
+}
+
+fn inputs(records: &[DecisionRecord]) -> Vec<&str> {
+    records
+        .iter()
+        .map(|r| r.key.sanitized_input.as_str())
+        .collect()
+}
+
+fn rollout(percent: u8, since: DateTime<Utc>) -> RolloutConfig {
+    RolloutConfig {
+        since: Some(since),
+        percent,
+        salt: String::new(),
+    }
+}
+
+fn event(
+    cohort: Cohort,
+    enforced: Decision,
+    rolled_out: Decision,
+    since: DateTime<Utc>,
+) -> RolloutEvent {
+    RolloutEvent {
+        logged_at: Utc::now(),
+        since,
+        session_id: "s-1".into(),
+        cohort,
+        rule: "dec-000000000000".into(),
+        tool: "Bash".into(),
+        role: "coder".into(),
+        enforced,
+        rolled_out,
+    }
+}
+
+#[test]
+fn sessions_are_bucketed_stably_and_roughly_by_percent() {
+    assert_eq!(bucket("session-1", ""), bucket("session-1", ""));
+    let since = Utc::now();
+    let canary = (0..1000)
+        .filter_map(|i| Rollout::for_session(&rollout(20, since), &format!("session-{}", i)))
+        .filter(|r| r.cohort == Cohort::Canary)
+        .count();
+    assert!((150..250).contains(&canary), "{} canary sessions", canary);
+
+    // Nothing rolls out without `since`, or at 100%.
+    assert!(Rollout::for_session(&RolloutConfig::default(), "session-1").is_none());
+    assert!(Rollout::for_session(&rollout(100, since), "session-1").is_none());
+    let control = Rollout::for_session(&rollout(0, since), "session-1").unwrap();
+    assert_eq!(control.cohort, Cohort::Control);
+}
+
+#[test]
+fn control_sessions_hold_back_new_rules() {
+    let since = Utc::now();
+    let old = org_rule("curl", "*", Decision::Ask, since - Duration::days(3));
+    let new = org_rule(
+        "npm publish",
+        "*",
+        Decision::Deny,
+        since + Duration::hours(1),
+    );
+
+    let control = Rollout::for_session(&rollout(0, since), "s-1").unwrap();
+    let (enforced, rolled_out) = control.split(vec![old.clone(), new.clone()]);
+    assert_eq!(inputs(&enforced), ["curl"]);
+    assert_eq!(inputs(&rolled_out), ["npm publish"]);
+
+    let canary = Rollout {
+        since,
+        percent: 10,
+        cohort: Cohort::Canary,
+    };
+    let (enforced, rolled_out) = canary.split(vec![old, new]);
+    assert_eq!(inputs(&enforced), ["curl", "npm publish"]);
+    assert_eq!(inputs(&rolled_out), ["npm publish"]);
+}
+
+#[test]
+fn shadow_evaluation_matches_role_or_wildcard_with_deny_first() {
+    let at = Utc::now();
+    let rules = vec![
+        org_rule("npm publish", "*", Decision::Ask, at),
+        org_rule("npm publish", "coder", Decision::Deny, at),
+        org_rule("npm publish", "reviewer", Decision::Allow, at),
+    ];
+    let key = |role: &str| CacheKey {
+        sanitized_input: "npm publish".into(),
+        tool: "Bash".into(),
+        role: role.into(),
+    };
+    assert_eq!(
+        evaluate(&rules, &key("coder")).unwrap().decision,
+        Decision::Deny
+    );
+    assert_eq!(
+        evaluate(&rules, &key("tester")).unwrap().decision,
+        Decision::Ask
+    );
+    let other = CacheKey {
+        sanitized_input: "ls".into(),
+        ..key("coder")
+    };
+    assert!(evaluate(&rules, &other).is_none());
+}
+
+#[test]
+fn summary_reports_divergence_for_the_current_rollout() {
+    let since = Utc::now();
+    let earlier = since - Duration::days(7);
+    let events = vec![
+        event(Cohort::Control, Decision::Allow, Decision::Deny, since),
+        event(Cohort::Control, Decision::Deny, Decision::Deny, since),
+        event(Cohort::Control, Decision::Allow, Decision::Deny, since),
+        event(Cohort::Control, Decision::Allow, Decision::Ask, since),
+        event(Cohort::Canary, Decision::Deny, Decision::Deny, since),
+        event(Cohort::Control, Decision::Allow, Decision::Deny, earlier),
+    ];
+
+    let summary = summarize(&events, since);
+    assert_eq!(summary.canary_hits, 1);
+    assert_eq!(summary.shadow_hits, 4);
+    assert_eq!(summary.diverged, 3);
+    assert_eq!(summary.divergence_rate(), Some(0.75));
+    assert_eq!(summary.changes[&("allow".into(), "deny".into())], 2);
+    assert_eq!(summary.changes[&("allow".into(), "ask".into())], 1);
+}
+
+#[test]
+fn org_policy_is_read_from_the_org_directory() {
+    let tmp = TempDir::new().unwrap();
+    assert!(OrgPolicyConfig::load(tmp.path(), "acme")
+        .unwrap()
+        .rollout
+        .since
+        .is_none());
+
+    let dir = tmp.path().join("org").join("acme");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("policy.yml"),
+        "rollout:\n  since: 2026-10-01T00:00:00Z\n  percent: 25\n",
+    )
+    .unwrap();
+    let policy = OrgPolicyConfig::load(tmp.path(), "acme").unwrap();
+    assert_eq!(policy.rollout.percent, 25);
+    assert_eq!(
+        policy.rollout.since.unwrap().to_rfc3339(),
+        "2026-10-01T00:00:00+00:00"
+    );
+}
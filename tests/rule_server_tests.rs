@@ -0,0 +1,235 @@
+//! Tests for syncing org rules from a rule server over the HTTP sync
+//! protocol: full pulls, ETag-conditional requests, deltas, and validation
+//! before anything is written.
+
+mod common;
+
+use chrono::Utc;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use hookwise::config::OrgRulesConfig;
+use hookwise::decision::{CacheKey, Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::identity::Identity;
+use hookwise::sync::{self, http, RuleChange};
+
+fn line(command: &str, decision: Decision) -> String {
+    let record = DecisionRecord {
+        key: CacheKey {
+            sanitized_input: command.into(),
+            tool: "Bash".into(),
+            role: "*".into(),
+        },
+        decision,
+        metadata: common::metadata(DecisionTier::Human, "org rule"),
+        timestamp: Utc::now(),
+        scope: ScopeLevel::Org,
+        file_path: None,
+        session_id: "server".into(),
+    };
+    serde_json::to_string(&record).unwrap() + "\n"
+}
+
+/// A canned response: status line, ETag and JSON body.
+struct Reply {
+    status: &'static str,
+    etag: Option<&'static str>,
+    body: String,
+}
+
+fn ok(etag: &'static str, body: serde_json::Value) -> Reply {
+    Reply {
+        status: "200 OK",
+        etag: Some(etag),
+        body: body.to_string(),
+    }
+}
+
+/// Answer one connection per reply, in order, returning the requests
+/// received.
+async fn serve(listener: TcpListener, replies: Vec<Reply>) -> Vec<String> {
+    let mut requests = Vec::new();
+    for reply in replies {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0u8; 8192];
+        let mut request = String::new();
+        while !request.contains("\r\n\r\n") {
+            let n = stream.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            request.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+        let etag = reply
+            .etag
+            .map(|e| format!("etag: {}\r\n", e))
+            .unwrap_or_default();
+        let response = format!(
+            "HTTP/1.1 {}\r\ncontent-type: application/json\r\n{}content-length: {}\r\nconnection: close\r\n\r\n{}",
+            reply.status,
+            etag,
+            reply.body.len(),
+            reply.body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        requests.push(request.to_lowercase());
+    }
+    requests
+}
+
+fn summary(changes: &[RuleChange]) -> Vec<String> {
+    changes
+        .iter()
+        .map(|change| {
+            let command = &change.record().key.sanitized_input;
+            match change {
+                RuleChange::Added(r) => format!("+{} {}", r.decision, command),
+                RuleChange::Changed { from, to } => {
+                    format!("~{}>{} {}", from.decision, to.decision, command)
+                }
+                RuleChange::Removed(r) => format!("-{} {}", r.decision, command),
+            }
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn pulls_full_then_conditional_then_deltas() {
+    let tmp = TempDir::new().unwrap();
+    let rules_root = tmp.path().join("state");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server = format!("http://{}", listener.local_addr().unwrap());
+    let replies = vec![
+        ok(
+            "\"v1\"",
+            json!({
+                "protocol": 1,
+                "full": true,
+                "files": {
+                    "rules/allow.jsonl": line("cargo test", Decision::Allow),
+                    "policy.yml": "maintainers: [lead@example.com]\n",
+                    "../evil": "ignored",
+                },
+            }),
+        ),
+        Reply {
+            status: "304 Not Modified",
+            etag: Some("\"v1\""),
+            body: String::new(),
+        },
+        ok(
+            "\"v2\"",
+            json!({
+                "protocol": 1,
+                "full": false,
+                "files": {
+                    "rules/allow.jsonl": null,
+                    "rules/deny.jsonl": line("cargo test", Decision::Deny),
+                },
+            }),
+        ),
+        ok(
+            "\"v3\"",
+            json!({
+                "protocol": 1,
+                "full": false,
+                "files": { "rules/ask.jsonl": line("rm -rf /", Decision::Deny) },
+            }),
+        ),
+    ];
+    let served = tokio::spawn(serve(listener, replies));
+    let dir = sync::org_dir(&rules_root, "acme");
+
+    let report = http::pull(&rules_root, "acme", &server, Some("s3cret"))
+        .await
+        .unwrap();
+    assert_eq!(summary(&report.changes), ["+allow cargo test"]);
+    assert_eq!(report.version, "v1");
+    assert!(dir.join("policy.yml").exists());
+    assert!(!tmp.path().join("state/org/evil").exists());
+
+    let report = http::pull(&rules_root, "acme", &server, Some("s3cret"))
+        .await
+        .unwrap();
+    assert!(report.changes.is_empty());
+
+    let report = http::pull(&rules_root, "acme", &server, Some("s3cret"))
+        .await
+        .unwrap();
+    assert_eq!(summary(&report.changes), ["~allow>deny cargo test"]);
+    assert!(!dir.join("rules/allow.jsonl").exists());
+    // A delta leaves files it doesn't mention alone
+    assert!(dir.join("policy.yml").exists());
+
+    // An invalid delta changes nothing, and the next pull asks from v2 again
+    let err = http::pull(&rules_root, "acme", &server, Some("s3cret"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("rules/ask.jsonl:1"), "{err}");
+    assert!(!dir.join("rules/ask.jsonl").exists());
+    let state = std::fs::read_to_string(dir.join(".sync-state.json")).unwrap();
+    assert!(state.contains("v2"), "{state}");
+
+    let requests = served.await.unwrap();
+    assert!(requests[0].starts_with("get /v1/orgs/acme/rules "));
+    assert!(requests
+        .iter()
+        .all(|r| r.contains("authorization: bearer s3cret")));
+    assert!(!requests[0].contains("if-none-match"));
+    assert!(requests[1].contains("if-none-match: \"v1\""));
+    assert!(requests[3].contains("if-none-match: \"v2\""));
+}
+
+#[tokio::test]
+async fn refused_tokens_and_newer_protocols_are_errors() {
+    let tmp = TempDir::new().unwrap();
+    let rules_root = tmp.path().join("state");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server = format!("http://{}", listener.local_addr().unwrap());
+    let replies = vec![
+        Reply {
+            status: "401 Unauthorized",
+            etag: None,
+            body: String::new(),
+        },
+        ok(
+            "\"v9\"",
+            json!({ "protocol": 2, "full": true, "files": {} }),
+        ),
+    ];
+    let served = tokio::spawn(serve(listener, replies));
+
+    let err = http::pull(&rules_root, "acme", &server, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("token"), "{err}");
+    let err = http::pull(&rules_root, "acme", &server, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("protocol 2"), "{err}");
+    assert!(!sync::org_dir(&rules_root, "acme").exists());
+    served.await.unwrap();
+}
+
+#[test]
+fn push_needs_a_git_source() {
+    let tmp = TempDir::new().unwrap();
+    let config = OrgRulesConfig {
+        repo: None,
+        server: Some("https://rules.example.com".into()),
+        token_env: None,
+        branch: None,
+        org: None,
+    };
+    let identity = Identity {
+        user: "lead@example.com".into(),
+        org: Some("acme".into()),
+        provider: "env".into(),
+        verified: false,
+        expires_at: None,
+    };
+    let err = sync::push(tmp.path(), "acme", &config, &identity).unwrap_err();
+    assert!(err.to_string().contains("rule server"), "{err}");
+}
@@ -0,0 +1,144 @@
+//! Tests for the versioned DecisionRecord layout: `schema_version` on
+//! write, reading older and newer records, and the published JSON Schema.
+
+use chrono::Utc;
+use serde_json::Value;
+use tempfile::TempDir;
+
+use hookwise::decision::{
+    CacheKey, ClientInfo, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
+    SecretFinding, TargetDecision, SCHEMA_VERSION,
+};
+use hookwise::hook_io::HookFormat;
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::StorageBackend;
+
+fn full_record() -> DecisionRecord {
+    let key = CacheKey {
+        sanitized_input: "cat <SECRET>".into(),
+        tool: "Bash".into(),
+        role: "coder".into(),
+    };
+    DecisionRecord {
+        key: key.clone(),
+        decision: Decision::Ask,
+        metadata: DecisionMetadata {
+            tier: DecisionTier::TokenJaccard,
+            confidence: 0.8,
+            reason: "similar to an earlier ask".into(),
+            matched_key: Some(key),
+            similarity_score: Some(0.9),
+            targets: vec![TargetDecision {
+                path: "src/main.rs".into(),
+                decision: Some(Decision::Allow),
+            }],
+            message: Some("ask the lead".into()),
+            suggestion: Some("use the reviewer role".into()),
+            risk_score: Some(2.5),
+            secrets: vec![SecretFinding {
+                kind: "bearer_token".into(),
+                count: 1,
+            }],
+            client: Some(ClientInfo {
+                format: HookFormat::Gemini,
+                assistant: "gemini-cli".into(),
+                version: Some("0.9.0".into()),
+            }),
+        },
+        timestamp: Utc::now(),
+        scope: ScopeLevel::Project,
+        file_path: Some("src/main.rs".into()),
+        session_id: "schema".into(),
+    }
+}
+
+const V1_LINE: &str = r#"{"key":{"sanitized_input":"ls","tool":"Bash","role":"coder"},"decision":"allow","metadata":{"tier":"Human","confidence":1.0,"reason":"approved","matched_key":null,"similarity_score":null},"timestamp":"2025-01-01T00:00:00Z","scope":"project","file_path":null,"session_id":"old"}"#;
+
+#[test]
+fn records_are_written_with_the_schema_version_first() {
+    let line = serde_json::to_string(&full_record()).unwrap();
+    assert!(
+        line.starts_with(&format!("{{\"schema_version\":{},", SCHEMA_VERSION)),
+        "{line}"
+    );
+    let read: DecisionRecord = serde_json::from_str(&line).unwrap();
+    assert_eq!(read.key, full_record().key);
+    assert_eq!(read.metadata.secrets, full_record().metadata.secrets);
+}
+
+#[test]
+fn unversioned_records_read_as_version_one() {
+    let record: DecisionRecord = serde_json::from_str(V1_LINE).unwrap();
+    assert_eq!(record.key.sanitized_input, "ls");
+    assert_eq!(record.decision, Decision::Allow);
+    assert!(record.metadata.targets.is_empty());
+
+    // Rewritten in the current layout
+    let line = serde_json::to_string(&record).unwrap();
+    assert!(line.contains(&format!("\"schema_version\":{}", SCHEMA_VERSION)));
+}
+
+#[test]
+fn records_from_a_newer_version_are_rejected_and_skipped_on_load() {
+    let newer = V1_LINE.replacen('{', "{\"schema_version\":99,", 1);
+    let err = serde_json::from_str::<DecisionRecord>(&newer).unwrap_err();
+    assert!(err.to_string().contains("upgrade hookwise"), "{err}");
+
+    let tmp = TempDir::new().unwrap();
+    let rules = tmp.path().join(".hookwise/rules");
+    std::fs::create_dir_all(&rules).unwrap();
+    std::fs::write(rules.join("allow.jsonl"), format!("{newer}\n{V1_LINE}\n")).unwrap();
+    let storage = JsonlStorage::new(
+        tmp.path().join(".hookwise"),
+        tmp.path().join("global"),
+        None,
+    );
+    let loaded = storage.load_decisions(ScopeLevel::Project).unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].session_id, "old");
+}
+
+#[test]
+fn published_schema_matches_the_generated_one() {
+    let published: Value =
+        serde_json::from_str(include_str!("../docs/schema/decision-record.schema.json")).unwrap();
+    assert_eq!(published, hookwise::schema::decision_record());
+}
+
+/// Every field a record can carry is described, and every required one is
+/// written.
+#[test]
+fn schema_covers_every_serialized_field() {
+    let schema = hookwise::schema::decision_record();
+    let record = serde_json::to_value(full_record()).unwrap();
+    let check = |value: &Value, schema: &Value, at: &str| {
+        let properties = schema["properties"].as_object().unwrap();
+        for field in value.as_object().unwrap().keys() {
+            assert!(properties.contains_key(field), "{at}.{field} not in schema");
+        }
+        for field in schema["required"].as_array().unwrap() {
+            let field = field.as_str().unwrap();
+            assert!(value.get(field).is_some(), "{at}.{field} not written");
+        }
+    };
+    check(&record, &schema, "record");
+    check(&record["key"], &schema["$defs"]["cache_key"], "key");
+    check(
+        &record["metadata"],
+        &schema["properties"]["metadata"],
+        "metadata",
+    );
+    check(
+        &record["metadata"]["client"],
+        &schema["properties"]["metadata"]["properties"]["client"],
+        "metadata.client",
+    );
+    assert_eq!(
+        schema["properties"]["schema_version"]["const"],
+        SCHEMA_VERSION
+    );
+    let tiers = schema["properties"]["metadata"]["properties"]["tier"]["enum"]
+        .as_array()
+        .unwrap();
+    assert!(tiers.contains(&record["metadata"]["tier"]));
+}
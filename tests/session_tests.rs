@@ -1,5 +1,7 @@
 //! Unit tests for session registration file handling.
 
+mod common;
+
 use hookwise::session::registration;
 use hookwise::session::RegistrationEntry;
 use chrono::Utc;
@@ -13,6 +15,8 @@ fn make_entry(role: &str) -> RegistrationEntry {
         prompt_path: None,
         registered_at: Utc::now(),
         registered_by: None,
+        mode: None,
+        fallback_roles: Vec::new(),
     }
 }
 
@@ -24,6 +28,8 @@ fn make_entry_with_task(role: &str, task: &str) -> RegistrationEntry {
         prompt_path: None,
         registered_at: Utc::now(),
         registered_by: None,
+        mode: None,
+        fallback_roles: Vec::new(),
     }
 }
 
@@ -162,6 +168,8 @@ fn entry_serialization_roundtrip() {
         prompt_path: Some("/tmp/prompt.md".into()),
         registered_at: Utc::now(),
         registered_by: Some("user@example.com".into()),
+        mode: None,
+        fallback_roles: Vec::new(),
     };
 
     let json = serde_json::to_string(&entry).unwrap();
@@ -177,6 +185,22 @@ fn entry_serialization_roundtrip() {
     );
 }
 
+#[test]
+fn fallback_roles_are_written_only_when_registered() {
+    let single = make_entry("coder");
+    let json = serde_json::to_string(&single).unwrap();
+    assert!(!json.contains("fallback_roles"), "{json}");
+    assert_eq!(single.roles(), ["coder"]);
+
+    let entry = RegistrationEntry {
+        fallback_roles: vec!["tester".into()],
+        ..make_entry("coder")
+    };
+    let json = serde_json::to_string(&entry).unwrap();
+    let read: RegistrationEntry = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.roles(), ["coder", "tester"]);
+}
+
 // ---------------------------------------------------------------------------
 // SessionManager: basic operations with tempfiles
 // ---------------------------------------------------------------------------
@@ -201,7 +225,8 @@ fn session_manager_register_and_check() {
     let _ = mgr.enable(&session_id);
 
     // Register the session
-    mgr.register(&session_id, "coder", None, None).unwrap();
+    mgr.register(&session_id, "coder", None, None, None)
+        .unwrap();
     assert!(mgr.is_registered(&session_id));
 
     // Clean up
@@ -220,7 +245,8 @@ fn session_manager_disable_and_enable() {
 
     let session_id = format!("test-dis-{}", Utc::now().timestamp_nanos_opt().unwrap_or(0));
 
-    mgr.register(&session_id, "coder", None, None).unwrap();
+    mgr.register(&session_id, "coder", None, None, None)
+        .unwrap();
     assert!(!mgr.is_disabled(&session_id));
 
     mgr.disable(&session_id).unwrap();
@@ -265,3 +291,260 @@ fn scope_level_display() {
     assert_eq!(format!("{}", ScopeLevel::User), "user");
     assert_eq!(format!("{}", ScopeLevel::Role), "role");
 }
+
+// ---------------------------------------------------------------------------
+// session-check registration prompt
+// ---------------------------------------------------------------------------
+
+#[test]
+fn registration_prompt_uses_claude_additional_context() {
+    use hookwise::cli::session_check::registration_output;
+    use hookwise::hook_io::HookFormat;
+
+    let output = registration_output(HookFormat::Claude, "abc-123", &["coder", "tester"], None);
+    let specific = &output["hookSpecificOutput"];
+    assert_eq!(specific["hookEventName"], "UserPromptSubmit");
+    let context = specific["additionalContext"].as_str().unwrap();
+    assert!(context.contains("Available roles: coder, tester"));
+    assert!(context.contains("`hookwise_register` MCP tool"));
+    assert!(context.contains("session_id \"abc-123\""));
+    assert!(context.contains("hookwise register --session-id abc-123 --role <ROLE>"));
+}
+
+#[test]
+fn registration_prompt_uses_gemini_decision_and_reason() {
+    use hookwise::cli::session_check::registration_output;
+    use hookwise::hook_io::HookFormat;
+
+    let output = registration_output(HookFormat::Gemini, "abc-123", &["coder"], None);
+    assert_eq!(output["decision"], "deny");
+    let reason = output["reason"].as_str().unwrap();
+    assert!(reason.contains("hookwise register --session-id abc-123 --role <ROLE>"));
+    assert!(reason.contains("hookwise_register"));
+    assert!(output.get("hookSpecificOutput").is_none());
+}
+
+#[test]
+fn registration_prompt_offers_the_recommended_role() {
+    use hookwise::cli::session_check::registration_output;
+    use hookwise::config::RoleRecommendation;
+    use hookwise::hook_io::HookFormat;
+
+    let recommended = RoleRecommendation {
+        role: "tester".into(),
+        reason: "3 of 4 changed files are ones it may write".into(),
+    };
+    let output = registration_output(
+        HookFormat::Claude,
+        "abc-123",
+        &["coder", "tester"],
+        Some(&recommended),
+    );
+    let context = output["hookSpecificOutput"]["additionalContext"]
+        .as_str()
+        .unwrap();
+    assert!(context.contains(
+        "Suggested role: tester (3 of 4 changed files are ones it may write). Offer it first"
+    ));
+
+    let output = registration_output(
+        HookFormat::Gemini,
+        "abc-123",
+        &["tester"],
+        Some(&recommended),
+    );
+    assert!(output["reason"]
+        .as_str()
+        .unwrap()
+        .contains("Suggested role: tester"));
+}
+
+// ---------------------------------------------------------------------------
+// Role recommendations
+// ---------------------------------------------------------------------------
+
+/// The roles `hookwise init` writes: source, tests-only and unrestricted.
+fn init_roles() -> hookwise::config::RolesConfig {
+    use hookwise::config::{RoleDefinition, RolesConfig};
+
+    RolesConfig::default()
+        .with_role(
+            RoleDefinition::new("coder", "writes source")
+                .with_allow_write(["{{source}}", "{{config_files}}"])
+                .with_deny_write(["{{tests}}", "{{docs}}"]),
+        )
+        .with_role(
+            RoleDefinition::new("tester", "writes tests")
+                .with_allow_write(["{{tests}}", "{{test_config}}"])
+                .with_deny_write(["{{source}}", "{{docs}}"]),
+        )
+        .with_role(RoleDefinition::new("maintainer", "anything").with_allow_write(["**"]))
+        .expanded()
+        .unwrap()
+}
+
+fn paths(paths: &[&str]) -> Vec<String> {
+    paths.iter().map(|p| p.to_string()).collect()
+}
+
+#[test]
+fn recommend_role_prefers_the_narrowest_role_writing_most_changes() {
+    let roles = init_roles();
+    let tracked = paths(&[
+        "src/main.rs",
+        "src/lib.rs",
+        "src/parser.rs",
+        "tests/parser.rs",
+        "docs/guide.md",
+        "Cargo.toml",
+    ]);
+    let recommend = |changed: &[&str]| {
+        roles
+            .recommend_role(&paths(changed), &tracked, None)
+            .map(|r| r.role)
+    };
+
+    let tests = roles
+        .recommend_role(
+            &paths(&["tests/parser.rs", "tests/lexer.rs", "src/parser.rs"]),
+            &tracked,
+            None,
+        )
+        .unwrap();
+    assert_eq!(tests.role, "tester");
+    assert_eq!(tests.reason, "2 of 3 changed files are ones it may write");
+    assert_eq!(
+        recommend(&["src/parser.rs", "Cargo.toml"]).as_deref(),
+        Some("coder")
+    );
+    // Half source, half tests: only the unrestricted role covers most
+    assert_eq!(
+        recommend(&["src/parser.rs", "tests/parser.rs", "docs/guide.md"]).as_deref(),
+        Some("maintainer")
+    );
+    assert_eq!(recommend(&[]), None);
+}
+
+#[test]
+fn recommend_role_falls_back_to_the_role_the_prompt_names() {
+    let roles = init_roles();
+    let recommend = |prompt: &str| roles.recommend_role(&[], &[], Some(prompt));
+
+    let named = recommend("Add tests for the parser").unwrap();
+    assert_eq!(named.role, "tester");
+    assert_eq!(named.reason, "the prompt mentions \"tests\"");
+    assert_eq!(recommend("fix the CODE formatting").unwrap().role, "coder");
+    // Naming two roles, or none, settles nothing
+    assert_eq!(recommend("code and tests"), None);
+    assert_eq!(recommend("what time is it"), None);
+}
+
+// ---------------------------------------------------------------------------
+// Closest-role suggestions
+// ---------------------------------------------------------------------------
+
+fn roles_config(names: &[&str]) -> hookwise::config::RolesConfig {
+    let mut yaml = String::from("roles:\n");
+    for name in names {
+        yaml.push_str(&format!(
+            "  {name}:\n    name: {name}\n    description: test\n    paths:\n      allow_write: []\n      deny_write: []\n      allow_read: []\n"
+        ));
+    }
+    serde_yaml::from_str(&yaml).unwrap()
+}
+
+#[test]
+fn closest_role_matches_typos_case_and_prefixes() {
+    let roles = roles_config(&["coder", "tester", "devops", "docs", "maintainer"]);
+    assert_eq!(roles.closest_role("codr"), Some("coder"));
+    assert_eq!(roles.closest_role("Tester"), Some("tester"));
+    assert_eq!(roles.closest_role("maintiner"), Some("maintainer"));
+    assert_eq!(roles.closest_role("dev"), Some("devops"));
+    assert_eq!(roles.closest_role("astronaut"), None);
+    assert_eq!(roles.closest_role(""), None);
+}
+
+#[test]
+fn resolve_role_only_substitutes_when_asked() {
+    use hookwise::cli::register::{resolve_role, RoleMatch};
+
+    let roles = roles_config(&["coder", "tester"]);
+    assert_eq!(resolve_role(&roles, "coder", false), RoleMatch::Exact("coder"));
+    assert_eq!(
+        resolve_role(&roles, "codr", false),
+        RoleMatch::Unknown {
+            suggestion: Some("coder")
+        }
+    );
+    assert_eq!(resolve_role(&roles, "codr", true), RoleMatch::Closest("coder"));
+    assert_eq!(
+        resolve_role(&roles, "zzzzzz", true),
+        RoleMatch::Unknown { suggestion: None }
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Capabilities summary
+// ---------------------------------------------------------------------------
+
+fn cached(
+    input: &str,
+    tool: &str,
+    role: &str,
+    decision: hookwise::decision::Decision,
+) -> hookwise::decision::DecisionRecord {
+    let mut record = common::record(input, role, decision);
+    record.key.tool = tool.into();
+    record
+}
+
+#[test]
+fn capabilities_report_role_globs_and_cached_tools() {
+    use hookwise::config::PolicyConfig;
+    use hookwise::decision::Decision;
+    use hookwise::session::capabilities::{Autonomy, Capabilities, DecisionCounts};
+
+    let roles = roles_config(&["coder", "tester"]);
+    let coder = roles.get_role("coder").unwrap();
+    let decisions = vec![
+        cached("cargo build", "Bash", "coder", Decision::Allow),
+        cached("rm -rf /", "Bash", "*", Decision::Deny),
+        cached("cargo test", "Bash", "tester", Decision::Allow),
+        cached("src/lib.rs", "Write", "coder", Decision::Ask),
+    ];
+
+    let caps = Capabilities::new("s-1", false, Some(coder), &PolicyConfig::default(), &decisions);
+    assert_eq!(caps.autonomy, Autonomy::Gated);
+    assert_eq!(caps.role.as_deref(), Some("coder"));
+    assert!(caps.sensitive_paths.contains(&".env*".to_string()));
+    assert_eq!(
+        caps.tools.cached["Bash"],
+        DecisionCounts {
+            allow: 1,
+            deny: 1,
+            ask: 0
+        }
+    );
+    assert_eq!(caps.tools.cached["Write"].ask, 1);
+    assert!(caps.tools.write_checked.contains(&"Bash".to_string()));
+
+    let json = serde_json::to_value(&caps).unwrap();
+    assert_eq!(json["autonomy"], "gated");
+    assert_eq!(json["path_precedence"], "sensitive_first");
+}
+
+#[test]
+fn capabilities_autonomy_levels() {
+    use hookwise::config::PolicyConfig;
+    use hookwise::session::capabilities::{Autonomy, Capabilities};
+
+    let policy = PolicyConfig::default();
+    let unregistered = Capabilities::new("s-2", false, None, &policy, &[]);
+    assert_eq!(unregistered.autonomy, Autonomy::Unregistered);
+    assert!(unregistered.role.is_none());
+    assert!(unregistered.allow_write.is_empty());
+
+    let roles = roles_config(&["coder"]);
+    let disabled = Capabilities::new("s-3", true, roles.get_role("coder"), &policy, &[]);
+    assert_eq!(disabled.autonomy, Autonomy::Unrestricted);
+}
@@ -0,0 +1,128 @@
+//! Tests for compound Bash command decomposition.
+
+use hookwise::cascade::shell::{split_commands, split_words};
+
+#[test]
+fn splits_on_control_operators() {
+    assert_eq!(
+        split_commands("ls && rm -rf src; echo done || true | tee log & wait"),
+        vec!["ls", "rm -rf src", "echo done", "true", "tee log", "wait"]
+    );
+    assert_eq!(
+        split_commands("cargo build\ncargo test"),
+        vec!["cargo build", "cargo test"]
+    );
+}
+
+#[test]
+fn substitutions_become_commands_of_their_own() {
+    assert_eq!(
+        split_commands("echo $(rm -rf src) > out.txt"),
+        vec!["rm -rf src", "echo  > out.txt"]
+    );
+    assert_eq!(
+        split_commands("echo `touch a.txt`"),
+        vec!["touch a.txt", "echo"]
+    );
+    assert_eq!(
+        split_commands("diff <(ls a) <(ls b)"),
+        vec!["ls a", "ls b", "diff"]
+    );
+    assert_eq!(
+        split_commands(r#"echo "$(cd src && rm x.rs)""#),
+        vec!["cd src", "rm x.rs", r#"echo """#]
+    );
+}
+
+#[test]
+fn quotes_and_escapes_are_not_split() {
+    assert_eq!(
+        split_commands(r#"git commit -m "fix; && |" && echo 'a | b'"#),
+        vec![r#"git commit -m "fix; && |""#, "echo 'a | b'"]
+    );
+    assert_eq!(split_commands(r"echo a\;b"), vec![r"echo a\;b"]);
+    assert_eq!(
+        split_commands("echo '$(rm -rf src)'"),
+        vec!["echo '$(rm -rf src)'"]
+    );
+}
+
+#[test]
+fn redirections_are_not_split() {
+    assert_eq!(
+        split_commands("make 2>&1 | tee log"),
+        vec!["make 2>&1", "tee log"]
+    );
+    assert_eq!(
+        split_commands("make &> build.log"),
+        vec!["make &> build.log"]
+    );
+    assert_eq!(split_commands("echo x >| file"), vec!["echo x >| file"]);
+}
+
+#[test]
+fn subshells_and_keywords_are_unwrapped() {
+    assert_eq!(
+        split_commands("(cd src && rm -rf gen) && { touch a; }"),
+        vec!["cd src", "rm -rf gen", "touch a"]
+    );
+    assert_eq!(
+        split_commands("if test -f x; then rm x; fi"),
+        vec!["test -f x", "rm x"]
+    );
+    assert_eq!(
+        split_commands("for f in *.rs; do rm $f; done"),
+        vec!["for f in *.rs", "rm $f"]
+    );
+}
+
+#[test]
+fn prefixes_are_dropped() {
+    assert_eq!(split_commands("sudo -E rm -rf /etc"), vec!["rm -rf /etc"]);
+    assert_eq!(
+        split_commands("RUST_LOG=debug env -i nohup cargo test"),
+        vec!["cargo test"]
+    );
+    assert_eq!(
+        split_commands("find . -name '*.o' | xargs -0 rm"),
+        vec!["find . -name '*.o'", "rm"]
+    );
+}
+
+#[test]
+fn heredoc_bodies_are_skipped() {
+    let command = "cat > notes.md <<'EOF'\nrm -rf src && echo hi\nEOF\nls";
+    assert_eq!(
+        split_commands(command),
+        vec!["cat > notes.md <<'EOF'", "ls"]
+    );
+    let tabbed = "cat <<-END > a.txt\n\trm x\n\tEND\ntouch b";
+    assert_eq!(
+        split_commands(tabbed),
+        vec!["cat <<-END > a.txt", "touch b"]
+    );
+    assert_eq!(
+        split_commands("cat <<< 'rm x'; ls"),
+        vec!["cat <<< 'rm x'", "ls"]
+    );
+}
+
+#[test]
+fn unterminated_input_does_not_panic() {
+    assert_eq!(split_commands("echo $(rm x"), vec!["rm x", "echo"]);
+    assert_eq!(split_commands("echo `ls"), vec!["ls", "echo"]);
+    assert_eq!(split_commands("echo \"open"), vec!["echo \"open"]);
+    assert_eq!(split_commands("echo $("), vec!["echo"]);
+    assert!(split_commands("").is_empty());
+    assert!(split_commands(" ; && ").is_empty());
+}
+
+#[test]
+fn words_are_unquoted_like_the_shell() {
+    assert_eq!(
+        split_words(r#"python3 -c "print('a b')" x\ y 'it''s' "a\"b\$c\d""#),
+        vec!["python3", "-c", "print('a b')", "x y", "its", r#"a"b$c\d"#]
+    );
+    assert_eq!(split_words("  a\n\tb  "), vec!["a", "b"]);
+    assert_eq!(split_words("echo ''"), vec!["echo", ""]);
+}
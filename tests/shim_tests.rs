@@ -0,0 +1,178 @@
+//! Tests for `hookwise-shim`: which calls it decides locally or over IPC,
+//! and which it hands to the full `hookwise check`.
+
+use tempfile::TempDir;
+
+use hookwise::config::default_socket_path;
+use hookwise::decision::{Decision, DecisionTier};
+use hookwise::hook_io::{HookFormat, HookInput};
+use hookwise::session::SessionManager;
+use hookwise::shim::{self, ShimOutcome};
+
+fn input(
+    tmp: &TempDir,
+    session_id: &str,
+    tool_name: &str,
+    tool_input: serde_json::Value,
+) -> HookInput {
+    serde_json::from_value(serde_json::json!({
+        "session_id": session_id,
+        "cwd": tmp.path(),
+        "tool_name": tool_name,
+        "tool_input": tool_input,
+    }))
+    .unwrap()
+}
+
+fn bash(tmp: &TempDir, session_id: &str, command: &str) -> HookInput {
+    input(
+        tmp,
+        session_id,
+        "Bash",
+        serde_json::json!({ "command": command }),
+    )
+}
+
+fn decided(outcome: ShimOutcome) -> hookwise::decision::DecisionRecord {
+    match outcome {
+        ShimOutcome::Decided(record) => record,
+        ShimOutcome::Handoff(reason) => panic!("handed off: {reason}"),
+    }
+}
+
+fn handoff(outcome: ShimOutcome) -> String {
+    match outcome {
+        ShimOutcome::Handoff(reason) => reason,
+        ShimOutcome::Decided(record) => panic!("decided: {record:?}"),
+    }
+}
+
+// One test: the shim reads the session registry from process-wide env vars
+#[cfg(unix)]
+#[tokio::test]
+async fn shim_decides_locally_or_over_ipc_and_hands_off_the_rest() {
+    let tmp = TempDir::new().unwrap();
+    let runtime = tmp.path().join("runtime");
+    std::fs::create_dir_all(&runtime).unwrap();
+    let team = format!("shim-test-{}", std::process::id());
+    std::env::set_var("XDG_RUNTIME_DIR", &runtime);
+    std::env::set_var("XDG_CONFIG_HOME", tmp.path().join("config"));
+    std::env::set_var("CLAUDE_TEAM_ID", &team);
+
+    assert_cmd::cargo::cargo_bin_cmd!("hookwise")
+        .arg("init")
+        .current_dir(tmp.path())
+        .assert()
+        .success();
+
+    // Unregistered sessions need the registration prompt from the full check
+    let outcome = shim::evaluate(&bash(&tmp, "shim-1", "cargo build"), HookFormat::Claude)
+        .await
+        .unwrap();
+    assert!(handoff(outcome).contains("not registered"));
+
+    SessionManager::new(Some(&team))
+        .register("shim-1", "coder", None, None, None)
+        .unwrap();
+
+    // Path policy decides locally
+    let write = input(
+        &tmp,
+        "shim-1",
+        "Write",
+        serde_json::json!({
+            "file_path": tmp.path().join("tests/new_test.rs"),
+            "content": "",
+        }),
+    );
+    let record = decided(shim::evaluate(&write, HookFormat::Gemini).await.unwrap());
+    assert_eq!(record.decision, Decision::Deny);
+    assert_eq!(record.metadata.tier, DecisionTier::PathPolicy);
+    let client = record.metadata.client.unwrap();
+    assert_eq!(client.format, HookFormat::Gemini);
+    assert_eq!(client.assistant, "gemini-cli");
+
+    // No supervisor listening: the human tier is the full check's job, and
+    // nothing is persisted on the way
+    let allow_rules = tmp.path().join(".hookwise/rules/allow.jsonl");
+    let outcome = shim::evaluate(&bash(&tmp, "shim-1", "cargo build"), HookFormat::Claude)
+        .await
+        .unwrap();
+    assert!(handoff(outcome).contains("human"));
+    assert_eq!(std::fs::metadata(&allow_rules).unwrap().len(), 0);
+
+    // A supervisor daemon decides over IPC, and the decision is kept
+    let socket = default_socket_path(Some(&team));
+    let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+    let server = tokio::spawn(async move {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            stream.read_to_end(&mut request).await.unwrap();
+            let reply = serde_json::json!({
+                "decision": "allow",
+                "confidence": 0.99,
+                "reason": "build is fine",
+            });
+            stream
+                .write_all(reply.to_string().as_bytes())
+                .await
+                .unwrap();
+        }
+    });
+
+    let record = decided(
+        shim::evaluate(&bash(&tmp, "shim-1", "cargo build"), HookFormat::Claude)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(record.decision, Decision::Allow);
+    assert_eq!(record.metadata.tier, DecisionTier::Supervisor);
+    server.abort();
+    let _ = std::fs::remove_file(&socket);
+
+    // The next identical call hits the exact cache without the daemon
+    let record = decided(
+        shim::evaluate(&bash(&tmp, "shim-1", "cargo build"), HookFormat::Claude)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(record.decision, Decision::Allow);
+    assert_eq!(record.metadata.tier, DecisionTier::ExactCache);
+
+    // Disabled sessions go to the full check, which expires lapsed disables
+    SessionManager::new(Some(&team)).disable("shim-1").unwrap();
+    let outcome = shim::evaluate(&bash(&tmp, "shim-1", "cargo build"), HookFormat::Claude)
+        .await
+        .unwrap();
+    assert!(handoff(outcome).contains("disabled"));
+}
+
+#[cfg(unix)]
+#[test]
+fn hand_off_pipes_the_input_to_check_and_returns_its_exit_code() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new().unwrap();
+    let binary = tmp.path().join("hookwise");
+    let record = tmp.path().join("record");
+    std::fs::write(
+        &binary,
+        format!(
+            "#!/bin/sh\necho \"$@\" > {0}\ncat >> {0}\nexit 2\n",
+            record.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let code = shim::hand_off(&binary, HookFormat::Gemini, br#"{"tool_name":"Bash"}"#).unwrap();
+    assert_eq!(code, 2);
+    assert_eq!(
+        std::fs::read_to_string(&record).unwrap(),
+        "check --format gemini\n{\"tool_name\":\"Bash\"}"
+    );
+
+    let missing = tmp.path().join("missing");
+    assert!(shim::hand_off(&missing, HookFormat::Claude, b"{}").is_err());
+}
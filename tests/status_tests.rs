@@ -0,0 +1,67 @@
+//! Tests for `hookwise status` helpers: hook detection, policy
+//! fingerprints and storage sizes.
+
+use tempfile::TempDir;
+
+use hookwise::cli::status::{dir_size, format_size, hook_installs, policy_fingerprint};
+
+#[test]
+fn hooks_are_found_in_settings_and_plugins() {
+    let tmp = TempDir::new().unwrap();
+    let (cwd, home) = (tmp.path().join("project"), tmp.path().join("home"));
+    assert!(hook_installs(&cwd, Some(&home)).is_empty());
+
+    let hook = r#"{"hooks": {"PreToolUse": [{"command": "hookwise check"}]}}"#;
+    std::fs::create_dir_all(cwd.join(".claude")).unwrap();
+    std::fs::write(cwd.join(".claude/settings.local.json"), hook).unwrap();
+    std::fs::write(cwd.join(".claude/settings.json"), "{}").unwrap();
+    let plugin = home.join(".claude/plugins/cache/hookwise/0.3.0/hooks");
+    std::fs::create_dir_all(&plugin).unwrap();
+    std::fs::write(plugin.join("hooks.json"), hook).unwrap();
+
+    assert_eq!(
+        hook_installs(&cwd, Some(&home)),
+        vec![
+            cwd.join(".claude/settings.local.json"),
+            plugin.join("hooks.json")
+        ]
+    );
+    assert_eq!(hook_installs(&cwd, None).len(), 1);
+}
+
+#[test]
+fn policy_fingerprint_tracks_policy_and_roles() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path();
+    assert_eq!(policy_fingerprint(dir), None);
+
+    std::fs::write(dir.join("policy.yml"), "session_mode: enforce\n").unwrap();
+    let policy_only = policy_fingerprint(dir).unwrap();
+    assert_eq!(policy_only.len(), 12);
+    assert_eq!(policy_fingerprint(dir).unwrap(), policy_only);
+
+    std::fs::write(dir.join("roles.yml"), "roles: {}\n").unwrap();
+    let with_roles = policy_fingerprint(dir).unwrap();
+    assert_ne!(with_roles, policy_only);
+
+    // Moving text between the files changes the fingerprint too.
+    std::fs::write(dir.join("policy.yml"), "").unwrap();
+    std::fs::write(dir.join("roles.yml"), "session_mode: enforce\nroles: {}\n").unwrap();
+    assert_ne!(policy_fingerprint(dir).unwrap(), with_roles);
+}
+
+#[test]
+fn storage_sizes_are_summed_and_formatted() {
+    let tmp = TempDir::new().unwrap();
+    assert_eq!(dir_size(&tmp.path().join("missing")), 0);
+    std::fs::create_dir_all(tmp.path().join("a/b")).unwrap();
+    std::fs::write(tmp.path().join("a/one"), vec![0u8; 1000]).unwrap();
+    std::fs::write(tmp.path().join("a/b/two"), vec![0u8; 1048]).unwrap();
+    assert_eq!(dir_size(tmp.path()), 2048);
+
+    assert_eq!(format_size(0), "0 B");
+    assert_eq!(format_size(1023), "1023 B");
+    assert_eq!(format_size(2048), "2.0 KiB");
+    assert_eq!(format_size(5 * 1024 * 1024 + 512 * 1024), "5.5 MiB");
+    assert_eq!(format_size(3 << 40), "3072.0 GiB");
+}
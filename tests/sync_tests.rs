@@ -0,0 +1,293 @@
+//! Tests for `hookwise sync`: validating org rules, diffing effective
+//! decisions, cloning and fast-forwarding an org checkout from a git
+//! repository, and maintainer-only pushes.
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tempfile::TempDir;
+
+use hookwise::config::OrgRulesConfig;
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::identity::Identity;
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::StorageBackend;
+use hookwise::sync::{self, RuleChange};
+
+fn record(command: &str, decision: Decision, scope: ScopeLevel) -> DecisionRecord {
+    DecisionRecord {
+        metadata: common::metadata(DecisionTier::Human, "org rule"),
+        scope,
+        ..common::record(command, "*", decision)
+    }
+}
+
+fn line(command: &str, decision: Decision) -> String {
+    serde_json::to_string(&record(command, decision, ScopeLevel::Org)).unwrap() + "\n"
+}
+
+fn git(repo: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .env("GIT_AUTHOR_NAME", "Lead")
+        .env("GIT_AUTHOR_EMAIL", "lead@example.com")
+        .env("GIT_COMMITTER_NAME", "Lead")
+        .env("GIT_COMMITTER_EMAIL", "lead@example.com")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+/// An org rules repository: a bare `origin.git` and the `seed` working
+/// copy that publishes to it.
+struct OrgRepo {
+    seed: PathBuf,
+    bare: PathBuf,
+}
+
+impl OrgRepo {
+    fn new(tmp: &Path) -> Self {
+        let seed = tmp.join("seed");
+        let bare = tmp.join("origin.git");
+        std::fs::create_dir_all(seed.join("rules")).unwrap();
+        git(&seed, &["init", "-q"]);
+        let repo = OrgRepo { seed, bare };
+        repo.write("policy.yml", "maintainers: [lead@example.com]\n");
+        repo.write("rules/allow.jsonl", &line("cargo test", Decision::Allow));
+        git(&repo.seed, &["add", "--all"]);
+        git(&repo.seed, &["commit", "-q", "-m", "Initial org rules"]);
+        git(
+            tmp,
+            &[
+                "clone",
+                "-q",
+                "--bare",
+                repo.seed.to_str().unwrap(),
+                repo.bare.to_str().unwrap(),
+            ],
+        );
+        git(
+            &repo.seed,
+            &["remote", "add", "origin", repo.bare.to_str().unwrap()],
+        );
+        repo
+    }
+
+    fn write(&self, file: &str, contents: &str) {
+        std::fs::write(self.seed.join(file), contents).unwrap();
+    }
+
+    fn publish(&self, message: &str) {
+        git(&self.seed, &["add", "--all"]);
+        git(&self.seed, &["commit", "-q", "-m", message]);
+        git(&self.seed, &["push", "-q", "origin", "HEAD"]);
+    }
+
+    fn url(&self) -> &str {
+        self.bare.to_str().unwrap()
+    }
+
+    fn config(&self) -> OrgRulesConfig {
+        OrgRulesConfig {
+            repo: Some(self.bare.to_string_lossy().into_owned()),
+            server: None,
+            token_env: None,
+            branch: None,
+            org: None,
+        }
+    }
+}
+
+fn identity(user: &str) -> Identity {
+    Identity {
+        user: user.into(),
+        org: Some("acme".into()),
+        provider: "env".into(),
+        verified: false,
+        expires_at: None,
+    }
+}
+
+fn summary(changes: &[RuleChange]) -> Vec<String> {
+    changes
+        .iter()
+        .map(|change| {
+            let command = &change.record().key.sanitized_input;
+            match change {
+                RuleChange::Added(r) => format!("+{} {}", r.decision, command),
+                RuleChange::Changed { from, to } => {
+                    format!("~{}>{} {}", from.decision, to.decision, command)
+                }
+                RuleChange::Removed(r) => format!("-{} {}", r.decision, command),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn validate_rejects_misfiled_and_malformed_rules() {
+    let check = |files: &[(&str, String)]| {
+        let files: HashMap<&str, String> = files.iter().cloned().collect();
+        sync::validate(|file| Ok(files.get(file).cloned()))
+    };
+
+    let err = check(&[("rules/allow.jsonl", line("rm -rf /", Decision::Deny))]).unwrap_err();
+    assert!(err.to_string().contains("rules/allow.jsonl:1"), "{err}");
+
+    let project =
+        serde_json::to_string(&record("ls", Decision::Allow, ScopeLevel::Project)).unwrap();
+    let err = check(&[("rules/allow.jsonl", project)]).unwrap_err();
+    assert!(err.to_string().contains("scope is project"), "{err}");
+
+    let err = check(&[("rules/ask.jsonl", "{not json".into())]).unwrap_err();
+    assert!(err.to_string().contains("rules/ask.jsonl:1"), "{err}");
+
+    let err = check(&[("policy.yml", "maintainers: 3".into())]).unwrap_err();
+    assert!(err.to_string().contains("policy.yml"), "{err}");
+
+    // Records from a newer hookwise are skipped, not rejected
+    let newer = line("ls", Decision::Allow).replacen('{', "{\"schema_version\":99,", 1);
+    let rules = check(&[(
+        "rules/allow.jsonl",
+        format!("{}{}", newer, line("ls", Decision::Allow)),
+    )])
+    .unwrap();
+    assert_eq!(rules.records.len(), 1);
+    assert_eq!(rules.skipped, 1);
+}
+
+#[test]
+fn diff_compares_effective_decisions() {
+    let before = vec![
+        record("cargo test", Decision::Allow, ScopeLevel::Org),
+        record("git push", Decision::Allow, ScopeLevel::Org),
+        record("curl", Decision::Deny, ScopeLevel::Org),
+    ];
+    let after = vec![
+        record("cargo test", Decision::Allow, ScopeLevel::Org),
+        record("git push", Decision::Allow, ScopeLevel::Org),
+        record("git push", Decision::Ask, ScopeLevel::Org),
+        record("npm publish", Decision::Deny, ScopeLevel::Org),
+    ];
+    assert_eq!(
+        summary(&sync::diff(&before, &after)),
+        ["-deny curl", "~allow>ask git push", "+deny npm publish"]
+    );
+}
+
+#[test]
+fn sync_clones_then_fast_forwards_the_org_checkout() {
+    let tmp = TempDir::new().unwrap();
+    let repo = OrgRepo::new(tmp.path());
+    let rules_root = tmp.path().join("state");
+
+    let report = sync::git::sync(&rules_root, "acme", repo.url(), None).unwrap();
+    assert_eq!(summary(&report.changes), ["+allow cargo test"]);
+    let storage = JsonlStorage::new(
+        tmp.path().join(".hookwise"),
+        rules_root.clone(),
+        Some("acme".into()),
+    );
+    assert_eq!(storage.load_decisions(ScopeLevel::Org).unwrap().len(), 1);
+
+    repo.write("rules/allow.jsonl", "");
+    repo.write(
+        "rules/deny.jsonl",
+        &(line("cargo test", Decision::Deny) + &line("curl", Decision::Deny)),
+    );
+    repo.publish("Deny cargo test and curl");
+    let report = sync::git::sync(&rules_root, "acme", repo.url(), None).unwrap();
+    assert_eq!(
+        summary(&report.changes),
+        ["~allow>deny cargo test", "+deny curl"]
+    );
+    assert_eq!(report.counts(), (1, 1, 0));
+
+    // Nothing new upstream
+    let report = sync::git::sync(&rules_root, "acme", repo.url(), None).unwrap();
+    assert!(report.changes.is_empty());
+}
+
+#[test]
+fn invalid_upstream_rules_leave_the_checkout_alone() {
+    let tmp = TempDir::new().unwrap();
+    let repo = OrgRepo::new(tmp.path());
+    let rules_root = tmp.path().join("state");
+    sync::git::sync(&rules_root, "acme", repo.url(), None).unwrap();
+    let allow = sync::org_dir(&rules_root, "acme").join("rules/allow.jsonl");
+    let before = std::fs::read_to_string(&allow).unwrap();
+
+    repo.write("rules/allow.jsonl", &line("rm -rf /", Decision::Deny));
+    repo.publish("Misfiled rule");
+    let err = sync::git::sync(&rules_root, "acme", repo.url(), None).unwrap_err();
+    assert!(err.to_string().contains("rules/allow.jsonl:1"), "{err}");
+    assert_eq!(std::fs::read_to_string(&allow).unwrap(), before);
+
+    // A first sync of a bad repository leaves no checkout behind
+    let err = sync::git::sync(&rules_root, "other", repo.url(), None).unwrap_err();
+    assert!(err.to_string().contains("rules/allow.jsonl:1"), "{err}");
+    assert!(!sync::org_dir(&rules_root, "other").exists());
+}
+
+#[test]
+fn push_publishes_for_maintainers_only() {
+    let tmp = TempDir::new().unwrap();
+    let repo = OrgRepo::new(tmp.path());
+    let rules_root = tmp.path().join("state");
+    sync::git::sync(&rules_root, "acme", repo.url(), None).unwrap();
+    let checkout = sync::org_dir(&rules_root, "acme");
+    git(&checkout, &["config", "user.name", "Lead"]);
+    git(&checkout, &["config", "user.email", "lead@example.com"]);
+    std::fs::write(
+        checkout.join("rules/deny.jsonl"),
+        line("npm publish", Decision::Deny),
+    )
+    .unwrap();
+
+    // A local policy edit doesn't make anyone a maintainer
+    std::fs::write(checkout.join("policy.yml"), "maintainers: ['*']\n").unwrap();
+    let err = sync::push(
+        &rules_root,
+        "acme",
+        &repo.config(),
+        &identity("dev@example.com"),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("not a maintainer"), "{err}");
+    std::fs::write(
+        checkout.join("policy.yml"),
+        "maintainers: [lead@example.com]\n",
+    )
+    .unwrap();
+
+    let report = sync::push(
+        &rules_root,
+        "acme",
+        &repo.config(),
+        &identity("lead@example.com"),
+    )
+    .unwrap()
+    .expect("changes to publish");
+    assert_eq!(summary(&report.changes), ["+deny npm publish"]);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&repo.bare)
+        .args(["show", "HEAD:rules/deny.jsonl"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("npm publish"));
+
+    assert!(sync::push(
+        &rules_root,
+        "acme",
+        &repo.config(),
+        &identity("lead@example.com")
+    )
+    .unwrap()
+    .is_none());
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use hookwise::cascade::cache::CacheStats;
+use hookwise::telemetry;
+use hookwise::{Decision, DecisionTier};
+
+#[test]
+fn recording_without_provider_is_a_noop() {
+    let _guard = telemetry::init();
+    telemetry::record_decision(DecisionTier::ExactCache, Decision::Allow);
+    telemetry::record_supervisor_latency(Duration::from_millis(120), true);
+    telemetry::record_cascade_latency(Duration::from_millis(3), DecisionTier::Human);
+    telemetry::record_queue_depth(4);
+    telemetry::record_cache_sizes(&CacheStats {
+        total_entries: 3,
+        allow_entries: 1,
+        deny_entries: 1,
+        ask_entries: 1,
+        hits: 0,
+        misses: 0,
+        evictions: 0,
+    });
+    telemetry::shutdown();
+}
@@ -0,0 +1,209 @@
+//! Tests for `max_auto_tier`: per-path ceilings on cascade autonomy.
+
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tempfile::TempDir;
+
+use common::session;
+
+use hookwise::cascade::tier_cap::TierCaps;
+use hookwise::cascade::{CascadeInput, CascadeRunner, CascadeTier};
+use hookwise::config::{AutoTier, PolicyConfig};
+use hookwise::decision::{CacheKey, Decision, DecisionRecord, DecisionTier, ScopeLevel};
+
+/// A tier that always decides `decision`.
+struct Fixed {
+    tier: DecisionTier,
+    decision: Decision,
+}
+
+#[async_trait]
+impl CascadeTier for Fixed {
+    async fn evaluate(
+        &self,
+        input: &CascadeInput,
+    ) -> hookwise::error::Result<Option<DecisionRecord>> {
+        Ok(Some(DecisionRecord {
+            key: CacheKey {
+                sanitized_input: input.sanitized_input.clone(),
+                tool: input.tool_name.clone(),
+                role: "*".into(),
+            },
+            decision: self.decision,
+            metadata: common::metadata(self.tier, "fixed"),
+            timestamp: Utc::now(),
+            scope: ScopeLevel::Project,
+            file_path: input.file_path.clone(),
+            session_id: String::new(),
+        }))
+    }
+    fn tier(&self) -> DecisionTier {
+        self.tier
+    }
+    fn name(&self) -> &str {
+        "fixed"
+    }
+}
+
+fn write_input(file_path: &str) -> CascadeInput {
+    CascadeInput {
+        sanitized_input: file_path.into(),
+        ..common::input(
+            session(),
+            "Write",
+            serde_json::json!({ "file_path": file_path }),
+        )
+    }
+}
+
+fn caps(entries: &[(&str, AutoTier)]) -> TierCaps {
+    let config: BTreeMap<String, AutoTier> = entries
+        .iter()
+        .map(|(pattern, tier)| (pattern.to_string(), *tier))
+        .collect();
+    TierCaps::compile(&config, &HashMap::new())
+        .unwrap()
+        .unwrap()
+}
+
+fn runner(tmp: &TempDir) -> CascadeRunner {
+    common::runner(
+        tmp.path(),
+        Box::new(Fixed {
+            tier: DecisionTier::Supervisor,
+            decision: Decision::Allow,
+        }),
+        Box::new(Fixed {
+            tier: DecisionTier::Human,
+            decision: Decision::Ask,
+        }),
+    )
+}
+
+#[test]
+fn permits_tiers_up_to_the_cap() {
+    let cap = AutoTier::ExactCache;
+    assert!(cap.permits(DecisionTier::PathPolicy));
+    assert!(cap.permits(DecisionTier::ExactCache));
+    assert!(!cap.permits(DecisionTier::TokenJaccard));
+    assert!(!cap.permits(DecisionTier::EmbeddingSimilarity));
+    assert!(!cap.permits(DecisionTier::Supervisor));
+    assert!(cap.permits(DecisionTier::Human));
+    assert!(cap.permits(DecisionTier::Override));
+    assert!(AutoTier::Supervisor.permits(DecisionTier::Supervisor));
+}
+
+#[test]
+fn policy_yaml_parses_max_auto_tier() {
+    let policy: PolicyConfig = serde_yaml::from_str(
+        "max_auto_tier:\n  \"{{infra}}\": exact_cache\n  \"deploy/**\": supervisor\n",
+    )
+    .unwrap();
+    assert_eq!(policy.max_auto_tier["{{infra}}"], AutoTier::ExactCache);
+    assert_eq!(policy.max_auto_tier["deploy/**"], AutoTier::Supervisor);
+    assert!(PolicyConfig::default().max_auto_tier.is_empty());
+}
+
+#[test]
+fn caps_expand_categories_and_lowest_wins() {
+    let caps = caps(&[
+        ("{{infra}}", AutoTier::ExactCache),
+        ("{{ci}}", AutoTier::TokenJaccard),
+        ("**/*.yml", AutoTier::Supervisor),
+    ]);
+    assert_eq!(
+        caps.cap_for(&write_input("infra/main.tf")),
+        Some(AutoTier::ExactCache)
+    );
+    assert_eq!(
+        caps.cap_for(&write_input(".github/workflows/ci.yml")),
+        Some(AutoTier::TokenJaccard)
+    );
+    assert_eq!(
+        caps.cap_for(&write_input("config/app.yml")),
+        Some(AutoTier::Supervisor)
+    );
+    assert_eq!(caps.cap_for(&write_input("src/lib.rs")), None);
+}
+
+#[test]
+fn caps_see_bash_write_targets() {
+    let caps = caps(&[("{{ci}}", AutoTier::ExactCache)]);
+    let command = "echo 'on: push' > .github/workflows/release.yml";
+    let input = CascadeInput {
+        tool_name: "Bash".into(),
+        tool_input: serde_json::json!({ "command": command }),
+        sanitized_input: command.into(),
+        file_path: None,
+        ..write_input("")
+    };
+    assert_eq!(caps.cap_for(&input), Some(AutoTier::ExactCache));
+}
+
+#[test]
+fn empty_config_compiles_to_none_and_unknown_category_errors() {
+    assert!(TierCaps::compile(&BTreeMap::new(), &HashMap::new())
+        .unwrap()
+        .is_none());
+    let config = BTreeMap::from([("{{nope}}".to_string(), AutoTier::ExactCache)]);
+    let err = TierCaps::compile(&config, &HashMap::new())
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("max_auto_tier"), "{}", err);
+}
+
+#[tokio::test]
+async fn capped_paths_skip_the_supervisor() {
+    let tmp = TempDir::new().unwrap();
+    let mut runner = runner(&tmp);
+    let tool_input = serde_json::json!({ "file_path": "infra/main.tf" });
+
+    let record = runner
+        .evaluate(&session(), "Write", &tool_input)
+        .await
+        .unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::Supervisor);
+
+    // The supervisor's allow is now cached, but it may not decide either.
+    runner.tier_caps = Some(caps(&[("{{infra}}", AutoTier::ExactCache)]));
+    let record = runner
+        .evaluate(&session(), "Write", &tool_input)
+        .await
+        .unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::Human);
+    assert_eq!(record.decision, Decision::Ask);
+
+    // Uncapped paths are unaffected.
+    let other = serde_json::json!({ "file_path": "src/lib.rs" });
+    let record = runner.evaluate(&session(), "Write", &other).await.unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::Supervisor);
+}
+
+#[tokio::test]
+async fn capped_paths_still_honor_explicit_rules() {
+    let tmp = TempDir::new().unwrap();
+    let mut runner = runner(&tmp);
+    runner.tier_caps = Some(caps(&[("{{infra}}", AutoTier::ExactCache)]));
+    let tool_input = serde_json::json!({ "file_path": "infra/main.tf" });
+
+    let asked = runner
+        .evaluate(&session(), "Write", &tool_input)
+        .await
+        .unwrap();
+    let mut rule = asked.clone();
+    rule.decision = Decision::Allow;
+    rule.metadata.tier = DecisionTier::Override;
+    runner.exact_cache.insert(rule);
+
+    let record = runner
+        .evaluate(&session(), "Write", &tool_input)
+        .await
+        .unwrap();
+    assert_eq!(record.metadata.tier, DecisionTier::ExactCache);
+    assert_eq!(record.decision, Decision::Allow);
+}
@@ -1,31 +1,18 @@
 //! Unit tests for Tier 2a: token-level Jaccard similarity.
 
+mod common;
+
+use hookwise::cascade::margin::{DenyMargin, MarginVerdict};
 use hookwise::cascade::token_sim::TokenJaccard;
-use hookwise::decision::{
-    CacheKey, Decision, DecisionMetadata, DecisionRecord, DecisionTier, ScopeLevel,
-};
-use chrono::Utc;
+use hookwise::cascade::{CascadeInput, CascadeTier};
+use hookwise::config::{DenyConflict, SimilarityConfig, TokenMode};
+use hookwise::session::SessionContext;
+use hookwise::decision::{Decision, DecisionRecord};
 
 fn make_record(input: &str, tool: &str, role: &str, decision: Decision) -> DecisionRecord {
-    DecisionRecord {
-        key: CacheKey {
-            sanitized_input: input.into(),
-            tool: tool.into(),
-            role: role.into(),
-        },
-        decision,
-        metadata: DecisionMetadata {
-            tier: DecisionTier::Human,
-            confidence: 1.0,
-            reason: "test".into(),
-            matched_key: None,
-            similarity_score: None,
-        },
-        timestamp: Utc::now(),
-        scope: ScopeLevel::Project,
-        file_path: None,
-        session_id: "test".into(),
-    }
+    let mut record = common::record(input, role, decision);
+    record.key.tool = tool.into();
+    record
 }
 
 // ---------------------------------------------------------------------------
@@ -218,14 +205,12 @@ fn load_from_records() {
 // ---------------------------------------------------------------------------
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn design_similarity_never_auto_denies() {
     // This is a design invariant verified in the CascadeTier::evaluate implementation.
-    // When a deny entry is the best match, TokenJaccard returns None (falls through).
-    // This test documents that invariant for reference -- the actual enforcement
-    // is tested in the cascade_integration tests using the async evaluate method.
-    //
-    // From token_sim.rs line 161-162:
-    //   Decision::Deny => Ok(None), // Never auto-deny from similarity
+    // Deny entries are only negative examples: they never resolve, and an
+    // allow/ask match too close to one falls through (see the deny margin
+    // tests below).
     //
     // We verify the threshold and min_tokens configuration here.
     let _tj = TokenJaccard::new(0.7, 3);
@@ -233,3 +218,233 @@ fn design_similarity_never_auto_denies() {
     // min_tokens=3 means queries with <3 tokens are skipped
     assert!(true, "design invariant documented");
 }
+
+// ---------------------------------------------------------------------------
+// TF-IDF token mode
+// ---------------------------------------------------------------------------
+
+fn tokens(input: &str) -> Vec<String> {
+    TokenJaccard::tokenize(input)
+}
+
+#[test]
+fn weighted_jaccard_with_unit_weights_matches_jaccard() {
+    let a = tokens("cargo build --release --target x86_64");
+    let b = tokens("cargo build --target aarch64");
+    let weighted = TokenJaccard::weighted_jaccard(&a, &b, |_| 1.0);
+    assert!((weighted - TokenJaccard::jaccard_coefficient(&a, &b)).abs() < 1e-9);
+}
+
+#[test]
+fn token_mode_parses_and_defaults_to_jaccard() {
+    assert_eq!(SimilarityConfig::default().token_mode, TokenMode::Jaccard);
+    let config: SimilarityConfig = serde_yaml::from_str(
+        "jaccard_threshold: 0.7\nembedding_threshold: 0.85\njaccard_min_tokens: 3\ntoken_mode: tfidf\n",
+    )
+    .unwrap();
+    assert_eq!(config.token_mode, TokenMode::Tfidf);
+}
+
+#[test]
+fn tfidf_discounts_tokens_common_to_the_corpus() {
+    let corpus = [
+        "cargo build --release",
+        "cargo test --release",
+        "cargo clippy --release",
+        "cargo doc --release",
+        "psql migrate production",
+    ];
+    let tfidf = TokenJaccard::new(0.7, 3).with_mode(TokenMode::Tfidf);
+    let plain = TokenJaccard::new(0.7, 3);
+    for input in corpus {
+        tfidf.insert(&make_record(input, "Bash", "coder", Decision::Allow));
+        plain.insert(&make_record(input, "Bash", "coder", Decision::Allow));
+    }
+
+    // Shares only the ubiquitous tokens: TF-IDF scores it lower.
+    let a = tokens("cargo bench --release");
+    let b = tokens("cargo build --release");
+    assert!(tfidf.similarity(&a, &b) < plain.similarity(&a, &b));
+
+    // Shares the rare tokens: TF-IDF scores it higher.
+    let a = tokens("psql migrate production --release");
+    let b = tokens("psql migrate production");
+    assert!(tfidf.similarity(&a, &b) > plain.similarity(&a, &b));
+
+    // Invalidation drops the corpus statistics with the entries.
+    tfidf.invalidate_all();
+    let score = tfidf.similarity(&tokens("cargo bench --release"), &tokens("cargo build --release"));
+    assert!((score - 0.5).abs() < 1e-9);
+}
+
+// ---------------------------------------------------------------------------
+// Deny margin (negative examples)
+// ---------------------------------------------------------------------------
+
+fn bash_input(command: &str) -> CascadeInput {
+    CascadeInput {
+        session: SessionContext {
+            user: "test-user".into(),
+            org: "test-org".into(),
+            project: "test-project".into(),
+            team: None,
+            role: None,
+            path_policy: None,
+            agent_prompt_hash: None,
+            agent_prompt_path: None,
+            task_description: None,
+            registered_at: None,
+            disabled: false,
+            mode: Default::default(),
+            fallback_roles: Vec::new(),
+        },
+        tool_name: "Bash".into(),
+        tool_input: serde_json::Value::Null,
+        sanitized_input: command.into(),
+        file_path: None,
+        cwd: None,
+        recommendation: None,
+    }
+}
+
+fn push_rules(tj: &TokenJaccard) {
+    tj.insert(&make_record("git push origin feature", "Bash", "*", Decision::Allow));
+    tj.insert(&make_record("git push origin main", "Bash", "*", Decision::Deny));
+}
+
+#[test]
+fn margin_verdicts() {
+    let margin = DenyMargin {
+        margin: 0.1,
+        on_conflict: DenyConflict::Fallthrough,
+    };
+    assert_eq!(margin.verdict(0.9, None), MarginVerdict::Resolve);
+    assert_eq!(margin.verdict(0.9, Some(0.7)), MarginVerdict::Resolve);
+    assert_eq!(margin.verdict(0.9, Some(0.85)), MarginVerdict::FallThrough);
+    let ask = DenyMargin {
+        on_conflict: DenyConflict::Ask,
+        ..margin
+    };
+    assert_eq!(ask.verdict(0.9, Some(0.95)), MarginVerdict::Ask);
+}
+
+#[tokio::test]
+async fn allow_equally_close_to_deny_falls_through() {
+    let tj = TokenJaccard::new(0.7, 3);
+    push_rules(&tj);
+    // 0.8 to both the cached allow and the cached deny
+    let result = tj
+        .evaluate(&bash_input("git push origin feature main"))
+        .await
+        .unwrap();
+    assert!(result.is_none());
+}
+
+#[tokio::test]
+async fn allow_clear_of_deny_resolves() {
+    let tj = TokenJaccard::new(0.7, 3);
+    push_rules(&tj);
+    // 0.8 to the allow, 0.5 to the deny
+    let result = tj
+        .evaluate(&bash_input("git push origin feature now"))
+        .await
+        .unwrap()
+        .expect("allow clears the margin");
+    assert_eq!(result.decision, Decision::Allow);
+}
+
+#[tokio::test]
+async fn deny_conflict_can_ask_or_be_disabled() {
+    let tj = TokenJaccard::new(0.7, 3).with_deny_margin(DenyMargin {
+        margin: 0.1,
+        on_conflict: DenyConflict::Ask,
+    });
+    push_rules(&tj);
+    let result = tj
+        .evaluate(&bash_input("git push origin feature main"))
+        .await
+        .unwrap()
+        .expect("conflict asks");
+    assert_eq!(result.decision, Decision::Ask);
+    assert!(result.metadata.reason.contains("cached deny"));
+
+    let tj = TokenJaccard::new(0.7, 3).with_deny_margin(DenyMargin {
+        margin: 0.0,
+        on_conflict: DenyConflict::Fallthrough,
+    });
+    push_rules(&tj);
+    let result = tj
+        .evaluate(&bash_input("git push origin feature main"))
+        .await
+        .unwrap();
+    assert_eq!(result.map(|r| r.decision), Some(Decision::Allow));
+}
+
+#[test]
+fn deny_margin_config_defaults() {
+    let config = SimilarityConfig::default();
+    assert_eq!(config.deny_margin, 0.1);
+    assert_eq!(config.on_deny_conflict, DenyConflict::Fallthrough);
+    let config: SimilarityConfig = serde_yaml::from_str(
+        "jaccard_threshold: 0.7\nembedding_threshold: 0.85\njaccard_min_tokens: 3\ndeny_margin: 0.2\non_deny_conflict: ask\n",
+    )
+    .unwrap();
+    assert_eq!(DenyMargin::from_config(&config).margin, 0.2);
+    assert_eq!(config.on_deny_conflict, DenyConflict::Ask);
+}
+
+// ---------------------------------------------------------------------------
+// (role, tool) partitions
+// ---------------------------------------------------------------------------
+
+fn role_input(role: &str, tool: &str, command: &str) -> CascadeInput {
+    let mut input = bash_input(command);
+    input.tool_name = tool.into();
+    input.session.role = Some(hookwise::config::roles::RoleDefinition {
+        name: role.into(),
+        description: "test role".into(),
+        paths: hookwise::config::roles::PathPolicyConfig {
+            allow_write: vec![],
+            deny_write: vec![],
+            allow_read: vec![],
+        },
+        rule_scope: None,
+    });
+    input
+}
+
+#[tokio::test]
+async fn partitions_isolate_roles_and_tools() {
+    let tj = TokenJaccard::new(0.7, 3);
+    tj.insert(&make_record("cargo publish --dry-run", "Bash", "maintainer", Decision::Allow));
+    tj.insert(&make_record("cargo publish --dry-run", "Task", "tester", Decision::Allow));
+    assert_eq!(tj.partition_count(), 2);
+
+    let same = tj
+        .evaluate(&role_input("maintainer", "Bash", "cargo publish --dry-run --locked"))
+        .await
+        .unwrap();
+    assert_eq!(same.map(|r| r.decision), Some(Decision::Allow));
+
+    // Identical text, but no Bash partition for tester.
+    let other_role = tj
+        .evaluate(&role_input("tester", "Bash", "cargo publish --dry-run --locked"))
+        .await
+        .unwrap();
+    assert!(other_role.is_none());
+
+    tj.invalidate_role("maintainer");
+    assert_eq!(tj.partition_count(), 1);
+}
+
+#[tokio::test]
+async fn wildcard_partition_serves_every_role() {
+    let tj = TokenJaccard::new(0.7, 3);
+    tj.insert(&make_record("cargo build --release", "Bash", "*", Decision::Allow));
+
+    let result = tj
+        .evaluate(&role_input("tester", "Bash", "cargo build --release --locked"))
+        .await
+        .unwrap();
+    assert_eq!(result.map(|r| r.decision), Some(Decision::Allow));
+}
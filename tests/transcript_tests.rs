@@ -0,0 +1,149 @@
+//! Tests for the supervisor transcript log.
+
+mod common;
+
+use std::time::Duration;
+
+use hookwise::cascade::supervisor::{SupervisorExchange, SupervisorRequest};
+use hookwise::cascade::transcript::{
+    find_transcript, load_transcripts, rotated_path, transcript_log_path, TranscriptEntry,
+    TranscriptLog,
+};
+use hookwise::config::SupervisorTranscriptConfig;
+use hookwise::decision::{Decision, DecisionMetadata, DecisionRecord, DecisionTier};
+use hookwise::HookwiseError;
+
+fn make_request() -> SupervisorRequest {
+    SupervisorRequest {
+        session_id: String::new(),
+        role: "coder".into(),
+        role_description: "Writes code".into(),
+        tool_name: "Bash".into(),
+        sanitized_input: r#"{"command":"cargo publish"}"#.into(),
+        file_path: None,
+        task_description: Some("release the crate".into()),
+        agent_prompt_path: None,
+        cwd: String::new(),
+        precedents: Vec::new(),
+    }
+}
+
+fn make_record(decision: Decision) -> DecisionRecord {
+    DecisionRecord {
+        metadata: DecisionMetadata {
+            confidence: 0.8,
+            ..common::metadata(DecisionTier::Supervisor, "publishing is out of scope")
+        },
+        ..common::record(r#"{"command":"cargo publish"}"#, "coder", decision)
+    }
+}
+
+fn make_entry(prompt: &str, reply: &str) -> TranscriptEntry {
+    TranscriptEntry::new(
+        &make_request(),
+        "socket",
+        SupervisorExchange {
+            prompt: prompt.into(),
+            reply: Some(reply.into()),
+        },
+        &Ok(make_record(Decision::Deny)),
+        Duration::from_millis(42),
+    )
+}
+
+fn enabled(max_bytes: u64, redact_patterns: Vec<String>) -> SupervisorTranscriptConfig {
+    SupervisorTranscriptConfig {
+        enabled: true,
+        max_bytes,
+        redact_patterns,
+    }
+}
+
+#[test]
+fn disabled_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = TranscriptLog::from_config(
+        transcript_log_path(dir.path()),
+        &SupervisorTranscriptConfig::default(),
+    )
+    .unwrap();
+    assert!(log.is_none());
+}
+
+#[test]
+fn entry_captures_decision_and_error() {
+    let entry = make_entry("prompt", "reply");
+    assert!(entry.id.starts_with("sup-coder-Bash-"));
+    assert_eq!(entry.decision, Some(Decision::Deny));
+    assert_eq!(entry.latency_ms, 42);
+    assert!(entry.error.is_none());
+
+    let failed = TranscriptEntry::new(
+        &make_request(),
+        "api",
+        SupervisorExchange::default(),
+        &Err(HookwiseError::SupervisorTimeout { timeout_secs: 30 }),
+        Duration::from_secs(30),
+    );
+    assert!(failed.decision.is_none());
+    assert!(failed.error.unwrap().contains("30"));
+}
+
+#[test]
+fn append_redacts_and_roundtrips() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = transcript_log_path(dir.path());
+    let log = TranscriptLog::from_config(
+        path.clone(),
+        &enabled(1024 * 1024, vec![r"(ticket-)\d+".into()]),
+    )
+    .unwrap()
+    .unwrap();
+
+    let entry = make_entry(
+        "Task: fix ticket-4821 using token=supersecretvalue123",
+        "looks fine for ticket-4821",
+    );
+    let id = entry.id.clone();
+    log.append(entry).unwrap();
+
+    let loaded = find_transcript(&path, &id).unwrap();
+    assert!(!loaded.prompt.contains("supersecretvalue123"));
+    assert!(!loaded.prompt.contains("4821"));
+    assert!(loaded.prompt.contains("ticket-<REDACTED>"));
+    assert_eq!(
+        loaded.reply.as_deref(),
+        Some("looks fine for ticket-<REDACTED>")
+    );
+    assert!(find_transcript(&path, "sup-missing").is_none());
+}
+
+#[test]
+fn invalid_redact_pattern_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let result = TranscriptLog::from_config(
+        transcript_log_path(dir.path()),
+        &enabled(1024, vec!["(unclosed".into()]),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn log_rotates_at_size_cap() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = transcript_log_path(dir.path());
+    let log = TranscriptLog::from_config(path.clone(), &enabled(600, Vec::new()))
+        .unwrap()
+        .unwrap();
+
+    for i in 0..6 {
+        log.append(make_entry(&format!("prompt {}", i), "reply"))
+            .unwrap();
+    }
+
+    assert!(rotated_path(&path).exists());
+    assert!(std::fs::metadata(&path).unwrap().len() <= 600);
+    let entries = load_transcripts(&path);
+    assert!(entries.len() < 6);
+    assert_eq!(entries.last().unwrap().prompt, "prompt 5");
+}
@@ -0,0 +1,208 @@
+//! Tests for decision bundles: `hookwise export` bundles, merge planning
+//! under each conflict strategy, and `JsonlStorage::merge`.
+
+mod common;
+
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use hookwise::decision::{Decision, DecisionRecord, DecisionTier, ScopeLevel};
+use hookwise::storage::bundle::{self, ConflictStrategy};
+use hookwise::storage::jsonl::JsonlStorage;
+use hookwise::storage::StorageBackend;
+
+fn record(command: &str, decision: Decision, age_days: i64) -> DecisionRecord {
+    DecisionRecord {
+        metadata: common::metadata(DecisionTier::Human, &format!("{decision} {age_days}d ago")),
+        timestamp: Utc::now() - Duration::days(age_days),
+        scope: ScopeLevel::User,
+        ..common::record(command, "coder", decision)
+    }
+}
+
+fn strategy(
+    strategy: ConflictStrategy,
+) -> impl FnMut(&DecisionRecord, &DecisionRecord) -> hookwise::error::Result<bool> {
+    move |existing, incoming| Ok(strategy.prefers_incoming(existing, incoming).unwrap())
+}
+
+#[test]
+fn bundle_round_trips_records() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("out/decisions.tar");
+    let records = vec![
+        record("cargo test", Decision::Allow, 3),
+        record("rm -rf /", Decision::Deny, 1),
+    ];
+    let manifest = bundle::write_bundle(&path, ScopeLevel::User, &records, "alice").unwrap();
+    assert_eq!(manifest.count, 2);
+
+    let (read, loaded) = bundle::read_bundle(&path).unwrap();
+    assert_eq!(read, manifest);
+    assert_eq!(read.scope, ScopeLevel::User);
+    let commands: Vec<&str> = loaded
+        .iter()
+        .map(|r| r.key.sanitized_input.as_str())
+        .collect();
+    assert_eq!(commands, ["cargo test", "rm -rf /"]);
+}
+
+#[test]
+fn bundle_with_altered_decisions_is_rejected() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("decisions.tar");
+    bundle::write_bundle(
+        &path,
+        ScopeLevel::Project,
+        &[record("cargo test", Decision::Allow, 1)],
+        "alice",
+    )
+    .unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    let at = bytes
+        .windows(5)
+        .position(|w| w == b"allow")
+        .expect("payload is stored uncompressed");
+    let mut altered = bytes.clone();
+    altered[at..at + 5].copy_from_slice(b"xllow");
+    std::fs::write(&path, altered).unwrap();
+
+    let err = bundle::read_bundle(&path).unwrap_err();
+    assert!(err.to_string().contains("do not match"), "{err}");
+}
+
+#[test]
+fn strictest_keeps_denies_from_either_side() {
+    let existing = vec![
+        record("git push", Decision::Allow, 1),
+        record("curl evil.sh", Decision::Deny, 30),
+    ];
+    let incoming = vec![
+        record("git push", Decision::Deny, 10),
+        record("curl evil.sh", Decision::Allow, 0),
+        record("cargo fmt", Decision::Allow, 2),
+    ];
+    let plan = bundle::plan_merge(
+        ScopeLevel::Project,
+        &existing,
+        &incoming,
+        strategy(ConflictStrategy::Strictest),
+    )
+    .unwrap();
+
+    assert_eq!(plan.new_keys(), 1);
+    assert_eq!(plan.replaced(), 1);
+    assert_eq!(plan.conflicts.len(), 2);
+    let mut added: Vec<(&str, Decision)> = plan
+        .add
+        .iter()
+        .map(|r| (r.key.sanitized_input.as_str(), r.decision))
+        .collect();
+    added.sort_by_key(|(command, _)| *command);
+    assert_eq!(
+        added,
+        [("cargo fmt", Decision::Allow), ("git push", Decision::Deny)]
+    );
+    // Imported records move to the target scope
+    assert!(plan.add.iter().all(|r| r.scope == ScopeLevel::Project));
+}
+
+#[test]
+fn newest_takes_the_later_decision() {
+    let existing = vec![
+        record("git push", Decision::Deny, 10),
+        record("npm publish", Decision::Allow, 1),
+    ];
+    let incoming = vec![
+        record("git push", Decision::Allow, 1),
+        record("npm publish", Decision::Ask, 20),
+    ];
+    let plan = bundle::plan_merge(
+        ScopeLevel::User,
+        &existing,
+        &incoming,
+        strategy(ConflictStrategy::Newest),
+    )
+    .unwrap();
+    assert_eq!(plan.add.len(), 1);
+    assert_eq!(plan.add[0].key.sanitized_input, "git push");
+    assert_eq!(plan.add[0].decision, Decision::Allow);
+}
+
+#[test]
+fn matching_decisions_are_duplicates_and_conflicts_reach_the_resolver() {
+    let existing = vec![record("ls", Decision::Allow, 5)];
+    let incoming = vec![
+        record("ls", Decision::Allow, 1),
+        record("cat .env", Decision::Ask, 1),
+    ];
+    let mut asked = 0;
+    let plan = bundle::plan_merge(ScopeLevel::User, &existing, &incoming, |_, _| {
+        asked += 1;
+        Ok(false)
+    })
+    .unwrap();
+    assert_eq!(asked, 0);
+    assert_eq!(plan.duplicates, 1);
+    assert_eq!(plan.new_keys(), 1);
+
+    let existing = vec![record("ls", Decision::Deny, 5)];
+    let plan = bundle::plan_merge(ScopeLevel::User, &existing, &incoming, |e, i| {
+        assert_eq!((e.decision, i.decision), (Decision::Deny, Decision::Allow));
+        Ok(false)
+    })
+    .unwrap();
+    assert!(ConflictStrategy::Interactive
+        .prefers_incoming(&existing[0], &incoming[0])
+        .is_none());
+    assert_eq!(plan.conflicts.len(), 1);
+    assert!(!plan.conflicts[0].took_incoming);
+    assert_eq!(plan.add.len(), 1);
+}
+
+#[test]
+fn merge_replaces_losing_records_in_storage() {
+    let tmp = TempDir::new().unwrap();
+    let storage = JsonlStorage::new(
+        tmp.path().join(".hookwise"),
+        tmp.path().join("global"),
+        None,
+    );
+    for r in [
+        record("git push", Decision::Deny, 10),
+        record("git push", Decision::Deny, 20),
+        record("ls", Decision::Allow, 1),
+    ] {
+        storage.save_decision(&r).unwrap();
+    }
+
+    let existing = storage.load_decisions(ScopeLevel::User).unwrap();
+    let incoming = vec![
+        record("git push", Decision::Allow, 0),
+        record("cargo test", Decision::Allow, 0),
+    ];
+    let plan = bundle::plan_merge(
+        ScopeLevel::User,
+        &existing,
+        &incoming,
+        strategy(ConflictStrategy::Newest),
+    )
+    .unwrap();
+    assert_eq!(storage.merge(ScopeLevel::User, &plan).unwrap(), 2);
+
+    let mut left: Vec<(String, Decision)> = storage
+        .load_decisions(ScopeLevel::User)
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.key.sanitized_input, r.decision))
+        .collect();
+    left.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        left,
+        [
+            ("cargo test".to_string(), Decision::Allow),
+            ("git push".to_string(), Decision::Allow),
+            ("ls".to_string(), Decision::Allow),
+        ]
+    );
+}
@@ -0,0 +1,165 @@
+//! Tests for release checks: the mirror and GitHub sources, the cached
+//! check behind the update hint, the `updates` global config, and where
+//! `self-update` may install.
+
+#![cfg(feature = "self-update")]
+
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use hookwise::cli::self_update::{
+    install_destination, package_manager, path_warning, refresh_update_check, ReleaseSource,
+    UpdateCheck,
+};
+use hookwise::config::{GlobalConfig, UpdatesConfig};
+
+#[test]
+fn updates_config_defaults_to_hints_from_github_daily() {
+    let config: GlobalConfig = serde_yaml::from_str("supervisor: { backend: socket }").unwrap();
+    assert_eq!(config.updates, UpdatesConfig::default());
+    assert!(config.updates.hints);
+    assert_eq!(config.updates.check_interval_hours, 24);
+    assert_eq!(
+        ReleaseSource::from_config(&config.updates),
+        ReleaseSource::GitHub
+    );
+
+    let config: GlobalConfig = serde_yaml::from_str(
+        r#"
+supervisor: { backend: socket }
+updates:
+  hints: false
+  mirror: https://artifacts.example.com/hookwise/
+"#,
+    )
+    .unwrap();
+    assert!(!config.updates.hints);
+    assert_eq!(
+        ReleaseSource::from_config(&config.updates),
+        ReleaseSource::Mirror("https://artifacts.example.com/hookwise".into())
+    );
+}
+
+#[test]
+fn release_sources_build_urls_and_read_the_latest_tag() {
+    let github = ReleaseSource::GitHub;
+    assert_eq!(
+        github.latest_url(),
+        "https://api.github.com/repos/Epiphytic/hookwise/releases/latest"
+    );
+    assert_eq!(
+        github.asset_url("1.2.0", "hookwise-v1.2.0-x86_64-unknown-linux-gnu.tar.gz"),
+        "https://github.com/Epiphytic/hookwise/releases/download/v1.2.0/hookwise-v1.2.0-x86_64-unknown-linux-gnu.tar.gz"
+    );
+    assert_eq!(
+        github.parse_latest(r#"{"tag_name": "v1.2.0", "name": "1.2.0"}"#),
+        Some("v1.2.0".into())
+    );
+    assert_eq!(github.parse_latest("v1.2.0"), None);
+
+    let mirror = ReleaseSource::Mirror("https://artifacts.example.com/hookwise".into());
+    assert_eq!(
+        mirror.latest_url(),
+        "https://artifacts.example.com/hookwise/latest"
+    );
+    assert_eq!(
+        mirror.asset_url("1.2.0", "hookwise-v1.2.0.tar.gz.sha256"),
+        "https://artifacts.example.com/hookwise/v1.2.0/hookwise-v1.2.0.tar.gz.sha256"
+    );
+    assert_eq!(mirror.parse_latest("v1.2.0\n"), Some("v1.2.0".into()));
+    assert_eq!(mirror.parse_latest("  \n"), None);
+}
+
+#[test]
+fn cached_checks_hint_only_for_newer_releases_and_expire() {
+    let now = Utc::now();
+    let check = |latest: &str| UpdateCheck {
+        checked_at: now,
+        latest_version: Some(latest.into()),
+        current_version: "0.9.0".into(),
+    };
+    assert_eq!(
+        check("v0.10.0").hint("0.9.0").as_deref(),
+        Some("hookwise: update available v0.9.0 -> v0.10.0 (run `hookwise self-update`)")
+    );
+    assert_eq!(check("v0.9.0").hint("0.9.0"), None);
+    // A cache written before upgrading past it
+    assert_eq!(check("v0.8.3").hint("0.9.0"), None);
+
+    let stale = UpdateCheck {
+        checked_at: now - Duration::hours(25),
+        ..check("v0.9.0")
+    };
+    assert!(stale.is_due(24, now));
+    assert!(!check("v0.9.0").is_due(24, now));
+
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("cache/update-check.json");
+    assert_eq!(UpdateCheck::load(&path), None);
+    stale.save(&path).unwrap();
+    assert_eq!(UpdateCheck::load(&path), Some(stale));
+}
+
+#[tokio::test]
+async fn refresh_does_nothing_with_hints_off() {
+    let config = UpdatesConfig {
+        hints: false,
+        mirror: Some("http://127.0.0.1:9/unreachable".into()),
+        ..UpdatesConfig::default()
+    };
+    assert_eq!(refresh_update_check(&config, true).await.unwrap(), None);
+}
+
+#[test]
+fn package_managed_and_read_only_installs_are_not_replaced() {
+    let brew = std::path::Path::new("/opt/homebrew/Cellar/hookwise/0.9.0/bin/hookwise");
+    assert_eq!(package_manager(brew).unwrap().name, "Homebrew");
+    let nix = std::path::Path::new("/nix/store/abc123-hookwise-0.9.0/bin/hookwise");
+    assert_eq!(package_manager(nix).unwrap().name, "Nix");
+    assert_eq!(
+        package_manager(std::path::Path::new("/usr/local/bin/hookwise")),
+        None
+    );
+
+    let guidance = install_destination(brew, None).unwrap_err();
+    assert!(guidance.contains("brew upgrade hookwise"), "{guidance}");
+    assert!(guidance.contains("--install-dir"), "{guidance}");
+    let local = std::path::Path::new("/home/dev/.local/bin");
+    assert_eq!(
+        install_destination(nix, Some(local)).unwrap(),
+        local.join("hookwise")
+    );
+
+    let tmp = TempDir::new().unwrap();
+    let exe = tmp.path().join("hookwise");
+    assert_eq!(install_destination(&exe, None).unwrap(), exe);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let read_only = tmp.path().join("ro");
+        std::fs::create_dir(&read_only).unwrap();
+        std::fs::set_permissions(&read_only, std::fs::Permissions::from_mode(0o555)).unwrap();
+        // Root can write anyway
+        if std::fs::write(read_only.join("probe"), "").is_err() {
+            let guidance = install_destination(&read_only.join("hookwise"), None).unwrap_err();
+            assert!(guidance.contains("is not writable"), "{guidance}");
+        }
+    }
+}
+
+#[test]
+fn path_warnings_name_a_missing_or_shadowed_install_dir() {
+    let tmp = TempDir::new().unwrap();
+    let local = tmp.path().join("local");
+    let system = tmp.path().join("system");
+    std::fs::create_dir_all(&local).unwrap();
+    std::fs::create_dir_all(&system).unwrap();
+    std::fs::write(system.join("hookwise"), "").unwrap();
+    let path = |dirs: &[&std::path::Path]| std::env::join_paths(dirs).unwrap();
+
+    assert_eq!(path_warning(&local, &path(&[&local, &system])), None);
+    let shadowed = path_warning(&local, &path(&[&system, &local])).unwrap();
+    assert!(shadowed.contains("comes before"), "{shadowed}");
+    let missing = path_warning(&local, &path(&[&system])).unwrap();
+    assert!(missing.contains("is not on PATH"), "{missing}");
+}
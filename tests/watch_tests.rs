@@ -0,0 +1,106 @@
+//! Tests for the shared filesystem watcher: native and polling backends,
+//! debouncing, and which paths are reported.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use hookwise::watch::{FileWatcher, WatchBackend, WatchStream};
+
+const WAIT: Duration = Duration::from_secs(5);
+
+fn start(paths: Vec<PathBuf>, polling: bool) -> WatchStream {
+    FileWatcher::new(paths)
+        .with_debounce(Duration::from_millis(150))
+        .with_poll_interval(Duration::from_millis(20))
+        .with_polling(polling)
+        .start()
+        .unwrap()
+}
+
+async fn next(watcher: &mut WatchStream) -> Vec<PathBuf> {
+    tokio::time::timeout(WAIT, watcher.changed())
+        .await
+        .expect("no change reported")
+        .expect("watcher stopped")
+}
+
+/// Give a freshly started watcher time to take its first snapshot.
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(100)).await;
+}
+
+async fn reports_files_and_directory_entries(polling: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = std::path::absolute(dir.path()).unwrap();
+    let queue = root.join("queue.json");
+    let rules = root.join("rules");
+    std::fs::create_dir(&rules).unwrap();
+
+    let mut watcher = start(vec![queue.clone(), rules.clone()], polling);
+    if polling {
+        assert_eq!(watcher.backend(), WatchBackend::Polling);
+    }
+    settle().await;
+
+    // Creating a watched file that didn't exist is a change; a sibling
+    // that isn't watched is not.
+    std::fs::write(root.join("other.json"), "{}").unwrap();
+    std::fs::write(&queue, "{}").unwrap();
+    assert_eq!(next(&mut watcher).await, vec![queue.clone()]);
+
+    // A burst of writes arrives as one batch.
+    for i in 0..5 {
+        std::fs::write(rules.join("allow.jsonl"), "x".repeat(i + 1)).unwrap();
+        std::fs::write(&queue, "y".repeat(i + 2)).unwrap();
+    }
+    assert_eq!(
+        next(&mut watcher).await,
+        vec![queue.clone(), rules.join("allow.jsonl")]
+    );
+
+    // Replace-by-rename, as the storage layer saves files.
+    let tmp = rules.join(".deny.jsonl.tmp");
+    std::fs::write(&tmp, "z").unwrap();
+    std::fs::rename(&tmp, rules.join("deny.jsonl")).unwrap();
+    let batch = next(&mut watcher).await;
+    assert!(batch.contains(&rules.join("deny.jsonl")), "{batch:?}");
+
+    assert!(watcher
+        .changed_within(Duration::from_millis(300))
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+async fn polling_reports_files_and_directory_entries() {
+    reports_files_and_directory_entries(true).await;
+}
+
+#[tokio::test]
+async fn native_reports_files_and_directory_entries() {
+    reports_files_and_directory_entries(false).await;
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn linux_uses_native_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let watcher = FileWatcher::new([dir.path()]).start().unwrap();
+    assert_eq!(watcher.backend(), WatchBackend::Native);
+}
+
+#[tokio::test]
+async fn missing_directories_fall_back_to_polling() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("not-yet").join("queue.json");
+    let mut watcher = start(vec![file.clone()], false);
+    assert_eq!(watcher.backend(), WatchBackend::Polling);
+    settle().await;
+
+    std::fs::create_dir(dir.path().join("not-yet")).unwrap();
+    std::fs::write(&file, "{}").unwrap();
+    assert_eq!(
+        next(&mut watcher).await,
+        vec![std::path::absolute(&file).unwrap()]
+    );
+}